@@ -0,0 +1,47 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use xtal::internal::benchmarks::{self, read_next_frame};
+use xtal::prelude::Mode;
+
+fn bench_automate(c: &mut Criterion) {
+    let animation = benchmarks::animation();
+    let breakpoints = benchmarks::automate_breakpoints();
+    let mut frame = 0;
+
+    c.bench_function("automate", |b| {
+        b.iter(|| {
+            frame += 1;
+            xtal::internal::frame_controller::set_frame_count(frame);
+            black_box(animation.automate(&breakpoints, Mode::Loop))
+        })
+    });
+}
+
+fn bench_dependency_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dependency_chain");
+
+    for depth in [1, 8, 32] {
+        let hub = benchmarks::hub(&benchmarks::dependency_chain_script(depth));
+        let name = format!("chain_{}", depth - 1);
+        group.bench_function(depth.to_string(), |b| {
+            b.iter(|| black_box(read_next_frame(&hub, &name)))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_effects_chain(c: &mut Criterion) {
+    let hub = benchmarks::hub(&benchmarks::effects_chain_script());
+
+    c.bench_function("effects_chain", |b| {
+        b.iter(|| black_box(read_next_frame(&hub, "effects_chain")))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_automate,
+    bench_dependency_chain,
+    bench_effects_chain
+);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+"""
+Sends MediaPipe pose and hand landmarks from a webcam to Xtal over OSC so they
+can be used as `type: landmark` controls.
+
+Messages are sent to `/landmark/<group>/<point>` with `x y z visibility`
+arguments (hands omit visibility), each normalized to [0, 1] with y increasing
+upward and z increasing toward the camera. Groups are `body`, `left_hand`, and
+`right_hand`; points are MediaPipe's landmark names in lowercase, e.g.
+`body/left_wrist` or `right_hand/index_finger_tip`.
+
+Setup:
+    pip install mediapipe opencv-python python-osc
+
+Usage:
+    python scripts/landmarks.py [--camera 0] [--port 2346] [--mirror] [--preview]
+"""
+
+import argparse
+
+import cv2
+import mediapipe as mp
+from pythonosc.udp_client import SimpleUDPClient
+
+
+def clamp(value):
+    return min(max(value, 0.0), 1.0)
+
+
+def normalize(landmark, mirror):
+    x = 1.0 - landmark.x if mirror else landmark.x
+    # MediaPipe's z is negative toward the camera in roughly x's scale
+    return [clamp(x), clamp(1.0 - landmark.y), clamp(0.5 - landmark.z)]
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.split("\n\n")[0])
+    parser.add_argument("--camera", type=int, default=0)
+    parser.add_argument("--host", default="127.0.0.1")
+    parser.add_argument(
+        "--port",
+        type=int,
+        default=2346,
+        help="Xtal's OSC port (UI > Settings > OSC > Port)",
+    )
+    parser.add_argument(
+        "--mirror",
+        action="store_true",
+        help="Flip x so moving your right hand right increases x",
+    )
+    parser.add_argument("--preview", action="store_true")
+    args = parser.parse_args()
+
+    client = SimpleUDPClient(args.host, args.port)
+    capture = cv2.VideoCapture(args.camera)
+    pose = mp.solutions.pose.Pose()
+    hands = mp.solutions.hands.Hands(max_num_hands=2)
+
+    pose_names = [p.name.lower() for p in mp.solutions.pose.PoseLandmark]
+    hand_names = [p.name.lower() for p in mp.solutions.hands.HandLandmark]
+
+    while capture.isOpened():
+        ok, frame = capture.read()
+        if not ok:
+            break
+
+        rgb = cv2.cvtColor(frame, cv2.COLOR_BGR2RGB)
+        pose_results = pose.process(rgb)
+        hand_results = hands.process(rgb)
+
+        if pose_results.pose_landmarks:
+            landmarks = pose_results.pose_landmarks.landmark
+            for name, landmark in zip(pose_names, landmarks):
+                values = normalize(landmark, args.mirror)
+                values.append(clamp(landmark.visibility))
+                client.send_message(f"/landmark/body/{name}", values)
+
+        if hand_results.multi_hand_landmarks:
+            for landmarks, handedness in zip(
+                hand_results.multi_hand_landmarks,
+                hand_results.multi_handedness,
+            ):
+                label = handedness.classification[0].label.lower()
+                for name, landmark in zip(hand_names, landmarks.landmark):
+                    client.send_message(
+                        f"/landmark/{label}_hand/{name}",
+                        normalize(landmark, args.mirror),
+                    )
+
+        if args.preview:
+            cv2.imshow("landmarks", cv2.flip(frame, 1) if args.mirror else frame)
+            if cv2.waitKey(1) & 0xFF == ord("q"):
+                break
+
+    capture.release()
+    cv2.destroyAllWindows()
+
+
+if __name__ == "__main__":
+    main()
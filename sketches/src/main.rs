@@ -98,7 +98,9 @@ fn main() {
         basic_cube_shader_template,
         du_fs_template,
         du_fs_texture_template,
-        fullscreen_shader_template
+        fullscreen_shader_template,
+        physarum_template,
+        reaction_diffusion_template
     );
 
     run();
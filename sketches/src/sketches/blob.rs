@@ -9,11 +9,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "blob",
     display_name: "Blob",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 1244,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -64,6 +68,7 @@ pub fn init(app: &App, ctx: &Context) -> Blob {
 
     let shader = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         window_rect.resolution_u32(),
         to_absolute_path(file!(), "blob.wgsl"),
         &params,
@@ -135,9 +140,9 @@ impl Sketch for Blob {
         self.feedback_texture = Some(self.shader.render_to_texture(app));
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
-        ctx.background(&frame, &draw, hsla(0.0, 0.0, 0.3, 0.02));
-        self.shader.render(&frame);
+        ctx.background(frame, &draw, hsla(0.0, 0.0, 0.3, 0.02));
+        self.shader.render(frame);
     }
 }
@@ -1,4 +1,3 @@
-pub mod displacer;
 pub mod distance;
 pub mod drop;
 pub mod sand_line;
@@ -12,6 +12,9 @@ use nannou::rand::Rng;
 use nannou::rand::rand;
 use nannou::rand::thread_rng;
 
+/// Promoted to the framework; re-exported here for existing sketches
+pub use xtal::geometry::create_grid;
+
 pub const PHI_F32: f32 = 1.618_033_9;
 
 pub const QUAD_POSITIONS: [[f32; 3]; 6] = [
@@ -112,37 +115,6 @@ pub fn luminance(color: &LinSrgb) -> f32 {
     0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue
 }
 
-pub fn create_grid<F>(
-    w: f32,
-    h: f32,
-    divisions: usize,
-    transform_xy: F,
-) -> (Vec<Vec2>, f32)
-where
-    F: Fn(f32, f32) -> Vec2,
-{
-    let mut grid = Vec::new();
-    let cell_size = (f32::min(w, h) / divisions as f32).floor();
-    let cols = (w / cell_size).floor() as usize;
-    let rows = (h / cell_size).floor() as usize;
-
-    let grid_width = cols as f32 * cell_size;
-    let grid_height = rows as f32 * cell_size;
-
-    let start_x = -grid_width / 2.0;
-    let start_y = grid_height / 2.0;
-
-    for col in 0..cols {
-        for row in 0..rows {
-            let x = start_x + col as f32 * cell_size + cell_size / 2.0;
-            let y = start_y - row as f32 * cell_size - cell_size / 2.0;
-            grid.push(transform_xy(x, y));
-        }
-    }
-
-    (grid, cell_size)
-}
-
 pub trait TrigonometricExt {
     fn sec(self) -> Self;
     fn csc(self) -> Self;
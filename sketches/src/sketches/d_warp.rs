@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "d_warp",
     display_name: "Domain Warping",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -34,6 +38,7 @@ pub fn init(app: &App, ctx: &Context) -> DWarp {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "d_warp.wgsl"),
         &params,
@@ -51,7 +56,7 @@ impl Sketch for DWarp {
         self.gpu.update_params(app, wr.resolution_u32(), &params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.gpu.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.gpu.render(frame);
     }
 }
@@ -7,12 +7,16 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "animation_dev",
     display_name: "Animation Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     fps: 60.0,
     bpm: 134.0,
     // fps: 24.0,
     // bpm: 360.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
     play_mode: PlayMode::Loop,
 };
 
@@ -30,7 +34,7 @@ pub fn init(_app: &App, ctx: &Context) -> AnimationDev {
 impl Sketch for AnimationDev {
     fn update(&mut self, _app: &App, _update: Update, _ctx: &Context) {}
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
 
         let draw = app.draw();
@@ -236,6 +240,6 @@ impl Sketch for AnimationDev {
                 .color(BLACK);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
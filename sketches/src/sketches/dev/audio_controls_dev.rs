@@ -7,11 +7,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "audio_controls_dev",
     display_name: "Audio Controls Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -33,8 +37,11 @@ pub fn init(_app: &App, ctx: &Context) -> AudioControlsDev {
                 slew_limiter: SlewLimiter::default(),
                 pre_emphasis: 0.0,
                 detect: 0.0,
+                mode: DetectMode::Frame,
+                window: 1.0,
                 range: (0.0, 700.0),
                 value: 0.0,
+                pitch: None,
             },
         )
         .audio(
@@ -44,8 +51,11 @@ pub fn init(_app: &App, ctx: &Context) -> AudioControlsDev {
                 slew_limiter: SlewLimiter::default(),
                 pre_emphasis: 0.0,
                 detect: 0.0,
+                mode: DetectMode::Frame,
+                window: 1.0,
                 range: (0.0, 700.0),
                 value: 0.0,
+                pitch: None,
             },
         )
         .audio(
@@ -55,8 +65,11 @@ pub fn init(_app: &App, ctx: &Context) -> AudioControlsDev {
                 slew_limiter: SlewLimiter::default(),
                 pre_emphasis: 0.0,
                 detect: 0.0,
+                mode: DetectMode::Frame,
+                window: 1.0,
                 range: (0.0, 700.0),
                 value: 0.0,
+                pitch: None,
             },
         )
         .build();
@@ -89,7 +102,7 @@ impl Sketch for AudioControlsDev {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -114,6 +127,6 @@ impl Sketch for AudioControlsDev {
             .radius(hh)
             .x_y(wr.w() / 4.0, 0.0);
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
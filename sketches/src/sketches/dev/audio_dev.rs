@@ -8,11 +8,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "audio_dev",
     display_name: "Audio Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 30.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const N_BANDS: usize = 8;
@@ -54,7 +58,7 @@ impl Sketch for AudioDev {
         // debug_throttled!(1_000, "fft_bands: {:?}", self.fft_bands);
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -82,6 +86,6 @@ impl Sketch for AudioDev {
                 );
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "bug_repro",
     display_name: "Bug Repro",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -30,7 +34,7 @@ pub fn init(_app: &App, ctx: &Context) -> BugRepro {
 impl Sketch for BugRepro {
     fn update(&mut self, _app: &App, _update: Update, _ctx: &Context) {}
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -44,6 +48,6 @@ impl Sketch for BugRepro {
             .radius(self.hub.get("radius"))
             .x_y(self.hub.get("x_pos"), 0.0);
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -8,11 +8,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "control_script_dev",
     display_name: "ControlScript Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -32,12 +36,12 @@ pub fn init(_app: &App, ctx: &Context) -> ControlScriptDev {
 impl Sketch for ControlScriptDev {
     fn update(&mut self, _app: &App, _update: Update, _ctx: &Context) {}
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
         ctx.background(
-            &frame,
+            frame,
             &draw,
             hsla(0.0, 0.0, 0.02, self.hub.get("bg_alpha")),
         );
@@ -147,6 +151,6 @@ impl Sketch for ControlScriptDev {
                 .w_h(100.0, 100.0);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -7,11 +7,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "cv_test",
     display_name: "CV Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -29,8 +33,11 @@ pub fn init(_app: &App, _ctx: &Context) -> CvTest {
                 slew_limiter: SlewLimiter::default(),
                 pre_emphasis: 0.0,
                 detect: 0.0,
+                mode: DetectMode::Frame,
+                window: 1.0,
                 range: (0.0, 700.0),
                 value: 0.0,
+                pitch: None,
             },
         )
         .control_from_config(
@@ -40,8 +47,11 @@ pub fn init(_app: &App, _ctx: &Context) -> CvTest {
                 slew_limiter: SlewLimiter::default(),
                 pre_emphasis: 0.0,
                 detect: 0.0,
+                mode: DetectMode::Frame,
+                window: 1.0,
                 range: (0.0, 700.0),
                 value: 0.0,
+                pitch: None,
             },
         )
         .build();
@@ -59,7 +69,7 @@ impl Sketch for CvTest {
         );
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -78,6 +88,6 @@ impl Sketch for CvTest {
             .radius(b)
             .x_y(wr.w() / 16.0, 0.0);
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "effects_wavefolder_dev",
     display_name: "Effects WaveFolder Dev",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
 };
 
 const N_POINTS: usize = 2048;
@@ -65,7 +69,7 @@ impl Sketch for EffectsWavefolderDev {
         )];
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -125,7 +129,7 @@ impl Sketch for EffectsWavefolderDev {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -7,10 +7,14 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "midi_dev",
     display_name: "MIDI Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
     play_mode: PlayMode::Loop,
 };
 
@@ -37,7 +41,7 @@ impl Sketch for MidiDev {
         // debug!("{}", self.midi.get("a"));
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -72,6 +76,6 @@ impl Sketch for MidiDev {
             -wr.hh() + self.hub.get("d") * wr.h(),
         );
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
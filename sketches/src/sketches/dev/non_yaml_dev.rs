@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "non_yaml_dev",
     display_name: "ControlScript w/o YAML Dev",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
 };
 
 const COUNT: usize = 512;
@@ -48,7 +52,7 @@ impl Sketch for NonYamlDev {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -60,6 +64,6 @@ impl Sketch for NonYamlDev {
 
         draw.ellipse().color(ORANGERED).radius(100.0).x_y(0.0, 0.0);
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
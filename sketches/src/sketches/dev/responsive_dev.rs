@@ -2,29 +2,30 @@ use xtal::prelude::*;
 use nannou::color::*;
 use nannou::prelude::*;
 
-use crate::util::*;
-
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "responsive_dev",
     display_name: "Responsive Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
 pub struct ResponsiveDev {
-    grid: Vec<Vec2>,
-    cell_size: f32,
+    grid: geometry::Grid,
 }
 
 pub fn init(_app: &App, ctx: &Context) -> ResponsiveDev {
     let wr = ctx.window_rect();
-    let (grid, cell_size) = create_grid(wr.w(), wr.h(), 64, vec2);
+    let grid = geometry::Grid::from_window_rect(&wr, 64);
 
-    ResponsiveDev { grid, cell_size }
+    ResponsiveDev { grid }
 }
 
 impl Sketch for ResponsiveDev {
@@ -33,12 +34,12 @@ impl Sketch for ResponsiveDev {
 
         if wr.changed() {
             debug!("changed w: {}, h: {}", wr.w(), wr.h());
-            (self.grid, self.cell_size) = create_grid(wr.w(), wr.h(), 64, vec2);
+            self.grid.update(&wr);
             wr.mark_unchanged();
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -47,15 +48,15 @@ impl Sketch for ResponsiveDev {
             .w_h(wr.w(), wr.h())
             .hsla(0.0, 0.0, 0.02, 0.1);
 
-        for point in self.grid.iter() {
+        for cell in self.grid.cells() {
             draw.rect()
-                .xy(*point)
-                .w_h(self.cell_size, self.cell_size)
+                .xy(cell.center)
+                .w_h(cell.size, cell.size)
                 .color(ORANGE)
                 .stroke_weight(2.0)
                 .stroke(BLACK);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
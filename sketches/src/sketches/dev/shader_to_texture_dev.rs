@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "shader_to_texture_dev",
     display_name: "Shader to Texture Development",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const BACKGROUND: f32 = 0.0;
@@ -68,6 +72,7 @@ pub fn init(app: &App, ctx: &Context) -> ShaderToTextureDev {
 
     let first_pass = gpu::GpuState::new(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "shader_to_texture_dev.wgsl"),
         &first_pass_params,
@@ -81,6 +86,7 @@ pub fn init(app: &App, ctx: &Context) -> ShaderToTextureDev {
 
     let second_pass = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "shader_to_texture_dev2.wgsl"),
         &post_process_params,
@@ -135,9 +141,18 @@ impl Sketch for ShaderToTextureDev {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.second_pass.render(&frame);
+        self.second_pass.render(frame);
+    }
+
+    // Lets other sketches use the first pass via `inputs`
+    fn render_output(
+        &self,
+        app: &App,
+        _ctx: &Context,
+    ) -> Option<wgpu::TextureView> {
+        Some(self.first_pass.render_to_texture(app))
     }
 }
 
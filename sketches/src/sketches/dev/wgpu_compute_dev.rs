@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "wgpu_compute_dev",
     display_name: "WGPU Compute Test",
+    tags: &["dev"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const MAX_POINTS: u32 = 5_000_000;
@@ -365,7 +369,7 @@ impl Sketch for Model {
         read_buffer.unmap();
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -408,7 +412,7 @@ impl Sketch for Model {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -6,17 +6,20 @@ use std::str::FromStr;
 use std::sync::Arc;
 use xtal::prelude::*;
 
-use super::common::displacer::*;
 use crate::util::*;
 
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "displacement_2a",
     display_name: "Displacement 2a",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 30.0,
     bpm: 135.0,
     w: 1000,
     h: 1000,
+    msaa_samples: 4,
 };
 
 const DEBUG_QUADS: bool = false;
@@ -413,7 +416,7 @@ impl Sketch for Displacement2a {
             .collect();
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
 
         frame.clear(BLACK);
@@ -432,7 +435,7 @@ impl Sketch for Displacement2a {
                 .xy(*position);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -10,11 +10,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "drop",
     display_name: "Drop",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -89,7 +93,7 @@ impl Sketch for Drops {
         });
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
 
         draw.background()
@@ -101,7 +105,7 @@ impl Sketch for Drops {
                 .points(drop.vertices().iter().cloned());
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
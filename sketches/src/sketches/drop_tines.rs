@@ -8,11 +8,15 @@ use super::common::drop::Drop;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "drop_tines",
     display_name: "DropTines",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -91,7 +95,7 @@ impl Sketch for DropTines {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -118,6 +122,6 @@ impl Sketch for DropTines {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
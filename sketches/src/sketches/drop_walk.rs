@@ -12,11 +12,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "drop_walk",
     display_name: "Drop Walk",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const MAX_DROPS: usize = 5000;
@@ -161,7 +165,7 @@ impl Sketch for Drops {
             });
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
 
         draw.background().color(hsl(0.0, 0.0, 1.0));
@@ -187,7 +191,7 @@ impl Sketch for Drops {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -4,11 +4,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g_warp",
     display_name: "Grid Warp",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -32,6 +36,7 @@ pub fn init(app: &App, ctx: &Context) -> GWarp {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "g_warp.wgsl"),
         &params,
@@ -49,7 +54,7 @@ impl Sketch for GWarp {
         self.gpu.update_params(app, wr.resolution_u32(), &params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.gpu.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.gpu.render(frame);
     }
 }
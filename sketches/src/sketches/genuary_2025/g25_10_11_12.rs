@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_10_11_12",
     display_name: "Genuary 10-12: Spiral (Automated)",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[repr(C)]
@@ -133,6 +137,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
 
     let gpu = gpu::GpuState::new_procedural(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "./g25_10_11_12.wgsl"),
         &params,
@@ -224,7 +229,7 @@ impl Sketch for Template {
         self.controls.mark_unchanged();
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(WHITE);
 
         let points_per_line = self.midi.get("points_per_segment") as u32;
@@ -235,7 +240,7 @@ impl Sketch for Template {
         let background_vertices = 3;
         let total_vertices = background_vertices + spiral_vertices;
 
-        self.gpu.render_procedural(&frame, total_vertices);
+        self.gpu.render_procedural(frame, total_vertices);
     }
 }
 
@@ -7,11 +7,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_13_triangle",
     display_name: "Genuary 13: Triangles and nothing else",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -49,6 +53,7 @@ pub fn init(app: &App, ctx: &Context) -> G25_13Triangle {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "g25_13_triangle.wgsl"),
         &params,
@@ -96,8 +101,8 @@ impl Sketch for G25_13Triangle {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
@@ -9,11 +9,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_14_black_and_white",
     display_name: "Genuary 14: Interference",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 127.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -61,6 +65,7 @@ pub fn init(app: &App, ctx: &Context) -> BlackAndWhite {
 
     let gpu = GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "./g25_14_black_and_white.wgsl"),
         &params,
@@ -124,8 +129,8 @@ impl Sketch for BlackAndWhite {
         self.gpu.update_params(app, wr.resolution_u32(), &params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
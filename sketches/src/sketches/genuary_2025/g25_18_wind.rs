@@ -7,11 +7,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_18_wind",
     display_name: "Genuary 18: What does wind look like?",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 1244,
+    msaa_samples: 4,
 };
 
 const MAX_COUNT: usize = 100_000;
@@ -107,6 +111,7 @@ pub fn init(app: &App, ctx: &Context) -> G25_18Wind {
 
     let gpu = gpu::GpuState::new(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "g25_18_wind.wgsl"),
         &params,
@@ -226,8 +231,8 @@ impl Sketch for G25_18Wind {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.gpu.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.gpu.render(frame);
     }
 }
 
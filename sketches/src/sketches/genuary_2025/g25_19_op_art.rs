@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_19_op_art",
     display_name: "Genuary 19: Op Art",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 127.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
     // h: 1244,
 };
 
@@ -78,6 +82,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "./g25_19_op_art.wgsl"),
         &params,
@@ -136,8 +141,8 @@ impl Sketch for Template {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
@@ -8,11 +8,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_1_horiz_vert",
     display_name: "Genuary 1: Vertical or horizontal lines only",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 127.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const N_LINES: u32 = 64;
@@ -77,7 +81,7 @@ impl Sketch for HorizVert {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
         let window_rect = ctx.window_rect();
@@ -119,7 +123,7 @@ impl Sketch for HorizVert {
             draw3 = draw3.translate(vec3(0.0, space, 0.0));
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
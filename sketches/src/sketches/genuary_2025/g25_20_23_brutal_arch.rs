@@ -11,11 +11,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_20_23_brutal_arch",
     display_name: "Genuary 20, 23 | Generative Architecture, Inspired by Brutalism",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const BACKGROUND: f32 = 0.0;
@@ -111,6 +115,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
 
     let main_shader = gpu::GpuState::new(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "g25_20_23_brutal_arch_shader1.wgsl"),
         &params,
@@ -123,6 +128,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
     );
     let post_shader = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "g25_20_23_brutal_arch_shader2.wgsl"),
         &post_params,
@@ -241,9 +247,9 @@ impl Sketch for Template {
             .update_params(app, window_size, &post_params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(WHITE);
-        self.post_shader.render(&frame);
+        self.post_shader.render(frame);
     }
 }
 
@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_22_gradients_only",
     display_name: "Genuary 22: Gradients Only",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 1244,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -50,6 +54,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "./g25_22_gradients_only.wgsl"),
         &params,
@@ -88,8 +93,8 @@ impl Sketch for Template {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "g25_5_isometric",
     display_name: "Genuary 5: Isometric Art",
+    tags: &["genuary"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -45,6 +49,7 @@ pub fn init(app: &App, ctx: &Context) -> Template {
 
     let gpu = GpuState::new_fullscreen(
         app,
+        ctx,
         window_size,
         to_absolute_path(file!(), "./g25_5_isometric.wgsl"),
         &params,
@@ -75,8 +80,8 @@ impl Sketch for Template {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
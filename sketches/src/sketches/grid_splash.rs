@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "grid_splash",
     display_name: "Grid Splash",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -34,6 +38,7 @@ pub fn init(app: &App, ctx: &Context) -> GridSplash {
 
     let shader = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "grid_splash.wgsl"),
         &params,
@@ -60,7 +65,7 @@ impl Sketch for GridSplash {
         self.texture = Some(self.shader.render_to_texture(app));
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.shader.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.shader.render(frame);
     }
 }
@@ -4,18 +4,21 @@ use rayon::prelude::*;
 use std::sync::Arc;
 use xtal::prelude::*;
 
-use super::common::displacer::*;
 use super::common::distance;
 use crate::util::*;
 
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "heat_mask",
     display_name: "Heat Mask",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 1000,
     h: 1000,
+    msaa_samples: 4,
 };
 
 const GRID_SIZE: usize = 128;
@@ -447,7 +450,7 @@ impl Sketch for HeatMask {
         );
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
         let wr = ctx.window_rect();
 
@@ -504,7 +507,7 @@ impl Sketch for HeatMask {
                 .color(lin_srgb_to_lin_srgba(*color, alpha));
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
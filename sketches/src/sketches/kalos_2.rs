@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "kalos_2",
     display_name: "Kalos 2",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -35,6 +39,7 @@ pub fn init(app: &App, ctx: &Context) -> Kalos2Dyn {
 
     let shader_1 = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "kalos_2_shader_1.wgsl"),
         &params,
@@ -43,6 +48,7 @@ pub fn init(app: &App, ctx: &Context) -> Kalos2Dyn {
 
     let shader_2 = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "kalos_2_shader_2.wgsl"),
         &params,
@@ -81,7 +87,7 @@ impl Sketch for Kalos2Dyn {
         self.prev_texture = Some(shader_2_output);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.shader_2.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.shader_2.render(frame);
     }
 }
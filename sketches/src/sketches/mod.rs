@@ -82,4 +82,6 @@ pub use self::templates::basic_cube_shader_template;
 pub use self::templates::du_fs_template;
 pub use self::templates::du_fs_texture_template;
 pub use self::templates::fullscreen_shader_template;
+pub use self::templates::physarum_template;
+pub use self::templates::reaction_diffusion_template;
 pub use self::templates::template;
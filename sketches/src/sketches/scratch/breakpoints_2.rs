@@ -5,11 +5,15 @@ use nannou::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "breakpoints_2",
     display_name: "Breakpoints 2",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Advance,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -140,7 +144,7 @@ impl Sketch for Breakpoints2 {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -200,7 +204,7 @@ impl Sketch for Breakpoints2 {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
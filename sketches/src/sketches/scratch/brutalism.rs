@@ -11,11 +11,15 @@ use crate::util::{CUBE_POSITIONS, QUAD_POSITIONS};
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "brutalism",
     display_name: "Inspired by Brutalism",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const BACKGROUND: f32 = 0.0;
@@ -111,6 +115,7 @@ pub fn init(app: &App, ctx: &Context) -> Brutalism {
 
     let main_shader = gpu::GpuState::new(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "brutalism_shader1.wgsl"),
         &params,
@@ -124,6 +129,7 @@ pub fn init(app: &App, ctx: &Context) -> Brutalism {
 
     let post_shader = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "brutalism_shader2.wgsl"),
         &post_params,
@@ -227,9 +233,9 @@ impl Sketch for Brutalism {
             .update_params(app, window_size, &post_params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(WHITE);
-        self.post_shader.render(&frame);
+        self.post_shader.render(frame);
     }
 }
 
@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "chromatic_aberration",
     display_name: "Chromatic Aberration",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const GRID_SIZE: usize = 8;
@@ -54,7 +58,7 @@ impl Sketch for Template {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
         let wr = ctx.window_rect();
 
@@ -86,6 +90,6 @@ impl Sketch for Template {
                 .color(rgba(0.0, 0.0, 255.0, alpha));
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
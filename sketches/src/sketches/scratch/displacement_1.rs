@@ -4,17 +4,20 @@ use rayon::prelude::*;
 use std::sync::Arc;
 use xtal::prelude::*;
 
-use crate::sketches::common::displacer::*;
 use crate::util::*;
 
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "displacement_1",
     display_name: "Displacement 1",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 1000,
     h: 1000,
+    msaa_samples: 4,
 };
 
 const GRID_SIZE: usize = 128;
@@ -153,7 +156,7 @@ impl Sketch for Displacement1 {
             .collect();
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
 
         draw.background().color(hsl(0.0, 0.0, 0.02));
@@ -167,7 +170,7 @@ impl Sketch for Displacement1 {
                 .xy(*position);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
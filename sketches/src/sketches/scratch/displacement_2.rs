@@ -4,17 +4,20 @@ use rayon::prelude::*;
 use std::sync::Arc;
 use xtal::prelude::*;
 
-use crate::sketches::common::displacer::*;
 use crate::util::*;
 
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "displacement_2",
     display_name: "Displacement 2",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 30.0,
     bpm: 134.0,
     w: 1000,
     h: 1000,
+    msaa_samples: 4,
 };
 
 const GRID_SIZE: usize = 128;
@@ -246,7 +249,7 @@ impl Sketch for Displacement2 {
             .collect();
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
 
         frame.clear(BLACK);
@@ -261,7 +264,7 @@ impl Sketch for Displacement2 {
                 .xy(*position);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -8,11 +8,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "easing_vis",
     display_name: "Easing",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -101,9 +105,9 @@ impl Sketch for EasingVis {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
-        ctx.background(&frame, &draw, hsl(0.0, 0.0, 0.02));
+        ctx.background(frame, &draw, hsl(0.0, 0.0, 0.02));
 
         let n_points = 100;
         let line_weight = 2.0;
@@ -144,6 +148,6 @@ impl Sketch for EasingVis {
             .points(points_down)
             .color(hsl(self.hub.get("down_hue"), 0.5, 0.5));
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
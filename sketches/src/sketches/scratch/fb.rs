@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "fb",
     display_name: "fb",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -35,6 +39,7 @@ pub fn init(app: &App, ctx: &Context) -> DynamicUniformsDev {
 
     let shader = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "fb.wgsl"),
         &params,
@@ -82,7 +87,7 @@ impl Sketch for DynamicUniformsDev {
         }
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.shader.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.shader.render(frame);
     }
 }
@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "flow_field_basic",
     display_name: "Flow Field Basic",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -94,7 +98,7 @@ impl Sketch for FlowFieldBasic {
         });
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         // let start = Instant::now();
 
         let draw = app.draw();
@@ -118,7 +122,7 @@ impl Sketch for FlowFieldBasic {
                 .color(hsla(0.7, 0.2, 0.02, 1.0));
         });
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
 
         // debug!("draw: {:?}", start.elapsed());
     }
@@ -12,11 +12,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "perlin_loop",
     display_name: "Perlin Loop",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -58,7 +62,7 @@ impl Sketch for PerlinLoop {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, _ctx: &Context) {
         let draw = app.draw();
         draw.background().hsl(0.0, 0.0, 0.03);
 
@@ -98,6 +102,6 @@ impl Sketch for PerlinLoop {
                 .rotate(current_angle);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -10,6 +10,9 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "sand_lines",
     display_name: "Sand Lines",
+    tags: &["scratch"],
+    author: "",
+    description: "",
 
     // The sketch absolutely kills the CPU so is only good for static drawings
     play_mode: PlayMode::ManualAdvance,
@@ -17,6 +20,7 @@ pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     bpm: 134.0,
     w: 1000,
     h: 1000,
+    msaa_samples: 4,
 };
 
 const N_LINES: usize = 64;
@@ -459,7 +463,7 @@ impl Sketch for SandLines {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
 
         draw.rect()
@@ -497,7 +501,7 @@ impl Sketch for SandLines {
             }
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
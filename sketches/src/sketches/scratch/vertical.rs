@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "vertical",
     display_name: "Vertical",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -176,7 +180,7 @@ impl Sketch for Vertical {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let window_rect = ctx.window_rect();
         let draw = app.draw();
 
@@ -195,7 +199,7 @@ impl Sketch for Vertical {
                 .color(hsla(0.4, 0.0, 0.0, 0.9));
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
 
@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "z_sim",
     display_name: "Z Axis Simulation",
+    tags: &["scratch"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const GRID_SIZE: usize = 32;
@@ -54,7 +58,7 @@ impl Sketch for ZSim {
         }
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let draw = app.draw();
         let wr = ctx.window_rect();
 
@@ -114,6 +118,6 @@ impl Sketch for ZSim {
                 .color(depth_color);
         }
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -4,11 +4,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "spiral",
     display_name: "Spiral",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 90.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[repr(C)]
@@ -137,6 +141,7 @@ pub fn init(app: &App, ctx: &Context) -> Spiral {
 
     let gpu = gpu::GpuState::new_procedural(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "./spiral.wgsl"),
         &params,
@@ -207,7 +212,7 @@ impl Sketch for Spiral {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(WHITE);
 
         let points_per_line = self.controls.get("points_per_segment") as u32;
@@ -218,7 +223,7 @@ impl Sketch for Spiral {
         let background_vertices = 3;
         let total_vertices = background_vertices + spiral_vertices;
 
-        self.gpu.render_procedural(&frame, total_vertices);
+        self.gpu.render_procedural(frame, total_vertices);
     }
 }
 
@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "spiral_lines",
     display_name: "Spiral | Lines Version",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 120.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[repr(C)]
@@ -70,6 +74,7 @@ pub fn init(app: &App, ctx: &Context) -> SpiralLines {
 
     let gpu = gpu::GpuState::new_procedural(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "spiral_lines.wgsl"),
         &params,
@@ -132,7 +137,7 @@ impl Sketch for SpiralLines {
         self.gpu.update_params(app, wr.resolution_u32(), &params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(WHITE);
 
         let points_per_line = self.hub.get("points_per_segment") as u32;
@@ -143,6 +148,6 @@ impl Sketch for SpiralLines {
         let background_vertices = 3;
         let total_vertices = background_vertices + spiral_vertices;
 
-        self.gpu.render_procedural(&frame, total_vertices);
+        self.gpu.render_procedural(frame, total_vertices);
     }
 }
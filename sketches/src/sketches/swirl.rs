@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "swirl",
     display_name: "Swirl",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 800,
     h: 800,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -33,6 +37,7 @@ pub fn init(app: &App, ctx: &Context) -> DynamicUniformsDev {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "swirl.wgsl"),
         &params,
@@ -50,7 +55,7 @@ impl Sketch for DynamicUniformsDev {
         self.gpu.update_params(app, wr.resolution_u32(), &params);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.gpu.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.gpu.render(frame);
     }
 }
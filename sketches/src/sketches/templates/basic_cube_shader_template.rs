@@ -7,11 +7,15 @@ use crate::util::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "basic_cube_shader_template",
     display_name: "Template | Basic Cube Shader",
+    tags: &["template"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 const BACKGROUND: f32 = 0.0;
@@ -55,6 +59,7 @@ pub fn init(app: &App, ctx: &Context) -> BasicCubeShader {
 
     let gpu = gpu::GpuState::new(
         app,
+        ctx,
         ctx.window_rect().resolution_u32(),
         to_absolute_path(file!(), "basic_cube_shader_template.wgsl"),
         &params,
@@ -89,9 +94,9 @@ impl Sketch for BasicCubeShader {
             .update(app, wr.resolution_u32(), &params, &vertices);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
 
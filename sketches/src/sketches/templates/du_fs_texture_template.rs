@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "du_fs_texture_template",
     display_name: "Dynamic Uniforms w/ Texture Pass Template",
+    tags: &["template"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -34,6 +38,7 @@ pub fn init(app: &App, ctx: &Context) -> DynamicUniformsDev {
 
     let shader_1 = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "du_fs_texture_template_1.wgsl"),
         &params,
@@ -42,6 +47,7 @@ pub fn init(app: &App, ctx: &Context) -> DynamicUniformsDev {
 
     let shader_2 = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         wr.resolution_u32(),
         to_absolute_path(file!(), "du_fs_texture_template_2.wgsl"),
         &params,
@@ -69,7 +75,7 @@ impl Sketch for DynamicUniformsDev {
         self.shader_2.set_texture(app, &texture);
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
-        self.shader_2.render(&frame);
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        self.shader_2.render(frame);
     }
 }
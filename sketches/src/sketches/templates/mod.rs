@@ -2,4 +2,6 @@ pub mod basic_cube_shader_template;
 pub mod du_fs_template;
 pub mod du_fs_texture_template;
 pub mod fullscreen_shader_template;
+pub mod physarum_template;
+pub mod reaction_diffusion_template;
 pub mod template;
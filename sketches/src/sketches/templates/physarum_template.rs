@@ -0,0 +1,98 @@
+use nannou::prelude::*;
+
+use xtal::prelude::*;
+
+pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
+    name: "physarum_template",
+    display_name: "Template | Physarum",
+    tags: &["template"],
+    author: "",
+    description: "",
+    play_mode: PlayMode::Loop,
+    fps: 60.0,
+    bpm: 134.0,
+    w: 700,
+    h: 700,
+    msaa_samples: 4,
+};
+
+#[derive(SketchComponents)]
+pub struct PhysarumTemplate {
+    hub: ControlHub<Timing>,
+    slime: gpu::Physarum,
+    gpu: gpu::GpuState<gpu::BasicPositionVertex>,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShaderParams {
+    // w, h, ..unused
+    resolution: [f32; 4],
+
+    // exposure, hue, ..unused
+    a: [f32; 4],
+}
+
+pub fn init(app: &App, ctx: &Context) -> PhysarumTemplate {
+    let hub = ControlHub::from_path(
+        to_absolute_path(file!(), "physarum_template.yaml"),
+        Timing::new(ctx.bpm()),
+    );
+
+    let wr = ctx.window_rect();
+
+    // To customize the simulation itself, copy xtal's physarum.wgsl next to
+    // this file and chain `.shader(to_absolute_path(file!(), "..."))`
+    let slime = gpu::Physarum::new(
+        app,
+        wr.resolution_u32(),
+        hub.get("slime.agents") as usize,
+    );
+
+    let params = ShaderParams {
+        resolution: [0.0; 4],
+        a: [0.0; 4],
+    };
+
+    let gpu = gpu::GpuState::new_fullscreen(
+        app,
+        ctx,
+        wr.resolution_u32(),
+        to_absolute_path(file!(), "physarum_template.wgsl"),
+        &params,
+        1,
+    );
+
+    PhysarumTemplate { hub, slime, gpu }
+}
+
+impl Sketch for PhysarumTemplate {
+    fn update(&mut self, app: &App, _update: Update, ctx: &Context) {
+        let wr = ctx.window_rect();
+
+        self.slime.update_params(&self.hub, "slime");
+        self.slime.step(app, self.hub.get("steps") as usize);
+
+        let params = ShaderParams {
+            resolution: [wr.w(), wr.h(), 0.0, 0.0],
+            a: [self.hub.get("exposure"), self.hub.get("hue"), 0.0, 0.0],
+        };
+
+        self.gpu.update_params(app, wr.resolution_u32(), &params);
+        self.gpu.set_texture(app, self.slime.texture_view());
+    }
+
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        frame.clear(BLACK);
+        self.gpu.render(frame);
+    }
+
+    // Lets other sketches use the trail map via `inputs`
+    fn render_output(
+        &self,
+        _app: &App,
+        _ctx: &Context,
+    ) -> Option<wgpu::TextureView> {
+        Some(self.slime.texture_view().clone())
+    }
+}
@@ -0,0 +1,47 @@
+struct VertexInput {
+    @location(0) position: vec2f,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+struct Params {
+    // w, h, ..unused
+    resolution: vec4f,
+
+    // exposure, hue, ..unused
+    a: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(1) @binding(0)
+var source_sampler: sampler;
+
+// Physarum trail map (unbounded intensity in every channel)
+@group(1) @binding(1)
+var source_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(vert: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4f(vert.position, 0.0, 1.0);
+    out.uv = vert.position * 0.5 + 0.5;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let exposure = params.a.x;
+    let hue = params.a.y;
+
+    let trail = textureSample(source_texture, source_sampler, in.uv).r;
+    let v = 1.0 - exp(-trail * exposure);
+
+    let tint = 0.5 + 0.5 * cos(6.28318 * (hue + vec3f(0.0, 0.33, 0.67)));
+    let color = mix(tint * v, vec3f(1.0), v * v * v);
+    return vec4f(color, 1.0);
+}
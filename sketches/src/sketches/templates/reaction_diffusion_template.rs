@@ -0,0 +1,119 @@
+use nannou::prelude::*;
+
+use xtal::prelude::*;
+
+pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
+    name: "reaction_diffusion_template",
+    display_name: "Template | Reaction Diffusion",
+    tags: &["template"],
+    author: "",
+    description: "",
+    play_mode: PlayMode::Loop,
+    fps: 60.0,
+    bpm: 134.0,
+    w: 700,
+    h: 700,
+    msaa_samples: 4,
+};
+
+#[derive(SketchComponents)]
+pub struct ReactionDiffusionTemplate {
+    hub: ControlHub<Timing>,
+    rd: gpu::ReactionDiffusion,
+    gpu: gpu::GpuState<gpu::BasicPositionVertex>,
+    rng: RngStream,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShaderParams {
+    // w, h, ..unused
+    resolution: [f32; 4],
+
+    // contrast, invert, ..unused
+    a: [f32; 4],
+}
+
+pub fn init(app: &App, ctx: &Context) -> ReactionDiffusionTemplate {
+    let hub = ControlHub::from_path(
+        to_absolute_path(file!(), "reaction_diffusion_template.yaml"),
+        Timing::new(ctx.bpm()),
+    );
+
+    let wr = ctx.window_rect();
+
+    // Half the window's resolution; the display pass filters it back up
+    let [w, h] = wr.resolution_u32();
+    let mut rd = gpu::ReactionDiffusion::new(app, [w / 2, h / 2]);
+    let mut rng = ctx.rng("seed");
+    rd.seed_random(
+        app,
+        &mut rng,
+        hub.get("seed_count") as usize,
+        hub.get("seed_radius"),
+    );
+
+    let params = ShaderParams {
+        resolution: [0.0; 4],
+        a: [0.0; 4],
+    };
+
+    let gpu = gpu::GpuState::new_fullscreen(
+        app,
+        ctx,
+        wr.resolution_u32(),
+        to_absolute_path(file!(), "reaction_diffusion_template.wgsl"),
+        &params,
+        1,
+    );
+
+    ReactionDiffusionTemplate { hub, rd, gpu, rng }
+}
+
+impl Sketch for ReactionDiffusionTemplate {
+    fn update(&mut self, app: &App, _update: Update, ctx: &Context) {
+        let wr = ctx.window_rect();
+
+        if self.hub.changed() {
+            if self.hub.any_changed_in(&["seed_count", "seed_radius"]) {
+                self.rd.seed_random(
+                    app,
+                    &mut self.rng,
+                    self.hub.get("seed_count") as usize,
+                    self.hub.get("seed_radius"),
+                );
+            }
+            self.hub.mark_unchanged();
+        }
+
+        self.rd.update_params(&self.hub, "rd");
+        self.rd.step(app, self.hub.get("steps") as usize);
+
+        let params = ShaderParams {
+            resolution: [wr.w(), wr.h(), 0.0, 0.0],
+            a: [
+                self.hub.get("contrast"),
+                self.hub.bool_as_f32("invert"),
+                0.0,
+                0.0,
+            ],
+        };
+
+        self.gpu.update_params(app, wr.resolution_u32(), &params);
+        self.gpu.set_texture(app, self.rd.texture_view());
+    }
+
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
+        frame.clear(BLACK);
+        self.gpu.render(frame);
+    }
+
+    // Lets other sketches use the simulation via `inputs`
+    fn render_output(
+        &self,
+        _app: &App,
+        _ctx: &Context,
+    ) -> Option<wgpu::TextureView> {
+        Some(self.rd.texture_view().clone())
+    }
+}
@@ -0,0 +1,47 @@
+struct VertexInput {
+    @location(0) position: vec2f,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+};
+
+struct Params {
+    // w, h, ..unused
+    resolution: vec4f,
+
+    // contrast, invert, ..unused
+    a: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(1) @binding(0)
+var source_sampler: sampler;
+
+// Reaction diffusion state: A in red, B in green
+@group(1) @binding(1)
+var source_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(vert: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4f(vert.position, 0.0, 1.0);
+    out.uv = vert.position * 0.5 + 0.5;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let contrast = params.a.x;
+    let invert = params.a.y;
+
+    let state = textureSample(source_texture, source_sampler, in.uv).rg;
+    var v = clamp((state.r - state.g) * contrast - contrast * 0.5 + 0.5, 0.0, 1.0);
+    v = select(v, 1.0 - v, invert == 1.0);
+
+    let color = mix(vec3f(0.02, 0.03, 0.08), vec3f(0.95, 0.9, 0.8), v);
+    return vec4f(color, 1.0);
+}
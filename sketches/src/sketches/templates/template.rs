@@ -6,11 +6,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "template",
     display_name: "Template",
+    tags: &["template"],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 500,
     h: 500,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -33,7 +37,7 @@ impl Sketch for Template {
         self.hue = self.hub.animation.tri(12.0);
     }
 
-    fn view(&self, app: &App, frame: Frame, ctx: &Context) {
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context) {
         let wr = ctx.window_rect();
         let draw = app.draw();
 
@@ -47,6 +51,6 @@ impl Sketch for Template {
             .radius(self.hub.get("radius"))
             .x_y(0.0, 0.0);
 
-        draw.to_frame(app, &frame).unwrap();
+        draw.to_frame(app, frame).unwrap();
     }
 }
@@ -5,11 +5,15 @@ use xtal::prelude::*;
 pub const SKETCH_CONFIG: SketchConfig = SketchConfig {
     name: "wave_fract",
     display_name: "Wave Fract",
+    tags: &[],
+    author: "",
+    description: "",
     play_mode: PlayMode::Loop,
     fps: 60.0,
     bpm: 134.0,
     w: 700,
     h: 700,
+    msaa_samples: 4,
 };
 
 #[derive(SketchComponents)]
@@ -63,6 +67,7 @@ pub fn init(app: &App, ctx: &Context) -> WaveFract {
 
     let gpu = gpu::GpuState::new_fullscreen(
         app,
+        ctx,
         window_rect.resolution_u32(),
         to_absolute_path(file!(), "wave_fract.wgsl"),
         &params,
@@ -131,8 +136,8 @@ impl Sketch for WaveFract {
         );
     }
 
-    fn view(&self, _app: &App, frame: Frame, _ctx: &Context) {
+    fn view(&self, _app: &App, frame: &Frame, _ctx: &Context) {
         frame.clear(BLACK);
-        self.gpu.render(&frame);
+        self.gpu.render(frame);
     }
 }
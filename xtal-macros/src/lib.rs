@@ -5,7 +5,8 @@ mod uniforms;
 
 /// Saves sketches from the burden of having to manually implement the
 /// `SketchDerived` trait which is required to integrate a sketch's controls and
-/// animations usages with the UI
+/// animations usages with the UI. Fields marked `#[sketch(lifecycle)]` receive
+/// the sketch's lifecycle hooks (see `SketchLifecycle`)
 #[proc_macro_derive(SketchComponents, attributes(sketch))]
 pub fn sketch_components(input: TokenStream) -> TokenStream {
     sketch_components::sketch_components_impl(input)
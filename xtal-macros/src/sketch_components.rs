@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Data, DataStruct, DeriveInput, Fields, parse_macro_input};
+use syn::{Data, DataStruct, DeriveInput, Field, Fields, parse_macro_input};
 
 pub fn sketch_components_impl(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
@@ -24,6 +24,12 @@ pub fn sketch_components_impl(input: TokenStream) -> TokenStream {
         ident == "controls"
     });
 
+    let lifecycle_fields: Vec<_> = fields
+        .iter()
+        .filter(|f| has_lifecycle_attr(f))
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect();
+
     let controls_impl = if has_hub {
         quote! { Some(&mut self.hub) }
     } else if has_controls {
@@ -37,8 +43,35 @@ pub fn sketch_components_impl(input: TokenStream) -> TokenStream {
             fn hub(&mut self) -> Option<&mut dyn ControlHubProvider> {
                 #controls_impl
             }
+
+            fn lifecycle_components(
+                &mut self,
+            ) -> Vec<&mut dyn SketchLifecycle> {
+                vec![#(
+                    &mut self.#lifecycle_fields as &mut dyn SketchLifecycle
+                ),*]
+            }
         }
     };
 
     generated.into()
 }
+
+/// Whether a field is marked `#[sketch(lifecycle)]`
+fn has_lifecycle_attr(field: &Field) -> bool {
+    let mut found = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("sketch")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("lifecycle") {
+                found = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported sketch field attribute"))
+            }
+        })
+        .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    found
+}
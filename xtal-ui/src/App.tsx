@@ -1,14 +1,34 @@
 import { useCallback, useEffect, useState } from 'react'
 
 import {
+  AudioDeviceStatus,
   Bypassed,
   Control,
   ControlKind,
   ControlValue,
+  DebugGraph,
+  EncodingProgress,
   Exclusions,
+  HistoryTracking,
+  LatencyOffsets,
   Mappings,
+  MidiAction,
+  MidiActions,
   OsDir,
+  OutputWarps,
+  PendingEncodes,
   RawControl,
+  RecordingSettings,
+  ScriptError,
+  SetList,
+  SketchGpuUsage,
+  SketchMetadata,
+  SliderRange,
+  SnapshotDiff,
+  StereoMode,
+  StereoSettings,
+  Subsystem,
+  SyncRole,
   UserDir,
   View,
 } from './types'
@@ -17,47 +37,98 @@ import Header from './Header'
 import Controls from './Controls'
 import Settings from './Settings'
 import Console from './Console'
+import QuitDialog from './QuitDialog'
 import useKeyDownOnce from './useKeyDownOnce'
 import { isMac, setCssBeat } from './util'
 
+const SKETCH_FILTER_ALL = 'All'
+const SKETCH_FILTER_FAVORITES = 'Favorites'
+const SKETCH_FILTER_RECENT = 'Recent'
+
 type EventMap = {
   Advance: void
   Alert: string
+  AudioDevice: {
+    status: AudioDeviceStatus
+    devices: string[]
+  }
+  AutoBpmEnabled: boolean
   AverageFps: number
   Bpm: number
+  BypassedChanged: Bypassed
+  CaptureFlipbook: [number, number]
   CaptureFrame: void
   ChangeAudioDevice: string
+  ChangeBpm: number
+  ChangeDepthDevice: string
   ChangeDir: UserDir
+  ChangeFps: number
+  ChangeLatencyOffsets: LatencyOffsets
   ChangeMidiClockPort: string
   ChangeMidiControlInputPort: string
   ChangeMidiControlOutputPort: string
+  ChangeMsaaSamples: number
   ChangeOscPort: number
+  ChangeRecordingSettings: RecordingSettings
+  ChangeSliderRange: [string, SliderRange | null]
+  ChangeStereo: StereoSettings
+  ChangeSyncRole: SyncRole
   ClearBuffer: void
   CommitMappings: void
+  ConfirmQuit: number
+  ControlHistory: Record<string, number[]>
+  ControlsChanged: RawControl[]
+  CountIn: number | null
   CurrentlyMapping: string
+  CycleAspectPreset: void
+  CycleCalibration: void
+  DebugGraph: DebugGraph | null
   Encoding: boolean
+  EncodingProgress: EncodingProgress | null
   Error: string
+  Favorites: string[]
+  Fps: number
+  Frozen: boolean
+  GpuStats: SketchGpuUsage[]
+  Held: string[]
+  Hold: [string, boolean]
   Hrcc: boolean
   HubPopulated: [RawControl[], Bypassed]
   Init: {
     audioDevice: string
     audioDevices: string[]
+    depthDevice: string
+    depthDevices: string[]
+    favoriteSketches: string[]
     hrcc: boolean
     imagesDir: string
     isLightTheme: boolean
+    latencyOffsets: LatencyOffsets
     mappingsEnabled: boolean
+    midiActions: MidiActions
     midiClockPort: string
     midiInputPort: string
     midiOutputPort: string
     midiInputPorts: [number, string][]
     midiOutputPorts: [number, string][]
     oscPort: number
+    outputWarps: OutputWarps
+    prewarm: boolean
+    recentSketches: string[]
+    recordingSettings: RecordingSettings
+    setList: SetList
     sketchNames: string[]
+    sketches: SketchMetadata[]
     sketchName: string
+    snapshotPhase: boolean
+    stereo: StereoSettings
+    syncRole: SyncRole
     transitionTime: number
     userDataDir: string
     videosDir: string
   }
+  Instrumentation: boolean
+  LearnMidiAction: MidiAction | null
   LoadSketch: {
     bpm: number
     bypassed: Bypassed
@@ -66,36 +137,87 @@ type EventMap = {
     fps: number
     paused: boolean
     mappings: Mappings
+    msaaSamples: number
+    profile: string
+    profiles: string[]
+    recentSketches: string[]
     sketchName: string
     snapshotSlots: string[]
     tapTempoEnabled: boolean
+    autoBpmEnabled: boolean
   }
   Mappings: Mappings
   MappingsEnabled: boolean
+  MidiActions: MidiActions
   OpenOsDir: OsDir
+  OutputWarps: OutputWarps
   Paused: boolean
   PerfMode: boolean
+  Prewarm: boolean
+  PrewarmSketch: string
+  Preview: {
+    name: string
+    values: number[]
+  }
+  Profile: string
   QueueRecord: void
   Quit: void
   Randomize: Exclusions
   Ready: void
   ReceiveDir: [UserDir, string]
+  RecordingMarker: string | null
+  RecordingValues: boolean
+  RecordValues: string[] | null
   RemoveMapping: string
+  RemoveMidiAction: MidiAction
+  ReorderFavorites: string[]
+  Replay: string | null
+  RequestDebugGraph: void
+  RequestGpuStats: void
+  RequestPreview: {
+    name: string
+    resolution: number
+    beats: number
+  }
+  RequestSnapshotDiff: [string, string]
   Reset: void
+  ResetTimingOverrides: void
+  RuntimeBypass: [string, boolean]
   Save: string[]
+  SaveTrace: void
+  ScriptError: ScriptError | null
   SendMidi: void
-  SnapshotEnded: RawControl[]
+  SetList: SetList
+  SetListGoTo: number
+  SetListNext: void
+  SetListPrevious: void
+  Shutdown: PendingEncodes
+  SnapshotEnded: void
+  SnapshotCopyValues: { from: string; to: string; names: string[] }
   SnapshotDelete: string
+  SnapshotDiff: SnapshotDiff
+  SnapshotPhase: boolean
   SnapshotRecall: string
+  SnapshotRecallOnly: [string, string[]]
   SnapshotStore: string
+  Solo: string | null
   StartRecording: void
   StopRecording: void
   SwitchSketch: string
+  SyncRole: SyncRole
+  SysExBackup: boolean
+  SysExRestore: void
   Tap: void
   TapTempoEnabled: boolean
+  ToggleFavorite: string
   ToggleFullScreen: void
+  ToggleLetterbox: void
   ToggleMainFocus: void
+  ToggleSafeAreaGuides: void
+  TraceSubsystem: [Subsystem, boolean]
+  TrackControlHistory: HistoryTracking
   TransitionTime: number
+  TriggerEnvelope: string
   UpdateControlBool: {
     name: string
     value: boolean
@@ -108,7 +230,6 @@ type EventMap = {
     name: string
     value: string
   }
-  UpdatedControls: RawControl[]
 }
 
 function subscribe<K extends keyof EventMap>(
@@ -181,35 +302,88 @@ export default function App() {
   const [alertText, setAlertText] = useState('')
   const [audioDevices, setAudioDevices] = useState<string[]>([])
   const [audioDevice, setAudioDevice] = useState('')
+  const [audioDeviceStatus, setAudioDeviceStatus] = useState(
+    AudioDeviceStatus.Inactive
+  )
+  const [depthDevices, setDepthDevices] = useState<string[]>([])
+  const [depthDevice, setDepthDevice] = useState('')
+  const [autoBpmEnabled, setAutoBpmEnabled] = useState(false)
   const [bpm, setBpm] = useState(134)
   const [bypassed, setBypassed] = useState<Bypassed>({})
   const [controls, setControls] = useState<Control[]>([])
   const [controlsLastSaved, setControlsLastSaved] = useState<Control[]>([])
+  const [countIn, setCountIn] = useState<number | null>(null)
+  const [cuedSketch, setCuedSketch] = useState('')
   const [exclusions, setExclusions] = useState<string[]>([])
+  const [favorites, setFavorites] = useState<string[]>([])
   const [fps, setFps] = useState(60)
+  const [frozen, setFrozen] = useState(false)
   const [hrcc, setHrcc] = useState(false)
   const [imagesDir, setImagesDir] = useState('')
   const [isEncoding, setIsEncoding] = useState(false)
+  const [encodingProgress, setEncodingProgress] =
+    useState<EncodingProgress | null>(null)
   const [isQueued, setIsQueued] = useState(false)
   const [isRecording, setIsRecording] = useState(false)
+  const [isRecordingSysEx, setIsRecordingSysEx] = useState(false)
+  const [isRecordingValues, setIsRecordingValues] = useState(false)
+  const [latencyOffsets, setLatencyOffsets] = useState<LatencyOffsets>({
+    midi: { Ms: 0 },
+    osc: { Ms: 0 },
+    audio: { Ms: 0 },
+  })
   const [mappings, setMappings] = useState<Mappings>({})
   const [mappingsEnabled, setMappingsEnabled] = useState(true)
+  const [midiActions, setMidiActions] = useState<MidiActions>({})
   const [midiClockPort, setMidiClockPort] = useState('')
   const [midiInputPort, setMidiInputPort] = useState('')
   const [midiInputPorts, setMidiInputPorts] = useState<string[]>([])
   const [midiOutputPort, setMidiOutputPort] = useState('')
   const [midiOutputPorts, setMidiOutputPorts] = useState<string[]>([])
   const [oscPort, setOscPort] = useState(5000)
+  const [outputWarps, setOutputWarps] = useState<OutputWarps>({
+    enabled: false,
+    venue: 'default',
+    venues: {},
+  })
   const [paused, setPaused] = useState(false)
   const [perfMode, setPerfMode] = useState(false)
+  const [prewarm, setPrewarm] = useState(false)
+  const [profile, setProfile] = useState('')
+  const [profiles, setProfiles] = useState<string[]>([])
+  const [quitTakes, setQuitTakes] = useState<number | null>(null)
+  const [recentSketches, setRecentSketches] = useState<string[]>([])
+  const [recordingSettings, setRecordingSettings] =
+    useState<RecordingSettings>({
+      preRoll: 0,
+      countIn: 0,
+      timestampOverlay: false,
+    })
+  const [scriptError, setScriptError] = useState<ScriptError | null>(null)
+  const [setList, setSetList] = useState<SetList>({
+    entries: [],
+    nextCc: null,
+    position: null,
+  })
   const [showExclusions, setShowExclusions] = useState(false)
   const [showHelp, setShowHelp] = useState(false)
   const [showSnapshots, setShowSnapshots] = useState(false)
   const [singleTransitionControlName, setSingleTransitionControlName] =
     useState('')
+  const [sketchFps, setSketchFps] = useState(60)
+  const [sketchMsaaSamples, setSketchMsaaSamples] = useState(4)
   const [sketchName, setSketchName] = useState('')
+  const [sketchFilter, setSketchFilter] = useState(SKETCH_FILTER_ALL)
   const [sketchNames, setSketchNames] = useState<string[]>([])
+  const [sketches, setSketches] = useState<SketchMetadata[]>([])
+  const [snapshotPhase, setSnapshotPhase] = useState(false)
   const [snapshots, setSnapshots] = useState<string[]>([])
+  const [snapshotDiff, setSnapshotDiff] = useState<SnapshotDiff | null>(null)
+  const [stereo, setStereo] = useState<StereoSettings>({
+    mode: StereoMode.Off,
+    separation: 0.05,
+  })
+  const [syncRole, setSyncRole] = useState(SyncRole.Off)
   const [tapTempoEnabled, setTapTempoEnabled] = useState(false)
   const [transitionTime, setTransitionTime] = useState(4)
   const [transitionInProgress, setTransitionInProgress] = useState(false)
@@ -228,6 +402,16 @@ export default function App() {
           setAlertText(data as EventMap['Alert'])
           break
         }
+        case 'AudioDevice': {
+          const d = data as EventMap['AudioDevice']
+          setAudioDeviceStatus(d.status)
+          setAudioDevices(d.devices)
+          break
+        }
+        case 'AutoBpmEnabled': {
+          setAutoBpmEnabled(data as EventMap['AutoBpmEnabled'])
+          break
+        }
         case 'AverageFps': {
           setFps(data as EventMap['AverageFps'])
           break
@@ -237,6 +421,30 @@ export default function App() {
           setBpm(bpm)
           break
         }
+        case 'BypassedChanged': {
+          setBypassed(data as EventMap['BypassedChanged'])
+          break
+        }
+        case 'ControlsChanged': {
+          const changed = new Map(
+            fromRawControls(data as EventMap['ControlsChanged']).map((c) => [
+              c.name,
+              c,
+            ])
+          )
+          setControls((controls) =>
+            controls.map((c) => changed.get(c.name) ?? c)
+          )
+          break
+        }
+        case 'ConfirmQuit': {
+          setQuitTakes(data as EventMap['ConfirmQuit'])
+          break
+        }
+        case 'CountIn': {
+          setCountIn(data as EventMap['CountIn'])
+          break
+        }
         case 'Encoding': {
           setIsEncoding(data as EventMap['Encoding'])
           if (data) {
@@ -245,6 +453,18 @@ export default function App() {
           }
           break
         }
+        case 'EncodingProgress': {
+          setEncodingProgress(data as EventMap['EncodingProgress'])
+          break
+        }
+        case 'Favorites': {
+          setFavorites(data as EventMap['Favorites'])
+          break
+        }
+        case 'Fps': {
+          setSketchFps(data as EventMap['Fps'])
+          break
+        }
         case 'HubPopulated': {
           const [controls, bypassed] = data as EventMap['HubPopulated']
           setControls(fromRawControls(controls))
@@ -255,9 +475,14 @@ export default function App() {
           const d = data as EventMap['Init']
           setAudioDevice(d.audioDevice)
           setAudioDevices(d.audioDevices)
+          setDepthDevice(d.depthDevice)
+          setDepthDevices(d.depthDevices)
+          setFavorites(d.favoriteSketches)
           setHrcc(d.hrcc)
           setImagesDir(d.imagesDir)
+          setLatencyOffsets(d.latencyOffsets)
           setMappingsEnabled(d.mappingsEnabled)
+          setMidiActions(d.midiActions)
           setMidiClockPort(d.midiClockPort)
           setMidiInputPort(d.midiInputPort)
           setMidiOutputPort(d.midiOutputPort)
@@ -265,9 +490,18 @@ export default function App() {
           setMidiInputPorts(d.midiInputPorts.map(getPort))
           setMidiOutputPorts(d.midiOutputPorts.map(getPort))
           setOscPort(d.oscPort)
+          setOutputWarps(d.outputWarps)
+          setPrewarm(d.prewarm)
+          setRecentSketches(d.recentSketches)
+          setRecordingSettings(d.recordingSettings)
+          setSetList(d.setList)
           setUserDataDir(d.userDataDir)
           setSketchName(d.sketchName)
           setSketchNames(d.sketchNames)
+          setSketches(d.sketches)
+          setSnapshotPhase(d.snapshotPhase)
+          setStereo(d.stereo)
+          setSyncRole(d.syncRole)
           setTransitionTime(d.transitionTime)
           setVideosDir(d.videosDir)
           break
@@ -281,18 +515,39 @@ export default function App() {
           setControlsLastSaved(controls)
           setExclusions(d.exclusions)
           setFps(d.fps)
+          setSketchFps(d.fps)
           setMappings(d.mappings)
+          setSketchMsaaSamples(d.msaaSamples)
           setPaused(d.paused)
+          setProfile(d.profile)
+          setProfiles(d.profiles)
+          setCuedSketch('')
+          setRecentSketches(d.recentSketches)
+          setScriptError(null)
           setSketchName(d.sketchName)
           setSnapshots(d.snapshotSlots)
+          setSnapshotDiff(null)
           // TODO: why are we sending this with the sketch?
           setTapTempoEnabled(d.tapTempoEnabled)
+          setAutoBpmEnabled(d.autoBpmEnabled)
           break
         }
         case 'Mappings': {
           setMappings(data as EventMap['Mappings'])
           break
         }
+        case 'MidiActions': {
+          setMidiActions(data as EventMap['MidiActions'])
+          break
+        }
+        case 'Profile': {
+          setProfile(data as EventMap['Profile'])
+          break
+        }
+        case 'RecordingValues': {
+          setIsRecordingValues(data as EventMap['RecordingValues'])
+          break
+        }
         case 'ReceiveDir': {
           const [kind, dir] = data as EventMap['ReceiveDir']
           if (kind === UserDir.Images) {
@@ -304,8 +559,23 @@ export default function App() {
           }
           break
         }
+        case 'ScriptError': {
+          setScriptError(data as EventMap['ScriptError'])
+          break
+        }
+        case 'OutputWarps': {
+          setOutputWarps(data as EventMap['OutputWarps'])
+          break
+        }
+        case 'SetList': {
+          setSetList(data as EventMap['SetList'])
+          break
+        }
+        case 'SnapshotDiff': {
+          setSnapshotDiff(data as EventMap['SnapshotDiff'])
+          break
+        }
         case 'SnapshotEnded': {
-          setControls(fromRawControls(data as EventMap['SnapshotEnded']))
           setTransitionInProgress(false)
           setSingleTransitionControlName('')
           break
@@ -315,8 +585,12 @@ export default function App() {
           setIsQueued(false)
           break
         }
-        case 'UpdatedControls': {
-          setControls(fromRawControls(data as EventMap['UpdatedControls']))
+        case 'SyncRole': {
+          setSyncRole(data as EventMap['SyncRole'])
+          break
+        }
+        case 'TransitionTime': {
+          setTransitionTime(data as EventMap['TransitionTime'])
           break
         }
         default: {
@@ -360,11 +634,19 @@ export default function App() {
             break
           }
           case 'KeyA': {
-            if (paused) {
+            if (e.shiftKey) {
+              post('CycleAspectPreset')
+            } else if (paused) {
               post('Advance')
             }
             break
           }
+          case 'KeyB': {
+            if (isRecording) {
+              post('RecordingMarker', null)
+            }
+            break
+          }
           case 'KeyE': {
             setShowExclusions(!showExclusions)
             break
@@ -374,7 +656,15 @@ export default function App() {
             break
           }
           case 'KeyI': {
-            post('CaptureFrame')
+            if (e.shiftKey) {
+              post('CaptureFlipbook', [9, 1])
+            } else {
+              post('CaptureFrame')
+            }
+            break
+          }
+          case 'KeyL': {
+            post('ToggleLetterbox')
             break
           }
           case 'KeyM': {
@@ -384,10 +674,20 @@ export default function App() {
             }
             break
           }
+          case 'KeyN': {
+            post(e.shiftKey ? 'SetListPrevious' : 'SetListNext')
+            break
+          }
           case 'KeyP': {
-            const value = !paused
-            setPaused(value)
-            post('Paused', value)
+            if (e.shiftKey) {
+              const value = !frozen
+              setFrozen(value)
+              post('Frozen', value)
+            } else {
+              const value = !paused
+              setPaused(value)
+              post('Paused', value)
+            }
             break
           }
           case 'KeyQ': {
@@ -414,6 +714,14 @@ export default function App() {
             }
             break
           }
+          case 'KeyT': {
+            if (e.shiftKey) {
+              post('CycleCalibration')
+            } else {
+              post('ToggleSafeAreaGuides')
+            }
+            break
+          }
           case 'Slash': {
             setShowHelp(!showHelp)
             break
@@ -431,6 +739,8 @@ export default function App() {
       },
       [
         exclusions,
+        frozen,
+        isRecording,
         paused,
         showExclusions,
         showHelp,
@@ -447,6 +757,43 @@ export default function App() {
     setCssBeat(bpm)
   }, [bpm])
 
+  function getSketchFilters() {
+    const tags = new Set(sketches.flatMap((sketch) => sketch.tags))
+    return [
+      SKETCH_FILTER_ALL,
+      SKETCH_FILTER_FAVORITES,
+      SKETCH_FILTER_RECENT,
+      ...Array.from(tags).sort(),
+    ]
+  }
+
+  function getFilteredSketchNames() {
+    const names =
+      sketchFilter === SKETCH_FILTER_ALL
+        ? sketchNames
+        : sketchFilter === SKETCH_FILTER_FAVORITES
+        ? favorites
+        : sketchFilter === SKETCH_FILTER_RECENT
+        ? recentSketches
+        : sketches
+            .filter((sketch) => sketch.tags.includes(sketchFilter))
+            .map((sketch) => sketch.name)
+
+    // The select needs the active (or cued) sketch among its options
+    const selected = cuedSketch || sketchName
+    return names.includes(selected) ? names : [selected, ...names]
+  }
+
+  function getSketchDescription() {
+    const sketch = sketches.find((sketch) => sketch.name === sketchName)
+    if (!sketch) {
+      return ''
+    }
+    return [sketch.description, sketch.author && `by ${sketch.author}`]
+      .filter(Boolean)
+      .join(' — ')
+  }
+
   function getSliderNames() {
     return controls
       .filter((control) => control.kind === 'Slider')
@@ -474,6 +821,22 @@ export default function App() {
     post('ChangeAudioDevice', name)
   }
 
+  function onChangeAutoBpmEnabled() {
+    const enabled = !autoBpmEnabled
+    setAutoBpmEnabled(enabled)
+    post('AutoBpmEnabled', enabled)
+  }
+
+  function onChangeDepthDevice(name: string) {
+    setDepthDevice(name)
+    post('ChangeDepthDevice', name)
+  }
+
+  function onChangeBpm(value: number) {
+    setBpm(value)
+    post('ChangeBpm', value)
+  }
+
   function onChangeControl(control: Control, value: ControlValue) {
     setControls(
       controls.map((c) =>
@@ -496,12 +859,34 @@ export default function App() {
     post('ChangeDir', kind)
   }
 
+  function onChangeFps(value: number) {
+    setSketchFps(value)
+    post('ChangeFps', value)
+  }
+
+  function onChangeLatencyOffsets(latencyOffsets: LatencyOffsets) {
+    setLatencyOffsets(latencyOffsets)
+    post('ChangeLatencyOffsets', latencyOffsets)
+  }
+
+  function onChangeMsaaSamples(value: number) {
+    setSketchMsaaSamples(value)
+    post('ChangeMsaaSamples', value)
+  }
+
   function onChangeHrcc() {
     const value = !hrcc
     setHrcc(value)
     post('Hrcc', value)
   }
 
+  function onChangePrewarm() {
+    const value = !prewarm
+    setPrewarm(value)
+    setCuedSketch('')
+    post('Prewarm', value)
+  }
+
   function onChangeMidiClockPort(port: string) {
     setMidiClockPort(port)
     post('ChangeMidiClockPort', port)
@@ -528,12 +913,37 @@ export default function App() {
     post('ChangeOscPort', port)
   }
 
+  function onChangeOutputWarps(warps: OutputWarps) {
+    setOutputWarps(warps)
+    post('OutputWarps', warps)
+  }
+
   function onChangePerfMode() {
     const value = !perfMode
     setPerfMode(value)
     post('PerfMode', value)
   }
 
+  function onChangeProfile(name: string) {
+    setProfile(name)
+    post('Profile', name)
+  }
+
+  function onChangeRecordingSettings(settings: RecordingSettings) {
+    setRecordingSettings(settings)
+    post('ChangeRecordingSettings', settings)
+  }
+
+  function onChangeStereo(settings: StereoSettings) {
+    setStereo(settings)
+    post('ChangeStereo', settings)
+  }
+
+  function onChangeSyncRole(role: SyncRole) {
+    setSyncRole(role)
+    post('ChangeSyncRole', role)
+  }
+
   function onChangeTapTempoEnabled() {
     const enabled = !tapTempoEnabled
     setTapTempoEnabled(enabled)
@@ -562,6 +972,10 @@ export default function App() {
     setTransitionInProgress(true)
   }
 
+  function onChangeSliderRange(name: string, range: SliderRange | null) {
+    post('ChangeSliderRange', [name, range])
+  }
+
   function onClickRandomizeSingleControl(name: string) {
     post(
       'Randomize',
@@ -599,6 +1013,20 @@ export default function App() {
     post('SendMidi')
   }
 
+  function onClickRecordValues() {
+    post('RecordValues', isRecordingValues ? null : [])
+  }
+
+  function onClickSysExBackup() {
+    const value = !isRecordingSysEx
+    setIsRecordingSysEx(value)
+    post('SysExBackup', value)
+  }
+
+  function onClickSysExRestore() {
+    post('SysExRestore')
+  }
+
   function onDeleteMappings() {
     Object.keys(mappings).forEach((key) => {
       post('RemoveMapping', key)
@@ -606,6 +1034,10 @@ export default function App() {
     setMappings({})
   }
 
+  function onLearnMidiAction(action: MidiAction | null) {
+    post('LearnMidiAction', action)
+  }
+
   function onOpenOsDir(osDir: OsDir) {
     post('OpenOsDir', osDir)
   }
@@ -634,10 +1066,18 @@ export default function App() {
     post('RemoveMapping', name)
   }
 
+  function onRemoveMidiAction(action: MidiAction) {
+    post('RemoveMidiAction', action)
+  }
+
   function onReset() {
     post('Reset')
   }
 
+  function onResetTimingOverrides() {
+    post('ResetTimingOverrides')
+  }
+
   function onSave() {
     post('Save', exclusions)
     setControlsLastSaved(controls)
@@ -647,23 +1087,63 @@ export default function App() {
     post('CurrentlyMapping', name)
   }
 
+  function onChangeSnapshotPhase(value: boolean) {
+    setSnapshotPhase(value)
+    post('SnapshotPhase', value)
+  }
+
+  function onCopySnapshotValues(from: string, to: string, names: string[]) {
+    post('SnapshotCopyValues', { from, to, names })
+    if (snapshotDiff) {
+      post('RequestSnapshotDiff', [snapshotDiff.a, snapshotDiff.b])
+    }
+  }
+
   function onDeleteSnapshot(slot: string) {
     setSnapshots(snapshots.filter((s) => s !== slot))
+    if (snapshotDiff?.a === slot || snapshotDiff?.b === slot) {
+      setSnapshotDiff(null)
+    }
     post('SnapshotDelete', slot)
   }
 
+  function onDiffSnapshots(a: string, b: string) {
+    post('RequestSnapshotDiff', [a, b])
+  }
+
   function onLoadSnapshot(slot: string) {
     post('SnapshotRecall', slot)
     setTransitionInProgress(true)
   }
 
+  function onLoadSnapshotOnly(slot: string, selection: string[]) {
+    post('SnapshotRecallOnly', [slot, selection])
+  }
+
   function onSaveSnapshot(slot: string) {
     setSnapshots(snapshots.concat(slot).slice().sort())
     post('SnapshotStore', slot)
   }
 
-  function onSwitchSketch(sketchName: string) {
-    post('SwitchSketch', sketchName)
+  function onSwitchSketch(name: string) {
+    if (prewarm && name !== sketchName) {
+      setCuedSketch(name)
+      post('PrewarmSketch', name)
+    } else {
+      post('SwitchSketch', name)
+    }
+  }
+
+  function onSwitchToCuedSketch() {
+    if (cuedSketch) {
+      post('SwitchSketch', cuedSketch)
+    }
+  }
+
+  function onToggleFreeze() {
+    const value = !frozen
+    setFrozen(value)
+    post('Frozen', value)
   }
 
   function onTogglePlay() {
@@ -684,23 +1164,37 @@ export default function App() {
     <div id="app">
       <Header
         fps={fps}
+        autoBpmEnabled={autoBpmEnabled}
         bpm={bpm}
+        countIn={countIn}
+        cuedSketch={cuedSketch}
+        encodingProgress={encodingProgress}
+        favorite={favorites.includes(sketchName)}
+        frozen={frozen}
         isEncoding={isEncoding}
         isQueued={isQueued}
         isRecording={isRecording}
         paused={paused}
         perfMode={perfMode}
+        profile={profile}
+        profiles={profiles}
         showExclusions={showExclusions}
         showSnapshots={showSnapshots}
+        sketchDescription={getSketchDescription()}
+        sketchFilter={sketchFilter}
+        sketchFilters={getSketchFilters()}
         sketchName={sketchName}
-        sketchNames={sketchNames}
+        sketchNames={getFilteredSketchNames()}
         tapTempoEnabled={tapTempoEnabled}
         transitionTime={transitionTime}
         view={view}
         onAdvance={onAdvance}
         onCaptureFrame={onCaptureFrame}
+        onChangeAutoBpmEnabled={onChangeAutoBpmEnabled}
         onChangePerfMode={onChangePerfMode}
+        onChangeProfile={onChangeProfile}
         onChangeTapTempoEnabled={onChangeTapTempoEnabled}
+        onChangeSketchFilter={setSketchFilter}
         onChangeTransitionTime={onChangeTransitionTime}
         onChangeView={onChangeView}
         onClearBuffer={onClearBuffer}
@@ -711,9 +1205,14 @@ export default function App() {
         onRecord={onRecord}
         onSave={onSave}
         onSwitchSketch={onSwitchSketch}
+        onSwitchToCuedSketch={onSwitchToCuedSketch}
         onToggleExclusions={() => {
           setShowExclusions(!showExclusions)
         }}
+        onToggleFavorite={() => {
+          post('ToggleFavorite', sketchName)
+        }}
+        onToggleFreeze={onToggleFreeze}
         onTogglePlay={onTogglePlay}
         onToggleSnapshots={() => {
           setShowSnapshots(!showSnapshots)
@@ -723,33 +1222,80 @@ export default function App() {
         {view === View.Settings ? (
           <Settings
             audioDevice={audioDevice}
+            audioDeviceStatus={audioDeviceStatus}
             audioDevices={audioDevices}
+            bpm={bpm}
+            depthDevice={depthDevice}
+            depthDevices={depthDevices}
             hrcc={hrcc}
             imagesDir={imagesDir}
+            isRecording={isRecording}
+            isRecordingSysEx={isRecordingSysEx}
+            isRecordingValues={isRecordingValues}
+            latencyOffsets={latencyOffsets}
             mappings={mappings}
             mappingsEnabled={mappingsEnabled}
+            midiActions={midiActions}
             midiClockPort={midiClockPort}
             midiInputPort={midiInputPort}
             midiInputPorts={midiInputPorts}
             midiOutputPort={midiOutputPort}
             midiOutputPorts={midiOutputPorts}
             oscPort={oscPort}
+            outputWarps={outputWarps}
+            prewarm={prewarm}
+            recordingSettings={recordingSettings}
+            setList={setList}
+            sketchFps={sketchFps}
+            sketchMsaaSamples={sketchMsaaSamples}
+            sketchName={sketchName}
+            sketchNames={sketchNames}
             sliderNames={getSliderNames()}
+            stereo={stereo}
+            syncRole={syncRole}
             userDataDir={userDataDir}
             videosDir={videosDir}
             onChangeAudioDevice={onChangeAudioDevice}
+            onChangeBpm={onChangeBpm}
+            onChangeDepthDevice={onChangeDepthDevice}
             onChangeFolder={onChangeFolder}
+            onChangeFps={onChangeFps}
+            onChangeLatencyOffsets={onChangeLatencyOffsets}
             onChangeHrcc={onChangeHrcc}
             onChangeMappingsEnabled={onChangeMappingsEnabled}
             onChangeMidiClockPort={onChangeMidiClockPort}
             onChangeMidiInputPort={onChangeMidiInputPort}
             onChangeMidiOutputPort={onChangeMidiOutputPort}
+            onChangeMsaaSamples={onChangeMsaaSamples}
             onChangeOscPort={onChangeOscPort}
+            onChangeOutputWarps={onChangeOutputWarps}
+            onChangePrewarm={onChangePrewarm}
+            onChangeRecordingSettings={onChangeRecordingSettings}
+            onChangeSetList={(setList) => {
+              post('SetList', setList)
+            }}
+            onChangeStereo={onChangeStereo}
+            onChangeSyncRole={onChangeSyncRole}
             onClickSend={onClickSendMidi}
+            onClickRecordValues={onClickRecordValues}
+            onClickSysExBackup={onClickSysExBackup}
+            onClickSysExRestore={onClickSysExRestore}
             onDeleteMappings={onDeleteMappings}
+            onLearnMidiAction={onLearnMidiAction}
             onOpenOsDir={onOpenOsDir}
             onRemoveMapping={onRemoveMapping}
+            onRemoveMidiAction={onRemoveMidiAction}
+            onResetTimingOverrides={onResetTimingOverrides}
             onSetCurrentlyMapping={onSetCurrentlyMapping}
+            onSetListGoTo={(position) => {
+              post('SetListGoTo', position)
+            }}
+            onSetListNext={() => {
+              post('SetListNext')
+            }}
+            onSetListPrevious={() => {
+              post('SetListPrevious')
+            }}
           />
         ) : (
           <Controls
@@ -763,19 +1309,42 @@ export default function App() {
             singleTransitionControlName={singleTransitionControlName}
             transitionInProgress={transitionInProgress}
             onChange={onChangeControl}
+            onChangeRange={onChangeSliderRange}
             onClickRandomize={onClickRandomizeSingleControl}
             onClickRevert={onClickRevert}
             onToggleExclusion={onToggleExclusion}
+            snapshotDiff={snapshotDiff}
+            snapshotPhase={snapshotPhase}
             snapshots={snapshots}
+            onChangeSnapshotPhase={onChangeSnapshotPhase}
+            onCloseSnapshotDiff={() => {
+              setSnapshotDiff(null)
+            }}
+            onCopySnapshotValues={onCopySnapshotValues}
             onDeleteSnapshot={onDeleteSnapshot}
+            onDiffSnapshots={onDiffSnapshots}
             onLoadSnapshot={onLoadSnapshot}
+            onLoadSnapshotOnly={onLoadSnapshotOnly}
             onSaveSnapshot={onSaveSnapshot}
           />
         )}
       </main>
+      {quitTakes !== null && (
+        <QuitDialog
+          takes={quitTakes}
+          onDismiss={() => {
+            setQuitTakes(null)
+          }}
+          onQuit={(pending) => {
+            setQuitTakes(null)
+            post('Shutdown', pending)
+          }}
+        />
+      )}
       <footer>
         <Console
           alertText={alertText}
+          scriptError={scriptError}
           showHelp={showHelp}
           onToggleShowHelp={setShowHelp}
         />
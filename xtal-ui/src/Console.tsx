@@ -1,15 +1,18 @@
 import { useEffect, useState } from 'react'
 import { Help } from './Help'
 import IconButton from './IconButton'
+import { ScriptError } from './types'
 
 type Props = {
   alertText: string
+  scriptError: ScriptError | null
   showHelp: boolean
   onToggleShowHelp: (negatedShowHelp: boolean) => void
 }
 
 export default function Console({
   alertText,
+  scriptError,
   showHelp,
   onToggleShowHelp,
 }: Props) {
@@ -19,15 +22,20 @@ export default function Console({
     function onMouseOver(e: MouseEvent) {
       let currentTarget = e.target as HTMLElement | null
       let helpId = null
+      // Set on controls whose script declares `help`
+      let text = null
 
-      while (currentTarget && !helpId) {
+      while (currentTarget && !helpId && !text) {
         helpId = currentTarget.dataset.helpId
-        if (!helpId) {
+        text = currentTarget.dataset.helpText
+        if (!helpId && !text) {
           currentTarget = currentTarget.parentElement
         }
       }
 
-      if (helpId && helpId in Help) {
+      if (text) {
+        setHelpText(text)
+      } else if (helpId && helpId in Help) {
         const text = Help[helpId as keyof typeof Help]
         setHelpText(text)
       } else {
@@ -53,6 +61,21 @@ export default function Console({
           onToggleShowHelp(!showHelp)
         }}
       />
+      {!showHelp && scriptError && (
+        <div
+          className="script-error"
+          title="Controls keep the last good version of the script until this is fixed"
+        >
+          <div>
+            {scriptError.file}
+            {scriptError.line !== null &&
+              `:${scriptError.line}:${scriptError.column}`}
+            {' – '}
+            {scriptError.message}
+          </div>
+          {scriptError.snippet && <pre>{scriptError.snippet}</pre>}
+        </div>
+      )}
       {showHelp ? helpText : alertText}
     </div>
   )
@@ -1,10 +1,20 @@
-import { ReactNode, useEffect, useState } from 'react'
+import { Fragment, ReactNode, useEffect, useState } from 'react'
 import NumberBox from '@lokua/number-box'
 import ExcludedIcon from '@material-symbols/svg-400/outlined/keep.svg?react'
 import MappedIcon from '@material-symbols/svg-400/outlined/app_badging.svg?react'
 import clsx from 'clsx/lite'
 
-import { Bypassed, Control, ControlValue, Exclusions, Mappings } from './types'
+import {
+  Bypassed,
+  Control,
+  ControlInput,
+  ControlValue,
+  Exclusions,
+  InputSource,
+  Mappings,
+  SliderRange,
+  SnapshotDiff,
+} from './types'
 
 import CheckboxInput from './Checkbox'
 import Select from './Select'
@@ -22,6 +32,22 @@ const ExcludedIndicator = () => (
   </span>
 )
 
+function toShown(c: Control, value: number) {
+  if (!c.display) {
+    return value
+  }
+  const shown = value * c.display.multiplier
+  return c.display.decimals === null
+    ? shown
+    : Number(shown.toFixed(c.display.decimals))
+}
+
+function fromShown(c: Control, shown: number) {
+  return c.display && c.display.multiplier !== 0
+    ? shown / c.display.multiplier
+    : shown
+}
+
 const MappedIndicator = () => (
   <span
     className="indicator-icon"
@@ -31,6 +57,80 @@ const MappedIndicator = () => (
   </span>
 )
 
+const inputNames: Record<InputSource, string> = {
+  Ui: 'the UI',
+  Midi: 'MIDI',
+  Osc: 'OSC',
+}
+
+function InputIndicator({ input }: { input: ControlInput }) {
+  const policy =
+    input.arbitration === 'latest' ? 'latest input wins' : 'fixed priority'
+  const last = input.last ? `; last moved by ${inputNames[input.last]}` : ''
+
+  return (
+    <span
+      data-help-id="InputSource"
+      className="indicator-icon input-indicator"
+      title={`Following ${inputNames[input.active]} (${policy})${last}`}
+    >
+      {input.active.charAt(0)}
+    </span>
+  )
+}
+
+function RangeEditor({
+  control,
+  onChange,
+  onClose,
+}: {
+  control: Control
+  onChange: (range: SliderRange | null) => void
+  onClose: () => void
+}) {
+  const [range, setRange] = useState<SliderRange>({
+    min: control.min,
+    max: control.max,
+    step: control.step,
+  })
+
+  return (
+    <fieldset className="range-editor" data-help-id="SliderRange">
+      {(['min', 'max', 'step'] as const).map((key) => (
+        <NumberBox
+          key={key}
+          title={key}
+          className="number-box"
+          value={range[key]}
+          min={key === 'step' ? 0 : -100_000}
+          max={100_000}
+          step={key === 'step' ? 0.0001 : control.step}
+          onChange={(value) => {
+            setRange({ ...range, [key]: value })
+          }}
+        />
+      ))}
+      <button
+        disabled={range.min >= range.max || range.step <= 0}
+        onClick={() => {
+          onChange(range)
+          onClose()
+        }}
+      >
+        Apply
+      </button>
+      <button
+        onClick={() => {
+          onChange(null)
+          onClose()
+        }}
+      >
+        Reset
+      </button>
+    </fieldset>
+  )
+}
+
 export type Props = {
   bypassed: Bypassed
   controls: Control[]
@@ -40,13 +140,21 @@ export type Props = {
   showExclusions: boolean
   showSnapshots: boolean
   singleTransitionControlName: string
+  snapshotDiff: SnapshotDiff | null
+  snapshotPhase: boolean
   snapshots: string[]
   transitionInProgress: boolean
   onChange: (control: Control, value: ControlValue) => void
+  onChangeRange: (name: string, range: SliderRange | null) => void
   onClickRandomize: (name: string) => void
   onClickRevert: (control: Control) => void
+  onChangeSnapshotPhase: (snapshotPhase: boolean) => void
+  onCloseSnapshotDiff: () => void
+  onCopySnapshotValues: (from: string, to: string, names: string[]) => void
   onDeleteSnapshot: (snapshot: string) => void
+  onDiffSnapshots: (a: string, b: string) => void
   onLoadSnapshot: (snapshot: string) => void
+  onLoadSnapshotOnly: (snapshot: string, selection: string[]) => void
   onToggleExclusion: (name: string) => void
   onSaveSnapshot: (snapshot: string) => void
 }
@@ -60,17 +168,26 @@ export default function Controls({
   showExclusions,
   showSnapshots,
   singleTransitionControlName,
+  snapshotDiff,
+  snapshotPhase,
   snapshots,
   transitionInProgress,
   onChange,
+  onChangeRange,
   onClickRandomize,
   onClickRevert,
+  onChangeSnapshotPhase,
+  onCloseSnapshotDiff,
+  onCopySnapshotValues,
   onDeleteSnapshot,
+  onDiffSnapshots,
   onLoadSnapshot,
+  onLoadSnapshotOnly,
   onToggleExclusion,
   onSaveSnapshot,
 }: Props) {
   const [platformModPressed, setPlatformModPressed] = useState(false)
+  const [editingRange, setEditingRange] = useState('')
   const { localSettings } = useLocalSettings()
 
   useEffect(() => {
@@ -121,9 +238,17 @@ export default function Controls({
       {showSnapshots && (
         <header>
           <Snapshots
+            diff={snapshotDiff}
+            groups={[...new Set(controls.flatMap((c) => c.groups))]}
+            snapshotPhase={snapshotPhase}
             snapshots={snapshots}
+            onChangeSnapshotPhase={onChangeSnapshotPhase}
+            onCloseDiff={onCloseSnapshotDiff}
+            onCopyValues={onCopySnapshotValues}
             onDelete={onDeleteSnapshot}
+            onDiff={onDiffSnapshots}
             onLoad={onLoadSnapshot}
+            onLoadOnly={onLoadSnapshotOnly}
             onSave={onSaveSnapshot}
           />
         </header>
@@ -134,7 +259,11 @@ export default function Controls({
             const [excluded, nodeWithCheckbox] = excludedAndNode(c.name)
 
             return (
-              <div key={c.name} className={controlClass(c.name, excluded)}>
+              <div
+                key={c.name}
+                className={controlClass(c.name, excluded)}
+                data-help-text={c.help ?? undefined}
+              >
                 {nodeWithCheckbox}
                 <fieldset>
                   <CheckboxInput
@@ -148,7 +277,7 @@ export default function Controls({
                   />
                   <label htmlFor={c.name}>
                     {excluded && <ExcludedIndicator />}
-                    <span>{c.name}</span>
+                    <span title={c.help ?? undefined}>{c.name}</span>
                   </label>
                 </fieldset>
               </div>
@@ -158,78 +287,115 @@ export default function Controls({
           if (c.kind === 'Slider') {
             const isBypassed = c.name in bypassed
             const isMapped = mappingsEnabled && c.name in mappings
-            const disabled = c.disabled || isBypassed || isMapped
+            // With `latest` arbitration the UI can take over from a mapping
+            const disabled =
+              c.disabled ||
+              isBypassed ||
+              (isMapped && c.input?.arbitration !== 'latest')
+            const hasIndicator = isMapped || c.input !== null
             const [excluded, nodeWithCheckbox] = excludedAndNode(c.name)
 
             return (
-              <div key={c.name} className={controlClass(c.name, excluded)}>
-                {nodeWithCheckbox}
-                <fieldset key={c.name}>
-                  <input
-                    id={c.name}
-                    type="range"
-                    value={c.value as number}
-                    min={c.min}
-                    max={c.max}
-                    step={c.step}
-                    disabled={disabled}
-                    onChange={(e) => {
-                      onChange(c, e.currentTarget.valueAsNumber)
-                    }}
-                  />
-                  <NumberBox
-                    data-help-id="NumberBox"
-                    className="number-box"
-                    value={c.value as number}
-                    min={c.min}
-                    max={c.max}
-                    step={c.step}
-                    disabled={disabled}
-                    onChange={(value) => {
-                      onChange(c, value)
-                    }}
-                  />
-                  <label
-                    data-help-id="ControlLabel"
-                    htmlFor={c.name}
-                    className={clsx(!c.disabled && !isBypassed && 'clickable')}
-                    onClick={() => {
-                      if (platformModPressed) {
-                        onClickRevert(c)
-                      } else {
-                        onClickRandomize(c.name)
-                      }
-                    }}
-                  >
-                    {excluded && <ExcludedIndicator />}
-                    {isMapped && <MappedIndicator />}
-                    <span
-                      title={
-                        isBypassed
-                          ? 'This control is currently bypassed/overwritten in a Control Script'
-                          : ''
-                      }
-                      style={{
-                        width:
-                          (showExclusions ? -1.625 : 0) +
-                          (excluded ? -0.875 : 0) +
-                          (isMapped ? -0.875 : 0) +
-                          { 16: 9.75, 17: 8.5, 18: 6.5 }[
-                            localSettings.fontSize
-                          ] +
-                          'rem',
-                        textDecoration: isBypassed ? 'line-through' : 'none',
+              <Fragment key={c.name}>
+                <div
+                  className={controlClass(c.name, excluded)}
+                  data-help-text={c.help ?? undefined}
+                >
+                  {nodeWithCheckbox}
+                  <fieldset key={c.name}>
+                    <input
+                      id={c.name}
+                      type="range"
+                      value={c.value as number}
+                      min={c.min}
+                      max={c.max}
+                      step={c.step}
+                      disabled={disabled}
+                      onChange={(e) => {
+                        onChange(c, e.currentTarget.valueAsNumber)
+                      }}
+                    />
+                    <NumberBox
+                      data-help-id="NumberBox"
+                      className="number-box"
+                      value={toShown(c, c.value as number)}
+                      min={toShown(c, c.min)}
+                      max={toShown(c, c.max)}
+                      step={c.step * Math.abs(c.display?.multiplier ?? 1)}
+                      disabled={disabled}
+                      onChange={(value) => {
+                        onChange(c, fromShown(c, value))
+                      }}
+                    />
+                    {c.display?.unit && (
+                      <span className="unit">{c.display.unit}</span>
+                    )}
+                    <label
+                      data-help-id="ControlLabel"
+                      htmlFor={c.name}
+                      className={clsx(
+                        !c.disabled && !isBypassed && 'clickable'
+                      )}
+                      onClick={() => {
+                        if (platformModPressed) {
+                          onClickRevert(c)
+                        } else {
+                          onClickRandomize(c.name)
+                        }
+                      }}
+                      onContextMenu={(e) => {
+                        e.preventDefault()
+                        setEditingRange(editingRange === c.name ? '' : c.name)
                       }}
                     >
+                      {excluded && <ExcludedIndicator />}
+                      {c.input ? (
+                        <InputIndicator input={c.input} />
+                      ) : (
+                        isMapped && <MappedIndicator />
+                      )}
                       <span
-                        className={clsx('text', platformModPressed && 'revert')}
+                        title={
+                          isBypassed
+                            ? 'This control is currently bypassed/overwritten in a Control Script'
+                            : (c.help ?? '')
+                        }
+                        style={{
+                          width:
+                            (showExclusions ? -1.625 : 0) +
+                            (excluded ? -0.875 : 0) +
+                            (hasIndicator ? -0.875 : 0) +
+                            { 16: 9.75, 17: 8.5, 18: 6.5 }[
+                              localSettings.fontSize
+                            ] +
+                            'rem',
+                          textDecoration: isBypassed ? 'line-through' : 'none',
+                        }}
                       >
-                        {c.name}
+                        <span
+                          className={clsx(
+                            'text',
+                            platformModPressed && 'revert'
+                          )}
+                        >
+                          {c.name}
+                        </span>
                       </span>
-                    </span>
-                  </label>
-                </fieldset>
-              </div>
+                    </label>
+                  </fieldset>
+                </div>
+                {editingRange === c.name && (
+                  <RangeEditor
+                    control={c}
+                    onChange={(range) => {
+                      onChangeRange(c.name, range)
+                    }}
+                    onClose={() => {
+                      setEditingRange('')
+                    }}
+                  />
+                )}
+              </Fragment>
             )
           }
 
@@ -237,7 +403,11 @@ export default function Controls({
             const [excluded, nodeWithCheckbox] = excludedAndNode(c.name)
 
             return (
-              <div key={c.name} className={controlClass(c.name, excluded)}>
+              <div
+                key={c.name}
+                className={controlClass(c.name, excluded)}
+                data-help-text={c.help ?? undefined}
+              >
                 {nodeWithCheckbox}
                 <fieldset key={c.name}>
                   <Select
@@ -258,7 +428,9 @@ export default function Controls({
                     }}
                   >
                     {excluded && <ExcludedIndicator />}
-                    <span className="text">{c.name}</span>
+                    <span className="text" title={c.help ?? undefined}>
+                      {c.name}
+                    </span>
                   </label>
                 </fieldset>
               </div>
@@ -1,25 +1,36 @@
 import clsx from 'clsx/lite'
 
-import type { noop } from './types'
+import type { EncodingProgress, noop } from './types'
 import { View } from './types'
 
 import Select from './Select'
 import Separator, { VerticalSeparator } from './Separator'
 import IconButton from './IconButton'
 
-const transitionTimes = [32, 24, 16, 12, 8, 6, 4, 3, 2, 1.5, 1, 0.75, 5, 0.25]
+const transitionTimes = [32, 24, 16, 12, 8, 6, 4, 3, 2, 1.5, 1, 0.75, 0.5, 0.25]
 type TransitionTime = (typeof transitionTimes)[number]
 
 type HeaderProps = {
+  autoBpmEnabled: boolean
   bpm: number
+  countIn: number | null
+  cuedSketch: string
+  encodingProgress: EncodingProgress | null
+  favorite: boolean
   fps: number
+  frozen: boolean
   isEncoding: boolean
   isQueued: boolean
   isRecording: boolean
   paused: boolean
   perfMode: boolean
+  profile: string
+  profiles: string[]
   showExclusions: boolean
   showSnapshots: boolean
+  sketchDescription: string
+  sketchFilter: string
+  sketchFilters: string[]
   sketchName: string
   sketchNames: string[]
   tapTempoEnabled: boolean
@@ -27,7 +38,10 @@ type HeaderProps = {
   view: View
   onAdvance: noop
   onCaptureFrame: noop
+  onChangeAutoBpmEnabled: noop
   onChangePerfMode: noop
+  onChangeProfile: (profile: string) => void
+  onChangeSketchFilter: (filter: string) => void
   onChangeTapTempoEnabled: noop
   onChangeTransitionTime: (transitionTime: TransitionTime) => void
   onChangeView: noop
@@ -39,21 +53,35 @@ type HeaderProps = {
   onReset: noop
   onSave: noop
   onSwitchSketch: (sketchName: string) => void
+  onSwitchToCuedSketch: noop
   onToggleExclusions: noop
+  onToggleFavorite: noop
+  onToggleFreeze: noop
   onTogglePlay: noop
   onToggleSnapshots: noop
 }
 
 export default function Header({
+  autoBpmEnabled,
   bpm,
+  countIn,
+  cuedSketch,
+  encodingProgress,
+  favorite,
   fps,
+  frozen,
   isEncoding,
   isQueued,
   isRecording,
   paused,
   perfMode,
+  profile,
+  profiles,
   showExclusions,
   showSnapshots,
+  sketchDescription,
+  sketchFilter,
+  sketchFilters,
   sketchName,
   sketchNames,
   tapTempoEnabled,
@@ -61,7 +89,10 @@ export default function Header({
   view,
   onAdvance,
   onCaptureFrame,
+  onChangeAutoBpmEnabled,
   onChangePerfMode,
+  onChangeProfile,
+  onChangeSketchFilter,
   onChangeTapTempoEnabled,
   onChangeTransitionTime,
   onChangeView,
@@ -73,7 +104,10 @@ export default function Header({
   onReset,
   onSave,
   onSwitchSketch,
+  onSwitchToCuedSketch,
   onToggleExclusions,
+  onToggleFavorite,
+  onToggleFreeze,
   onTogglePlay,
   onToggleSnapshots,
 }: HeaderProps) {
@@ -92,6 +126,13 @@ export default function Header({
           disabled={!paused}
           onClick={onAdvance}
         />
+        <IconButton
+          data-help-id="Freeze"
+          name="Freeze"
+          on={frozen}
+          isToggle
+          onClick={onToggleFreeze}
+        />
         <IconButton data-help-id="Reset" name="Reset" onClick={onReset} />
 
         <VerticalSeparator />
@@ -106,7 +147,7 @@ export default function Header({
         <IconButton
           data-help-id="Queue"
           name={isQueued ? 'Queued' : 'Queue'}
-          disabled={isRecording || isEncoding}
+          disabled={isRecording}
           on={isQueued}
           isToggle
           onClick={onQueueRecord}
@@ -114,7 +155,6 @@ export default function Header({
         <IconButton
           data-help-id="Record"
           name={isRecording ? 'StopRecording' : 'Record'}
-          disabled={isEncoding}
           className={clsx(
             isRecording && 'is-recording',
             isEncoding && 'is-encoding',
@@ -123,6 +163,20 @@ export default function Header({
           isToggle
           onClick={onRecord}
         />
+        {countIn !== null && (
+          <div data-help-id="CountIn" className="meter">
+            Count-in: <span className="meter-value">{countIn}</span>
+          </div>
+        )}
+        {encodingProgress && (
+          <div data-help-id="Encoding" className="meter">
+            Take {encodingProgress.take}:{' '}
+            <span className="meter-value">
+              {Math.round(encodingProgress.progress * 100)}%
+            </span>
+            {encodingProgress.queued > 0 && ` +${encodingProgress.queued}`}
+          </div>
+        )}
 
         <VerticalSeparator />
 
@@ -145,13 +199,45 @@ export default function Header({
       <Separator style={{ margin: '2px 0' }} />
 
       <section>
+        <Select
+          data-help-id="SketchFilter"
+          value={sketchFilter}
+          options={sketchFilters}
+          onChange={onChangeSketchFilter}
+          style={{ maxWidth: '80px' }}
+        />
         <Select
           data-help-id="Sketch"
-          value={sketchName}
+          title={sketchDescription}
+          value={cuedSketch || sketchName}
           options={sketchNames}
           onChange={onSwitchSketch}
           style={{ maxWidth: '164px' }}
         />
+        <IconButton
+          data-help-id="Favorite"
+          name={favorite ? 'Favorited' : 'Favorite'}
+          isToggle
+          on={favorite}
+          onClick={onToggleFavorite}
+        />
+        {cuedSketch && (
+          <IconButton
+            data-help-id="Go"
+            name="Go"
+            onClick={onSwitchToCuedSketch}
+          />
+        )}
+        {profiles.length > 0 && (
+          <Select
+            data-help-id="Profile"
+            title="Control script profile"
+            value={profile}
+            options={profiles}
+            onChange={onChangeProfile}
+            style={{ maxWidth: '72px' }}
+          />
+        )}
 
         <IconButton data-help-id="Reload" name="Reload" onClick={onReload} />
 
@@ -175,6 +261,14 @@ export default function Header({
           on={tapTempoEnabled}
           onClick={onChangeTapTempoEnabled}
         />
+        <IconButton
+          data-help-id="AutoBpm"
+          title="Auto BPM"
+          name="AutoBpm"
+          isToggle
+          on={autoBpmEnabled}
+          onClick={onChangeAutoBpmEnabled}
+        />
 
         <VerticalSeparator />
 
@@ -7,31 +7,85 @@ export const Help = {
     `When the [Play/Pause] toggle is set to [Pause], allows manually advancing 
     frames (Shortcut: [${mod} A])`
   ),
-  Audio: 'The Audio input device used for audio controls',
+  Audio: format(
+    `The Audio input device used for audio controls. If the device disconnects
+    it will reconnect automatically once it's available again`
+  ),
+  AutoBpm: format(
+    `Enable/disable auto BPM. When enabled, beats are detected on the [Audio]
+    device and used to set the BPM and keep beats in phase with the music; the
+    first detected beat starts a new bar. For when there's no MIDI clock`
+  ),
   Clear: format(
     `Clear any alpha blending or "fade trails" from frame persistence. Requires 
     your sketch is using the clear_color attribute via sketch_components macro`
   ),
   ControlLabel: format(
     `Clicking this label will randomize this parameter. [${mod} Click] will
-    revert it to its last saved value.`
+    revert it to its last saved value. Right clicking a slider's label edits
+    its min, max, and step.`
   ),
+  CountIn: format(`
+    Beats counted in after pressing Record before capture starts. Each beat
+    is shown in the header and sent as a MIDI click (GM side stick) to the
+    MIDI Output Port
+  `),
   DeleteMappings: 'Delete all MIDI Mappings',
+  DepthDevice: format(
+    `The depth camera (e.g. Intel RealSense) used by sketches with a
+    DepthCamera. Sketches reconnect to the new device on their next frame`
+  ),
   DisableMappings: 'Disable/Enable MIDI Mappings',
+  Encoding: format(`
+    Progress of the take being encoded and how many more are queued behind
+    it. Recording can continue while earlier takes encode
+  `),
   Exclusions: format(
     `Exclusions: select controls to exclude from Randomization (Shortcut: E)`
   ),
   Fps: 'The effective framerate over a 1 second running average',
+  Freeze: format(`
+    Freeze Toggle (Shortcut: [Shift P]). Holds the current frame on screen while
+    the clock keeps running, so unfreezing jumps to wherever the sketch would
+    be by now. Useful for stutter effects.
+  `),
+  Favorite: format(`
+    Add or remove the current sketch from your favorites. Choose [Favorites]
+    in the sketch filter to only list favorite sketches
+  `),
+  Go: format(`
+    Switch to the sketch cued in the sketch chooser. Only shown when
+    [Pre-warm sketches] is enabled in Settings
+  `),
   Hrcc: format(`
     Enable high resolution (14bit) MIDI for CCs 0-31 (requires support 
     from your MIDI device)
   `),
-  Image: `Capture PNG to disk (Shortcut: [${mod} I])`,
+  Image: format(`
+    Capture PNG to disk (Shortcut: [${mod} I]). [Shift ${mod} I] captures 9
+    frames one beat apart onto a single contact sheet image
+  `),
   ImagesDir: `The directory where image captures will be saved`,
+  InputSource: format(`
+    The input this slider follows: M for MIDI, O for OSC, or U for the UI.
+    Hover for which input last moved it. Set with the slider's
+    \`arbitration\` field in the Control Script
+  `),
+  Latency: format(
+    `Delay incoming MIDI, OSC, or audio control data by milliseconds or beats
+    so motion lines up with what the audience hears, e.g. when audio reaches
+    the room through a mixer or PA with its own latency`
+  ),
   Mappings: format(`
     Mappings: allows mapping of external MIDI CCs to UI sliders, aka
     "MIDI Learn". Mappings are saved with the sketch when you click [Save]. 
   `),
+  MidiActions: format(`
+    MIDI Actions: map external MIDI CCs to runtime functions that apply to
+    every sketch. Click a button then move a control to learn it. Transition
+    Time follows a knob or fader and is sent back out of the MIDI Output Port;
+    the others fire when a button crosses the halfway point.
+  `),
   MidiClockPort:
     "The MIDI port used to sync all Xtal's frame counter and animations",
   MidiInputPort:
@@ -47,7 +101,8 @@ export const Help = {
   `),
   OscPort: 'The OSC port Xtal will use for OSC controls',
   Play: format(`
-    Play/Pause Toggle (Shortcut: [P]). When Pause is engaged, use the [Advance]
+    Play/Pause Toggle (Shortcut: [P]). Pausing stops the clock and resuming
+    continues from the paused frame. When Pause is engaged, use the [Advance]
     button or [${mod} A] to manually advance frames.
   `),
   Perf: format(
@@ -57,36 +112,152 @@ export const Help = {
     you likely will fullsize the screen and want to keep it that way when 
     switching sketches`
   ),
+  PreRoll: format(`
+    Beats captured before MIDI Start when recording is queued so the first
+    downbeat is never missed. The downbeat is marked "Bar 1" in the take's
+    sidecar for trimming later
+  `),
+  Prewarm: format(`
+    When enabled, choosing a sketch in the sketch chooser builds it between
+    frames (cueing it) instead of switching right away. The current sketch
+    keeps running and keeps its MIDI and OSC inputs, though it may stutter
+    once while the cued one builds. Click [Go] to switch to the cued sketch
+    without the usual load hitch. Requires enough memory to hold two sketches
+    at once
+  `),
+  Profile: format(`
+    Switch between the control scripts the sketch declares as profiles, e.g. a
+    reduced "live" set of controls and a full "studio" one. Values carry over
+    wherever control names match
+  `),
   Queue: 'Queue recording to start upon receiving a MIDI Start message',
   Random: `Randomize all UI controls (Shortcut: [${mod} R])`,
   Reload: format(
     `Reload the current sketch back to its last saved state 
     (Shortcut: [Shift ${mod} R])`
   ),
-  Record: 'Start/Stop recording',
+  Record: format(`
+    Start/Stop recording. Each recording is a numbered take saved with a JSON
+    sidecar. Press [B] while recording to drop a marker at the current frame
+  `),
+  RecordValues: format(`
+    Writes the value of every control to a CSV file in the user data directory
+    once per frame until clicked again, for turning a performance into
+    automation afterwards
+  `),
   Reset: 'Reset the frame counter and all animations (Shortcut: [R])',
+  ResetTimingOverrides: format(`
+    Restore the FPS and BPM defined in the sketch's config. Click [Save] to
+    persist
+  `),
   Tap: `
     Enabled/disable tap tempo. When enabled, use the [Space] key to tap. 
     Note that keeping enabled will preserve the currently tapped-in tempo when 
-    switching sketches; disabling will always revert to a sketch's configured BPM.
+    switching sketches; disabling will always revert to a sketch's configured (or overridden) BPM.
   `,
   TransitionTime: 'Snapshot and Randomization transition time (in beats)',
   Save: format(`
-    Save UI control states and MIDI mappings for this sketch to disk 
+    Save UI control states, MIDI mappings, and FPS/BPM overrides for this 
+    sketch to disk 
     (Shortcut: [${mod} S])
   `),
   Send: 'Sends the state of all CCs to the MIDI output port',
+  SetList: format(`
+    An ordered list of sketches, each with an optional snapshot to recall and
+    the transition time to recall it over. Step through it with [N] and
+    [Shift N] or click an entry's number to jump to it. Saved to the user data
+    directory
+  `),
+  SetListNextCc: format(`
+    Step to the next set list entry whenever this MIDI channel and CC on the
+    MIDI input port goes from below 64 to 64 or above
+  `),
   Settings: 'Global settings and MIDI mappings',
-  Sketch: 'Sketch chooser',
+  Sketch: format(`
+    Sketch chooser. Hover to see the current sketch's description
+  `),
+  SketchBpm: format(`
+    Override the sketch's configured BPM. Overrides are saved with the sketch
+    when you click [Save]
+  `),
+  SketchFps: format(`
+    Override the sketch's configured target framerate. Overrides are saved
+    with the sketch when you click [Save]. Cannot be changed while recording
+  `),
+  SketchFilter: format(`
+    Narrow the sketch chooser to favorite sketches, recently used sketches,
+    or sketches with a given tag
+  `),
+  SketchMsaa: format(`
+    Multisample anti-aliasing sample count for shader (GpuState) pipelines.
+    1 disables MSAA. Resets to the sketch's configured value when switching
+    sketches
+  `),
   Snapshots: format(`
     Snapshot Editor: store and recall up to 10 snapshots (Shortcut: [S]).
     You can also save snapshots via [Shift Digit] and recall them
     via [${mod} Digit] without entering the editor.
   `),
+  SnapshotDiff: format(`
+    Compare two snapshots to see which controls differ and by how much. The
+    arrow buttons copy a single value from one snapshot into the other
+  `),
+  SnapshotPhase: format(`
+    Also store where every animation is when saving a snapshot. Recalling it
+    jumps those animations back to the same point so the scene looks the way
+    it did when stored, rather than continuing from the current beat
+  `),
+  SnapshotRecallGroup: format(`
+    Recall snapshots for only the controls in this group (declared with the
+    group field in the Control Script), leaving every other control alone
+  `),
+  Stereo: format(`
+    Render shader (GpuState) pipelines once per eye and combine them as a
+    red/cyan anaglyph or half side-by-side for 3D displays. Shaders read
+    which eye they're rendering via the xtal::eye include
+  `),
+  StereoSeparation: format(`
+    Distance between the eyes passed to shaders. Its scale depends on the
+    units of the sketch's camera
+  `),
+  SyncRole: format(`
+    Keeps several Xtal instances on the same network in lockstep. The Leader
+    broadcasts its frame count, BPM, current sketch, and snapshot recalls;
+    Followers mirror them. Use one Leader per installation
+  `),
+  SysExBackup: format(`
+    Records SysEx from the MIDI input port until clicked again, then saves it
+    for this sketch. Start it, then send a setup dump from your controller
+  `),
+  SysExRestore: format(`
+    Sends the SysEx saved for this sketch to the MIDI output port to restore
+    your controller's setup
+  `),
+  TimestampOverlay: format(`
+    Burns the sketch name, frame number, bar and beat, and BPM into recorded
+    videos for reviewing long captures against the music. The live output is
+    not affected
+  `),
   UserDataDir: format(`
     The directory where sketch data including control values, MIDI mappings, 
     and Snapshots will be saved to. It is recommended to choose a location that
     is source controlled.
   `),
   VideosDir: `The directory where encoded videos will be saved`,
+  WarpCorners: format(`
+    Drag the handles to where the corners of the frame should land on the
+    projection surface. The frame is pinned with a perspective correct warp
+  `),
+  WarpEnabled: format(`
+    Warps the main window's output for projection mapping
+  `),
+  WarpMesh: format(`
+    A 4x4 Bezier mesh applied before the corner pin. Drag its control points
+    to bend the frame onto curved or uneven surfaces
+  `),
+  WarpReset: `Restore the current venue's warp to the full, unwarped frame`,
+  WarpVenue: format(`
+    Warps are saved per venue in the user data directory. Type a new name to
+    start a fresh warp or pick an existing one to restore it
+  `),
 }
@@ -1,9 +1,14 @@
 import Advance from '@material-symbols/svg-400/outlined/skip_next.svg?react'
+import AutoBpm from '@material-symbols/svg-400/outlined/graphic_eq.svg?react'
 import Camera from '@material-symbols/svg-400/outlined/camera.svg?react'
 import Clear from '@material-symbols/svg-400/outlined/remove_from_queue.svg?react'
 import Close from '@material-symbols/svg-400/outlined/close.svg?react'
 import DeleteMappings from '@material-symbols/svg-400/outlined/delete.svg?react'
 import DisableMappings from '@material-symbols/svg-400/outlined/block.svg?react'
+import Go from '@material-symbols/svg-400/outlined/double_arrow.svg?react'
+import Favorite from '@material-symbols/svg-400/outlined/star.svg?react'
+import Favorited from '@material-symbols/svg-400/outlined/star-fill.svg?react'
+import Freeze from '@material-symbols/svg-400/outlined/ac_unit.svg?react'
 import Folder from '@material-symbols/svg-400/outlined/folder.svg?react'
 import Help from '@material-symbols/svg-400/outlined/help.svg?react'
 import Image from '@material-symbols/svg-400/outlined/image.svg?react'
@@ -30,13 +35,18 @@ import type { Override } from './types.ts'
 
 const icons = {
   Advance,
+  AutoBpm,
   Camera,
   Clear,
   Close,
   DeleteMappings,
   DisableMappings,
   Exclusions,
+  Favorite,
+  Favorited,
   Folder,
+  Freeze,
+  Go,
   Help,
   Image,
   Info,
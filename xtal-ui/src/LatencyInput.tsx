@@ -0,0 +1,43 @@
+import NumberBox from '@lokua/number-box'
+
+import { Latency } from './types'
+import Select from './Select'
+
+type Unit = 'ms' | 'beats'
+
+type Props = {
+  id: string
+  label: string
+  latency: Latency
+  onChange: (latency: Latency) => void
+}
+
+export default function LatencyInput({ id, label, latency, onChange }: Props) {
+  const unit: Unit = 'Beats' in latency ? 'beats' : 'ms'
+  const value = 'Beats' in latency ? latency.Beats : latency.Ms
+
+  function toLatency(value: number, unit: Unit): Latency {
+    return unit === 'beats' ? { Beats: value } : { Ms: value }
+  }
+
+  return (
+    <fieldset data-help-id="Latency">
+      <NumberBox
+        id={id}
+        className="number-box"
+        value={value}
+        min={0}
+        max={unit === 'beats' ? 4 : 1000}
+        step={unit === 'beats' ? 0.01 : 1}
+        onChange={(value) => onChange(toLatency(value, unit))}
+      />
+      <Select
+        id={`${id}-unit`}
+        value={unit}
+        options={['ms', 'beats']}
+        onChange={(unit) => onChange(toLatency(0, unit as Unit))}
+      />
+      <label htmlFor={id}>{label}</label>
+    </fieldset>
+  )
+}
@@ -0,0 +1,85 @@
+import React, { useEffect, useState } from 'react'
+import clsx from 'clsx/lite'
+
+import { MidiAction, MidiActions as MidiActionMappings } from './types'
+import IconButton from './IconButton'
+
+const actions: [MidiAction, string][] = [
+  ['TransitionTime', 'Transition Time'],
+  ['BpmUp', 'BPM +'],
+  ['BpmDown', 'BPM -'],
+  ['Tap', 'Tap'],
+]
+
+type Props = {
+  midiActions: MidiActionMappings
+  onLearn: (action: MidiAction | null) => void
+  onRemove: (action: MidiAction) => void
+}
+
+export default function MidiActions({ midiActions, onLearn, onRemove }: Props) {
+  const [learning, setLearning] = useState<MidiAction | null>(null)
+
+  // The parent stops listening once a CC arrives
+  useEffect(() => {
+    setLearning(null)
+  }, [midiActions])
+
+  useEffect(() => {
+    document.addEventListener('click', onOutsideClick)
+
+    return () => {
+      document.removeEventListener('click', onOutsideClick)
+    }
+
+    function onOutsideClick(e: MouseEvent) {
+      if (
+        learning &&
+        !(e.target as HTMLButtonElement)?.classList?.contains('map-button')
+      ) {
+        setLearning(null)
+        onLearn(null)
+      }
+    }
+  }, [learning, onLearn])
+
+  return (
+    <div id="midi-actions" data-help-id="MidiActions">
+      {actions.map(([action, label]) => {
+        const mapping = midiActions[action]
+        const isLearning = learning === action
+
+        return (
+          <React.Fragment key={action}>
+            <label>{mapping ? <b>{label}</b> : label}</label>
+            <span style={{ display: 'inline-flex' }}>
+              <button
+                className={clsx(
+                  'map-button',
+                  isLearning && 'mapping',
+                  !isLearning && !mapping && 'inactive'
+                )}
+                onClick={() => {
+                  if (!isLearning) {
+                    setLearning(action)
+                    onLearn(action)
+                  }
+                }}
+              >
+                {isLearning ? '...' : mapping ? mapping.join('/') : '—'}
+              </button>
+              {mapping && (
+                <IconButton
+                  name="Close"
+                  onClick={() => {
+                    onRemove(action)
+                  }}
+                />
+              )}
+            </span>
+          </React.Fragment>
+        )
+      })}
+    </div>
+  )
+}
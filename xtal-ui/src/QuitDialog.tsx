@@ -0,0 +1,43 @@
+import { PendingEncodes } from './types'
+
+type Props = {
+  takes: number
+  onDismiss: () => void
+  onQuit: (pending: PendingEncodes) => void
+}
+
+export default function QuitDialog({ takes, onDismiss, onQuit }: Props) {
+  return (
+    <div id="quit-dialog" onClick={onDismiss}>
+      <div
+        onClick={(e) => {
+          e.stopPropagation()
+        }}
+      >
+        <p>
+          {takes === 1 ? '1 take is' : `${takes} takes are`} still recording
+          or encoding.
+        </p>
+        <footer>
+          <button
+            autoFocus
+            onClick={() => {
+              onQuit('Finish')
+            }}
+          >
+            Finish and Quit
+          </button>
+          <button
+            title="Frames are kept so takes can be encoded later"
+            onClick={() => {
+              onQuit('Cancel')
+            }}
+          >
+            Cancel Encoding and Quit
+          </button>
+          <button onClick={onDismiss}>Keep Running</button>
+        </footer>
+      </div>
+    </div>
+  )
+}
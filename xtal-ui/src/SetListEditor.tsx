@@ -0,0 +1,173 @@
+import NumberBox from '@lokua/number-box'
+import clsx from 'clsx/lite'
+
+import type { SetList, SetListEntry } from './types'
+
+import Checkbox from './Checkbox'
+import IconButton from './IconButton'
+import Select from './Select'
+
+const NONE = '-'
+const GLOBAL = 'global'
+
+const snapshotOptions = [
+  NONE,
+  ...Array(10)
+    .fill(0)
+    .map((_, i) => String((i + 1) % 10)),
+]
+
+const transitionOptions = [GLOBAL, '32', '16', '8', '4', '2', '1', '0.5', '0']
+
+type Props = {
+  setList: SetList
+  sketchName: string
+  sketchNames: string[]
+  onChange: (setList: SetList) => void
+  onGoTo: (position: number) => void
+  onNext: () => void
+  onPrevious: () => void
+}
+
+export default function SetListEditor({
+  setList,
+  sketchName,
+  sketchNames,
+  onChange,
+  onGoTo,
+  onNext,
+  onPrevious,
+}: Props) {
+  function updateEntry(index: number, entry: Partial<SetListEntry>) {
+    onChange({
+      ...setList,
+      entries: setList.entries.map((e, i) =>
+        i === index ? { ...e, ...entry } : e
+      ),
+    })
+  }
+
+  function removeEntry(index: number) {
+    onChange({
+      ...setList,
+      entries: setList.entries.filter((_, i) => i !== index),
+    })
+  }
+
+  function addEntry() {
+    onChange({
+      ...setList,
+      entries: setList.entries.concat({
+        sketch: sketchName,
+        snapshot: null,
+        transitionTime: null,
+      }),
+    })
+  }
+
+  const [channel, controller] = setList.nextCc ?? [0, 0]
+
+  return (
+    <div id="set-list" data-help-id="SetList">
+      {setList.entries.map((entry, index) => (
+        <fieldset
+          key={index}
+          className={clsx(setList.position === index && 'on')}
+        >
+          <button
+            title="Go to this entry"
+            onClick={() => {
+              onGoTo(index)
+            }}
+          >
+            {index + 1}
+          </button>
+          <Select
+            value={entry.sketch}
+            options={sketchNames}
+            onChange={(sketch) => {
+              updateEntry(index, { sketch })
+            }}
+            style={{ maxWidth: '120px' }}
+          />
+          <Select
+            title="Snapshot"
+            value={entry.snapshot ?? NONE}
+            options={snapshotOptions}
+            onChange={(snapshot) => {
+              updateEntry(index, {
+                snapshot: snapshot === NONE ? null : snapshot,
+              })
+            }}
+          />
+          <Select
+            title="Transition time (beats)"
+            value={
+              entry.transitionTime === null
+                ? GLOBAL
+                : String(entry.transitionTime)
+            }
+            options={transitionOptions}
+            onChange={(time) => {
+              updateEntry(index, {
+                transitionTime: time === GLOBAL ? null : Number(time),
+              })
+            }}
+          />
+          <IconButton
+            name="Close"
+            onClick={() => {
+              removeEntry(index)
+            }}
+          />
+        </fieldset>
+      ))}
+      <aside>
+        <button onClick={addEntry}>Add current sketch</button>
+        <button disabled={!setList.position} onClick={onPrevious}>
+          Previous
+        </button>
+        <button disabled={setList.entries.length === 0} onClick={onNext}>
+          Next
+        </button>
+      </aside>
+      <fieldset data-help-id="SetListNextCc">
+        <Checkbox
+          id="set-list-next-cc"
+          checked={setList.nextCc !== null}
+          onChange={(enabled) => {
+            onChange({
+              ...setList,
+              nextCc: enabled ? [channel, controller] : null,
+            })
+          }}
+        />
+        <label htmlFor="set-list-next-cc">Next CC</label>
+        {setList.nextCc && (
+          <>
+            <NumberBox
+              className="number-box"
+              value={channel}
+              min={0}
+              max={15}
+              step={1}
+              onChange={(value) => {
+                onChange({ ...setList, nextCc: [value, controller] })
+              }}
+            />
+            <NumberBox
+              className="number-box"
+              value={controller}
+              min={0}
+              max={127}
+              step={1}
+              onChange={(value) => {
+                onChange({ ...setList, nextCc: [channel, value] })
+              }}
+            />
+          </>
+        )}
+      </fieldset>
+    </div>
+  )
+}
@@ -1,8 +1,29 @@
-import { Mappings, noop, OsDir, UserDir } from './types'
+import NumberBox from '@lokua/number-box'
+
+import {
+  AudioDeviceStatus,
+  LatencyOffsets,
+  Mappings,
+  MidiAction,
+  MidiActions as MidiActionMappings,
+  noop,
+  OsDir,
+  OutputWarps,
+  RecordingSettings,
+  SetList,
+  StereoMode,
+  StereoSettings,
+  SyncRole,
+  UserDir,
+} from './types'
 import Checkbox from './Checkbox'
+import LatencyInput from './LatencyInput'
 import MapMode from './MapMode'
+import MidiActions from './MidiActions'
 import OscPortInput from './OscPortInput'
 import Select from './Select'
+import SetListEditor from './SetListEditor'
+import WarpEditor from './WarpEditor'
 import IconButton from './IconButton'
 import { FontSizeChoice, useLocalSettings } from './LocalSettings'
 
@@ -26,63 +47,141 @@ function fromSizePreset(size: SizePreset) {
 
 type Props = {
   audioDevice: string
+  audioDeviceStatus: AudioDeviceStatus
   audioDevices: string[]
+  bpm: number
+  depthDevice: string
+  depthDevices: string[]
   hrcc: boolean
   imagesDir: string
+  isRecording: boolean
+  isRecordingSysEx: boolean
+  isRecordingValues: boolean
+  latencyOffsets: LatencyOffsets
   mappings: Mappings
   mappingsEnabled: boolean
+  midiActions: MidiActionMappings
   midiClockPort: string
   midiInputPort: string
   midiInputPorts: string[]
   midiOutputPort: string
   midiOutputPorts: string[]
   oscPort: number
+  outputWarps: OutputWarps
+  prewarm: boolean
+  recordingSettings: RecordingSettings
+  setList: SetList
+  sketchFps: number
+  sketchMsaaSamples: number
+  sketchName: string
+  sketchNames: string[]
   sliderNames: string[]
+  stereo: StereoSettings
+  syncRole: SyncRole
   userDataDir: string
   videosDir: string
   onChangeAudioDevice: (name: string) => void
+  onChangeBpm: (bpm: number) => void
+  onChangeDepthDevice: (name: string) => void
   onChangeFolder: (kind: UserDir) => void
+  onChangeFps: (fps: number) => void
+  onChangeLatencyOffsets: (latencyOffsets: LatencyOffsets) => void
   onChangeHrcc: noop
   onChangeMappingsEnabled: () => void
   onChangeMidiClockPort: (port: string) => void
   onChangeMidiInputPort: (port: string) => void
   onChangeMidiOutputPort: (port: string) => void
+  onChangeMsaaSamples: (samples: number) => void
   onChangeOscPort: (port: number) => void
+  onChangeOutputWarps: (outputWarps: OutputWarps) => void
+  onChangePrewarm: noop
+  onChangeRecordingSettings: (settings: RecordingSettings) => void
+  onChangeSetList: (setList: SetList) => void
+  onChangeStereo: (stereo: StereoSettings) => void
+  onChangeSyncRole: (role: SyncRole) => void
+  onClickRecordValues: () => void
   onClickSend: () => void
+  onClickSysExBackup: () => void
+  onClickSysExRestore: () => void
   onDeleteMappings: () => void
+  onLearnMidiAction: (action: MidiAction | null) => void
   onOpenOsDir: (osDir: OsDir) => void
   onRemoveMapping: (name: string) => void
+  onRemoveMidiAction: (action: MidiAction) => void
+  onResetTimingOverrides: noop
+  onSetListGoTo: (position: number) => void
+  onSetListNext: noop
+  onSetListPrevious: noop
   onSetCurrentlyMapping: (name: string) => void
 }
 
 export default function Settings({
   audioDevice,
+  audioDeviceStatus,
   audioDevices,
+  bpm,
+  depthDevice,
+  depthDevices,
   hrcc,
   imagesDir,
+  isRecording,
+  isRecordingSysEx,
+  isRecordingValues,
+  latencyOffsets,
   mappings,
   mappingsEnabled,
+  midiActions,
   midiClockPort,
   midiInputPort,
   midiInputPorts,
   midiOutputPort,
   midiOutputPorts,
   oscPort,
+  outputWarps,
+  prewarm,
+  recordingSettings,
+  setList,
+  sketchFps,
+  sketchMsaaSamples,
+  sketchName,
+  sketchNames,
   sliderNames,
+  stereo,
+  syncRole,
   userDataDir,
   videosDir,
   onChangeAudioDevice,
+  onChangeBpm,
+  onChangeDepthDevice,
   onChangeFolder,
+  onChangeFps,
+  onChangeLatencyOffsets,
   onChangeHrcc,
   onChangeMappingsEnabled,
   onChangeMidiClockPort,
   onChangeMidiInputPort,
   onChangeMidiOutputPort,
+  onChangeMsaaSamples,
   onChangeOscPort,
+  onChangeOutputWarps,
+  onChangePrewarm,
+  onChangeRecordingSettings,
+  onChangeSetList,
+  onChangeStereo,
+  onChangeSyncRole,
+  onClickRecordValues,
   onClickSend,
+  onClickSysExBackup,
+  onClickSysExRestore,
   onDeleteMappings,
+  onLearnMidiAction,
   onOpenOsDir,
   onRemoveMapping,
+  onRemoveMidiAction,
+  onResetTimingOverrides,
+  onSetListGoTo,
+  onSetListNext,
+  onSetListPrevious,
   onSetCurrentlyMapping,
 }: Props) {
   const { localSettings, updateLocalSettings } = useLocalSettings()
@@ -105,6 +204,140 @@ export default function Settings({
           <label htmlFor="size">Size</label>
         </fieldset>
 
+        <h2>Sketch Timing</h2>
+        <fieldset data-help-id="SketchFps">
+          <NumberBox
+            id="sketch-fps"
+            className="number-box"
+            value={sketchFps}
+            min={1}
+            max={240}
+            step={1}
+            disabled={isRecording}
+            onChange={onChangeFps}
+          />
+          <label htmlFor="sketch-fps">FPS</label>
+        </fieldset>
+        <fieldset data-help-id="SketchBpm">
+          <NumberBox
+            id="sketch-bpm"
+            className="number-box"
+            value={bpm}
+            min={20}
+            max={300}
+            step={0.1}
+            onChange={onChangeBpm}
+          />
+          <label htmlFor="sketch-bpm">BPM</label>
+        </fieldset>
+        <aside>
+          <button
+            data-help-id="ResetTimingOverrides"
+            disabled={isRecording}
+            onClick={onResetTimingOverrides}
+          >
+            Restore sketch defaults
+          </button>
+        </aside>
+
+        <h2>Rendering</h2>
+        <fieldset data-help-id="SketchMsaa">
+          <Select
+            id="sketch-msaa"
+            value={String(sketchMsaaSamples)}
+            options={['1', '2', '4', '8']}
+            onChange={(samples) => onChangeMsaaSamples(Number(samples))}
+          />
+          <label htmlFor="sketch-msaa">MSAA</label>
+        </fieldset>
+        <fieldset data-help-id="Stereo">
+          <Select
+            id="stereo-mode"
+            value={stereo.mode}
+            options={Object.values(StereoMode)}
+            onChange={(mode) =>
+              onChangeStereo({ ...stereo, mode: mode as StereoMode })
+            }
+          />
+          <label htmlFor="stereo-mode">Stereo</label>
+        </fieldset>
+        <fieldset data-help-id="StereoSeparation">
+          <NumberBox
+            id="stereo-separation"
+            className="number-box"
+            value={stereo.separation}
+            min={0}
+            max={1}
+            step={0.005}
+            disabled={stereo.mode === StereoMode.Off}
+            onChange={(separation) => onChangeStereo({ ...stereo, separation })}
+          />
+          <label htmlFor="stereo-separation">Eye separation</label>
+        </fieldset>
+        <fieldset data-help-id="Prewarm">
+          <Checkbox
+            id="prewarm"
+            type="checkbox"
+            checked={prewarm}
+            onChange={onChangePrewarm}
+          />
+          <label htmlFor="prewarm">Pre-warm sketches</label>
+        </fieldset>
+
+        <h2>Output Warp</h2>
+        <WarpEditor outputWarps={outputWarps} onChange={onChangeOutputWarps} />
+
+        <h2>Recording</h2>
+        <fieldset data-help-id="PreRoll">
+          <NumberBox
+            id="pre-roll"
+            className="number-box"
+            value={recordingSettings.preRoll}
+            min={0}
+            max={32}
+            step={1}
+            disabled={isRecording}
+            onChange={(preRoll) =>
+              onChangeRecordingSettings({ ...recordingSettings, preRoll })
+            }
+          />
+          <label htmlFor="pre-roll">Pre-roll (beats)</label>
+        </fieldset>
+        <fieldset data-help-id="CountIn">
+          <NumberBox
+            id="count-in"
+            className="number-box"
+            value={recordingSettings.countIn}
+            min={0}
+            max={16}
+            step={1}
+            disabled={isRecording}
+            onChange={(countIn) =>
+              onChangeRecordingSettings({ ...recordingSettings, countIn })
+            }
+          />
+          <label htmlFor="count-in">Count-in (beats)</label>
+        </fieldset>
+        <fieldset data-help-id="TimestampOverlay">
+          <Checkbox
+            id="timestamp-overlay"
+            checked={recordingSettings.timestampOverlay}
+            disabled={isRecording}
+            onChange={(timestampOverlay) =>
+              onChangeRecordingSettings({
+                ...recordingSettings,
+                timestampOverlay,
+              })
+            }
+          />
+          <label htmlFor="timestamp-overlay">Burn in timestamps</label>
+        </fieldset>
+        <aside>
+          <button data-help-id="RecordValues" onClick={onClickRecordValues}>
+            {isRecordingValues ? 'Stop value recording' : 'Record values'}
+          </button>
+        </aside>
+
         <h2>Storage</h2>
         <fieldset
           data-help-id="UserDataDir"
@@ -205,6 +438,21 @@ export default function Settings({
           />
           <label htmlFor="hrcc">HRCC</label>
         </fieldset>
+        <aside>
+          <button data-help-id="SysExBackup" onClick={onClickSysExBackup}>
+            {isRecordingSysEx ? 'Stop SysEx backup' : 'Backup SysEx'}
+          </button>
+          <button data-help-id="SysExRestore" onClick={onClickSysExRestore}>
+            Restore SysEx
+          </button>
+        </aside>
+
+        <h2>MIDI Actions</h2>
+        <MidiActions
+          midiActions={midiActions}
+          onLearn={onLearnMidiAction}
+          onRemove={onRemoveMidiAction}
+        />
 
         <h2>Audio</h2>
         <fieldset data-help-id="Audio">
@@ -214,15 +462,68 @@ export default function Settings({
             options={audioDevices}
             onChange={onChangeAudioDevice}
           />
-          <label htmlFor="audio-device">Device</label>
+          <label htmlFor="audio-device">
+            {audioDeviceStatus === AudioDeviceStatus.Disconnected
+              ? 'Device (disconnected)'
+              : 'Device'}
+          </label>
+        </fieldset>
+
+        <h2>Depth Camera</h2>
+        <fieldset data-help-id="DepthDevice">
+          <Select
+            id="depth-device"
+            value={depthDevice}
+            options={depthDevices}
+            onChange={onChangeDepthDevice}
+          />
+          <label htmlFor="depth-device">Device</label>
         </fieldset>
 
+        <h2>Latency Compensation</h2>
+        {(['midi', 'osc', 'audio'] as const).map((source) => (
+          <LatencyInput
+            key={source}
+            id={`latency-${source}`}
+            label={source === 'audio' ? 'Audio' : source.toUpperCase()}
+            latency={latencyOffsets[source]}
+            onChange={(latency) =>
+              onChangeLatencyOffsets({
+                ...latencyOffsets,
+                [source]: latency,
+              })
+            }
+          />
+        ))}
+
         <h2>OSC</h2>
         <OscPortInput
           data-help-id="OscPort"
           port={oscPort}
           onChange={onChangeOscPort}
         />
+
+        <h2>Set List</h2>
+        <SetListEditor
+          setList={setList}
+          sketchName={sketchName}
+          sketchNames={sketchNames}
+          onChange={onChangeSetList}
+          onGoTo={onSetListGoTo}
+          onNext={onSetListNext}
+          onPrevious={onSetListPrevious}
+        />
+
+        <h2>Network Sync</h2>
+        <fieldset data-help-id="SyncRole">
+          <Select
+            id="sync-role"
+            value={syncRole}
+            options={Object.values(SyncRole)}
+            onChange={(role) => onChangeSyncRole(role as SyncRole)}
+          />
+          <label htmlFor="sync-role">Role</label>
+        </fieldset>
       </section>
 
       <section id="mappings-section" data-help-id="Mappings">
@@ -1,23 +1,58 @@
+import { useState } from 'react'
 import clsx from 'clsx/lite'
+import { SnapshotDiff } from './types'
+import Checkbox from './Checkbox'
 import IconButton from './IconButton'
+import Select from './Select'
 
 const availableSlots = Array(10)
   .fill(0)
   .map((_, i) => String((i + 1) % 10))
 
+const RECALL_ALL = 'All controls'
+
 type Props = {
+  diff: SnapshotDiff | null
+  groups: string[]
+  snapshotPhase: boolean
   snapshots: string[]
+  onChangeSnapshotPhase: (snapshotPhase: boolean) => void
+  onCloseDiff: () => void
+  onCopyValues: (from: string, to: string, names: string[]) => void
   onDelete: (snapshot: string) => void
+  onDiff: (a: string, b: string) => void
   onLoad: (snapshot: string) => void
+  onLoadOnly: (snapshot: string, selection: string[]) => void
   onSave: (snapshot: string) => void
 }
 
+function formatDelta(delta: number | null) {
+  if (delta === null) {
+    return ''
+  }
+  return (delta > 0 ? '+' : '') + Number(delta.toFixed(3))
+}
+
 export default function Snapshots({
+  diff,
+  groups,
+  snapshotPhase,
   snapshots,
+  onChangeSnapshotPhase,
+  onCloseDiff,
+  onCopyValues,
   onDelete,
+  onDiff,
   onLoad,
+  onLoadOnly,
   onSave,
 }: Props) {
+  const [recallGroup, setRecallGroup] = useState(RECALL_ALL)
+  const [compareA, setCompareA] = useState('')
+  const [compareB, setCompareB] = useState('')
+  const a = snapshots.includes(compareA) ? compareA : snapshots[0]
+  const b = snapshots.includes(compareB) ? compareB : snapshots[1]
+
   return (
     <div id="snapshots">
       {availableSlots.map((slot) => {
@@ -27,7 +62,9 @@ export default function Snapshots({
             <button
               className={clsx('slot', hasSnapshot && 'on')}
               onClick={() => {
-                if (hasSnapshot) {
+                if (hasSnapshot && groups.includes(recallGroup)) {
+                  onLoadOnly(slot, [recallGroup])
+                } else if (hasSnapshot) {
                   onLoad(slot)
                 } else {
                   onSave(slot)
@@ -47,6 +84,88 @@ export default function Snapshots({
           </div>
         )
       })}
+      <footer data-help-id="SnapshotPhase">
+        <Checkbox
+          id="snapshot-phase"
+          checked={snapshotPhase}
+          onChange={onChangeSnapshotPhase}
+        />
+        <label htmlFor="snapshot-phase">Capture animation phase</label>
+      </footer>
+      {groups.length > 0 && (
+        <footer data-help-id="SnapshotRecallGroup">
+          <label>Recall</label>
+          <Select
+            value={groups.includes(recallGroup) ? recallGroup : RECALL_ALL}
+            options={[RECALL_ALL, ...groups]}
+            onChange={setRecallGroup}
+          />
+        </footer>
+      )}
+      {snapshots.length > 1 && (
+        <footer data-help-id="SnapshotDiff">
+          <Select value={a} options={snapshots} onChange={setCompareA} />
+          <Select value={b} options={snapshots} onChange={setCompareB} />
+          <button
+            disabled={a === b}
+            onClick={() => {
+              onDiff(a, b)
+            }}
+          >
+            Compare
+          </button>
+        </footer>
+      )}
+      {diff && (
+        <table id="snapshot-diff">
+          <thead>
+            <tr>
+              <th>Control</th>
+              <th>[{diff.a}]</th>
+              <th>[{diff.b}]</th>
+              <th>Delta</th>
+              <th>
+                <IconButton name="Close" onClick={onCloseDiff} />
+              </th>
+            </tr>
+          </thead>
+          <tbody>
+            {diff.deltas.length === 0 && (
+              <tr>
+                <td colSpan={5}>Identical</td>
+              </tr>
+            )}
+            {diff.deltas.map((d) => (
+              <tr key={d.name}>
+                <td>{d.name}</td>
+                <td>{d.a ?? '-'}</td>
+                <td>{d.b ?? '-'}</td>
+                <td>{formatDelta(d.delta)}</td>
+                <td>
+                  <button
+                    title={`Copy [${diff.b}] value into [${diff.a}]`}
+                    disabled={d.b === null}
+                    onClick={() => {
+                      onCopyValues(diff.b, diff.a, [d.name])
+                    }}
+                  >
+                    &larr;
+                  </button>
+                  <button
+                    title={`Copy [${diff.a}] value into [${diff.b}]`}
+                    disabled={d.a === null}
+                    onClick={() => {
+                      onCopyValues(diff.a, diff.b, [d.name])
+                    }}
+                  >
+                    &rarr;
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
     </div>
   )
 }
@@ -0,0 +1,197 @@
+import { useState } from 'react'
+
+import type { OutputWarps, Warp, WarpMesh, WarpPoint } from './types'
+
+import Checkbox from './Checkbox'
+
+const WIDTH = 240
+const HEIGHT = 135
+const HANDLE_RADIUS = 5
+const MESH_SIZE = 4
+
+const identityWarp = (): Warp => ({
+  corners: [
+    [0, 0],
+    [1, 0],
+    [1, 1],
+    [0, 1],
+  ],
+  mesh: null,
+})
+
+function identityMesh(cols: number, rows: number): WarpMesh {
+  const points: WarpPoint[] = []
+  for (let row = 0; row < rows; row++) {
+    for (let col = 0; col < cols; col++) {
+      points.push([col / (cols - 1), row / (rows - 1)])
+    }
+  }
+  return { cols, rows, points }
+}
+
+const clamp = (n: number) => Math.min(1, Math.max(0, n))
+
+type PointsProps = {
+  label: string
+  points: WarpPoint[]
+  lines: number[][]
+  onChange: (index: number, point: WarpPoint) => void
+}
+
+function Points({ label, points, lines, onChange }: PointsProps) {
+  const [dragging, setDragging] = useState<number | null>(null)
+
+  function onPointerMove(e: React.PointerEvent<SVGSVGElement>) {
+    if (dragging === null) {
+      return
+    }
+    const rect = e.currentTarget.getBoundingClientRect()
+    onChange(dragging, [
+      clamp((e.clientX - rect.left) / rect.width),
+      clamp((e.clientY - rect.top) / rect.height),
+    ])
+  }
+
+  return (
+    <svg
+      aria-label={label}
+      width={WIDTH}
+      height={HEIGHT}
+      onPointerMove={onPointerMove}
+      onPointerUp={() => {
+        setDragging(null)
+      }}
+    >
+      {lines.map((line, i) => (
+        <polyline
+          key={i}
+          points={line
+            .map((p) => `${points[p][0] * WIDTH},${points[p][1] * HEIGHT}`)
+            .join(' ')}
+        />
+      ))}
+      {points.map(([x, y], index) => (
+        <circle
+          key={index}
+          className={dragging === index ? 'on' : undefined}
+          cx={x * WIDTH}
+          cy={y * HEIGHT}
+          r={HANDLE_RADIUS}
+          onPointerDown={(e) => {
+            e.currentTarget.ownerSVGElement?.setPointerCapture(e.pointerId)
+            setDragging(index)
+          }}
+        />
+      ))}
+    </svg>
+  )
+}
+
+function meshLines({ cols, rows }: WarpMesh): number[][] {
+  const lines: number[][] = []
+  for (let row = 0; row < rows; row++) {
+    lines.push(Array.from({ length: cols }, (_, col) => row * cols + col))
+  }
+  for (let col = 0; col < cols; col++) {
+    lines.push(Array.from({ length: rows }, (_, row) => row * cols + col))
+  }
+  return lines
+}
+
+type Props = {
+  outputWarps: OutputWarps
+  onChange: (outputWarps: OutputWarps) => void
+}
+
+export default function WarpEditor({ outputWarps, onChange }: Props) {
+  const { enabled, venue, venues } = outputWarps
+  const warp = venues[venue] ?? identityWarp()
+
+  function updateWarp(update: Partial<Warp>) {
+    onChange({
+      ...outputWarps,
+      venues: { ...venues, [venue]: { ...warp, ...update } },
+    })
+  }
+
+  return (
+    <div id="warp-editor">
+      <fieldset data-help-id="WarpEnabled">
+        <Checkbox
+          id="warp-enabled"
+          checked={enabled}
+          onChange={(enabled) => {
+            onChange({ ...outputWarps, enabled })
+          }}
+        />
+        <label htmlFor="warp-enabled">Enabled</label>
+      </fieldset>
+      <fieldset data-help-id="WarpVenue">
+        <input
+          id="warp-venue"
+          list="warp-venues"
+          value={venue}
+          onChange={(e) => {
+            onChange({ ...outputWarps, venue: e.currentTarget.value })
+          }}
+        />
+        <datalist id="warp-venues">
+          {Object.keys(venues).map((name) => (
+            <option key={name} value={name} />
+          ))}
+        </datalist>
+        <label htmlFor="warp-venue">Venue</label>
+      </fieldset>
+      <div data-help-id="WarpCorners">
+        <Points
+          label="Corner pin"
+          points={warp.corners}
+          lines={[[0, 1, 2, 3, 0]]}
+          onChange={(index, point) => {
+            const corners = [...warp.corners] as Warp['corners']
+            corners[index] = point
+            updateWarp({ corners })
+          }}
+        />
+      </div>
+      {warp.mesh && (
+        <div data-help-id="WarpMesh">
+          <Points
+            label="Mesh"
+            points={warp.mesh.points}
+            lines={meshLines(warp.mesh)}
+            onChange={(index, point) => {
+              const mesh = warp.mesh!
+              updateWarp({
+                mesh: {
+                  ...mesh,
+                  points: mesh.points.map((p, i) => (i === index ? point : p)),
+                },
+              })
+            }}
+          />
+        </div>
+      )}
+      <aside>
+        <button
+          data-help-id="WarpMesh"
+          onClick={() => {
+            updateWarp({
+              mesh: warp.mesh ? null : identityMesh(MESH_SIZE, MESH_SIZE),
+            })
+          }}
+        >
+          {warp.mesh ? 'Remove mesh' : 'Add mesh'}
+        </button>
+        <button
+          data-help-id="WarpReset"
+          onClick={() => {
+            updateWarp(identityWarp())
+          }}
+        >
+          Reset
+        </button>
+      </aside>
+    </div>
+  )
+}
@@ -29,14 +29,163 @@ export enum OsDir {
   Config = 'Config',
 }
 
+export type DebugGraph = {
+  frame: number
+  nodes: {
+    name: string
+    kind: string
+    cached: number | null
+    proxy: string | null
+    bypass: number | null
+    modulators: string[]
+  }[]
+  edges: { from: string; to: string; via: string }[]
+  evalOrder: string[]
+  aliases: Record<string, string>
+  runtimeBypassed: string[]
+  solo: string | null
+}
+
+export type GpuUsage = {
+  buffers: number
+  bufferBytes: number
+  textures: number
+  textureBytes: number
+  pipelines: number
+}
+
+export type SketchGpuUsage = {
+  name: string
+  live: GpuUsage
+  peakVramBytes: number
+}
+
+export type SnapshotDiffEntry = {
+  name: string
+  a: string | null
+  b: string | null
+  delta: number | null
+}
+
+export type SnapshotDiff = {
+  a: string
+  b: string
+  deltas: SnapshotDiffEntry[]
+}
+
+export type SketchMetadata = {
+  name: string
+  displayName: string
+  tags: string[]
+  author: string
+  description: string
+}
+
+export type SetListEntry = {
+  sketch: string
+  snapshot: string | null
+  transitionTime: number | null
+}
+
+export type SetList = {
+  entries: SetListEntry[]
+  nextCc: ChannelAndController | null
+  position: number | null
+}
+
+export type ScriptError = {
+  file: string
+  message: string
+  line: number | null
+  column: number | null
+  snippet: string | null
+}
+
+export type HistoryTracking = 'Off' | 'All' | { Only: string[] }
+
+export type Subsystem = 'Update' | 'View' | 'Hub' | 'Audio' | 'Midi'
+
+export enum AudioDeviceStatus {
+  Inactive = 'Inactive',
+  Connected = 'Connected',
+  Disconnected = 'Disconnected',
+}
+
+export type Latency = { Ms: number } | { Beats: number }
+
+export type LatencyOffsets = {
+  midi: Latency
+  osc: Latency
+  audio: Latency
+}
+
+export enum SyncRole {
+  Off = 'Off',
+  Leader = 'Leader',
+  Follower = 'Follower',
+}
+
+export enum StereoMode {
+  Off = 'Off',
+  Anaglyph = 'Anaglyph',
+  SideBySide = 'SideBySide',
+}
+
+export type StereoSettings = {
+  mode: StereoMode
+  separation: number
+}
+
+export type WarpPoint = [number, number]
+
+export type WarpMesh = {
+  cols: number
+  rows: number
+  points: WarpPoint[]
+}
+
+export type Warp = {
+  corners: [WarpPoint, WarpPoint, WarpPoint, WarpPoint]
+  mesh: WarpMesh | null
+}
+
+export type OutputWarps = {
+  enabled: boolean
+  venue: string
+  venues: Record<string, Warp>
+}
+
 export type ChannelAndController = [number, number]
 export type Mappings = {
   [key: string]: ChannelAndController
 }
 export type Exclusions = string[]
 
+export type RecordingSettings = {
+  preRoll: number
+  countIn: number
+  timestampOverlay: boolean
+}
+
+export type EncodingProgress = {
+  take: number
+  progress: number
+  queued: number
+}
+
+export type PendingEncodes = 'Finish' | 'Cancel'
+
+export type MidiAction = 'BpmDown' | 'BpmUp' | 'Tap' | 'TransitionTime'
+export type MidiActions = Partial<Record<MidiAction, ChannelAndController>>
+
 export type Bypassed = Record<string, number>
 
+export type SliderRange = {
+  min: number
+  max: number
+  step: number
+}
+
 export type ControlValue = boolean | number | string
 
 export type ControlKind = 'Checkbox' | 'Select' | 'Separator' | 'Slider'
@@ -50,6 +199,26 @@ export type RawControl = {
   min: number
   max: number
   step: number
+  help: string | null
+  display: ValueDisplay | null
+  groups: string[]
+  input: ControlInput | null
+}
+
+export type InputSource = 'Ui' | 'Midi' | 'Osc'
+
+// Only present for sliders that more than one input can set
+export type ControlInput = {
+  active: InputSource
+  last: InputSource | null
+  arbitration: 'priority' | 'latest'
+}
+
+// Presentational only; the sketch always reads the unscaled value
+export type ValueDisplay = {
+  unit: string | null
+  decimals: number | null
+  multiplier: number
 }
 
 export type Control = Omit<RawControl, 'value'> & {
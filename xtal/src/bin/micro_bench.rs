@@ -0,0 +1,12 @@
+use xtal::internal::benchmarks;
+
+fn main() {
+    let iterations = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(10_000);
+
+    for result in benchmarks::run(iterations) {
+        println!("{}", result);
+    }
+}
@@ -0,0 +1,247 @@
+//! Fixtures for the hot paths that run every frame – [`Animation::automate`],
+//! [`ControlHub::get`] through deep dependency chains, and `mod` effect
+//! chains – along with a tiny runner so they can be timed without criterion.
+//! The criterion benches under `benches/` share these fixtures so both measure
+//! the same thing. Only built with the `bench` feature.
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::framework::frame_controller;
+use crate::framework::prelude::*;
+
+pub const BPM: f32 = 120.0;
+
+/// Every breakpoint kind `automate` supports, 16 beats long
+pub fn automate_breakpoints() -> Vec<Breakpoint> {
+    vec![
+        Breakpoint::step(0.0, 0.0),
+        Breakpoint::ramp(2.0, 0.5, Easing::EaseInOut),
+        Breakpoint::wave(
+            4.0,
+            1.0,
+            Shape::Sine,
+            0.25,
+            0.5,
+            0.5,
+            Easing::Linear,
+            Constrain::Clamp(0.0, 1.0),
+        ),
+        Breakpoint::random(8.0, 0.5, 0.5),
+        Breakpoint::random_smooth(
+            12.0,
+            0.5,
+            0.25,
+            0.5,
+            Easing::Linear,
+            Constrain::Clamp(0.0, 1.0),
+        ),
+        Breakpoint::end(16.0, 0.0),
+    ]
+}
+
+pub fn animation() -> Animation<FrameTiming> {
+    Animation::new(FrameTiming::new(Bpm::new(BPM)))
+}
+
+/// `depth` triangles where each one's phase is modulated by the previous.
+/// Reading `chain_{depth - 1}` evaluates the whole chain
+pub fn dependency_chain_script(depth: usize) -> String {
+    (0..depth).fold(String::new(), |mut yaml, i| {
+        yaml.push_str(&format!("chain_{}:\n", i));
+        yaml.push_str("  type: triangle\n  beats: 1\n");
+        if i > 0 {
+            yaml.push_str(&format!("  phase: $chain_{}\n", i - 1));
+        }
+        yaml
+    })
+}
+
+/// A triangle run through every stateless and stateful effect, read as
+/// `effects_chain`
+pub fn effects_chain_script() -> String {
+    r#"
+effects_chain:
+  type: triangle
+  beats: 2
+
+wave_folder:
+  type: effect
+  kind: wave_folder
+  iterations: 4
+
+saturator:
+  type: effect
+  kind: saturator
+  drive: 2.0
+
+quantizer:
+  type: effect
+  kind: quantizer
+  step: 0.125
+
+slew_limiter:
+  type: effect
+  kind: slew_limiter
+  rise: 0.5
+  fall: 0.5
+
+hysteresis:
+  type: effect
+  kind: hysteresis
+  pass_through: true
+
+map:
+  type: effect
+  kind: map
+  domain: [0.0, 1.0]
+  range: [-1.0, 1.0]
+
+constrain:
+  type: effect
+  kind: constrain
+  mode: fold
+
+effects_chain_mod:
+  type: mod
+  source: effects_chain
+  modulators:
+    - wave_folder
+    - saturator
+    - quantizer
+    - slew_limiter
+    - hysteresis
+    - map
+    - constrain
+"#
+    .to_string()
+}
+
+pub fn hub(yaml: &str) -> ControlHub<FrameTiming> {
+    ControlHub::new(Some(yaml), FrameTiming::new(Bpm::new(BPM)))
+}
+
+/// Reads `name` once per frame as a sketch would. Advancing the frame keeps
+/// the hub's per-frame cache from turning the read into a lookup
+pub fn read_next_frame(hub: &ControlHub<FrameTiming>, name: &str) -> f32 {
+    frame_controller::set_frame_count(
+        frame_controller::frame_count().wrapping_add(1),
+    );
+    hub.get(name)
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    pub label: String,
+    pub iterations: u32,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<32} mean: {:>10.2?}  min: {:>10.2?}  max: {:>10.2?}  \
+            ({} iterations)",
+            self.label, self.mean, self.min, self.max, self.iterations
+        )
+    }
+}
+
+/// Times `iterations` calls of `f` after a warm-up of a tenth as many
+pub fn bench<R>(
+    label: &str,
+    iterations: u32,
+    mut f: impl FnMut() -> R,
+) -> BenchResult {
+    let iterations = iterations.max(1);
+
+    for _ in 0..(iterations / 10) {
+        black_box(f());
+    }
+
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    let mut max = Duration::ZERO;
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        black_box(f());
+        let elapsed = start.elapsed();
+        total += elapsed;
+        min = min.min(elapsed);
+        max = max.max(elapsed);
+    }
+
+    BenchResult {
+        label: label.to_string(),
+        iterations,
+        mean: total / iterations,
+        min,
+        max,
+    }
+}
+
+/// Runs every hot path fixture `iterations` times
+pub fn run(iterations: u32) -> Vec<BenchResult> {
+    let mut results = vec![];
+
+    let animation = animation();
+    let breakpoints = automate_breakpoints();
+    results.push(bench("Animation::automate", iterations, || {
+        frame_controller::set_frame_count(
+            frame_controller::frame_count().wrapping_add(1),
+        );
+        animation.automate(&breakpoints, Mode::Loop)
+    }));
+
+    for depth in [1, 8, 32] {
+        let chain = hub(&dependency_chain_script(depth));
+        let name = format!("chain_{}", depth - 1);
+        results.push(bench(
+            &format!("ControlHub::get (depth {})", depth),
+            iterations,
+            || read_next_frame(&chain, &name),
+        ));
+    }
+
+    let effects = hub(&effects_chain_script());
+    results.push(bench("ControlHub::get (effects chain)", iterations, || {
+        read_next_frame(&effects, "effects_chain")
+    }));
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::motion::animation_tests::init;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_fixtures() {
+        init(0);
+
+        let chain = hub(&dependency_chain_script(4));
+        let value = read_next_frame(&chain, "chain_3");
+        assert!((0.0..=1.0).contains(&value));
+
+        let effects = hub(&effects_chain_script());
+        let value = read_next_frame(&effects, "effects_chain");
+        assert!((0.0..=1.0).contains(&value));
+    }
+
+    #[test]
+    #[serial]
+    fn test_run() {
+        init(0);
+        let results = run(2);
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.iterations == 2 && r.min <= r.max));
+    }
+}
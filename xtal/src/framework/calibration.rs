@@ -0,0 +1,241 @@
+//! Built-in calibration pattern for setting up projectors and checking output
+//! warps: a grid, a center cross and circle, 4:3, 16:9, and 1:1 markers, color
+//! bars, and a gray ramp. Cycled at runtime with `Shift T` between off, drawn
+//! over the sketch, and drawn on its own as a test pattern. The runtime draws
+//! it after the sketch's `view` and before the output warp, so the pattern is
+//! warped along with the sketch. It's only drawn for display and never ends up
+//! in captured images or recordings.
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+
+use crate::framework::prelude::*;
+
+/// The WGSL source of the calibration pass
+pub const CALIBRATION_WGSL: &str = include_str!("calibration.wgsl");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CalibrationMode {
+    #[default]
+    Off,
+    /// Draw the pattern over the sketch
+    Overlay,
+    /// Replace the sketch with the pattern
+    TestPattern,
+}
+
+impl CalibrationMode {
+    /// The mode `Shift T` switches to next
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Off => Self::Overlay,
+            Self::Overlay => Self::TestPattern,
+            Self::TestPattern => Self::Off,
+        }
+    }
+}
+
+/// Mirrors `Params` in calibration.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct Params {
+    resolution: [f32; 2],
+    opaque: f32,
+    _pad: f32,
+}
+
+struct Resources {
+    dst_sample_count: u32,
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Resources {
+    fn new(frame: &Frame) -> Self {
+        let device = frame.device_queue_pair().device();
+        let dst_sample_count = frame.texture_msaa_samples();
+
+        let shader_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Calibration Shader"),
+                source: wgpu::ShaderSource::Wgsl(CALIBRATION_WGSL.into()),
+            });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<Params>() as _,
+                        ),
+                    },
+                    count: None,
+                }],
+                label: Some("Calibration Bind Group Layout"),
+            });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Calibration Params Buffer"),
+            size: std::mem::size_of::<Params>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("Calibration Bind Group"),
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Calibration Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Calibration Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Frame::TEXTURE_FORMAT,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: dst_sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            });
+
+        Self {
+            dst_sample_count,
+            pipeline,
+            params_buffer,
+            bind_group,
+        }
+    }
+}
+
+/// Draws the calibration pattern onto the frame. Applied by the runtime after
+/// `view`
+#[derive(Default)]
+pub(crate) struct CalibrationOverlay {
+    mode: CalibrationMode,
+    resources: Option<Resources>,
+}
+
+impl std::fmt::Debug for CalibrationOverlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CalibrationOverlay")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CalibrationOverlay {
+    pub fn mode(&self) -> CalibrationMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: CalibrationMode) {
+        self.mode = mode;
+    }
+
+    pub fn apply(&mut self, frame: &Frame) {
+        if self.mode == CalibrationMode::Off {
+            return;
+        }
+
+        if self
+            .resources
+            .as_ref()
+            .is_none_or(|r| r.dst_sample_count != frame.texture_msaa_samples())
+        {
+            self.resources = Some(Resources::new(frame));
+        }
+        let resources = self.resources.as_ref().unwrap();
+
+        let [w, h] = frame.texture_size();
+        frame.device_queue_pair().queue().write_buffer(
+            &resources.params_buffer,
+            0,
+            bytemuck::bytes_of(&Params {
+                resolution: [w as f32, h as f32],
+                opaque: ternary!(
+                    self.mode == CalibrationMode::TestPattern,
+                    1.0,
+                    0.0
+                ),
+                _pad: 0.0,
+            }),
+        );
+
+        let mut encoder = frame.command_encoder();
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Load)
+            })
+            .begin(&mut encoder);
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, &resources.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::gpu::validate_shader;
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        validate_shader(CALIBRATION_WGSL).unwrap();
+    }
+
+    #[test]
+    fn test_mode_cycles() {
+        let mut mode = CalibrationMode::default();
+        let mut seen = vec![];
+        for _ in 0..3 {
+            mode = mode.next();
+            seen.push(mode);
+        }
+        assert_eq!(
+            seen,
+            [
+                CalibrationMode::Overlay,
+                CalibrationMode::TestPattern,
+                CalibrationMode::Off
+            ]
+        );
+    }
+
+    #[test]
+    fn test_params_match_wgsl() {
+        assert_eq!(std::mem::size_of::<Params>(), 16);
+    }
+}
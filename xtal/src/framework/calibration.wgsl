@@ -0,0 +1,110 @@
+// -----------------------------------------------------------------------------
+//  XTAL CALIBRATION
+//  Built-in test pattern drawn over (or instead of) the sketch output for
+//  lining up projectors and checking warps: a grid, center cross and circle,
+//  aspect ratio markers, color bars, and a gray ramp. Everything is computed
+//  in pixels so lines stay 1px wide at any resolution.
+//  See xtal::calibration
+// -----------------------------------------------------------------------------
+
+struct Params {
+    // w, h
+    resolution: vec2f,
+    // 1.0 to draw over an opaque background instead of the sketch
+    opaque: f32,
+    _pad: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+const GRID_DIVISIONS: f32 = 10.0;
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let res = params.resolution;
+    let p = in.position.xy;
+    let uv = p / res;
+    let center = res * 0.5;
+    let d = p - center;
+    let short_side = min(res.x, res.y);
+
+    var color = vec4f(0.1, 0.1, 0.1, params.opaque);
+
+    // Square grid anchored at the center so the cross sits on a line
+    let cell = short_side / GRID_DIVISIONS;
+    let grid = abs(fract(d / cell + 0.5) - 0.5) * cell;
+    if (min(grid.x, grid.y) < 0.5) {
+        color = over(color, vec4f(1.0, 1.0, 1.0, 0.35));
+    }
+
+    // Aspect markers, each fit inside the frame
+    let yellow = vec3f(1.0, 1.0, 0.0);
+    let magenta = vec3f(1.0, 0.0, 1.0);
+    let cyan = vec3f(0.0, 1.0, 1.0);
+    color = over(color, aspect_marker(d, res, 4.0 / 3.0, yellow));
+    color = over(color, aspect_marker(d, res, 16.0 / 9.0, magenta));
+    color = over(color, aspect_marker(d, res, 1.0, cyan));
+
+    // Only looks round when the output isn't stretched
+    if (abs(length(d) - short_side * 0.45) < 1.0) {
+        color = over(color, vec4f(1.0));
+    }
+
+    if (min(abs(d.x), abs(d.y)) < 1.0) {
+        color = over(color, vec4f(1.0));
+    }
+
+    // Color bars: white, yellow, cyan, green, magenta, red, blue, black
+    if (uv.x > 0.2 && uv.x < 0.8 && uv.y > 0.68 && uv.y < 0.78) {
+        let bar = u32((uv.x - 0.2) / 0.6 * 8.0);
+        let rgb = vec3f(
+            f32(bar == 0u || bar == 1u || bar == 4u || bar == 5u),
+            f32(bar < 4u),
+            f32(bar == 0u || bar == 2u || bar == 4u || bar == 6u),
+        );
+        color = vec4f(rgb, 1.0);
+    }
+
+    // Gray ramp for checking gamma and edge blends
+    if (uv.x > 0.2 && uv.x < 0.8 && uv.y >= 0.78 && uv.y < 0.83) {
+        color = vec4f(vec3f((uv.x - 0.2) / 0.6), 1.0);
+    }
+
+    return color;
+}
+
+fn aspect_marker(d: vec2f, res: vec2f, aspect: f32, rgb: vec3f) -> vec4f {
+    var half_size = vec2f(res.y * aspect, res.y) * 0.5;
+    if (res.x / res.y < aspect) {
+        half_size = vec2f(res.x, res.x / aspect) * 0.5;
+    }
+    let edge = half_size - abs(d);
+    if (all(edge > vec2f(0.0)) && min(edge.x, edge.y) < 1.0) {
+        return vec4f(rgb, 0.8);
+    }
+    return vec4f(0.0);
+}
+
+// Straight alpha "over" compositing
+fn over(dst: vec4f, src: vec4f) -> vec4f {
+    let a = src.a + dst.a * (1.0 - src.a);
+    if (a <= 0.0) {
+        return vec4f(0.0);
+    }
+    let rgb = (src.rgb * src.a + dst.rgb * dst.a * (1.0 - src.a)) / a;
+    return vec4f(rgb, a);
+}
@@ -0,0 +1,414 @@
+//! Orbit and fly cameras for 3D shader sketches, with smooth damping and a
+//! standard uniform block that every [`crate::prelude::gpu::GpuState`] binds
+//! at `@group(0) @binding(2)`. A camera's goal pose can be moved by hand or
+//! read from controls, so `automate` lanes in the control script make for
+//! beat-synced camera paths:
+//!
+//! ```yaml
+//! camera.yaw:
+//!   type: automate
+//!   breakpoints:
+//!     - kind: ramp
+//!       position: 0
+//!       value: 0
+//!     - kind: end
+//!       position: 16
+//!       value: 360
+//!
+//! camera.distance:
+//!   type: slider
+//!   range: [1, 20]
+//!   default: 5
+//! ```
+//!
+//! ```rust,ignore
+//! // init
+//! let camera = Camera::orbit(Vec3::ZERO, 5.0).damping(0.25);
+//!
+//! // update
+//! self.camera.update_params(&self.hub, "camera");
+//! self.camera.update(update.since_last.as_secs_f32());
+//! self.gpu.set_camera(app, &self.camera);
+//! ```
+//!
+//! Shaders pull the block in with `// #include xtal::camera`, which also
+//! provides `camera_clip` for vertex shaders and `camera_ray` for raymarchers.
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+
+use super::prelude::*;
+
+/// The `xtal::camera` shader include that declares the camera uniform
+pub const CAMERA_WGSL: &str = include_str!("camera.wgsl");
+
+/// Keeps [`Mat4::look_at_rh`] away from its degenerate straight up/down case
+const MAX_PITCH: f32 = 89.0;
+
+/// Where a camera is and where it's looking. Orbit cameras look at `target`
+/// from `distance` away; fly cameras have a `distance` of 0 so the eye sits
+/// at `target` itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraPose {
+    pub target: Vec3,
+    /// Degrees around the y axis. 0 looks down -z
+    pub yaw: f32,
+    /// Degrees above the horizon, clamped to ±89
+    pub pitch: f32,
+    pub distance: f32,
+    /// Vertical field of view in degrees
+    pub fov: f32,
+}
+
+impl Default for CameraPose {
+    fn default() -> Self {
+        Self {
+            target: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 5.0,
+            fov: 45.0,
+        }
+    }
+}
+
+impl CameraPose {
+    pub fn forward(&self) -> Vec3 {
+        let yaw = self.yaw.to_radians();
+        let pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH).to_radians();
+        vec3(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            -pitch.cos() * yaw.cos(),
+        )
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.forward().cross(Vec3::Y).normalize()
+    }
+
+    /// World position of the camera itself
+    pub fn eye(&self) -> Vec3 {
+        self.target - self.forward() * self.distance
+    }
+
+    pub fn view(&self) -> Mat4 {
+        let eye = self.eye();
+        Mat4::look_at_rh(eye, eye + self.forward(), Vec3::Y)
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            target: self.target.lerp(other.target, t),
+            yaw: lerp(self.yaw, other.yaw, t),
+            pitch: lerp(self.pitch, other.pitch, t),
+            distance: lerp(self.distance, other.distance, t),
+            fov: lerp(self.fov, other.fov, t),
+        }
+    }
+}
+
+/// A camera that eases its current pose toward a goal pose. Move the goal
+/// with [`Self::rotate`], [`Self::zoom`], [`Self::move_by`], or
+/// [`Self::update_params`], then call [`Self::update`] once per frame.
+#[derive(Clone, Debug)]
+pub struct Camera {
+    goal: CameraPose,
+    current: CameraPose,
+    damping: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::from_pose(CameraPose::default())
+    }
+}
+
+impl Camera {
+    pub fn from_pose(pose: CameraPose) -> Self {
+        Self {
+            goal: pose,
+            current: pose,
+            damping: 0.0,
+            near: 0.01,
+            far: 1000.0,
+        }
+    }
+
+    /// Looks at `target` from `distance` away down the -z axis
+    pub fn orbit(target: Vec3, distance: f32) -> Self {
+        Self::from_pose(CameraPose {
+            target,
+            distance,
+            ..Default::default()
+        })
+    }
+
+    /// Sits at `position` looking down the -z axis
+    pub fn fly(position: Vec3) -> Self {
+        Self::from_pose(CameraPose {
+            target: position,
+            distance: 0.0,
+            ..Default::default()
+        })
+    }
+
+    /// Seconds the camera takes to cover ~63% of the way to its goal. 0 (the
+    /// default) follows the goal exactly
+    pub fn damping(mut self, seconds: f32) -> Self {
+        self.damping = seconds.max(0.0);
+        self
+    }
+
+    pub fn set_damping(&mut self, seconds: f32) {
+        self.damping = seconds.max(0.0);
+    }
+
+    /// The pose as of the last [`Self::update`]
+    pub fn pose(&self) -> &CameraPose {
+        &self.current
+    }
+
+    pub fn goal(&self) -> &CameraPose {
+        &self.goal
+    }
+
+    pub fn set_goal(&mut self, pose: CameraPose) {
+        self.goal = pose;
+    }
+
+    /// Jumps straight to the goal, e.g. after switching camera paths
+    pub fn snap(&mut self) {
+        self.current = self.goal;
+    }
+
+    /// Turns by `yaw` and `pitch` degrees. Orbit cameras circle their target;
+    /// fly cameras look around
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.goal.yaw += yaw;
+        self.goal.pitch =
+            (self.goal.pitch + pitch).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Multiplies the orbit distance, e.g. 0.9 to move 10% closer
+    pub fn zoom(&mut self, factor: f32) {
+        self.goal.distance = (self.goal.distance * factor).max(0.0);
+    }
+
+    /// Moves along the camera's own axes (`up` is world up)
+    pub fn move_by(&mut self, forward: f32, right: f32, up: f32) {
+        self.goal.target += self.goal.forward() * forward
+            + self.goal.right() * right
+            + Vec3::Y * up;
+    }
+
+    /// Sets goal fields from `{prefix}.x`, `.y`, `.z` (the target), `.yaw`,
+    /// `.pitch`, `.distance`, `.fov`, and `.damping` for those that exist
+    pub fn update_params<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        prefix: &str,
+    ) {
+        let goal = &mut self.goal;
+        for (name, field) in [
+            ("x", &mut goal.target.x),
+            ("y", &mut goal.target.y),
+            ("z", &mut goal.target.z),
+            ("yaw", &mut goal.yaw),
+            ("pitch", &mut goal.pitch),
+            ("distance", &mut goal.distance),
+            ("fov", &mut goal.fov),
+        ] {
+            let name = format!("{}.{}", prefix, name);
+            if hub.has(&name) {
+                *field = hub.get(&name);
+            }
+        }
+        goal.pitch = goal.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let name = format!("{}.damping", prefix);
+        if hub.has(&name) {
+            self.set_damping(hub.get(&name));
+        }
+    }
+
+    /// Eases the current pose toward the goal. `dt` is in seconds, typically
+    /// `update.since_last.as_secs_f32()`
+    pub fn update(&mut self, dt: f32) {
+        if self.damping <= 0.0 {
+            self.snap();
+            return;
+        }
+        let t = 1.0 - (-dt.max(0.0) / self.damping).exp();
+        self.current = self.current.lerp(&self.goal, t);
+    }
+
+    pub fn view(&self) -> Mat4 {
+        self.current.view()
+    }
+
+    pub fn projection(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh(
+            self.current.fov.to_radians(),
+            aspect,
+            self.near,
+            self.far,
+        )
+    }
+
+    pub fn uniform(&self, aspect: f32) -> CameraUniform {
+        CameraUniform::new(
+            self.view(),
+            self.projection(aspect),
+            self.current.eye(),
+        )
+    }
+}
+
+/// Mirrors the `Camera` struct in camera.wgsl. Matrices are column-major
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CameraUniform {
+    view_projection: [[f32; 4]; 4],
+    inverse_view_projection: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+    projection: [[f32; 4]; 4],
+    position: [f32; 4],
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new(Mat4::IDENTITY, Mat4::IDENTITY, Vec3::ZERO)
+    }
+}
+
+impl CameraUniform {
+    pub fn new(view: Mat4, projection: Mat4, position: Vec3) -> Self {
+        let view_projection = projection * view;
+        Self {
+            view_projection: view_projection.to_cols_array_2d(),
+            inverse_view_projection: view_projection
+                .inverse()
+                .to_cols_array_2d(),
+            view: view.to_cols_array_2d(),
+            projection: projection.to_cols_array_2d(),
+            position: position.extend(1.0).to_array(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::framework::gpu::{preprocess_shader, validate_shader};
+
+    fn assert_vec3_eq(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_orbit() {
+        let mut camera = Camera::orbit(Vec3::ZERO, 5.0);
+        assert_vec3_eq(camera.pose().eye(), vec3(0.0, 0.0, 5.0));
+
+        camera.rotate(90.0, 0.0);
+        camera.update(1.0 / 60.0);
+        assert_vec3_eq(camera.pose().eye(), vec3(-5.0, 0.0, 0.0));
+
+        camera.rotate(0.0, 180.0);
+        assert_eq!(camera.goal().pitch, MAX_PITCH);
+
+        camera.zoom(0.5);
+        assert_approx_eq!(camera.goal().distance, 2.5);
+    }
+
+    #[test]
+    fn test_fly() {
+        let mut camera = Camera::fly(vec3(0.0, 1.0, 0.0));
+        camera.move_by(2.0, 1.0, 0.0);
+        camera.update(1.0 / 60.0);
+        assert_vec3_eq(camera.pose().eye(), vec3(1.0, 1.0, -2.0));
+    }
+
+    #[test]
+    fn test_damping() {
+        let mut camera = Camera::orbit(Vec3::ZERO, 5.0).damping(0.5);
+        camera.set_goal(CameraPose {
+            distance: 10.0,
+            ..*camera.goal()
+        });
+
+        camera.update(0.5);
+        let distance = camera.pose().distance;
+        assert_approx_eq!(distance, 5.0 + 5.0 * (1.0 - (-1.0_f32).exp()));
+
+        for _ in 0..100 {
+            camera.update(0.1);
+        }
+        assert_approx_eq!(camera.pose().distance, 10.0);
+    }
+
+    #[test]
+    fn test_update_params() {
+        let hub = ControlHub::new(
+            Some(
+                r#"
+camera.yaw:
+  type: slider
+  range: [0, 360]
+  default: 90
+camera.distance:
+  type: slider
+  range: [1, 20]
+  default: 2
+"#,
+            ),
+            FrameTiming::new(Bpm::new(120.0)),
+        );
+
+        let mut camera = Camera::default();
+        camera.update_params(&hub, "camera");
+        camera.update(1.0 / 60.0);
+
+        assert_eq!(camera.pose().yaw, 90.0);
+        assert_eq!(camera.pose().distance, 2.0);
+        assert_eq!(camera.pose().fov, CameraPose::default().fov);
+    }
+
+    #[test]
+    fn test_uniform() {
+        assert_eq!(std::mem::size_of::<CameraUniform>(), 272);
+
+        let camera = Camera::orbit(Vec3::ZERO, 5.0);
+        let uniform = camera.uniform(1.0);
+        let view_projection =
+            Mat4::from_cols_array_2d(&uniform.view_projection);
+        let clip = view_projection * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        assert_approx_eq!(clip.x / clip.w, 0.0);
+        assert_approx_eq!(clip.y / clip.w, 0.0);
+        assert_eq!(uniform.position, [0.0, 0.0, 5.0, 1.0]);
+    }
+
+    #[test]
+    fn test_camera_include() {
+        let shader = preprocess_shader(
+            r#"
+// #include xtal::camera
+
+@vertex
+fn vs_main(@location(0) position: vec3f) -> @builtin(position) vec4f {
+    return camera_clip(position);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    return vec4f(camera_ray(position.xy), 1.0);
+}
+"#,
+        );
+        validate_shader(&shader).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+// -----------------------------------------------------------------------------
+//  XTAL CAMERA
+//  The standard camera block every GpuState binds. Mirrors
+//  xtal::camera::CameraUniform - keep in sync! Set it from a sketch with
+//  `GpuState::set_camera` and include in any GpuState shader with:
+//      // #include xtal::camera
+// -----------------------------------------------------------------------------
+
+struct Camera {
+    view_projection: mat4x4f,
+    inverse_view_projection: mat4x4f,
+    view: mat4x4f,
+    projection: mat4x4f,
+    // xyz world position, w unused
+    position: vec4f,
+}
+
+@group(0) @binding(2)
+var<uniform> camera: Camera;
+
+// World space position to clip space for vertex shaders
+fn camera_clip(position: vec3f) -> vec4f {
+    return camera.view_projection * vec4f(position, 1.0);
+}
+
+// Normalized world space direction of the ray through `uv` ([0, 1], y down)
+// for raymarchers. Start marching from `camera.position.xyz`
+fn camera_ray(uv: vec2f) -> vec3f {
+    let ndc = vec2f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    let far = camera.inverse_view_projection * vec4f(ndc, 1.0, 1.0);
+    return normalize(far.xyz / far.w - camera.position.xyz);
+}
@@ -0,0 +1,293 @@
+//! Perceptual color helpers shared between the CPU and GPU. Naive RGB lerps
+//! pass through muddy, darker midpoints; blending in OKLab (or OKLCH, which
+//! also rotates hue) keeps perceived lightness and saturation even. Every
+//! function here has an identically named and behaving counterpart in
+//! `color.wgsl` which any [`crate::prelude::gpu::GpuState`] shader can pull in
+//! with:
+//!
+//! ```wgsl
+//! // #include xtal::color
+//! ```
+//!
+//! Colors are `Vec3`s in **linear** sRGB unless a function says otherwise.
+//! OKLCH is `(lightness, chroma, hue)` with hue in radians.
+//!
+//! # Example
+//! ```rust,ignore
+//! let a = color::srgb_to_linear(vec3(0.9, 0.3, 0.1));
+//! let b = color::srgb_to_linear(vec3(0.1, 0.4, 0.9));
+//!
+//! let midpoint = color::mix_oklch(a, b, 0.5);
+//! let ramp = color::ramp(&[a, b], 8);
+//!
+//! // Paste into (or prepend to) a shader
+//! let wgsl = color::to_wgsl_const("RAMP", &ramp);
+//! ```
+
+use nannou::prelude::*;
+
+/// The WGSL counterpart of this module
+pub const COLOR_WGSL: &str = include_str!("color.wgsl");
+
+/// Applies the sRGB transfer curve's inverse (display to linear) per channel
+pub fn srgb_to_linear(c: Vec3) -> Vec3 {
+    let f = |v: f32| {
+        ternary!(v <= 0.040_45, v / 12.92, ((v + 0.055) / 1.055).powf(2.4))
+    };
+    vec3(f(c.x), f(c.y), f(c.z))
+}
+
+/// Applies the sRGB transfer curve (linear to display) per channel
+pub fn linear_to_srgb(c: Vec3) -> Vec3 {
+    let f = |v: f32| {
+        ternary!(
+            v <= 0.003_130_8,
+            v * 12.92,
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        )
+    };
+    vec3(f(c.x), f(c.y), f(c.z))
+}
+
+pub fn linear_srgb_to_oklab(c: Vec3) -> Vec3 {
+    let l = 0.412_221_46 * c.x + 0.536_332_55 * c.y + 0.051_445_995 * c.z;
+    let m = 0.211_903_5 * c.x + 0.680_699_5 * c.y + 0.107_396_96 * c.z;
+    let s = 0.088_302_46 * c.x + 0.281_718_85 * c.y + 0.629_978_7 * c.z;
+
+    let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+    vec3(
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    )
+}
+
+/// Not gamut mapped: colors outside of sRGB produce channels outside 0..1
+pub fn oklab_to_linear_srgb(lab: Vec3) -> Vec3 {
+    let l = lab.x + 0.396_337_78 * lab.y + 0.215_803_76 * lab.z;
+    let m = lab.x - 0.105_561_346 * lab.y - 0.063_854_17 * lab.z;
+    let s = lab.x - 0.089_484_18 * lab.y - 1.291_485_5 * lab.z;
+
+    let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+
+    vec3(
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    )
+}
+
+pub fn oklab_to_oklch(lab: Vec3) -> Vec3 {
+    vec3(lab.x, lab.y.hypot(lab.z), lab.z.atan2(lab.y))
+}
+
+pub fn oklch_to_oklab(lch: Vec3) -> Vec3 {
+    vec3(lch.x, lch.y * lch.z.cos(), lch.y * lch.z.sin())
+}
+
+/// Perceptually even blend of two linear sRGB colors
+pub fn mix_oklab(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    oklab_to_linear_srgb(
+        linear_srgb_to_oklab(a).lerp(linear_srgb_to_oklab(b), t),
+    )
+}
+
+/// Chroma below which a color is treated as gray
+const ACHROMATIC: f32 = 1e-4;
+
+/// Like [`mix_oklab`] but travels around the hue wheel (the shorter way)
+/// instead of through the middle, so saturation holds up between distant
+/// hues. Grays adopt the other color's hue
+pub fn mix_oklch(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    let a = oklab_to_oklch(linear_srgb_to_oklab(a));
+    let b = oklab_to_oklch(linear_srgb_to_oklab(b));
+
+    let hue_a = ternary!(a.y < ACHROMATIC, b.z, a.z);
+    let hue_b = ternary!(b.y < ACHROMATIC, a.z, b.z);
+    let delta = (hue_b - hue_a + PI).rem_euclid(TAU) - PI;
+
+    let lch = vec3(
+        a.x + (b.x - a.x) * t,
+        a.y + (b.y - a.y) * t,
+        hue_a + delta * t,
+    );
+
+    oklab_to_linear_srgb(oklch_to_oklab(lch))
+}
+
+/// `count` colors evenly spaced (in OKLab) through `stops`, clamped to the
+/// sRGB gamut
+pub fn ramp(stops: &[Vec3], count: usize) -> Vec<Vec3> {
+    match stops {
+        [] => vec![],
+        [only] => vec![*only; count],
+        _ => (0..count)
+            .map(|i| {
+                let t = ternary!(count > 1, i as f32 / (count - 1) as f32, 0.0);
+                let position = t * (stops.len() - 1) as f32;
+                let index = (position.floor() as usize).min(stops.len() - 2);
+                mix_oklab(
+                    stops[index],
+                    stops[index + 1],
+                    position - index as f32,
+                )
+                .clamp(Vec3::ZERO, Vec3::ONE)
+            })
+            .collect(),
+    }
+}
+
+/// WCAG relative luminance of a linear sRGB color
+pub fn relative_luminance(c: Vec3) -> f32 {
+    0.212_6 * c.x + 0.715_2 * c.y + 0.072_2 * c.z
+}
+
+/// WCAG-style contrast ratio between two linear sRGB colors, from 1 (none)
+/// to 21 (black on white). Order doesn't matter. 4.5 is the usual minimum for
+/// text; for visuals it's a handy "will this read?" estimate
+pub fn contrast_ratio(a: Vec3, b: Vec3) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// The candidate with the highest contrast against `background`
+pub fn most_contrasting(background: Vec3, candidates: &[Vec3]) -> Option<Vec3> {
+    candidates.iter().copied().max_by(|a, b| {
+        contrast_ratio(background, *a)
+            .total_cmp(&contrast_ratio(background, *b))
+    })
+}
+
+/// Emits `colors` as a WGSL constant array, e.g.
+/// `const RAMP: array<vec3f, 2> = array<vec3f, 2>(vec3f(...), vec3f(...));`
+pub fn to_wgsl_const(name: &str, colors: &[Vec3]) -> String {
+    let values = colors
+        .iter()
+        .map(|c| format!("vec3f({:?}, {:?}, {:?})", c.x, c.y, c.z))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let ty = format!("array<vec3f, {}>", colors.len());
+    format!("const {}: {} = {}({});", name, ty, ty, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn assert_vec3_approx_eq(a: Vec3, b: Vec3) {
+        assert_approx_eq!(a.x, b.x);
+        assert_approx_eq!(a.y, b.y);
+        assert_approx_eq!(a.z, b.z);
+    }
+
+    #[test]
+    fn test_oklab_round_trip() {
+        for c in [vec3(0.2, 0.5, 0.8), Vec3::ONE, vec3(1.0, 0.0, 0.0)] {
+            let lab = linear_srgb_to_oklab(c);
+            assert_vec3_approx_eq(oklab_to_linear_srgb(lab), c);
+            assert_vec3_approx_eq(oklch_to_oklab(oklab_to_oklch(lab)), lab);
+        }
+
+        assert_vec3_approx_eq(
+            linear_srgb_to_oklab(Vec3::ONE),
+            vec3(1.0, 0.0, 0.0),
+        );
+        assert_vec3_approx_eq(
+            srgb_to_linear(linear_to_srgb(vec3(0.1, 0.5, 0.9))),
+            vec3(0.1, 0.5, 0.9),
+        );
+    }
+
+    #[test]
+    fn test_mix() {
+        let mid = linear_srgb_to_oklab(mix_oklab(Vec3::ZERO, Vec3::ONE, 0.5));
+        assert_approx_eq!(mid.x, 0.5);
+
+        // Red to blue through OKLCH keeps its chroma; OKLab dips through gray
+        let red = vec3(1.0, 0.0, 0.0);
+        let blue = vec3(0.0, 0.0, 1.0);
+        let chroma = |c| oklab_to_oklch(linear_srgb_to_oklab(c)).y;
+        assert!(
+            chroma(mix_oklch(red, blue, 0.5))
+                > chroma(mix_oklab(red, blue, 0.5))
+        );
+
+        assert_vec3_approx_eq(mix_oklch(red, blue, 0.0), red);
+        assert_vec3_approx_eq(mix_oklch(red, blue, 1.0), blue);
+    }
+
+    #[test]
+    fn test_ramp() {
+        let colors = ramp(&[Vec3::ZERO, vec3(1.0, 0.0, 0.0), Vec3::ONE], 5);
+        assert_eq!(colors.len(), 5);
+        assert_vec3_approx_eq(colors[0], Vec3::ZERO);
+        assert_vec3_approx_eq(colors[2], vec3(1.0, 0.0, 0.0));
+        assert_vec3_approx_eq(colors[4], Vec3::ONE);
+        assert!(ramp(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert_approx_eq!(contrast_ratio(Vec3::ZERO, Vec3::ONE), 21.0);
+        assert_approx_eq!(contrast_ratio(Vec3::ONE, Vec3::ZERO), 21.0);
+        assert_approx_eq!(
+            contrast_ratio(Vec3::splat(0.3), Vec3::splat(0.3)),
+            1.0
+        );
+        assert_eq!(
+            most_contrasting(Vec3::splat(0.9), &[Vec3::ONE, Vec3::ZERO]),
+            Some(Vec3::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_to_wgsl_const() {
+        assert_eq!(
+            to_wgsl_const("RAMP", &[vec3(1.0, 0.5, 0.0)]),
+            "const RAMP: array<vec3f, 1> = array<vec3f, 1>(vec3f(1.0, 0.5, 0.0));"
+        );
+
+        let source = format!(
+            "{}\nfn f() -> vec3f {{ return RAMP[0]; }}",
+            to_wgsl_const("RAMP", &ramp(&[Vec3::ZERO, Vec3::ONE], 4))
+        );
+        naga::front::wgsl::parse_str(&source).unwrap();
+    }
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        let module = naga::front::wgsl::parse_str(COLOR_WGSL).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_wgsl_mirrors_rust() {
+        for name in [
+            "srgb_to_linear",
+            "linear_to_srgb",
+            "linear_srgb_to_oklab",
+            "oklab_to_linear_srgb",
+            "oklab_to_oklch",
+            "oklch_to_oklab",
+            "mix_oklab",
+            "mix_oklch",
+            "relative_luminance",
+            "contrast_ratio",
+        ] {
+            assert!(
+                COLOR_WGSL.contains(&format!("fn {}(", name)),
+                "color.wgsl is missing {}",
+                name
+            );
+        }
+    }
+}
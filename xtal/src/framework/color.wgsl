@@ -0,0 +1,106 @@
+// -----------------------------------------------------------------------------
+//  XTAL COLOR
+//  OKLab/OKLCH conversion, perceptual blending, and contrast helpers. Mirrors
+//  xtal::color - keep in sync! Colors are linear sRGB unless noted.
+//  Include in any GpuState shader with:
+//      // #include xtal::color
+// -----------------------------------------------------------------------------
+
+const COLOR_ACHROMATIC: f32 = 1e-4;
+const COLOR_PI: f32 = 3.14159265;
+const COLOR_TAU: f32 = 6.28318531;
+
+fn srgb_to_linear(c: vec3f) -> vec3f {
+    return select(
+        pow((c + 0.055) / 1.055, vec3f(2.4)),
+        c / 12.92,
+        c <= vec3f(0.04045)
+    );
+}
+
+fn linear_to_srgb(c: vec3f) -> vec3f {
+    return select(
+        1.055 * pow(c, vec3f(1.0 / 2.4)) - 0.055,
+        c * 12.92,
+        c <= vec3f(0.0031308)
+    );
+}
+
+fn color_cbrt(v: vec3f) -> vec3f {
+    return sign(v) * pow(abs(v), vec3f(1.0 / 3.0));
+}
+
+fn linear_srgb_to_oklab(c: vec3f) -> vec3f {
+    let lms = color_cbrt(vec3f(
+        0.41222146 * c.r + 0.53633255 * c.g + 0.051445995 * c.b,
+        0.2119035 * c.r + 0.6806995 * c.g + 0.10739696 * c.b,
+        0.08830246 * c.r + 0.28171885 * c.g + 0.6299787 * c.b,
+    ));
+
+    return vec3f(
+        0.21045426 * lms.x + 0.7936178 * lms.y - 0.004072047 * lms.z,
+        1.9779985 * lms.x - 2.4285922 * lms.y + 0.4505937 * lms.z,
+        0.025904037 * lms.x + 0.78277177 * lms.y - 0.80867577 * lms.z,
+    );
+}
+
+// Not gamut mapped: colors outside of sRGB produce channels outside 0..1
+fn oklab_to_linear_srgb(lab: vec3f) -> vec3f {
+    let l = lab.x + 0.39633778 * lab.y + 0.21580376 * lab.z;
+    let m = lab.x - 0.105561346 * lab.y - 0.06385417 * lab.z;
+    let s = lab.x - 0.08948418 * lab.y - 1.2914855 * lab.z;
+
+    let lms = vec3f(l * l * l, m * m * m, s * s * s);
+
+    return vec3f(
+        4.0767417 * lms.x - 3.3077116 * lms.y + 0.23096994 * lms.z,
+        -1.268438 * lms.x + 2.6097574 * lms.y - 0.34131938 * lms.z,
+        -0.0041960863 * lms.x - 0.7034186 * lms.y + 1.7076147 * lms.z,
+    );
+}
+
+// (lightness, chroma, hue in radians)
+fn oklab_to_oklch(lab: vec3f) -> vec3f {
+    return vec3f(lab.x, length(lab.yz), atan2(lab.z, lab.y));
+}
+
+fn oklch_to_oklab(lch: vec3f) -> vec3f {
+    return vec3f(lch.x, lch.y * cos(lch.z), lch.y * sin(lch.z));
+}
+
+fn mix_oklab(a: vec3f, b: vec3f, t: f32) -> vec3f {
+    return oklab_to_linear_srgb(
+        mix(linear_srgb_to_oklab(a), linear_srgb_to_oklab(b), t)
+    );
+}
+
+// Travels around the hue wheel (the shorter way). Grays adopt the other
+// color's hue
+fn mix_oklch(a: vec3f, b: vec3f, t: f32) -> vec3f {
+    let lch_a = oklab_to_oklch(linear_srgb_to_oklab(a));
+    let lch_b = oklab_to_oklch(linear_srgb_to_oklab(b));
+
+    let hue_a = select(lch_a.z, lch_b.z, lch_a.y < COLOR_ACHROMATIC);
+    let hue_b = select(lch_b.z, lch_a.z, lch_b.y < COLOR_ACHROMATIC);
+    let shifted = hue_b - hue_a + COLOR_PI;
+    let delta = shifted - COLOR_TAU * floor(shifted / COLOR_TAU) - COLOR_PI;
+
+    let lch = vec3f(
+        mix(lch_a.x, lch_b.x, t),
+        mix(lch_a.y, lch_b.y, t),
+        hue_a + delta * t,
+    );
+
+    return oklab_to_linear_srgb(oklch_to_oklab(lch));
+}
+
+fn relative_luminance(c: vec3f) -> f32 {
+    return dot(c, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+// 1 (none) to 21 (black on white)
+fn contrast_ratio(a: vec3f, b: vec3f) -> f32 {
+    let la = relative_luminance(a);
+    let lb = relative_luminance(b);
+    return (max(la, lb) + 0.05) / (min(la, lb) + 0.05);
+}
@@ -0,0 +1,271 @@
+//! Deciding which input a slider follows when several can set it: its own
+//! value (moved in the UI or set by snapshots, cues, and [`ControlHub::set`]),
+//! a MIDI mapping, and OSC messages to `/<name>` when the slider is declared
+//! with `osc: true`. Each slider picks a policy with its `arbitration` field:
+//!
+//! - `priority` (default): MIDI when mapped, then OSC once a message has
+//!   arrived, then the UI
+//! - `latest`: whichever input moved most recently, falling back to
+//!   `priority` until one has
+//!
+//! Inputs are compared against what was seen on the previous
+//! [`ControlHub::update`], so a source only counts as having moved once its
+//! value actually changes.
+//!
+//! Sketches do not need to interact with this module directly – see
+//! [`ControlHub::active_input`] and [`ControlHub::last_input`].
+
+use nannou_core::math::map_range;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use super::osc_controls::osc_float;
+use crate::framework::osc_receiver::SHARED_OSC_RECEIVER;
+use crate::framework::prelude::*;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Arbitration {
+    #[default]
+    Priority,
+    Latest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InputSource {
+    Ui,
+    Midi,
+    Osc,
+}
+
+impl InputSource {
+    /// Highest priority first
+    const PRIORITY: [Self; 3] = [Self::Midi, Self::Osc, Self::Ui];
+}
+
+/// Tracks the inputs of one slider. See the module docs
+#[derive(Clone, Debug, Default)]
+pub struct InputArbiter {
+    policy: Arbitration,
+    ui: Option<f32>,
+    midi: Option<f32>,
+    /// The latest OSC value, already mapped to the slider's range. `None`
+    /// until a message arrives
+    osc: Option<f32>,
+    last: Option<InputSource>,
+}
+
+impl InputArbiter {
+    /// Applies a (re)declared slider, keeping what has been observed so a
+    /// script edit doesn't reset which input is in control
+    pub fn configure(&mut self, policy: Arbitration, osc: bool) {
+        self.policy = policy;
+        if !osc {
+            self.osc = None;
+        }
+    }
+
+    /// Records each input's current value. The UI and MIDI only count as
+    /// moved once they differ from a previous observation while the first
+    /// OSC message counts immediately. Returns true if any input moved
+    pub fn observe(
+        &mut self,
+        ui: Option<f32>,
+        midi: Option<f32>,
+        osc: Option<f32>,
+    ) -> bool {
+        let ui_moved = self.ui.is_some() && ui.is_some() && ui != self.ui;
+        let midi_moved =
+            self.midi.is_some() && midi.is_some() && midi != self.midi;
+        let osc_moved = osc.is_some() && osc != self.osc;
+        self.ui = ui;
+        self.midi = midi;
+        self.osc = osc;
+
+        // Ties go to the higher priority input
+        let moved = [
+            (InputSource::Midi, midi_moved),
+            (InputSource::Osc, osc_moved),
+            (InputSource::Ui, ui_moved),
+        ]
+        .into_iter()
+        .find_map(|(source, moved)| moved.then_some(source));
+        if moved.is_some() {
+            self.last = moved;
+        }
+
+        moved.is_some()
+    }
+
+    pub fn policy(&self) -> Arbitration {
+        self.policy
+    }
+
+    /// The input that moved most recently, if any has since this slider was
+    /// declared
+    pub fn last(&self) -> Option<InputSource> {
+        self.last
+    }
+
+    /// The latest OSC value, if any
+    pub fn osc(&self) -> Option<f32> {
+        self.osc
+    }
+
+    /// The input the slider follows given whether it is currently mapped to
+    /// MIDI
+    pub fn active(&self, midi_mapped: bool) -> InputSource {
+        let available = |source: &InputSource| match source {
+            InputSource::Ui => true,
+            InputSource::Midi => midi_mapped,
+            InputSource::Osc => self.osc.is_some(),
+        };
+        if self.policy == Arbitration::Latest {
+            if let Some(last) = self.last.filter(available) {
+                return last;
+            }
+        }
+        InputSource::PRIORITY
+            .into_iter()
+            .find(available)
+            .unwrap_or(InputSource::Ui)
+    }
+}
+
+/// `name => (range, latest value)`
+type OscState = HashMap<String, ([f32; 2], Option<f32>)>;
+
+/// OSC values addressed to sliders declared with `osc: true`. Messages arrive
+/// on the OSC thread and are picked up by [`InputArbiter::observe`]
+#[derive(Clone, Debug, Default)]
+pub struct OscInputs {
+    state: Arc<Mutex<OscState>>,
+    osc_registered: bool,
+}
+
+impl OscInputs {
+    pub fn start(&mut self) {
+        // The shared receiver has no way to unregister so only do this once
+        if self.osc_registered {
+            return;
+        }
+        let inputs = self.clone();
+        SHARED_OSC_RECEIVER.register_callback("*", move |msg| {
+            let key = msg.addr.trim_start_matches('/');
+            if let Some(value) = msg.args.first().and_then(osc_float) {
+                inputs.receive(key, value);
+            }
+        });
+        self.osc_registered = true;
+    }
+
+    /// Stores a normalized `value` for `name`, mapped to its range. Ignored
+    /// for sliders that don't accept OSC
+    pub fn receive(&self, name: &str, value: f32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some((range, latest)) = state.get_mut(name) {
+            *latest = Some(map_range(
+                value.clamp(0.0, 1.0),
+                0.0,
+                1.0,
+                range[0],
+                range[1],
+            ));
+        }
+    }
+
+    /// Accepts OSC for `name`. The latest value is kept unless the range
+    /// changed
+    pub fn add(&mut self, name: &str, range: [f32; 2]) {
+        let mut state = self.state.lock().unwrap();
+        let entry = state.entry(name.to_string()).or_insert((range, None));
+        if entry.0 != range {
+            *entry = (range, None);
+        }
+    }
+
+    /// Removes every slider not in `names`
+    pub fn retain(&mut self, names: &[&str]) {
+        let mut state = self.state.lock().unwrap();
+        state.retain(|name, _| names.contains(&name.as_str()));
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.state.lock().unwrap().contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.state
+            .lock()
+            .unwrap()
+            .get(name)
+            .and_then(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority() {
+        let mut arbiter = InputArbiter::default();
+        assert!(!arbiter.observe(Some(0.5), None, None));
+        assert_eq!(arbiter.active(false), InputSource::Ui);
+        assert_eq!(arbiter.active(true), InputSource::Midi);
+
+        assert!(arbiter.observe(Some(0.6), None, None));
+        assert_eq!(arbiter.last(), Some(InputSource::Ui));
+        assert!(arbiter.observe(Some(0.6), None, Some(0.1)));
+        assert_eq!(arbiter.last(), Some(InputSource::Osc));
+
+        // The UI moving again doesn't take over from OSC
+        arbiter.observe(Some(0.7), None, Some(0.1));
+        assert_eq!(arbiter.last(), Some(InputSource::Ui));
+        assert_eq!(arbiter.active(false), InputSource::Osc);
+    }
+
+    #[test]
+    fn test_latest() {
+        let mut arbiter = InputArbiter::default();
+        arbiter.configure(Arbitration::Latest, true);
+        arbiter.observe(Some(0.5), Some(0.0), None);
+        assert_eq!(arbiter.last(), None);
+        assert_eq!(arbiter.active(true), InputSource::Midi);
+
+        arbiter.observe(Some(0.6), Some(0.0), None);
+        assert_eq!(arbiter.active(true), InputSource::Ui);
+
+        // Ties go to the higher priority input
+        arbiter.observe(Some(0.7), Some(0.2), Some(0.3));
+        assert_eq!(arbiter.active(true), InputSource::Midi);
+        arbiter.observe(Some(0.7), Some(0.2), Some(0.4));
+        assert_eq!(arbiter.active(true), InputSource::Osc);
+
+        // An input that is no longer available falls back to priority
+        arbiter.configure(Arbitration::Latest, false);
+        assert_eq!(arbiter.active(false), InputSource::Ui);
+    }
+
+    #[test]
+    fn test_osc_inputs() {
+        let mut inputs = OscInputs::default();
+        inputs.add("size", [10.0, 20.0]);
+        inputs.receive("other", 1.0);
+        assert_eq!(inputs.get("size"), None);
+
+        inputs.receive("size", 0.5);
+        assert_eq!(inputs.get("size"), Some(15.0));
+        inputs.add("size", [10.0, 20.0]);
+        assert_eq!(inputs.get("size"), Some(15.0));
+
+        inputs.retain(&[]);
+        assert!(!inputs.has("size"));
+    }
+}
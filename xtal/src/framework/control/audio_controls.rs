@@ -7,15 +7,22 @@
 //!
 //! [device]: crate::config::MULTICHANNEL_AUDIO_DEVICE_NAME
 
-use cpal::{Device, Stream, StreamConfig, traits::*};
-use nannou::math::map_range;
+use cpal::{Device, Stream, StreamConfig, StreamError, traits::*};
+use nannou_core::math::map_range;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::error::Error;
-use std::sync::{Arc, Mutex};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tracing::trace_span;
 
+use super::latency::DelayLine;
 use crate::framework::frame_controller;
 use crate::framework::prelude::*;
+use crate::framework::spans;
 use crate::runtime::global;
 
 #[derive(Clone, Debug)]
@@ -33,14 +40,25 @@ pub struct AudioControlConfig {
     pub pre_emphasis: f32,
 
     /// Linearly interpolate between peak and RMS amplitude detection.
-    /// 0.0 = peak, 1.0 = RMS
+    /// 0.0 = peak, 1.0 = RMS. Only used by [`DetectMode::Frame`]
     pub detect: f32,
 
+    /// See [`DetectMode`]
+    pub mode: DetectMode,
+
+    /// Length in beats of the window the windowed [`DetectMode`]s measure
+    /// over. Longer windows make for slower, steadier values
+    pub window: f32,
+
     pub range: (f32, f32),
 
     /// Represents the initial value of this control and will not be updated
     /// after instantiation
     pub value: f32,
+
+    /// When set, the control follows the pitch of a monophonic signal instead
+    /// of its level. See [`PitchTracking`]
+    pub pitch: Option<PitchTracking>,
 }
 
 impl AudioControlConfig {
@@ -57,12 +75,71 @@ impl AudioControlConfig {
             slew_limiter,
             detect,
             pre_emphasis,
+            mode: DetectMode::default(),
+            window: 1.0,
             range,
             value: default,
+            pitch: None,
+        }
+    }
+
+    pub fn with_detect_mode(mut self, mode: DetectMode, window: f32) -> Self {
+        self.mode = mode;
+        self.window = window;
+        self
+    }
+
+    pub fn with_pitch_tracking(mut self, pitch: PitchTracking) -> Self {
+        self.pitch = Some(pitch);
+        self
+    }
+}
+
+/// Monophonic pitch detection ([YIN][yin]) for melodic sources like bass
+/// lines, leads, and vocals. The control's value is the detected pitch
+/// normalized to 0..1 on a log (semitone) scale across the frequency range
+/// before being mapped to [`AudioControlConfig::range`], so equal intervals
+/// produce equal changes. The last pitch is held through silence and unvoiced
+/// sounds. A second value, how periodic the signal is (0..1), is stored under
+/// [`AudioControls::confidence_name`].
+///
+/// [yin]: http://audition.ens.fr/adc/pdf/2002_JASA_YIN.pdf
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchTracking {
+    /// Lowest detectable frequency in Hz. Lower values cost more CPU
+    pub min_frequency: f32,
+    pub max_frequency: f32,
+    /// YIN's aperiodicity threshold; lower is stricter. 0.1 to 0.2 is typical
+    pub threshold: f32,
+}
+
+impl Default for PitchTracking {
+    fn default() -> Self {
+        Self {
+            min_frequency: 55.0,
+            max_frequency: 1_760.0,
+            threshold: 0.15,
         }
     }
 }
 
+/// How an audio control reduces its channel to a single value
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectMode {
+    /// The [`BufferProcessor`] applied to the last frame's worth of audio.
+    /// Snappy but twitchy
+    #[default]
+    Frame,
+    /// Highest absolute sample within the window
+    Peak,
+    /// Root mean square of the window
+    Rms,
+    /// K-weighted loudness of the window (BS.1770 without gating) mapped from
+    /// -60..0 LUFS to 0..1. Tracks perceived loudness rather than level
+    Lufs,
+}
+
 impl ControlConfig<f32, f32> for AudioControlConfig {}
 
 /// A function used in [`AudioControls`] to reduce a channel's audio buffer to a
@@ -104,6 +181,25 @@ struct State {
     processor: MultichannelAudioProcessor,
     values: HashMap<String, f32>,
     previous_values: Vec<f32>,
+    detectors: HashMap<String, WindowedDetector>,
+    pitch_trackers: HashMap<String, PitchTracker>,
+    sample_rate: f32,
+    bpm: f32,
+    delay_line: DelayLine,
+}
+
+/// How often to look for a disconnected device
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum AudioDeviceStatus {
+    /// No stream has been started
+    #[default]
+    Inactive,
+    Connected,
+    /// The device is missing or went away. [`AudioControls::update`] will
+    /// reconnect once it shows up again
+    Disconnected,
 }
 
 pub struct AudioControls {
@@ -111,6 +207,13 @@ pub struct AudioControls {
     buffer_processor: BufferProcessor,
     state: Arc<Mutex<State>>,
     stream: Option<Stream>,
+    status: AudioDeviceStatus,
+    /// Set from the stream's error callback when the device goes away
+    stream_lost: Arc<AtomicBool>,
+    /// Set from the reconnect thread once the device is back. Replaced on
+    /// every start, which also ends any reconnect thread still holding the
+    /// previous one
+    device_found: Arc<AtomicBool>,
 }
 
 impl AudioControls {
@@ -124,8 +227,16 @@ impl AudioControls {
                 values: HashMap::default(),
                 processor,
                 previous_values: vec![0.0],
+                detectors: HashMap::default(),
+                pitch_trackers: HashMap::default(),
+                sample_rate: 48_000.0,
+                bpm: 120.0,
+                delay_line: DelayLine::default(),
             })),
             stream: None,
+            status: AudioDeviceStatus::Inactive,
+            stream_lost: Arc::new(AtomicBool::new(false)),
+            device_found: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -149,15 +260,70 @@ impl AudioControls {
         }
     }
 
+    /// The name under which a [`PitchTracking`] control's confidence is stored
+    pub fn confidence_name(name: &str) -> String {
+        format!("{}_confidence", name)
+    }
+
+    /// Used to convert [`AudioControlConfig::window`] from beats to samples
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.state.lock().unwrap().bpm = bpm;
+    }
+
     pub fn is_active(&self) -> bool {
         self.is_active
     }
 
+    /// Holds processed values back by `delay` and releases those now due. See
+    /// [`LatencyOffsets`]
+    pub fn update_latency(&mut self, delay: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            values, delay_line, ..
+        } = &mut *state;
+        delay_line.set_delay(delay, values);
+        delay_line.flush(values, Instant::now());
+    }
+
     pub fn set_buffer_processor(&mut self, buffer_processor: BufferProcessor) {
         self.buffer_processor = buffer_processor
     }
 
+    pub fn device_status(&self) -> AudioDeviceStatus {
+        self.status
+    }
+
+    /// Detects a lost device and reconnects once it returns. Returns the new
+    /// status when it changed since the last call
+    pub fn update(&mut self) -> Option<AudioDeviceStatus> {
+        let previous = self.status;
+
+        if self.stream.is_some() && self.stream_lost.load(Ordering::Acquire) {
+            warn!("Audio device disconnected. Waiting for it to return.");
+            self.disconnect();
+        } else if self.status == AudioDeviceStatus::Disconnected
+            && self.device_found.load(Ordering::Acquire)
+        {
+            info!("Audio device found. Reconnecting...");
+            if let Err(e) = self.start() {
+                error!("Unable to reconnect audio device: {}", e);
+            }
+        }
+
+        (self.status != previous).then_some(self.status)
+    }
+
+    /// Starts the input stream. When the device can't be opened the controls
+    /// go [`AudioDeviceStatus::Disconnected`] and keep trying in the
+    /// background via [`Self::update`]
     pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        self.device_found = Arc::new(AtomicBool::new(false));
+        self.stream_lost = Arc::new(AtomicBool::new(false));
+
+        self.connect().inspect_err(|_| self.disconnect())
+    }
+
+    fn connect(&mut self) -> Result<(), Box<dyn Error>> {
         let buffer_processor = self.buffer_processor;
         let (device, stream_config) = Self::device_and_stream_config()?;
 
@@ -170,6 +336,9 @@ impl AudioControls {
             state.processor =
                 MultichannelAudioProcessor::new(buffer_size, channels);
             state.previous_values = vec![0.0; channels];
+            state.sample_rate = stream_config.sample_rate.0 as f32;
+            state.detectors.clear();
+            state.pitch_trackers.clear();
         }
 
         let state = self.state.clone();
@@ -177,15 +346,28 @@ impl AudioControls {
         let stream = device.build_input_stream(
             &stream_config,
             move |data: &[f32], _: &_| {
+                let _span =
+                    trace_span!(target: spans::AUDIO, "audio_input").entered();
                 let mut state = state.lock().unwrap();
                 state.processor.add_samples(data);
 
-                let updates: Vec<(String, f32, usize, f32)> = state
-                    .configs
+                let State {
+                    configs,
+                    processor,
+                    detectors,
+                    pitch_trackers,
+                    sample_rate,
+                    bpm,
+                    ..
+                } = &mut *state;
+                let channel_count = processor.channel_data.len();
+
+                let mut confidences = vec![];
+
+                let updates: Vec<(String, f32, usize, f32)> = configs
                     .iter()
                     .filter_map(|(name, config)| {
-                        if config.channel >= state.processor.channel_data.len()
-                        {
+                        if config.channel >= channel_count {
                             warn_once!(
                                 "Using AudioControlConfig with channel \
                                 beyond available device channels: {:?}",
@@ -194,11 +376,65 @@ impl AudioControls {
                             return None;
                         }
 
-                        let channel_buffer =
-                            state.processor.channel_buffer(config.channel);
+                        let channel_samples = data
+                            .iter()
+                            .skip(config.channel)
+                            .step_by(channel_count)
+                            .copied();
 
-                        let processed_value =
-                            buffer_processor(channel_buffer, config);
+                        let processed_value = if let Some(pitch) = config.pitch
+                        {
+                            let tracker = pitch_trackers
+                                .entry(name.clone())
+                                .and_modify(|t| {
+                                    if t.config != pitch {
+                                        *t = PitchTracker::new(
+                                            pitch,
+                                            *sample_rate,
+                                        );
+                                    }
+                                })
+                                .or_insert_with(|| {
+                                    PitchTracker::new(pitch, *sample_rate)
+                                });
+
+                            tracker.process(channel_samples);
+                            confidences.push((
+                                AudioControls::confidence_name(name),
+                                tracker.confidence,
+                            ));
+                            tracker.value
+                        } else if config.mode == DetectMode::Frame {
+                            let channel_buffer =
+                                processor.channel_buffer(config.channel);
+                            buffer_processor(channel_buffer, config)
+                        } else {
+                            let detector = detectors
+                                .entry(name.clone())
+                                .and_modify(|d| {
+                                    if d.mode != config.mode {
+                                        *d = WindowedDetector::new(
+                                            config.mode,
+                                            *sample_rate,
+                                        );
+                                    }
+                                })
+                                .or_insert_with(|| {
+                                    WindowedDetector::new(
+                                        config.mode,
+                                        *sample_rate,
+                                    )
+                                });
+
+                            let window_seconds =
+                                config.window * 60.0 / bpm.max(1.0);
+                            detector.process(
+                                channel_samples,
+                                config.pre_emphasis,
+                                (window_seconds * *sample_rate) as usize,
+                            );
+                            detector.value()
+                        };
 
                         let value = config.slew_limiter.apply(processed_value);
 
@@ -214,18 +450,38 @@ impl AudioControls {
                     })
                     .collect();
 
+                let now = Instant::now();
+                let State {
+                    values,
+                    previous_values,
+                    delay_line,
+                    ..
+                } = &mut *state;
+
                 for (name, mapped, channel, value) in updates {
-                    state.values.insert(name, mapped);
-                    state.previous_values[channel] = value;
+                    delay_line.push(values, &name, mapped, now);
+                    previous_values[channel] = value;
+                }
+                for (name, confidence) in confidences {
+                    delay_line.push(values, &name, confidence, now);
+                }
+            },
+            {
+                let stream_lost = self.stream_lost.clone();
+                move |err| {
+                    error!("Error in audio stream: {}", err);
+                    if matches!(err, StreamError::DeviceNotAvailable) {
+                        stream_lost.store(true, Ordering::Release);
+                    }
                 }
             },
-            move |err| error!("Error in audio stream: {}", err),
             None,
         )?;
 
         stream.play()?;
         self.stream = Some(stream);
         self.is_active = true;
+        self.status = AudioDeviceStatus::Connected;
 
         info!(
             "AudioControls connected to device: {:?}",
@@ -240,6 +496,37 @@ impl AudioControls {
             self.is_active = false;
             debug!("Audio stream stopped");
         }
+        self.device_found = Arc::new(AtomicBool::new(false));
+        self.status = AudioDeviceStatus::Inactive;
+    }
+
+    fn disconnect(&mut self) {
+        self.stop();
+        self.status = AudioDeviceStatus::Disconnected;
+
+        let device_name = global::audio_device_name().unwrap_or_default();
+        let device_found = Arc::downgrade(&self.device_found);
+
+        thread::spawn(move || {
+            Self::wait_for_device(&device_name, device_found)
+        });
+    }
+
+    /// Polls the host's devices until `device_name` appears or `device_found`
+    /// is dropped by a newer start or stop
+    fn wait_for_device(device_name: &str, device_found: Weak<AtomicBool>) {
+        loop {
+            thread::sleep(RECONNECT_INTERVAL);
+
+            let Some(device_found) = device_found.upgrade() else {
+                return;
+            };
+
+            if Self::find_device(device_name).is_ok() {
+                device_found.store(true, Ordering::Release);
+                return;
+            }
+        }
     }
 
     pub fn restart(&mut self) -> Result<(), Box<dyn Error>> {
@@ -249,15 +536,19 @@ impl AudioControls {
         self.start()
     }
 
-    fn device_and_stream_config()
-    -> Result<(Device, StreamConfig), Box<dyn Error>> {
-        let host = cpal::default_host();
-        let device_name = global::audio_device_name().unwrap_or_default();
-        let device = host
+    fn find_device(device_name: &str) -> Result<Device, Box<dyn Error>> {
+        cpal::default_host()
             .input_devices()?
             .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
-            .expect("Audio device not found");
+            .ok_or_else(|| {
+                format!("Audio device not found: {:?}", device_name).into()
+            })
+    }
 
+    fn device_and_stream_config()
+    -> Result<(Device, StreamConfig), Box<dyn Error>> {
+        let device_name = global::audio_device_name().unwrap_or_default();
+        let device = Self::find_device(&device_name)?;
         let stream_config = device.default_input_config()?.into();
 
         Ok((device, stream_config))
@@ -276,6 +567,9 @@ impl
     fn add(&mut self, name: &str, config: AudioControlConfig) {
         let mut state = self.state.lock().unwrap();
         state.values.insert(name.to_string(), config.value);
+        if config.pitch.is_some() {
+            state.values.insert(Self::confidence_name(name), 0.0);
+        }
         state.configs.insert(name.to_string(), config);
     }
 
@@ -298,10 +592,19 @@ impl
         self.state.lock().unwrap().values.get(name).copied()
     }
 
+    /// Unlike other collections this includes derived values such as
+    /// [`Self::confidence_name`]
+    fn has(&self, name: &str) -> bool {
+        self.state.lock().unwrap().values.contains_key(name)
+    }
+
     fn remove(&mut self, name: &str) {
         let mut state = self.state.lock().unwrap();
         state.configs.remove(name);
         state.values.remove(name);
+        state.values.remove(&Self::confidence_name(name));
+        state.detectors.remove(name);
+        state.pitch_trackers.remove(name);
     }
 
     fn set(&mut self, name: &str, value: f32) {
@@ -472,3 +775,355 @@ impl MultichannelAudioProcessor {
             .sqrt()
     }
 }
+
+/// Running measurement for the windowed [`DetectMode`]s. Fed only the samples
+/// that arrived since the last callback, unlike [`MultichannelAudioProcessor`]
+/// which always holds exactly one frame's worth
+#[derive(Debug)]
+struct WindowedDetector {
+    mode: DetectMode,
+
+    /// `(peak or sum of squares, sample count)` per callback, oldest first
+    blocks: VecDeque<(f32, usize)>,
+    sample_count: usize,
+
+    /// Last raw sample of the previous callback, for pre-emphasis
+    previous_sample: f32,
+
+    /// Only applied in [`DetectMode::Lufs`]
+    k_weighting: [Biquad; 2],
+}
+
+impl WindowedDetector {
+    fn new(mode: DetectMode, sample_rate: f32) -> Self {
+        Self {
+            mode,
+            blocks: VecDeque::new(),
+            sample_count: 0,
+            previous_sample: 0.0,
+            k_weighting: Biquad::k_weighting(sample_rate),
+        }
+    }
+
+    fn process(
+        &mut self,
+        samples: impl Iterator<Item = f32>,
+        pre_emphasis: f32,
+        window_samples: usize,
+    ) {
+        let mut peak = 0.0_f32;
+        let mut sum = 0.0;
+        let mut count = 0;
+
+        for sample in samples {
+            let mut x = sample - pre_emphasis * self.previous_sample;
+            self.previous_sample = sample;
+
+            if self.mode == DetectMode::Lufs {
+                for stage in &mut self.k_weighting {
+                    x = stage.process(x);
+                }
+            }
+
+            peak = peak.max(x.abs());
+            sum += x * x;
+            count += 1;
+        }
+
+        if count == 0 {
+            return;
+        }
+
+        let block = ternary!(self.mode == DetectMode::Peak, peak, sum);
+        self.blocks.push_back((block, count));
+        self.sample_count += count;
+
+        while let Some((_, oldest)) = self.blocks.front().copied() {
+            if self.blocks.len() == 1
+                || self.sample_count - oldest < window_samples
+            {
+                break;
+            }
+            self.blocks.pop_front();
+            self.sample_count -= oldest;
+        }
+    }
+
+    fn value(&self) -> f32 {
+        if self.sample_count == 0 {
+            return 0.0;
+        }
+
+        let mean_square = || {
+            self.blocks.iter().map(|(sum, _)| sum).sum::<f32>()
+                / self.sample_count as f32
+        };
+
+        match self.mode {
+            DetectMode::Peak => {
+                self.blocks.iter().fold(0.0, |a, (peak, _)| a.max(*peak))
+            }
+            DetectMode::Rms | DetectMode::Frame => mean_square().sqrt(),
+            DetectMode::Lufs => {
+                let mean_square = mean_square();
+                if mean_square <= 0.0 {
+                    return 0.0;
+                }
+                let lufs = -0.691 + 10.0 * mean_square.log10();
+                ((lufs + 60.0) / 60.0).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+/// YIN pitch detector over a sliding buffer of the most recent samples
+#[derive(Debug)]
+struct PitchTracker {
+    config: PitchTracking,
+    sample_rate: f32,
+    min_lag: usize,
+    max_lag: usize,
+    buffer: VecDeque<f32>,
+    /// Normalized pitch, held while no pitch is detected
+    value: f32,
+    confidence: f32,
+}
+
+impl PitchTracker {
+    /// Signals quieter than this (RMS) are treated as silence
+    const SILENCE: f32 = 1e-3;
+
+    fn new(config: PitchTracking, sample_rate: f32) -> Self {
+        let max_frequency = config.max_frequency.max(1.0);
+        let min_frequency = config.min_frequency.clamp(1.0, max_frequency);
+        let max_lag = (sample_rate / min_frequency).ceil() as usize;
+
+        Self {
+            config,
+            sample_rate,
+            min_lag: ((sample_rate / max_frequency).floor() as usize).max(2),
+            max_lag,
+            buffer: VecDeque::with_capacity(max_lag * 2),
+            value: 0.0,
+            confidence: 0.0,
+        }
+    }
+
+    fn process(&mut self, samples: impl Iterator<Item = f32>) {
+        let size = self.max_lag * 2;
+        self.buffer.extend(samples);
+        if self.buffer.len() > size {
+            self.buffer.drain(..self.buffer.len() - size);
+        }
+        if self.buffer.len() < size {
+            return;
+        }
+
+        self.buffer.make_contiguous();
+        match self.detect() {
+            Some((lag, confidence)) => {
+                self.value = self.normalize(self.sample_rate / lag);
+                self.confidence = confidence;
+            }
+            None => self.confidence = 0.0,
+        }
+    }
+
+    /// Returns the fractional period in samples and a 0..1 confidence
+    fn detect(&self) -> Option<(f32, f32)> {
+        let (buffer, _) = self.buffer.as_slices();
+        let window = self.max_lag;
+
+        let rms = (buffer[..window].iter().map(|x| x * x).sum::<f32>()
+            / window as f32)
+            .sqrt();
+        if rms < Self::SILENCE {
+            return None;
+        }
+
+        // Cumulative mean normalized difference function
+        let mut cmnd = vec![1.0; self.max_lag + 1];
+        let mut running_sum = 0.0;
+        for lag in 1..=self.max_lag {
+            let difference: f32 = (0..window)
+                .map(|i| {
+                    let delta = buffer[i] - buffer[i + lag];
+                    delta * delta
+                })
+                .sum();
+            running_sum += difference;
+            cmnd[lag] = ternary!(
+                running_sum > 0.0,
+                difference * lag as f32 / running_sum,
+                1.0
+            );
+        }
+
+        // First dip below the threshold, followed down to its local minimum
+        let mut lag = (self.min_lag..self.max_lag)
+            .find(|lag| cmnd[*lag] < self.config.threshold)?;
+        while lag + 1 < self.max_lag && cmnd[lag + 1] < cmnd[lag] {
+            lag += 1;
+        }
+
+        // Parabolic interpolation for sub-sample accuracy
+        let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+        let denominator = a - 2.0 * b + c;
+        let offset = ternary!(
+            denominator.abs() > f32::EPSILON,
+            (0.5 * (a - c) / denominator).clamp(-0.5, 0.5),
+            0.0
+        );
+
+        Some((lag as f32 + offset, (1.0 - b).clamp(0.0, 1.0)))
+    }
+
+    /// Maps a frequency to 0..1 in semitones across the configured range
+    fn normalize(&self, frequency: f32) -> f32 {
+        let min = self.config.min_frequency.max(1.0).log2();
+        let max = self.config.max_frequency.max(1.0).log2();
+        if max <= min {
+            return 0.0;
+        }
+        ((frequency.log2() - min) / (max - min)).clamp(0.0, 1.0)
+    }
+}
+
+/// Transposed direct form II biquad filter
+#[derive(Clone, Debug, Default)]
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    z: [f32; 2],
+}
+
+impl Biquad {
+    /// The two stage "K" pre-filter from ITU-R BS.1770 (a high shelf modeling
+    /// the head followed by a high pass) for any sample rate
+    fn k_weighting(sample_rate: f32) -> [Self; 2] {
+        let sample_rate = sample_rate as f64;
+
+        let k = (PI as f64 * 1_681.974_450_955_533 / sample_rate).tan();
+        let q = 0.707_175_236_955_419_6;
+        let vh = 10.0_f64.powf(3.999_843_853_973_347 / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Self::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        let k = (PI as f64 * 38.135_470_876_024_44 / sample_rate).tan();
+        let q = 0.500_327_037_323_877_3;
+        let a0 = 1.0 + k / q + k * k;
+        let high_pass = Self::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        );
+
+        [shelf, high_pass]
+    }
+
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b: b.map(|x| x as f32),
+            a: a.map(|x| x as f32),
+            z: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn sine(frequency: f32, sample_rate: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * frequency * i as f32 / sample_rate).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_k_weighting_coefficients() {
+        let [shelf, high_pass] = Biquad::k_weighting(48_000.0);
+        assert_approx_eq!(shelf.b[0], 1.535_124_9, 1e-5);
+        assert_approx_eq!(shelf.b[1], -2.691_696_2, 1e-5);
+        assert_approx_eq!(shelf.b[2], 1.198_392_8, 1e-5);
+        assert_approx_eq!(shelf.a[0], -1.690_659_3, 1e-5);
+        assert_approx_eq!(shelf.a[1], 0.732_480_8, 1e-5);
+        assert_approx_eq!(high_pass.a[0], -1.990_047_5, 1e-5);
+        assert_approx_eq!(high_pass.a[1], 0.990_072_3, 1e-5);
+    }
+
+    #[test]
+    fn test_windowed_rms_and_peak() {
+        let signal = sine(100.0, 48_000.0, 48_000);
+
+        let mut rms = WindowedDetector::new(DetectMode::Rms, 48_000.0);
+        let mut peak = WindowedDetector::new(DetectMode::Peak, 48_000.0);
+        for chunk in signal.chunks(512) {
+            rms.process(chunk.iter().copied(), 0.0, 24_000);
+            peak.process(chunk.iter().copied(), 0.0, 24_000);
+        }
+
+        assert_approx_eq!(rms.value(), 1.0 / 2.0_f32.sqrt(), 0.01);
+        assert_approx_eq!(peak.value(), 1.0, 0.01);
+
+        // Silence pushes the signal out of the window
+        for _ in 0..60 {
+            rms.process([0.0; 512].into_iter(), 0.0, 24_000);
+        }
+        assert_approx_eq!(rms.value(), 0.0);
+    }
+
+    #[test]
+    fn test_lufs_of_full_scale_sine() {
+        // BS.1770 defines a 0 dBFS 997Hz sine as -3.01 LUFS
+        let mut lufs = WindowedDetector::new(DetectMode::Lufs, 48_000.0);
+        for chunk in sine(997.0, 48_000.0, 96_000).chunks(512) {
+            lufs.process(chunk.iter().copied(), 0.0, 48_000);
+        }
+        assert_approx_eq!(lufs.value(), (60.0 - 3.01) / 60.0, 0.005);
+    }
+
+    #[test]
+    fn test_pitch_tracking() {
+        let mut tracker = PitchTracker::new(PitchTracking::default(), 48_000.0);
+        for chunk in sine(220.0, 48_000.0, 4_800).chunks(512) {
+            tracker.process(chunk.iter().copied());
+        }
+
+        // 220Hz is two of the five octaves between 55Hz and 1760Hz
+        assert_approx_eq!(tracker.value, 0.4, 0.001);
+        assert!(tracker.confidence > 0.9);
+
+        // A richer tone with a strong 2nd harmonic still tracks the fundamental
+        let harmonic = sine(880.0, 48_000.0, 4_800);
+        let tone: Vec<f32> = sine(440.0, 48_000.0, 4_800)
+            .iter()
+            .zip(harmonic)
+            .map(|(a, b)| 0.5 * a + 0.4 * b)
+            .collect();
+        for chunk in tone.chunks(512) {
+            tracker.process(chunk.iter().copied());
+        }
+        assert_approx_eq!(tracker.value, 0.6, 0.001);
+
+        // Silence drops confidence but holds the last pitch
+        tracker.process([0.0; 4_800].into_iter());
+        assert_approx_eq!(tracker.value, 0.6, 0.001);
+        assert_eq!(tracker.confidence, 0.0);
+    }
+}
@@ -3,6 +3,7 @@
 
 use std::error::Error;
 use std::fmt;
+use std::time::Duration;
 
 use indexmap::IndexMap;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -18,15 +19,14 @@ use crate::framework::prelude::*;
 /// declared in yaml
 pub type ConfigFile = IndexMap<String, MaybeControlConfig>;
 
-#[derive(Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum MaybeControlConfig {
     Control(ScriptedControlConfig),
-    #[allow(dead_code)]
     Other(serde_yml::Value),
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Clone, Deserialize, Debug)]
 pub struct ScriptedControlConfig {
     #[serde(rename = "type")]
     pub control_type: ControlType,
@@ -45,6 +45,8 @@ pub enum ControlType {
     Select,
     #[serde(rename = "separator")]
     Separator,
+    #[serde(rename = "macro")]
+    Macro,
 
     // External control
     #[serde(rename = "midi")]
@@ -53,6 +55,20 @@ pub enum ControlType {
     Osc,
     #[serde(rename = "audio")]
     Audio,
+    #[serde(rename = "audio_pitch")]
+    AudioPitch,
+    #[serde(rename = "midi_file")]
+    MidiFile,
+    #[serde(rename = "midi_velocity")]
+    MidiVelocity,
+    #[serde(rename = "midi_note")]
+    MidiNote,
+    #[serde(rename = "landmark")]
+    Landmark,
+
+    // Color
+    #[serde(rename = "palette")]
+    Palette,
 
     // Animation
     #[serde(rename = "automate")]
@@ -65,6 +81,8 @@ pub enum ControlType {
     RandomSlewed,
     #[serde(rename = "triangle")]
     Triangle,
+    #[serde(rename = "adsr", alias = "env")]
+    Envelope,
 
     // Modulation & Effects
     #[serde(rename = "mod")]
@@ -78,13 +96,82 @@ pub enum ControlType {
 pub struct Shared {
     #[serde(default, deserialize_with = "deserialize_number_or_none")]
     pub bypass: Option<f32>,
+    /// One alias or a list of them. See `ControlHub::parse_vars`
     #[serde(default)]
-    pub var: Option<String>,
+    pub var: Option<serde_yml::Value>,
     // TODO: this really shouldn't be on shared because only UI controls use it
     #[serde(default, deserialize_with = "to_disabled_fn")]
     pub disabled: Option<DisabledConfig>,
 }
 
+/// The optional `map_to` field available on every control. Either
+/// `[min, max]` or `{ range: [min, max], easing: ease_in }`
+#[derive(Clone, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum MapToConfig {
+    Range([f32; 2]),
+    Eased {
+        range: [f32; 2],
+        #[serde(default = "default_easing")]
+        easing: String,
+    },
+}
+
+impl MapToConfig {
+    pub fn range(&self) -> [f32; 2] {
+        match self {
+            Self::Range(range) | Self::Eased { range, .. } => *range,
+        }
+    }
+
+    pub fn easing(&self) -> &str {
+        match self {
+            Self::Range(_) => "linear",
+            Self::Eased { easing, .. } => easing,
+        }
+    }
+}
+
+/// The optional `stepped` field available on every control. Snapshot
+/// transitions jump the control at this end instead of interpolating it
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stepped {
+    Start,
+    End,
+}
+
+/// The optional `display` field available on every control. Only affects
+/// how the value is shown; the sketch still reads the unscaled value
+#[derive(Clone, Deserialize, Debug)]
+pub struct DisplayConfig {
+    /// Fixed number of decimal places to show
+    #[serde(default)]
+    pub decimals: Option<usize>,
+    /// Shown values are the control's value times this, e.g. `100` to show
+    /// a 0..1 slider as a percentage
+    #[serde(default = "default_display_multiplier")]
+    pub multiplier: f32,
+}
+
+fn default_display_multiplier() -> f32 {
+    1.0
+}
+
+/// The optional `humanize` field available on every control
+#[derive(Clone, Deserialize, Debug)]
+pub struct HumanizeConfig {
+    /// Peak deviation as a fraction of the control's range
+    pub amount: f32,
+    /// How quickly the deviation wanders, in cycles per beat
+    #[serde(default = "default_humanize_rate")]
+    pub rate: f32,
+}
+
+fn default_humanize_rate() -> f32 {
+    1.0
+}
+
 //------------------------------------------------------------------------------
 // UI
 //------------------------------------------------------------------------------
@@ -97,6 +184,10 @@ pub struct SliderConfig {
     pub range: [f32; 2],
     pub default: f32,
     pub step: f32,
+    /// See [`Arbitration`]
+    pub arbitration: Arbitration,
+    /// Accept OSC messages to `/<name>`, mapped from 0..1 to the range
+    pub osc: bool,
 }
 
 impl Default for SliderConfig {
@@ -106,6 +197,8 @@ impl Default for SliderConfig {
             range: [0.0, 1.0],
             default: 0.0,
             step: 0.000_1,
+            arbitration: Arbitration::default(),
+            osc: false,
         }
     }
 }
@@ -126,13 +219,73 @@ pub struct SelectConfig {
     pub default: String,
 }
 
+/// A 0..1 slider that drives each of `targets` through its own range and
+/// curve
 #[derive(Deserialize, Debug)]
-struct Separator {}
+pub struct MacroConfig {
+    #[serde(flatten)]
+    pub shared: Shared,
+    #[serde(default)]
+    pub default: f32,
+    pub targets: IndexMap<String, MacroTargetConfig>,
+}
+
+/// Either `[min, max]` or `{ range: [min, max], curve: ease_in, invert: true }`
+#[derive(Clone, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum MacroTargetConfig {
+    Range([f32; 2]),
+    Curved {
+        range: [f32; 2],
+        #[serde(default = "default_easing")]
+        curve: String,
+        #[serde(default)]
+        invert: bool,
+    },
+}
+
+impl MacroTargetConfig {
+    pub fn range(&self) -> [f32; 2] {
+        match self {
+            Self::Range(range) | Self::Curved { range, .. } => *range,
+        }
+    }
+
+    pub fn curve(&self) -> &str {
+        match self {
+            Self::Range(_) => "linear",
+            Self::Curved { curve, .. } => curve,
+        }
+    }
+
+    pub fn invert(&self) -> bool {
+        matches!(self, Self::Curved { invert: true, .. })
+    }
+}
 
 //------------------------------------------------------------------------------
 // External
 //------------------------------------------------------------------------------
 
+/// The optional `smooth` field of MIDI and OSC controls: either milliseconds
+/// or `{ beats: 0.25 }`
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum SmoothConfig {
+    Ms(f32),
+    Beats { beats: f32 },
+}
+
+impl SmoothConfig {
+    pub fn duration(&self, bpm: f32) -> Duration {
+        let seconds = match self {
+            Self::Ms(ms) => ms / 1_000.0,
+            Self::Beats { beats } => beats * 60.0 / bpm.max(1.0),
+        };
+        Duration::from_secs_f32(seconds.max(0.0))
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(default)]
 pub struct MidiConfig {
@@ -143,6 +296,7 @@ pub struct MidiConfig {
     pub cc: u8,
     pub range: [f32; 2],
     pub default: f32,
+    pub smooth: Option<SmoothConfig>,
 }
 
 impl Default for MidiConfig {
@@ -153,10 +307,63 @@ impl Default for MidiConfig {
             cc: 0,
             range: [0.0, 1.0],
             default: 0.0,
+            smooth: None,
         }
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct MidiFileConfig {
+    /// Path to a `.mid` file, relative to the control script
+    pub path: String,
+    #[serde(default)]
+    pub channel: u8,
+    /// Follow a CC lane. Mutually exclusive with `note`
+    #[serde(default)]
+    pub cc: Option<u8>,
+    /// Follow a note as a gate (1 while held). Mutually exclusive with `cc`
+    #[serde(default)]
+    pub note: Option<u8>,
+    #[serde(default = "default_unit_range")]
+    pub range: [f32; 2],
+    /// Used before a CC lane's first event
+    #[serde(default)]
+    pub default: f32,
+    /// Loop length in beats; defaults to the file's length
+    #[serde(default, rename = "loop")]
+    pub loop_beats: Option<f32>,
+}
+
+/// Shared by the `midi_velocity` and `midi_note` control types
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct MidiNoteConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    /// Any channel when omitted
+    pub channel: Option<u8>,
+    pub notes: [u8; 2],
+    pub range: [f32; 2],
+    pub default: f32,
+}
+
+impl Default for MidiNoteConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            channel: None,
+            notes: [0, 127],
+            range: [0.0, 1.0],
+            default: 0.0,
+        }
+    }
+}
+
+fn default_unit_range() -> [f32; 2] {
+    [0.0, 1.0]
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(default)]
 pub struct OscConfig {
@@ -165,6 +372,7 @@ pub struct OscConfig {
     shared: Shared,
     pub range: [f32; 2],
     pub default: f32,
+    pub smooth: Option<SmoothConfig>,
 }
 
 impl Default for OscConfig {
@@ -173,6 +381,31 @@ impl Default for OscConfig {
             shared: Shared::default(),
             range: [0.0, 1.0],
             default: 0.0,
+            smooth: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct LandmarkConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub range: [f32; 2],
+    pub default: f32,
+    pub smooth: Option<SmoothConfig>,
+    pub min_visibility: f32,
+}
+
+impl Default for LandmarkConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            range: [0.0, 1.0],
+            default: 0.5,
+            smooth: None,
+            min_visibility: 0.5,
         }
     }
 }
@@ -187,6 +420,9 @@ pub struct AudioConfig {
     pub slew: [f32; 2],
     pub pre: f32,
     pub detect: f32,
+    pub mode: DetectMode,
+    /// In beats
+    pub window: f32,
     pub range: [f32; 2],
     pub bypass: Option<f32>,
 }
@@ -199,12 +435,42 @@ impl Default for AudioConfig {
             slew: [0.0, 0.0],
             pre: 0.0,
             detect: 0.0,
+            mode: DetectMode::default(),
+            window: 1.0,
             range: [0.0, 1.0],
             bypass: None,
         }
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct AudioPitchConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub channel: usize,
+    pub slew: [f32; 2],
+    /// The `[min, max]` frequencies in Hz that map to `range`
+    pub frequencies: [f32; 2],
+    pub threshold: f32,
+    pub range: [f32; 2],
+}
+
+impl Default for AudioPitchConfig {
+    fn default() -> Self {
+        let pitch = PitchTracking::default();
+        Self {
+            shared: Shared::default(),
+            channel: 0,
+            slew: [0.0, 0.0],
+            frequencies: [pitch.min_frequency, pitch.max_frequency],
+            threshold: pitch.threshold,
+            range: [0.0, 1.0],
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 // Animation
 //------------------------------------------------------------------------------
@@ -386,6 +652,42 @@ impl Default for TriangleConfig {
     }
 }
 
+/// The `adsr` control type. Stage lengths are in beats
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct EnvelopeConfig {
+    #[allow(dead_code)]
+    #[serde(flatten)]
+    shared: Shared,
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub hold: f32,
+    pub release: f32,
+    pub range: [f32; 2],
+    pub velocity: bool,
+    /// Any channel when omitted
+    pub channel: Option<u8>,
+    pub note: Option<u8>,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            shared: Shared::default(),
+            attack: 0.0,
+            decay: 0.25,
+            sustain: 0.5,
+            hold: 0.5,
+            release: 0.5,
+            range: [0.0, 1.0],
+            velocity: true,
+            channel: None,
+            note: None,
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
 // Modulation & Effects
 //------------------------------------------------------------------------------
@@ -396,7 +698,107 @@ pub struct ModulationConfig {
     #[serde(flatten)]
     shared: Shared,
     pub source: String,
-    pub modulators: Vec<String>,
+    pub modulators: Vec<ModulationRoute>,
+}
+
+/// A `modulators` entry: either a bare name or a single `name: operator` map,
+/// e.g. `- wobble: add` or `- lfo: { crossfade: 0.25 }`
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "RawModulationRoute")]
+pub struct ModulationRoute {
+    pub modulator: String,
+    /// How the modulator's value combines with the value so far. None uses
+    /// the default: multiply for controls, replace for effects
+    pub operator: Option<ModulationOperator>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModulationOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Replace,
+    Min,
+    Max,
+    /// Blends from the value (0.0) to the modulator (1.0)
+    Crossfade(f32),
+}
+
+impl ModulationOperator {
+    pub fn apply(&self, value: f32, modulation: f32) -> f32 {
+        match self {
+            Self::Add => value + modulation,
+            Self::Subtract => value - modulation,
+            Self::Multiply => value * modulation,
+            Self::Replace => modulation,
+            Self::Min => value.min(modulation),
+            Self::Max => value.max(modulation),
+            Self::Crossfade(t) => lerp(value, modulation, *t),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawModulationRoute {
+    Name(String),
+    WithOperator(IndexMap<String, RawModulationOperator>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawModulationOperator {
+    Name(String),
+    Crossfade { crossfade: f32 },
+}
+
+impl TryFrom<RawModulationRoute> for ModulationRoute {
+    type Error = String;
+
+    fn try_from(raw: RawModulationRoute) -> Result<Self, Self::Error> {
+        let map = match raw {
+            RawModulationRoute::Name(modulator) => {
+                return Ok(Self {
+                    modulator,
+                    operator: None,
+                });
+            }
+            RawModulationRoute::WithOperator(map) => map,
+        };
+
+        if map.len() != 1 {
+            return Err("Modulators with an operator must be a single \
+                `name: operator` map"
+                .to_string());
+        }
+
+        let (modulator, operator) = map.into_iter().next().unwrap();
+
+        let operator = match operator {
+            RawModulationOperator::Crossfade { crossfade } => {
+                ModulationOperator::Crossfade(crossfade.clamp(0.0, 1.0))
+            }
+            RawModulationOperator::Name(name) => match name.as_str() {
+                "add" => ModulationOperator::Add,
+                "subtract" => ModulationOperator::Subtract,
+                "multiply" => ModulationOperator::Multiply,
+                "replace" => ModulationOperator::Replace,
+                "min" => ModulationOperator::Min,
+                "max" => ModulationOperator::Max,
+                _ => {
+                    return Err(format!(
+                        "Unknown modulation operator `{}` for `{}`",
+                        name, modulator
+                    ));
+                }
+            },
+        };
+
+        Ok(Self {
+            modulator,
+            operator: Some(operator),
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -444,8 +846,10 @@ pub enum EffectKind {
     Quantizer {
         #[serde(default = "default_param_value_0_25")]
         step: ParamValue,
-        #[serde(default = "default_normalized_range")]
-        range: (f32, f32),
+        #[serde(default)]
+        range: EffectRangeConfig,
+        #[serde(default = "default_range_decay")]
+        range_decay: f32,
     },
 
     RingModulator {
@@ -482,12 +886,447 @@ pub enum EffectKind {
         bias: ParamValue,
         #[serde(default = "default_param_value_1")]
         shape: ParamValue,
-        // TODO: make Option and consider None to mean "adaptive range"?
-        #[serde(default = "default_normalized_range")]
-        range: (f32, f32),
+        #[serde(default)]
+        range: EffectRangeConfig,
+        #[serde(default = "default_range_decay")]
+        range_decay: f32,
     },
 }
 
+/// Either `[min, max]` or `adaptive` to follow the running min/max of the input
+/// (see [`AdaptiveRange`])
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "RawEffectRange")]
+pub enum EffectRangeConfig {
+    Fixed(f32, f32),
+    Adaptive,
+}
+
+impl EffectRangeConfig {
+    pub fn to_effect_range(self, decay: f32) -> EffectRange {
+        match self {
+            Self::Fixed(min, max) => EffectRange::Fixed(min, max),
+            Self::Adaptive => EffectRange::Adaptive(AdaptiveRange::new(decay)),
+        }
+    }
+}
+
+impl Default for EffectRangeConfig {
+    fn default() -> Self {
+        Self::Fixed(0.0, 1.0)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawEffectRange {
+    Fixed((f32, f32)),
+    Keyword(String),
+}
+
+impl TryFrom<RawEffectRange> for EffectRangeConfig {
+    type Error = String;
+
+    fn try_from(raw: RawEffectRange) -> Result<Self, Self::Error> {
+        match raw {
+            RawEffectRange::Fixed((min, max)) => Ok(Self::Fixed(min, max)),
+            RawEffectRange::Keyword(k) if k == "adaptive" => Ok(Self::Adaptive),
+            RawEffectRange::Keyword(k) => Err(format!(
+                "Invalid range `{}`. Expected [min, max] or `adaptive`",
+                k
+            )),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// Palette
+//------------------------------------------------------------------------------
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+pub struct PaletteConfig {
+    /// Path to a reference image, relative to the control script
+    pub path: String,
+    /// Number of colors to extract
+    #[serde(default = "default_palette_colors")]
+    pub colors: usize,
+    #[serde(default)]
+    pub sort: PaletteSort,
+}
+
+#[cfg(feature = "runtime")]
+fn default_palette_colors() -> usize {
+    5
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaletteSort {
+    /// Most dominant color first
+    #[default]
+    Weight,
+    /// Darkest color first
+    Lightness,
+}
+
+//------------------------------------------------------------------------------
+// Post Processing
+//------------------------------------------------------------------------------
+
+/// The top-level `post` key. Not a control: a list of single-key maps applied
+/// to the final frame in the order declared, e.g.
+///
+/// ```yaml
+/// post:
+///   - bloom:
+///       intensity: $glow
+///   - vignette: {}
+/// ```
+pub const POST_KEY: &str = "post";
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Debug)]
+pub enum PostEffectConfig {
+    Bloom(BloomConfig),
+    Grain(GrainConfig),
+    Vignette(VignetteConfig),
+    ChromaticAberration(ChromaticAberrationConfig),
+    Lut(LutConfig),
+    PixelSort(PixelSortConfig),
+    Datamosh(DatamoshConfig),
+    ScanlineGlitch(ScanlineGlitchConfig),
+}
+
+#[cfg(feature = "runtime")]
+impl PostEffectConfig {
+    pub fn parse_list(
+        value: &serde_yml::Value,
+    ) -> Result<Vec<Self>, Box<dyn Error>> {
+        let items = value
+            .as_sequence()
+            .ok_or("`post` must be a list of effects")?;
+
+        items.iter().map(Self::parse).collect()
+    }
+
+    fn parse(item: &serde_yml::Value) -> Result<Self, Box<dyn Error>> {
+        let (name, params) = item
+            .as_mapping()
+            .filter(|mapping| mapping.len() == 1)
+            .and_then(|mapping| mapping.iter().next())
+            .ok_or(
+                "Each `post` entry must be a single `effect: {params}` map",
+            )?;
+
+        let name = name.as_str().ok_or("Post effect name must be a string")?;
+
+        // Allows `- vignette:` without any params
+        let params = ternary!(
+            params.is_null(),
+            serde_yml::Value::Mapping(serde_yml::Mapping::new()),
+            params.clone()
+        );
+
+        Ok(match name {
+            "bloom" => Self::Bloom(serde_yml::from_value(params)?),
+            "grain" => Self::Grain(serde_yml::from_value(params)?),
+            "vignette" => Self::Vignette(serde_yml::from_value(params)?),
+            "chromatic_aberration" => {
+                Self::ChromaticAberration(serde_yml::from_value(params)?)
+            }
+            "lut" => Self::Lut(serde_yml::from_value(params)?),
+            "pixel_sort" => Self::PixelSort(serde_yml::from_value(params)?),
+            "datamosh" => Self::Datamosh(serde_yml::from_value(params)?),
+            "scanline_glitch" => {
+                Self::ScanlineGlitch(serde_yml::from_value(params)?)
+            }
+            _ => return Err(format!("Unknown post effect: {}", name).into()),
+        })
+    }
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BloomConfig {
+    /// Brightness above which pixels start to glow
+    #[serde(default = "default_param_value_0_7")]
+    pub threshold: ParamValue,
+    #[serde(default = "default_param_value_0_5")]
+    pub intensity: ParamValue,
+    /// Blur tap spacing in pixels
+    #[serde(default = "default_param_value_4")]
+    pub radius: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GrainConfig {
+    #[serde(default = "default_param_value_0_05")]
+    pub amount: ParamValue,
+    /// Grain size in pixels
+    #[serde(default = "default_param_value_1")]
+    pub size: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct VignetteConfig {
+    #[serde(default = "default_param_value_0_5")]
+    pub amount: ParamValue,
+    /// 0 is a hard edge at the corners, 1 starts darkening from the center
+    #[serde(default = "default_param_value_0_5")]
+    pub softness: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ChromaticAberrationConfig {
+    /// Red/blue channel offset in pixels at the edges of the frame
+    #[serde(default = "default_param_value_2")]
+    pub amount: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LutConfig {
+    /// Path to a `.cube` file, relative to the control script
+    pub path: String,
+    /// 0 bypasses the grade, 1 applies it fully
+    #[serde(default = "default_param_value_1")]
+    pub strength: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PixelSortConfig {
+    /// Only runs of pixels brighter than this are sorted
+    #[serde(default = "default_param_value_0_5")]
+    pub threshold: ParamValue,
+    /// The direction brighter pixels move in
+    #[serde(default)]
+    pub direction: PixelSortDirection,
+    /// Sort passes per frame. Pixels move at most this many pixels, so more
+    /// passes make longer streaks
+    #[serde(default = "default_param_value_32")]
+    pub iterations: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelSortDirection {
+    Up,
+    #[default]
+    Down,
+    Left,
+    Right,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DatamoshConfig {
+    /// Largest block displacement in pixels
+    #[serde(default = "default_param_value_20")]
+    pub amount: ParamValue,
+    /// Block size in pixels
+    #[serde(default = "default_param_value_16")]
+    pub block_size: ParamValue,
+    /// Chance of each block being displaced
+    #[serde(default = "default_param_value_0_3")]
+    pub probability: ParamValue,
+    /// Frames between picking new blocks
+    #[serde(default = "default_param_value_4")]
+    pub hold: ParamValue,
+}
+
+#[cfg(feature = "runtime")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ScanlineGlitchConfig {
+    /// Largest horizontal tear in pixels
+    #[serde(default = "default_param_value_20")]
+    pub amount: ParamValue,
+    /// Chance of each line tearing on a given frame
+    #[serde(default = "default_param_value_0_1")]
+    pub frequency: ParamValue,
+    /// Height of each tearing line in pixels
+    #[serde(default = "default_param_value_4")]
+    pub line_height: ParamValue,
+    /// Darkening of alternate pixel rows, 0 to 1
+    #[serde(default = "default_param_value_0_25")]
+    pub scanlines: ParamValue,
+}
+
+//------------------------------------------------------------------------------
+// Sketch Inputs
+//------------------------------------------------------------------------------
+
+/// The top-level `inputs` key. Not a control: a map of `texture name => sketch
+/// name` whose rendered output the runtime makes available through
+/// [`crate::prelude::Context::input`], e.g.
+///
+/// ```yaml
+/// inputs:
+///   source: shader_to_texture_dev
+/// ```
+pub const INPUTS_KEY: &str = "inputs";
+
+pub fn parse_inputs(
+    value: &serde_yml::Value,
+) -> Result<IndexMap<String, String>, Box<dyn Error>> {
+    serde_yml::from_value(value.clone()).map_err(|_| {
+        "`inputs` must be a map of `texture name: sketch name`".into()
+    })
+}
+
+//------------------------------------------------------------------------------
+// Renames
+//------------------------------------------------------------------------------
+
+/// The top-level `renames` key. Not a control: a map of `old name => new name`
+/// used to migrate saved values, snapshots, and MIDI mappings after renaming
+/// controls, e.g.
+///
+/// ```yaml
+/// renames:
+///   radius: size
+/// ```
+pub const RENAMES_KEY: &str = "renames";
+
+pub fn parse_renames(
+    value: &serde_yml::Value,
+) -> Result<IndexMap<String, String>, Box<dyn Error>> {
+    serde_yml::from_value(value.clone())
+        .map_err(|_| "`renames` must be a map of `old name: new name`".into())
+}
+
+//------------------------------------------------------------------------------
+// Repeat
+//------------------------------------------------------------------------------
+
+/// A top-level entry with a `repeat` count and a map of `controls` instead of a
+/// `type`. Each control is declared once per index so N similar entities don't
+/// need copy-pasted definitions, e.g.
+///
+/// ```yaml
+/// quad:
+///   repeat: 4
+///   controls:
+///     speed:
+///       type: slider
+///     wobble:
+///       type: triangle
+///       beats: $speed
+/// ```
+///
+/// declares `quad_1.speed`, `quad_1.wobble` through `quad_4.wobble`, where
+/// each `$speed` refers to the speed of the same index. See [`indexed_name`]
+#[derive(Clone, Deserialize, Debug)]
+pub struct RepeatConfig {
+    pub repeat: usize,
+    pub controls: IndexMap<String, serde_yml::Value>,
+}
+
+pub const REPEAT_KEY: &str = "repeat";
+
+/// The name of `name` in the `index`th (zero-based) instance of the `group`
+/// repeat. Instances are numbered from 1 in the script and UI
+pub fn indexed_name(group: &str, index: usize, name: &str) -> String {
+    format!("{}_{}.{}", group, index + 1, name)
+}
+
+/// The count of the `repeat` entry `value`, if it is one
+pub fn repeat_count(value: &serde_yml::Value) -> Option<usize> {
+    if value.get("type").is_some() {
+        return None;
+    }
+    value.get(REPEAT_KEY)?.as_u64().map(|count| count as usize)
+}
+
+/// Declares the controls of every [`RepeatConfig`] in `config` right after
+/// its entry, which is kept so the hub can tell how many instances there are
+pub fn expand_repeats(
+    config: serde_yml::Value,
+) -> Result<serde_yml::Value, Box<dyn Error>> {
+    let serde_yml::Value::Mapping(mapping) = config else {
+        return Ok(config);
+    };
+
+    let mut expanded = serde_yml::Mapping::new();
+
+    for (key, value) in mapping {
+        let group = key.as_str().map(str::to_string);
+        let repeat = repeat_count(&value);
+        expanded.insert(key, value.clone());
+
+        let (Some(group), Some(_)) = (group, repeat) else {
+            continue;
+        };
+
+        let conf: RepeatConfig = serde_yml::from_value(value)
+            .map_err(|e| format!("Invalid repeat `{}`: {}", group, e))?;
+
+        for index in 0..conf.repeat {
+            for (name, control) in &conf.controls {
+                let full_name = indexed_name(&group, index, name);
+                if expanded.contains_key(full_name.as_str()) {
+                    return Err(format!(
+                        "`{}` of repeat `{}` is already declared",
+                        full_name, group
+                    )
+                    .into());
+                }
+                let control = resolve_siblings(control.clone(), &|sibling| {
+                    conf.controls
+                        .contains_key(sibling)
+                        .then(|| indexed_name(&group, index, sibling))
+                });
+                expanded.insert(full_name.into(), control);
+            }
+        }
+    }
+
+    Ok(serde_yml::Value::Mapping(expanded))
+}
+
+/// Rewrites `$name` references for which `resolve` returns a name
+fn resolve_siblings(
+    value: serde_yml::Value,
+    resolve: &impl Fn(&str) -> Option<String>,
+) -> serde_yml::Value {
+    match value {
+        serde_yml::Value::String(s) => {
+            let resolved = s
+                .strip_prefix('$')
+                .and_then(resolve)
+                .map_or(s, |name| format!("${}", name));
+            serde_yml::Value::String(resolved)
+        }
+        serde_yml::Value::Sequence(items) => serde_yml::Value::Sequence(
+            items
+                .into_iter()
+                .map(|item| resolve_siblings(item, resolve))
+                .collect(),
+        ),
+        serde_yml::Value::Mapping(mapping) => serde_yml::Value::Mapping(
+            mapping
+                .into_iter()
+                .map(|(k, v)| (k, resolve_siblings(v, resolve)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
 //------------------------------------------------------------------------------
 // Disabled Impl
 //------------------------------------------------------------------------------
@@ -672,6 +1511,9 @@ fn default_iterations() -> usize {
 fn default_normalized_range() -> (f32, f32) {
     (0.0, 1.0)
 }
+fn default_range_decay() -> f32 {
+    AdaptiveRange::DEFAULT_DECAY
+}
 fn default_mode() -> String {
     "loop".to_string()
 }
@@ -702,9 +1544,37 @@ fn default_param_value_0_5() -> ParamValue {
 fn default_param_value_0_7() -> ParamValue {
     ParamValue::Cold(0.7)
 }
+#[cfg(feature = "runtime")]
+fn default_param_value_0_05() -> ParamValue {
+    ParamValue::Cold(0.05)
+}
+#[cfg(feature = "runtime")]
+fn default_param_value_0_1() -> ParamValue {
+    ParamValue::Cold(0.1)
+}
 fn default_param_value_0() -> ParamValue {
     ParamValue::Cold(0.0)
 }
 fn default_param_value_1() -> ParamValue {
     ParamValue::Cold(1.0)
 }
+#[cfg(feature = "runtime")]
+fn default_param_value_2() -> ParamValue {
+    ParamValue::Cold(2.0)
+}
+#[cfg(feature = "runtime")]
+fn default_param_value_4() -> ParamValue {
+    ParamValue::Cold(4.0)
+}
+#[cfg(feature = "runtime")]
+fn default_param_value_16() -> ParamValue {
+    ParamValue::Cold(16.0)
+}
+#[cfg(feature = "runtime")]
+fn default_param_value_20() -> ParamValue {
+    ParamValue::Cold(20.0)
+}
+#[cfg(feature = "runtime")]
+fn default_param_value_32() -> ParamValue {
+    ParamValue::Cold(32.0)
+}
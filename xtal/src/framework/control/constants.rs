@@ -0,0 +1,361 @@
+//! Plain values for tuning a sketch that don't deserve a place in the control
+//! panel. They live in a YAML file next to the control script named after it,
+//! e.g. `my_sketch.constants.yaml` for `my_sketch.yaml`, and hot-reload like
+//! the script but never generate UI controls. Read them with
+//! [`ControlHub::constant`] and friends.
+//!
+//! # Example
+//! ```yaml
+//! radius: 0.25
+//! mirror: true
+//! blend: multiply
+//! offsets: [0.0, 0.5, 1.5]
+//! # Hex (#rgb, #rrggbb, #rrggbbaa) or [r, g, b(, a)] in [0, 1]
+//! colors: ["#ff8800", "#0088ffcc", [0.2, 0.2, 0.2]]
+//! ```
+//!
+//! [`ControlHub::constant`]: super::ControlHub::constant
+
+use notify::{Event, RecursiveMode, Watcher};
+use serde_yml::Value;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Float(f32),
+    Bool(bool),
+    String(String),
+    Vec(Vec<f32>),
+    /// RGBA in [0, 1]
+    Colors(Vec<[f32; 4]>),
+}
+
+impl Constant {
+    fn from_yaml(name: &str, value: &Value) -> Result<Self, Box<dyn Error>> {
+        match value {
+            Value::Number(n) => n
+                .as_f64()
+                .map(|n| Self::Float(n as f32))
+                .ok_or_else(|| format!("`{}`: invalid number", name).into()),
+            Value::Bool(b) => Ok(Self::Bool(*b)),
+            Value::String(s) => Ok(Self::String(s.clone())),
+            Value::Sequence(items) => Self::from_sequence(name, items),
+            _ => Err(format!(
+                "`{}`: constants must be numbers, bools, strings, lists of \
+                numbers, or lists of colors",
+                name
+            )
+            .into()),
+        }
+    }
+
+    fn from_sequence(
+        name: &str,
+        items: &[Value],
+    ) -> Result<Self, Box<dyn Error>> {
+        if items.iter().all(Value::is_number) {
+            return Ok(Self::Vec(
+                items
+                    .iter()
+                    .filter_map(Value::as_f64)
+                    .map(|n| n as f32)
+                    .collect(),
+            ));
+        }
+
+        items
+            .iter()
+            .map(|item| match item {
+                Value::String(hex) => parse_hex(hex),
+                Value::Sequence(rgba) => parse_rgba(rgba),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Self::Colors)
+            .ok_or_else(|| {
+                format!(
+                    "`{}`: lists must hold only numbers or only colors",
+                    name
+                )
+                .into()
+            })
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Self::Float(n) => Some(*n),
+            Self::Bool(b) => Some(bool_to_f32(*b)),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_vec(&self) -> Option<&[f32]> {
+        match self {
+            Self::Vec(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_colors(&self) -> Option<&[[f32; 4]]> {
+        match self {
+            Self::Colors(c) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<[f32; 4]> {
+    let hex = hex.strip_prefix('#')?;
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+
+    let channels: Vec<u8> = match digits.len() {
+        3 | 4 => digits.iter().map(|d| d * 17).collect(),
+        6 | 8 => digits.chunks(2).map(|c| c[0] * 16 + c[1]).collect(),
+        _ => return None,
+    };
+
+    let channel = |i: usize| channels.get(i).map_or(1.0, |c| *c as f32 / 255.0);
+    Some([channel(0), channel(1), channel(2), channel(3)])
+}
+
+fn parse_rgba(rgba: &[Value]) -> Option<[f32; 4]> {
+    let values: Vec<f32> = rgba
+        .iter()
+        .map(|v| v.as_f64().map(|n| n as f32))
+        .collect::<Option<_>>()?;
+
+    match values[..] {
+        [r, g, b] => Some([r, g, b, 1.0]),
+        [r, g, b, a] => Some([r, g, b, a]),
+        _ => None,
+    }
+}
+
+pub fn parse_constants(
+    yaml: &str,
+) -> Result<HashMap<String, Constant>, Box<dyn Error>> {
+    let value: Value = serde_yml::from_str(yaml)?;
+
+    let mapping = match value {
+        Value::Null => return Ok(HashMap::default()),
+        Value::Mapping(mapping) => mapping,
+        _ => return Err("Constants must be a mapping of name: value".into()),
+    };
+
+    mapping
+        .iter()
+        .map(|(key, value)| -> Result<_, Box<dyn Error>> {
+            let name = key
+                .as_str()
+                .ok_or_else(|| format!("Invalid constant name: {:?}", key))?;
+            Ok((name.to_string(), Constant::from_yaml(name, value)?))
+        })
+        .collect()
+}
+
+/// Where [`Constants`] for the control script at `script_path` live
+pub fn constants_path(script_path: &Path) -> PathBuf {
+    script_path.with_extension("constants.yaml")
+}
+
+#[derive(Debug)]
+struct UpdateState {
+    #[allow(dead_code)]
+    watcher: notify::RecommendedWatcher,
+    values: Arc<Mutex<Option<HashMap<String, Constant>>>>,
+    has_changes: Arc<AtomicBool>,
+}
+
+/// Constants loaded from disk that reload whenever the file changes. A reload
+/// that fails to parse is logged and the previous values are kept
+#[derive(Debug, Default)]
+pub struct Constants {
+    values: HashMap<String, Constant>,
+    update_state: Option<UpdateState>,
+}
+
+impl Constants {
+    pub fn from_yaml(yaml: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            values: parse_constants(yaml)?,
+            update_state: None,
+        })
+    }
+
+    pub fn from_path(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let values = parse_constants(&fs::read_to_string(&path)?)?;
+        let state = Arc::new(Mutex::new(None));
+        let has_changes = Arc::new(AtomicBool::new(false));
+
+        Ok(Self {
+            values,
+            update_state: Some(UpdateState {
+                watcher: Self::setup_watcher(
+                    path,
+                    state.clone(),
+                    has_changes.clone(),
+                ),
+                values: state,
+                has_changes,
+            }),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Constant> {
+        self.values.get(name)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Swaps in reloaded values (if any). Returns true when they changed
+    pub fn update(&mut self) -> bool {
+        let Some(update_state) = &self.update_state else {
+            return false;
+        };
+
+        if !update_state.has_changes.swap(false, Ordering::AcqRel) {
+            return false;
+        }
+
+        let new_values = update_state
+            .values
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take());
+
+        match new_values {
+            Some(values) if values != self.values => {
+                self.values = values;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn setup_watcher(
+        path: PathBuf,
+        state: Arc<Mutex<Option<HashMap<String, Constant>>>>,
+        has_changes: Arc<AtomicBool>,
+    ) -> notify::RecommendedWatcher {
+        let path_to_watch = path.clone();
+
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let event: Event = match res {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+
+            if event.kind
+                != notify::EventKind::Modify(notify::event::ModifyKind::Data(
+                    notify::event::DataChange::Content,
+                ))
+            {
+                return;
+            }
+
+            info!("{:?} changed. Attempting to reload constants.", path);
+
+            let values = fs::read_to_string(&path)
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|yaml| parse_constants(&yaml));
+
+            match values {
+                Ok(values) => {
+                    if let Ok(mut guard) = state.lock() {
+                        has_changes.store(true, Ordering::Release);
+                        *guard = Some(values);
+                    }
+                }
+                Err(e) => error!(
+                    "Failed to reload constants: {}. Keeping previous values.",
+                    e
+                ),
+            }
+        })
+        .expect("Failed to create watcher");
+
+        watcher
+            .watch(&path_to_watch, RecursiveMode::NonRecursive)
+            .expect("Failed to start watching constants file");
+
+        watcher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_constants() {
+        let constants = parse_constants(
+            r##"
+radius: 0.25
+count: 3
+mirror: true
+blend: multiply
+offsets: [0.0, 0.5, 1.5]
+colors: ["#ff8800", "#0088ffcc", "#fff", [0.2, 0.2, 0.2]]
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(constants["radius"], Constant::Float(0.25));
+        assert_eq!(constants["count"], Constant::Float(3.0));
+        assert_eq!(constants["mirror"], Constant::Bool(true));
+        assert_eq!(constants["blend"], Constant::String("multiply".into()));
+        assert_eq!(constants["offsets"], Constant::Vec(vec![0.0, 0.5, 1.5]));
+        assert_eq!(
+            constants["colors"],
+            Constant::Colors(vec![
+                [1.0, 136.0 / 255.0, 0.0, 1.0],
+                [0.0, 136.0 / 255.0, 1.0, 204.0 / 255.0],
+                [1.0, 1.0, 1.0, 1.0],
+                [0.2, 0.2, 0.2, 1.0],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_constants_errors() {
+        assert!(parse_constants("").unwrap().is_empty());
+        assert!(parse_constants("- 1\n- 2").is_err());
+        assert!(parse_constants("a: { b: 1 }").is_err());
+        assert!(parse_constants("a: [1, '#fff']").is_err());
+        assert!(parse_constants("a: ['#ffff0']").is_err());
+        assert!(parse_constants("a: [[1, 2]]").is_err());
+    }
+
+    #[test]
+    fn test_constants_path() {
+        assert_eq!(
+            constants_path(Path::new("/sketches/my_sketch.yaml")),
+            PathBuf::from("/sketches/my_sketch.constants.yaml")
+        );
+    }
+}
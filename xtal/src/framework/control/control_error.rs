@@ -0,0 +1,44 @@
+//! Errors returned by the `try_*` getters of [`ControlHub`], and raised as
+//! panics by its plain getters in strict mode – see
+//! [`ControlHub::set_strict`].
+//!
+//! [`ControlHub`]: super::ControlHub
+//! [`ControlHub::set_strict`]: super::ControlHub::set_strict
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlError {
+    /// No control, animation, or `var` alias has this name
+    Unknown(String),
+
+    /// The control exists but holds another kind of value, e.g. calling
+    /// `try_bool` on a slider
+    WrongType {
+        name: String,
+        expected: &'static str,
+    },
+}
+
+impl ControlError {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Unknown(name) => name,
+            Self::WrongType { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "No control named `{}`", name),
+            Self::WrongType { name, expected } => {
+                write!(f, "Control `{}` is not a {}", name, expected)
+            }
+        }
+    }
+}
+
+impl Error for ControlError {}
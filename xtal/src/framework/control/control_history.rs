@@ -0,0 +1,133 @@
+//! Short per-control history of the values the sketch actually received, after
+//! animation, modulation, effects, transitions and bypasses. Recording is
+//! passive – values are captured as [`ControlHub::get`] is called – so
+//! tracking a control never changes how or how often it is evaluated. Meant to
+//! feed UI sparklines when debugging modulation stacks.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::framework::prelude::*;
+
+/// Samples kept per control (~2 seconds at 60fps)
+pub const HISTORY_LENGTH: usize = 120;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum HistoryTracking {
+    #[default]
+    Off,
+    All,
+    Only(Vec<String>),
+}
+
+impl HistoryTracking {
+    fn includes(&self, name: &str) -> bool {
+        match self {
+            Self::Off => false,
+            Self::All => true,
+            Self::Only(names) => names.iter().any(|n| n == name),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ControlHistory {
+    tracking: HistoryTracking,
+    /// `name => (frame of the last sample, samples oldest first)`
+    samples: HashMap<String, (u32, VecDeque<f32>)>,
+}
+
+impl ControlHistory {
+    pub fn tracking(&self) -> &HistoryTracking {
+        &self.tracking
+    }
+
+    pub fn set_tracking(&mut self, tracking: HistoryTracking) {
+        self.samples.retain(|name, _| tracking.includes(name));
+        self.tracking = tracking;
+    }
+
+    /// Keeps one sample per control per frame: the last value read
+    pub fn record(&mut self, name: &str, frame: u32, value: f32) {
+        if !self.tracking.includes(name) {
+            return;
+        }
+
+        match self.samples.get_mut(name) {
+            Some((last_frame, samples)) => {
+                if *last_frame == frame {
+                    if let Some(last) = samples.back_mut() {
+                        *last = value;
+                    }
+                    return;
+                }
+                *last_frame = frame;
+                if samples.len() == HISTORY_LENGTH {
+                    samples.pop_front();
+                }
+                samples.push_back(value);
+            }
+            None => {
+                self.samples
+                    .insert(name.to_string(), (frame, VecDeque::from([value])));
+            }
+        }
+    }
+
+    /// Samples per control, oldest first
+    pub fn values(&self) -> HashMap<String, Vec<f32>> {
+        self.samples
+            .iter()
+            .map(|(name, (_, samples))| {
+                (name.clone(), samples.iter().copied().collect())
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut history = ControlHistory::default();
+        history.record("a", 0, 1.0);
+        assert!(history.values().is_empty());
+
+        history.set_tracking(HistoryTracking::Only(vec!["a".to_string()]));
+        history.record("a", 0, 0.1);
+        history.record("a", 0, 0.2);
+        history.record("a", 1, 0.3);
+        history.record("b", 1, 0.5);
+
+        let values = history.values();
+        assert_eq!(values["a"], vec![0.2, 0.3]);
+        assert!(!values.contains_key("b"));
+    }
+
+    #[test]
+    fn test_capacity_and_tracking_changes() {
+        let mut history = ControlHistory::default();
+        history.set_tracking(HistoryTracking::All);
+
+        for frame in 0..(HISTORY_LENGTH as u32 + 10) {
+            history.record("a", frame, frame as f32);
+            history.record("b", frame, 0.0);
+        }
+
+        let a = &history.values()["a"];
+        assert_eq!(a.len(), HISTORY_LENGTH);
+        assert_eq!(a[0], 10.0);
+
+        history.set_tracking(HistoryTracking::Only(vec!["b".to_string()]));
+        assert_eq!(history.values().len(), 1);
+
+        history.set_tracking(HistoryTracking::Off);
+        assert!(history.values().is_empty());
+    }
+}
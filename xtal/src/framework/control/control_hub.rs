@@ -4,26 +4,44 @@
 //!
 //! [ref]: https://github.com/Lokua/xtal/blob/main/docs/control_script_reference.md
 
-use nannou::rand::{Rng, thread_rng};
+use indexmap::IndexMap;
+use nannou_core::math::map_range;
+use nannou_core::rand::{Rng, thread_rng};
 use notify::{Event, RecursiveMode, Watcher};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
 use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::trace_span;
 use yaml_merge_keys::merge_keys_serde_yml;
 
 use super::config::*;
+use super::constants::{Constant, Constants, constants_path};
+use super::control_history::{ControlHistory, HistoryTracking};
+use super::debug_graph::{DebugEdge, DebugGraph, DebugNode};
 use super::dep_graph::{DepGraph, Node};
-use super::eval_cache::EvalCache;
+use super::eval_cache::{EvalCache, EvalPosition};
 use super::param_mod::{FromColdParams, ParamValue, SetFromParam};
+use super::script_error::ScriptError;
 
 #[cfg(feature = "instrumentation")]
 use crate::framework::instrumentation::Instrumentation;
-
+#[cfg(feature = "runtime")]
+use crate::framework::lut::HotLut;
+use crate::framework::midi_file::MidiFile;
+#[cfg(feature = "runtime")]
+use crate::framework::palette::Palette;
+#[cfg(feature = "runtime")]
+use crate::framework::post::PostEffect;
+use crate::framework::spans;
 use crate::framework::{frame_controller, prelude::*};
+use crate::runtime::global;
 use crate::runtime::map_mode::MapMode;
 use crate::runtime::serialization::TransitorySketchState;
 
@@ -32,26 +50,178 @@ pub const TRANSITION_TIMES: [f32; 15] = [
     0.25,
 ];
 
+/// The profile of the script passed to [`ControlHub::from_path`]. See
+/// [`ControlHub::with_profile`]
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// How many times [`ControlHub::update`] runs after the controls are populated
+/// before names that failed to resolve are reported. By the second update the
+/// sketch has been through a full update and view
+const AUDIT_AFTER_UPDATES: u32 = 2;
+
+/// The outcome of reading and parsing a changed script on the watcher thread
+type Reload = Result<ConfigFile, ScriptError>;
+
 #[derive(Debug)]
 struct UpdateState {
     #[allow(dead_code)]
     watcher: notify::RecommendedWatcher,
-    state: Arc<Mutex<Option<ConfigFile>>>,
+    path: PathBuf,
+    state: Arc<Mutex<Option<Reload>>>,
 
     /// Optimization to speed up checking for changes vs having to acquire a
     /// lock on the above state mutex
     has_changes: Arc<AtomicBool>,
 }
 
+/// Rescales what [`ControlHub::get`] returns for a control with a `map_to`
+/// field
+#[derive(Debug)]
+struct OutputMap {
+    domain: [f32; 2],
+    range: [f32; 2],
+    easing: Easing,
+}
+
+impl OutputMap {
+    fn apply(&self, value: f32) -> f32 {
+        let [d_min, d_max] = self.domain;
+        if d_min == d_max {
+            return self.range[0];
+        }
+        let t = (value - d_min) / (d_max - d_min);
+        // Linear mapping is left unclamped so modulation that overshoots the
+        // domain still overshoots the range
+        let t = match self.easing {
+            Easing::Linear => t,
+            _ => self.easing.apply(t.clamp(0.0, 1.0)),
+        };
+        lerp(self.range[0], self.range[1], t)
+    }
+}
+
+/// A control latched by [`ControlHub::set_hold`]
+#[derive(Clone, Copy, Debug)]
+struct Hold {
+    /// What [`ControlHub::get`] returned, including modulation and `map_to`
+    value: f32,
+    /// The unmodulated value other controls read when using this one as a
+    /// modulator or parameter
+    raw: f32,
+}
+
+/// Band-limited noise added to a control with a `humanize` field. The noise
+/// is a function of the beat, so it freezes while paused and replays
+/// identically when scrubbing
+struct Humanizer {
+    amplitude: f32,
+    rate: f32,
+    noise: PerlinNoise,
+}
+
+impl Humanizer {
+    fn new(name: &str, amplitude: f32, rate: f32) -> Self {
+        let seed = name.bytes().fold(2_166_136_261_u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(16_777_619)
+        });
+        Self {
+            amplitude,
+            rate,
+            noise: PerlinNoise::new(seed),
+        }
+    }
+
+    fn apply(&self, value: f32, beats: f32) -> f32 {
+        // Perlin noise is zero on integer lattice points so sample off-grid
+        value + self.noise.get([beats * self.rate, 0.5]) * self.amplitude
+    }
+}
+
+impl fmt::Debug for Humanizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Humanizer")
+            .field("amplitude", &self.amplitude)
+            .field("rate", &self.rate)
+            .finish()
+    }
+}
+
+/// One destination of a `macro` control
+#[derive(Debug)]
+struct MacroTarget {
+    range: [f32; 2],
+    easing: Easing,
+    invert: bool,
+}
+
+impl MacroTarget {
+    fn apply(&self, input: f32) -> f32 {
+        let t = input.clamp(0.0, 1.0);
+        let t = ternary!(self.invert, 1.0 - t, t);
+        lerp(self.range[0], self.range[1], self.easing.apply(t))
+    }
+}
+
 #[derive(Debug)]
 struct SnapshotTransition {
     values: HashMap<String, (f32, f32)>,
     start_frame: u32,
-    end_frame: u32,
+    /// In frames, left fractional so short transitions at high frame rates
+    /// aren't rounded down
+    duration: f32,
+}
+
+impl SnapshotTransition {
+    fn new(start_frame: u32, duration: f32) -> Self {
+        Self {
+            values: HashMap::default(),
+            start_frame,
+            duration,
+        }
+    }
+
+    /// How far through the transition `frame` is; exceeds 1 once it has
+    /// ended
+    fn progress(&self, frame: u32) -> f32 {
+        if self.duration <= 0.0 {
+            return f32::INFINITY;
+        }
+        frame.saturating_sub(self.start_frame) as f32 / self.duration
+    }
+}
+
+/// Tracks everything that should cause a re-render when the frame controller
+/// is in on-demand mode. See [`PlayMode::OnDemand`]
+#[derive(Debug, Default)]
+struct DirtyTracker {
+    /// Set whenever [`ControlHub::get`] resolves a value that changes on its
+    /// own over time (animations and audio)
+    time_varying: Cell<bool>,
+    ui_values: ControlValues,
+    midi_values: HashMap<String, f32>,
+    osc_values: HashMap<String, f32>,
 }
 
 pub type Snapshots = HashMap<String, ControlValues>;
 
+/// Map of `snapshot id => animation name => beats` recording where each
+/// animation was when the snapshot was taken. See
+/// [`ControlHub::set_snapshot_phase`]
+pub type SnapshotPhases = HashMap<String, HashMap<String, f32>>;
+
+/// How one control differs between two snapshots. See
+/// [`ControlHub::diff_snapshots`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotDelta {
+    pub name: String,
+    /// `None` when the first snapshot doesn't hold the control
+    pub a: Option<ControlValue>,
+    /// `None` when the second snapshot doesn't hold the control
+    pub b: Option<ControlValue>,
+    /// `b - a` when both values are floats
+    pub delta: Option<f32>,
+}
+
 pub type Exclusions = Vec<String>;
 
 struct Callback(Box<dyn Fn()>);
@@ -82,25 +252,167 @@ pub struct ControlHub<T: TimingSource> {
     pub osc_controls: OscControls,
     pub audio_controls: AudioControls,
     pub snapshots: Snapshots,
+    pub snapshot_phases: SnapshotPhases,
     pub midi_proxies_enabled: bool,
     animations: HashMap<String, (AnimationConfig, KeyframeSequence)>,
-    modulations: HashMap<String, Vec<String>>,
+    /// Map of `source => [(mod name, route)]`
+    modulations: HashMap<String, Vec<(String, ModulationRoute)>>,
     effects: RefCell<HashMap<String, (EffectConfig, Effect)>>,
 
-    /// Map of `var => name` Used to allow `get` to be called with the name used
-    /// in a YAML `var` field. See ./docs/control_script_reference.md **Using
-    /// `var`** section for more info.
+    /// Map of `var => name` Used to allow `get` to be called with any of the
+    /// names used in a YAML `var` field. See ./docs/control_script_reference.md
+    /// **Using `var`** section for more info.
     vars: HashMap<String, String>,
     bypassed: HashMap<String, Option<f32>>,
+
+    /// Declared with the `help` (or `tooltip`) field of any control. See
+    /// [`Self::describe`]
+    help: HashMap<String, String>,
+
+    /// Declared with the `unit` and `display` fields of any control. See
+    /// [`Self::display`]
+    displays: HashMap<String, ValueDisplay>,
+
+    /// Map of `group => control names` declared with the `group` field of any
+    /// control. See [`Self::group`]
+    groups: IndexMap<String, Vec<String>>,
+
+    /// Map of `name => held value` for names bypassed at runtime. See
+    /// [`Self::set_runtime_bypass`]
+    runtime_bypassed: HashMap<String, f32>,
+
+    /// See [`Self::set_hold`]
+    holds: HashMap<String, Hold>,
+
+    /// See [`Self::set_solo`]
+    solo: Option<String>,
+
+    /// See [`Self::set_slider_range`]
+    slider_ranges: HashMap<String, SliderRange>,
+
+    /// Declared with the `map_to` field. See [`OutputMap`]
+    output_maps: HashMap<String, OutputMap>,
+
+    /// Declared with the `humanize` field. See [`Humanizer`]
+    humanizers: HashMap<String, Humanizer>,
+
+    /// Declared with the `stepped` field. See [`Self::get_transition_value`]
+    stepped: HashMap<String, Stepped>,
+
+    /// Declared with the `smooth` field of MIDI and OSC controls. See
+    /// [`Self::update_smoothing`]
+    smoothing: HashMap<String, SmoothConfig>,
+
+    /// Map of `target => [(macro name, target config)]` declared with the
+    /// `macro` control type. See [`Self::apply_macros`]
+    macro_targets: HashMap<String, Vec<(String, MacroTarget)>>,
     dep_graph: DepGraph,
     eval_cache: EvalCache,
+    /// `(frame, beats)` read from the timing source once per frame. See
+    /// [`Self::eval_position`]
+    frame_beats: Cell<Option<(u32, f32)>>,
     update_state: Option<UpdateState>,
     active_transition: Option<SnapshotTransition>,
     transition_time: f32,
+
+    /// Whether snapshots capture animation phase. See
+    /// [`Self::set_snapshot_phase`]
+    snapshot_phase: bool,
+
+    /// Map of `animation name => beats` each animation runs ahead of the
+    /// timing source since a snapshot restored its phase
+    phase_offsets: HashMap<String, f32>,
     snapshot_ended_callbacks: Vec<Callback>,
     populated_callbacks: Vec<Callback>,
+    script_error_callbacks: Vec<Callback>,
+    audio_device_callbacks: Vec<Callback>,
+
+    /// The configuration controls were last successfully populated from.
+    /// Restored when a hot-reloaded script fails to apply
+    last_good_config: Option<ConfigFile>,
+
+    /// Set while the hot-reloaded script is broken. See [`Self::script_error`]
+    script_error: Option<ScriptError>,
+
+    /// Panic instead of falling back to a default for unknown names. See
+    /// [`Self::set_strict`]
+    strict: bool,
+
+    /// Names requested through the getters that don't exist. See
+    /// [`Self::missing`]
+    missing: RefCell<BTreeSet<String>>,
+    updates_since_populate: u32,
+    trigger_bus: TriggerBus,
+
+    /// Map of `trigger name => (source control name, gate)` used to derive
+    /// discrete triggers from continuous values. See [`Self::gate`]
+    gates: HashMap<String, (String, Gate)>,
+    dirty_tracker: DirtyTracker,
+
+    /// Declared under the top-level `post` key. See [`Self::post_effects`]
+    #[cfg(feature = "runtime")]
+    post_effects: Vec<PostEffectConfig>,
+
+    /// Declared under the top-level `inputs` key. See [`Self::inputs`]
+    inputs: IndexMap<String, String>,
+
+    /// Declared under the top-level `renames` key. See [`Self::renames`]
+    renames: IndexMap<String, String>,
+
+    /// Map of `group => count` for entries declared with `repeat`. See
+    /// [`Self::get_indexed`]
+    repeats: HashMap<String, usize>,
+
+    /// Declared with the `palette` control type. See [`Self::palette`]
+    #[cfg(feature = "runtime")]
+    palettes: HashMap<String, Palette>,
+
+    /// Declared with the `midi_file` control type. Controls reading the same
+    /// file share one parsed copy, re-read whenever the script changes
+    midi_files: HashMap<String, (MidiFileConfig, Arc<MidiFile>)>,
+
+    /// Declared with the `midi_velocity` and `midi_note` control types
+    midi_note_controls: MidiNoteControls,
+
+    /// Declared with the `adsr` control type
+    envelopes: Envelopes,
+
+    /// One per slider. See [`InputArbiter`]
+    arbiters: HashMap<String, InputArbiter>,
+
+    /// Declared with the `osc` field of sliders
+    osc_inputs: OscInputs,
+
+    /// See [`Self::take_input_changes`]
+    inputs_changed: HashSet<String>,
+
+    /// Directory of the control script; relative image and LUT paths resolve
+    /// against it
+    script_dir: Option<PathBuf>,
+
+    /// Map of `profile name => control script` declared with
+    /// [`Self::with_profile`]. Empty unless the sketch declares profiles
+    profiles: IndexMap<String, PathBuf>,
+
+    /// See [`Self::set_profile`]
+    profile: String,
+
+    /// See [`Self::constant`]
+    constants: Constants,
+
+    /// LUTs referenced by `post`, keyed by resolved path. `None` marks a LUT
+    /// that failed to load; it is retried the next time the script changes
+    #[cfg(feature = "runtime")]
+    luts: HashMap<PathBuf, Option<HotLut>>,
+    history: RefCell<ControlHistory>,
+    /// See [`Self::start_value_recording`]
+    value_recorder: RefCell<Option<ValueRecorder>>,
+    /// See [`Self::start_replay`]
+    replay: Option<ValueReplay>,
     #[cfg(feature = "instrumentation")]
     instrumentation: RefCell<Instrumentation>,
+    #[cfg(feature = "instrumentation")]
+    modulator_instrumentation: RefCell<Instrumentation>,
 }
 
 impl<T: TimingSource> ControlHub<T> {
@@ -116,18 +428,70 @@ impl<T: TimingSource> ControlHub<T> {
             effects: RefCell::new(HashMap::default()),
             vars: HashMap::default(),
             bypassed: HashMap::default(),
+            help: HashMap::default(),
+            displays: HashMap::default(),
+            groups: IndexMap::default(),
+            runtime_bypassed: HashMap::default(),
+            holds: HashMap::default(),
+            solo: None,
+            slider_ranges: HashMap::default(),
+            output_maps: HashMap::default(),
+            humanizers: HashMap::default(),
+            stepped: HashMap::default(),
+            smoothing: HashMap::default(),
+            macro_targets: HashMap::default(),
             eval_cache: EvalCache::default(),
+            frame_beats: Cell::new(None),
             dep_graph: DepGraph::default(),
             update_state: None,
             snapshots: HashMap::default(),
+            snapshot_phases: HashMap::default(),
             active_transition: None,
             transition_time: 4.0,
+            snapshot_phase: false,
+            phase_offsets: HashMap::default(),
             snapshot_ended_callbacks: vec![],
             populated_callbacks: vec![],
+            script_error_callbacks: vec![],
+            audio_device_callbacks: vec![],
+            last_good_config: None,
+            script_error: None,
+            strict: false,
+            missing: RefCell::new(BTreeSet::new()),
+            updates_since_populate: 0,
+            trigger_bus: TriggerBus::default(),
+            gates: HashMap::default(),
+            dirty_tracker: DirtyTracker::default(),
+            #[cfg(feature = "runtime")]
+            post_effects: vec![],
+            inputs: IndexMap::default(),
+            renames: IndexMap::default(),
+            repeats: HashMap::default(),
+            #[cfg(feature = "runtime")]
+            palettes: HashMap::default(),
+            midi_files: HashMap::default(),
+            midi_note_controls: MidiNoteControls::default(),
+            envelopes: Envelopes::default(),
+            arbiters: HashMap::default(),
+            osc_inputs: OscInputs::default(),
+            inputs_changed: HashSet::default(),
+            script_dir: None,
+            profiles: IndexMap::default(),
+            profile: DEFAULT_PROFILE.to_string(),
+            constants: Constants::default(),
+            #[cfg(feature = "runtime")]
+            luts: HashMap::default(),
+            history: RefCell::new(ControlHistory::default()),
+            value_recorder: RefCell::new(None),
+            replay: None,
             midi_proxies_enabled: true,
             #[cfg(feature = "instrumentation")]
             instrumentation: RefCell::new(Instrumentation::new(
-                "ControlScript::get",
+                "ControlHub::get",
+            )),
+            #[cfg(feature = "instrumentation")]
+            modulator_instrumentation: RefCell::new(Instrumentation::new(
+                "ControlHub modulators",
             )),
         };
 
@@ -165,7 +529,22 @@ impl<T: TimingSource> ControlHub<T> {
         let file_content =
             fs::read_to_string(&path).expect("Unable to read file");
 
-        let mut script = Self::new(Some(&file_content), timing);
+        // Populated after `script_dir` is known so relative paths resolve
+        let mut script = Self::new(None, timing);
+        script.script_dir = path.parent().map(|dir| dir.to_path_buf());
+        let config =
+            Self::parse_from_str(&file_content).expect("Unable to parse yaml");
+        script
+            .populate_controls(&config)
+            .expect("Unable to populate controls");
+
+        let constants_path = constants_path(&path);
+        if constants_path.exists() {
+            if let Err(e) = script.load_constants(constants_path) {
+                error!("Unable to load constants: {}", e);
+            }
+        }
+
         let has_changes = Arc::new(AtomicBool::new(false));
 
         script.update_state = Some(UpdateState {
@@ -174,6 +553,7 @@ impl<T: TimingSource> ControlHub<T> {
                 state_clone,
                 has_changes.clone(),
             ),
+            path,
             state: state.clone(),
             has_changes,
         });
@@ -181,20 +561,262 @@ impl<T: TimingSource> ControlHub<T> {
         script
     }
 
+    /// Declares an alternative control script for the same sketch, e.g. a
+    /// reduced "live" surface next to the full "studio" one, that can be
+    /// switched to at runtime with [`Self::set_profile`]. The script passed to
+    /// [`Self::from_path`] is the [`DEFAULT_PROFILE`]
+    ///
+    /// # Example
+    /// ```rs
+    /// let hub = ControlHub::from_path(
+    ///     to_absolute_path(file!(), "my_sketch.yaml"),
+    ///     Timing::new(ctx.bpm()),
+    /// )
+    /// .with_profile("live", to_absolute_path(file!(), "my_sketch_live.yaml"));
+    /// ```
+    pub fn with_profile(mut self, name: &str, path: PathBuf) -> Self {
+        if self.profiles.is_empty() {
+            let Some(update_state) = &self.update_state else {
+                warn!(
+                    "Profiles require a hub created with `from_path`. \
+                    Ignoring profile `{}`",
+                    name
+                );
+                return self;
+            };
+            self.profiles
+                .insert(DEFAULT_PROFILE.to_string(), update_state.path.clone());
+        }
+        self.profiles.insert(name.to_string(), path);
+        self
+    }
+
+    /// Names of the declared profiles, starting with [`DEFAULT_PROFILE`].
+    /// Empty when the sketch declares none
+    pub fn profiles(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Swaps in another profile's control script. Values carry over wherever
+    /// control names match, just as they do when a script is hot-reloaded, and
+    /// the new script is watched for changes in place of the old one
+    pub fn set_profile(&mut self, name: &str) -> Result<(), String> {
+        if name == self.profile {
+            return Ok(());
+        }
+
+        let path = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("No profile named `{}`", name))?;
+        let config = Self::parse_from_path(&path).map_err(|e| e.to_string())?;
+
+        let state = Arc::new(Mutex::new(None));
+        let has_changes = Arc::new(AtomicBool::new(false));
+        self.update_state = Some(UpdateState {
+            watcher: Self::setup_watcher(
+                path.clone(),
+                state.clone(),
+                has_changes.clone(),
+            ),
+            path: path.clone(),
+            state,
+            has_changes,
+        });
+        self.script_dir = path.parent().map(|dir| dir.to_path_buf());
+        self.profile = name.to_string();
+        self.apply_reloaded_config(&config);
+
+        Ok(())
+    }
+
+    /// [`Self::get`] for `name` in the `index`th (zero-based) instance of a
+    /// group declared with `repeat`, e.g. `get_indexed("quad", 0, "strength")`
+    /// for `quad_1.strength`
+    pub fn get_indexed(&self, group: &str, index: usize, name: &str) -> f32 {
+        self.get(&indexed_name(group, index, name))
+    }
+
+    /// The number of instances of a group declared with `repeat`; 0 if there
+    /// is no such group
+    pub fn repeat_count(&self, group: &str) -> usize {
+        self.repeats.get(group).copied().unwrap_or(0)
+    }
+
     pub fn get(&self, name: &str) -> f32 {
+        // Checked here so disabled lookups skip the subscriber entirely
+        let _span = spans::is_enabled(spans::Subsystem::Hub).then(|| {
+            trace_span!(target: spans::HUB, "get", control = name).entered()
+        });
+
         #[cfg(feature = "instrumentation")]
         let start = self.instrumentation.borrow().start();
 
-        let current_frame = frame_controller::frame_count();
+        let position = self.eval_position();
+        let held = self.holds.get(self.canonical_name(name));
+        let replayed = self.replay.as_ref().and_then(|replay| {
+            replay.get(self.canonical_name(name), position.beats)
+        });
+        let value = match (held, replayed) {
+            (Some(hold), _) => hold.value,
+            (None, Some(value)) => {
+                self.dirty_tracker.time_varying.set(true);
+                value
+            }
+            (None, None) => {
+                let value = self.evaluate(name, position);
+                self.map_output(name, value)
+            }
+        };
+        self.history.borrow_mut().record(
+            self.canonical_name(name),
+            position.frame,
+            value,
+        );
+        if let Some(recorder) = self.value_recorder.borrow_mut().as_mut() {
+            recorder.record(self.canonical_name(name), value);
+        }
+
+        #[cfg(feature = "instrumentation")]
+        self.instrumentation.borrow_mut().record(start);
+
+        value
+    }
+
+    /// [`Self::get`] mapped from the control's [`Self::range`] to `0..=1`,
+    /// e.g. for shader uniforms or driving controls with different ranges
+    /// from one another. 0 for unknown controls
+    pub fn get_normalized(&self, name: &str) -> f32 {
+        let value = self.get(name);
+        self.range(name).map_or(0.0, |[min, max]| {
+            ternary!(
+                min == max,
+                0.0,
+                map_range(value, min, max, 0.0_f32, 1.0).clamp(0.0, 1.0)
+            )
+        })
+    }
+
+    /// Sets a slider, MIDI, or OSC control to the point `value` (`0..=1`) of
+    /// its range. Since `map_to` only changes what [`Self::get`] returns, this
+    /// is the inverse of [`Self::get_normalized`] only for linear `map_to`
+    /// easing
+    pub fn set_normalized(
+        &mut self,
+        name: &str,
+        value: f32,
+    ) -> Result<(), String> {
+        let name = self.canonical_name(name).to_string();
+        let [min, max] = self
+            .input_range(&name)
+            .ok_or_else(|| format!("No control named `{}`", name))?;
+        let value = map_range(value.clamp(0.0, 1.0), 0.0, 1.0, min, max);
+        self.set(&name, value)
+    }
+
+    /// Sets a slider, MIDI, or OSC control to `value` in its own range, as
+    /// opposed to [`Self::set_normalized`]
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        let name = self.canonical_name(name).to_string();
+        if !self.has(&name) {
+            return Err(format!("No control named `{}`", name));
+        }
+
+        if matches!(
+            self.ui_controls.config(&name),
+            Some(UiControlConfig::Slider { .. })
+        ) {
+            self.ui_controls.set(&name, ControlValue::Float(value));
+        } else if self.midi_controls.has(&name) {
+            self.midi_controls.set(&name, value);
+        } else if self.osc_controls.has(&name) {
+            self.osc_controls.set(&name, value);
+        } else {
+            return Err(format!(
+                "`{}` can't be set; only sliders, MIDI, and OSC controls can",
+                name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The range [`Self::get`] returns values in: the `map_to` range if
+    /// declared, otherwise the slider, MIDI, or OSC range, or the script's
+    /// `range` field (`[0, 1]` when omitted). `None` for unknown controls
+    pub fn range(&self, name: &str) -> Option<[f32; 2]> {
+        let name = self.canonical_name(name);
+        match self.output_maps.get(name) {
+            Some(output_map) => Some(output_map.range),
+            None => self.input_range(name),
+        }
+    }
+
+    /// Like [`Self::range`] but ignoring `map_to`
+    fn input_range(&self, name: &str) -> Option<[f32; 2]> {
+        if let Some(UiControlConfig::Slider { min, max, .. }) =
+            self.ui_controls.config(name)
+        {
+            return Some([min, max]);
+        }
+        if let Some(config) = self.midi_controls.config(name) {
+            return Some([config.min, config.max]);
+        }
+        if let Some(config) = self.osc_controls.config(name) {
+            return Some([config.min, config.max]);
+        }
+        if !self.has(name) {
+            return None;
+        }
+
+        let declared = match self.last_good_config.as_ref()?.get(name)? {
+            MaybeControlConfig::Control(config) => config
+                .config
+                .get("range")
+                .and_then(|r| serde_yml::from_value(r.clone()).ok()),
+            MaybeControlConfig::Other(_) => None,
+        };
+
+        Some(declared.unwrap_or([0.0, 1.0]))
+    }
+
+    /// Applies the control's `map_to` field, if any. Only what [`Self::get`]
+    /// returns is mapped; other controls that reference this one through
+    /// `$name` params or modulation see its unmapped value
+    fn map_output(&self, name: &str, value: f32) -> f32 {
+        let name = self.canonical_name(name);
+        self.output_maps
+            .get(name)
+            .map_or(value, |output_map| output_map.apply(value))
+    }
 
-        let mut name = match self.vars.get(name) {
-            Some(alias) => alias,
-            None => name,
+    /// Where [`Self::get`] evaluates controls: the current frame plus the
+    /// animation clock's beat, which may sit between frames. The beat is read
+    /// once per frame (or [`Self::update`]) so clocks that move between calls,
+    /// like MIDI, can't give controls read in the same frame different beats
+    fn eval_position(&self) -> EvalPosition {
+        let frame = frame_controller::frame_count();
+        let beats = match self.frame_beats.get() {
+            Some((beats_frame, beats)) if beats_frame == frame => beats,
+            _ => {
+                let beats = self.animation.timing.beats();
+                self.frame_beats.set(Some((frame, beats)));
+                beats
+            }
         };
+        EvalPosition { frame, beats }
+    }
+
+    fn evaluate(&self, name: &str, position: EvalPosition) -> f32 {
+        let mut name = self.canonical_name(name);
 
         let midi_proxy_name = MapMode::proxy_name(name);
-        if self.midi_proxies_enabled && self.midi_controls.has(&midi_proxy_name)
-        {
+        if self.follows_midi(name) {
             name = &midi_proxy_name;
         }
 
@@ -202,125 +824,471 @@ impl<T: TimingSource> ControlHub<T> {
             return *bypass;
         }
 
+        if let Some(held) = self.runtime_bypassed.get(name) {
+            return *held;
+        }
+
         if let Some(x) = self
             .active_transition
             .as_ref()
-            .and_then(|t| self.get_transition_value(current_frame, name, t))
+            .and_then(|t| self.get_transition_value(position.frame, name, t))
         {
             return x;
         }
 
-        self.run_dependencies(name, current_frame);
+        self.run_dependencies(name, position);
 
-        let value = self.get_raw(name, current_frame);
+        let value = self.get_raw(name, position);
+        let value = self.apply_macros(name, value, position);
 
-        let result = self.modulations.get(name).map_or(value, |modulators| {
-            modulators.iter().fold(value, |v, modulator| {
-                self.apply_modulator(v, modulator, current_frame)
-            })
+        let value = self.modulations.get(name).map_or(value, |modulators| {
+            #[cfg(feature = "instrumentation")]
+            let start = self.modulator_instrumentation.borrow().start();
+            let value = modulators
+                .iter()
+                .filter(|(mod_name, route)| {
+                    self.is_route_active(mod_name, route)
+                })
+                .fold(value, |v, (_, route)| {
+                    self.apply_modulator(v, route, position)
+                });
+            #[cfg(feature = "instrumentation")]
+            self.modulator_instrumentation.borrow_mut().record(start);
+            value
         });
 
-        #[cfg(feature = "instrumentation")]
-        self.instrumentation.borrow_mut().record(start);
-
-        result
+        match self.humanizers.get(name) {
+            Some(humanizer) => {
+                self.dirty_tracker.time_varying.set(true);
+                humanizer.apply(value, position.beats)
+            }
+            None => value,
+        }
     }
 
-    fn get_transition_value(
+    /// Replaces `value` with the output of the macros targeting `name`, with
+    /// the last declared winning. Runtime bypassed macros are skipped
+    fn apply_macros(
         &self,
-        current_frame: u32,
         name: &str,
-        transition: &SnapshotTransition,
-    ) -> Option<f32> {
-        let (from, to) = *transition.values.get(name)?;
-        if current_frame > transition.end_frame
-            || transition.start_frame == transition.end_frame
-        {
-            return Some(to);
-        }
-        let duration = transition.end_frame - transition.start_frame;
-        let progress = current_frame - transition.start_frame;
-        let t = progress as f32 / duration as f32;
-        Some(lerp(from, to, t))
+        value: f32,
+        position: EvalPosition,
+    ) -> f32 {
+        self.macro_targets.get(name).map_or(value, |targets| {
+            targets
+                .iter()
+                .filter(|(macro_name, _)| {
+                    !self.runtime_bypassed.contains_key(macro_name)
+                })
+                .fold(value, |_, (macro_name, target)| {
+                    target.apply(self.get_raw(macro_name, position))
+                })
+        })
     }
 
-    fn run_dependencies(&self, target_name: &str, current_frame: u32) {
-        if let Some(order) = &self.dep_graph.order() {
-            for name in order.iter() {
-                let midi_proxy_name = MapMode::proxy_name(name);
+    /// Chooses which controls keep a short history of the values returned by
+    /// [`Self::get`]. See [`Self::history`]
+    pub fn set_history_tracking(&mut self, tracking: HistoryTracking) {
+        self.history.borrow_mut().set_tracking(tracking);
+    }
 
-                let name = if self.midi_proxies_enabled
-                    && self.midi_controls.has(&midi_proxy_name)
-                {
-                    &midi_proxy_name
-                } else {
-                    name
-                };
+    /// Recent values of each tracked control, oldest first, one per frame the
+    /// control was read in
+    pub fn history(&self) -> HashMap<String, Vec<f32>> {
+        self.history.borrow().values()
+    }
 
-                if name == target_name {
-                    break;
+    /// Starts writing the values [`Self::get`] returns for the controls and
+    /// groups in `selection` (every control when empty) to a CSV file at
+    /// `path`, one row per frame. Replaces any recording in progress. See
+    /// [`ValueRecorder`]
+    pub fn start_value_recording(
+        &mut self,
+        path: &Path,
+        selection: &[String],
+    ) -> Result<(), Box<dyn Error>> {
+        let mut names: Vec<String> = vec![];
+        if selection.is_empty() {
+            names.extend(
+                self.debug_graph()
+                    .nodes
+                    .into_iter()
+                    .filter(|node| node.kind != "effect")
+                    .map(|node| node.name),
+            );
+        }
+        for entry in selection {
+            let members = match self.groups.get(entry) {
+                Some(members) => members.clone(),
+                None => vec![self.canonical_name(entry).to_string()],
+            };
+            for name in members {
+                if !self.has(&name) {
+                    warn!("Not recording unknown control `{}`", name);
+                } else if !names.contains(&name) {
+                    names.push(name);
                 }
+            }
+        }
+        if names.is_empty() {
+            return Err("No controls to record".into());
+        }
 
-                if self.eval_cache.has(name, current_frame) {
-                    continue;
-                }
+        let recorder = ValueRecorder::create(path, &names)?;
+        *self.value_recorder.get_mut() = Some(recorder);
+        Ok(())
+    }
 
-                self.get_raw(name, current_frame);
-            }
+    /// Finishes the recording started with [`Self::start_value_recording`],
+    /// returning its path and number of rows
+    pub fn stop_value_recording(
+        &mut self,
+    ) -> Result<(PathBuf, usize), Box<dyn Error>> {
+        let recorder = self
+            .value_recorder
+            .get_mut()
+            .take()
+            .ok_or("Not recording values")?;
+        let path = recorder.path().to_path_buf();
+        let rows = recorder.finish()?;
+        Ok((path, rows))
+    }
+
+    pub fn is_recording_values(&self) -> bool {
+        self.value_recorder.borrow().is_some()
+    }
+
+    /// Plays back a file written by [`Self::start_value_recording`]: until
+    /// [`Self::stop_replay`], [`Self::get`] returns the recorded values of the
+    /// controls in it (ignoring live input) at the current beat. Rows are
+    /// matched by beat so reset the timing before recording a performance
+    /// meant to be re-rendered. See [`ValueReplay`]
+    pub fn start_replay(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let replay = ValueReplay::load(path)?;
+        let unknown: Vec<String> = replay
+            .names()
+            .into_iter()
+            .filter(|name| !self.has(name))
+            .collect();
+        if !unknown.is_empty() {
+            warn!(
+                "Replaying values for unknown controls: {}",
+                unknown.join(", ")
+            );
         }
+        self.replay = Some(replay);
+        Ok(())
     }
 
-    fn apply_modulator(
-        &self,
-        value: f32,
-        modulator: &str,
-        current_frame: u32,
-    ) -> f32 {
-        let mut effects = self.effects.borrow_mut();
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
 
-        if !effects.contains_key(modulator) {
-            return value * self.get_raw(modulator, current_frame);
+    /// Samples what [`Self::get`] would return for `name` at `resolution`
+    /// evenly spaced points over the next `beats` beats, e.g. to plot how
+    /// stacked animations, modulation, and effects combine. The end of the
+    /// span is excluded so a lane that loops every `beats` plots exactly one
+    /// cycle. Stateful effects (slew, hysteresis) step once per sample rather
+    /// than once per frame; their live state is restored afterwards and
+    /// nothing is recorded in [`Self::history`]. Empty for unknown controls
+    pub fn preview(
+        &self,
+        name: &str,
+        resolution: usize,
+        beats: f32,
+    ) -> Vec<f32> {
+        if !self.has(name) {
+            return vec![];
         }
 
-        let (config, effect) = effects.get_mut(modulator).unwrap();
+        let effects = self.effects.borrow().clone();
+        let time_varying = self.dirty_tracker.time_varying.get();
+        let now = self.eval_position();
+
+        let values = self.eval_cache.preserving(|| {
+            self.animation.preserving_state(|| {
+                (0..resolution)
+                    .map(|i| {
+                        let offset = beats * i as f32 / resolution as f32;
+                        let position = EvalPosition {
+                            frame: now.frame,
+                            beats: now.beats + offset,
+                        };
+                        let value = self.evaluate(name, position);
+                        self.map_output(name, value)
+                    })
+                    .collect()
+            })
+        });
 
-        if let (
-            EffectKind::RingModulator {
-                modulator: modulation_source,
-                ..
-            },
-            Effect::RingModulator(m),
-        ) = (&config.kind, &mut *effect)
-        {
-            let carrier = modulator;
-            self.update_effect_params(m, carrier, current_frame);
-            m.apply(value, self.get_raw(modulation_source, current_frame))
-        } else {
-            match effect {
-                Effect::Constrain(m) => m.apply(value),
+        *self.effects.borrow_mut() = effects;
+        self.dirty_tracker.time_varying.set(time_varying);
+
+        values
+    }
+
+    /// Describes every control, how they feed one another, and what the hub
+    /// currently knows about them this frame. Purely observational: nothing is
+    /// evaluated
+    pub fn debug_graph(&self) -> DebugGraph {
+        let frame = frame_controller::frame_count();
+
+        let mut kinds: Vec<(String, String)> = vec![];
+        for (name, config) in self.ui_controls.configs() {
+            if !config.is_separator() {
+                kinds.push((name, config.variant_string().to_lowercase()));
+            }
+        }
+        for (kind, names) in [
+            ("midi", self.midi_controls.configs().into_keys().collect()),
+            ("osc", self.osc_controls.configs().into_keys().collect()),
+            ("audio", self.audio_controls.values().into_keys().collect()),
+            (
+                "midi_file",
+                self.midi_files.keys().cloned().collect::<Vec<_>>(),
+            ),
+            ("effect", self.effects.borrow().keys().cloned().collect()),
+        ] {
+            kinds
+                .extend(names.into_iter().map(|name| (name, kind.to_string())));
+        }
+        for (name, source) in self.midi_note_controls.sources() {
+            let kind = match source {
+                NoteSource::Velocity => "midi_velocity",
+                NoteSource::Note => "midi_note",
+            };
+            kinds.push((name, kind.to_string()));
+        }
+        for name in self.envelopes.names() {
+            kinds.push((name, "adsr".to_string()));
+        }
+        for (name, (config, _)) in &self.animations {
+            let kind = match config {
+                AnimationConfig::Automate(_) => "automate",
+                AnimationConfig::Ramp(_) => "ramp",
+                AnimationConfig::Random(_) => "random",
+                AnimationConfig::RandomSlewed(_) => "random_slewed",
+                AnimationConfig::Triangle(_) => "triangle",
+            };
+            kinds.push((name.clone(), kind.to_string()));
+        }
+
+        let mut nodes: Vec<DebugNode> = kinds
+            .into_iter()
+            // MIDI proxies are reported on the control they stand in for
+            .filter(|(name, _)| !MapMode::is_proxy_name(name))
+            .map(|(name, kind)| {
+                let proxy_name = MapMode::proxy_name(&name);
+                DebugNode {
+                    cached: self.eval_cache.get_in_frame(&name, frame),
+                    proxy: self.follows_midi(&name).then_some(proxy_name),
+                    bypass: self.bypassed.get(&name).copied().flatten(),
+                    modulators: self
+                        .modulations
+                        .get(&name)
+                        .map(|routes| {
+                            routes
+                                .iter()
+                                .map(|(_, route)| route.modulator.clone())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    name,
+                    kind,
+                }
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut edges = vec![];
+        for (name, params) in self.dep_graph.nodes() {
+            for (param, value) in params {
+                if let ParamValue::Hot(source) = value {
+                    edges.push(DebugEdge {
+                        from: source.clone(),
+                        to: name.clone(),
+                        via: param.clone(),
+                    });
+                }
+            }
+        }
+        for (name, routes) in &self.modulations {
+            for (_, route) in routes {
+                edges.push(DebugEdge {
+                    from: route.modulator.clone(),
+                    to: name.clone(),
+                    via: "modulation".to_string(),
+                });
+            }
+        }
+        for (name, targets) in &self.macro_targets {
+            for (macro_name, _) in targets {
+                edges.push(DebugEdge {
+                    from: macro_name.clone(),
+                    to: name.clone(),
+                    via: "macro".to_string(),
+                });
+            }
+        }
+        for (name, (config, _)) in self.effects.borrow().iter() {
+            if let EffectKind::RingModulator { modulator, .. } = &config.kind {
+                edges.push(DebugEdge {
+                    from: modulator.clone(),
+                    to: name.clone(),
+                    via: "modulator".to_string(),
+                });
+            }
+        }
+        edges.sort_by(|a, b| (&a.to, &a.via).cmp(&(&b.to, &b.via)));
+
+        DebugGraph {
+            frame,
+            nodes,
+            edges,
+            eval_order: self.dep_graph.order().clone().unwrap_or_default(),
+            aliases: self
+                .vars
+                .iter()
+                .map(|(var, name)| (var.clone(), name.clone()))
+                .collect(),
+            runtime_bypassed: self.runtime_bypassed(),
+            solo: self.solo.clone(),
+        }
+    }
+
+    /// Interpolates `name` over the transition unless it is `stepped`, in
+    /// which case it jumps at the start or end instead
+    fn get_transition_value(
+        &self,
+        current_frame: u32,
+        name: &str,
+        transition: &SnapshotTransition,
+    ) -> Option<f32> {
+        let (from, to) = *transition.values.get(name)?;
+        let t = transition.progress(current_frame);
+        if t >= 1.0 {
+            return Some(to);
+        }
+        let stepped = self.stepped.get(name).or_else(|| {
+            MapMode::unproxied_name(name).and_then(|n| self.stepped.get(&n))
+        });
+        match stepped {
+            Some(Stepped::Start) => Some(to),
+            Some(Stepped::End) => Some(from),
+            None => Some(lerp(from, to, t)),
+        }
+    }
+
+    fn run_dependencies(&self, target_name: &str, position: EvalPosition) {
+        if let Some(order) = &self.dep_graph.order() {
+            for name in order.iter() {
+                let midi_proxy_name = MapMode::proxy_name(name);
+
+                let name = if self.follows_midi(name) {
+                    &midi_proxy_name
+                } else {
+                    name
+                };
+
+                if name == target_name {
+                    break;
+                }
+
+                if self.eval_cache.has(name, position) {
+                    continue;
+                }
+
+                self.get_raw(name, position);
+            }
+        }
+    }
+
+    /// A route is skipped while its `mod` entry or modulator is bypassed at
+    /// runtime, or while something else is soloed
+    fn is_route_active(&self, mod_name: &str, route: &ModulationRoute) -> bool {
+        let names = [mod_name, route.modulator.as_str()];
+
+        if names.iter().any(|n| self.runtime_bypassed.contains_key(*n)) {
+            return false;
+        }
+
+        self.solo
+            .as_deref()
+            .is_none_or(|solo| names.contains(&solo))
+    }
+
+    /// Combines `value` with a route's modulator. Controls multiply by default
+    /// while effects replace the value with their output
+    fn apply_modulator(
+        &self,
+        value: f32,
+        route: &ModulationRoute,
+        position: EvalPosition,
+    ) -> f32 {
+        let modulator = route.modulator.as_str();
+
+        if !self.effects.borrow().contains_key(modulator) {
+            let modulation = self.get_raw(modulator, position);
+            return route
+                .operator
+                .unwrap_or(ModulationOperator::Multiply)
+                .apply(value, modulation);
+        }
+
+        let output = self.apply_effect(value, modulator, position);
+        route.operator.map_or(output, |op| op.apply(value, output))
+    }
+
+    fn apply_effect(
+        &self,
+        value: f32,
+        modulator: &str,
+        position: EvalPosition,
+    ) -> f32 {
+        let mut effects = self.effects.borrow_mut();
+        let (config, effect) = effects.get_mut(modulator).unwrap();
+
+        if let (
+            EffectKind::RingModulator {
+                modulator: modulation_source,
+                ..
+            },
+            Effect::RingModulator(m),
+        ) = (&config.kind, &mut *effect)
+        {
+            let carrier = modulator;
+            self.update_effect_params(m, carrier, position);
+            m.apply(value, self.get_raw(modulation_source, position))
+        } else {
+            match effect {
+                Effect::Constrain(m) => m.apply(value),
                 Effect::Hysteresis(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::Map(m) => m.apply(value),
                 Effect::Math(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::Quantizer(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::Saturator(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::SlewLimiter(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::WaveFolder(m) => {
-                    self.update_effect_params(m, modulator, current_frame);
+                    self.update_effect_params(m, modulator, position);
                     m.apply(value)
                 }
                 Effect::RingModulator(_) => panic!(),
@@ -328,27 +1296,38 @@ impl<T: TimingSource> ControlHub<T> {
         }
     }
 
+    /// The value of a `$name` param: its bypass value if bypassed, 0.0 if it
+    /// can't be resolved because of a dependency cycle
+    fn resolve_hot_param(&self, name: &str, position: EvalPosition) -> f32 {
+        if self.dep_graph.is_cyclic(name) {
+            return 0.0;
+        }
+        if let Some(Some(bypass_value)) = self.bypassed.get(name) {
+            return *bypass_value;
+        }
+        self.get_raw(name, position)
+    }
+
     fn update_effect_params(
         &self,
         effect: &mut impl SetFromParam,
         node_name: &str,
-        current_frame: u32,
+        position: EvalPosition,
     ) {
         if let Some(params) = self.dep_graph.node(node_name) {
             for (param_name, param_value) in params.iter() {
-                let value = param_value.cold_or(|name| {
-                    if let Some(Some(bypass_value)) = self.bypassed.get(&name) {
-                        *bypass_value
-                    } else {
-                        self.get_raw(&name, current_frame)
-                    }
-                });
+                let value = param_value
+                    .cold_or(|name| self.resolve_hot_param(&name, position));
                 effect.set_from_param(param_name, value);
             }
         }
     }
 
-    fn get_raw(&self, name: &str, current_frame: u32) -> f32 {
+    fn get_raw(&self, name: &str, position: EvalPosition) -> f32 {
+        if let Some(hold) = self.holds.get(name) {
+            return hold.raw;
+        }
+
         let is_proxy = MapMode::is_proxy_name(name);
         let unproxied_name = &MapMode::unproxied_name(name).unwrap_or_default();
 
@@ -359,114 +1338,342 @@ impl<T: TimingSource> ControlHub<T> {
         });
 
         if is_dep {
-            if let Some(value) = self.eval_cache.get(name, current_frame) {
+            if let Some(value) = self.eval_cache.get(name, position) {
                 return value;
             }
         }
 
         let value = self
-            .ui_controls
-            .get_optional(name)
+            .osc_input(name)
+            .or_else(|| self.ui_controls.get_optional(name))
             .or_else(|| self.midi_controls.get_optional(name))
             .or_else(|| self.audio_controls.get_optional(name))
             .or_else(|| self.osc_controls.get_optional(name))
+            .or_else(|| self.midi_file_value(name, position.beats))
+            .or_else(|| self.midi_note_controls.get_optional(name))
+            .or_else(|| self.envelopes.get_optional(name, position.beats))
             .or_else(|| {
                 self.animations.get(name).map(|(config, sequence)| {
-                    match (config, sequence) {
-                        (
-                            AnimationConfig::Automate(conf),
-                            KeyframeSequence::Breakpoints(breakpoints),
-                        ) => {
-                            let breakpoints = self.resolve_breakpoint_params(
-                                name,
-                                breakpoints,
-                                current_frame,
-                            );
-                            self.animation.automate(
-                                &breakpoints,
-                                Mode::from_str(&conf.mode).unwrap(),
-                            )
-                        }
-                        (
-                            AnimationConfig::Ramp(conf),
-                            KeyframeSequence::None,
-                        ) => {
-                            let conf = self.resolve_animation_config_params(
-                                conf,
-                                name,
-                                current_frame,
-                            );
-                            self.animation.ramp_plus(
-                                conf.beats.as_float(),
-                                (conf.range[0], conf.range[1]),
-                                conf.phase.as_float(),
-                            )
-                        }
-                        (
-                            AnimationConfig::Random(conf),
-                            KeyframeSequence::None,
-                        ) => {
-                            let conf = self.resolve_animation_config_params(
-                                conf,
-                                name,
-                                current_frame,
-                            );
-                            self.animation.random(
-                                conf.beats.as_float(),
-                                (conf.range[0], conf.range[1]),
-                                conf.delay.as_float(),
-                                conf.stem,
-                            )
-                        }
-                        (
-                            AnimationConfig::RandomSlewed(conf),
-                            KeyframeSequence::None,
-                        ) => {
-                            let conf = self.resolve_animation_config_params(
-                                conf,
-                                name,
-                                current_frame,
-                            );
-                            self.animation.random_slewed(
-                                conf.beats.as_float(),
-                                (conf.range[0], conf.range[1]),
-                                conf.slew.as_float(),
-                                conf.delay.as_float(),
-                                conf.stem,
-                            )
-                        }
-                        (
-                            AnimationConfig::Triangle(conf),
-                            KeyframeSequence::None,
-                        ) => {
-                            let conf = self.resolve_animation_config_params(
-                                conf,
-                                name,
-                                current_frame,
-                            );
-                            self.animation.triangle(
-                                conf.beats.as_float(),
-                                (conf.range[0], conf.range[1]),
-                                conf.phase.as_float(),
-                            )
-                        }
-                        _ => unimplemented!(),
-                    }
+                    self.animate(name, config, sequence, position)
                 })
             });
 
+        if self.animations.contains_key(name)
+            || self.audio_controls.has(name)
+            || self.midi_files.contains_key(name)
+            || self.midi_note_controls.has(name)
+            || self.envelopes.has(name)
+        {
+            self.dirty_tracker.time_varying.set(true);
+        }
+
         match value {
             Some(value) => {
                 if is_dep {
                     let name = ternary!(is_proxy, unproxied_name, name);
-                    self.eval_cache.store(name, current_frame, value);
+                    self.eval_cache.store(name, position, value);
                 }
                 value
             }
-            None => {
-                warn_once!("No control named {}. Defaulting to 0.0", name);
-                0.0
+            None => self.fallback(ControlError::Unknown(name.to_string()), 0.0),
+        }
+    }
+
+    /// Panics in strict mode, otherwise notes unknown names for the audit and
+    /// returns `default`
+    fn fallback<V: fmt::Debug>(&self, error: ControlError, default: V) -> V {
+        if self.strict {
+            panic!("{}", error);
+        }
+        if let ControlError::Unknown(name) = &error {
+            self.missing.borrow_mut().insert(name.clone());
+        }
+        warn_once!("{}. Returning {:?}", error, default);
+        default
+    }
+
+    /// Whether `name`, or the control it is a `var` alias of, exists
+    pub fn has(&self, name: &str) -> bool {
+        let name = self.canonical_name(name);
+        self.ui_controls.has(name)
+            || self.midi_controls.has(name)
+            || self.audio_controls.has(name)
+            || self.osc_controls.has(name)
+            || self.midi_files.contains_key(name)
+            || self.midi_note_controls.has(name)
+            || self.envelopes.has(name)
+            || self.animations.contains_key(name)
+    }
+
+    /// The control `name` is a `var` alias of, or `name` itself
+    pub fn canonical_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.vars.get(name).map_or(name, String::as_str)
+    }
+
+    /// What the control does, as written in its `help` (or `tooltip`) field.
+    /// Accepts `var` aliases
+    pub fn describe(&self, name: &str) -> Option<&str> {
+        self.help.get(self.canonical_name(name)).map(String::as_str)
+    }
+
+    /// How the control's value should be shown, as declared with its `unit`
+    /// and `display` fields. Purely presentational: [`Self::get`] always
+    /// returns the unscaled value. Accepts `var` aliases
+    pub fn display(&self, name: &str) -> Option<&ValueDisplay> {
+        self.displays.get(self.canonical_name(name))
+    }
+
+    /// The controls declared with `group: <group>` (or listing `group` among
+    /// several), in declaration order. Empty if there is no such group
+    pub fn group(&self, group: &str) -> &[String] {
+        self.groups.get(group).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every group declared with the `group` field, in declaration order
+    pub fn group_names(&self) -> Vec<&str> {
+        self.groups.keys().map(String::as_str).collect()
+    }
+
+    /// The groups `name` belongs to. Accepts `var` aliases
+    pub fn groups_of(&self, name: &str) -> Vec<String> {
+        let name = self.canonical_name(name);
+        self.groups
+            .iter()
+            .filter(|(_, members)| members.iter().any(|m| m == name))
+            .map(|(group, _)| group.clone())
+            .collect()
+    }
+
+    /// Every `var` alias of `name` (itself resolved first), sorted
+    pub fn aliases_of(&self, name: &str) -> Vec<String> {
+        let name = self.canonical_name(name);
+        let mut aliases: Vec<_> = self
+            .vars
+            .iter()
+            .filter(|(_, canonical)| *canonical == name)
+            .map(|(alias, _)| alias.clone())
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    /// Like [`Self::get`] but returns an error for unknown names instead of
+    /// 0.0
+    pub fn try_get(&self, name: &str) -> Result<f32, ControlError> {
+        if self.has(name) {
+            Ok(self.get(name))
+        } else {
+            Err(ControlError::Unknown(name.to_string()))
+        }
+    }
+
+    /// Like [`Self::bool`] but returns an error for unknown names or
+    /// controls that aren't checkboxes instead of `false`
+    pub fn try_bool(&self, name: &str) -> Result<bool, ControlError> {
+        self.ui_value(name, "checkbox")?
+            .as_bool()
+            .ok_or_else(|| Self::wrong_type(name, "checkbox"))
+    }
+
+    /// Like [`Self::string`] but returns an error for unknown names or
+    /// controls that aren't selects instead of an empty string
+    pub fn try_string(&self, name: &str) -> Result<String, ControlError> {
+        self.ui_value(name, "select")?
+            .as_string()
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| Self::wrong_type(name, "select"))
+    }
+
+    fn ui_value(
+        &self,
+        name: &str,
+        expected: &'static str,
+    ) -> Result<&ControlValue, ControlError> {
+        match self.ui_controls.value(self.canonical_name(name)) {
+            Some(value) => Ok(value),
+            None if self.has(name) => Err(Self::wrong_type(name, expected)),
+            None => Err(ControlError::Unknown(name.to_string())),
+        }
+    }
+
+    fn wrong_type(name: &str, expected: &'static str) -> ControlError {
+        ControlError::WrongType {
+            name: name.to_string(),
+            expected,
+        }
+    }
+
+    /// In strict mode the plain getters ([`Self::get`], [`Self::bool`],
+    /// [`Self::string`]) panic on names that don't exist or hold the wrong
+    /// kind of value rather than quietly returning a default. Handy while
+    /// developing a sketch to catch typos in control names
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Every name requested through the getters since the controls were last
+    /// populated that didn't exist, sorted. These are also logged once shortly
+    /// after the controls are populated
+    pub fn missing(&self) -> Vec<String> {
+        self.missing.borrow().iter().cloned().collect()
+    }
+
+    fn audit(&self) {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            warn!(
+                "Requested controls that don't exist: {}",
+                missing.join(", ")
+            );
+        }
+    }
+
+    /// Loads (and watches) a [constants file][constants] from somewhere other
+    /// than next to the control script, or for hubs built in code. Scripts
+    /// loaded with [`Self::from_path`] pick up their constants file
+    /// automatically
+    ///
+    /// [constants]: super::constants
+    pub fn load_constants(
+        &mut self,
+        path: PathBuf,
+    ) -> Result<(), Box<dyn Error>> {
+        self.constants = Constants::from_path(path)?;
+        Ok(())
+    }
+
+    /// A number (or bool as 0.0/1.0) from the constants file. Returns 0.0 for
+    /// unknown names or other kinds of constant
+    pub fn constant(&self, name: &str) -> f32 {
+        self.try_constant(name)
+            .and_then(|c| {
+                c.as_float()
+                    .ok_or_else(|| Self::wrong_type(name, "number constant"))
+            })
+            .unwrap_or_else(|e| self.fallback(e, 0.0))
+    }
+
+    /// A list of numbers from the constants file
+    pub fn constant_vec(&self, name: &str) -> Vec<f32> {
+        self.try_constant(name)
+            .and_then(|c| {
+                c.as_vec()
+                    .map(<[f32]>::to_vec)
+                    .ok_or_else(|| Self::wrong_type(name, "list constant"))
+            })
+            .unwrap_or_else(|e| self.fallback(e, vec![]))
+    }
+
+    /// A list of RGBA colors from the constants file
+    pub fn constant_colors(&self, name: &str) -> Vec<[f32; 4]> {
+        self.try_constant(name)
+            .and_then(|c| {
+                c.as_colors().map(<[[f32; 4]]>::to_vec).ok_or_else(|| {
+                    Self::wrong_type(name, "color list constant")
+                })
+            })
+            .unwrap_or_else(|e| self.fallback(e, vec![]))
+    }
+
+    /// Any kind of constant, e.g. for bools and strings
+    pub fn try_constant(&self, name: &str) -> Result<&Constant, ControlError> {
+        self.constants
+            .get(name)
+            .ok_or_else(|| ControlError::Unknown(name.to_string()))
+    }
+
+    fn midi_file_value(&self, name: &str, beats: f32) -> Option<f32> {
+        let (conf, file) = self.midi_files.get(name)?;
+
+        let length = conf.loop_beats.unwrap_or(file.length);
+        let beat = ternary!(length > 0.0, beats % length, beats);
+
+        let value = match (conf.cc, conf.note) {
+            (Some(cc), _) => file.cc(conf.channel, cc, beat),
+            (None, Some(note)) => {
+                Some(ternary!(file.note(conf.channel, note, beat), 1.0, 0.0))
+            }
+            (None, None) => None,
+        };
+
+        Some(value.map_or(conf.default, |value| {
+            map_range(value, 0.0, 1.0, conf.range[0], conf.range[1])
+        }))
+    }
+
+    /// Runs the animation behind `name` with its params resolved at
+    /// `position`. Only the animation itself sees the lane's phase offset so
+    /// hot params that reference other lanes resolve on the shared clock
+    fn animate(
+        &self,
+        name: &str,
+        config: &AnimationConfig,
+        sequence: &KeyframeSequence,
+        position: EvalPosition,
+    ) -> f32 {
+        let beats = position.beats + self.phase_offset(name);
+        let animation = &self.animation;
+
+        match (config, sequence) {
+            (
+                AnimationConfig::Automate(conf),
+                KeyframeSequence::Breakpoints(breakpoints),
+            ) => {
+                let breakpoints =
+                    self.resolve_breakpoint_params(name, breakpoints, position);
+                let mode = Mode::from_str(&conf.mode).unwrap();
+                animation.with_beats(beats, || {
+                    animation.automate(&breakpoints, mode)
+                })
+            }
+            (AnimationConfig::Ramp(conf), KeyframeSequence::None) => {
+                let conf =
+                    self.resolve_animation_config_params(conf, name, position);
+                animation.with_beats(beats, || {
+                    animation.ramp_plus(
+                        conf.beats.as_float(),
+                        (conf.range[0], conf.range[1]),
+                        conf.phase.as_float(),
+                    )
+                })
+            }
+            (AnimationConfig::Random(conf), KeyframeSequence::None) => {
+                let conf =
+                    self.resolve_animation_config_params(conf, name, position);
+                animation.with_beats(beats, || {
+                    animation.random(
+                        conf.beats.as_float(),
+                        (conf.range[0], conf.range[1]),
+                        conf.delay.as_float(),
+                        conf.stem,
+                    )
+                })
+            }
+            (AnimationConfig::RandomSlewed(conf), KeyframeSequence::None) => {
+                let conf =
+                    self.resolve_animation_config_params(conf, name, position);
+                animation.with_beats(beats, || {
+                    animation.random_slewed(
+                        conf.beats.as_float(),
+                        (conf.range[0], conf.range[1]),
+                        conf.slew.as_float(),
+                        conf.delay.as_float(),
+                        conf.stem,
+                    )
+                })
+            }
+            (AnimationConfig::Triangle(conf), KeyframeSequence::None) => {
+                let conf =
+                    self.resolve_animation_config_params(conf, name, position);
+                animation.with_beats(beats, || {
+                    animation.triangle(
+                        conf.beats.as_float(),
+                        (conf.range[0], conf.range[1]),
+                        conf.phase.as_float(),
+                    )
+                })
             }
+            _ => unimplemented!(),
         }
     }
 
@@ -474,7 +1681,7 @@ impl<T: TimingSource> ControlHub<T> {
         &self,
         node_name: &str,
         breakpoints: &[Breakpoint],
-        current_frame: u32,
+        position: EvalPosition,
     ) -> Vec<Breakpoint> {
         let mut breakpoints = breakpoints.to_vec();
 
@@ -489,13 +1696,7 @@ impl<T: TimingSource> ControlHub<T> {
 
                 if let Ok(index) = path_segments[1].parse::<usize>() {
                     let value = param_value.cold_or(|name| {
-                        if let Some(Some(bypass_value)) =
-                            self.bypassed.get(&name)
-                        {
-                            *bypass_value
-                        } else {
-                            self.get_raw(&name, current_frame)
-                        }
+                        self.resolve_hot_param(&name, position)
                     });
                     breakpoints[index].set_from_param(param_name, value);
                 }
@@ -509,7 +1710,7 @@ impl<T: TimingSource> ControlHub<T> {
         &self,
         config: &P,
         node_name: &str,
-        current_frame: u32,
+        position: EvalPosition,
     ) -> P
     where
         P: SetFromParam + Clone + std::fmt::Debug,
@@ -518,13 +1719,8 @@ impl<T: TimingSource> ControlHub<T> {
 
         if let Some(params) = self.dep_graph.node(node_name) {
             for (param_name, param_value) in params.iter() {
-                let value = param_value.cold_or(|name| {
-                    if let Some(Some(bypass_value)) = self.bypassed.get(&name) {
-                        *bypass_value
-                    } else {
-                        self.get_raw(&name, current_frame)
-                    }
-                });
+                let value = param_value
+                    .cold_or(|name| self.resolve_hot_param(&name, position));
                 config.set_from_param(param_name, value);
             }
         }
@@ -534,7 +1730,7 @@ impl<T: TimingSource> ControlHub<T> {
 
     pub fn breakpoints(&self, name: &str) -> Vec<Breakpoint> {
         self.animations
-            .get(name)
+            .get(self.canonical_name(name))
             .and_then(|(_, sequence)| match sequence {
                 KeyframeSequence::Breakpoints(breakpoints) => {
                     Some(breakpoints.clone())
@@ -544,11 +1740,207 @@ impl<T: TimingSource> ControlHub<T> {
             .unwrap_or_else(|| panic!("No breakpoints for name: {}", name))
     }
 
-    pub fn bypassed(&self) -> HashMap<String, f32> {
-        self.bypassed
-            .iter()
-            .filter_map(|(k, v)| v.map(|f| (k.clone(), f)))
-            .collect()
+    /// Temporarily bypasses a control, effect, or `mod` entry without
+    /// touching the script, e.g. to find which part of a complex patch is
+    /// misbehaving. Bypassed effects and `mod` entries are skipped in every
+    /// modulation chain they appear in, while controls hold the value they
+    /// had when bypassed (and are skipped too when used as modulators).
+    /// Survives script reloads
+    pub fn set_runtime_bypass(
+        &mut self,
+        name: &str,
+        bypassed: bool,
+    ) -> Result<(), String> {
+        if !bypassed {
+            self.runtime_bypassed.remove(name);
+            return Ok(());
+        }
+
+        let held = if self.has(name) {
+            self.evaluate(name, self.eval_position())
+        } else if self.is_modulator(name) {
+            0.0
+        } else {
+            return Err(format!("Nothing named \"{}\" to bypass", name));
+        };
+
+        self.runtime_bypassed.insert(name.to_string(), held);
+        Ok(())
+    }
+
+    pub fn runtime_bypassed(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.runtime_bypassed.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Latches the current value of `name`, animation and modulation
+    /// included, until released, e.g. to hold a nice moment of an LFO live
+    /// while everything else keeps moving. Controls using `name` as a
+    /// modulator or parameter see the held value too. Survives script
+    /// reloads as long as `name` still exists. Accepts `var` aliases
+    pub fn set_hold(&mut self, name: &str, held: bool) -> Result<(), String> {
+        let name = self.canonical_name(name).to_string();
+        if !held {
+            self.holds.remove(&name);
+            return Ok(());
+        }
+        if !self.has(&name) {
+            return Err(format!("Nothing named \"{}\" to hold", name));
+        }
+        if self.holds.contains_key(&name) {
+            return Ok(());
+        }
+
+        let hold = Hold {
+            value: self.get(&name),
+            raw: self.get_raw(&name, self.eval_position()),
+        };
+        self.holds.insert(name, hold);
+        Ok(())
+    }
+
+    /// Whether `name` is latched by [`Self::set_hold`]. Accepts `var` aliases
+    pub fn is_held(&self, name: &str) -> bool {
+        self.holds.contains_key(self.canonical_name(name))
+    }
+
+    /// Every control latched by [`Self::set_hold`], sorted
+    pub fn held(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.holds.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Overrides the range and step a slider was declared with, e.g. when the
+    /// script's range turns out too narrow mid-session. The value is clamped
+    /// into the new range and a MIDI mapping to the slider is rescaled so the
+    /// controller keeps its position. Survives script reloads and is saved
+    /// with program state
+    pub fn set_slider_range(
+        &mut self,
+        name: &str,
+        range: SliderRange,
+    ) -> Result<(), String> {
+        let finite = [range.min, range.max, range.step]
+            .iter()
+            .all(|value| value.is_finite());
+        if !finite || range.max <= range.min || range.step <= 0.0 {
+            return Err(format!(
+                "Invalid range for `{}`: [{}, {}] step {}",
+                name, range.min, range.max, range.step
+            ));
+        }
+
+        self.apply_slider_range(name, range)?;
+        self.slider_ranges.insert(name.to_string(), range);
+        Ok(())
+    }
+
+    /// Restores the range and step declared in the control script
+    pub fn reset_slider_range(&mut self, name: &str) -> Result<(), String> {
+        let declared = self.declared_slider_range(name).ok_or_else(|| {
+            format!("No slider named `{}` in the control script", name)
+        })?;
+        self.apply_slider_range(name, declared)?;
+        self.slider_ranges.remove(name);
+        Ok(())
+    }
+
+    pub fn slider_ranges(&self) -> &HashMap<String, SliderRange> {
+        &self.slider_ranges
+    }
+
+    fn apply_slider_range(
+        &mut self,
+        name: &str,
+        range: SliderRange,
+    ) -> Result<(), String> {
+        let (old_min, old_max) = self
+            .ui_controls
+            .slider_range(name)
+            .ok_or_else(|| format!("No slider named `{}`", name))?;
+
+        self.ui_controls.set_slider_range(name, range);
+
+        let proxy_name = MapMode::proxy_name(name);
+        if let Some(config) = self.midi_controls.config(&proxy_name) {
+            let value = map_range(
+                self.midi_controls.get(&proxy_name),
+                old_min,
+                old_max,
+                range.min,
+                range.max,
+            );
+            self.midi_controls.add(
+                &proxy_name,
+                MidiControlConfig {
+                    min: range.min,
+                    max: range.max,
+                    ..config
+                },
+            );
+            self.midi_controls.set(&proxy_name, value);
+            if let Err(e) = self.midi_controls.restart() {
+                error!("Error restarting MIDI after range change: {}", e);
+            }
+        }
+
+        self.eval_cache.clear();
+        Ok(())
+    }
+
+    fn declared_slider_range(&self, name: &str) -> Option<SliderRange> {
+        let config = match self.last_good_config.as_ref()?.get(name)? {
+            MaybeControlConfig::Control(config) => config,
+            MaybeControlConfig::Other(_) => return None,
+        };
+
+        if !matches!(config.control_type, ControlType::Slider) {
+            return None;
+        }
+
+        let conf: SliderConfig =
+            serde_yml::from_value(config.config.clone()).ok()?;
+
+        Some(SliderRange {
+            min: conf.range[0],
+            max: conf.range[1],
+            step: conf.step,
+        })
+    }
+
+    /// Applies only the modulation routes whose `mod` entry or modulator is
+    /// `name`, muting every other modulator in every chain. `None` unsolos
+    pub fn set_solo(&mut self, name: Option<&str>) -> Result<(), String> {
+        if let Some(name) = name {
+            if !self.is_modulator(name) {
+                return Err(format!("\"{}\" is not a modulator", name));
+            }
+        }
+        self.solo = name.map(str::to_string);
+        Ok(())
+    }
+
+    pub fn solo(&self) -> Option<&str> {
+        self.solo.as_deref()
+    }
+
+    /// Whether `name` is a `mod` entry or is used as a modulator in one
+    fn is_modulator(&self, name: &str) -> bool {
+        self.modulations
+            .values()
+            .flatten()
+            .any(|(mod_name, route)| {
+                mod_name == name || route.modulator == name
+            })
+    }
+
+    pub fn bypassed(&self) -> HashMap<String, f32> {
+        self.bypassed
+            .iter()
+            .filter_map(|(k, v)| v.map(|f| (k.clone(), f)))
+            .collect()
     }
 
     /// Helper to create snapshot (values only)
@@ -601,28 +1993,99 @@ impl<T: TimingSource> ControlHub<T> {
     pub fn take_snapshot(&mut self, id: &str) {
         let snapshot = self.create_snapshot(Vec::new());
         self.snapshots.insert(id.to_string(), snapshot);
+
+        if self.snapshot_phase {
+            let beats = self.eval_position().beats;
+            let phases = self
+                .animations
+                .keys()
+                .map(|name| (name.clone(), beats + self.phase_offset(name)))
+                .collect();
+            self.snapshot_phases.insert(id.to_string(), phases);
+        } else {
+            self.snapshot_phases.remove(id);
+        }
     }
 
     pub fn recall_snapshot(&mut self, id: &str) -> Result<(), String> {
+        self.recall_snapshot_filtered(id, None)
+    }
+
+    /// Like [`Self::recall_snapshot`] but only transitions the controls in
+    /// `selection`, which may hold control names, `var` aliases, and group
+    /// names (see [`Self::group`]), e.g. to bring back a look's colors while
+    /// leaving its motion alone. Starting a recall replaces any transition
+    /// still in progress
+    pub fn recall_snapshot_only(
+        &mut self,
+        id: &str,
+        selection: &[String],
+    ) -> Result<(), String> {
+        let only = self.resolve_selection(selection);
+        if only.is_empty() {
+            return Err(format!("Nothing to recall for {:?}", selection));
+        }
+        self.recall_snapshot_filtered(id, Some(&only))
+    }
+
+    /// Expands group names and aliases in `selection` to control names,
+    /// including the MIDI proxies of mapped sliders
+    fn resolve_selection(&self, selection: &[String]) -> HashSet<String> {
+        let mut names = HashSet::default();
+
+        for entry in selection {
+            match self.groups.get(entry) {
+                Some(members) => names.extend(members.iter().cloned()),
+                None => {
+                    names.insert(self.canonical_name(entry).to_string());
+                }
+            }
+        }
+
+        let proxies: Vec<String> =
+            names.iter().map(|name| MapMode::proxy_name(name)).collect();
+        names.extend(proxies);
+
+        names
+    }
+
+    fn recall_snapshot_filtered(
+        &mut self,
+        id: &str,
+        only: Option<&HashSet<String>>,
+    ) -> Result<(), String> {
+        if let Some(phases) = self.snapshot_phases.get(id) {
+            let beats = self.eval_position().beats;
+            for (name, phase) in phases {
+                if self.animations.contains_key(name)
+                    && only.is_none_or(|only| only.contains(name))
+                {
+                    self.phase_offsets.insert(name.clone(), phase - beats);
+                }
+            }
+        }
+
         match self.snapshots.get(id) {
             Some(snapshot) => {
-                let current_frame = frame_controller::frame_count();
-                let duration =
-                    self.animation.beats_to_frames(self.transition_time) as u32;
-
-                let mut transition = SnapshotTransition {
-                    values: HashMap::default(),
-                    start_frame: current_frame,
-                    end_frame: current_frame + duration,
-                };
+                let position = self.eval_position();
+                let mut transition = SnapshotTransition::new(
+                    position.frame,
+                    self.animation.beats_to_frames(self.transition_time),
+                );
 
                 for (name, value) in snapshot {
+                    // Snapshots saved before a control was renamed may hold
+                    // the old name, kept around as a `var`
+                    let name = self.vars.get(name).map_or(name, |n| n);
+                    if only.is_some_and(|only| !only.contains(name)) {
+                        continue;
+                    }
                     if self.ui_controls.has(name) {
                         match value {
                             ControlValue::Float(v) => {
                                 transition.values.insert(
                                     name.to_string(),
-                                    (self.get_raw(name, current_frame), *v),
+                                    (self.get_raw(name, position), *v),
                                 );
                             }
                             ControlValue::Bool(_) | ControlValue::String(_) => {
@@ -642,7 +2105,7 @@ impl<T: TimingSource> ControlHub<T> {
                         transition.values.insert(
                             name.to_string(),
                             (
-                                self.get_raw(name, current_frame),
+                                self.get_raw(name, position),
                                 value.as_float().unwrap(),
                             ),
                         );
@@ -660,10 +2123,26 @@ impl<T: TimingSource> ControlHub<T> {
 
     pub fn delete_snapshot(&mut self, id: &str) {
         self.snapshots.remove(id);
+        self.snapshot_phases.remove(id);
     }
 
     pub fn clear_snapshots(&mut self) {
-        self.snapshots.clear()
+        self.snapshots.clear();
+        self.snapshot_phases.clear();
+    }
+
+    /// When enabled, [`Self::take_snapshot`] also records where every
+    /// animation is so that [`Self::recall_snapshot`] can jump them back to
+    /// the same phase instead of leaving them wherever the clock has moved
+    /// on to. Snapshots taken while enabled keep their phase either way
+    pub fn set_snapshot_phase(&mut self, enabled: bool) {
+        self.snapshot_phase = enabled;
+    }
+
+    /// Beats an animation runs ahead of the timing source. Non-zero once a
+    /// snapshot with phase has been recalled
+    fn phase_offset(&self, name: &str) -> f32 {
+        self.phase_offsets.get(name).copied().unwrap_or(0.0)
     }
 
     pub fn register_snapshot_ended_callback<F>(&mut self, callback: F)
@@ -678,6 +2157,73 @@ impl<T: TimingSource> ControlHub<T> {
         self.transition_time = transition_time;
     }
 
+    /// Every control whose value differs between snapshots `a` and `b`,
+    /// including controls only one of them holds, sorted by name
+    pub fn diff_snapshots(
+        &self,
+        a: &str,
+        b: &str,
+    ) -> Result<Vec<SnapshotDelta>, String> {
+        let snapshot = |id: &str| {
+            self.snapshots
+                .get(id)
+                .ok_or_else(|| format!("No snapshot \"{}\"", id))
+        };
+        let (a, b) = (snapshot(a)?, snapshot(b)?);
+
+        let names: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+        let deltas = names
+            .into_iter()
+            .filter(|name| a.get(*name) != b.get(*name))
+            .map(|name| {
+                let (a, b) = (a.get(name).cloned(), b.get(name).cloned());
+                let delta = a
+                    .as_ref()
+                    .and_then(ControlValue::as_float)
+                    .zip(b.as_ref().and_then(ControlValue::as_float))
+                    .map(|(a, b)| b - a);
+                SnapshotDelta {
+                    name: name.clone(),
+                    a,
+                    b,
+                    delta,
+                }
+            })
+            .collect();
+
+        Ok(deltas)
+    }
+
+    /// Copies the values of `names` from snapshot `from` into snapshot `to`,
+    /// e.g. to carry a few controls of one look over to another. Names
+    /// `from` doesn't hold are skipped. Returns how many values were copied
+    pub fn copy_snapshot_values(
+        &mut self,
+        from: &str,
+        to: &str,
+        names: &[String],
+    ) -> Result<usize, String> {
+        let source = self
+            .snapshots
+            .get(from)
+            .ok_or_else(|| format!("No snapshot \"{}\"", from))?;
+        let values: Vec<_> = names
+            .iter()
+            .filter_map(|name| {
+                source.get(name).map(|value| (name.clone(), value.clone()))
+            })
+            .collect();
+
+        let target = self
+            .snapshots
+            .get_mut(to)
+            .ok_or_else(|| format!("No snapshot \"{}\"", to))?;
+        let count = values.len();
+        target.extend(values);
+
+        Ok(count)
+    }
+
     pub fn snapshot_keys_sorted(&self) -> Vec<String> {
         let mut keys: Vec<_> = self.snapshots.keys().cloned().collect();
         keys.sort();
@@ -691,15 +2237,11 @@ impl<T: TimingSource> ControlHub<T> {
     ///
     /// [commit]: https://github.com/Lokua/xtal/commit/bcb1328
     pub fn randomize(&mut self, exclusions: Exclusions) {
-        let current_frame = frame_controller::frame_count();
-        let duration =
-            self.animation.beats_to_frames(self.transition_time) as u32;
-
-        let mut transition = SnapshotTransition {
-            values: HashMap::default(),
-            start_frame: current_frame,
-            end_frame: current_frame + duration,
-        };
+        let position = self.eval_position();
+        let mut transition = SnapshotTransition::new(
+            position.frame,
+            self.animation.beats_to_frames(self.transition_time),
+        );
 
         for (name, value) in &self.create_snapshot(exclusions) {
             if self.ui_controls.has(name) {
@@ -709,7 +2251,7 @@ impl<T: TimingSource> ControlHub<T> {
                             min, max, step, ..
                         } = self.ui_controls.config(name).unwrap()
                         {
-                            let from = self.get_raw(name, current_frame);
+                            let from = self.get_raw(name, position);
                             let to =
                                 random_within_range_stepped(min, max, step);
                             transition
@@ -745,7 +2287,7 @@ impl<T: TimingSource> ControlHub<T> {
                 transition.values.insert(
                     name.to_string(),
                     (
-                        self.get_raw(name, current_frame),
+                        self.get_raw(name, position),
                         thread_rng().gen_range(config.min..=config.max),
                     ),
                 );
@@ -754,7 +2296,7 @@ impl<T: TimingSource> ControlHub<T> {
                 transition.values.insert(
                     name.to_string(),
                     (
-                        self.get_raw(name, current_frame),
+                        self.get_raw(name, position),
                         thread_rng().gen_range(config.min..=config.max),
                     ),
                 );
@@ -768,6 +2310,9 @@ impl<T: TimingSource> ControlHub<T> {
     }
 
     pub fn update(&mut self) {
+        let _span = trace_span!(target: spans::HUB, "hub_update").entered();
+        self.frame_beats.set(None);
+
         let new_config = self.update_state.as_ref().and_then(|update_state| {
             if !update_state.has_changes.load(Ordering::Acquire) {
                 return None;
@@ -777,14 +2322,20 @@ impl<T: TimingSource> ControlHub<T> {
             state.ok().and_then(|mut guard| guard.take())
         });
 
-        if let Some(config) = new_config {
-            if let Err(e) = self.populate_controls(&config) {
-                error!("Failed to apply new configuration: {:?}", e);
-            }
+        match new_config {
+            Some(Ok(config)) => self.apply_reloaded_config(&config),
+            Some(Err(e)) => self.set_script_error(Some(e)),
+            None => {}
+        }
+
+        self.updates_since_populate =
+            self.updates_since_populate.saturating_add(1);
+        if self.updates_since_populate == AUDIT_AFTER_UPDATES {
+            self.audit();
         }
 
         if let Some(transition) = &self.active_transition {
-            if frame_controller::frame_count() > transition.end_frame {
+            if transition.progress(frame_controller::frame_count()) > 1.0 {
                 for (name, (_from, to)) in &transition.values {
                     if self.ui_controls.has(name) {
                         let value = ControlValue::Float(*to);
@@ -804,9 +2355,336 @@ impl<T: TimingSource> ControlHub<T> {
                 }
             }
         }
+
+        self.envelopes.apply_pending(self.eval_position().beats);
+        let inputs_changed = self.update_arbiters();
+        self.update_value_recorder();
+        self.update_gates();
+        #[cfg(feature = "runtime")]
+        self.update_luts();
+
+        if self.constants.update() && frame_controller::on_demand() {
+            frame_controller::invalidate();
+        }
+
+        if self.audio_controls.update().is_some() {
+            for callback in &self.audio_device_callbacks {
+                callback.call();
+            }
+        }
+
+        self.update_latency();
+        self.update_smoothing();
+
+        if self.audio_controls.is_active() {
+            self.audio_controls.set_bpm(self.animation.timing.bpm());
+        }
+
+        if frame_controller::on_demand()
+            && (self.check_dirty() || inputs_changed)
+        {
+            frame_controller::invalidate();
+        }
+    }
+
+    /// Observes the inputs of every slider. Returns true if any moved
+    fn update_arbiters(&mut self) -> bool {
+        let mut changed = false;
+        for (name, arbiter) in self.arbiters.iter_mut() {
+            let midi = if self.midi_proxies_enabled {
+                self.midi_controls.get_optional(&MapMode::proxy_name(name))
+            } else {
+                None
+            };
+            if arbiter.observe(
+                self.ui_controls.get_optional(name),
+                midi,
+                self.osc_inputs.get(name),
+            ) {
+                self.inputs_changed.insert(name.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Whether `name` reads its MIDI proxy instead of its own value
+    fn follows_midi(&self, name: &str) -> bool {
+        self.midi_proxies_enabled
+            && self.midi_controls.has(&MapMode::proxy_name(name))
+            && self
+                .arbiters
+                .get(name)
+                .is_none_or(|arbiter| arbiter.active(true) == InputSource::Midi)
+    }
+
+    /// The OSC value of slider `name` when OSC is the input it follows
+    fn osc_input(&self, name: &str) -> Option<f32> {
+        let arbiter = self.arbiters.get(name)?;
+        let value = arbiter.osc()?;
+        (arbiter.active(false) == InputSource::Osc).then_some(value)
+    }
+
+    /// The input slider `name` currently follows. See [`Arbitration`].
+    /// Accepts `var` aliases; `None` for anything but sliders
+    pub fn active_input(&self, name: &str) -> Option<InputSource> {
+        let name = self.canonical_name(name);
+        let arbiter = self.arbiters.get(name)?;
+        let midi_mapped = self.midi_proxies_enabled
+            && self.midi_controls.has(&MapMode::proxy_name(name));
+        Some(arbiter.active(midi_mapped))
+    }
+
+    /// The input that most recently changed slider `name`, whether or not it
+    /// is the one being followed. `None` until one has since the slider was
+    /// declared. Accepts `var` aliases
+    pub fn last_input(&self, name: &str) -> Option<InputSource> {
+        let name = self.canonical_name(name);
+        self.arbiters.get(name).and_then(InputArbiter::last)
+    }
+
+    /// The `arbitration` policy of slider `name`. Accepts `var` aliases
+    pub fn arbitration(&self, name: &str) -> Option<Arbitration> {
+        let name = self.canonical_name(name);
+        self.arbiters.get(name).map(InputArbiter::policy)
+    }
+
+    /// The inputs able to set slider `name`: always the UI, plus MIDI when
+    /// mapped and OSC when declared with `osc: true`. Accepts `var` aliases
+    pub fn input_sources(&self, name: &str) -> Vec<InputSource> {
+        let name = self.canonical_name(name);
+        if !self.arbiters.contains_key(name) {
+            return vec![];
+        }
+        let mut sources = vec![InputSource::Ui];
+        if self.midi_proxies_enabled
+            && self.midi_controls.has(&MapMode::proxy_name(name))
+        {
+            sources.push(InputSource::Midi);
+        }
+        if self.osc_inputs.has(name) {
+            sources.push(InputSource::Osc);
+        }
+        sources
+    }
+
+    /// The sliders whose inputs moved since the last call. MIDI and OSC move
+    /// sliders without the UI knowing, so this tells it which to catch up on
+    pub fn take_input_changes(&mut self) -> HashSet<String> {
+        std::mem::take(&mut self.inputs_changed)
+    }
+
+    fn update_value_recorder(&mut self) {
+        let EvalPosition { frame, beats } = self.eval_position();
+        let recorder = self.value_recorder.get_mut();
+        if let Some(Err(e)) = recorder.as_mut().map(|r| r.tick(frame, beats)) {
+            error!("Stopping value recording. {}", e);
+            *recorder = None;
+        }
+    }
+
+    /// Applies the global [`LatencyOffsets`] so that incoming MIDI, OSC, and
+    /// audio values land when they are due
+    fn update_latency(&mut self) {
+        let bpm = self.animation.timing.bpm();
+        let offsets = global::latency_offsets();
+        self.midi_controls
+            .update_latency(offsets.midi.duration(bpm));
+        self.osc_controls.update_latency(offsets.osc.duration(bpm));
+        self.audio_controls
+            .update_latency(offsets.audio.duration(bpm));
+    }
+
+    /// Smoothing times in beats follow the tempo so they're reapplied every
+    /// frame along with advancing the smoothed values
+    fn update_smoothing(&mut self) {
+        let bpm = self.animation.timing.bpm();
+        for (name, smooth) in &self.smoothing {
+            let time = smooth.duration(bpm);
+            if self.midi_controls.has(name) {
+                self.midi_controls.set_smoothing(name, time);
+            } else {
+                self.osc_controls.set_smoothing(name, time);
+            }
+        }
+        self.midi_controls.update_smoothing();
+        self.osc_controls.update_smoothing();
+    }
+
+    /// Populates controls from a hot-reloaded script. If that fails midway the
+    /// last good configuration is restored along with the UI values it had
+    fn apply_reloaded_config(&mut self, config: &ConfigFile) {
+        let ui_values = self.ui_controls.values();
+
+        let Err(e) = self.populate_controls(config) else {
+            self.set_script_error(None);
+            return;
+        };
+
+        error!(
+            "Failed to apply new configuration: {}. \
+            Keeping the last good configuration.",
+            e
+        );
+
+        if let Some(last_good) = self.last_good_config.take() {
+            if let Err(e) = self.populate_controls(&last_good) {
+                error!("Failed to restore last good configuration: {}", e);
+            }
+            for (name, value) in ui_values {
+                if self.ui_controls.has(&name) {
+                    self.ui_controls.set(&name, value);
+                }
+            }
+        }
+
+        let path = self
+            .update_state
+            .as_ref()
+            .map_or_else(PathBuf::new, |state| state.path.clone());
+        self.set_script_error(Some(ScriptError::new(&path, e.to_string())));
+    }
+
+    fn set_script_error(&mut self, script_error: Option<ScriptError>) {
+        if self.script_error == script_error {
+            return;
+        }
+        self.script_error = script_error;
+        for callback in &self.script_error_callbacks {
+            callback.call();
+        }
+    }
+
+    /// The error from the most recent hot-reload of the control script, or
+    /// `None` if it loaded. While set the hub keeps running with the last
+    /// configuration that loaded
+    pub fn script_error(&self) -> Option<&ScriptError> {
+        self.script_error.as_ref()
+    }
+
+    /// Called whenever [`Self::script_error`] is set or cleared
+    pub fn register_script_error_callback<F>(&mut self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.script_error_callbacks
+            .push(Callback(Box::new(callback)));
+    }
+
+    /// Called whenever the audio device disconnects or reconnects. See
+    /// [`AudioControls::device_status`]
+    pub fn register_audio_device_callback<F>(&mut self, callback: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.audio_device_callbacks
+            .push(Callback(Box::new(callback)));
+    }
+
+    /// Loads LUTs newly referenced under `post`, drops unreferenced ones, and
+    /// swaps in any that changed on disk
+    #[cfg(feature = "runtime")]
+    fn update_luts(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .post_effects
+            .iter()
+            .filter_map(|config| match config {
+                PostEffectConfig::Lut(c) => Some(self.script_path(&c.path)),
+                _ => None,
+            })
+            .collect();
+
+        self.luts.retain(|path, _| paths.contains(path));
+
+        for path in paths {
+            let changed = match self.luts.get_mut(&path) {
+                Some(Some(lut)) => lut.update(),
+                Some(None) => false,
+                None => {
+                    let lut = HotLut::from_path(path.clone())
+                        .inspect_err(|e| error!("{}", e))
+                        .ok();
+                    self.luts.insert(path, lut);
+                    true
+                }
+            };
+
+            if changed && frame_controller::on_demand() {
+                frame_controller::invalidate();
+            }
+        }
+    }
+
+    fn script_path(&self, path: &str) -> PathBuf {
+        let path = PathBuf::from(path);
+        match &self.script_dir {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path,
+        }
+    }
+
+    /// True if anything that affects rendering has changed since the last
+    /// check: a UI, MIDI, or OSC value, an in-progress snapshot transition, or
+    /// the sketch having read an animation or audio control since the last
+    /// check.
+    fn check_dirty(&mut self) -> bool {
+        let tracker = &mut self.dirty_tracker;
+        let mut dirty = tracker.time_varying.replace(false)
+            || self.active_transition.is_some()
+            || self.midi_controls.is_smoothing()
+            || self.osc_controls.is_smoothing();
+
+        let ui_values = self.ui_controls.values();
+        if ui_values != tracker.ui_values {
+            tracker.ui_values = ui_values;
+            dirty = true;
+        }
+
+        let midi_values = self.midi_controls.values();
+        if midi_values != tracker.midi_values {
+            tracker.midi_values = midi_values;
+            dirty = true;
+        }
+
+        let osc_values = self.osc_controls.values();
+        if osc_values != tracker.osc_values {
+            tracker.osc_values = osc_values;
+            dirty = true;
+        }
+
+        dirty
+    }
+
+    fn update_gates(&mut self) {
+        if self.gates.is_empty() {
+            return;
+        }
+
+        let beat = self.eval_position().beats;
+        let values: Vec<(String, f32)> = self
+            .gates
+            .values()
+            .map(|(source, _)| (source.clone(), self.get(source)))
+            .collect();
+
+        for ((name, (_, gate)), (_, value)) in self.gates.iter_mut().zip(values)
+        {
+            if gate.process(value) {
+                self.trigger_bus
+                    .emit(name, TriggerEvent::new(beat, value.clamp(0.0, 1.0)));
+            }
+        }
     }
 
     pub fn merge_program_state(&mut self, state: &TransitorySketchState) {
+        // Before values so they aren't clamped to the declared ranges. MIDI
+        // proxies below already carry the overridden ranges
+        for (name, range) in &state.slider_ranges {
+            if self.ui_controls.set_slider_range(name, *range) {
+                self.slider_ranges.insert(name.clone(), *range);
+            }
+        }
+
         for (k, v) in state.ui_controls.values().iter() {
             self.ui_controls.set(k, v.clone());
         }
@@ -831,6 +2709,10 @@ impl<T: TimingSource> ControlHub<T> {
         for (k, v) in state.snapshots.clone() {
             self.snapshots.insert(k, v);
         }
+
+        for (k, v) in state.snapshot_phases.clone() {
+            self.snapshot_phases.insert(k, v);
+        }
     }
 
     pub fn register_populated_callback<F>(&mut self, callback: F)
@@ -844,19 +2726,23 @@ impl<T: TimingSource> ControlHub<T> {
         self.get(name)
     }
     pub fn bool(&self, name: &str) -> bool {
-        self.ui_controls.bool(name)
+        self.try_bool(name)
+            .unwrap_or_else(|e| self.fallback(e, false))
     }
     pub fn bool_as_f32(&self, name: &str) -> f32 {
-        self.ui_controls.bool_as_f32(name)
+        bool_to_f32(self.bool(name))
     }
     pub fn string(&self, name: &str) -> String {
-        self.ui_controls.string(name)
+        self.try_string(name)
+            .unwrap_or_else(|e| self.fallback(e, String::new()))
     }
     pub fn changed(&self) -> bool {
         self.ui_controls.changed()
     }
     pub fn any_changed_in(&self, names: &[&str]) -> bool {
-        self.ui_controls.any_changed_in(names)
+        let names: Vec<&str> =
+            names.iter().map(|name| self.canonical_name(name)).collect();
+        self.ui_controls.any_changed_in(&names)
     }
     pub fn mark_unchanged(&mut self) {
         self.ui_controls.mark_unchanged();
@@ -865,20 +2751,131 @@ impl<T: TimingSource> ControlHub<T> {
         self.midi_controls.hrcc = hrcc;
     }
 
-    /// Abstracts around a common pattern where you have a checkbox, slider, and
-    /// animation that are all connected as follows:
-    ///
-    /// ```yaml,ignore
-    /// animate_radius:
-    ///   type: checkbox
-    ///
-    /// radius:
-    ///   type: slider
-    ///   disabled: animate_radius
-    ///
-    /// radius_animation:
-    ///   type: triangle
-    /// ```
+    /// Starts listening to the shared MIDI and OSC inputs the script uses
+    /// unless already listening
+    fn start_inputs(&mut self) {
+        if !self.osc_controls.is_active {
+            self.osc_controls
+                .start()
+                .expect("Unable to start OSC receiver");
+        }
+
+        if !self.midi_controls.is_active() {
+            if let Err(e) = self.midi_controls.start() {
+                warn!("Unable to start MIDI receiver. {}", e);
+            }
+        }
+
+        if !self.midi_note_controls.is_empty()
+            && !self.midi_note_controls.is_active()
+        {
+            if let Err(e) = self.midi_note_controls.start() {
+                warn!("Unable to start MIDI note receiver. {}", e);
+            }
+        }
+
+        if !self.envelopes.is_empty() && !self.envelopes.is_active() {
+            if let Err(e) = self.envelopes.start() {
+                warn!("Unable to start envelope receiver. {}", e);
+            }
+        }
+
+        if !self.osc_inputs.is_empty() {
+            self.osc_inputs.start();
+        }
+    }
+
+    /// Takes the shared MIDI and OSC inputs, including those of the timing
+    /// source, for a hub built without them (see `global::without_inputs`),
+    /// e.g. once a sketch built ahead of time goes live
+    pub fn connect_inputs(&mut self) {
+        self.animation.timing.connect();
+        self.start_inputs();
+    }
+
+    /// Only one hub at a time can listen to the shared MIDI and OSC inputs and
+    /// the most recently created one wins. Call this to take them back, e.g.
+    /// after a sketch input has been built
+    pub fn reconnect_inputs(&mut self) {
+        if let Err(e) = self.midi_controls.restart() {
+            warn!("Unable to restart MIDI receiver. {}", e);
+        }
+        if self.midi_note_controls.is_active() {
+            if let Err(e) = self.midi_note_controls.restart() {
+                warn!("Unable to restart MIDI note receiver. {}", e);
+            }
+        }
+        if self.envelopes.is_active() {
+            if let Err(e) = self.envelopes.restart() {
+                warn!("Unable to restart envelope receiver. {}", e);
+            }
+        }
+        if let Err(e) = self.osc_controls.start() {
+            warn!("Unable to restart OSC receiver. {}", e);
+        }
+    }
+
+    /// Drains all trigger events received for `name` since the last call.
+    /// Unlike [`Self::get`], which samples a value once per frame, this yields
+    /// every hit along with the beat it occurred on so multiple events within a
+    /// single frame are not lost.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// for event in self.hub.triggers("kick") {
+    ///     let age = self.hub.animation.beats() - event.beat;
+    ///     self.spawn(age, event.velocity);
+    /// }
+    /// ```
+    pub fn triggers(&self, name: &str) -> impl Iterator<Item = TriggerEvent> {
+        self.trigger_bus.drain(name).into_iter()
+    }
+
+    /// Emits a trigger stamped with the current beat. Useful for sketch-side
+    /// sequencers (euclidean lanes, etc.) that want to share the same bus
+    pub fn emit_trigger(&self, name: &str, velocity: f32) {
+        let event = TriggerEvent::new(self.eval_position().beats, velocity);
+        self.trigger_bus.emit(name, event);
+    }
+
+    /// Restarts the `adsr` envelope `name` from the current beat, peaking at
+    /// `velocity` when the envelope is velocity sensitive
+    pub fn trigger_envelope(&self, name: &str, velocity: f32) -> bool {
+        let name = self.canonical_name(name);
+        self.envelopes
+            .trigger(name, self.eval_position().beats, velocity)
+    }
+
+    /// Returns a handle to the underlying bus that can be moved to other
+    /// threads to emit events from e.g. audio callbacks
+    pub fn trigger_bus(&self) -> TriggerBus {
+        self.trigger_bus.clone()
+    }
+
+    /// Derive a discrete `name` trigger from any continuous control (typically
+    /// an audio control). A trigger is emitted on [`Self::update`] whenever
+    /// `source` rises above `threshold`.
+    pub fn gate(&mut self, name: &str, source: &str, threshold: f32) {
+        self.gates.insert(
+            name.to_string(),
+            (source.to_string(), Gate::new(threshold, threshold * 0.2)),
+        );
+    }
+
+    /// Abstracts around a common pattern where you have a checkbox, slider, and
+    /// animation that are all connected as follows:
+    ///
+    /// ```yaml,ignore
+    /// animate_radius:
+    ///   type: checkbox
+    ///
+    /// radius:
+    ///   type: slider
+    ///   disabled: animate_radius
+    ///
+    /// radius_animation:
+    ///   type: triangle
+    /// ```
     ///
     /// When `animate_radius` is true, the above only results in the `radius`
     /// slider appearing disabled in the UI, but you still need to implement
@@ -914,22 +2911,129 @@ impl<T: TimingSource> ControlHub<T> {
         )
     }
 
+    /// The sketches declared under the control script's top-level `inputs`
+    /// key as `texture name => sketch name`. The runtime renders each one and
+    /// shares its output through [`crate::prelude::Context::input`]
+    pub fn inputs(&self) -> &IndexMap<String, String> {
+        &self.inputs
+    }
+
+    /// Control renames declared under the control script's top-level
+    /// `renames` key as `old name => new name`. The runtime applies them to
+    /// the sketch's saved state when loading it and rewrites the file, so
+    /// values, snapshots, and MIDI mappings follow renamed controls
+    pub fn renames(&self) -> &IndexMap<String, String> {
+        &self.renames
+    }
+
+    /// The post-processing effects declared under the control script's
+    /// top-level `post` key with all `$name` params resolved through
+    /// [`Self::get`]. Applied to the final frame by the runtime. LUT effects
+    /// are omitted until their file has loaded
+    #[cfg(feature = "runtime")]
+    pub fn post_effects(&self) -> Vec<PostEffect> {
+        let get = |param: &ParamValue| param.cold_or(|name| self.get(&name));
+
+        self.post_effects
+            .iter()
+            .filter_map(|config| {
+                Some(match config {
+                    PostEffectConfig::Bloom(c) => PostEffect::Bloom {
+                        threshold: get(&c.threshold),
+                        intensity: get(&c.intensity),
+                        radius: get(&c.radius),
+                    },
+                    PostEffectConfig::Grain(c) => PostEffect::Grain {
+                        amount: get(&c.amount),
+                        size: get(&c.size),
+                    },
+                    PostEffectConfig::Vignette(c) => PostEffect::Vignette {
+                        amount: get(&c.amount),
+                        softness: get(&c.softness),
+                    },
+                    PostEffectConfig::ChromaticAberration(c) => {
+                        PostEffect::ChromaticAberration {
+                            amount: get(&c.amount),
+                        }
+                    }
+                    PostEffectConfig::Lut(c) => PostEffect::Lut {
+                        lut: self
+                            .luts
+                            .get(&self.script_path(&c.path))?
+                            .as_ref()?
+                            .lut(),
+                        strength: get(&c.strength),
+                    },
+                    PostEffectConfig::PixelSort(c) => PostEffect::PixelSort {
+                        threshold: get(&c.threshold),
+                        direction: match c.direction {
+                            PixelSortDirection::Up => [0.0, -1.0],
+                            PixelSortDirection::Down => [0.0, 1.0],
+                            PixelSortDirection::Left => [-1.0, 0.0],
+                            PixelSortDirection::Right => [1.0, 0.0],
+                        },
+                        iterations: get(&c.iterations).round().max(0.0)
+                            as usize,
+                    },
+                    PostEffectConfig::Datamosh(c) => PostEffect::Datamosh {
+                        amount: get(&c.amount),
+                        block_size: get(&c.block_size),
+                        probability: get(&c.probability),
+                        hold: get(&c.hold),
+                    },
+                    PostEffectConfig::ScanlineGlitch(c) => {
+                        PostEffect::ScanlineGlitch {
+                            amount: get(&c.amount),
+                            frequency: get(&c.frequency),
+                            line_height: get(&c.line_height),
+                            scanlines: get(&c.scanlines),
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The palette extracted from the image of a `palette` control. `None`
+    /// if there is no such control or its image failed to load
+    #[cfg(feature = "runtime")]
+    pub fn palette(&self, name: &str) -> Option<&Palette> {
+        self.palettes.get(name)
+    }
+
     fn parse_from_str(yaml_str: &str) -> Result<ConfigFile, Box<dyn Error>> {
         let raw_config = serde_yml::from_str(yaml_str)?;
-        let merged_config = merge_keys_serde_yml(raw_config)?;
+        let merged_config = expand_repeats(merge_keys_serde_yml(raw_config)?)?;
         let config: ConfigFile = serde_yml::from_value(merged_config)?;
         Ok(config)
     }
 
-    fn parse_from_path(path: &PathBuf) -> Result<ConfigFile, Box<dyn Error>> {
-        let file_content = fs::read_to_string(path)?;
-        let config = Self::parse_from_str(&file_content)?;
-        Ok(config)
+    fn parse_from_path(path: &Path) -> Reload {
+        let file_content = fs::read_to_string(path)
+            .map_err(|e| ScriptError::new(path, e.to_string()))?;
+        Self::parse_from_str(&file_content).map_err(|e| {
+            ScriptError::from_error(path, &file_content, e.as_ref())
+        })
     }
 
     fn populate_controls(
         &mut self,
         control_configs: &ConfigFile,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = self.try_populate_controls(control_configs);
+        if result.is_err() {
+            // Cycles are otherwise only looked for once every control is in.
+            // Without this the `$` params of whatever made it in before the
+            // failure could recurse forever
+            self.resolve_aliases();
+            self.dep_graph.build_graph();
+        }
+        result
+    }
+
+    fn try_populate_controls(
+        &mut self,
+        control_configs: &ConfigFile,
     ) -> Result<(), Box<dyn Error>> {
         let current_values: ControlValues = self.ui_controls.values().clone();
         let osc_values: HashMap<String, f32> = self
@@ -951,13 +3055,65 @@ impl<T: TimingSource> ControlHub<T> {
         self.modulations.clear();
         self.vars.clear();
         self.bypassed.clear();
+        self.help.clear();
+        self.displays.clear();
+        self.groups.clear();
+        self.output_maps.clear();
+        self.humanizers.clear();
+        self.stepped.clear();
+        self.smoothing.clear();
+        self.macro_targets.clear();
         self.dep_graph.clear();
         self.eval_cache.clear();
+        self.history.borrow_mut().clear();
+        #[cfg(feature = "runtime")]
+        self.post_effects.clear();
+        self.inputs.clear();
+        self.renames.clear();
+        self.repeats.clear();
+        #[cfg(feature = "runtime")]
+        {
+            self.luts.retain(|_, lut| lut.is_some());
+            self.palettes.clear();
+        }
+        self.midi_files.clear();
+        let mut loaded_midi_files: HashMap<PathBuf, Arc<MidiFile>> =
+            HashMap::default();
+        let mut midi_note_names: Vec<&str> = vec![];
+        let mut envelope_names: Vec<&str> = vec![];
+        let mut previous_arbiters = std::mem::take(&mut self.arbiters);
+        let mut osc_input_names: Vec<&str> = vec![];
 
         for (id, maybe_config) in control_configs {
             let config = match maybe_config {
                 MaybeControlConfig::Control(config) => config,
-                MaybeControlConfig::Other(_) => continue,
+                #[cfg(feature = "runtime")]
+                MaybeControlConfig::Other(value) if id == POST_KEY => {
+                    self.post_effects = PostEffectConfig::parse_list(value)?;
+                    continue;
+                }
+                #[cfg(not(feature = "runtime"))]
+                MaybeControlConfig::Other(_) if id == POST_KEY => {
+                    warn!(
+                        "Ignoring `post` effects. Post processing requires \
+                        the `runtime` feature"
+                    );
+                    continue;
+                }
+                MaybeControlConfig::Other(value) if id == INPUTS_KEY => {
+                    self.inputs = parse_inputs(value)?;
+                    continue;
+                }
+                MaybeControlConfig::Other(value) if id == RENAMES_KEY => {
+                    self.renames = parse_renames(value)?;
+                    continue;
+                }
+                MaybeControlConfig::Other(value) => {
+                    if let Some(count) = repeat_count(value) {
+                        self.repeats.insert(id.to_string(), count);
+                    }
+                    continue;
+                }
             };
 
             let hot_params = self.find_hot_params(&config.config);
@@ -965,8 +3121,19 @@ impl<T: TimingSource> ControlHub<T> {
                 self.dep_graph.insert_node(id, hot_params);
             }
 
-            if let Some(v) = config.config.get("var").and_then(|v| v.as_str()) {
-                self.vars.insert(v.to_string(), id.to_string());
+            for alias in Self::parse_vars(&config.config) {
+                if let Some(other) = self.vars.get(&alias) {
+                    warn!(
+                        "`var: {}` of {} is already used by {}. Ignoring.",
+                        alias, id, other
+                    );
+                    continue;
+                }
+                self.vars.insert(alias, id.to_string());
+            }
+
+            for group in Self::parse_string_list(config.config.get("group")) {
+                self.groups.entry(group).or_default().push(id.to_string());
             }
 
             let bypass = config
@@ -979,6 +3146,76 @@ impl<T: TimingSource> ControlHub<T> {
                 self.bypassed.insert(id.to_string(), bypass);
             }
 
+            let help = config
+                .config
+                .get("help")
+                .or_else(|| config.config.get("tooltip"))
+                .and_then(|h| h.as_str());
+
+            if let Some(help) = help {
+                self.help.insert(id.to_string(), help.trim().to_string());
+            }
+
+            let unit = config
+                .config
+                .get("unit")
+                .and_then(|u| u.as_str())
+                .map(str::to_string);
+            let display = config
+                .config
+                .get("display")
+                .map(|d| serde_yml::from_value::<DisplayConfig>(d.clone()))
+                .transpose()?;
+
+            if unit.is_some() || display.is_some() {
+                let mut value_display = ValueDisplay {
+                    unit,
+                    ..Default::default()
+                };
+                if let Some(display) = display {
+                    value_display.decimals = display.decimals;
+                    value_display.multiplier = display.multiplier;
+                }
+                self.displays.insert(id.to_string(), value_display);
+            }
+
+            let domain: [f32; 2] = config
+                .config
+                .get("range")
+                .and_then(|r| serde_yml::from_value(r.clone()).ok())
+                .unwrap_or([0.0, 1.0]);
+
+            if let Some(stepped) = config.config.get("stepped") {
+                let stepped: Stepped = serde_yml::from_value(stepped.clone())?;
+                self.stepped.insert(id.to_string(), stepped);
+            }
+
+            if let Some(humanize) = config.config.get("humanize") {
+                let humanize: HumanizeConfig =
+                    serde_yml::from_value(humanize.clone())?;
+                self.humanizers.insert(
+                    id.to_string(),
+                    Humanizer::new(
+                        id,
+                        humanize.amount * (domain[1] - domain[0]).abs(),
+                        humanize.rate,
+                    ),
+                );
+            }
+
+            if let Some(map_to) = config.config.get("map_to") {
+                let map_to: MapToConfig =
+                    serde_yml::from_value(map_to.clone())?;
+                self.output_maps.insert(
+                    id.to_string(),
+                    OutputMap {
+                        domain,
+                        range: map_to.range(),
+                        easing: Easing::from_str(map_to.easing())?,
+                    },
+                );
+            }
+
             match config.control_type {
                 ControlType::Slider => {
                     let mut conf: SliderConfig =
@@ -991,12 +3228,67 @@ impl<T: TimingSource> ControlHub<T> {
 
                     let disabled = Self::extract_disabled_fn(&mut conf.shared);
 
+                    let range = self.slider_ranges.get(id).copied().unwrap_or(
+                        SliderRange {
+                            min: conf.range[0],
+                            max: conf.range[1],
+                            step: conf.step,
+                        },
+                    );
+
+                    let slider = UiControlConfig::Slider {
+                        name: id.to_string(),
+                        value,
+                        min: range.min,
+                        max: range.max,
+                        step: range.step,
+                        disabled,
+                    };
+
+                    self.ui_controls.add(id, slider);
+
+                    let mut arbiter =
+                        previous_arbiters.remove(id).unwrap_or_default();
+                    arbiter.configure(conf.arbitration, conf.osc);
+                    self.arbiters.insert(id.to_string(), arbiter);
+                    if conf.osc {
+                        self.osc_inputs.add(id, conf.range);
+                        osc_input_names.push(id);
+                    }
+                }
+                ControlType::Macro => {
+                    let mut conf: MacroConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    for (target, target_conf) in &conf.targets {
+                        self.macro_targets
+                            .entry(target.clone())
+                            .or_default()
+                            .push((
+                                id.to_string(),
+                                MacroTarget {
+                                    range: target_conf.range(),
+                                    easing: Easing::from_str(
+                                        target_conf.curve(),
+                                    )?,
+                                    invert: target_conf.invert(),
+                                },
+                            ));
+                    }
+
+                    let value = current_values
+                        .get(id)
+                        .and_then(ControlValue::as_float)
+                        .unwrap_or(conf.default);
+
+                    let disabled = Self::extract_disabled_fn(&mut conf.shared);
+
                     let slider = UiControlConfig::Slider {
                         name: id.to_string(),
                         value,
-                        min: conf.range[0],
-                        max: conf.range[1],
-                        step: conf.step,
+                        min: 0.0,
+                        max: 1.0,
+                        step: 0.001,
                         disabled,
                     };
 
@@ -1059,11 +3351,12 @@ impl<T: TimingSource> ControlHub<T> {
                         None
                     };
 
-                    let osc_control = OscControlConfig::new(
+                    let mut osc_control = OscControlConfig::new(
                         id,
                         (conf.range[0], conf.range[1]),
                         conf.default,
                     );
+                    osc_control.display = self.displays.get(id).cloned();
 
                     self.osc_controls
                         .add(&osc_control.address, osc_control.clone());
@@ -1071,6 +3364,41 @@ impl<T: TimingSource> ControlHub<T> {
                     if let Some(value) = existing_value {
                         self.osc_controls.set(&osc_control.address, *value);
                     }
+
+                    match conf.smooth {
+                        Some(smooth) => {
+                            self.smoothing.insert(id.to_string(), smooth);
+                        }
+                        None => {
+                            self.osc_controls.set_smoothing(id, Duration::ZERO)
+                        }
+                    }
+                }
+                ControlType::Landmark => {
+                    let conf: LandmarkConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    let landmark_control = OscControlConfig::landmark(
+                        id,
+                        (conf.range[0], conf.range[1]),
+                        conf.default,
+                        conf.min_visibility,
+                    )?;
+
+                    self.osc_controls.add(id, landmark_control);
+
+                    if let Some(value) = osc_values.get(id) {
+                        self.osc_controls.set(id, *value);
+                    }
+
+                    match conf.smooth {
+                        Some(smooth) => {
+                            self.smoothing.insert(id.to_string(), smooth);
+                        }
+                        None => {
+                            self.osc_controls.set_smoothing(id, Duration::ZERO)
+                        }
+                    }
                 }
                 ControlType::Midi => {
                     let conf: MidiConfig =
@@ -1093,6 +3421,15 @@ impl<T: TimingSource> ControlHub<T> {
                     if let Some(value) = existing_value {
                         self.midi_controls.set(id, *value);
                     }
+
+                    match conf.smooth {
+                        Some(smooth) => {
+                            self.smoothing.insert(id.to_string(), smooth);
+                        }
+                        None => {
+                            self.midi_controls.set_smoothing(id, Duration::ZERO)
+                        }
+                    }
                 }
                 ControlType::Audio => {
                     let conf: AudioConfig =
@@ -1105,14 +3442,168 @@ impl<T: TimingSource> ControlHub<T> {
                         conf.pre,
                         (conf.range[0], conf.range[1]),
                         0.0,
-                    );
+                    )
+                    .with_detect_mode(conf.mode, conf.window);
 
                     self.audio_controls.add(id, audio_control);
                 }
+                ControlType::AudioPitch => {
+                    let conf: AudioPitchConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    let audio_control = AudioControlConfig::new(
+                        conf.channel,
+                        SlewLimiter::new(conf.slew[0], conf.slew[1]),
+                        0.0,
+                        0.0,
+                        (conf.range[0], conf.range[1]),
+                        0.0,
+                    )
+                    .with_pitch_tracking(PitchTracking {
+                        min_frequency: conf.frequencies[0],
+                        max_frequency: conf.frequencies[1],
+                        threshold: conf.threshold,
+                    });
+
+                    self.audio_controls.add(id, audio_control);
+                }
+                ControlType::MidiFile => {
+                    let conf: MidiFileConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    if conf.cc.is_some() == conf.note.is_some() {
+                        error!(
+                            "midi_file control {} must set exactly one of \
+                            `cc` or `note`",
+                            id
+                        );
+                        continue;
+                    }
+
+                    let path = self.script_path(&conf.path);
+                    let file = match loaded_midi_files.get(&path) {
+                        Some(file) => file.clone(),
+                        None => match MidiFile::from_path(&path) {
+                            Ok(file) => {
+                                let file = Arc::new(file);
+                                loaded_midi_files.insert(path, file.clone());
+                                file
+                            }
+                            Err(e) => {
+                                error!("{}", e);
+                                continue;
+                            }
+                        },
+                    };
+
+                    self.midi_files.insert(id.to_string(), (conf, file));
+                }
+                ControlType::MidiVelocity | ControlType::MidiNote => {
+                    let conf: MidiNoteConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    if conf.notes[0] > conf.notes[1] || conf.notes[1] > 127 {
+                        error!(
+                            "Control {} has an invalid `notes` range; \
+                            expected [low, high] within 0-127",
+                            id
+                        );
+                        continue;
+                    }
+
+                    let source = match config.control_type {
+                        ControlType::MidiNote => NoteSource::Note,
+                        _ => NoteSource::Velocity,
+                    };
+
+                    self.midi_note_controls.add(
+                        id,
+                        MidiNoteControlConfig {
+                            source,
+                            channel: conf.channel,
+                            notes: conf.notes,
+                            range: conf.range,
+                            default: conf.default,
+                        },
+                    );
+                    midi_note_names.push(id);
+                }
+                ControlType::Envelope => {
+                    let conf: EnvelopeConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    let stages =
+                        [conf.attack, conf.decay, conf.hold, conf.release];
+                    if stages.iter().any(|beats| *beats < 0.0) {
+                        error!("Control {} has a negative envelope stage", id);
+                        continue;
+                    }
+                    if conf.note.is_some_and(|note| note > 127) {
+                        error!("Control {} has a `note` above 127", id);
+                        continue;
+                    }
+
+                    self.envelopes.add(
+                        id,
+                        EnvelopeControlConfig {
+                            attack: conf.attack,
+                            decay: conf.decay,
+                            sustain: conf.sustain,
+                            hold: conf.hold,
+                            release: conf.release,
+                            range: conf.range,
+                            velocity: conf.velocity,
+                            channel: conf.channel,
+                            note: conf.note,
+                        },
+                    );
+                    envelope_names.push(id);
+                }
+                #[cfg(not(feature = "runtime"))]
+                ControlType::Palette => {
+                    warn!(
+                        "Ignoring palette control `{}`. Palettes require the \
+                        `runtime` feature",
+                        id
+                    );
+                }
+                #[cfg(feature = "runtime")]
+                ControlType::Palette => {
+                    let conf: PaletteConfig =
+                        serde_yml::from_value(config.config.clone())?;
+
+                    match Palette::from_image_path(
+                        self.script_path(&conf.path),
+                        conf.colors,
+                    ) {
+                        Ok(palette) => {
+                            let palette = ternary!(
+                                conf.sort == PaletteSort::Lightness,
+                                palette.by_lightness(),
+                                palette
+                            );
+                            self.palettes.insert(id.to_string(), palette);
+                        }
+                        Err(e) => error!("{}", e),
+                    }
+                }
                 ControlType::Automate => {
                     let conf: AutomateConfig =
                         serde_yml::from_value(config.config.clone())?;
 
+                    Mode::from_str(&conf.mode)?;
+                    let starts_at_zero = conf.breakpoints.first().is_some_and(
+                        |b| matches!(b.position, ParamValue::Cold(p) if p == 0.0),
+                    );
+                    if !starts_at_zero {
+                        return Err(format!(
+                            "automate control {} must start with a breakpoint \
+                            at position 0",
+                            id
+                        )
+                        .into());
+                    }
+
                     let breakpoints = conf
                         .breakpoints
                         .iter()
@@ -1174,10 +3665,11 @@ impl<T: TimingSource> ControlHub<T> {
                     let conf: ModulationConfig =
                         serde_yml::from_value(config.config.clone())?;
 
-                    self.modulations
-                        .entry(conf.source)
-                        .or_default()
-                        .extend(conf.modulators);
+                    self.modulations.entry(conf.source).or_default().extend(
+                        conf.modulators
+                            .into_iter()
+                            .map(|route| (id.to_string(), route)),
+                    );
                 }
                 ControlType::Effects => {
                     let conf: EffectConfig =
@@ -1207,12 +3699,15 @@ impl<T: TimingSource> ControlHub<T> {
                             operator: ref op, ..
                         } => {
                             let mut effect = Math::from_cold_params(&conf);
-                            effect.operator = Operator::from_str(op).unwrap();
+                            effect.operator = Operator::from_str(op)?;
                             Effect::Math(effect)
                         }
-                        EffectKind::Quantizer { range, .. } => {
+                        EffectKind::Quantizer {
+                            range, range_decay, ..
+                        } => {
                             let mut effect = Quantizer::from_cold_params(&conf);
-                            effect.set_range(range);
+                            effect
+                                .set_range(range.to_effect_range(range_decay));
                             Effect::Quantizer(effect)
                         }
                         EffectKind::RingModulator { range, .. } => {
@@ -1230,12 +3725,16 @@ impl<T: TimingSource> ControlHub<T> {
                             SlewLimiter::from_cold_params(&conf),
                         ),
                         EffectKind::WaveFolder {
-                            iterations, range, ..
+                            iterations,
+                            range,
+                            range_decay,
+                            ..
                         } => {
                             let mut effect =
                                 WaveFolder::from_cold_params(&conf);
                             effect.iterations = iterations;
-                            effect.set_range(range);
+                            effect
+                                .set_range(range.to_effect_range(range_decay));
                             Effect::WaveFolder(effect)
                         }
                     };
@@ -1247,26 +3746,33 @@ impl<T: TimingSource> ControlHub<T> {
             }
         }
 
+        self.resolve_aliases();
         self.dep_graph.build_graph();
         trace!("node_graph: {:#?}", self.dep_graph);
 
-        if !self.osc_controls.is_active {
-            self.osc_controls
-                .start()
-                .expect("Unable to start OSC receiver");
+        self.midi_note_controls.retain(&midi_note_names);
+        self.envelopes.retain(&envelope_names);
+        self.osc_inputs.retain(&osc_input_names);
+        if !global::inputs_deferred() {
+            self.start_inputs();
         }
 
-        if !self.midi_controls.is_active() {
-            if let Err(e) = self.midi_controls.start() {
-                warn!("Unable to start MIDI receiver. {}", e);
-            }
-        }
+        let holds = std::mem::take(&mut self.holds);
+        self.holds = holds
+            .into_iter()
+            .filter(|(name, _)| self.has(name))
+            .collect();
+
+        self.last_good_config = Some(control_configs.clone());
+        self.missing.borrow_mut().clear();
+        self.updates_since_populate = 0;
 
         for callback in &self.populated_callbacks {
             callback.call();
         }
 
         self.ui_controls.mark_changed();
+        frame_controller::invalidate();
 
         info!("Controls populated");
 
@@ -1281,16 +3787,65 @@ impl<T: TimingSource> ControlHub<T> {
         }
     }
 
-    fn find_hot_params(&self, raw_config: &serde_yml::Value) -> Node {
-        let mut hot_params = Node::default();
+    /// `var` may be a single alias or a list of them
+    fn parse_vars(raw_config: &serde_yml::Value) -> Vec<String> {
+        Self::parse_string_list(raw_config.get("var"))
+    }
 
-        let obj = match raw_config.as_mapping() {
-            Some(mapping) => mapping,
+    /// Fields like `var` and `group` that take either one string or a list
+    fn parse_string_list(value: Option<&serde_yml::Value>) -> Vec<String> {
+        match value {
+            Some(serde_yml::Value::String(item)) => vec![item.clone()],
+            Some(serde_yml::Value::Sequence(items)) => items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// `$` params and `mod` entries may reference controls by alias. Since an
+    /// alias can be declared after its first use, they can only be resolved
+    /// once every control has been parsed
+    fn resolve_aliases(&mut self) {
+        self.dep_graph.resolve_aliases(&self.vars);
+
+        let modulations = std::mem::take(&mut self.modulations);
+        for (source, mut routes) in modulations {
+            for (_, route) in &mut routes {
+                if let Some(canonical) = self.vars.get(&route.modulator) {
+                    route.modulator = canonical.clone();
+                }
+            }
+            let source = self.canonical_name(&source).to_string();
+            self.modulations.entry(source).or_default().extend(routes);
+        }
+
+        let macro_targets = std::mem::take(&mut self.macro_targets);
+        for (target, macros) in macro_targets {
+            let target = self.canonical_name(&target).to_string();
+            if !self.has(&target) {
+                warn!(
+                    "Macro {} targets unknown control {}",
+                    macros[0].0, target
+                );
+            }
+            self.macro_targets.entry(target).or_default().extend(macros);
+        }
+    }
+
+    fn find_hot_params(&self, raw_config: &serde_yml::Value) -> Node {
+        let mut hot_params = Node::default();
+
+        let obj = match raw_config.as_mapping() {
+            Some(mapping) => mapping,
             None => return hot_params,
         };
 
         for (key, value) in obj {
-            let key_str = key.as_str().unwrap().to_string();
+            let Some(key_str) = key.as_str().map(str::to_string) else {
+                continue;
+            };
 
             if let Some(param) = self.try_parse_hot_param(value) {
                 hot_params.insert(key_str, param);
@@ -1325,7 +3880,7 @@ impl<T: TimingSource> ControlHub<T> {
 
     fn setup_watcher(
         path: PathBuf,
-        state: Arc<Mutex<Option<ConfigFile>>>,
+        state: Arc<Mutex<Option<Reload>>>,
         has_changes: Arc<AtomicBool>,
     ) -> notify::RecommendedWatcher {
         let path_to_watch = path.clone();
@@ -1346,17 +3901,19 @@ impl<T: TimingSource> ControlHub<T> {
 
             info!("{:?} changed. Attempting to reload configuration.", path);
 
-            match Self::parse_from_path(&path) {
-                Ok(new_config) => {
-                    if let Ok(mut guard) = state.lock() {
-                        info!("Loaded new configuration");
-                        has_changes.store(true, Ordering::Release);
-                        *guard = Some(new_config);
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to load updated configuration: {:?}", e)
-                }
+            let reload = Self::parse_from_path(&path);
+            match &reload {
+                Ok(_) => info!("Loaded new configuration"),
+                Err(e) => error!(
+                    "Failed to load updated configuration: {}\n{}",
+                    e,
+                    e.snippet.as_deref().unwrap_or_default()
+                ),
+            }
+
+            if let Ok(mut guard) = state.lock() {
+                *guard = Some(reload);
+                has_changes.store(true, Ordering::Release);
             }
         })
         .expect("Failed to create watcher");
@@ -1369,10 +3926,29 @@ impl<T: TimingSource> ControlHub<T> {
     }
 }
 
+impl<T: TimingSource + Send + Sync + 'static> ControlHub<T> {
+    /// Emit a trigger for every MIDI Note On matching one of `notes`, a list of
+    /// `(trigger name, (channel, note))` pairs. Events are stamped with the
+    /// hub's timing from the MIDI thread so they keep sub-frame accuracy.
+    pub fn listen_midi_notes(
+        &self,
+        notes: &[(&str, (u8, u8))],
+    ) -> Result<(), Box<dyn Error>> {
+        let lookup = notes
+            .iter()
+            .map(|(name, ch_note)| (*ch_note, name.to_string()))
+            .collect();
+        let timing = self.animation.timing.clone();
+        self.trigger_bus
+            .listen_midi_notes(lookup, move || timing.beats())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serial_test::serial;
+    use std::rc::Rc;
 
     // 1 frame = 1/16; 4 frames per beat; 16 frames per bar
     use crate::framework::motion::animation_tests::{BPM, init};
@@ -1474,6 +4050,27 @@ automate:
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_landmark() {
+        let hub = create_instance(
+            r#"
+body.left_wrist.y:
+  type: landmark
+  range: [-1, 1]
+  default: 0.25
+            "#,
+        );
+
+        assert_eq!(hub.get("body.left_wrist.y"), 0.25);
+
+        let config = hub.osc_controls.config("body.left_wrist.y").unwrap();
+        let source = config.landmark.unwrap();
+        assert_eq!(source.address, "landmark/body/left_wrist");
+        assert_eq!(source.axis, 1);
+        assert_eq!(source.min_visibility, 0.5);
+    }
+
     #[test]
     #[serial]
     #[ignore]
@@ -1516,6 +4113,191 @@ c:
         assert_eq!(controls.get("c"), 30.0);
     }
 
+    #[test]
+    #[serial]
+    fn test_stepped_transition() {
+        use crate::assert_approx_eq;
+
+        let mut hub = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0.0, 10.0]
+b:
+  type: slider
+  range: [0.0, 10.0]
+  stepped: start
+c:
+  type: slider
+  range: [0.0, 10.0]
+  stepped: end
+"#,
+        );
+
+        hub.set_transition_time(1.0);
+        for name in ["a", "b", "c"] {
+            hub.ui_controls.set(name, ControlValue::Float(10.0));
+        }
+        hub.take_snapshot("full");
+        for name in ["a", "b", "c"] {
+            hub.ui_controls.set(name, ControlValue::Float(0.0));
+        }
+
+        init(0);
+        hub.recall_snapshot("full").unwrap();
+
+        init(2);
+        assert_approx_eq!(hub.get("a"), 5.0);
+        assert_eq!(hub.get("b"), 10.0);
+        assert_eq!(hub.get("c"), 0.0);
+
+        init(4);
+        assert_eq!(hub.get("c"), 10.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_snapshot_phase() {
+        let mut hub = create_instance(
+            r#"
+ramp:
+  type: ramp
+  beats: 4
+"#,
+        );
+
+        init(4);
+        hub.take_snapshot("no_phase");
+        hub.set_snapshot_phase(true);
+        hub.take_snapshot("phase");
+        assert_eq!(hub.get("ramp"), 0.25);
+
+        init(8);
+        hub.recall_snapshot("no_phase").unwrap();
+        assert_eq!(hub.get("ramp"), 0.5);
+
+        hub.recall_snapshot("phase").unwrap();
+        assert_eq!(hub.get("ramp"), 0.25);
+
+        // Carries on from the recalled phase
+        init(10);
+        assert_eq!(hub.get("ramp"), 0.375);
+    }
+
+    #[test]
+    #[serial]
+    fn test_phase_offset_skips_params() {
+        let mut hub = create_instance(
+            r#"
+b:
+  type: ramp
+  beats: 16
+a:
+  type: ramp
+  beats: 4
+  phase: $b
+"#,
+        );
+
+        init(0);
+        hub.phase_offsets.insert("a".to_string(), 1.0);
+
+        // `b` resolves on the shared clock while only `a` runs ahead
+        assert_eq!(hub.get("b"), 0.0);
+        assert_eq!(hub.get("a"), 0.25);
+    }
+
+    #[test]
+    #[serial]
+    fn test_recall_snapshot_only() {
+        let mut hub = create_instance(
+            r#"
+hue:
+  type: slider
+  group: color
+saturation:
+  type: slider
+  var: s
+  group: [color, tone]
+speed:
+  type: slider
+invert:
+  type: checkbox
+  group: tone
+"#,
+        );
+
+        assert_eq!(hub.group("color"), ["hue", "saturation"]);
+        assert_eq!(hub.group_names(), vec!["color", "tone"]);
+        assert_eq!(hub.groups_of("s"), vec!["color", "tone"]);
+
+        hub.set_transition_time(1.0);
+        for name in ["hue", "saturation", "speed"] {
+            hub.ui_controls.set(name, ControlValue::Float(1.0));
+        }
+        hub.ui_controls.set("invert", ControlValue::Bool(true));
+        hub.take_snapshot("1");
+        for name in ["hue", "saturation", "speed"] {
+            hub.ui_controls.set(name, ControlValue::Float(0.0));
+        }
+        hub.ui_controls.set("invert", ControlValue::Bool(false));
+
+        init(0);
+        hub.recall_snapshot_only("1", &["color".to_string()])
+            .unwrap();
+        init(4);
+        assert_eq!(hub.get("hue"), 1.0);
+        assert_eq!(hub.get("saturation"), 1.0);
+        assert_eq!(hub.get("speed"), 0.0);
+        assert!(!hub.bool("invert"));
+
+        hub.recall_snapshot_only("1", &["invert".to_string()])
+            .unwrap();
+        assert!(hub.bool("invert"));
+        assert!(
+            hub.recall_snapshot_only("1", &[]).is_err(),
+            "an empty selection recalls nothing"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_diff_snapshots() {
+        let mut hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.25
+b:
+  type: checkbox
+c:
+  type: osc
+  default: 0.5
+"#,
+        );
+
+        hub.take_snapshot("1");
+        hub.ui_controls.set("a", ControlValue::Float(0.75));
+        hub.ui_controls.set("b", ControlValue::Bool(true));
+        hub.take_snapshot("2");
+
+        let deltas = hub.diff_snapshots("1", "2").unwrap();
+        let names: Vec<_> = deltas.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(deltas[0].delta, Some(0.5));
+        assert_eq!(deltas[1].a, Some(ControlValue::Bool(false)));
+        assert_eq!(deltas[1].delta, None);
+        assert!(hub.diff_snapshots("1", "3").is_err());
+
+        let copied = hub
+            .copy_snapshot_values("2", "1", &["b".to_string(), "x".to_string()])
+            .unwrap();
+        assert_eq!(copied, 1);
+        let deltas = hub.diff_snapshots("1", "2").unwrap();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].name, "a");
+    }
+
     #[test]
     #[serial]
     // #[ignore]
@@ -1571,4 +4353,1786 @@ foo_animation:
         init(1);
         assert_eq!(hub.get("foo_animation"), 99.0);
     }
+
+    #[test]
+    #[serial]
+    fn test_gate_triggers() {
+        let mut hub = create_instance(
+            r#"
+level:
+  type: slider
+  default: 0
+            "#,
+        );
+
+        hub.gate("hit", "level", 0.5);
+
+        init(0);
+        hub.update();
+        assert_eq!(hub.triggers("hit").count(), 0);
+
+        hub.ui_controls.set("level", ControlValue::Float(0.8));
+        init(2);
+        hub.update();
+        hub.emit_trigger("hit", 1.0);
+
+        let events: Vec<TriggerEvent> = hub.triggers("hit").collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].velocity, 0.8);
+        assert_eq!(events[0].beat, events[1].beat);
+        assert_eq!(hub.triggers("hit").count(), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_dirty() {
+        let mut hub = create_instance(
+            r#"
+slider:
+  type: slider
+  default: 0.5
+
+wave:
+  type: triangle
+  beats: 4
+            "#,
+        );
+
+        init(0);
+        assert!(hub.check_dirty());
+        assert!(!hub.check_dirty());
+
+        hub.get("slider");
+        assert!(!hub.check_dirty());
+
+        hub.ui_controls.set("slider", ControlValue::Float(0.75));
+        assert!(hub.check_dirty());
+        assert!(!hub.check_dirty());
+
+        hub.get("wave");
+        assert!(hub.check_dirty());
+        assert!(!hub.check_dirty());
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "runtime")]
+    fn test_post_effects() {
+        let hub = create_instance(
+            r#"
+glow:
+  type: slider
+  default: 0.25
+
+post:
+  - bloom:
+      intensity: $glow
+  - vignette:
+  - grain:
+      amount: 0.1
+            "#,
+        );
+
+        init(0);
+        let effects = hub.post_effects();
+        assert_eq!(effects.len(), 3);
+        assert_eq!(
+            effects[0],
+            PostEffect::Bloom {
+                threshold: 0.7,
+                intensity: 0.25,
+                radius: 4.0,
+            }
+        );
+        assert_eq!(
+            effects[1],
+            PostEffect::Vignette {
+                amount: 0.5,
+                softness: 0.5,
+            }
+        );
+        assert_eq!(
+            effects[2],
+            PostEffect::Grain {
+                amount: 0.1,
+                size: 1.0,
+            }
+        );
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "runtime")]
+    fn test_post_effects_glitch() {
+        let hub = create_instance(
+            r#"
+passes:
+  type: slider
+  range: [0, 64]
+  default: 7.6
+
+post:
+  - pixel_sort:
+      direction: left
+      iterations: $passes
+  - datamosh:
+      probability: 0.5
+  - scanline_glitch:
+            "#,
+        );
+
+        init(0);
+        let effects = hub.post_effects();
+        assert_eq!(
+            effects,
+            vec![
+                PostEffect::PixelSort {
+                    threshold: 0.5,
+                    direction: [-1.0, 0.0],
+                    iterations: 8,
+                },
+                PostEffect::Datamosh {
+                    amount: 20.0,
+                    block_size: 16.0,
+                    probability: 0.5,
+                    hold: 4.0,
+                },
+                PostEffect::ScanlineGlitch {
+                    amount: 20.0,
+                    frequency: 0.1,
+                    line_height: 4.0,
+                    scanlines: 0.25,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_repeat() {
+        let hub = create_instance(
+            r#"
+scale:
+  type: slider
+  default: 0.1
+
+quad:
+  repeat: 3
+  controls:
+    strength:
+      type: slider
+      default: 0.5
+    radius:
+      type: slider
+      range: [0, 10]
+      default: 2
+            "#,
+        );
+
+        init(0);
+        assert_eq!(hub.repeat_count("quad"), 3);
+        assert_eq!(hub.repeat_count("scale"), 0);
+        assert!(hub.has("quad_1.strength"));
+        assert!(hub.has("quad_3.radius"));
+        assert!(!hub.has("quad_4.strength"));
+        assert_eq!(hub.get_indexed("quad", 2, "strength"), 0.5);
+        assert_eq!(hub.get_indexed("quad", 0, "radius"), 2.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_describe() {
+        let hub = create_instance(
+            r#"
+radius:
+  type: slider
+  var: a1
+  help: >
+    Size of each circle
+    relative to the window
+wobble:
+  type: triangle
+  beats: 4
+  tooltip: How far circles drift
+count:
+  type: slider
+            "#,
+        );
+
+        assert_eq!(
+            hub.describe("radius"),
+            Some("Size of each circle relative to the window")
+        );
+        assert_eq!(hub.describe("a1"), hub.describe("radius"));
+        assert_eq!(hub.describe("wobble"), Some("How far circles drift"));
+        assert_eq!(hub.describe("count"), None);
+        assert_eq!(hub.describe("nope"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_display() {
+        let hub = create_instance(
+            r#"
+mix:
+  type: slider
+  default: 0.25
+  var: a1
+  unit: "%"
+  display:
+    decimals: 0
+    multiplier: 100
+angle:
+  type: slider
+  range: [0, 360]
+  unit: deg
+count:
+  type: slider
+            "#,
+        );
+
+        init(0);
+        assert_eq!(hub.get("mix"), 0.25);
+        assert_eq!(hub.display("a1").unwrap().format(hub.get("mix")), "25%");
+        assert_eq!(hub.display("angle").unwrap().multiplier, 1.0);
+        assert_eq!(hub.display("angle").unwrap().format(90.0), "90deg");
+        assert_eq!(hub.display("count"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_repeat_sibling_references() {
+        let config = ControlHub::<FrameTiming>::parse_from_str(
+            r#"
+scale:
+  type: slider
+
+quad:
+  repeat: 2
+  controls:
+    speed:
+      type: slider
+    wobble:
+      type: triangle
+      beats: $speed
+      phase: $scale
+            "#,
+        )
+        .unwrap();
+
+        let MaybeControlConfig::Control(wobble) = &config["quad_2.wobble"]
+        else {
+            panic!("quad_2.wobble should be a control");
+        };
+        assert_eq!(wobble.config["beats"].as_str(), Some("$quad_2.speed"));
+        assert_eq!(wobble.config["phase"].as_str(), Some("$scale"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_repeat_collision() {
+        let result = ControlHub::<FrameTiming>::parse_from_str(
+            r#"
+quad_1.strength:
+  type: slider
+
+quad:
+  repeat: 2
+  controls:
+    strength:
+      type: slider
+            "#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_inputs() {
+        let hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+
+inputs:
+  source: shader_to_texture_dev
+  noise: noise_dev
+            "#,
+        );
+
+        let inputs: Vec<_> = hub.inputs().iter().collect();
+        assert_eq!(
+            inputs,
+            vec![
+                (&"source".to_string(), &"shader_to_texture_dev".to_string()),
+                (&"noise".to_string(), &"noise_dev".to_string()),
+            ]
+        );
+        assert!(hub.has("a"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_renames() {
+        let hub = create_instance(
+            r#"
+size:
+  type: slider
+
+renames:
+  radius: size
+            "#,
+        );
+
+        assert_eq!(hub.renames().get("radius"), Some(&"size".to_string()));
+        assert!(hub.has("size"));
+        assert!(!hub.has("renames"));
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "runtime")]
+    fn test_post_effects_lut() {
+        let path = std::env::temp_dir().join("xtal_test_post_effects.cube");
+        fs::write(
+            &path,
+            "LUT_3D_SIZE 2\n0 0 0\n1 0 0\n0 1 0\n1 1 0\n\
+             0 0 1\n1 0 1\n0 1 1\n1 1 1\n",
+        )
+        .unwrap();
+
+        let mut hub = create_instance(&format!(
+            "post:\n  - lut:\n      path: {}\n      strength: 0.5\n",
+            path.display()
+        ));
+
+        init(0);
+        assert!(hub.post_effects().is_empty());
+
+        hub.update();
+        let effects = hub.post_effects();
+        assert_eq!(effects.len(), 1);
+        let PostEffect::Lut { lut, strength } = &effects[0] else {
+            panic!("Expected a LUT effect, got {:?}", effects[0]);
+        };
+        assert_eq!(lut.size, 2);
+        assert_eq!(*strength, 0.5);
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "runtime")]
+    fn test_palette() {
+        use crate::assert_approx_eq;
+
+        let path = std::env::temp_dir().join("xtal_test_palette.png");
+        nannou::image::RgbImage::from_fn(4, 4, |x, _| {
+            nannou::image::Rgb(ternary!(x == 0, [0, 0, 255], [255, 0, 0]))
+        })
+        .save(&path)
+        .unwrap();
+
+        let hub = create_instance(&format!(
+            "mood:\n  type: palette\n  path: {}\n  colors: 2\n",
+            path.display()
+        ));
+
+        let palette = hub.palette("mood").unwrap();
+        assert_eq!(palette.len(), 2);
+        assert_approx_eq!(palette.weights()[0], 0.75);
+        assert_approx_eq!(palette.colors()[0].red, 1.0);
+        assert!(hub.palette("missing").is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_midi_file() {
+        use crate::framework::midi_file::midi_file_tests::fixture;
+
+        let path = std::env::temp_dir().join("xtal_test_midi_file.mid");
+        fs::write(&path, fixture()).unwrap();
+
+        let hub = create_instance(&format!(
+            r#"
+cutoff:
+  type: midi_file
+  path: {path}
+  cc: 74
+  range: [0.0, 100.0]
+
+kick:
+  type: midi_file
+  path: {path}
+  channel: 9
+  note: 36
+
+invalid:
+  type: midi_file
+  path: {path}
+"#,
+            path = path.display()
+        ));
+
+        init(2);
+        assert_eq!(hub.get("cutoff"), 0.0);
+        assert_eq!(hub.get("kick"), 0.0);
+
+        init(4);
+        assert_eq!(hub.get("cutoff"), 100.0);
+
+        init(9);
+        assert_eq!(hub.get("kick"), 1.0);
+
+        // Loops after two bars
+        init(32);
+        assert_eq!(hub.get("kick"), 1.0);
+        assert_eq!(hub.get("cutoff"), 0.0);
+
+        assert!(!hub.midi_files.contains_key("invalid"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_midi_velocity_and_note() {
+        let hub = create_instance(
+            r#"
+accent:
+  type: midi_velocity
+  channel: 9
+  range: [0.0, 10.0]
+  default: 5.0
+
+position:
+  type: midi_note
+  notes: [48, 72]
+
+invalid:
+  type: midi_note
+  notes: [72, 48]
+"#,
+        );
+
+        assert_eq!(hub.get("accent"), 5.0);
+        assert_eq!(hub.get("position"), 0.0);
+
+        hub.midi_note_controls.receive(9, 60, 127);
+        assert_eq!(hub.get("accent"), 10.0);
+        assert_eq!(hub.get("position"), 0.5);
+
+        hub.midi_note_controls.receive(0, 72, 64);
+        assert_eq!(hub.get("accent"), 10.0);
+        assert_eq!(hub.get("position"), 1.0);
+
+        assert!(!hub.has("invalid"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_envelope() {
+        use crate::assert_approx_eq;
+
+        let mut hub = create_instance(
+            r#"
+flash:
+  type: adsr
+  attack: 1
+  decay: 1
+  sustain: 0.5
+  hold: 1
+  release: 1
+  range: [0.0, 10.0]
+  note: 36
+
+pluck:
+  type: env
+  velocity: false
+
+invalid:
+  type: adsr
+  release: -1
+"#,
+        );
+
+        init(0);
+        assert_eq!(hub.get("flash"), 0.0);
+        assert!(hub.trigger_envelope("flash", 1.0));
+        assert!(!hub.trigger_envelope("invalid", 1.0));
+
+        init(2);
+        assert_approx_eq!(hub.get("flash"), 5.0);
+        init(6);
+        assert_approx_eq!(hub.get("flash"), 7.5);
+        init(10);
+        assert_approx_eq!(hub.get("flash"), 5.0);
+        init(16);
+        assert_eq!(hub.get("flash"), 0.0);
+
+        // MIDI hits wait for the next update to be stamped with a beat
+        hub.envelopes.receive(0, 36, 127);
+        hub.envelopes.queue("pluck", 0.25);
+        assert_eq!(hub.get("flash"), 0.0);
+        hub.update();
+        init(18);
+        assert_approx_eq!(hub.get("pluck"), 0.5);
+        init(20);
+        assert_approx_eq!(hub.get("flash"), 10.0);
+
+        assert!(!hub.has("invalid"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_input_arbitration() {
+        let mut hub = create_instance(
+            r#"
+size:
+  type: slider
+  range: [0, 10]
+  default: 5
+  osc: true
+
+speed:
+  type: slider
+  arbitration: latest
+  osc: true
+"#,
+        );
+
+        init(0);
+        hub.update();
+        assert_eq!(hub.active_input("size"), Some(InputSource::Ui));
+        assert_eq!(
+            hub.input_sources("size"),
+            vec![InputSource::Ui, InputSource::Osc]
+        );
+        assert_eq!(hub.last_input("size"), None);
+        assert!(hub.take_input_changes().is_empty());
+
+        hub.osc_inputs.receive("size", 0.25);
+        hub.update();
+        assert_eq!(
+            hub.take_input_changes(),
+            HashSet::from_iter(["size".to_string()])
+        );
+        assert!(hub.take_input_changes().is_empty());
+        hub.osc_inputs.receive("speed", 0.25);
+        hub.update();
+        init(1);
+        assert_eq!(hub.get("size"), 2.5);
+        assert_eq!(hub.last_input("size"), Some(InputSource::Osc));
+
+        // Moving the UI only takes over with the `latest` policy
+        hub.ui_controls.set("size", ControlValue::Float(8.0));
+        hub.ui_controls.set("speed", ControlValue::Float(0.75));
+        hub.update();
+        init(2);
+        assert_eq!(hub.get("size"), 2.5);
+        assert_eq!(hub.last_input("size"), Some(InputSource::Ui));
+        assert_eq!(hub.get("speed"), 0.75);
+        assert_eq!(hub.active_input("speed"), Some(InputSource::Ui));
+
+        // A MIDI mapping outranks OSC
+        hub.midi_controls.add(
+            &MapMode::proxy_name("size"),
+            MidiControlConfig::new((0, 1), (0.0, 10.0), 1.0),
+        );
+        init(3);
+        assert_eq!(hub.get("size"), 1.0);
+        assert_eq!(hub.active_input("size"), Some(InputSource::Midi));
+        assert_eq!(hub.active_input("missing"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_preview() {
+        use crate::assert_approx_eq;
+
+        let hub = create_instance(
+            r#"
+lane:
+  type: triangle
+  beats: 4
+  map_to: [0.0, 10.0]
+"#,
+        );
+
+        init(4);
+        let values = hub.preview("lane", 4, 4.0);
+        for (value, expected) in values.iter().zip([5.0, 10.0, 5.0, 0.0]) {
+            assert_approx_eq!(*value, expected);
+        }
+        assert_eq!(values.len(), 4);
+        assert_approx_eq!(hub.get("lane"), 5.0);
+
+        assert!(hub.preview("missing", 8, 2.0).is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_value_recording() {
+        let mut hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+b:
+  type: slider
+  default: 0.25
+  group: pair
+c:
+  type: slider
+  default: 0.75
+  group: pair
+"#,
+        );
+        let path = std::env::temp_dir().join("xtal_test_value_recording.csv");
+
+        let missing = vec!["missing".to_string()];
+        assert!(hub.start_value_recording(&path, &missing).is_err());
+
+        let selection = vec!["pair".to_string(), "a".to_string()];
+        hub.start_value_recording(&path, &selection).unwrap();
+        assert!(hub.is_recording_values());
+
+        init(0);
+        hub.update();
+        hub.get("a");
+        hub.get("b");
+        init(1);
+        hub.update();
+        hub.get("c");
+
+        let (_, rows) = hub.stop_value_recording().unwrap();
+        assert_eq!(rows, 2);
+        assert!(!hub.is_recording_values());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "frame,beats,b,c,a\n0,0,0.25,,0.5\n1,0.25,0.25,0.75,0.5\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_replay() {
+        let mut hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+b:
+  type: slider
+  default: 0.25
+"#,
+        );
+        let path = std::env::temp_dir().join("xtal_test_replay.csv");
+        fs::write(&path, "frame,beats,a\n0,0,0\n4,1,1\n").unwrap();
+
+        hub.start_replay(&path).unwrap();
+        assert!(hub.is_replaying());
+
+        init(0);
+        assert_eq!(hub.get("a"), 0.0);
+        assert_eq!(hub.get("b"), 0.25);
+        init(2);
+        assert_eq!(hub.get("a"), 0.5);
+        init(8);
+        assert_eq!(hub.get("a"), 1.0);
+
+        hub.stop_replay();
+        assert_eq!(hub.get("a"), 0.5);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_control_history() {
+        let mut hub = create_instance(
+            r#"
+slider:
+  type: slider
+  default: 0.5
+
+triangle:
+  type: triangle
+  beats: 1
+"#,
+        );
+
+        init(0);
+        hub.get("triangle");
+        assert!(hub.history().is_empty());
+
+        hub.set_history_tracking(HistoryTracking::Only(vec![
+            "triangle".to_string(),
+        ]));
+
+        let mut expected = vec![];
+        for frame in 0..3 {
+            init(frame);
+            hub.get("slider");
+            hub.get("triangle");
+            expected.push(hub.get("triangle"));
+        }
+
+        let history = hub.history();
+        assert_eq!(history["triangle"], expected);
+        assert!(!history.contains_key("slider"));
+
+        hub.set_history_tracking(HistoryTracking::All);
+        hub.get("slider");
+        assert_eq!(hub.history()["slider"], vec![0.5]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_modulation_operators() {
+        use crate::assert_approx_eq;
+
+        let hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+
+b:
+  type: slider
+  default: 0.25
+
+c:
+  type: slider
+  default: 0.8
+
+d:
+  type: slider
+  default: 0.4
+
+clamp:
+  type: effect
+  kind: constrain
+  range: [0.0, 0.2]
+
+a_mod:
+  type: mod
+  source: a
+  modulators:
+    - b: add
+    - c:
+        crossfade: 0.5
+
+d_mod:
+  type: mod
+  source: d
+  modulators:
+    - b
+    - clamp: add
+"#,
+        );
+
+        init(0);
+        // (0.5 + 0.25) halfway to 0.8
+        assert_approx_eq!(hub.get("a"), 0.775);
+        // 0.4 * 0.25, plus the effect's output rather than replacing with it
+        assert_approx_eq!(hub.get("d"), 0.2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_runtime_bypass_and_solo() {
+        use crate::assert_approx_eq;
+
+        let mut hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+
+b:
+  type: slider
+  default: 0.25
+
+c:
+  type: slider
+  default: 0.1
+
+a_mod:
+  type: mod
+  source: a
+  modulators:
+    - b: add
+    - c: add
+"#,
+        );
+
+        init(0);
+        assert_approx_eq!(hub.get("a"), 0.85);
+
+        hub.set_runtime_bypass("b", true).unwrap();
+        assert_approx_eq!(hub.get("a"), 0.6);
+        assert_eq!(hub.runtime_bypassed(), vec!["b".to_string()]);
+
+        hub.set_runtime_bypass("a_mod", true).unwrap();
+        assert_approx_eq!(hub.get("a"), 0.5);
+
+        hub.set_runtime_bypass("a_mod", false).unwrap();
+        hub.set_runtime_bypass("b", false).unwrap();
+        hub.set_solo(Some("c")).unwrap();
+        assert_approx_eq!(hub.get("a"), 0.6);
+
+        hub.set_solo(None).unwrap();
+        assert_approx_eq!(hub.get("a"), 0.85);
+
+        // Bypassed controls hold their value
+        hub.set_runtime_bypass("a", true).unwrap();
+        hub.ui_controls.set("b", ControlValue::Float(0.0));
+        assert_approx_eq!(hub.get("a"), 0.85);
+
+        assert!(hub.set_runtime_bypass("nope", true).is_err());
+        assert!(hub.set_solo(Some("a")).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_hold() {
+        use crate::assert_approx_eq;
+
+        let mut hub = create_instance(
+            r#"
+lfo:
+  type: triangle
+  beats: 4
+  var: l
+
+size:
+  type: slider
+  default: 0.5
+
+size_mod:
+  type: mod
+  source: size
+  modulators:
+    - lfo: add
+"#,
+        );
+
+        init(2);
+        let lfo = hub.get("lfo");
+        hub.set_hold("l", true).unwrap();
+        assert!(hub.is_held("lfo"));
+        assert_eq!(hub.held(), vec!["lfo".to_string()]);
+
+        init(4);
+        assert_eq!(hub.get("lfo"), lfo);
+        hub.ui_controls.set("size", ControlValue::Float(0.25));
+        assert_approx_eq!(hub.get("size"), 0.25 + lfo);
+
+        hub.set_hold("lfo", false).unwrap();
+        assert_ne!(hub.get("lfo"), lfo);
+        assert!(hub.set_hold("nope", true).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_macro() {
+        use crate::assert_approx_eq;
+
+        let hub = create_instance(
+            r#"
+size:
+  type: slider
+  range: [0.0, 100.0]
+  default: 50.0
+
+blur:
+  type: slider
+  var: b
+
+speed:
+  type: slider
+
+intensity:
+  type: macro
+  default: 0.25
+  targets:
+    size: [10.0, 20.0]
+    b:
+      range: [0.0, 1.0]
+      invert: true
+    speed:
+      range: [0.0, 1.0]
+      curve: ease_in
+"#,
+        );
+
+        assert_approx_eq!(hub.get("size"), 12.5);
+        assert_approx_eq!(hub.get("blur"), 0.75);
+        assert_approx_eq!(hub.get("speed"), 0.0625);
+        assert_approx_eq!(hub.get("intensity"), 0.25);
+    }
+
+    #[test]
+    #[serial]
+    fn test_humanize() {
+        let hub = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0.0, 10.0]
+  default: 5.0
+  humanize:
+    amount: 0.1
+    rate: 0.5
+
+b:
+  type: slider
+  default: 0.5
+"#,
+        );
+
+        let mut values = vec![];
+        for frame in 0..64 {
+            init(frame);
+            let value = hub.get("a");
+            assert!((4.0..=6.0).contains(&value), "{}", value);
+            values.push(value);
+            assert_eq!(hub.get("b"), 0.5);
+        }
+        assert!(values.iter().any(|v| *v != 5.0));
+
+        // Deterministic per beat
+        init(17);
+        assert_eq!(hub.get("a"), values[17]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_map_to() {
+        use crate::assert_approx_eq;
+
+        let hub = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0.0, 10.0]
+  default: 5.0
+  var: a1
+  map_to: [100.0, 200.0]
+
+b:
+  type: slider
+  default: 0.5
+  map_to:
+    range: [0.0, 2.0]
+    easing: ease_in
+
+c:
+  type: slider
+  default: 0.1
+
+c_mod:
+  type: mod
+  source: c
+  modulators:
+    - a
+"#,
+        );
+
+        init(0);
+        assert_approx_eq!(hub.get("a"), 150.0);
+        assert_approx_eq!(hub.get("a1"), 150.0);
+        assert_approx_eq!(hub.get("b"), 0.5);
+        // Modulators see the unmapped value
+        assert_approx_eq!(hub.get("c"), 0.5);
+    }
+
+    #[test]
+    #[serial]
+    fn test_multiple_vars() {
+        use crate::assert_approx_eq;
+
+        let hub = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.5
+  var: [a1, radius]
+
+b:
+  type: triangle
+  beats: 4
+  phase: $radius
+
+c:
+  type: slider
+  default: 0.0
+  var: a1
+"#,
+        );
+
+        init(0);
+        assert_approx_eq!(hub.get("radius"), 0.5);
+        assert_eq!(hub.canonical_name("a1"), "a");
+        assert_eq!(hub.canonical_name("a"), "a");
+        assert_eq!(hub.aliases_of("a"), vec!["a1", "radius"]);
+        assert_eq!(hub.aliases_of("radius"), vec!["a1", "radius"]);
+
+        // `$radius` resolves to `a` in the dep graph
+        assert_eq!(hub.dep_graph.order().clone(), Some(vec!["a".to_string()]));
+        assert_approx_eq!(hub.get("b"), 0.5);
+
+        // The first control to claim an alias keeps it
+        assert_approx_eq!(hub.get("a1"), 0.5);
+    }
+
+    #[test]
+    #[serial]
+    fn test_beats_read_once_per_frame() {
+        use crate::assert_approx_eq;
+
+        init(0);
+        let mut hub = ControlHub::new(
+            Some("a:\n  type: ramp\n  beats: 4\n"),
+            ManualTiming::new(Bpm::new(BPM)),
+        );
+        hub.update();
+        assert_approx_eq!(hub.get("a"), 0.0);
+
+        // A clock that moves mid-frame doesn't change what this frame sees
+        hub.animation.timing.set_beats(1.0);
+        assert_approx_eq!(hub.get("a"), 0.0);
+
+        hub.update();
+        assert_approx_eq!(hub.get("a"), 0.25);
+    }
+
+    #[test]
+    #[serial]
+    fn test_without_inputs() {
+        let mut hub = global::without_inputs(|| {
+            create_instance("a:\n  type: slider\n  osc: true\n")
+        });
+        assert!(!global::inputs_deferred());
+        assert!(!hub.osc_controls.is_active);
+
+        hub.connect_inputs();
+        assert!(hub.osc_controls.is_active);
+    }
+
+    #[test]
+    #[serial]
+    fn test_debug_graph() {
+        let hub = create_instance(
+            r#"
+slider:
+  type: slider
+  var: a1
+  default: 0.5
+
+triangle:
+  type: triangle
+  beats: 4
+  phase: $slider
+
+clamp:
+  type: effect
+  kind: constrain
+  range: [0.0, 0.8]
+
+triangle_mod:
+  type: mod
+  source: triangle
+  modulators:
+    - clamp
+"#,
+        );
+
+        init(0);
+        hub.get("triangle");
+        let graph = hub.debug_graph();
+
+        let names: Vec<&str> =
+            graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["clamp", "slider", "triangle"]);
+
+        let slider = &graph.nodes[1];
+        assert_eq!(slider.kind, "slider");
+        assert_eq!(slider.cached, Some(0.5));
+        assert_eq!(slider.proxy, None);
+
+        let triangle = &graph.nodes[2];
+        assert_eq!(triangle.kind, "triangle");
+        assert_eq!(triangle.modulators, vec!["clamp"]);
+
+        assert_eq!(
+            graph.edges,
+            vec![
+                DebugEdge {
+                    from: "clamp".to_string(),
+                    to: "triangle".to_string(),
+                    via: "modulation".to_string(),
+                },
+                DebugEdge {
+                    from: "slider".to_string(),
+                    to: "triangle".to_string(),
+                    via: "phase".to_string(),
+                },
+            ]
+        );
+        assert_eq!(graph.eval_order, vec!["slider"]);
+        assert_eq!(graph.aliases["a1"], "slider");
+    }
+
+    #[test]
+    #[serial]
+    fn test_reload_keeps_last_good_config() {
+        init(0);
+
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  default: 0.25
+
+b:
+  type: triangle
+  beats: 4
+"#,
+        );
+        controls.ui_controls.set("a", ControlValue::Float(0.75));
+
+        let errors = Rc::new(Cell::new(0));
+        let errors_clone = errors.clone();
+        controls.register_script_error_callback(move || {
+            errors_clone.set(errors_clone.get() + 1);
+        });
+
+        let broken = ControlHub::<FrameTiming>::parse_from_str(
+            r#"
+a:
+  type: slider
+
+c:
+  type: effect
+  kind: math
+  operator: nope
+  operand: 1
+"#,
+        )
+        .unwrap();
+        controls.apply_reloaded_config(&broken);
+
+        assert!(controls.script_error().is_some());
+        assert_eq!(errors.get(), 1);
+        assert_eq!(controls.get("a"), 0.75);
+        assert!(controls.animations.contains_key("b"));
+
+        let fixed =
+            ControlHub::<FrameTiming>::parse_from_str("a:\n  type: slider\n")
+                .unwrap();
+        controls.apply_reloaded_config(&fixed);
+
+        assert!(controls.script_error().is_none());
+        assert_eq!(errors.get(), 2);
+        assert_eq!(controls.get("a"), 0.75);
+        assert!(!controls.animations.contains_key("b"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_normalized() {
+        init(0);
+
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  range: [10, 20]
+  default: 15
+
+b:
+  type: slider
+  range: [0, 1]
+  default: 0.5
+  map_to:
+    range: [-1, 1]
+
+c:
+  type: checkbox
+  default: true
+"#,
+        );
+
+        assert_eq!(controls.range("a"), Some([10.0, 20.0]));
+        assert_eq!(controls.get_normalized("a"), 0.5);
+        assert_eq!(controls.range("b"), Some([-1.0, 1.0]));
+        assert_eq!(controls.get_normalized("b"), 0.5);
+        assert_eq!(controls.get_normalized("missing"), 0.0);
+        assert_eq!(controls.range("missing"), None);
+
+        controls.set_normalized("a", 0.25).unwrap();
+        assert_eq!(controls.get("a"), 12.5);
+        assert_eq!(controls.get_normalized("a"), 0.25);
+
+        controls.set_normalized("b", 1.0).unwrap();
+        assert_eq!(controls.get("b"), 1.0);
+
+        assert!(controls.set_normalized("c", 1.0).is_err());
+        assert!(controls.set_normalized("missing", 1.0).is_err());
+
+        controls.set("a", 18.0).unwrap();
+        assert_eq!(controls.get("a"), 18.0);
+        assert!(controls.set("c", 1.0).is_err());
+        assert!(controls.set("missing", 1.0).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_slider_range_override() {
+        init(0);
+
+        let mut controls = create_instance(
+            r#"
+a:
+  type: slider
+  range: [0, 1]
+  default: 0.75
+"#,
+        );
+
+        let proxy = MapMode::proxy_name("a");
+        controls
+            .midi_controls
+            .add(&proxy, MidiControlConfig::new((0, 1), (0.0, 1.0), 0.0));
+        controls.midi_controls.set(&proxy, 0.5);
+
+        let range = SliderRange {
+            min: 0.0,
+            max: 0.5,
+            step: 0.01,
+        };
+        controls.set_slider_range("a", range).unwrap();
+        assert_eq!(controls.ui_controls.slider_range("a"), Some((0.0, 0.5)));
+        assert_eq!(controls.ui_controls.float("a"), 0.5);
+        assert_eq!(controls.midi_controls.get(&proxy), 0.25);
+
+        let reloaded = ControlHub::<FrameTiming>::parse_from_str(
+            "a:\n  type: slider\n  range: [0, 1]\n",
+        )
+        .unwrap();
+        controls.apply_reloaded_config(&reloaded);
+        assert_eq!(controls.ui_controls.slider_range("a"), Some((0.0, 0.5)));
+
+        controls.reset_slider_range("a").unwrap();
+        assert_eq!(controls.ui_controls.slider_range("a"), Some((0.0, 1.0)));
+        assert!(controls.slider_ranges().is_empty());
+
+        let inverted = SliderRange {
+            min: 1.0,
+            max: 0.0,
+            step: 0.1,
+        };
+        assert!(controls.set_slider_range("a", inverted).is_err());
+        let infinite = SliderRange {
+            min: 0.0,
+            max: f32::INFINITY,
+            step: 0.01,
+        };
+        assert!(controls.set_slider_range("a", infinite).is_err());
+        let nan = SliderRange {
+            min: f32::NAN,
+            max: 1.0,
+            step: 0.01,
+        };
+        assert!(controls.set_slider_range("a", nan).is_err());
+        assert!(controls.set_slider_range("nope", range).is_err());
+    }
+
+    const TYPED_CONTROLS: &str = r#"
+slider:
+  type: slider
+  default: 0.5
+  var: s1
+
+checkbox:
+  type: checkbox
+  default: true
+
+select:
+  type: select
+  default: b
+  options: [a, b]
+"#;
+
+    #[test]
+    #[serial]
+    fn test_try_getters() {
+        init(0);
+        let controls = create_instance(TYPED_CONTROLS);
+
+        assert_eq!(controls.try_get("slider"), Ok(0.5));
+        assert_eq!(controls.try_get("s1"), Ok(0.5));
+        assert_eq!(
+            controls.try_get("slidr"),
+            Err(ControlError::Unknown("slidr".to_string()))
+        );
+        assert_eq!(controls.try_bool("checkbox"), Ok(true));
+        assert_eq!(controls.try_string("select"), Ok("b".to_string()));
+        assert_eq!(
+            controls.try_bool("slider"),
+            Err(ControlError::WrongType {
+                name: "slider".to_string(),
+                expected: "checkbox"
+            })
+        );
+        assert!(controls.missing().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_missing() {
+        init(0);
+        let mut controls = create_instance(TYPED_CONTROLS);
+
+        assert_eq!(controls.get("slidr"), 0.0);
+        assert!(!controls.bool("chekbox"));
+        assert_eq!(controls.string("slider"), "");
+        assert_eq!(controls.missing(), vec!["chekbox", "slidr"]);
+
+        let config =
+            ControlHub::<FrameTiming>::parse_from_str(TYPED_CONTROLS).unwrap();
+        controls.apply_reloaded_config(&config);
+        assert!(controls.missing().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_constants() {
+        init(0);
+
+        let dir = std::env::temp_dir().join("xtal_test_constants");
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("sketch.yaml");
+        fs::write(&script, "a:\n  type: slider\n").unwrap();
+        fs::write(
+            constants_path(&script),
+            "radius: 0.5\noffsets: [1, 2]\ncolors: ['#f00']\nlabel: hi\n",
+        )
+        .unwrap();
+
+        let controls =
+            ControlHub::from_path(script, FrameTiming::new(Bpm::new(BPM)));
+
+        assert_eq!(controls.constant("radius"), 0.5);
+        assert_eq!(controls.constant_vec("offsets"), vec![1.0, 2.0]);
+        assert_eq!(
+            controls.constant_colors("colors"),
+            vec![[1.0, 0.0, 0.0, 1.0]]
+        );
+        assert_eq!(
+            controls.try_constant("label").unwrap().as_string(),
+            Some("hi")
+        );
+        assert!(!controls.has("radius"));
+
+        assert_eq!(controls.constant("label"), 0.0);
+        assert_eq!(controls.constant("nope"), 0.0);
+        assert_eq!(controls.missing(), vec!["nope"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_profiles() {
+        init(0);
+
+        let dir = std::env::temp_dir().join("xtal_test_profiles");
+        fs::create_dir_all(&dir).unwrap();
+        let studio = dir.join("sketch.yaml");
+        let live = dir.join("sketch_live.yaml");
+        fs::write(&studio, "a:\n  type: slider\nb:\n  type: slider\n").unwrap();
+        fs::write(&live, "a:\n  type: slider\n  default: 0.9\n").unwrap();
+
+        let mut controls =
+            ControlHub::from_path(studio, FrameTiming::new(Bpm::new(BPM)))
+                .with_profile("live", live);
+        assert_eq!(controls.profiles(), vec![DEFAULT_PROFILE, "live"]);
+        assert_eq!(controls.profile(), DEFAULT_PROFILE);
+
+        controls.ui_controls.set("a", ControlValue::Float(0.5));
+        controls.set_profile("live").unwrap();
+        assert_eq!(controls.profile(), "live");
+        assert_eq!(controls.get("a"), 0.5, "shared values carry over");
+        assert!(!controls.has("b"));
+
+        assert!(controls.set_profile("nope").is_err());
+        assert_eq!(controls.profile(), "live");
+
+        controls.set_profile(DEFAULT_PROFILE).unwrap();
+        assert!(controls.has("b"));
+        assert_eq!(controls.get("a"), 0.5);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    #[should_panic(expected = "No control named `slidr`")]
+    fn test_strict() {
+        init(0);
+        let mut controls = create_instance(TYPED_CONTROLS);
+        controls.set_strict(true);
+        assert_eq!(controls.get("slider"), 0.5);
+        controls.get("slidr");
+    }
+}
+
+/// Generates random control scripts, both plausible and malformed, to make sure
+/// a bad hot reload surfaces as an error instead of a panic
+#[cfg(test)]
+mod prop_tests {
+    use proptest::prelude::*;
+    use proptest::sample::{Index, select};
+    use proptest::test_runner::{Config, TestRunner};
+    use serial_test::serial;
+
+    use super::*;
+    use crate::framework::motion::animation_tests::{BPM, init};
+
+    const NAMES: [&str; 4] = ["a", "b", "c", "d"];
+
+    const TYPES: [&str; 15] = [
+        "slider",
+        "checkbox",
+        "select",
+        "separator",
+        "midi",
+        "osc",
+        "audio",
+        "audio_pitch",
+        "automate",
+        "ramp",
+        "random",
+        "random_slewed",
+        "triangle",
+        "mod",
+        "effect",
+    ];
+
+    const FIELDS: [&str; 32] = [
+        "default",
+        "range",
+        "step",
+        "options",
+        "disabled",
+        "var",
+        "bypass",
+        "channel",
+        "cc",
+        "beats",
+        "phase",
+        "delay",
+        "stem",
+        "slew",
+        "mode",
+        "source",
+        "modulators",
+        "kind",
+        "operator",
+        "operand",
+        "iterations",
+        "drive",
+        "rise",
+        "fall",
+        "lower_threshold",
+        "upper_threshold",
+        "output_high",
+        "pass_through",
+        "domain",
+        "amplitude",
+        "frequency",
+        "width",
+    ];
+
+    const KINDS: [&str; 7] = [
+        "step",
+        "ramp",
+        "wave",
+        "random",
+        "random_smooth",
+        "end",
+        "nope",
+    ];
+
+    fn value() -> impl Strategy<Value = String> {
+        prop_oneof![
+            (-4.0f32..4.0).prop_map(|x| format!("{:.2}", x)),
+            (0u32..8).prop_map(|x| x.to_string()),
+            select(vec![
+                ".nan",
+                ".inf",
+                "-.inf",
+                "-1",
+                "~",
+                "true",
+                "\"\"",
+                "$a",
+                "$b",
+                "$c",
+                "$missing",
+                "loop",
+                "once",
+                "fold",
+                "add",
+                "curve",
+                "nope",
+                "ease_in",
+                "custom",
+                "square",
+                "not a",
+                "a is x and not b or c is not y",
+                "[0.0, 1.0]",
+                "[1.0, 0.0]",
+                "[]",
+                "[a, b]",
+                "[$a, 1]",
+                "{}",
+                "{ 1: 2 }",
+                "wave_folder",
+                "math",
+            ])
+            .prop_map(str::to_string),
+        ]
+    }
+
+    fn field() -> impl Strategy<Value = (&'static str, String)> {
+        (select(FIELDS.to_vec()), value())
+    }
+
+    fn breakpoint() -> impl Strategy<Value = String> {
+        (
+            prop_oneof![Just("0".to_string()), value()],
+            value(),
+            select(KINDS.to_vec()),
+            prop::option::of(field()),
+        )
+            .prop_map(|(position, value, kind, extra)| {
+                let mut yaml = format!(
+                    "    - position: {}\n      value: {}\n      kind: {}\n",
+                    position, value, kind
+                );
+                if let Some((key, value)) = extra {
+                    yaml.push_str(&format!("      {}: {}\n", key, value));
+                }
+                yaml
+            })
+    }
+
+    fn control() -> impl Strategy<Value = String> {
+        (
+            select(NAMES.to_vec()),
+            select(TYPES.to_vec()),
+            prop::collection::vec(field(), 0..6),
+            prop::collection::vec(breakpoint(), 0..4),
+        )
+            .prop_map(|(name, kind, fields, breakpoints)| {
+                let mut yaml = format!("{}:\n  type: {}\n", name, kind);
+                for (key, value) in fields {
+                    yaml.push_str(&format!("  {}: {}\n", key, value));
+                }
+                if !breakpoints.is_empty() {
+                    yaml.push_str("  breakpoints:\n");
+                    yaml.push_str(&breakpoints.concat());
+                }
+                yaml
+            })
+    }
+
+    fn script() -> impl Strategy<Value = String> {
+        prop::collection::vec(control(), 0..6).prop_map(|c| c.join("\n"))
+    }
+
+    fn malformed() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[a-z$:\\-\\[\\]{}, \n0-9.&*<]{0,160}",
+            (script(), any::<Index>()).prop_map(|(yaml, index)| {
+                yaml[..index.index(yaml.len() + 1)].to_string()
+            }),
+        ]
+    }
+
+    /// Loads every generated script into the same hub, the way hot reloads
+    /// do, then reads every control over a few frames
+    fn assert_never_panics(strategy: impl Strategy<Value = String>) {
+        let hub = RefCell::new(ControlHub::new(
+            None,
+            FrameTiming::new(Bpm::new(BPM)),
+        ));
+
+        let mut runner = TestRunner::new(Config {
+            cases: 256,
+            failure_persistence: None,
+            ..Config::default()
+        });
+
+        runner
+            .run(&strategy, |yaml| {
+                let mut hub = hub.borrow_mut();
+                if let Ok(config) =
+                    ControlHub::<FrameTiming>::parse_from_str(&yaml)
+                {
+                    let _ = hub.populate_controls(&config);
+                }
+                for frame in [0, 7, 64] {
+                    init(frame);
+                    for name in NAMES.iter().chain(&["missing"]) {
+                        hub.get(name);
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn populate(yaml: &str) -> Result<ControlHub<FrameTiming>, Box<dyn Error>> {
+        let mut hub = ControlHub::new(None, FrameTiming::new(Bpm::new(BPM)));
+        let config = ControlHub::<FrameTiming>::parse_from_str(yaml)?;
+        hub.populate_controls(&config)?;
+        Ok(hub)
+    }
+
+    #[test]
+    #[serial]
+    fn prop_control_scripts_never_panic() {
+        assert_never_panics(script());
+    }
+
+    #[test]
+    #[serial]
+    fn prop_malformed_scripts_never_panic() {
+        assert_never_panics(malformed());
+    }
+
+    #[test]
+    #[serial]
+    fn test_param_cycles_resolve_to_zero() {
+        init(0);
+        let hub = populate(
+            r#"
+a:
+  type: triangle
+  beats: 1
+  phase: $b
+b:
+  type: triangle
+  beats: 1
+  phase: $a
+c:
+  type: triangle
+  beats: 1
+  phase: $c
+"#,
+        )
+        .unwrap();
+
+        assert!(hub.dep_graph.is_cyclic("a"));
+        assert_eq!(hub.get("a"), hub.get("b"));
+        hub.get("c");
+    }
+
+    #[test]
+    #[serial]
+    fn test_failed_populate_leaves_no_param_cycles() {
+        init(0);
+        let mut hub = ControlHub::new(None, FrameTiming::new(Bpm::new(BPM)));
+        let config = ControlHub::<FrameTiming>::parse_from_str(
+            r#"
+a:
+  type: triangle
+  beats: 1
+  phase: $a
+b:
+  type: select
+"#,
+        )
+        .unwrap();
+
+        assert!(hub.populate_controls(&config).is_err());
+        assert!(hub.dep_graph.is_cyclic("a"));
+        hub.get("a");
+    }
+
+    #[test]
+    #[serial]
+    fn test_invalid_scripts_are_errors() {
+        init(0);
+
+        let bad_operator = r#"
+a:
+  type: effect
+  kind: math
+  operator: nope
+"#;
+        assert!(populate(bad_operator).is_err());
+
+        let bad_mode = r#"
+a:
+  type: automate
+  mode: nope
+  breakpoints:
+    - { position: 0, value: 0, kind: step }
+"#;
+        assert!(populate(bad_mode).is_err());
+
+        for breakpoints in ["[]", "[{ position: 1, value: 0, kind: step }]"] {
+            let yaml = format!(
+                "a:\n  type: automate\n  breakpoints: {}\n",
+                breakpoints
+            );
+            assert!(populate(&yaml).is_err(), "{}", breakpoints);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_tolerates_odd_but_valid_scripts() {
+        init(0);
+        let hub = populate(
+            r#"
+a:
+  type: automate
+  breakpoints:
+    - { position: 0, value: 0, kind: end }
+    - { position: 1, value: 1, kind: random, amplitude: -1 }
+    - { position: 2, value: 0, kind: end }
+b:
+  type: random
+  range: [1.0, 0.0]
+"#,
+        )
+        .unwrap();
+
+        for frame in 0..8 {
+            init(frame);
+            hub.get("a");
+            assert!((0.0..=1.0).contains(&hub.get("b")));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_non_string_keys() {
+        init(0);
+        // Only needs to not panic; whether it parses is up to serde
+        let _ = populate(
+            r#"
+a:
+  type: automate
+  breakpoints:
+    - { position: 0, value: 0, kind: step, 1: $b }
+    - { position: 1, value: 0, kind: end }
+"#,
+        );
+    }
 }
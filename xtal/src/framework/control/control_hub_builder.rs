@@ -10,6 +10,7 @@ pub struct ControlHubBuilder<T: TimingSource> {
     midi_controls: Option<MidiControls>,
     osc_controls: Option<OscControls>,
     audio_controls: Option<AudioControls>,
+    strict: bool,
 }
 
 impl<T: TimingSource> Default for ControlHubBuilder<T> {
@@ -20,6 +21,7 @@ impl<T: TimingSource> Default for ControlHubBuilder<T> {
             midi_controls: None,
             osc_controls: None,
             audio_controls: None,
+            strict: false,
         }
     }
 }
@@ -197,8 +199,15 @@ impl<T: TimingSource> ControlHubBuilder<T> {
         self
     }
 
+    /// See [`ControlHub::set_strict`]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     pub fn build(self) -> ControlHub<T> {
         let mut c = ControlHub::new(None, self.timing.unwrap());
+        c.set_strict(self.strict);
 
         if let Some(controls) = self.ui_controls {
             c.ui_controls = controls;
@@ -255,8 +264,11 @@ mod tests {
                             slew_limiter: SlewLimiter::default(),
                             pre_emphasis: 0.0,
                             detect: 0.0,
+                            mode: DetectMode::Frame,
+                            window: 1.0,
                             range: (0.0, 1.0),
                             value: 11.0,
+                            pitch: None,
                         },
                     )
                     .build(),
@@ -285,8 +297,11 @@ mod tests {
                     slew_limiter: SlewLimiter::default(),
                     pre_emphasis: 0.0,
                     detect: 0.0,
+                    mode: DetectMode::Frame,
+                    window: 1.0,
                     range: (0.0, 1.0),
                     value: 11.0,
+                    pitch: None,
                 },
             )
             .build();
@@ -0,0 +1,53 @@
+//! A serializable snapshot of how a [`ControlHub`] resolves its controls –
+//! see [`ControlHub::debug_graph`]. Lets complex scripts be inspected (from
+//! code or the UI) without sprinkling log statements through the hub.
+//!
+//! [`ControlHub`]: super::ControlHub
+//! [`ControlHub::debug_graph`]: super::ControlHub::debug_graph
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugGraph {
+    /// The frame the snapshot was taken on. Cached values are only meaningful
+    /// for this frame
+    pub frame: u32,
+    /// Every control the hub can resolve, sorted by name
+    pub nodes: Vec<DebugNode>,
+    pub edges: Vec<DebugEdge>,
+    /// The order prerequisites are evaluated in before their consumers. Empty
+    /// if there are none or the graph has a cycle
+    pub eval_order: Vec<String>,
+    /// `var => name`
+    pub aliases: BTreeMap<String, String>,
+    /// Names bypassed at runtime, sorted
+    pub runtime_bypassed: Vec<String>,
+    /// The only modulator currently applied, if any
+    pub solo: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct DebugNode {
+    pub name: String,
+    /// The control type, e.g. `slider`, `midi`, `triangle`, `effect`
+    pub kind: String,
+    /// This frame's value if the node is a prerequisite that has already been
+    /// evaluated
+    pub cached: Option<f32>,
+    /// The MIDI proxy `get` reads instead of this control, if mapped
+    pub proxy: Option<String>,
+    pub bypass: Option<f32>,
+    /// Effects and controls applied to this control's value, in order
+    pub modulators: Vec<String>,
+}
+
+/// `from` feeds `to`, either through one of `to`'s parameters or as a
+/// modulator (`via` is then `"modulation"`)
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct DebugEdge {
+    pub from: String,
+    pub to: String,
+    pub via: String,
+}
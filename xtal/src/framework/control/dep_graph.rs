@@ -1,7 +1,5 @@
 use std::collections::VecDeque;
 
-use nannou_egui::egui::ahash::HashSet;
-
 use super::param_mod::ParamValue;
 use crate::framework::prelude::*;
 
@@ -41,6 +39,10 @@ pub struct DepGraph {
 
     /// Lookup map for faster dependency checking
     prerequisites: HashMap<String, bool>,
+
+    /// Nodes that are part of (or downstream of) a cycle. Their hot params
+    /// can't be resolved without infinite recursion
+    cyclic: HashSet<String>,
 }
 
 impl DepGraph {
@@ -48,6 +50,10 @@ impl DepGraph {
         *self.prerequisites.get(name).unwrap_or(&false)
     }
 
+    pub fn is_cyclic(&self, name: &str) -> bool {
+        self.cyclic.contains(name)
+    }
+
     pub fn order(&self) -> &EvalOrder {
         &self.eval_order
     }
@@ -56,13 +62,33 @@ impl DepGraph {
         self.node_defs.get(name)
     }
 
+    pub fn nodes(&self) -> &Graph {
+        &self.node_defs
+    }
+
     pub fn insert_node(&mut self, name: &str, node: Node) {
         self.node_defs.insert(name.to_string(), node);
     }
 
+    /// Points `$` params that use a `var` alias at the control it aliases so
+    /// nodes and edges only ever hold canonical names
+    pub fn resolve_aliases(&mut self, vars: &HashMap<String, String>) {
+        for node in self.node_defs.values_mut() {
+            for value in node.values_mut() {
+                if let ParamValue::Hot(name) = value {
+                    if let Some(canonical) = vars.get(name) {
+                        *name = canonical.clone();
+                    }
+                }
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.node_defs.clear();
         self.eval_order = None;
+        self.prerequisites.clear();
+        self.cyclic.clear();
     }
 
     /// Builds the prerequisite evaluation order using a modified Kahn's
@@ -115,9 +141,17 @@ impl DepGraph {
                 ternary!(sorted_order.is_empty(), None, Some(sorted_order));
         } else {
             self.eval_order = None;
-            warn!(
-                "cycle detected. sorted_order: {:?}, in_degree: {:?}",
-                sorted_order, in_degree
+            self.cyclic = in_degree
+                .into_iter()
+                .filter(|(_, degree)| *degree > 0)
+                .map(|(node, _)| node)
+                .collect();
+            let mut cyclic: Vec<_> = self.cyclic.iter().collect();
+            cyclic.sort();
+            error!(
+                "Parameter modulation cycle detected between {:?}. \
+                Their `$` params will resolve to 0.0",
+                cyclic
             );
         }
     }
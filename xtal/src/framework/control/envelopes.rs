@@ -0,0 +1,311 @@
+//! One-shot attack/decay/sustain/release envelopes (`adsr`, alias `env`)
+//! fired by a MIDI note, an OSC message to `/envelope/<name>`, or
+//! [`ControlHub::trigger_envelope`]. Stage lengths are in beats. Hits arriving
+//! on the MIDI and OSC threads are queued and stamped with the current beat on
+//! the next [`ControlHub::update`].
+//!
+//! Sketches do not need to interact with this module directly – see
+//! [`ControlHub`].
+
+use nannou_core::math::map_range;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use super::osc_controls::osc_float;
+use crate::framework::osc_receiver::SHARED_OSC_RECEIVER;
+use crate::framework::prelude::*;
+
+const OSC_PREFIX: &str = "envelope/";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvelopeControlConfig {
+    pub attack: f32,
+    pub decay: f32,
+    /// Level held after the decay, relative to the peak
+    pub sustain: f32,
+    /// How long the sustain level is held. Hits carry no Note Off so every
+    /// envelope runs to completion
+    pub hold: f32,
+    pub release: f32,
+    pub range: [f32; 2],
+    /// Scale the peak by the hit's velocity
+    pub velocity: bool,
+    /// Only notes on this channel fire the envelope; any channel when `None`
+    pub channel: Option<u8>,
+    /// MIDI note that fires the envelope; MIDI is ignored when `None`
+    pub note: Option<u8>,
+}
+
+impl EnvelopeControlConfig {
+    /// Normalized level `beats` after a hit that peaks at `peak`. The attack
+    /// rises from `from` so retriggering a running envelope doesn't jump
+    pub fn level(&self, beats: f32, from: f32, peak: f32) -> f32 {
+        let mut t = beats.max(0.0);
+        if t < self.attack {
+            return from + (peak - from) * t / self.attack;
+        }
+        t -= self.attack;
+        let sustain = self.sustain * peak;
+        if t < self.decay {
+            return peak + (sustain - peak) * t / self.decay;
+        }
+        t -= self.decay;
+        if t < self.hold {
+            return sustain;
+        }
+        t -= self.hold;
+        if t < self.release {
+            return sustain * (1.0 - t / self.release);
+        }
+        0.0
+    }
+
+    fn accepts(&self, channel: u8, note: u8) -> bool {
+        self.note == Some(note) && self.channel.is_none_or(|ch| ch == channel)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Hit {
+    beat: f32,
+    from: f32,
+    peak: f32,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    configs: HashMap<String, EnvelopeControlConfig>,
+    hits: HashMap<String, Hit>,
+    pending: Vec<(String, f32)>,
+}
+
+impl State {
+    fn level(&self, name: &str, beat: f32) -> Option<f32> {
+        let config = self.configs.get(name)?;
+        Some(self.hits.get(name).map_or(0.0, |hit| {
+            config.level(beat - hit.beat, hit.from, hit.peak)
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Envelopes {
+    state: Arc<Mutex<State>>,
+    is_active: bool,
+    osc_registered: bool,
+}
+
+impl Envelopes {
+    pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        // The shared receiver has no way to unregister so only do this once
+        if !self.osc_registered {
+            let envelopes = self.clone();
+            SHARED_OSC_RECEIVER.register_callback("*", move |msg| {
+                let key = msg.addr.trim_start_matches('/');
+                if let Some(name) = key.strip_prefix(OSC_PREFIX) {
+                    let velocity =
+                        msg.args.first().and_then(osc_float).unwrap_or(1.0);
+                    envelopes.queue(name, velocity);
+                }
+            });
+            self.osc_registered = true;
+        }
+
+        self.is_active = true;
+
+        let Some(port) = crate::global::midi_control_in_port() else {
+            warn!(
+                "Skipping {} listener setup; no MIDI port.",
+                midi::ConnectionType::Envelope
+            );
+            return Ok(());
+        };
+
+        let envelopes = self.clone();
+
+        midi::on_message(
+            midi::ConnectionType::Envelope,
+            &port,
+            move |_, message| {
+                if message.len() < 3 || !midi::is_note_on(message[0]) {
+                    return;
+                }
+                envelopes.receive(message[0] & 0x0F, message[1], message[2]);
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn restart(&mut self) -> Result<(), Box<dyn Error>> {
+        self.is_active = false;
+        self.start()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Queues every envelope listening for this note. Velocity 0 is a Note Off
+    /// by convention and is ignored
+    pub fn receive(&self, channel: u8, note: u8, velocity: u8) {
+        if velocity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let State {
+            configs, pending, ..
+        } = &mut *state;
+        for (name, config) in configs.iter() {
+            if config.accepts(channel, note) {
+                pending.push((name.clone(), velocity as f32 / 127.0));
+            }
+        }
+    }
+
+    /// Queues a hit to be started on the next [`Self::apply_pending`]. Safe to
+    /// call from any thread
+    pub fn queue(&self, name: &str, velocity: f32) {
+        self.state
+            .lock()
+            .unwrap()
+            .pending
+            .push((name.to_string(), velocity));
+    }
+
+    /// Starts every queued hit at `beat`
+    pub fn apply_pending(&self, beat: f32) {
+        let pending = std::mem::take(&mut self.state.lock().unwrap().pending);
+        for (name, velocity) in pending {
+            self.trigger(&name, beat, velocity);
+        }
+    }
+
+    /// Restarts `name` at `beat`. Returns false for unknown envelopes
+    pub fn trigger(&self, name: &str, beat: f32, velocity: f32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(config) = state.configs.get(name) else {
+            return false;
+        };
+        let peak = ternary!(config.velocity, velocity.clamp(0.0, 1.0), 1.0);
+        let from = state.level(name, beat).unwrap_or(0.0);
+        state
+            .hits
+            .insert(name.to_string(), Hit { beat, from, peak });
+        true
+    }
+
+    /// Adds or replaces an envelope. A running envelope keeps going so script
+    /// edits don't cut it off
+    pub fn add(&mut self, name: &str, config: EnvelopeControlConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.configs.insert(name.to_string(), config);
+    }
+
+    /// Removes every envelope not in `names`
+    pub fn retain(&mut self, names: &[&str]) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .configs
+            .retain(|name, _| names.contains(&name.as_str()));
+        state.hits.retain(|name, _| names.contains(&name.as_str()));
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.state.lock().unwrap().configs.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().configs.is_empty()
+    }
+
+    /// The value of `name` at `beat`, which is the low end of its range until
+    /// the first hit and once the release has finished
+    pub fn get_optional(&self, name: &str, beat: f32) -> Option<f32> {
+        let state = self.state.lock().unwrap();
+        let level = state.level(name, beat)?;
+        let range = state.configs[name].range;
+        Some(map_range(level, 0.0, 1.0, range[0], range[1]))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.state.lock().unwrap().configs.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn config() -> EnvelopeControlConfig {
+        EnvelopeControlConfig {
+            attack: 1.0,
+            decay: 1.0,
+            sustain: 0.5,
+            hold: 1.0,
+            release: 1.0,
+            range: [0.0, 1.0],
+            velocity: true,
+            channel: Some(9),
+            note: Some(36),
+        }
+    }
+
+    #[test]
+    fn test_level() {
+        let config = config();
+        assert_approx_eq!(config.level(0.0, 0.0, 1.0), 0.0);
+        assert_approx_eq!(config.level(0.5, 0.0, 1.0), 0.5);
+        assert_approx_eq!(config.level(1.0, 0.0, 1.0), 1.0);
+        assert_approx_eq!(config.level(1.5, 0.0, 1.0), 0.75);
+        assert_approx_eq!(config.level(2.5, 0.0, 1.0), 0.5);
+        assert_approx_eq!(config.level(3.5, 0.0, 1.0), 0.25);
+        assert_approx_eq!(config.level(4.0, 0.0, 1.0), 0.0);
+        assert_approx_eq!(config.level(0.5, 0.5, 1.0), 0.75);
+        assert_approx_eq!(config.level(2.5, 0.0, 0.5), 0.25);
+
+        let percussive = EnvelopeControlConfig {
+            attack: 0.0,
+            decay: 0.0,
+            hold: 0.0,
+            ..config
+        };
+        assert_approx_eq!(percussive.level(0.0, 0.0, 1.0), 0.5);
+        assert_approx_eq!(percussive.level(1.0, 0.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_triggers() {
+        let mut envelopes = Envelopes::default();
+        envelopes.add(
+            "flash",
+            EnvelopeControlConfig {
+                range: [10.0, 20.0],
+                ..config()
+            },
+        );
+
+        assert_eq!(envelopes.get_optional("flash", 0.0), Some(10.0));
+        assert_eq!(envelopes.get_optional("missing", 0.0), None);
+        assert!(!envelopes.trigger("missing", 0.0, 1.0));
+
+        // Wrong channel, wrong note, and Note Off are all ignored
+        envelopes.receive(0, 36, 127);
+        envelopes.receive(9, 37, 127);
+        envelopes.receive(9, 36, 0);
+        envelopes.apply_pending(4.0);
+        assert_eq!(envelopes.get_optional("flash", 5.0), Some(10.0));
+
+        envelopes.receive(9, 36, 127);
+        envelopes.apply_pending(4.0);
+        assert_approx_eq!(envelopes.get_optional("flash", 5.0).unwrap(), 20.0);
+
+        envelopes.queue("flash", 0.5);
+        envelopes.apply_pending(10.0);
+        assert_approx_eq!(envelopes.get_optional("flash", 11.0).unwrap(), 15.0);
+
+        envelopes.retain(&[]);
+        assert!(!envelopes.has("flash"));
+    }
+}
@@ -16,49 +16,84 @@
 //! whether `a` or `b` is requested first, there will 100% be a second request
 //! for `a` from the UI, hence this cache.
 //!
+//! Values are keyed by [`EvalPosition`] rather than frame alone: timing sources
+//! that move between frames (e.g. an interpolated MIDI clock) are re-evaluated
+//! at their current beat instead of being held to whatever the first request
+//! of the frame saw.
+//!
 //! [pmod]: crate::framework::control::param_mod
 use std::cell::RefCell;
 
 use crate::framework::prelude::*;
 
 type NodeName = String;
-type Frame = u32;
 type CachedValue = f32;
 
+/// The point in time a control is evaluated at. The frame is part of the key
+/// so that values which don't depend on time (sliders, MIDI, etc.) are still
+/// refreshed every frame when the beat stands still
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvalPosition {
+    pub frame: u32,
+    pub beats: f32,
+}
+
 /// See [`crate:framework::control::eval_cache`]
 #[derive(Debug, Default)]
 pub struct EvalCache {
-    cache: RefCell<HashMap<NodeName, (Frame, CachedValue)>>,
+    cache: RefCell<HashMap<NodeName, (EvalPosition, CachedValue)>>,
 }
 
 impl EvalCache {
-    pub fn has(&self, name: &str, frame: Frame) -> bool {
-        if let Some(&(cached_frame, _)) = self.cache.borrow().get(name) {
-            return cached_frame == frame;
-        }
-        false
+    pub fn has(&self, name: &str, position: EvalPosition) -> bool {
+        self.get(name, position).is_some()
     }
 
-    pub fn store(&self, name: &str, frame: Frame, value: CachedValue) {
+    pub fn store(
+        &self,
+        name: &str,
+        position: EvalPosition,
+        value: CachedValue,
+    ) {
         self.cache
             .borrow_mut()
-            .insert(name.to_string(), (frame, value));
+            .insert(name.to_string(), (position, value));
     }
 
-    pub fn get(&self, name: &str, frame: Frame) -> Option<CachedValue> {
+    pub fn get(
+        &self,
+        name: &str,
+        position: EvalPosition,
+    ) -> Option<CachedValue> {
         self.cache
             .borrow()
             .get(name)
-            .and_then(|&(cached_frame, value)| {
-                if cached_frame == frame {
-                    Some(value)
-                } else {
-                    None
-                }
+            .and_then(|&(cached_position, value)| {
+                (cached_position == position).then_some(value)
+            })
+    }
+
+    /// The most recent value stored for `name` within `frame`, whatever beat
+    /// it was evaluated at
+    pub fn get_in_frame(&self, name: &str, frame: u32) -> Option<CachedValue> {
+        self.cache
+            .borrow()
+            .get(name)
+            .and_then(|&(position, value)| {
+                (position.frame == frame).then_some(value)
             })
     }
 
     pub fn clear(&self) {
         self.cache.borrow_mut().clear();
     }
+
+    /// Runs `f` and then restores what was cached before it so out-of-band
+    /// evaluations don't leave their values behind
+    pub fn preserving<R>(&self, f: impl FnOnce() -> R) -> R {
+        let cache = self.cache.borrow().clone();
+        let result = f();
+        *self.cache.borrow_mut() = cache;
+        result
+    }
 }
@@ -0,0 +1,131 @@
+//! Latency compensation for incoming control data. When audio reaches the
+//! audience through a mixer or PA with its own delay, reacting to MIDI, OSC, or
+//! audio the moment it arrives puts visuals ahead of the sound. Each source can
+//! be held back by an offset in milliseconds or beats so motion lines up with
+//! what the audience hears. Offsets are configured globally in **UI >
+//! Settings** since they depend on the rig rather than the sketch.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Latency {
+    Ms(f32),
+    Beats(f32),
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self::Ms(0.0)
+    }
+}
+
+impl Latency {
+    pub fn duration(&self, bpm: f32) -> Duration {
+        let seconds = match self {
+            Self::Ms(ms) => ms / 1_000.0,
+            Self::Beats(beats) => beats * 60.0 / bpm.max(1.0),
+        };
+        Duration::from_secs_f32(seconds.max(0.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LatencyOffsets {
+    pub midi: Latency,
+    pub osc: Latency,
+    pub audio: Latency,
+}
+
+/// Holds back values for [`Self::delay`] after they are received
+#[derive(Debug, Default)]
+pub(crate) struct DelayLine {
+    delay: Duration,
+    pending: VecDeque<(Instant, String, f32)>,
+}
+
+impl DelayLine {
+    /// Changing the delay releases anything still pending so that values
+    /// never arrive out of order
+    pub fn set_delay(&mut self, delay: Duration, values: &mut Values) {
+        if delay != self.delay {
+            self.delay = delay;
+            values.extend(self.pending.drain(..).map(|(_, k, v)| (k, v)));
+        }
+    }
+
+    /// Stores `value` in `values` straight away when there is no delay,
+    /// otherwise queues it until [`Self::flush`] is called after it is due
+    pub fn push(
+        &mut self,
+        values: &mut Values,
+        name: &str,
+        value: f32,
+        now: Instant,
+    ) {
+        if self.delay.is_zero() {
+            values.insert(name.to_string(), value);
+        } else {
+            self.pending
+                .push_back((now + self.delay, name.to_string(), value));
+        }
+    }
+
+    /// Moves every value due by `now` into `values`
+    pub fn flush(&mut self, values: &mut Values, now: Instant) {
+        while let Some((due, _, _)) = self.pending.front() {
+            if *due > now {
+                break;
+            }
+            let (_, name, value) = self.pending.pop_front().unwrap();
+            values.insert(name, value);
+        }
+    }
+}
+
+type Values = HashMap<String, f32>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_duration() {
+        assert_eq!(
+            Latency::Ms(250.0).duration(120.0),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            Latency::Beats(0.5).duration(120.0),
+            Duration::from_millis(250)
+        );
+        assert_eq!(Latency::Ms(-10.0).duration(120.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_delay_line() {
+        let mut values = Values::default();
+        let mut delay_line = DelayLine::default();
+        let now = Instant::now();
+
+        delay_line.push(&mut values, "a", 1.0, now);
+        assert_eq!(values["a"], 1.0);
+
+        delay_line.set_delay(Duration::from_millis(100), &mut values);
+        delay_line.push(&mut values, "a", 2.0, now);
+        delay_line.push(&mut values, "a", 3.0, now + Duration::from_millis(50));
+
+        delay_line.flush(&mut values, now + Duration::from_millis(99));
+        assert_eq!(values["a"], 1.0);
+
+        delay_line.flush(&mut values, now + Duration::from_millis(100));
+        assert_eq!(values["a"], 2.0);
+
+        delay_line.set_delay(Duration::ZERO, &mut values);
+        assert_eq!(values["a"], 3.0);
+    }
+}
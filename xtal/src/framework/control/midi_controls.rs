@@ -3,11 +3,14 @@
 //! Sketches do not need to interact with this module directly – see
 //! [`ControlHub`].
 
-use nannou::math::map_range;
+use nannou_core::math::map_range;
 use std::error::Error;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use super::control_traits::{ControlCollection, ControlConfig};
+use super::latency::DelayLine;
+use super::smoothing::Smoother;
 use crate::framework::midi::is_control_change;
 use crate::framework::prelude::*;
 
@@ -45,15 +48,21 @@ type Msb = u8;
 struct State {
     values: HashMap<String, f32>,
     last: HashMap<ChannelAndController, Msb>,
+    delay_line: DelayLine,
+    smoother: Smoother,
 }
 
 impl State {
     fn get(&self, name: &str) -> f32 {
-        *self.values.get(name).unwrap_or(&0.0)
+        self.get_optional(name).unwrap_or(0.0)
     }
 
-    fn get_optional(&self, address: &str) -> Option<&f32> {
-        self.values.get(address)
+    /// The smoothed value when `address` has a smoothing time, else the last
+    /// received one
+    fn get_optional(&self, address: &str) -> Option<f32> {
+        self.smoother
+            .get(address)
+            .or_else(|| self.values.get(address).copied())
     }
 
     fn has(&self, name: &str) -> bool {
@@ -62,10 +71,23 @@ impl State {
 
     fn remove(&mut self, name: &str) {
         self.values.remove(name);
+        self.smoother.remove(name);
     }
 
     fn set(&mut self, name: &str, value: f32) {
         self.values.insert(name.to_string(), value);
+        self.smoother.jump(name);
+    }
+
+    /// Like [`Self::set`] but subject to latency compensation
+    fn receive(&mut self, name: &str, value: f32) {
+        self.delay_line
+            .push(&mut self.values, name, value, Instant::now());
+    }
+
+    fn update_latency(&mut self, delay: Duration) {
+        self.delay_line.set_delay(delay, &mut self.values);
+        self.delay_line.flush(&mut self.values, Instant::now());
     }
 
     fn values(&self) -> HashMap<String, f32> {
@@ -137,7 +159,7 @@ impl MidiControls {
                         let mapped_value =
                             value * (config.max - config.min) + config.min;
 
-                        state.lock().unwrap().set(name, mapped_value);
+                        state.lock().unwrap().receive(name, mapped_value);
 
                         trace!("Storing regular 7bit (!hrcc || cc > 63 block)");
                     }
@@ -178,7 +200,7 @@ impl MidiControls {
                         let mapped_value =
                             value * (config.max - config.min) + config.min;
 
-                        state.set(name, mapped_value);
+                        state.receive(name, mapped_value);
 
                         trace!("Storing regular 7bit (32-63 block)");
                     }
@@ -201,7 +223,7 @@ impl MidiControls {
                 let mapped_value =
                     normalized_value * (config.max - config.min) + config.min;
 
-                state.set(name, mapped_value);
+                state.receive(name, mapped_value);
                 state.remove_last((channel, msb_cc));
 
                 trace!(
@@ -227,6 +249,33 @@ impl MidiControls {
         }
     }
 
+    /// Holds incoming values back by `delay` and releases those now due. See
+    /// [`LatencyOffsets`]
+    pub fn update_latency(&mut self, delay: Duration) {
+        self.state.lock().unwrap().update_latency(delay);
+    }
+
+    /// Eases `name` toward received values over `time` rather than jumping.
+    /// A zero `time` disables smoothing
+    pub fn set_smoothing(&mut self, name: &str, time: Duration) {
+        self.state.lock().unwrap().smoother.set_time(name, time);
+    }
+
+    /// Advances smoothed values; call once per frame
+    pub fn update_smoothing(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            values, smoother, ..
+        } = &mut *state;
+        smoother.update(values, Instant::now());
+    }
+
+    /// Whether a smoothed value has yet to reach its target
+    pub fn is_smoothing(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.smoother.is_moving(&state.values)
+    }
+
     pub fn restart(&mut self) -> Result<(), Box<dyn Error>> {
         self.is_active = false;
         info!("Restarting...");
@@ -328,7 +377,7 @@ impl
 
     fn get_optional(&self, name: &str) -> Option<f32> {
         let state = self.state.lock().unwrap();
-        state.get_optional(name).copied()
+        state.get_optional(name)
     }
 
     fn has(&self, name: &str) -> bool {
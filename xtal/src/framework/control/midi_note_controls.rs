@@ -0,0 +1,229 @@
+//! Value sources driven by incoming MIDI notes: the velocity of the most recent
+//! Note On (`midi_velocity`) and its note number normalized across a range
+//! (`midi_note`). Both hold their last value until the next matching note.
+//!
+//! Sketches do not need to interact with this module directly – see
+//! [`ControlHub`].
+
+use nannou_core::math::map_range;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoteSource {
+    Velocity,
+    Note,
+}
+
+#[derive(Clone, Debug)]
+pub struct MidiNoteControlConfig {
+    pub source: NoteSource,
+    /// Only notes on this channel are considered; any channel when `None`
+    pub channel: Option<u8>,
+    /// Inclusive `[low, high]` note filter. For [`NoteSource::Note`] this is
+    /// also the span normalized to `range`
+    pub notes: [u8; 2],
+    pub range: [f32; 2],
+    /// Used until the first matching note arrives
+    pub default: f32,
+}
+
+impl MidiNoteControlConfig {
+    fn accepts(&self, channel: u8, note: u8) -> bool {
+        self.channel.is_none_or(|ch| ch == channel)
+            && note >= self.notes[0]
+            && note <= self.notes[1]
+    }
+
+    fn value(&self, note: u8, velocity: u8) -> f32 {
+        let normalized = match self.source {
+            NoteSource::Velocity => velocity as f32 / 127.0,
+            NoteSource::Note => {
+                let [low, high] = self.notes;
+                ternary!(
+                    high > low,
+                    (note - low) as f32 / (high - low) as f32,
+                    0.0
+                )
+            }
+        };
+        map_range(normalized, 0.0, 1.0, self.range[0], self.range[1])
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    configs: HashMap<String, MidiNoteControlConfig>,
+    values: HashMap<String, f32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MidiNoteControls {
+    state: Arc<Mutex<State>>,
+    is_active: bool,
+}
+
+impl MidiNoteControls {
+    pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(port) = crate::global::midi_control_in_port() else {
+            warn!(
+                "Skipping {} listener setup; no MIDI port.",
+                midi::ConnectionType::Note
+            );
+            return Ok(());
+        };
+
+        let controls = self.clone();
+
+        midi::on_message(
+            midi::ConnectionType::Note,
+            &port,
+            move |_, message| {
+                if message.len() < 3 || !midi::is_note_on(message[0]) {
+                    return;
+                }
+                controls.receive(message[0] & 0x0F, message[1], message[2]);
+            },
+        )?;
+
+        self.is_active = true;
+
+        Ok(())
+    }
+
+    pub fn restart(&mut self) -> Result<(), Box<dyn Error>> {
+        self.is_active = false;
+        self.start()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Stores the value of every control matching a Note On. Velocity 0 is a
+    /// Note Off by convention and is ignored
+    pub fn receive(&self, channel: u8, note: u8, velocity: u8) {
+        if velocity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let State { configs, values } = &mut *state;
+        for (name, config) in configs.iter() {
+            if config.accepts(channel, note) {
+                values.insert(name.clone(), config.value(note, velocity));
+            }
+        }
+    }
+
+    /// Adds or replaces a control. The last received value is kept when the
+    /// range and filters are unchanged so script edits don't reset it
+    pub fn add(&mut self, name: &str, config: MidiNoteControlConfig) {
+        let mut state = self.state.lock().unwrap();
+        let unchanged = state.configs.get(name).is_some_and(|existing| {
+            existing.source == config.source
+                && existing.channel == config.channel
+                && existing.notes == config.notes
+                && existing.range == config.range
+        });
+        if !unchanged {
+            state.values.remove(name);
+        }
+        state.configs.insert(name.to_string(), config);
+    }
+
+    /// Removes every control not in `names`
+    pub fn retain(&mut self, names: &[&str]) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .configs
+            .retain(|name, _| names.contains(&name.as_str()));
+        state
+            .values
+            .retain(|name, _| names.contains(&name.as_str()));
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.state.lock().unwrap().configs.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().configs.is_empty()
+    }
+
+    pub fn get_optional(&self, name: &str) -> Option<f32> {
+        let state = self.state.lock().unwrap();
+        let config = state.configs.get(name)?;
+        Some(state.values.get(name).copied().unwrap_or(config.default))
+    }
+
+    pub fn sources(&self) -> Vec<(String, NoteSource)> {
+        let state = self.state.lock().unwrap();
+        state
+            .configs
+            .iter()
+            .map(|(name, config)| (name.clone(), config.source))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: NoteSource) -> MidiNoteControlConfig {
+        MidiNoteControlConfig {
+            source,
+            channel: Some(9),
+            notes: [36, 48],
+            range: [0.0, 1.0],
+            default: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_receive() {
+        let mut controls = MidiNoteControls::default();
+        controls.add("accent", config(NoteSource::Velocity));
+        controls.add("position", config(NoteSource::Note));
+
+        assert_eq!(controls.get_optional("accent"), Some(0.5));
+        assert_eq!(controls.get_optional("missing"), None);
+
+        controls.receive(9, 42, 127);
+        assert_eq!(controls.get_optional("accent"), Some(1.0));
+        assert_eq!(controls.get_optional("position"), Some(0.5));
+
+        // Wrong channel, out of range, and Note Off are all ignored
+        controls.receive(0, 48, 64);
+        controls.receive(9, 60, 64);
+        controls.receive(9, 48, 0);
+        assert_eq!(controls.get_optional("accent"), Some(1.0));
+        assert_eq!(controls.get_optional("position"), Some(0.5));
+
+        controls.receive(9, 48, 0x40);
+        assert!(
+            (controls.get_optional("accent").unwrap() - 0.504).abs() < 0.001
+        );
+        assert_eq!(controls.get_optional("position"), Some(1.0));
+    }
+
+    #[test]
+    fn test_add_keeps_value_unless_changed() {
+        let mut controls = MidiNoteControls::default();
+        controls.add("accent", config(NoteSource::Velocity));
+        controls.receive(9, 40, 127);
+
+        controls.add("accent", config(NoteSource::Velocity));
+        assert_eq!(controls.get_optional("accent"), Some(1.0));
+
+        let mut conf = config(NoteSource::Velocity);
+        conf.range = [0.0, 2.0];
+        controls.add("accent", conf);
+        assert_eq!(controls.get_optional("accent"), Some(0.5));
+
+        controls.retain(&[]);
+        assert!(!controls.has("accent"));
+    }
+}
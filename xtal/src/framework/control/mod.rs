@@ -3,24 +3,54 @@
 
 pub mod control_hub;
 
+pub mod arbitration;
 pub mod audio_controls;
 mod config;
+pub mod constants;
+pub mod control_error;
+pub mod control_history;
 pub mod control_hub_builder;
 pub mod control_hub_provider;
 pub mod control_traits;
+pub mod debug_graph;
 mod dep_graph;
 mod eval_cache;
+pub mod envelopes;
+pub mod latency;
 pub mod midi_controls;
+pub mod midi_note_controls;
 pub mod osc_controls;
 mod param_mod;
+mod smoothing;
+pub mod script_error;
+pub mod triggers;
 pub mod ui_controls;
+pub mod value_recorder;
 
+pub use arbitration::*;
 pub use audio_controls::*;
+#[cfg(feature = "runtime")]
+pub use constants::*;
+pub use control_error::*;
+#[cfg(feature = "runtime")]
+pub use control_history::*;
 pub use control_hub::*;
 #[allow(unused_imports)]
 pub use control_hub_builder::*;
+#[cfg(feature = "runtime")]
 pub use control_hub_provider::*;
 pub use control_traits::*;
+#[cfg(feature = "runtime")]
+pub use debug_graph::*;
+pub use envelopes::*;
+#[cfg(feature = "runtime")]
+pub use latency::Latency;
+pub use latency::LatencyOffsets;
 pub use midi_controls::*;
+pub use midi_note_controls::*;
 pub use osc_controls::*;
+#[cfg(feature = "runtime")]
+pub use script_error::*;
+pub use triggers::*;
 pub use ui_controls::*;
+pub use value_recorder::*;
@@ -2,10 +2,18 @@
 //!
 //! Sketches do not need to interact with this module directly – see
 //! [`ControlHub`].
+//!
+//! Besides plain float messages this understands pose and hand tracking
+//! landmarks sent by an external tracker (see `scripts/landmarks.py`) as
+//! `/landmark/<group>/<point> x y z visibility`, where every value is
+//! normalized to `[0, 1]` with y and z increasing up and toward the camera.
 
 use nannou_osc as osc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use super::latency::DelayLine;
+use super::smoothing::Smoother;
 use crate::framework::osc_receiver::SHARED_OSC_RECEIVER;
 use crate::framework::prelude::*;
 
@@ -18,6 +26,10 @@ pub struct OscControlConfig {
     /// Represents the initial value of this control and will not be updated
     /// after instantiation
     pub value: f32,
+    /// Read from a tracking landmark rather than `address`
+    pub landmark: Option<LandmarkSource>,
+    /// Advertised units for OSCQuery clients. See [`ValueDisplay`]
+    pub display: Option<ValueDisplay>,
 }
 
 impl OscControlConfig {
@@ -29,24 +41,99 @@ impl OscControlConfig {
             min,
             max,
             value,
+            landmark: None,
+            display: None,
         }
     }
+
+    /// A control fed by the landmark its `name` describes, e.g.
+    /// `body.left_wrist.x`. See [`LandmarkSource::parse`]
+    pub fn landmark(
+        name: &str,
+        range: (f32, f32),
+        value: f32,
+        min_visibility: f32,
+    ) -> Result<Self, String> {
+        let mut config = Self::new(name, range, value);
+        config.landmark = Some(LandmarkSource::parse(name, min_visibility)?);
+        Ok(config)
+    }
 }
 
 impl ControlConfig<f32, f32> for OscControlConfig {}
 
+/// The landmark coordinate a control follows
+#[derive(Clone, Debug, PartialEq)]
+pub struct LandmarkSource {
+    /// The message address without leading slash, e.g.
+    /// `landmark/body/left_wrist`
+    pub address: String,
+    /// Index of the message argument to read: x, y, z, then visibility
+    pub axis: usize,
+    /// Values arriving with a lower visibility are ignored so the control
+    /// holds its last value while the point is occluded or off screen
+    pub min_visibility: f32,
+}
+
+impl LandmarkSource {
+    const AXES: [&str; 4] = ["x", "y", "z", "visibility"];
+
+    /// Parses names of the form `<group>.<point>.<axis>`, e.g.
+    /// `body.left_wrist.x` or `left_hand.index_tip.visibility`
+    pub fn parse(name: &str, min_visibility: f32) -> Result<Self, String> {
+        let parts: Vec<&str> = name.split('.').collect();
+        let [group, point, axis] = parts[..] else {
+            return Err(format!(
+                "Landmark `{}` must be named <group>.<point>.<axis>",
+                name
+            ));
+        };
+
+        let axis =
+            Self::AXES.iter().position(|a| *a == axis).ok_or_else(|| {
+                format!(
+                    "Landmark `{}` axis must be one of {}",
+                    name,
+                    Self::AXES.join(", ")
+                )
+            })?;
+
+        Ok(Self {
+            address: format!("landmark/{}/{}", group, point),
+            axis,
+            min_visibility,
+        })
+    }
+
+    /// The normalized value this source reads from a landmark message's
+    /// arguments, if it's visible enough
+    fn read(&self, args: &[f32]) -> Option<f32> {
+        let visibility = args.get(3).copied().unwrap_or(1.0);
+        if visibility < self.min_visibility {
+            return None;
+        }
+        args.get(self.axis).copied()
+    }
+}
+
 #[derive(Debug, Default)]
 struct State {
     values: HashMap<String, f32>,
+    delay_line: DelayLine,
+    smoother: Smoother,
 }
 
 impl State {
     fn get(&self, address: &str) -> f32 {
-        *self.values.get(address).unwrap_or(&0.0)
+        self.get_optional(address).unwrap_or(0.0)
     }
 
-    fn get_optional(&self, address: &str) -> Option<&f32> {
-        self.values.get(address)
+    /// The smoothed value when `address` has a smoothing time, else the last
+    /// received one
+    fn get_optional(&self, address: &str) -> Option<f32> {
+        self.smoother
+            .get(address)
+            .or_else(|| self.values.get(address).copied())
     }
 
     fn has(&self, address: &str) -> bool {
@@ -55,10 +142,23 @@ impl State {
 
     fn remove(&mut self, name: &str) {
         self.values.remove(name);
+        self.smoother.remove(name);
     }
 
     fn set(&mut self, address: &str, value: f32) {
         self.values.insert(address.to_string(), value);
+        self.smoother.jump(address);
+    }
+
+    /// Like [`Self::set`] but subject to latency compensation
+    fn receive(&mut self, address: &str, value: f32) {
+        self.delay_line
+            .push(&mut self.values, address, value, Instant::now());
+    }
+
+    fn update_latency(&mut self, delay: Duration) {
+        self.delay_line.set_delay(delay, &mut self.values);
+        self.delay_line.flush(&mut self.values, Instant::now());
     }
 
     fn values(&self) -> HashMap<String, f32> {
@@ -83,19 +183,33 @@ impl OscControls {
         SHARED_OSC_RECEIVER.register_callback("*", move |msg| {
             let key = msg.addr.trim_start_matches('/');
 
+            if key.starts_with("landmark/") {
+                let args: Vec<f32> =
+                    msg.args.iter().filter_map(osc_float).collect();
+                let mut state = state.lock().unwrap();
+                for (name, config) in &configs {
+                    if let Some(landmark) = &config.landmark {
+                        if landmark.address != key {
+                            continue;
+                        }
+                        if let Some(value) = landmark.read(&args) {
+                            let mapped_value =
+                                value * (config.max - config.min) + config.min;
+                            state.receive(name, mapped_value);
+                        }
+                    }
+                }
+                return;
+            }
+
             if let Some(config) = configs.get(key) {
-                let value: Option<f32> = match msg.args.first() {
-                    Some(osc::Type::Float(value)) => Some(*value),
-                    Some(osc::Type::Int(value)) => Some(*value as f32),
-                    Some(osc::Type::Double(value)) => Some(*value as f32),
-                    _ => None,
-                };
+                let value = msg.args.first().and_then(osc_float);
 
                 if let Some(value) = value {
                     trace!("Setting {} to {}", key, value);
                     let mapped_value =
                         value * (config.max - config.min) + config.min;
-                    state.lock().unwrap().set(key, mapped_value);
+                    state.lock().unwrap().receive(key, mapped_value);
                 }
             }
         });
@@ -104,6 +218,33 @@ impl OscControls {
 
         Ok(())
     }
+
+    /// Holds incoming values back by `delay` and releases those now due. See
+    /// [`LatencyOffsets`]
+    pub fn update_latency(&mut self, delay: Duration) {
+        self.state.lock().unwrap().update_latency(delay);
+    }
+
+    /// Eases `name` toward received values over `time` rather than jumping.
+    /// A zero `time` disables smoothing
+    pub fn set_smoothing(&mut self, name: &str, time: Duration) {
+        self.state.lock().unwrap().smoother.set_time(name, time);
+    }
+
+    /// Advances smoothed values; call once per frame
+    pub fn update_smoothing(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        let State {
+            values, smoother, ..
+        } = &mut *state;
+        smoother.update(values, Instant::now());
+    }
+
+    /// Whether a smoothed value has yet to reach its target
+    pub fn is_smoothing(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.smoother.is_moving(&state.values)
+    }
 }
 
 impl
@@ -136,7 +277,7 @@ impl
     fn get_optional(&self, address: &str) -> Option<f32> {
         check_address(address);
         let state = self.state.lock().unwrap();
-        state.get_optional(address).copied()
+        state.get_optional(address)
     }
 
     fn has(&self, address: &str) -> bool {
@@ -205,8 +346,49 @@ impl OscControlBuilder {
     }
 }
 
+pub(super) fn osc_float(arg: &osc::Type) -> Option<f32> {
+    match arg {
+        osc::Type::Float(value) => Some(*value),
+        osc::Type::Int(value) => Some(*value as f32),
+        osc::Type::Double(value) => Some(*value as f32),
+        _ => None,
+    }
+}
+
 fn check_address(address: &str) {
     if address.starts_with('/') {
         panic!("Unsupported address format. Remove leading `/`.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_landmark_source_parse() {
+        let source = LandmarkSource::parse("left_hand.index_tip.z", 0.5);
+        assert_eq!(
+            source,
+            Ok(LandmarkSource {
+                address: "landmark/left_hand/index_tip".to_string(),
+                axis: 2,
+                min_visibility: 0.5,
+            })
+        );
+
+        assert!(LandmarkSource::parse("left_wrist.x", 0.5).is_err());
+        assert!(LandmarkSource::parse("body.left_wrist.w", 0.5).is_err());
+    }
+
+    #[test]
+    fn test_landmark_source_read() {
+        let source = LandmarkSource::parse("body.nose.y", 0.5).unwrap();
+        assert_eq!(source.read(&[0.1, 0.2, 0.3, 0.9]), Some(0.2));
+        assert_eq!(source.read(&[0.1, 0.2, 0.3, 0.1]), None);
+
+        // Hand landmarks don't carry visibility
+        assert_eq!(source.read(&[0.1, 0.2, 0.3]), Some(0.2));
+        assert_eq!(source.read(&[0.1]), None);
+    }
+}
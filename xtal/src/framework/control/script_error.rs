@@ -0,0 +1,150 @@
+//! Errors from hot-reloading a control script. The hub keeps running with the
+//! last configuration that loaded while one of these is set – see
+//! [`ControlHub::script_error`].
+//!
+//! [`ControlHub::script_error`]: super::ControlHub::script_error
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+/// Lines of context shown on either side of the offending line
+const SNIPPET_CONTEXT: usize = 1;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ScriptError {
+    /// File name of the script
+    pub file: String,
+    pub message: String,
+    /// 1-based. Only known for YAML syntax errors
+    pub line: Option<usize>,
+    /// 1-based. Only known for YAML syntax errors
+    pub column: Option<usize>,
+    /// The offending line and its neighbours, prefixed with line numbers and
+    /// with a caret under the column
+    pub snippet: Option<String>,
+}
+
+impl ScriptError {
+    pub fn new(path: &Path, message: impl Into<String>) -> Self {
+        Self {
+            file: path
+                .file_name()
+                .map_or_else(String::new, |f| f.to_string_lossy().to_string()),
+            message: message.into(),
+            line: None,
+            column: None,
+            snippet: None,
+        }
+    }
+
+    /// Locates `error` within `source` when it is a YAML error that carries a
+    /// location
+    pub fn from_error(
+        path: &Path,
+        source: &str,
+        error: &(dyn Error + 'static),
+    ) -> Self {
+        let mut script_error = Self::new(path, error.to_string());
+
+        let Some(location) = error
+            .downcast_ref::<serde_yml::Error>()
+            .and_then(|e| e.location())
+        else {
+            return script_error;
+        };
+
+        script_error.line = Some(location.line());
+        script_error.column = Some(location.column());
+        script_error.snippet =
+            Self::snippet(source, location.line(), location.column());
+
+        script_error
+    }
+
+    fn snippet(source: &str, line: usize, column: usize) -> Option<String> {
+        let lines: Vec<&str> = source.lines().collect();
+        if line == 0 || line > lines.len() {
+            return None;
+        }
+
+        let first = line.saturating_sub(SNIPPET_CONTEXT).max(1);
+        let last = (line + SNIPPET_CONTEXT).min(lines.len());
+        let width = last.to_string().len();
+
+        let mut snippet = String::new();
+        for n in first..=last {
+            snippet.push_str(&format!("{:>width$} | {}\n", n, lines[n - 1]));
+            if n == line {
+                snippet.push_str(&format!(
+                    "{:>width$} | {:>column$}\n",
+                    "",
+                    "^",
+                    column = column.max(1)
+                ));
+            }
+        }
+
+        Some(snippet)
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.file, self.message)
+    }
+}
+
+impl Error for ScriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str =
+        "a:\n  type: slider\n  range: [0, 1\nb:\n  type: checkbox\n";
+
+    #[test]
+    fn test_from_yaml_error() {
+        let error =
+            serde_yml::from_str::<serde_yml::Value>(SOURCE).unwrap_err();
+        let path = Path::new("/sketches/foo.yaml");
+        let script_error = ScriptError::from_error(path, SOURCE, &error);
+
+        assert_eq!(script_error.file, "foo.yaml");
+        assert!(script_error.line.is_some());
+        assert!(script_error.column.is_some());
+
+        let snippet = script_error.snippet.unwrap();
+        assert!(snippet.contains('^'));
+        assert!(snippet.contains(&format!(
+            "{} | {}",
+            script_error.line.unwrap(),
+            SOURCE.lines().nth(script_error.line.unwrap() - 1).unwrap()
+        )));
+    }
+
+    #[test]
+    fn test_other_errors_have_no_location() {
+        let error: Box<dyn Error> = "Unknown mode: foo".into();
+        let path = Path::new("foo.yaml");
+        let script_error =
+            ScriptError::from_error(path, SOURCE, error.as_ref());
+
+        assert_eq!(script_error.to_string(), "foo.yaml: Unknown mode: foo");
+        assert_eq!(script_error.line, None);
+        assert_eq!(script_error.snippet, None);
+    }
+
+    #[test]
+    fn test_snippet() {
+        let snippet = ScriptError::snippet(SOURCE, 3, 3).unwrap();
+        assert_eq!(
+            snippet,
+            "2 |   type: slider\n3 |   range: [0, 1\n  |   ^\n4 | b:\n"
+        );
+        assert_eq!(ScriptError::snippet(SOURCE, 0, 1), None);
+        assert_eq!(ScriptError::snippet(SOURCE, 99, 1), None);
+    }
+}
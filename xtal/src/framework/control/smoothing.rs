@@ -0,0 +1,107 @@
+//! Optional input smoothing for MIDI and OSC controls. 7bit CCs in particular
+//! move in visible steps when driving slow visual parameters, so controls with
+//! a `smooth` time ease toward each received value instead of jumping to it.
+
+use std::time::{Duration, Instant};
+
+use crate::framework::prelude::*;
+
+/// Below this distance from its target a control is considered settled
+const EPSILON: f32 = 1e-5;
+
+/// Exponentially eases each smoothed control toward its latest received value.
+/// The smoothing time is the time constant: the distance to the target shrinks
+/// by about 63% over that span
+#[derive(Debug, Default)]
+pub(crate) struct Smoother {
+    times: HashMap<String, Duration>,
+    current: HashMap<String, f32>,
+    last_update: Option<Instant>,
+}
+
+impl Smoother {
+    /// A zero `time` disables smoothing for `name`
+    pub fn set_time(&mut self, name: &str, time: Duration) {
+        if time.is_zero() {
+            self.times.remove(name);
+            self.current.remove(name);
+        } else {
+            self.times.insert(name.to_string(), time);
+        }
+    }
+
+    /// Skips straight to the target on the next update, e.g. when a value is
+    /// set directly rather than received
+    pub fn jump(&mut self, name: &str) {
+        self.current.remove(name);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.times.remove(name);
+        self.current.remove(name);
+    }
+
+    /// Moves every smoothed control toward its value in `targets`
+    pub fn update(&mut self, targets: &HashMap<String, f32>, now: Instant) {
+        let elapsed = self
+            .last_update
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_update = Some(now);
+
+        for (name, time) in &self.times {
+            let Some(target) = targets.get(name) else {
+                continue;
+            };
+            let current = self.current.entry(name.clone()).or_insert(*target);
+            let alpha =
+                1.0 - (-elapsed.as_secs_f32() / time.as_secs_f32()).exp();
+            *current += (target - *current) * alpha;
+            if (target - *current).abs() < EPSILON {
+                *current = *target;
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.current.get(name).copied()
+    }
+
+    /// Whether any control has yet to reach its target
+    pub fn is_moving(&self, targets: &HashMap<String, f32>) -> bool {
+        self.current
+            .iter()
+            .any(|(name, current)| targets.get(name) != Some(current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smoother() {
+        let mut smoother = Smoother::default();
+        let mut targets = HashMap::default();
+        targets.insert("a".to_string(), 0.0);
+        targets.insert("b".to_string(), 0.0);
+        smoother.set_time("a", Duration::from_millis(100));
+
+        let start = Instant::now();
+        smoother.update(&targets, start);
+        assert_eq!(smoother.get("a"), Some(0.0));
+        assert_eq!(smoother.get("b"), None);
+
+        targets.insert("a".to_string(), 1.0);
+        smoother.update(&targets, start + Duration::from_millis(100));
+        let a = smoother.get("a").unwrap();
+        assert!((a - 0.632).abs() < 0.001, "{}", a);
+        assert!(smoother.is_moving(&targets));
+
+        smoother.update(&targets, start + Duration::from_secs(10));
+        assert_eq!(smoother.get("a"), Some(1.0));
+        assert!(!smoother.is_moving(&targets));
+
+        smoother.set_time("a", Duration::ZERO);
+        assert_eq!(smoother.get("a"), None);
+    }
+}
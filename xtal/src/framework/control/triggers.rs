@@ -0,0 +1,208 @@
+//! Discrete trigger/gate events that can be consumed by sketches as events
+//! rather than sampled floats. Every event carries the beat at which it was
+//! received so sketches can place things exactly on a hit even when the frame
+//! rate is lower than the event rate.
+//!
+//! # Example
+//! ```rust,ignore
+//! for event in self.hub.triggers("kick") {
+//!     self.particles.spawn_at_beat(event.beat, event.velocity);
+//! }
+//! ```
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use crate::framework::prelude::*;
+
+/// A single trigger occurrence
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriggerEvent {
+    /// The beat (according to the hub's [`TimingSource`]) at which the event
+    /// was received
+    pub beat: f32,
+    /// Normalized [0, 1] strength of the event, e.g. MIDI note velocity / 127
+    pub velocity: f32,
+}
+
+impl TriggerEvent {
+    pub fn new(beat: f32, velocity: f32) -> Self {
+        Self { beat, velocity }
+    }
+}
+
+/// Thread-safe queue of named trigger events. Producers (MIDI threads, audio
+/// callbacks, sketch code) [`Self::emit`] while the sketch side drains events
+/// once per frame via [`ControlHub::triggers`]. Clones share the same queue.
+#[derive(Clone, Debug, Default)]
+pub struct TriggerBus {
+    events: Arc<Mutex<HashMap<String, Vec<TriggerEvent>>>>,
+}
+
+impl TriggerBus {
+    /// Events older than this many per name are dropped to avoid unbounded
+    /// growth when a lane is never drained
+    const MAX_PENDING: usize = 256;
+
+    pub fn emit(&self, name: &str, event: TriggerEvent) {
+        let mut events = self.events.lock().unwrap();
+        let queue = events.entry(name.to_string()).or_default();
+        if queue.len() >= Self::MAX_PENDING {
+            queue.remove(0);
+        }
+        queue.push(event);
+    }
+
+    /// Removes and returns all pending events for `name` in the order they
+    /// were received
+    pub fn drain(&self, name: &str) -> Vec<TriggerEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .get_mut(name)
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn pending(&self, name: &str) -> usize {
+        self.events
+            .lock()
+            .unwrap()
+            .get(name)
+            .map_or(0, |queue| queue.len())
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    /// Listen for MIDI Note On messages on the global MIDI control input port
+    /// and emit a trigger for every note found in `notes`, a map of
+    /// `(channel, note) => trigger name`. `beats` is called from the MIDI
+    /// thread to timestamp each event.
+    pub fn listen_midi_notes<F>(
+        &self,
+        notes: HashMap<(u8, u8), String>,
+        beats: F,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Fn() -> f32 + Send + Sync + 'static,
+    {
+        let Some(port) = crate::global::midi_control_in_port() else {
+            warn!(
+                "Skipping {} listener setup; no MIDI port.",
+                midi::ConnectionType::Trigger
+            );
+            return Ok(());
+        };
+
+        let bus = self.clone();
+
+        midi::on_message(
+            midi::ConnectionType::Trigger,
+            &port,
+            move |_, message| {
+                if message.len() < 3 || !midi::is_note_on(message[0]) {
+                    return;
+                }
+
+                // Note On with velocity 0 is a Note Off by convention
+                if message[2] == 0 {
+                    return;
+                }
+
+                let channel = message[0] & 0x0F;
+                if let Some(name) = notes.get(&(channel, message[1])) {
+                    let velocity = message[2] as f32 / 127.0;
+                    bus.emit(name, TriggerEvent::new(beats(), velocity));
+                }
+            },
+        )
+    }
+}
+
+/// Rising-edge detector used to derive discrete triggers from continuous
+/// signals like audio controls. Fires once when the input crosses
+/// `threshold` and won't fire again until the input falls below
+/// `threshold - hysteresis`.
+#[derive(Clone, Debug)]
+pub struct Gate {
+    pub threshold: f32,
+    pub hysteresis: f32,
+    open: bool,
+}
+
+impl Gate {
+    pub fn new(threshold: f32, hysteresis: f32) -> Self {
+        Self {
+            threshold,
+            hysteresis,
+            open: false,
+        }
+    }
+
+    /// Returns true only on the frame the gate opens
+    pub fn process(&mut self, value: f32) -> bool {
+        if self.open {
+            if value < self.threshold - self.hysteresis {
+                self.open = false;
+            }
+            false
+        } else if value >= self.threshold {
+            self.open = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self::new(0.5, 0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_drain_preserves_order_and_empties() {
+        let bus = TriggerBus::default();
+        bus.emit("kick", TriggerEvent::new(0.0, 1.0));
+        bus.emit("kick", TriggerEvent::new(0.5, 0.5));
+        bus.emit("snare", TriggerEvent::new(1.0, 1.0));
+
+        let kicks = bus.drain("kick");
+        assert_eq!(kicks.len(), 2);
+        assert_eq!(kicks[0].beat, 0.0);
+        assert_eq!(kicks[1].beat, 0.5);
+        assert!(bus.drain("kick").is_empty());
+        assert_eq!(bus.pending("snare"), 1);
+    }
+
+    #[test]
+    fn test_bus_clones_share_queue() {
+        let bus = TriggerBus::default();
+        let producer = bus.clone();
+        producer.emit("hat", TriggerEvent::new(2.0, 1.0));
+        assert_eq!(bus.drain("hat").len(), 1);
+    }
+
+    #[test]
+    fn test_gate_rising_edge() {
+        let mut gate = Gate::new(0.5, 0.1);
+        assert!(!gate.process(0.2));
+        assert!(gate.process(0.6));
+        assert!(!gate.process(0.8));
+        assert!(!gate.process(0.45));
+        assert!(!gate.process(0.6));
+        assert!(!gate.process(0.3));
+        assert!(gate.process(0.7));
+    }
+}
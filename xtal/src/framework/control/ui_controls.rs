@@ -43,6 +43,16 @@ impl ControlValue {
     }
 }
 
+impl fmt::Display for ControlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(v) => write!(f, "{}", v),
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::String(v) => write!(f, "{}", v),
+        }
+    }
+}
+
 impl Default for ControlValue {
     fn default() -> Self {
         Self::Float(0.0)
@@ -67,6 +77,72 @@ impl From<String> for ControlValue {
     }
 }
 
+/// A slider's bounds and step. See [`ControlHub::set_slider_range`]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SliderRange {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+/// How a control's value is shown in the UI and advertised over OSCQuery,
+/// declared with the `unit` and `display` fields. See
+/// [`ControlHub::display`]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ValueDisplay {
+    /// Appended to the shown value, e.g. `px`, `deg`, `%`, or `hz`
+    pub unit: Option<String>,
+    /// Fixed number of decimal places; as many as needed when `None`
+    pub decimals: Option<usize>,
+    pub multiplier: f32,
+}
+
+impl Default for ValueDisplay {
+    fn default() -> Self {
+        Self {
+            unit: None,
+            decimals: None,
+            multiplier: 1.0,
+        }
+    }
+}
+
+impl ValueDisplay {
+    /// The control's value in display units
+    pub fn scale(&self, value: f32) -> f32 {
+        value * self.multiplier
+    }
+
+    /// The inverse of [`Self::scale`]
+    pub fn unscale(&self, shown: f32) -> f32 {
+        ternary!(self.multiplier == 0.0, shown, shown / self.multiplier)
+    }
+
+    pub fn format(&self, value: f32) -> String {
+        let scaled = self.scale(value);
+        let number = match self.decimals {
+            Some(decimals) => format!("{:.*}", decimals, scaled),
+            None => scaled.to_string(),
+        };
+        match &self.unit {
+            Some(unit) => format!("{}{}", number, unit),
+            None => number,
+        }
+    }
+
+    /// Reads text shown by [`Self::format`] (with or without the unit) back
+    /// into the control's value
+    pub fn parse(&self, text: &str) -> Option<f32> {
+        let text = text.trim();
+        let number = self
+            .unit
+            .as_deref()
+            .and_then(|unit| text.strip_suffix(unit))
+            .unwrap_or(text);
+        number.trim().parse().ok().map(|shown| self.unscale(shown))
+    }
+}
+
 /// Used by [`UiControls`] to compute if a [`UiControlConfig`] should be
 /// disabled or not based on the value of other controls
 ///
@@ -390,6 +466,11 @@ impl UiControls {
 
     /// Returns the matching option index of a select as f32 (useful in shader
     /// context)
+    /// The current value of `name`, if there is such a control
+    pub fn value(&self, name: &str) -> Option<&ControlValue> {
+        self.values.get(name)
+    }
+
     pub fn string_as_f32(&self, name: &str) -> f32 {
         let value = self.string(name);
         if let Some(UiControlConfig::Select { options, .. }) = self.config(name)
@@ -430,6 +511,27 @@ impl UiControls {
         })
     }
 
+    /// Replaces a slider's bounds and step, clamping its value into the new
+    /// range. Returns false if there is no slider named `name`
+    pub fn set_slider_range(&mut self, name: &str, range: SliderRange) -> bool {
+        let Some(UiControlConfig::Slider { min, max, step, .. }) =
+            self.configs.get_mut(name)
+        else {
+            return false;
+        };
+
+        *min = range.min;
+        *max = range.max;
+        *step = range.step;
+
+        if let Some(ControlValue::Float(value)) = self.values.get_mut(name) {
+            *value = value.clamp(range.min, range.max);
+        }
+
+        self.change_tracker.mark_changed();
+        true
+    }
+
     pub fn config_refs(&self) -> &IndexMap<String, UiControlConfig> {
         &self.configs
     }
@@ -683,6 +785,36 @@ impl ChangeTracker {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_set_slider_range() {
+        let mut controls =
+            UiControls::new(&[UiControlConfig::slider_n("foo", 0.75)]);
+        let range = SliderRange {
+            min: 0.0,
+            max: 0.5,
+            step: 0.1,
+        };
+
+        assert!(controls.set_slider_range("foo", range));
+        assert_eq!(controls.slider_range("foo"), Some((0.0, 0.5)));
+        assert_eq!(controls.float("foo"), 0.5);
+        assert!(!controls.set_slider_range("bar", range));
+    }
+
+    #[test]
+    fn test_value_display() {
+        let display = ValueDisplay {
+            unit: Some("%".to_string()),
+            decimals: Some(1),
+            multiplier: 100.0,
+        };
+        assert_eq!(display.format(0.256), "25.6%");
+        assert_eq!(display.parse("50%"), Some(0.5));
+        assert_eq!(display.parse(" 12.5 "), Some(0.125));
+        assert_eq!(display.parse("nope"), None);
+        assert_eq!(ValueDisplay::default().format(0.5), "0.5");
+    }
+
     #[test]
     fn test_controls_changed() {
         let mut controls =
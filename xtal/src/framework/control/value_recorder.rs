@@ -0,0 +1,249 @@
+//! Opt-in recording of control values at frame rate for analyzing a
+//! performance afterwards, e.g. to turn what was actually played into
+//! `automate` lanes. Like [`ControlHistory`], recording is passive – values
+//! are captured as [`ControlHub::get`] is called – and one CSV row is written
+//! per frame holding each control's last value. Controls that weren't read in
+//! a frame repeat their previous value; cells are empty until a control's
+//! first read.
+//!
+//! [`ValueReplay`] plays such a file back, e.g. to re-render an improvised
+//! performance offline at a higher quality.
+//!
+//! Sketches do not need to interact with this module directly – see
+//! [`ControlHub::start_value_recording`] and [`ControlHub::start_replay`].
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::framework::prelude::*;
+
+#[derive(Debug)]
+pub struct ValueRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    columns: HashMap<String, usize>,
+    values: Vec<Option<f32>>,
+    /// The frame and beat of the row being filled
+    current: Option<(u32, f32)>,
+    rows: usize,
+}
+
+impl ValueRecorder {
+    /// Creates the file at `path` and writes the header:
+    /// `frame,beats,<names...>`
+    pub fn create(
+        path: &Path,
+        names: &[String],
+    ) -> Result<Self, Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "frame,beats,{}", names.join(","))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer,
+            columns: names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), i))
+                .collect(),
+            values: vec![None; names.len()],
+            current: None,
+            rows: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keeps `value` as the current value of `name` if it is being recorded
+    pub fn record(&mut self, name: &str, value: f32) {
+        if let Some(&index) = self.columns.get(name) {
+            self.values[index] = Some(value);
+        }
+    }
+
+    /// Writes the row of the previous frame once `frame` has moved on. Call
+    /// once per update
+    pub fn tick(&mut self, frame: u32, beats: f32) -> io::Result<()> {
+        if self.current.is_some_and(|(current, _)| current == frame) {
+            return Ok(());
+        }
+        self.write_row()?;
+        self.current = Some((frame, beats));
+        Ok(())
+    }
+
+    /// Writes the last row and flushes, returning the number of rows written
+    pub fn finish(mut self) -> io::Result<usize> {
+        self.write_row()?;
+        self.writer.flush()?;
+        Ok(self.rows)
+    }
+
+    fn write_row(&mut self) -> io::Result<()> {
+        let Some((frame, beats)) = self.current.take() else {
+            return Ok(());
+        };
+        let cells: Vec<String> = self
+            .values
+            .iter()
+            .map(|value| value.map_or_else(String::new, |v| v.to_string()))
+            .collect();
+        writeln!(self.writer, "{},{},{}", frame, beats, cells.join(","))?;
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+impl Drop for ValueRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.write_row().and_then(|_| self.writer.flush()) {
+            error!("Unable to finish value recording {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Plays back a file written by [`ValueRecorder`]. Rows are matched to the
+/// current beat and interpolated between so re-rendering at a higher frame
+/// rate stays smooth. Before the first row and after the last the nearest
+/// recorded value is held
+#[derive(Debug)]
+pub struct ValueReplay {
+    /// `name => (beats, value)` for every non-empty cell, in beat order
+    lanes: HashMap<String, Vec<(f32, f32)>>,
+}
+
+impl ValueReplay {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read {:?}: {}", path, e))?;
+        let lanes = parse_lanes(&text)?;
+        Ok(Self { lanes })
+    }
+
+    /// The recorded value of `name` at `beats`; `None` if it wasn't recorded
+    pub fn get(&self, name: &str, beats: f32) -> Option<f32> {
+        let lane = self.lanes.get(name)?;
+        let index = lane.partition_point(|(b, _)| *b <= beats);
+        if index == 0 {
+            return lane.first().map(|(_, value)| *value);
+        }
+        let (b0, v0) = lane[index - 1];
+        let Some(&(b1, v1)) = lane.get(index) else {
+            return Some(v0);
+        };
+        Some(v0 + (v1 - v0) * (beats - b0) / (b1 - b0))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.lanes.keys().cloned().collect()
+    }
+}
+
+fn parse_lanes(text: &str) -> Result<HashMap<String, Vec<(f32, f32)>>, String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let Some(names) = header.strip_prefix("frame,beats,") else {
+        return Err("Not a control value recording".to_string());
+    };
+    let names: Vec<&str> = names.split(',').collect();
+    let mut lanes: Vec<Vec<(f32, f32)>> = vec![vec![]; names.len()];
+    let mut previous_beats = f32::MIN;
+
+    for (index, line) in lines.enumerate() {
+        let line_number = index + 2;
+        if line.is_empty() {
+            continue;
+        }
+        let mut cells = line.split(',');
+        let beats: f32 = cells
+            .nth(1)
+            .and_then(|cell| cell.parse().ok())
+            .ok_or_else(|| format!("Invalid beat on line {}", line_number))?;
+        if beats < previous_beats {
+            return Err(format!(
+                "Beats go backwards on line {}; was the timing reset \
+                during the recording?",
+                line_number
+            ));
+        }
+        previous_beats = beats;
+
+        for (lane, cell) in lanes.iter_mut().zip(cells) {
+            if cell.is_empty() {
+                continue;
+            }
+            let value = cell.parse().map_err(|_| {
+                format!("Invalid value `{}` on line {}", cell, line_number)
+            })?;
+            // Only the last of several rows on the same beat counts
+            if lane.last().is_some_and(|(b, _)| *b == beats) {
+                lane.pop();
+            }
+            lane.push((beats, value));
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .map(String::from)
+        .zip(lanes)
+        .filter(|(_, lane)| !lane.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_rows() {
+        let path = std::env::temp_dir().join("xtal_test_value_recorder.csv");
+        let names = vec!["a".to_string(), "b".to_string()];
+        let mut recorder = ValueRecorder::create(&path, &names).unwrap();
+
+        recorder.tick(10, 2.5).unwrap();
+        recorder.record("a", 0.5);
+        recorder.record("c", 1.0);
+        recorder.tick(10, 2.5).unwrap();
+        recorder.record("a", 0.25);
+
+        recorder.tick(11, 2.75).unwrap();
+        recorder.record("b", 1.0);
+
+        assert_eq!(recorder.finish().unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "frame,beats,a,b\n10,2.5,0.25,\n11,2.75,0.25,1\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay() {
+        let lanes = parse_lanes(
+            "frame,beats,a,b,c\n0,0,,1,\n1,0.5,2,3,\n2,1,4,3,\n2,1,6,3,\n",
+        )
+        .unwrap();
+        let replay = ValueReplay { lanes };
+
+        assert_eq!(replay.get("missing", 0.0), None);
+        assert_eq!(replay.get("c", 0.0), None);
+        assert_eq!(replay.get("a", 0.0), Some(2.0));
+        assert_eq!(replay.get("b", 0.0), Some(1.0));
+        assert_approx_eq!(replay.get("b", 0.25).unwrap(), 2.0);
+        assert_approx_eq!(replay.get("a", 0.75).unwrap(), 4.0);
+        assert_eq!(replay.get("a", 8.0), Some(6.0));
+
+        assert!(parse_lanes("a,b\n").is_err());
+        assert!(parse_lanes("frame,beats,a\n0,1,0\n1,0.5,0\n").is_err());
+        assert!(parse_lanes("frame,beats,a\n0,0,x\n").is_err());
+    }
+}
@@ -0,0 +1,509 @@
+//! **⚠️ Experimental**
+//! Depth camera input. A [`DepthCamera`] reads frames from the device selected
+//! in the UI's Depth Camera setting on a background thread and exposes the
+//! latest one as a [`DepthFrame`] (distances in meters) for point clouds on the
+//! CPU, or as a texture via [`DepthTexture`] for shaders.
+//!
+//! Intel RealSense cameras are supported with the `realsense` feature, which
+//! requires librealsense2 to be installed. Other devices such as the Kinect can
+//! be added by implementing [`DepthBackend`] and calling
+//! [`register_depth_backend`] before [`crate::prelude::run`].
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let camera = DepthCamera::new();
+//! let depth_texture = gpu::DepthTexture::default();
+//!
+//! // update
+//! if let Some(frame) = self.camera.update() {
+//!     self.depth_texture.update(app, frame);
+//!     self.points = frame.point_cloud(0.5, 2.0, 4);
+//! }
+//! self.gpu.set_texture(app, self.depth_texture.view().unwrap());
+//! ```
+
+use nannou::prelude::*;
+use nannou::wgpu;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::{self, JoinHandle};
+
+use super::prelude::*;
+use crate::runtime::global;
+
+/// Separates the backend from the device in names from
+/// [`list_depth_devices`], e.g. "RealSense: D435 (123456789)"
+const SEPARATOR: &str = ": ";
+
+/// Pinhole camera parameters used to project depth pixels into 3D, all in
+/// pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthIntrinsics {
+    /// Focal length
+    pub fx: f32,
+    pub fy: f32,
+    /// Principal point
+    pub cx: f32,
+    pub cy: f32,
+}
+
+impl DepthIntrinsics {
+    /// Approximates intrinsics for devices that don't report them
+    pub fn from_fov(size: [usize; 2], horizontal_fov_degrees: f32) -> Self {
+        let [w, h] = size.map(|x| x as f32);
+        let f = w / 2.0 / (horizontal_fov_degrees.to_radians() / 2.0).tan();
+        Self {
+            fx: f,
+            fy: f,
+            cx: w / 2.0,
+            cy: h / 2.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthFrame {
+    pub size: [usize; 2],
+    /// Row-major distances in meters with the first row at the top. 0 means
+    /// the device had no reading for that pixel
+    pub depth: Vec<f32>,
+    pub intrinsics: DepthIntrinsics,
+}
+
+impl DepthFrame {
+    pub fn get(&self, x: usize, y: usize) -> f32 {
+        self.depth[y * self.size[0] + x]
+    }
+
+    /// Camera-space points (meters; x right, y up, z away from the camera)
+    /// for every `step`th pixel in both directions whose depth is within
+    /// `[near, far]`
+    pub fn point_cloud(&self, near: f32, far: f32, step: usize) -> Vec<Vec3> {
+        let [w, h] = self.size;
+        let step = step.max(1);
+        let DepthIntrinsics { fx, fy, cx, cy } = self.intrinsics;
+
+        let mut points = vec![];
+        for y in (0..h).step_by(step) {
+            for x in (0..w).step_by(step) {
+                let z = self.get(x, y);
+                if z > 0.0 && z >= near && z <= far {
+                    points.push(vec3(
+                        (x as f32 + 0.5 - cx) * z / fx,
+                        (cy - y as f32 - 0.5) * z / fy,
+                        z,
+                    ));
+                }
+            }
+        }
+        points
+    }
+}
+
+/// An open depth device
+pub trait DepthDevice: Send {
+    /// Waits briefly for the next frame. `Ok(None)` means none arrived in time
+    fn poll(&mut self) -> Result<Option<DepthFrame>, Box<dyn Error>>;
+}
+
+/// A kind of depth camera, e.g. RealSense
+pub trait DepthBackend: Send + Sync {
+    fn name(&self) -> &str;
+    fn list_devices(&self) -> Result<Vec<String>, Box<dyn Error>>;
+    fn open(
+        &self,
+        device: &str,
+    ) -> Result<Box<dyn DepthDevice>, Box<dyn Error>>;
+}
+
+static BACKENDS: LazyLock<Mutex<Vec<Arc<dyn DepthBackend>>>> =
+    LazyLock::new(|| {
+        #[allow(unused_mut)]
+        let mut backends: Vec<Arc<dyn DepthBackend>> = vec![];
+        #[cfg(feature = "realsense")]
+        backends.push(Arc::new(realsense::RealSenseBackend));
+        Mutex::new(backends)
+    });
+
+/// Makes a backend's devices available in the UI. Call before [`crate::prelude::run`]
+pub fn register_depth_backend(backend: impl DepthBackend + 'static) {
+    BACKENDS.lock().unwrap().push(Arc::new(backend));
+}
+
+/// Devices of every backend, as "Backend: Device"
+pub fn list_depth_devices() -> Result<Vec<String>, Box<dyn Error>> {
+    let backends = BACKENDS.lock().unwrap().clone();
+    let mut devices = vec![];
+    for backend in backends {
+        for device in backend.list_devices()? {
+            devices.push(format!("{}{}{}", backend.name(), SEPARATOR, device));
+        }
+    }
+    Ok(devices)
+}
+
+fn open_depth_device(
+    name: &str,
+) -> Result<Box<dyn DepthDevice>, Box<dyn Error>> {
+    let (backend_name, device) = name
+        .split_once(SEPARATOR)
+        .ok_or_else(|| format!("Invalid depth device name '{}'", name))?;
+
+    let backend = BACKENDS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|backend| backend.name() == backend_name)
+        .cloned()
+        .ok_or_else(|| format!("No depth backend named '{}'", backend_name))?;
+
+    backend.open(device)
+}
+
+/// Reads frames from the selected depth device on a background thread and
+/// reconnects when the selection changes
+pub struct DepthCamera {
+    device_name: Option<String>,
+    latest: Arc<Mutex<Option<DepthFrame>>>,
+    frame: Option<DepthFrame>,
+    running: Arc<AtomicBool>,
+    connected: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Default for DepthCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DepthCamera {
+    pub fn new() -> Self {
+        let mut camera = Self {
+            device_name: None,
+            latest: Arc::new(Mutex::new(None)),
+            frame: None,
+            running: Arc::new(AtomicBool::new(false)),
+            connected: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        };
+        camera.start();
+        camera
+    }
+
+    /// Whether the device opened and hasn't failed since
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// The device this camera reads from, e.g. "RealSense: D435 (123456789)"
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// Takes the newest frame from the device, returning it if there is one
+    /// since the last call. Call once per update
+    pub fn update(&mut self) -> Option<&DepthFrame> {
+        if global::depth_device_name() != self.device_name {
+            self.stop();
+            self.start();
+        }
+
+        let frame = self.latest.lock().unwrap().take();
+        if frame.is_some() {
+            self.frame = frame;
+            self.frame.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// The most recent frame, if any has arrived
+    pub fn frame(&self) -> Option<&DepthFrame> {
+        self.frame.as_ref()
+    }
+
+    /// See [`DepthFrame::point_cloud`]. Empty until a frame arrives
+    pub fn point_cloud(&self, near: f32, far: f32, step: usize) -> Vec<Vec3> {
+        self.frame
+            .as_ref()
+            .map_or_else(Vec::new, |frame| frame.point_cloud(near, far, step))
+    }
+
+    fn start(&mut self) {
+        self.device_name = global::depth_device_name();
+        let Some(name) = self.device_name.clone() else {
+            warn_once!("No depth device available");
+            return;
+        };
+
+        let latest = self.latest.clone();
+        let connected = self.connected.clone();
+        let running = self.running.clone();
+        running.store(true, Ordering::Release);
+
+        self.handle = Some(thread::spawn(move || {
+            let mut device = match open_depth_device(&name) {
+                Ok(device) => device,
+                Err(e) => {
+                    error!("Failed to open depth device '{}': {}", name, e);
+                    return;
+                }
+            };
+            info!("Depth device '{}' connected", name);
+            connected.store(true, Ordering::Relaxed);
+
+            while running.load(Ordering::Acquire) {
+                match device.poll() {
+                    Ok(Some(frame)) => *latest.lock().unwrap() = Some(frame),
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("Depth device '{}' failed: {}", name, e);
+                        break;
+                    }
+                }
+            }
+
+            connected.store(false, Ordering::Relaxed);
+        }));
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+        self.frame = None;
+        self.latest.lock().unwrap().take();
+    }
+}
+
+impl Drop for DepthCamera {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Uploads [`DepthFrame`]s to an R32Float texture of meters, for shaders. Sample
+/// it with `textureLoad` or a non-filtering sampler
+#[derive(Default)]
+pub struct DepthTexture {
+    texture: Option<wgpu::Texture>,
+    view: Option<wgpu::TextureView>,
+}
+
+impl DepthTexture {
+    pub fn update(&mut self, app: &App, frame: &DepthFrame) {
+        let window = app.main_window();
+        let device = window.device();
+        let [w, h] = frame.size.map(|x| x as u32);
+
+        let resize = self
+            .texture
+            .as_ref()
+            .is_none_or(|texture| texture.size() != [w, h]);
+
+        if resize {
+            let texture = wgpu::TextureBuilder::new()
+                .size([w, h])
+                .format(wgpu::TextureFormat::R32Float)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST,
+                )
+                .sample_count(1)
+                .build(device);
+            self.view = Some(texture.view().build());
+            self.texture = Some(texture);
+        }
+
+        if let Some(texture) = &self.texture {
+            window.queue().write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                bytemuck::cast_slice(&frame.depth),
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(w * 4),
+                    rows_per_image: Some(h),
+                },
+                wgpu::Extent3d {
+                    width: w,
+                    height: h,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+
+    /// `None` until the first [`Self::update`]
+    pub fn view(&self) -> Option<&wgpu::TextureView> {
+        self.view.as_ref()
+    }
+}
+
+#[cfg(feature = "realsense")]
+mod realsense {
+    use realsense_rust::config::Config;
+    use realsense_rust::context::Context;
+    use realsense_rust::frame::DepthFrame as RsDepthFrame;
+    use realsense_rust::kind::{Rs2CameraInfo, Rs2Format, Rs2StreamKind};
+    use realsense_rust::pipeline::{ActivePipeline, InactivePipeline};
+    use std::collections::HashSet;
+    use std::error::Error;
+    use std::ffi::CString;
+    use std::time::Duration;
+
+    use super::*;
+
+    const WIDTH: usize = 640;
+    const HEIGHT: usize = 480;
+    const FPS: usize = 30;
+
+    pub struct RealSenseBackend;
+
+    impl DepthBackend for RealSenseBackend {
+        fn name(&self) -> &str {
+            "RealSense"
+        }
+
+        /// "Name (serial number)"
+        fn list_devices(&self) -> Result<Vec<String>, Box<dyn Error>> {
+            let context = Context::new()?;
+            let devices = context
+                .query_devices(HashSet::new())
+                .iter()
+                .filter_map(|device| {
+                    let name = device.info(Rs2CameraInfo::Name)?;
+                    let serial = device.info(Rs2CameraInfo::SerialNumber)?;
+                    Some(format!(
+                        "{} ({})",
+                        name.to_string_lossy(),
+                        serial.to_string_lossy()
+                    ))
+                })
+                .collect();
+            Ok(devices)
+        }
+
+        fn open(
+            &self,
+            device: &str,
+        ) -> Result<Box<dyn DepthDevice>, Box<dyn Error>> {
+            let serial = device
+                .rsplit_once('(')
+                .and_then(|(_, serial)| serial.strip_suffix(')'))
+                .ok_or_else(|| format!("No serial number in '{}'", device))?;
+
+            let context = Context::new()?;
+            let pipeline = InactivePipeline::try_from(&context)?;
+            let mut config = Config::new();
+            config
+                .enable_device_from_serial(&CString::new(serial)?)?
+                .disable_all_streams()?
+                .enable_stream(
+                    Rs2StreamKind::Depth,
+                    None,
+                    WIDTH,
+                    HEIGHT,
+                    Rs2Format::Z16,
+                    FPS,
+                )?;
+
+            let pipeline = pipeline.start(Some(config))?;
+            Ok(Box::new(RealSenseDevice { pipeline }))
+        }
+    }
+
+    struct RealSenseDevice {
+        pipeline: ActivePipeline,
+    }
+
+    // The pipeline is only ever used from the camera's thread
+    unsafe impl Send for RealSenseDevice {}
+
+    impl DepthDevice for RealSenseDevice {
+        fn poll(&mut self) -> Result<Option<DepthFrame>, Box<dyn Error>> {
+            let frames =
+                match self.pipeline.wait(Some(Duration::from_millis(100))) {
+                    Ok(frames) => frames,
+                    // Timeouts are routine while the device warms up
+                    Err(_) => return Ok(None),
+                };
+
+            let Some(frame) = frames.frames_of_type::<RsDepthFrame>().pop()
+            else {
+                return Ok(None);
+            };
+
+            let (w, h) = (frame.width(), frame.height());
+            let mut depth = Vec::with_capacity(w * h);
+            for y in 0..h {
+                for x in 0..w {
+                    depth.push(frame.distance(x, y).unwrap_or(0.0));
+                }
+            }
+
+            let intrinsics = frame.stream_profile().intrinsics()?;
+
+            Ok(Some(DepthFrame {
+                size: [w, h],
+                depth,
+                intrinsics: DepthIntrinsics {
+                    fx: intrinsics.fx(),
+                    fy: intrinsics.fy(),
+                    cx: intrinsics.ppx(),
+                    cy: intrinsics.ppy(),
+                },
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn frame() -> DepthFrame {
+        DepthFrame {
+            size: [4, 2],
+            depth: vec![1.0, 0.0, 2.0, 3.0, 1.0, 1.0, 1.0, 1.0],
+            intrinsics: DepthIntrinsics::from_fov([4, 2], 90.0),
+        }
+    }
+
+    #[test]
+    fn test_from_fov() {
+        let intrinsics = DepthIntrinsics::from_fov([640, 480], 90.0);
+        assert_approx_eq!(intrinsics.fx, 320.0);
+        assert_approx_eq!(intrinsics.cy, 240.0);
+    }
+
+    #[test]
+    fn test_point_cloud_thresholds() {
+        let frame = frame();
+
+        // Skips the missing reading at (1, 0)
+        assert_eq!(frame.point_cloud(0.0, 10.0, 1).len(), 7);
+        assert_eq!(frame.point_cloud(1.5, 2.5, 1).len(), 1);
+        assert_eq!(frame.point_cloud(0.0, 10.0, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_point_cloud_projection() {
+        let frame = frame();
+        let points = frame.point_cloud(2.0, 2.0, 1);
+
+        // Pixel (2, 0)'s center is half a pixel right of and above the
+        // principal point (2, 1) with a focal length of 2
+        assert_eq!(points, vec![vec3(0.5, 0.5, 2.0)]);
+    }
+}
@@ -0,0 +1,362 @@
+//! Radial point displacement with configurable falloff kernels. A
+//! [`Displacer`] pushes (or with [`Displacer::attract`], pulls) points around
+//! its position with a force that fades out with distance according to its
+//! [`Falloff`]. Multiple displacers can be combined with
+//! [`aggregate_influence`].
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let displacers = vec![
+//!     Displacer::new_at_origin().falloff(Falloff::Gaussian),
+//!     Displacer::new_with_position(vec2(100.0, 0.0)),
+//! ];
+//!
+//! // update – radius and strength can be any control, including animations
+//! // with hot params defined in the sketch's yaml file
+//! for displacer in self.displacers.iter_mut() {
+//!     displacer.update_from_hub(&self.hub, "radius", "strength");
+//! }
+//!
+//! // view
+//! for point in grid.points(|x, y| vec2(x, y)) {
+//!     let p = point
+//!         + aggregate_influence(&self.displacers, point, Aggregation::Sum);
+//!     draw.ellipse().xy(p).radius(2.0);
+//! }
+//! ```
+
+use nannou::prelude::*;
+use std::fmt::{Debug, Formatter, Result};
+use std::sync::Arc;
+
+use crate::framework::prelude::*;
+
+pub type CustomDistanceFn =
+    Option<Arc<dyn Fn(Vec2, Vec2) -> f32 + Send + Sync>>;
+
+/// Shapes how a displacer's force fades out with distance. All kernels return
+/// a weight where 1.0 is full strength (at the displacer's position) and reach
+/// zero at twice the radius, with the exception of [`Falloff::Gaussian`],
+/// which only approaches zero (~0.02 at twice the radius).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    /// `(1 - d / 2r) ^ power`. `Power(2.0)` is the classic displacer curve
+    Power(f32),
+    /// `exp(-(d / r) ^ 2)`
+    Gaussian,
+    /// Hermite smoothstep from full strength to zero at twice the radius
+    Smoothstep,
+    /// Concentric waves under a linear envelope. Weights go negative in the
+    /// troughs, pulling points inward. `frequency` is the number of waves per
+    /// radius and `phase` (in cycles) can be animated to make the rings
+    /// travel outward.
+    Ripple { frequency: f32, phase: f32 },
+}
+
+impl Default for Falloff {
+    fn default() -> Self {
+        Self::Power(2.0)
+    }
+}
+
+impl Falloff {
+    pub fn weight(&self, distance: f32, radius: f32) -> f32 {
+        // Ensure radius is never zero to avoid division by zero
+        let radius = radius.max(f32::EPSILON);
+        let proximity = (1.0 - distance / (radius * 2.0)).max(0.0);
+
+        match *self {
+            Self::Power(power) => proximity.powf(power),
+            Self::Gaussian => (-(distance / radius).powi(2)).exp(),
+            Self::Smoothstep => proximity * proximity * (3.0 - 2.0 * proximity),
+            Self::Ripple { frequency, phase } => {
+                let cycles = distance / radius * frequency - phase;
+                proximity * (cycles * TAU).cos()
+            }
+        }
+    }
+}
+
+pub struct Displacer {
+    pub position: Vec2,
+    pub radius: f32,
+    pub strength: f32,
+    pub falloff: Falloff,
+    pub custom_distance_fn: CustomDistanceFn,
+}
+
+impl Displacer {
+    pub fn new(
+        position: Vec2,
+        radius: f32,
+        strength: f32,
+        custom_distance_fn: CustomDistanceFn,
+    ) -> Self {
+        Self {
+            position,
+            radius,
+            strength,
+            falloff: Falloff::default(),
+            custom_distance_fn,
+        }
+    }
+
+    pub fn new_with_position(position: Vec2) -> Self {
+        Self::new(position, 50.0, 10.0, None)
+    }
+
+    pub fn new_at_origin() -> Self {
+        Self::new_with_position(vec2(0.0, 0.0))
+    }
+
+    pub fn falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Outward displacement of `grid_point` using the displacer's
+    /// [`Falloff`]
+    pub fn influence(&self, grid_point: Vec2) -> Vec2 {
+        self.push(grid_point, self.falloff)
+    }
+
+    /// Outward displacement using a [`Falloff::Power`] curve with
+    /// `scaling_power`, ignoring the displacer's own falloff
+    pub fn core_influence(&self, grid_point: Vec2, scaling_power: f32) -> Vec2 {
+        self.push(grid_point, Falloff::Power(scaling_power))
+    }
+
+    fn push(&self, grid_point: Vec2, falloff: Falloff) -> Vec2 {
+        let distance_to_center = self.distance(grid_point);
+
+        if distance_to_center == 0.0 || distance_to_center.is_nan() {
+            return vec2(0.0, 0.0);
+        }
+
+        let force =
+            self.strength * falloff.weight(distance_to_center, self.radius);
+
+        // Calculate the angle between the grid point and displacer center
+        // atan2 gives us angle in radians (-π to π) based on x,y differences
+        let angle = (grid_point.y - self.position.y)
+            .atan2(grid_point.x - self.position.x);
+
+        // Convert polar coordinates (angle & force) to cartesian (x,y):
+        vec2(angle.cos() * force, angle.sin() * force)
+    }
+
+    pub fn attract(&self, grid_point: Vec2, scaling_power: f32) -> Vec2 {
+        let radius = self.radius.max(f32::EPSILON);
+
+        let distance_to_center = self.distance(grid_point);
+
+        if distance_to_center.is_nan() || distance_to_center < 0.0 {
+            return vec2(0.0, 0.0);
+        }
+
+        if distance_to_center == 0.0 {
+            return vec2(0.0, 0.0);
+        }
+
+        let proximity =
+            1.0 - (distance_to_center / (radius * 2.0)).clamp(0.0, 1.0);
+        let distance_factor = proximity.max(0.0);
+
+        let force = self.strength
+            * distance_factor
+            * (distance_to_center / radius)
+                .clamp(0.0, f32::MAX)
+                .powf(scaling_power);
+
+        if !force.is_finite() {
+            return vec2(0.0, 0.0);
+        }
+
+        let angle = (grid_point.y - self.position.y)
+            .atan2(grid_point.x - self.position.x);
+
+        let dx = -angle.cos() * force;
+        let dy = -angle.sin() * force;
+
+        vec2(dx, dy)
+    }
+
+    fn distance(&self, grid_point: Vec2) -> f32 {
+        match &self.custom_distance_fn {
+            Some(f) => f(grid_point, self.position),
+            None => grid_point.distance(self.position),
+        }
+    }
+
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+    }
+
+    pub fn set_strength(&mut self, strength: f32) {
+        self.strength = strength;
+    }
+
+    pub fn set_falloff(&mut self, falloff: Falloff) {
+        self.falloff = falloff;
+    }
+
+    pub fn set_custom_distance_fn(
+        &mut self,
+        custom_distance_fn: CustomDistanceFn,
+    ) {
+        self.custom_distance_fn = custom_distance_fn;
+    }
+
+    /// Sets radius and strength from the named controls. Since these are
+    /// read through [`ControlHub::get`], they can be sliders, MIDI, audio,
+    /// or animations whose params are hot-reloaded from yaml.
+    pub fn update_from_hub<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        radius: &str,
+        strength: &str,
+    ) {
+        self.radius = hub.get(radius);
+        self.strength = hub.get(strength);
+    }
+}
+
+impl Debug for Displacer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("Displacer")
+            .field("position", &self.position)
+            .field("radius", &self.radius)
+            .field("strength", &self.strength)
+            .field("falloff", &self.falloff)
+            .field("custom_distance_fn", &"<function>")
+            .finish()
+    }
+}
+
+/// How [`aggregate_influence`] combines the displacement of several
+/// displacers acting on the same point
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Aggregation {
+    /// Displacements add up; overlapping displacers compound
+    #[default]
+    Sum,
+    /// Mean displacement of the displacers that affect the point at all
+    Average,
+    /// Only the displacement with the largest magnitude is used
+    Strongest,
+}
+
+/// Combined [`Displacer::influence`] of all `displacers` at `point`
+pub fn aggregate_influence<'a, I>(
+    displacers: I,
+    point: Vec2,
+    aggregation: Aggregation,
+) -> Vec2
+where
+    I: IntoIterator<Item = &'a Displacer>,
+{
+    let influences = displacers.into_iter().map(|d| d.influence(point));
+
+    match aggregation {
+        Aggregation::Sum => influences.fold(Vec2::ZERO, |total, v| total + v),
+        Aggregation::Average => {
+            let (total, count) = influences
+                .filter(|v| *v != Vec2::ZERO)
+                .fold((Vec2::ZERO, 0), |(total, count), v| {
+                    (total + v, count + 1)
+                });
+            ternary!(count == 0, Vec2::ZERO, total / count as f32)
+        }
+        Aggregation::Strongest => influences
+            .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or(Vec2::ZERO),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::framework::motion::animation_tests::BPM;
+    use serial_test::serial;
+
+    #[test]
+    fn test_falloff_weights() {
+        for falloff in [
+            Falloff::Power(2.0),
+            Falloff::Gaussian,
+            Falloff::Smoothstep,
+            Falloff::Ripple {
+                frequency: 2.0,
+                phase: 0.0,
+            },
+        ] {
+            assert_approx_eq!(falloff.weight(0.0, 10.0), 1.0);
+            assert!(falloff.weight(25.0, 10.0).abs() < 0.02, "{:?}", falloff);
+        }
+        assert_approx_eq!(Falloff::Smoothstep.weight(10.0, 10.0), 0.5);
+        assert_approx_eq!(Falloff::Power(1.0).weight(10.0, 10.0), 0.5);
+        let ripple = Falloff::Ripple {
+            frequency: 1.0,
+            phase: 0.0,
+        };
+        assert!(ripple.weight(5.0, 10.0) < 0.0);
+    }
+
+    #[test]
+    fn test_default_influence_matches_core_influence() {
+        let displacer = Displacer::new(vec2(0.0, 0.0), 10.0, 5.0, None);
+        let p = vec2(3.0, 4.0);
+        assert_eq!(displacer.influence(p), displacer.core_influence(p, 2.0));
+        let v = displacer.influence(p);
+        assert_approx_eq!(v.length(), 5.0 * 0.75 * 0.75);
+        assert_approx_eq!(v.normalize().x, 0.6);
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let displacers = [
+            Displacer::new(vec2(-10.0, 0.0), 10.0, 4.0, None),
+            Displacer::new(vec2(10.0, 0.0), 10.0, 2.0, None),
+            Displacer::new(vec2(1000.0, 0.0), 10.0, 2.0, None),
+        ];
+        let p = vec2(0.0, 1.0);
+        let sum = aggregate_influence(&displacers, p, Aggregation::Sum);
+        let avg = aggregate_influence(&displacers, p, Aggregation::Average);
+        let max = aggregate_influence(&displacers, p, Aggregation::Strongest);
+        assert_approx_eq!(avg.x, sum.x / 2.0);
+        assert_approx_eq!(avg.y, sum.y / 2.0);
+        assert_eq!(max, displacers[0].influence(p));
+        assert_eq!(
+            aggregate_influence(&displacers[2..], p, Aggregation::Average),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_update_from_hub() {
+        let hub = ControlHub::new(
+            Some(
+                r#"
+radius:
+  type: slider
+  default: 25
+strength:
+  type: slider
+  default: 3
+                "#,
+            ),
+            FrameTiming::new(Bpm::new(BPM)),
+        );
+        let mut displacer = Displacer::new_at_origin();
+        displacer.update_from_hub(&hub, "radius", "strength");
+        assert_eq!(displacer.radius, 25.0);
+        assert_eq!(displacer.strength, 3.0);
+    }
+}
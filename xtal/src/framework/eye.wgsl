@@ -0,0 +1,25 @@
+// -----------------------------------------------------------------------------
+//  XTAL EYE
+//  Which eye a GpuState pipeline is rendering when the runtime's stereo mode
+//  is on. Mirrors xtal::gpu::EyeUniform - keep in sync! Offset your camera
+//  (or ray origin, or uv) by `eye_offset()` and the runtime takes care of
+//  rendering both eyes and compositing them. Include in any GpuState shader
+//  with:
+//      // #include xtal::eye
+// -----------------------------------------------------------------------------
+
+struct Eye {
+    // -1 for the left eye, 1 for the right, 0 when stereo is off
+    side: f32,
+    // Distance between the eyes in the shader's own units
+    separation: f32,
+    _pad: vec2f,
+}
+
+@group(0) @binding(1)
+var<uniform> eye: Eye;
+
+// Signed horizontal offset of the current eye from the center
+fn eye_offset() -> f32 {
+    return eye.side * eye.separation * 0.5;
+}
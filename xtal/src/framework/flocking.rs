@@ -0,0 +1,320 @@
+//! Reynolds-style flocking (separation, alignment, and cohesion) over a
+//! [`SpatialHash`] so thousands of agents only look at their neighbors. Like
+//! [`crate::framework::physics`], time is measured in beats.
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let mut flock = Flock::new().bounds(wr.rect()).perception(40.0);
+//! for _ in 0..1000 {
+//!     let position = vec2(random_range(-hw, hw), random_range(-hh, hh));
+//!     flock.add(position, Vec2::ZERO);
+//! }
+//!
+//! // update
+//! self.flock.update_params(&self.hub, "flock");
+//! self.flock.update(self.hub.animation.beats());
+//!
+//! // view
+//! for agent in &self.flock.agents {
+//!     draw.tri().xy(agent.position).rotate(agent.heading());
+//! }
+//! ```
+
+use nannou::prelude::*;
+use rayon::prelude::*;
+
+use crate::framework::geometry::SpatialHash;
+use crate::framework::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Agent {
+    pub position: Vec2,
+    /// Units per beat
+    pub velocity: Vec2,
+}
+
+impl Agent {
+    /// The direction the agent is moving in radians
+    pub fn heading(&self) -> f32 {
+        self.velocity.y.atan2(self.velocity.x)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Flock {
+    pub agents: Vec<Agent>,
+    /// Weight of steering away from neighbors closer than
+    /// [`Self::separation_distance`]
+    pub separation: f32,
+    /// Weight of steering toward the average heading of neighbors
+    pub alignment: f32,
+    /// Weight of steering toward the average position of neighbors
+    pub cohesion: f32,
+    /// Neighbors farther than this are ignored
+    pub perception: f32,
+    pub separation_distance: f32,
+    /// Units per beat
+    pub max_speed: f32,
+    /// Upper limit of each steering force in units per beat²
+    pub max_force: f32,
+    /// Agents leaving these bounds wrap around to the opposite side
+    pub bounds: Option<Rect>,
+    /// Upper limit of a single step in beats. Larger deltas are divided into
+    /// multiple sub-steps
+    pub max_step: f32,
+    index: SpatialHash,
+    last_beat: Option<f32>,
+}
+
+impl Default for Flock {
+    fn default() -> Self {
+        Self {
+            agents: vec![],
+            separation: 1.5,
+            alignment: 1.0,
+            cohesion: 1.0,
+            perception: 50.0,
+            separation_distance: 25.0,
+            max_speed: 200.0,
+            max_force: 400.0,
+            bounds: None,
+            max_step: 1.0 / 16.0,
+            index: SpatialHash::new(50.0),
+            last_beat: None,
+        }
+    }
+}
+
+impl Flock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn weights(
+        mut self,
+        separation: f32,
+        alignment: f32,
+        cohesion: f32,
+    ) -> Self {
+        self.separation = separation;
+        self.alignment = alignment;
+        self.cohesion = cohesion;
+        self
+    }
+
+    pub fn perception(mut self, perception: f32) -> Self {
+        self.perception = perception;
+        self
+    }
+
+    pub fn separation_distance(mut self, distance: f32) -> Self {
+        self.separation_distance = distance;
+        self
+    }
+
+    pub fn max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    pub fn max_force(mut self, max_force: f32) -> Self {
+        self.max_force = max_force;
+        self
+    }
+
+    pub fn bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Returns the index of the new agent
+    pub fn add(&mut self, position: Vec2, velocity: Vec2) -> usize {
+        self.agents.push(Agent { position, velocity });
+        self.agents.len() - 1
+    }
+
+    pub fn clear(&mut self) {
+        self.agents.clear();
+        self.last_beat = None;
+    }
+
+    /// Reads `<prefix>.separation`, `<prefix>.alignment`, `<prefix>.cohesion`,
+    /// `<prefix>.perception`, `<prefix>.separation_distance`,
+    /// `<prefix>.max_speed`, and `<prefix>.max_force` from `hub`, leaving any
+    /// that aren't declared unchanged. Call every frame so the parameters can
+    /// be driven by animations, MIDI, `$name` params, etc.
+    pub fn update_params<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        prefix: &str,
+    ) {
+        for (name, field) in [
+            ("separation", &mut self.separation),
+            ("alignment", &mut self.alignment),
+            ("cohesion", &mut self.cohesion),
+            ("perception", &mut self.perception),
+            ("separation_distance", &mut self.separation_distance),
+            ("max_speed", &mut self.max_speed),
+            ("max_force", &mut self.max_force),
+        ] {
+            let name = format!("{}.{}", prefix, name);
+            if hub.has(&name) {
+                *field = hub.get(&name);
+            }
+        }
+    }
+
+    /// Advance the flock to `beats`, usually `hub.animation.beats()`. The
+    /// first call only records the starting point. Backwards jumps reset the
+    /// reference without stepping
+    pub fn update(&mut self, beats: f32) {
+        let Some(last_beat) = self.last_beat.replace(beats) else {
+            return;
+        };
+
+        let delta = beats - last_beat;
+        if delta <= 0.0 {
+            return;
+        }
+
+        let steps = (delta / self.max_step).ceil().max(1.0) as usize;
+        let dt = delta / steps as f32;
+        for _ in 0..steps {
+            self.step(dt);
+        }
+    }
+
+    /// Advance the flock by `dt` beats
+    pub fn step(&mut self, dt: f32) {
+        let positions: Vec<Vec2> =
+            self.agents.iter().map(|agent| agent.position).collect();
+        if self.index.cell_size() != self.perception {
+            self.index = SpatialHash::new(self.perception);
+        }
+        self.index.rebuild(&positions);
+
+        let forces: Vec<Vec2> = (0..self.agents.len())
+            .into_par_iter()
+            .map(|i| self.steering(i))
+            .collect();
+
+        for (agent, force) in self.agents.iter_mut().zip(forces) {
+            agent.velocity =
+                (agent.velocity + force * dt).clamp_length_max(self.max_speed);
+            agent.position += agent.velocity * dt;
+
+            if let Some(bounds) = self.bounds {
+                agent.position = wrap(agent.position, bounds);
+            }
+        }
+    }
+
+    fn steering(&self, i: usize) -> Vec2 {
+        let agent = self.agents[i];
+        let mut away = Vec2::ZERO;
+        let mut heading = Vec2::ZERO;
+        let mut center = Vec2::ZERO;
+        let mut count = 0;
+
+        for j in self.index.neighbors(agent.position, self.perception) {
+            if j == i {
+                continue;
+            }
+            let other = self.agents[j];
+            let offset = agent.position - other.position;
+            let distance_squared = offset.length_squared();
+            if distance_squared
+                < self.separation_distance * self.separation_distance
+                && distance_squared > f32::EPSILON
+            {
+                away += offset / distance_squared;
+            }
+            heading += other.velocity;
+            center += other.position;
+            count += 1;
+        }
+
+        if count == 0 {
+            return Vec2::ZERO;
+        }
+
+        let count = count as f32;
+        self.steer(agent, away) * self.separation
+            + self.steer(agent, heading / count) * self.alignment
+            + self.steer(agent, center / count - agent.position) * self.cohesion
+    }
+
+    /// Force that turns `agent` toward `direction` at full speed
+    fn steer(&self, agent: Agent, direction: Vec2) -> Vec2 {
+        if direction.length_squared() <= f32::EPSILON {
+            return Vec2::ZERO;
+        }
+        let desired = direction.normalize() * self.max_speed;
+        (desired - agent.velocity).clamp_length_max(self.max_force)
+    }
+}
+
+fn wrap(position: Vec2, bounds: Rect) -> Vec2 {
+    let wrap_axis = |value: f32, min: f32, size: f32| {
+        ternary!(size > 0.0, min + (value - min).rem_euclid(size), value)
+    };
+    vec2(
+        wrap_axis(position.x, bounds.left(), bounds.w()),
+        wrap_axis(position.y, bounds.bottom(), bounds.h()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_alignment() {
+        let mut flock = Flock::new().weights(0.0, 1.0, 0.0);
+        flock.add(vec2(0.0, 0.0), vec2(100.0, 0.0));
+        flock.add(vec2(10.0, 0.0), vec2(0.0, 100.0));
+
+        for _ in 0..100 {
+            flock.step(0.01);
+        }
+
+        let [a, b] = [flock.agents[0], flock.agents[1]];
+        assert!(a.velocity.angle_between(b.velocity).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_separation() {
+        let mut flock = Flock::new().weights(1.0, 0.0, 0.0);
+        flock.add(vec2(0.0, 0.0), Vec2::ZERO);
+        flock.add(vec2(5.0, 0.0), Vec2::ZERO);
+
+        for _ in 0..10 {
+            flock.step(0.01);
+        }
+
+        assert!(flock.agents[0].position.x < 0.0);
+        assert!(flock.agents[1].position.x > 5.0);
+    }
+
+    #[test]
+    fn test_max_speed_and_isolation() {
+        let mut flock = Flock::new().max_speed(10.0).perception(20.0);
+        flock.add(vec2(0.0, 0.0), vec2(50.0, 0.0));
+        flock.add(vec2(1000.0, 0.0), vec2(0.0, 5.0));
+
+        flock.step(0.1);
+
+        assert_approx_eq!(flock.agents[0].velocity.length(), 10.0, 0.001);
+        // Too far from the other agent to be influenced
+        assert_eq!(flock.agents[1].velocity, vec2(0.0, 5.0));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let bounds = Rect::from_w_h(100.0, 100.0);
+        assert_eq!(wrap(vec2(60.0, -70.0), bounds), vec2(-40.0, 30.0));
+        assert_eq!(wrap(vec2(10.0, 20.0), bounds), vec2(10.0, 20.0));
+    }
+}
@@ -0,0 +1,219 @@
+//! Captured images, flipbook frames, and recording frames. Rather than
+//! capturing the window's final output, the runtime copies the frame into an
+//! intermediate texture partway through `Context::present` so display-only
+//! stages like the output warp never end up in exports.
+
+use nannou::prelude::*;
+use nannou::wgpu;
+use std::path::PathBuf;
+
+use super::prelude::*;
+
+/// The stages the runtime runs over the sketch's output after `view`, in order.
+/// Everything before [`Stage::Capture`] ends up in captured images and
+/// recordings; everything after is only shown in the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Stage {
+    Post,
+    Letterbox,
+    Capture,
+    Guides,
+    Calibration,
+    Warp,
+}
+
+pub(crate) const STAGES: [Stage; 6] = [
+    Stage::Post,
+    Stage::Letterbox,
+    Stage::Capture,
+    Stage::Guides,
+    Stage::Calibration,
+    Stage::Warp,
+];
+
+struct Resources {
+    size: [u32; 2],
+    src_sample_count: u32,
+    /// Single-sampled copy of the frame that snapshots are read from
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    /// Copies the frame into `texture` when the frame isn't multisampled
+    /// (multisampled frames are resolved instead)
+    reshaper: Option<wgpu::TextureReshaper>,
+}
+
+impl Resources {
+    fn new(frame: &Frame) -> Self {
+        let device = frame.device_queue_pair().device();
+        let src_sample_count = frame.texture_msaa_samples();
+
+        let texture = wgpu::TextureBuilder::new()
+            .size(frame.texture_size())
+            .format(Frame::TEXTURE_FORMAT)
+            .dimension(wgpu::TextureDimension::D2)
+            .usage(
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_SRC,
+            )
+            .sample_count(1)
+            .build(device);
+        let view = texture.view().build();
+
+        let reshaper = (src_sample_count == 1).then(|| {
+            wgpu::TextureReshaper::new(
+                device,
+                frame.texture_view(),
+                1,
+                wgpu::TextureSampleType::Float { filterable: true },
+                1,
+                Frame::TEXTURE_FORMAT,
+            )
+        });
+
+        Self {
+            size: frame.texture_size(),
+            src_sample_count,
+            texture,
+            view,
+            reshaper,
+        }
+    }
+
+    /// Copies the frame's current contents into `texture`
+    fn copy(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        match &self.reshaper {
+            Some(reshaper) => {
+                reshaper.encode_render_pass(&self.view, encoder);
+            }
+            None => {
+                // An empty pass is enough to resolve the multisampled frame
+                wgpu::RenderPassBuilder::new()
+                    .color_attachment(frame.texture_view(), |color| {
+                        color
+                            .load_op(wgpu::LoadOp::Load)
+                            .resolve_target(Some(&self.view))
+                    })
+                    .begin(encoder);
+            }
+        }
+    }
+}
+
+/// Writes frames requested with [`Self::request`] to disk. Applied by the
+/// runtime at [`Stage::Capture`]
+#[derive(Default)]
+pub(crate) struct FrameCapture {
+    capturer: wgpu::TextureCapturer,
+    resources: Option<Resources>,
+
+    /// Where to write the next presented frame
+    pending: Vec<PathBuf>,
+
+    /// Taken during the current frame and read back once it's submitted
+    snapshot: Option<(Vec<PathBuf>, wgpu::TextureSnapshot)>,
+}
+
+impl std::fmt::Debug for FrameCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameCapture")
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FrameCapture {
+    /// Writes the next presented frame to `path`
+    pub fn request(&mut self, path: PathBuf) {
+        self.pending.push(path);
+    }
+
+    pub fn apply(&mut self, frame: &Frame) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        if self.resources.as_ref().is_none_or(|r| {
+            r.size != frame.texture_size()
+                || r.src_sample_count != frame.texture_msaa_samples()
+        }) {
+            self.resources = Some(Resources::new(frame));
+        }
+        let resources = self.resources.as_ref().unwrap();
+
+        let device = frame.device_queue_pair().device();
+        let mut encoder = frame.command_encoder();
+        resources.copy(frame, &mut encoder);
+        let texture = &resources.texture;
+        let snapshot = self.capturer.capture(device, &mut encoder, texture);
+
+        self.snapshot = Some((std::mem::take(&mut self.pending), snapshot));
+    }
+
+    /// Writes out the snapshot taken by [`Self::apply`] on a worker thread.
+    /// Must be called after the frame has been submitted
+    pub fn save(&mut self) {
+        let Some((paths, snapshot)) = self.snapshot.take() else {
+            return;
+        };
+
+        let result = snapshot.read(move |result| match result {
+            Ok(image) => {
+                let image = image.to_owned();
+                for path in paths {
+                    if let Err(e) = image.save(&path) {
+                        error!("Failed to save frame to {:?}: {}", path, e);
+                    }
+                }
+            }
+            Err(e) => error!("Failed to read captured frame: {:?}", e),
+        });
+
+        if result.is_err() {
+            error!("Timed out waiting for a worker to save the captured frame");
+        }
+    }
+
+    /// Blocks until every frame handed to [`Self::save`] has been written
+    pub fn await_saves(
+        &self,
+        device: &wgpu::Device,
+    ) -> Result<(), wgpu::TextureCapturerAwaitWorkerTimeout<()>> {
+        self.capturer.await_active_snapshots(device)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The stages that make it into a capture when only `active` run
+    fn captured(active: &[Stage]) -> Vec<Stage> {
+        STAGES
+            .into_iter()
+            .take_while(|stage| *stage != Stage::Capture)
+            .filter(|stage| active.contains(stage))
+            .collect()
+    }
+
+    #[test]
+    fn test_capture_excludes_warp() {
+        let unwarped = [Stage::Post, Stage::Letterbox, Stage::Capture];
+        let warped = [unwarped.as_slice(), &[Stage::Warp]].concat();
+        assert_eq!(captured(&warped), captured(&unwarped));
+        assert_eq!(captured(&warped), [Stage::Post, Stage::Letterbox]);
+    }
+
+    #[test]
+    fn test_capture_excludes_calibration() {
+        let plain = [Stage::Post, Stage::Capture];
+        let calibrating = [plain.as_slice(), &[Stage::Calibration]].concat();
+        assert_eq!(captured(&calibrating), captured(&plain));
+    }
+
+    #[test]
+    fn test_capture_keeps_letterbox_and_excludes_guides() {
+        let active = [Stage::Letterbox, Stage::Capture, Stage::Guides];
+        assert_eq!(captured(&active), [Stage::Letterbox]);
+    }
+}
@@ -3,8 +3,18 @@
 //! frame rate so here we are. The implementation is technically flawed but so
 //! far has been working well enough for my purposes (animations are tight and
 //! videos seem perfectly synced). The module is meant for internal
-//! framework/runtime use and should not be interacted with directly.
-
+//! framework/runtime use and should not be interacted with directly, with the
+//! exception of [`tick`] for hosts other than the bundled runtime.
+//!
+//! # Pause vs. Freeze
+//! - **Paused**: the frame clock stops. Frame-based beats hold on the paused
+//!   beat and resume from it with no catch-up frames. External clocks (MIDI,
+//!   OSC) keep running since they belong to another device.
+//! - **Frozen**: rendering stops but the clock and updates keep going, so the
+//!   last frame holds on screen and unfreezing jumps to wherever the sketch
+//!   would be by now – handy for stutter effects.
+
+#[cfg(feature = "runtime")]
 use nannou::prelude::*;
 use parking_lot::RwLock;
 use std::sync::LazyLock;
@@ -22,7 +32,23 @@ static FPS: AtomicF32 = AtomicF32::new(60.0);
 static RENDER_FLAG: AtomicBool = AtomicBool::new(false);
 static FORCE_RENDER: AtomicBool = AtomicBool::new(false);
 static PAUSED: AtomicBool = AtomicBool::new(false);
+static FROZEN: AtomicBool = AtomicBool::new(false);
+static ON_DEMAND: AtomicBool = AtomicBool::new(false);
+static DIRTY: AtomicBool = AtomicBool::new(true);
+
+/// Guards against a zero or negative frame rate (which would make
+/// [`frame_duration`] infinite or panic)
+const MIN_FPS: f32 = 1.0;
+
+/// Advances the frame count as time passes and returns whether the host should
+/// update this frame. Hosts other than the bundled runtime call this once per
+/// iteration of their event loop and render too unless [`is_frozen`]
+pub fn tick() -> bool {
+    CONTROLLER.write().update();
+    should_update()
+}
 
+#[cfg(feature = "runtime")]
 pub fn wrapped_update<M, F>(
     app: &App,
     model: &mut M,
@@ -31,17 +57,12 @@ pub fn wrapped_update<M, F>(
 ) where
     F: FnOnce(&App, &mut M, Update),
 {
-    let should_update = {
-        let mut controller = CONTROLLER.write();
-        controller.update();
-        should_render()
-    };
-
-    if should_update {
+    if tick() {
         update_fn(app, model, update);
     }
 }
 
+#[cfg(feature = "runtime")]
 pub fn wrapped_view<M, F>(
     app: &App,
     model: &M,
@@ -60,12 +81,18 @@ where
     do_render
 }
 
-fn should_render() -> bool {
+fn should_update() -> bool {
     FORCE_RENDER.load(Ordering::Acquire)
         || (!PAUSED.load(Ordering::Acquire)
             && RENDER_FLAG.load(Ordering::Acquire))
 }
 
+#[cfg(any(test, feature = "runtime"))]
+fn should_render() -> bool {
+    FORCE_RENDER.load(Ordering::Acquire)
+        || (should_update() && !FROZEN.load(Ordering::Acquire))
+}
+
 pub fn frame_count() -> u32 {
     FRAME_COUNT.load(Ordering::Relaxed)
 }
@@ -82,14 +109,54 @@ pub fn fps() -> f32 {
     FPS.load(Ordering::Acquire)
 }
 
+/// Changes the target frame rate. Safe to call mid-session: any time
+/// accumulated toward the next frame is capped to the new frame duration so
+/// lowering the rate doesn't cause a burst of catch-up frames, and the average
+/// FPS meter starts fresh.
 pub fn set_fps(fps: f32) {
+    let fps = fps.max(MIN_FPS);
+    if fps == FPS.load(Ordering::Acquire) {
+        return;
+    }
     FPS.store(fps, Ordering::Release);
+    CONTROLLER.write().on_fps_changed();
 }
 
+/// Stops the frame clock. Resuming continues from the paused frame
 pub fn set_paused(paused: bool) {
     PAUSED.store(paused, Ordering::Relaxed);
 }
 
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Acquire)
+}
+
+/// Stops rendering while the clock and updates keep running
+pub fn set_frozen(frozen: bool) {
+    FROZEN.store(frozen, Ordering::Release);
+}
+
+pub fn is_frozen() -> bool {
+    FROZEN.load(Ordering::Acquire)
+}
+
+/// When enabled, frames are only counted and rendered after [`invalidate`]
+/// has been called. See [`PlayMode::OnDemand`]
+pub fn set_on_demand(on_demand: bool) {
+    ON_DEMAND.store(on_demand, Ordering::Release);
+    DIRTY.store(true, Ordering::Release);
+}
+
+pub fn on_demand() -> bool {
+    ON_DEMAND.load(Ordering::Acquire)
+}
+
+/// Request that the next frame be rendered when in on-demand mode. Has no
+/// effect otherwise since every frame is rendered anyway.
+pub fn invalidate() {
+    DIRTY.store(true, Ordering::Release);
+}
+
 pub fn average_fps() -> f32 {
     CONTROLLER.read().average_fps()
 }
@@ -108,6 +175,18 @@ pub fn frame_duration() -> Duration {
     Duration::from_secs_f32(1.0 / FPS.load(Ordering::Relaxed))
 }
 
+/// How far [0, 1) the clock is into the frame after the current one, based on
+/// the time accumulated since the last [`tick`]. Always 0 while paused or idle
+/// in on-demand mode since the frame count isn't advancing
+pub fn frame_progress() -> f32 {
+    if PAUSED.load(Ordering::Acquire)
+        || (ON_DEMAND.load(Ordering::Acquire) && !DIRTY.load(Ordering::Acquire))
+    {
+        return 0.0;
+    }
+    CONTROLLER.read().progress_at(Instant::now())
+}
+
 struct FrameController {
     last_frame_time: Instant,
     last_render_time: Instant,
@@ -132,6 +211,11 @@ impl FrameController {
         self.update_with_time(Instant::now());
     }
 
+    fn on_fps_changed(&mut self) {
+        self.accumulator = self.accumulator.min(frame_duration());
+        self.frame_intervals.clear();
+    }
+
     fn update_with_time(&mut self, now: Instant) {
         let elapsed = now - self.last_frame_time;
         self.accumulator += elapsed;
@@ -146,17 +230,32 @@ impl FrameController {
         }
 
         if !PAUSED.load(Ordering::Acquire) {
+            // In on-demand mode time keeps passing but nothing is counted or
+            // rendered until something invalidates the frame
+            let idle = ON_DEMAND.load(Ordering::Acquire)
+                && !DIRTY.load(Ordering::Acquire);
+
             // Render frames for each interval the accumulator surpasses
             while self.accumulator >= frame_duration {
                 self.accumulator -= frame_duration;
-                FRAME_COUNT.fetch_add(1, Ordering::Relaxed);
-                RENDER_FLAG.store(true, Ordering::Relaxed);
+                if !idle {
+                    FRAME_COUNT.fetch_add(1, Ordering::Relaxed);
+                    RENDER_FLAG.store(true, Ordering::Relaxed);
+                }
+            }
+
+            if RENDER_FLAG.load(Ordering::Acquire) {
+                DIRTY.store(false, Ordering::Release);
             }
 
             // Adjust for small drifts (do we really need this?)
             if self.accumulator < Duration::from_millis(1) {
                 self.accumulator = Duration::ZERO;
             }
+        } else {
+            // Time spent paused must not be counted or resuming would burst
+            // through every frame missed in the meantime
+            self.accumulator = Duration::ZERO;
         }
 
         if RENDER_FLAG.load(Ordering::Acquire) {
@@ -181,6 +280,13 @@ impl FrameController {
         }
     }
 
+    fn progress_at(&self, now: Instant) -> f32 {
+        let pending = self.accumulator
+            + now.saturating_duration_since(self.last_frame_time);
+        let progress = pending.as_secs_f32() / frame_duration().as_secs_f32();
+        progress.clamp(0.0, 1.0 - f32::EPSILON)
+    }
+
     fn frame_count(&self) -> u32 {
         FRAME_COUNT.load(Ordering::Relaxed)
     }
@@ -229,6 +335,9 @@ pub mod tests {
         RENDER_FLAG.store(false, Ordering::SeqCst);
         FORCE_RENDER.store(false, Ordering::SeqCst);
         PAUSED.store(false, Ordering::SeqCst);
+        FROZEN.store(false, Ordering::SeqCst);
+        ON_DEMAND.store(false, Ordering::SeqCst);
+        DIRTY.store(true, Ordering::SeqCst);
     }
 
     #[test]
@@ -280,4 +389,152 @@ pub mod tests {
         assert_eq!(controller.frame_count(), 4);
         assert!(should_render());
     }
+
+    #[test]
+    #[serial]
+    fn test_on_demand() {
+        init();
+        set_on_demand(true);
+        let clock = MockClock::new();
+        let mut controller = FrameController::new();
+        controller.last_frame_time = clock.now();
+        controller.last_render_time = clock.now();
+
+        // Starts dirty so the first frame always renders
+        clock.advance(frame_duration());
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 1);
+        assert!(should_render());
+
+        // Nothing invalidated
+        clock.advance(frame_duration() * 3);
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 1);
+        assert!(!should_render());
+
+        // Waits for the next frame boundary rather than rendering immediately
+        invalidate();
+        clock.advance(frame_duration() / 2);
+        controller.update_with_time(clock.now());
+        assert!(!should_render());
+        clock.advance(frame_duration() - frame_duration() / 2);
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 2);
+        assert!(should_render());
+
+        set_on_demand(false);
+    }
+
+    #[test]
+    #[serial]
+    fn test_pause_resumes_from_paused_frame() {
+        init();
+        let clock = MockClock::new();
+        let mut controller = FrameController::new();
+        controller.last_frame_time = clock.now();
+        controller.last_render_time = clock.now();
+
+        clock.advance(frame_duration());
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 1);
+
+        set_paused(true);
+        for _ in 0..10 {
+            clock.advance(frame_duration());
+            controller.update_with_time(clock.now());
+        }
+        assert_eq!(controller.frame_count(), 1);
+        assert!(!should_render());
+
+        // No burst of catch-up frames
+        set_paused(false);
+        clock.advance(frame_duration());
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 2);
+        assert!(should_render());
+    }
+
+    #[test]
+    #[serial]
+    fn test_freeze_keeps_clock() {
+        init();
+        let clock = MockClock::new();
+        let mut controller = FrameController::new();
+        controller.last_frame_time = clock.now();
+        controller.last_render_time = clock.now();
+
+        set_frozen(true);
+        for _ in 0..3 {
+            clock.advance(frame_duration());
+            controller.update_with_time(clock.now());
+            assert!(should_update());
+            assert!(!should_render());
+        }
+        assert_eq!(controller.frame_count(), 3);
+
+        set_frozen(false);
+        clock.advance(frame_duration());
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 4);
+        assert!(should_render());
+    }
+
+    #[test]
+    #[serial]
+    fn test_progress() {
+        init();
+        let clock = MockClock::new();
+        let mut controller = FrameController::new();
+        controller.last_frame_time = clock.now();
+        controller.last_render_time = clock.now();
+
+        assert_eq!(controller.progress_at(clock.now()), 0.0);
+
+        // Time passing between ticks counts as well as the accumulator
+        clock.advance(frame_duration() / 4);
+        controller.update_with_time(clock.now());
+        clock.advance(frame_duration() / 4);
+        let progress = controller.progress_at(clock.now());
+        assert!((progress - 0.5).abs() < 0.001, "{}", progress);
+
+        // Never reaches the next frame before it's counted
+        clock.advance(frame_duration() * 2);
+        assert!(controller.progress_at(clock.now()) < 1.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_fps_change() {
+        init();
+        // Other modules' tests set the frame rate once and rely on it
+        let previous_fps = fps();
+        set_fps(60.0);
+        let clock = MockClock::new();
+        let mut controller = FrameController::new();
+        controller.last_frame_time = clock.now();
+        controller.last_render_time = clock.now();
+
+        // Lag behind by several frames then halve the frame rate
+        controller.accumulator = frame_duration() * 5;
+        set_fps(30.0);
+        controller.on_fps_changed();
+        assert_eq!(controller.accumulator, frame_duration());
+
+        // Only a single catch-up frame is rendered
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 1);
+
+        // Half of a 30fps frame should not render
+        clock.advance(frame_duration() / 2);
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 1);
+
+        clock.advance(frame_duration() - frame_duration() / 2);
+        controller.update_with_time(clock.now());
+        assert_eq!(controller.frame_count(), 2);
+
+        set_fps(0.0);
+        assert_eq!(fps(), MIN_FPS);
+        set_fps(previous_fps);
+    }
 }
@@ -0,0 +1,323 @@
+//! Marching squares iso-line extraction from 2D scalar fields for topo-map and
+//! metaball style sketches
+//!
+//! # Example
+//! ```rust,ignore
+//! let field = ScalarField::from_fn(wr.rect(), 128, 128, |p| {
+//!     noise.get([p.x * 0.01, p.y * 0.01])
+//! });
+//!
+//! for (_threshold, contours) in field.contour_levels(&[-0.5, 0.0, 0.5]) {
+//!     for contour in contours {
+//!         draw.polyline().points(contour.points);
+//!     }
+//! }
+//! ```
+
+use nannou::prelude::*;
+use std::fmt::Write;
+
+use crate::framework::prelude::*;
+
+/// A grid of samples spanning `bounds` where `values[row * cols + col]` is the
+/// sample at the `col`th point from the left and the `row`th point from the
+/// bottom
+#[derive(Clone, Debug)]
+pub struct ScalarField {
+    pub bounds: Rect,
+    pub cols: usize,
+    pub rows: usize,
+    pub values: Vec<f32>,
+}
+
+impl ScalarField {
+    pub fn new(
+        bounds: Rect,
+        cols: usize,
+        rows: usize,
+        values: Vec<f32>,
+    ) -> Self {
+        assert!(cols >= 2 && rows >= 2, "ScalarField requires a 2x2 grid");
+        assert_eq!(values.len(), cols * rows, "values must be cols * rows");
+        Self {
+            bounds,
+            cols,
+            rows,
+            values,
+        }
+    }
+
+    /// Sample `f` at `cols` x `rows` evenly spaced points within `bounds`
+    pub fn from_fn<F>(bounds: Rect, cols: usize, rows: usize, f: F) -> Self
+    where
+        F: Fn(Vec2) -> f32,
+    {
+        let mut values = Vec::with_capacity(cols * rows);
+        for row in 0..rows {
+            for col in 0..cols {
+                values.push(f(Self::position_in(bounds, cols, rows, col, row)));
+            }
+        }
+        Self::new(bounds, cols, rows, values)
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> f32 {
+        self.values[row * self.cols + col]
+    }
+
+    pub fn position(&self, col: usize, row: usize) -> Vec2 {
+        Self::position_in(self.bounds, self.cols, self.rows, col, row)
+    }
+
+    fn position_in(
+        bounds: Rect,
+        cols: usize,
+        rows: usize,
+        col: usize,
+        row: usize,
+    ) -> Vec2 {
+        vec2(
+            bounds.left() + bounds.w() * col as f32 / (cols - 1) as f32,
+            bounds.bottom() + bounds.h() * row as f32 / (rows - 1) as f32,
+        )
+    }
+
+    /// Extract all iso-lines at `threshold`. Values greater than or equal to
+    /// `threshold` are considered "inside".
+    pub fn contours(&self, threshold: f32) -> Vec<Contour> {
+        let mut segments: Vec<[EdgeKey; 2]> = vec![];
+
+        for row in 0..self.rows - 1 {
+            for col in 0..self.cols - 1 {
+                self.march_cell(col, row, threshold, &mut segments);
+            }
+        }
+
+        self.stitch(&segments, threshold)
+    }
+
+    /// Convenience for extracting contours at multiple thresholds, returned
+    /// as `(threshold, contours)` pairs in the order given
+    pub fn contour_levels(
+        &self,
+        thresholds: &[f32],
+    ) -> Vec<(f32, Vec<Contour>)> {
+        thresholds.iter().map(|&t| (t, self.contours(t))).collect()
+    }
+
+    fn march_cell(
+        &self,
+        col: usize,
+        row: usize,
+        threshold: f32,
+        segments: &mut Vec<[EdgeKey; 2]>,
+    ) {
+        let corners = [
+            self.get(col, row),
+            self.get(col + 1, row),
+            self.get(col + 1, row + 1),
+            self.get(col, row + 1),
+        ];
+
+        let case = corners
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, v)| acc | ((*v >= threshold) as u8) << i);
+
+        let bottom = EdgeKey::Horizontal(col, row);
+        let right = EdgeKey::Vertical(col + 1, row);
+        let top = EdgeKey::Horizontal(col, row + 1);
+        let left = EdgeKey::Vertical(col, row);
+
+        let center_inside = corners.iter().sum::<f32>() / 4.0 >= threshold;
+
+        let pairs: &[[EdgeKey; 2]] = match case {
+            1 | 14 => &[[left, bottom]],
+            2 | 13 => &[[bottom, right]],
+            3 | 12 => &[[left, right]],
+            4 | 11 => &[[right, top]],
+            6 | 9 => &[[bottom, top]],
+            7 | 8 => &[[left, top]],
+            5 if center_inside => &[[bottom, right], [top, left]],
+            5 => &[[left, bottom], [right, top]],
+            10 if center_inside => &[[left, bottom], [right, top]],
+            10 => &[[bottom, right], [top, left]],
+            _ => &[],
+        };
+
+        segments.extend_from_slice(pairs);
+    }
+
+    fn edge_point(&self, key: EdgeKey, threshold: f32) -> Vec2 {
+        let (a, b) = match key {
+            EdgeKey::Horizontal(col, row) => ((col, row), (col + 1, row)),
+            EdgeKey::Vertical(col, row) => ((col, row), (col, row + 1)),
+        };
+        let va = self.get(a.0, a.1);
+        let vb = self.get(b.0, b.1);
+        let t = ternary!(
+            (vb - va).abs() < f32::EPSILON,
+            0.5,
+            ((threshold - va) / (vb - va)).clamp(0.0, 1.0)
+        );
+        self.position(a.0, a.1).lerp(self.position(b.0, b.1), t)
+    }
+
+    fn stitch(
+        &self,
+        segments: &[[EdgeKey; 2]],
+        threshold: f32,
+    ) -> Vec<Contour> {
+        let mut by_edge: HashMap<EdgeKey, Vec<usize>> = HashMap::default();
+        for (i, [a, b]) in segments.iter().enumerate() {
+            by_edge.entry(*a).or_default().push(i);
+            by_edge.entry(*b).or_default().push(i);
+        }
+
+        let mut used = vec![false; segments.len()];
+        let mut contours = vec![];
+
+        // Finds an unused segment touching `key` and returns its other end
+        let mut next = |key: EdgeKey, used: &mut Vec<bool>| {
+            by_edge.get_mut(&key).and_then(|candidates| {
+                let i = *candidates.iter().find(|&&i| !used[i])?;
+                used[i] = true;
+                let [a, b] = segments[i];
+                Some(ternary!(a == key, b, a))
+            })
+        };
+
+        for i in 0..segments.len() {
+            if used[i] {
+                continue;
+            }
+            used[i] = true;
+
+            let [start, end] = segments[i];
+            let mut keys = std::collections::VecDeque::from([start, end]);
+
+            let mut tail = end;
+            while let Some(key) = next(tail, &mut used) {
+                keys.push_back(key);
+                tail = key;
+            }
+
+            let closed = keys.len() > 2 && keys.front() == keys.back();
+
+            if !closed {
+                let mut head = start;
+                while let Some(key) = next(head, &mut used) {
+                    keys.push_front(key);
+                    head = key;
+                }
+            }
+
+            let mut points: Vec<Vec2> = keys
+                .iter()
+                .map(|key| self.edge_point(*key, threshold))
+                .collect();
+
+            if closed {
+                points.pop();
+            }
+
+            contours.push(Contour { points, closed });
+        }
+
+        contours
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum EdgeKey {
+    /// Edge from (col, row) to (col + 1, row)
+    Horizontal(usize, usize),
+    /// Edge from (col, row) to (col, row + 1)
+    Vertical(usize, usize),
+}
+
+/// A single iso-line. Closed contours do not repeat their first point.
+#[derive(Clone, Debug, Default)]
+pub struct Contour {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+impl Contour {
+    /// Points suitable for `draw.polyline()`, repeating the first point at the
+    /// end for closed contours
+    pub fn polyline(&self) -> Vec<Vec2> {
+        let mut points = self.points.clone();
+        points.extend(self.points.first().filter(|_| self.closed));
+        points
+    }
+
+    /// An SVG path `d` attribute. Note that nannou's y-axis points up while
+    /// SVG's points down, so you'll likely want to flip the y coordinates
+    /// (e.g. with a `scale(1, -1)` transform) when exporting
+    pub fn to_svg_path(&self) -> String {
+        let mut d = String::new();
+        for (i, p) in self.points.iter().enumerate() {
+            let command = ternary!(i == 0, 'M', 'L');
+            let _ = write!(d, "{}{:.3},{:.3} ", command, p.x, p.y);
+        }
+        if self.closed {
+            d.push('Z');
+        }
+        d.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    fn circle_field(radius: f32) -> ScalarField {
+        ScalarField::from_fn(Rect::from_w_h(10.0, 10.0), 41, 41, |p| {
+            radius - p.length()
+        })
+    }
+
+    #[test]
+    fn test_circle_is_single_closed_contour() {
+        let contours = circle_field(3.0).contours(0.0);
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        for p in &contours[0].points {
+            assert_approx_eq!(p.length(), 3.0, 0.05);
+        }
+    }
+
+    #[test]
+    fn test_clipped_contour_is_open() {
+        let field =
+            ScalarField::from_fn(Rect::from_w_h(10.0, 10.0), 21, 21, |p| p.x);
+        let contours = field.contours(1.0);
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        assert_eq!(contours[0].points.len(), 21);
+        for p in &contours[0].points {
+            assert_approx_eq!(p.x, 1.0);
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        let levels = circle_field(4.0).contour_levels(&[0.0, 1.0, 2.0]);
+        assert_eq!(levels.len(), 3);
+        assert!(levels.iter().all(|(_, contours)| contours.len() == 1));
+    }
+
+    #[test]
+    fn test_svg_path() {
+        let contour = Contour {
+            points: vec![vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0)],
+            closed: true,
+        };
+        assert_eq!(
+            contour.to_svg_path(),
+            "M0.000,0.000 L1.000,0.000 L1.000,1.000 Z"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Delaunay triangulation and bounded Voronoi diagrams over 2D point sets
+//!
+//! # Example
+//! ```rust,ignore
+//! let points = (0..100).map(|_| random_point_in(wr.rect())).collect();
+//! let voronoi = Voronoi::new(points, wr.rect()).relax(3);
+//!
+//! for cell in &voronoi.cells {
+//!     draw.polygon().points(cell.iter().cloned());
+//! }
+//! ```
+
+use nannou::prelude::*;
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Debug, Default)]
+pub struct Triangulation {
+    pub points: Vec<Vec2>,
+    /// Counter-clockwise triangles as indexes into [`Self::points`]
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Triangulation {
+    /// Bowyer-Watson triangulation. O(n²) which is plenty fast for the few
+    /// thousand points a sketch typically deals with. Duplicate points are
+    /// effectively ignored.
+    pub fn new(points: Vec<Vec2>) -> Self {
+        if points.len() < 3 {
+            return Self {
+                points,
+                triangles: vec![],
+            };
+        }
+
+        let (min, max) = points.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let size = (max - min).max_element().max(1.0);
+        let center = (min + max) * 0.5;
+
+        // Super triangle vertices live past the end of the input points
+        let n = points.len();
+        let mut vertices = points.clone();
+        vertices.push(center + vec2(-20.0 * size, -size));
+        vertices.push(center + vec2(0.0, 20.0 * size));
+        vertices.push(center + vec2(20.0 * size, -size));
+
+        let mut triangles: Vec<Triangle> =
+            vec![Triangle::new(&vertices, [n, n + 1, n + 2])];
+
+        for i in 0..n {
+            let p = vertices[i];
+            let mut edges: Vec<[usize; 2]> = vec![];
+
+            triangles.retain(|t| {
+                if t.circumcircle_contains(p) {
+                    let [a, b, c] = t.indexes;
+                    edges.push([a, b]);
+                    edges.push([b, c]);
+                    edges.push([c, a]);
+                    false
+                } else {
+                    true
+                }
+            });
+
+            // Boundary of the polygonal hole = edges that are not shared
+            for (j, edge) in edges.iter().enumerate() {
+                let shared = edges.iter().enumerate().any(|(k, other)| {
+                    j != k && edge[0] == other[1] && edge[1] == other[0]
+                });
+                if !shared {
+                    triangles
+                        .push(Triangle::new(&vertices, [edge[0], edge[1], i]));
+                }
+            }
+        }
+
+        let triangles = triangles
+            .into_iter()
+            .map(|t| t.indexes)
+            .filter(|indexes| indexes.iter().all(|&i| i < n))
+            .map(|[a, b, c]| {
+                let cross = (vertices[b] - vertices[a])
+                    .perp_dot(vertices[c] - vertices[a]);
+                ternary!(cross < 0.0, [a, c, b], [a, b, c])
+            })
+            .collect();
+
+        Self { points, triangles }
+    }
+
+    pub fn triangle_points(&self, index: usize) -> [Vec2; 3] {
+        self.triangles[index].map(|i| self.points[i])
+    }
+
+    /// Unique edges as pairs of point indexes with the lower index first
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        let mut edges: Vec<[usize; 2]> = self
+            .triangles
+            .iter()
+            .flat_map(|[a, b, c]| [[*a, *b], [*b, *c], [*c, *a]])
+            .map(|[a, b]| ternary!(a < b, [a, b], [b, a]))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// For each point, the indexes of the points it shares an edge with
+    pub fn neighbors(&self) -> Vec<Vec<usize>> {
+        let mut neighbors = vec![vec![]; self.points.len()];
+        for [a, b] in self.edges() {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+        neighbors
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Triangle {
+    indexes: [usize; 3],
+    center: Vec2,
+    radius_squared: f32,
+}
+
+impl Triangle {
+    fn new(vertices: &[Vec2], indexes: [usize; 3]) -> Self {
+        let [a, b, c] = indexes.map(|i| vertices[i]);
+        let center = circumcenter(a, b, c).unwrap_or((a + b + c) / 3.0);
+        Self {
+            indexes,
+            center,
+            radius_squared: center.distance_squared(a),
+        }
+    }
+
+    fn circumcircle_contains(&self, p: Vec2) -> bool {
+        self.center.distance_squared(p) <= self.radius_squared
+    }
+}
+
+/// Returns `None` for degenerate (collinear) triangles
+pub fn circumcenter(a: Vec2, b: Vec2, c: Vec2) -> Option<Vec2> {
+    let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+    if d.abs() < f32::EPSILON {
+        return None;
+    }
+    let a2 = a.length_squared();
+    let b2 = b.length_squared();
+    let c2 = c.length_squared();
+    Some(vec2(
+        (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+        (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d,
+    ))
+}
+
+/// Voronoi diagram clipped to `bounds`. Each cell is a convex,
+/// counter-clockwise polygon and `cells[i]` belongs to `sites[i]`.
+#[derive(Clone, Debug)]
+pub struct Voronoi {
+    pub sites: Vec<Vec2>,
+    pub cells: Vec<Vec<Vec2>>,
+    pub bounds: Rect,
+    triangulation: Triangulation,
+}
+
+impl Voronoi {
+    pub fn new(sites: Vec<Vec2>, bounds: Rect) -> Self {
+        let triangulation = Triangulation::new(sites.clone());
+        let cells = Self::compute_cells(&sites, &triangulation, bounds);
+        Self {
+            sites,
+            cells,
+            bounds,
+            triangulation,
+        }
+    }
+
+    /// Apply `iterations` of Lloyd relaxation, moving each site to the
+    /// centroid of its cell which results in more evenly sized cells
+    pub fn relax(mut self, iterations: usize) -> Self {
+        for _ in 0..iterations {
+            self.lloyd_step();
+        }
+        self
+    }
+
+    pub fn lloyd_step(&mut self) {
+        let sites = self
+            .cells
+            .iter()
+            .zip(&self.sites)
+            .map(|(cell, site)| polygon_centroid(cell).unwrap_or(*site))
+            .collect();
+        *self = Self::new(sites, self.bounds);
+    }
+
+    /// The dual Delaunay triangulation of [`Self::sites`]
+    pub fn triangulation(&self) -> &Triangulation {
+        &self.triangulation
+    }
+
+    fn compute_cells(
+        sites: &[Vec2],
+        triangulation: &Triangulation,
+        bounds: Rect,
+    ) -> Vec<Vec<Vec2>> {
+        let bounds_polygon = vec![
+            bounds.bottom_left(),
+            bounds.bottom_right(),
+            bounds.top_right(),
+            bounds.top_left(),
+        ];
+
+        // With fewer than 3 sites there is no triangulation so fall back to
+        // clipping against every other site
+        let neighbors = if triangulation.triangles.is_empty() {
+            (0..sites.len())
+                .map(|i| (0..sites.len()).filter(|&j| j != i).collect())
+                .collect()
+        } else {
+            triangulation.neighbors()
+        };
+
+        sites
+            .iter()
+            .enumerate()
+            .map(|(i, site)| {
+                neighbors[i]
+                    .iter()
+                    .fold(bounds_polygon.clone(), |cell, &j| {
+                        clip_to_bisector(&cell, *site, sites[j])
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Sutherland-Hodgman clip of a convex polygon against the half-plane of
+/// points closer to `site` than to `other`
+fn clip_to_bisector(polygon: &[Vec2], site: Vec2, other: Vec2) -> Vec<Vec2> {
+    let midpoint = (site + other) * 0.5;
+    let normal = other - site;
+    let side = |p: Vec2| (p - midpoint).dot(normal);
+
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+    for (i, &current) in polygon.iter().enumerate() {
+        let next = polygon[(i + 1) % polygon.len()];
+        let current_side = side(current);
+        let next_side = side(next);
+
+        if current_side <= 0.0 {
+            result.push(current);
+        }
+
+        if (current_side <= 0.0) != (next_side <= 0.0) {
+            let t = current_side / (current_side - next_side);
+            result.push(current.lerp(next, t));
+        }
+    }
+    result
+}
+
+/// Signed area; positive for counter-clockwise winding
+pub fn polygon_area(polygon: &[Vec2]) -> f32 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        * 0.5
+}
+
+pub fn polygon_centroid(polygon: &[Vec2]) -> Option<Vec2> {
+    let area = polygon_area(polygon);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+    let sum = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .fold(Vec2::ZERO, |acc, (a, b)| acc + (*a + *b) * a.perp_dot(*b));
+    Some(sum / (6.0 * area))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_square_triangulates_into_two() {
+        let points = vec![
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.1),
+        ];
+        let triangulation = Triangulation::new(points);
+        assert_eq!(triangulation.triangles.len(), 2);
+        assert_eq!(triangulation.edges().len(), 5);
+    }
+
+    #[test]
+    fn test_triangles_are_ccw() {
+        let points = (0..20)
+            .map(|i| {
+                let a = i as f32 * 2.4;
+                vec2(a.cos(), a.sin()) * (i as f32 + 1.0)
+            })
+            .collect();
+        let triangulation = Triangulation::new(points);
+        for i in 0..triangulation.triangles.len() {
+            let [a, b, c] = triangulation.triangle_points(i);
+            assert!((b - a).perp_dot(c - a) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_voronoi_cells_cover_bounds() {
+        let bounds = Rect::from_w_h(100.0, 100.0);
+        let sites = vec![
+            vec2(-25.0, -25.0),
+            vec2(25.0, -25.0),
+            vec2(25.0, 25.0),
+            vec2(-25.0, 25.0),
+            vec2(3.0, 1.0),
+        ];
+        let voronoi = Voronoi::new(sites, bounds);
+        let total: f32 = voronoi.cells.iter().map(|c| polygon_area(c)).sum();
+        assert_approx_eq!(total, 10_000.0, 0.5);
+    }
+
+    #[test]
+    fn test_lloyd_relaxation_centers_single_site() {
+        let bounds = Rect::from_w_h(10.0, 10.0);
+        let voronoi = Voronoi::new(vec![vec2(3.0, -2.0)], bounds).relax(1);
+        assert_approx_eq!(voronoi.sites[0].x, 0.0);
+        assert_approx_eq!(voronoi.sites[0].y, 0.0);
+    }
+}
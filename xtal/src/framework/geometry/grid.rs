@@ -0,0 +1,292 @@
+//! Square-celled grid layout with responsive resizing, cell queries, and per
+//! cell stable randomness
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let grid = Grid::new(wr.w(), wr.h(), 32).margin(20.0);
+//!
+//! // update
+//! self.grid.update(&ctx.window_rect());
+//!
+//! // view
+//! for cell in self.grid.cells() {
+//!     let size = cell.rng().gen_range(0.2..1.0) * cell.size;
+//!     draw.rect().xy(cell.center).w_h(size, size);
+//! }
+//! ```
+
+use nannou::prelude::*;
+use nannou::rand::SeedableRng;
+use nannou::rand::rngs::StdRng;
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub col: usize,
+    pub row: usize,
+    /// Position within [`Grid::cells`] iteration order
+    pub index: usize,
+    pub center: Vec2,
+    pub size: f32,
+    seed: u64,
+}
+
+impl Cell {
+    pub fn rect(&self) -> Rect {
+        Rect::from_xy_wh(self.center, Vec2::splat(self.size))
+    }
+
+    /// A random number generator seeded from the grid seed and this cell's
+    /// column and row. It yields the same sequence every time it's called so
+    /// per-cell randomness stays put across frames and window resizes (as long
+    /// as the cell still exists)
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed)
+    }
+}
+
+/// Cells are always square. Their size is derived from the smaller of the
+/// available width and height divided by `divisions`, which keeps the grid's
+/// proportions intact as the window's aspect ratio changes. The grid is
+/// centered within the available area.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    pub cols: usize,
+    pub rows: usize,
+    pub cell_size: f32,
+    divisions: usize,
+    margin: f32,
+    seed: u64,
+    w: f32,
+    h: f32,
+    /// Center of the top-left cell
+    start: Vec2,
+}
+
+impl Grid {
+    pub fn new(w: f32, h: f32, divisions: usize) -> Self {
+        let mut grid = Self {
+            cols: 0,
+            rows: 0,
+            cell_size: 0.0,
+            divisions: divisions.max(1),
+            margin: 0.0,
+            seed: 0,
+            w,
+            h,
+            start: Vec2::ZERO,
+        };
+        grid.layout();
+        grid
+    }
+
+    pub fn from_window_rect(wr: &WindowRect, divisions: usize) -> Self {
+        Self::new(wr.w(), wr.h(), divisions)
+    }
+
+    /// Space left empty on every side of the grid
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin.max(0.0);
+        self.layout();
+        self
+    }
+
+    /// Seed used to derive each [`Cell::rng`]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn divisions(&self) -> usize {
+        self.divisions
+    }
+
+    pub fn set_divisions(&mut self, divisions: usize) {
+        self.divisions = divisions.max(1);
+        self.layout();
+    }
+
+    pub fn resize(&mut self, w: f32, h: f32) {
+        self.w = w;
+        self.h = h;
+        self.layout();
+    }
+
+    /// Resizes the grid if the window has changed. Does not call
+    /// [`WindowRect::mark_unchanged`] so other window dependent state can
+    /// still react. Returns true if the grid was resized.
+    pub fn update(&mut self, wr: &WindowRect) -> bool {
+        if wr.changed() {
+            self.resize(wr.w(), wr.h());
+            return true;
+        }
+        false
+    }
+
+    fn layout(&mut self) {
+        let w = (self.w - self.margin * 2.0).max(0.0);
+        let h = (self.h - self.margin * 2.0).max(0.0);
+
+        self.cell_size = (w.min(h) / self.divisions as f32).floor().max(1.0);
+        self.cols = (w / self.cell_size).floor() as usize;
+        self.rows = (h / self.cell_size).floor() as usize;
+
+        let grid_w = self.cols as f32 * self.cell_size;
+        let grid_h = self.rows as f32 * self.cell_size;
+        let half = self.cell_size / 2.0;
+
+        self.start = vec2(-grid_w / 2.0 + half, grid_h / 2.0 - half);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cols * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The area covered by cells (excluding margins and leftover space)
+    pub fn bounds(&self) -> Rect {
+        Rect::from_w_h(
+            self.cols as f32 * self.cell_size,
+            self.rows as f32 * self.cell_size,
+        )
+    }
+
+    /// `col` counts from the left, `row` from the top
+    pub fn cell(&self, col: usize, row: usize) -> Option<Cell> {
+        if col >= self.cols || row >= self.rows {
+            return None;
+        }
+
+        Some(Cell {
+            col,
+            row,
+            index: col * self.rows + row,
+            center: self.start
+                + vec2(col as f32, -(row as f32)) * self.cell_size,
+            size: self.cell_size,
+            seed: cell_seed(self.seed, col, row),
+        })
+    }
+
+    /// Iterates column by column, top to bottom (the same order as the legacy
+    /// `create_grid` helper)
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.cols).flat_map(move |col| {
+            (0..self.rows).filter_map(move |row| self.cell(col, row))
+        })
+    }
+
+    /// Cell centers, optionally mapped through `transform_xy`
+    pub fn points<F>(&self, transform_xy: F) -> Vec<Vec2>
+    where
+        F: Fn(f32, f32) -> Vec2,
+    {
+        self.cells()
+            .map(|cell| transform_xy(cell.center.x, cell.center.y))
+            .collect()
+    }
+
+    /// The cell containing `point`, if any
+    pub fn cell_at(&self, point: Vec2) -> Option<Cell> {
+        let (col, row) = self.col_row(point);
+        if col < 0.0 || row < 0.0 {
+            return None;
+        }
+        self.cell(col as usize, row as usize)
+    }
+
+    /// The cell closest to `point`, clamping points outside of the grid to
+    /// its edges. Only `None` for an empty grid
+    pub fn nearest(&self, point: Vec2) -> Option<Cell> {
+        if self.is_empty() {
+            return None;
+        }
+        let (col, row) = self.col_row(point);
+        self.cell(
+            (col.max(0.0) as usize).min(self.cols - 1),
+            (row.max(0.0) as usize).min(self.rows - 1),
+        )
+    }
+
+    fn col_row(&self, point: Vec2) -> (f32, f32) {
+        let half = self.cell_size / 2.0;
+        let top_left = self.start + vec2(-half, half);
+        (
+            ((point.x - top_left.x) / self.cell_size).floor(),
+            ((top_left.y - point.y) / self.cell_size).floor(),
+        )
+    }
+}
+
+/// Legacy helper returning cell centers and the cell size
+pub fn create_grid<F>(
+    w: f32,
+    h: f32,
+    divisions: usize,
+    transform_xy: F,
+) -> (Vec<Vec2>, f32)
+where
+    F: Fn(f32, f32) -> Vec2,
+{
+    let grid = Grid::new(w, h, divisions);
+    (grid.points(transform_xy), grid.cell_size)
+}
+
+fn cell_seed(seed: u64, col: usize, row: usize) -> u64 {
+    // SplitMix64 finalizer to spread neighboring cells far apart
+    let mut z = seed
+        ^ (col as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (row as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::Rng;
+
+    #[test]
+    fn test_layout() {
+        let grid = Grid::new(100.0, 50.0, 5);
+        assert_eq!(grid.cell_size, 10.0);
+        assert_eq!((grid.cols, grid.rows), (10, 5));
+        assert_eq!(grid.cell(0, 0).unwrap().center, vec2(-45.0, 20.0));
+        assert_eq!(grid.cells().count(), 50);
+        assert!(grid.cell(10, 0).is_none());
+    }
+
+    #[test]
+    fn test_margin() {
+        let grid = Grid::new(120.0, 70.0, 5).margin(10.0);
+        assert_eq!((grid.cols, grid.rows), (10, 5));
+    }
+
+    #[test]
+    fn test_lookups() {
+        let grid = Grid::new(100.0, 100.0, 10);
+        let cell = grid.cell_at(vec2(-41.0, 49.0)).unwrap();
+        assert_eq!((cell.col, cell.row), (0, 0));
+        assert!(grid.cell_at(vec2(500.0, 0.0)).is_none());
+        let nearest = grid.nearest(vec2(500.0, -500.0)).unwrap();
+        assert_eq!((nearest.col, nearest.row), (9, 9));
+    }
+
+    #[test]
+    fn test_stable_rng() {
+        let a = Grid::new(100.0, 100.0, 10).seed(7);
+        let mut b = a.clone();
+        b.resize(300.0, 100.0);
+        let x: f32 = a.cell(2, 3).unwrap().rng().gen_range(0.0..1.0);
+        let y: f32 = b.cell(2, 3).unwrap().rng().gen_range(0.0..1.0);
+        let z: f32 = a.cell(3, 2).unwrap().rng().gen_range(0.0..1.0);
+        assert_eq!(x, y);
+        assert_ne!(x, z);
+    }
+}
@@ -0,0 +1,13 @@
+//! Computational geometry helpers
+
+pub mod contours;
+pub mod delaunay;
+pub mod grid;
+pub mod sdf;
+pub mod spatial;
+
+pub use contours::*;
+pub use delaunay::*;
+pub use grid::*;
+pub use sdf::*;
+pub use spatial::*;
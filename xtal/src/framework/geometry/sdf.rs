@@ -0,0 +1,203 @@
+//! 2D signed distance functions shared between the CPU and GPU. Every function
+//! here has an identically named and behaving counterpart in `sdf.wgsl` so
+//! CPU-side queries (collision, sampling, placement) agree with what a shader
+//! renders. Any [`crate::prelude::gpu::GpuState`] shader can pull in the WGSL
+//! version with a single comment line:
+//!
+//! ```wgsl
+//! // #include xtal::sdf
+//! ```
+//!
+//! Distances are negative inside a shape and positive outside.
+
+use nannou::prelude::*;
+
+/// The WGSL counterpart of this module
+pub const SDF_WGSL: &str = include_str!("sdf.wgsl");
+
+/// Maximum number of vertices the WGSL `sd_polygon` supports
+pub const SDF_POLYGON_MAX: usize = 16;
+
+pub fn sd_circle(p: Vec2, r: f32) -> f32 {
+    p.length() - r
+}
+
+/// `b` is the half size (extents) of the box
+pub fn sd_box(p: Vec2, b: Vec2) -> f32 {
+    let d = p.abs() - b;
+    d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.0)
+}
+
+pub fn sd_rounded_box(p: Vec2, b: Vec2, r: f32) -> f32 {
+    sd_box(p, b - Vec2::splat(r)) - r
+}
+
+pub fn sd_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = (pa.dot(ba) / ba.dot(ba)).clamp(0.0, 1.0);
+    (pa - ba * h).length()
+}
+
+/// Works with any simple (non self-intersecting) polygon regardless of
+/// winding. Unlike the WGSL version there is no vertex limit.
+pub fn sd_polygon(p: Vec2, vertices: &[Vec2]) -> f32 {
+    let Some(first) = vertices.first() else {
+        return f32::MAX;
+    };
+
+    let mut d = (p - *first).length_squared();
+    let mut s = 1.0;
+    let mut j = vertices.len() - 1;
+
+    for i in 0..vertices.len() {
+        let e = vertices[j] - vertices[i];
+        let w = p - vertices[i];
+        let b = w - e * (w.dot(e) / e.dot(e)).clamp(0.0, 1.0);
+        d = d.min(b.length_squared());
+        let c1 = p.y >= vertices[i].y;
+        let c2 = p.y < vertices[j].y;
+        let c3 = e.x * w.y > e.y * w.x;
+        if (c1 && c2 && c3) || (!c1 && !c2 && !c3) {
+            s = -s;
+        }
+        j = i;
+    }
+
+    s * d.sqrt()
+}
+
+pub fn op_union(a: f32, b: f32) -> f32 {
+    a.min(b)
+}
+
+/// `a` with `b` removed
+pub fn op_subtract(a: f32, b: f32) -> f32 {
+    a.max(-b)
+}
+
+pub fn op_intersect(a: f32, b: f32) -> f32 {
+    a.max(b)
+}
+
+pub fn op_smooth_union(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    mix(b, a, h) - k * h * (1.0 - h)
+}
+
+pub fn op_smooth_subtract(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 - 0.5 * (a + b) / k).clamp(0.0, 1.0);
+    mix(a, -b, h) + k * h * (1.0 - h)
+}
+
+pub fn op_smooth_intersect(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 - 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    mix(b, a, h) + k * h * (1.0 - h)
+}
+
+pub fn op_round(d: f32, r: f32) -> f32 {
+    d - r
+}
+
+pub fn op_annular(d: f32, r: f32) -> f32 {
+    d.abs() - r
+}
+
+/// Approximates the gradient of `f` at `p` with central differences. Handy
+/// for pushing particles out of a shape or orienting things along a surface
+pub fn sdf_normal<F>(f: F, p: Vec2) -> Vec2
+where
+    F: Fn(Vec2) -> f32,
+{
+    let e = 0.001;
+    vec2(
+        f(p + vec2(e, 0.0)) - f(p - vec2(e, 0.0)),
+        f(p + vec2(0.0, e)) - f(p - vec2(0.0, e)),
+    )
+    .normalize_or_zero()
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_primitives() {
+        assert_approx_eq!(sd_circle(vec2(3.0, 4.0), 1.0), 4.0);
+        assert_approx_eq!(sd_box(vec2(0.0, 0.0), vec2(1.0, 2.0)), -1.0);
+        assert_approx_eq!(sd_box(vec2(4.0, 6.0), vec2(1.0, 2.0)), 5.0);
+        assert_approx_eq!(
+            sd_segment(vec2(0.5, 1.0), vec2(0.0, 0.0), vec2(1.0, 0.0)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_polygon_matches_box() {
+        let square = [
+            vec2(-1.0, -1.0),
+            vec2(1.0, -1.0),
+            vec2(1.0, 1.0),
+            vec2(-1.0, 1.0),
+        ];
+        for p in [vec2(0.0, 0.0), vec2(0.5, -0.25), vec2(3.0, 2.0)] {
+            assert_approx_eq!(sd_polygon(p, &square), sd_box(p, Vec2::ONE));
+        }
+    }
+
+    #[test]
+    fn test_smooth_union_is_bounded_by_union() {
+        let a = 0.3;
+        let b = 0.4;
+        assert!(op_smooth_union(a, b, 0.5) <= op_union(a, b));
+        assert_approx_eq!(op_smooth_union(a, 10.0, 0.5), a);
+    }
+
+    #[test]
+    fn test_normal() {
+        let n = sdf_normal(|p| sd_circle(p, 1.0), vec2(2.0, 0.0));
+        assert_approx_eq!(n.x, 1.0);
+        assert_approx_eq!(n.y, 0.0);
+    }
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        let module = naga::front::wgsl::parse_str(SDF_WGSL).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_wgsl_mirrors_rust() {
+        for name in [
+            "sd_circle",
+            "sd_box",
+            "sd_rounded_box",
+            "sd_segment",
+            "sd_polygon",
+            "op_union",
+            "op_subtract",
+            "op_intersect",
+            "op_smooth_union",
+            "op_smooth_subtract",
+            "op_smooth_intersect",
+            "op_round",
+            "op_annular",
+        ] {
+            assert!(
+                SDF_WGSL.contains(&format!("fn {}(", name)),
+                "sdf.wgsl is missing {}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// -----------------------------------------------------------------------------
+//  XTAL SDF
+//  2D signed distance functions. Mirrors xtal::geometry::sdf - keep in sync!
+//  Include in any GpuState shader with:
+//      // #include xtal::sdf
+// -----------------------------------------------------------------------------
+
+const SDF_POLYGON_MAX: u32 = 16u;
+
+fn sd_circle(p: vec2f, r: f32) -> f32 {
+    return length(p) - r;
+}
+
+// `b` is the half size (extents) of the box
+fn sd_box(p: vec2f, b: vec2f) -> f32 {
+    let d = abs(p) - b;
+    return length(max(d, vec2f(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+fn sd_rounded_box(p: vec2f, b: vec2f, r: f32) -> f32 {
+    return sd_box(p, b - vec2f(r)) - r;
+}
+
+fn sd_segment(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+// Only the first `n` (<= SDF_POLYGON_MAX) vertices of `v` are used
+fn sd_polygon(p: vec2f, v: array<vec2f, SDF_POLYGON_MAX>, n: u32) -> f32 {
+    var vertices = v;
+    var d = dot(p - vertices[0], p - vertices[0]);
+    var s = 1.0;
+    var j = n - 1u;
+    for (var i = 0u; i < n; i++) {
+        let e = vertices[j] - vertices[i];
+        let w = p - vertices[i];
+        let b = w - e * clamp(dot(w, e) / dot(e, e), 0.0, 1.0);
+        d = min(d, dot(b, b));
+        let c1 = p.y >= vertices[i].y;
+        let c2 = p.y < vertices[j].y;
+        let c3 = e.x * w.y > e.y * w.x;
+        if ((c1 && c2 && c3) || (!c1 && !c2 && !c3)) {
+            s = -s;
+        }
+        j = i;
+    }
+    return s * sqrt(d);
+}
+
+fn op_union(a: f32, b: f32) -> f32 {
+    return min(a, b);
+}
+
+// `a` with `b` removed
+fn op_subtract(a: f32, b: f32) -> f32 {
+    return max(a, -b);
+}
+
+fn op_intersect(a: f32, b: f32) -> f32 {
+    return max(a, b);
+}
+
+fn op_smooth_union(a: f32, b: f32, k: f32) -> f32 {
+    let h = clamp(0.5 + 0.5 * (b - a) / k, 0.0, 1.0);
+    return mix(b, a, h) - k * h * (1.0 - h);
+}
+
+fn op_smooth_subtract(a: f32, b: f32, k: f32) -> f32 {
+    let h = clamp(0.5 - 0.5 * (a + b) / k, 0.0, 1.0);
+    return mix(a, -b, h) + k * h * (1.0 - h);
+}
+
+fn op_smooth_intersect(a: f32, b: f32, k: f32) -> f32 {
+    let h = clamp(0.5 - 0.5 * (b - a) / k, 0.0, 1.0);
+    return mix(b, a, h) + k * h * (1.0 - h);
+}
+
+fn op_round(d: f32, r: f32) -> f32 {
+    return d - r;
+}
+
+fn op_annular(d: f32, r: f32) -> f32 {
+    return abs(d) - r;
+}
@@ -0,0 +1,429 @@
+//! Spatial indexes for neighbor queries over many points, e.g. flocking or
+//! particle interactions, without comparing every pair of points.
+//!
+//! [`SpatialHash`] buckets points into a uniform grid and is the better choice
+//! when points are spread fairly evenly and queries share a radius (use that
+//! radius as the cell size). [`QuadTree`] adapts to clustered points and
+//! queries of varying size. Both are rebuilt rather than updated as points
+//! move, which for a few thousand points is cheaper than it sounds. Query
+//! results are indexes into the points as they were inserted.
+//!
+//! # Example
+//! ```rust,ignore
+//! // update
+//! let positions: Vec<Vec2> = self.boids.iter().map(|b| b.position).collect();
+//! self.index.rebuild(&positions);
+//!
+//! let neighbors = self.index.par_neighbors(&positions, VISION);
+//! for (boid, neighbors) in self.boids.iter_mut().zip(neighbors) {
+//!     boid.flock(neighbors.iter().map(|i| positions[*i]));
+//! }
+//! ```
+
+use nannou::prelude::*;
+use rayon::prelude::*;
+
+use crate::framework::prelude::*;
+
+/// Beyond this depth quad tree nodes hold any number of points. Keeps
+/// coincident points from splitting forever
+const MAX_DEPTH: usize = 16;
+
+//------------------------------------------------------------------------------
+// Spatial Hash
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    points: Vec<Vec2>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            points: vec![],
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn from_points(cell_size: f32, points: &[Vec2]) -> Self {
+        let mut hash = Self::new(cell_size);
+        hash.rebuild(points);
+        hash
+    }
+
+    /// Replaces every point, reusing allocations
+    pub fn rebuild(&mut self, points: &[Vec2]) {
+        self.clear();
+        for point in points {
+            self.insert(*point);
+        }
+    }
+
+    /// Adds a point and returns its index
+    pub fn insert(&mut self, point: Vec2) -> usize {
+        let index = self.points.len();
+        self.points.push(point);
+        self.cells.entry(self.key(point)).or_default().push(index);
+        index
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Indexes of the points within `radius` of `point`, including a point
+    /// at `point` itself
+    pub fn neighbors(
+        &self,
+        point: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let (min_x, min_y) = self.key(point - radius);
+        let (max_x, max_y) = self.key(point + radius);
+        let radius_squared = radius * radius;
+
+        (min_x..=max_x)
+            .flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .copied()
+            .filter(move |i| {
+                self.points[*i].distance_squared(point) <= radius_squared
+            })
+    }
+
+    /// [`Self::neighbors`] of every point in `queries`, in parallel
+    pub fn par_neighbors(
+        &self,
+        queries: &[Vec2],
+        radius: f32,
+    ) -> Vec<Vec<usize>> {
+        queries
+            .par_iter()
+            .map(|point| self.neighbors(*point, radius).collect())
+            .collect()
+    }
+
+    fn key(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+//------------------------------------------------------------------------------
+// Quad Tree
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Rect,
+    items: Vec<usize>,
+    children: Option<Box<[Node; 4]>>,
+}
+
+impl Node {
+    fn new(bounds: Rect) -> Self {
+        Self {
+            bounds,
+            items: vec![],
+            children: None,
+        }
+    }
+
+    fn insert(
+        &mut self,
+        index: usize,
+        points: &[Vec2],
+        capacity: usize,
+        depth: usize,
+    ) {
+        if let Some(children) = &mut self.children {
+            let child = quadrant(self.bounds, points[index]);
+            children[child].insert(index, points, capacity, depth + 1);
+            return;
+        }
+
+        self.items.push(index);
+
+        if self.items.len() > capacity && depth < MAX_DEPTH {
+            let (l, r, b, t) = self.bounds.l_r_b_t();
+            let (x, y) = (self.bounds.x(), self.bounds.y());
+            self.children = Some(Box::new([
+                Node::new(Rect::from_corners(vec2(l, b), vec2(x, y))),
+                Node::new(Rect::from_corners(vec2(x, b), vec2(r, y))),
+                Node::new(Rect::from_corners(vec2(l, y), vec2(x, t))),
+                Node::new(Rect::from_corners(vec2(x, y), vec2(r, t))),
+            ]));
+            for index in std::mem::take(&mut self.items) {
+                self.insert(index, points, capacity, depth);
+            }
+        }
+    }
+}
+
+/// Matches the child order in [`Node::insert`]. Points on a center line go
+/// to the right or top
+fn quadrant(bounds: Rect, point: Vec2) -> usize {
+    let right = point.x >= bounds.x();
+    let top = point.y >= bounds.y();
+    ternary!(top, 2, 0) + ternary!(right, 1, 0)
+}
+
+fn contains(rect: Rect, point: Vec2) -> bool {
+    point.x >= rect.left()
+        && point.x <= rect.right()
+        && point.y >= rect.bottom()
+        && point.y <= rect.top()
+}
+
+fn intersects(a: Rect, b: Rect) -> bool {
+    a.left() <= b.right()
+        && b.left() <= a.right()
+        && a.bottom() <= b.top()
+        && b.bottom() <= a.top()
+}
+
+/// Points outside of the tree's bounds are rejected, so size it to the
+/// window (or wherever the points can go)
+#[derive(Clone, Debug)]
+pub struct QuadTree {
+    capacity: usize,
+    points: Vec<Vec2>,
+    root: Node,
+}
+
+impl QuadTree {
+    /// `capacity` is how many points a node holds before splitting
+    pub fn new(bounds: Rect, capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            points: vec![],
+            root: Node::new(bounds),
+        }
+    }
+
+    pub fn from_points(bounds: Rect, capacity: usize, points: &[Vec2]) -> Self {
+        let mut tree = Self::new(bounds, capacity);
+        tree.rebuild(points);
+        tree
+    }
+
+    /// Replaces every point. Indexes still refer to `points` even if some of
+    /// them were out of bounds
+    pub fn rebuild(&mut self, points: &[Vec2]) {
+        self.clear();
+        for point in points {
+            self.insert(*point);
+        }
+    }
+
+    /// Adds a point and returns its index, or `None` if it's out of bounds.
+    /// Rejected points still take up an index
+    pub fn insert(&mut self, point: Vec2) -> Option<usize> {
+        let index = self.points.len();
+        self.points.push(point);
+        if !contains(self.root.bounds, point) {
+            return None;
+        }
+        self.root.insert(index, &self.points, self.capacity, 0);
+        Some(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.root = Node::new(self.root.bounds);
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.root.bounds
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Indexes of the points within `area`
+    pub fn query(&self, area: Rect) -> QuadTreeQuery<'_> {
+        QuadTreeQuery {
+            points: &self.points,
+            area,
+            stack: ternary!(
+                intersects(self.root.bounds, area),
+                vec![&self.root],
+                vec![]
+            ),
+            items: [].iter(),
+        }
+    }
+
+    /// Indexes of the points within `radius` of `point`, including a point
+    /// at `point` itself
+    pub fn neighbors(
+        &self,
+        point: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let radius_squared = radius * radius;
+        self.query(Rect::from_xy_wh(point, Vec2::splat(radius * 2.0)))
+            .filter(move |i| {
+                self.points[*i].distance_squared(point) <= radius_squared
+            })
+    }
+
+    /// [`Self::neighbors`] of every point in `queries`, in parallel
+    pub fn par_neighbors(
+        &self,
+        queries: &[Vec2],
+        radius: f32,
+    ) -> Vec<Vec<usize>> {
+        queries
+            .par_iter()
+            .map(|point| self.neighbors(*point, radius).collect())
+            .collect()
+    }
+}
+
+/// Iterator returned by [`QuadTree::query`]
+pub struct QuadTreeQuery<'a> {
+    points: &'a [Vec2],
+    area: Rect,
+    stack: Vec<&'a Node>,
+    items: std::slice::Iter<'a, usize>,
+}
+
+impl Iterator for QuadTreeQuery<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = self.items.next() {
+                if contains(self.area, self.points[*index]) {
+                    return Some(*index);
+                }
+                continue;
+            }
+
+            let node = self.stack.pop()?;
+            self.items = node.items.iter();
+            if let Some(children) = &node.children {
+                self.stack.extend(
+                    children
+                        .iter()
+                        .filter(|child| intersects(child.bounds, self.area)),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::{Rng, SeedableRng};
+
+    fn random_points(n: usize) -> Vec<Vec2> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..n)
+            .map(|_| {
+                vec2(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))
+            })
+            .collect()
+    }
+
+    fn brute_force(points: &[Vec2], point: Vec2, radius: f32) -> Vec<usize> {
+        (0..points.len())
+            .filter(|i| points[*i].distance(point) <= radius)
+            .collect()
+    }
+
+    fn sorted(iter: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut indexes: Vec<usize> = iter.collect();
+        indexes.sort();
+        indexes
+    }
+
+    #[test]
+    fn test_spatial_hash() {
+        let points = random_points(500);
+        let hash = SpatialHash::from_points(10.0, &points);
+
+        for point in points.iter().take(50) {
+            assert_eq!(
+                sorted(hash.neighbors(*point, 12.0)),
+                brute_force(&points, *point, 12.0)
+            );
+        }
+
+        let batch = hash.par_neighbors(&points[..10], 12.0);
+        for (point, neighbors) in points.iter().zip(batch) {
+            assert_eq!(
+                sorted(neighbors.into_iter()),
+                brute_force(&points, *point, 12.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_quad_tree() {
+        let points = random_points(500);
+        let bounds = Rect::from_w_h(200.0, 200.0);
+        let tree = QuadTree::from_points(bounds, 4, &points);
+
+        for point in points.iter().take(50) {
+            assert_eq!(
+                sorted(tree.neighbors(*point, 12.0)),
+                brute_force(&points, *point, 12.0)
+            );
+        }
+
+        let area = Rect::from_corners(vec2(-50.0, -20.0), vec2(10.0, 60.0));
+        assert_eq!(
+            sorted(tree.query(area)),
+            (0..points.len())
+                .filter(|i| contains(area, points[*i]))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_quad_tree_edge_cases() {
+        let bounds = Rect::from_w_h(10.0, 10.0);
+        let mut tree = QuadTree::new(bounds, 1);
+
+        assert_eq!(tree.insert(vec2(20.0, 0.0)), None);
+        // Coincident points stop splitting at MAX_DEPTH
+        for _ in 0..10 {
+            assert!(tree.insert(vec2(1.0, 1.0)).is_some());
+        }
+        assert_eq!(tree.insert(vec2(5.0, 5.0)), Some(11));
+
+        assert_eq!(tree.neighbors(vec2(1.0, 1.0), 0.5).count(), 10);
+        assert_eq!(sorted(tree.neighbors(vec2(5.0, 5.0), 0.5)), vec![11]);
+    }
+}
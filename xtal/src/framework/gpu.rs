@@ -11,12 +11,29 @@ use naga::valid::{Capabilities, ValidationFlags, Validator};
 use nannou::prelude::*;
 use nannou::wgpu;
 use notify::{Event, RecursiveMode, Watcher};
+use std::cell::RefCell;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use wgpu_types::SamplerBindingType;
 
+use super::camera::{self, Camera, CameraUniform};
+use super::color;
+pub use super::depth::DepthTexture;
+use super::geometry::sdf;
+pub use super::gpu_cache::GpuCache;
+use super::gpu_stats::{GpuUsage, UsageReport};
+pub use super::optical_flow::{OpticalFlow, VectorField};
+pub use super::physarum::{Physarum, PhysarumParams};
 use super::prelude::*;
+pub use super::reaction_diffusion::{
+    ReactionDiffusion, ReactionDiffusionParams, ReactionDiffusionPreset,
+};
+use super::stereo::{self, Eye, EyeUniform, StereoPass};
+pub use super::stereo::{StereoMode, StereoSettings, stereo_settings};
+pub use super::tonemap::Tonemap;
+use super::tonemap::TonemapPass;
 
 struct PipelineCreationState<'a> {
     device: &'a wgpu::Device,
@@ -30,6 +47,331 @@ struct PipelineCreationState<'a> {
     depth_stencil: Option<wgpu::DepthStencilState>,
 }
 
+/// Framework-provided WGSL snippets that can be pulled into any shader with a
+/// `// #include <name>` line
+const SHADER_INCLUDES: &[(&str, &str)] = &[
+    ("xtal::sdf", sdf::SDF_WGSL),
+    ("xtal::camera", camera::CAMERA_WGSL),
+    ("xtal::color", color::COLOR_WGSL),
+    ("xtal::eye", stereo::EYE_WGSL),
+];
+
+/// Expands `// #include <name>` lines with the matching [`SHADER_INCLUDES`]
+/// source. Unknown includes are left in place (they're just comments after
+/// all) and logged
+pub(crate) fn preprocess_shader(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            let Some(name) = line
+                .trim()
+                .strip_prefix("//")
+                .and_then(|rest| rest.trim().strip_prefix("#include"))
+                .map(str::trim)
+            else {
+                return line.to_string();
+            };
+
+            match SHADER_INCLUDES.iter().find(|(key, _)| *key == name) {
+                Some((_, source)) => source.to_string(),
+                None => {
+                    warn!("Unknown shader include: {}", name);
+                    line.to_string()
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sets `state` to `path` whenever the file's content changes
+pub(crate) fn watch_shader(
+    path: PathBuf,
+    state: Arc<Mutex<Option<PathBuf>>>,
+) -> notify::RecommendedWatcher {
+    let path_to_watch = path.clone();
+
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let event: Event = match res {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+
+        if event.kind
+            != notify::EventKind::Modify(notify::event::ModifyKind::Data(
+                notify::event::DataChange::Content,
+            ))
+        {
+            return;
+        }
+
+        trace!("Shader {:?} changed", path);
+        if let Ok(mut guard) = state.lock() {
+            *guard = Some(path.clone());
+        }
+    })
+    .expect("Failed to create watcher");
+
+    watcher
+        .watch(&path_to_watch, RecursiveMode::NonRecursive)
+        .expect("Failed to start watching shader file");
+
+    watcher
+}
+
+/// Parses and validates WGSL, logging any errors
+pub(crate) fn validate_shader(
+    shader_content: &str,
+) -> Result<naga::Module, ()> {
+    let module = match wgsl::parse_str(shader_content) {
+        Err(e) => {
+            error!("Failed to parse shader: {:?}", e);
+            return Err(());
+        }
+        Ok(m) => m,
+    };
+
+    let mut validator =
+        Validator::new(ValidationFlags::all(), Capabilities::empty());
+
+    if let Err(validation_error) = validator.validate(&module) {
+        error!("Shader validation failed:\n{:?}", validation_error);
+        return Err(());
+    }
+
+    Ok(module)
+}
+
+/// Width/height ratio of the letterboxed content area. Set by the runtime when
+/// letterbox mode is enabled and applied as a viewport in every render pass.
+/// The runtime paints the bars itself after `view`
+static LETTERBOX_ASPECT: Mutex<Option<f32>> = Mutex::new(None);
+
+pub(crate) fn set_letterbox_aspect(aspect: Option<f32>) {
+    *LETTERBOX_ASPECT.lock().unwrap() = aspect;
+}
+
+/// `[x, y, w, h]` of the letterboxed content area within a frame of
+/// `frame_size`, in physical pixels
+fn letterbox_viewport(frame_size: [u32; 2]) -> Option<[f32; 4]> {
+    let aspect = (*LETTERBOX_ASPECT.lock().unwrap())?;
+    let [fw, fh] = frame_size.map(|n| n as f32);
+    let (w, h) =
+        ternary!(fw / fh > aspect, (fh * aspect, fh), (fw, fw / aspect));
+    Some([
+        ((fw - w) / 2.0).floor(),
+        ((fh - h) / 2.0).floor(),
+        w.round(),
+        h.round(),
+    ])
+}
+
+/// Sample counts accepted by [`set_msaa_samples`]. 1 (off) and 4 are
+/// guaranteed by WebGPU; 2 and 8 depend on the adapter.
+pub const MSAA_SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// Nannou's default and the sample count of the main window. Draw-based
+/// rendering always uses the window's sample count.
+pub const DEFAULT_MSAA_SAMPLES: u32 = 4;
+
+/// Sample count every [`GpuState`] pipeline is built with. Set by the runtime
+/// from [`SketchConfig::msaa_samples`] and the runtime MSAA toggle
+static MSAA_SAMPLES: AtomicU32 = AtomicU32::new(DEFAULT_MSAA_SAMPLES);
+
+pub(crate) fn set_msaa_samples(samples: u32) {
+    let samples = if MSAA_SAMPLE_COUNTS.contains(&samples) {
+        samples
+    } else {
+        warn!(
+            "Unsupported MSAA sample count {}. Using {}",
+            samples, DEFAULT_MSAA_SAMPLES
+        );
+        DEFAULT_MSAA_SAMPLES
+    };
+    MSAA_SAMPLES.store(samples, Ordering::Release);
+}
+
+/// The sample count [`GpuState`] pipelines are currently built with
+pub fn msaa_samples() -> u32 {
+    MSAA_SAMPLES.load(Ordering::Acquire)
+}
+
+/// The entry after `samples` in [`MSAA_SAMPLE_COUNTS`], wrapping around
+pub(crate) fn next_msaa_samples(samples: u32) -> u32 {
+    let index = MSAA_SAMPLE_COUNTS
+        .iter()
+        .position(|n| *n == samples)
+        .map_or(0, |i| (i + 1) % MSAA_SAMPLE_COUNTS.len());
+    MSAA_SAMPLE_COUNTS[index]
+}
+
+/// What to draw once a render pass has been set up
+#[derive(Clone, Copy)]
+enum DrawCall {
+    /// The entire vertex buffer
+    Vertices,
+    /// `n` vertices without a vertex buffer
+    Procedural(u32),
+}
+
+struct PassTargets<'a> {
+    color: &'a wgpu::TextureViewHandle,
+    resolve: Option<&'a wgpu::TextureViewHandle>,
+    depth: Option<&'a wgpu::TextureViewHandle>,
+}
+
+/// How an [`Offscreen`] target ends up on the frame
+enum OffscreenOutput {
+    /// Copied as is (resampled to the frame's sample count)
+    Reshape(wgpu::TextureReshaper),
+    /// Exposed and tonemapped, see [`GpuState::hdr`]
+    Tonemap(TonemapPass),
+}
+
+/// A color target rendered into `msaa` (when multisampled) which resolves
+/// into `resolved` so it can be sampled
+struct RenderTarget {
+    msaa: Option<wgpu::TextureView>,
+    resolved: wgpu::TextureView,
+}
+
+impl RenderTarget {
+    fn new(device: &wgpu::Device, size: [u32; 2], sample_count: u32) -> Self {
+        let msaa = (sample_count > 1).then(|| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(Frame::TEXTURE_FORMAT)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                .sample_count(sample_count)
+                .build(device)
+                .view()
+                .build()
+        });
+
+        let resolved = wgpu::TextureBuilder::new()
+            .size(size)
+            .format(Frame::TEXTURE_FORMAT)
+            .dimension(wgpu::TextureDimension::D2)
+            .usage(
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+            .sample_count(1)
+            .build(device)
+            .view()
+            .build();
+
+        Self { msaa, resolved }
+    }
+
+    fn usage(
+        &self,
+        usage: GpuUsage,
+        size: [u32; 2],
+        sample_count: u32,
+    ) -> GpuUsage {
+        let usage = usage.texture(size, Frame::TEXTURE_FORMAT, 1);
+        match self.msaa {
+            Some(_) => usage.texture(size, Frame::TEXTURE_FORMAT, sample_count),
+            None => usage,
+        }
+    }
+
+    fn targets(
+        &self,
+    ) -> (&wgpu::TextureViewHandle, Option<&wgpu::TextureViewHandle>) {
+        match &self.msaa {
+            Some(msaa) => (&**msaa, Some(&*self.resolved)),
+            None => (&*self.resolved, None),
+        }
+    }
+}
+
+/// Per-eye targets used while the runtime's stereo mode is on. The pass
+/// composites both eyes into the [`Offscreen`]'s resolved texture
+struct StereoTargets {
+    left: RenderTarget,
+    right: RenderTarget,
+    pass: StereoPass,
+}
+
+/// Render target used when a [`GpuState`] is in HDR mode, its sample count
+/// differs from the window's, or stereo is on. The shader renders into
+/// `target` which is then copied onto the frame via `output`.
+struct Offscreen {
+    size: [u32; 2],
+    target: RenderTarget,
+    output: OffscreenOutput,
+    stereo: Option<StereoTargets>,
+}
+
+impl Offscreen {
+    fn new(
+        device: &wgpu::Device,
+        size: [u32; 2],
+        sample_count: u32,
+        dst_sample_count: u32,
+        hdr: bool,
+    ) -> Self {
+        let target = RenderTarget::new(device, size, sample_count);
+
+        let output = if hdr {
+            OffscreenOutput::Tonemap(TonemapPass::new(
+                device,
+                &target.resolved,
+                dst_sample_count,
+            ))
+        } else {
+            OffscreenOutput::Reshape(wgpu::TextureReshaper::new(
+                device,
+                &target.resolved,
+                1,
+                wgpu::TextureSampleType::Float { filterable: true },
+                dst_sample_count,
+                Frame::TEXTURE_FORMAT,
+            ))
+        };
+
+        Self {
+            size,
+            target,
+            output,
+            stereo: None,
+        }
+    }
+
+    /// Adds the targets and output passes to `usage`
+    fn usage(&self, usage: GpuUsage, sample_count: u32) -> GpuUsage {
+        let mut usage = self
+            .target
+            .usage(usage, self.size, sample_count)
+            .pipelines(1);
+        if let Some(stereo) = &self.stereo {
+            usage = stereo.left.usage(usage, self.size, sample_count);
+            usage = stereo.right.usage(usage, self.size, sample_count);
+            usage = usage.pipelines(1);
+        }
+        usage
+    }
+
+    /// (Re)builds the per-eye targets when `mode` changes
+    fn prepare_stereo(
+        &mut self,
+        device: &wgpu::Device,
+        sample_count: u32,
+        mode: StereoMode,
+    ) {
+        if self.stereo.as_ref().is_none_or(|s| s.pass.mode() != mode) {
+            let left = RenderTarget::new(device, self.size, sample_count);
+            let right = RenderTarget::new(device, self.size, sample_count);
+            let pass =
+                StereoPass::new(device, &left.resolved, &right.resolved, mode);
+            self.stereo = Some(StereoTargets { left, right, pass });
+        }
+    }
+}
+
 struct Textures {
     count: u32,
     bind_group_layout: wgpu::BindGroupLayout,
@@ -48,7 +390,11 @@ pub struct GpuState<V: Pod + Zeroable> {
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: Option<wgpu::Buffer>,
     params_buffer: wgpu::Buffer,
-    params_bind_group: wgpu::BindGroup,
+    /// One per [`Eye`], each pairing the params with that eye's uniform and
+    /// the camera
+    params_bind_groups: [wgpu::BindGroup; 3],
+    eye_buffers: [wgpu::Buffer; 3],
+    camera_buffer: wgpu::Buffer,
     n_vertices: u32,
     depth_texture: Option<wgpu::TextureView>,
     depth_stencil: Option<wgpu::DepthStencilState>,
@@ -59,8 +405,21 @@ pub struct GpuState<V: Pod + Zeroable> {
     sample_count: u32,
     window_size_physical: [u32; 2],
     textures: Option<Textures>,
+    offscreen: RefCell<Option<Offscreen>>,
+    hdr: Option<Tonemap>,
+    exposure: f32,
     _marker: std::marker::PhantomData<V>,
 
+    /// The last successfully compiled shader source, kept so the pipeline can
+    /// be rebuilt when the sample count changes
+    shader_content: String,
+
+    /// See [`Context::gpu_cache`]
+    cache: GpuCache,
+
+    /// See [`super::gpu_stats`]
+    usage_report: RefCell<UsageReport>,
+
     // State access for hot reloading
     update_state: Arc<Mutex<Option<PathBuf>>>,
     _watcher: Option<notify::RecommendedWatcher>,
@@ -74,6 +433,7 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
     #[allow(clippy::too_many_arguments)]
     pub fn new<P: Pod + Zeroable>(
         app: &App,
+        ctx: &Context,
         window_size_logical: [u32; 2],
         shader_path: PathBuf,
         params: &P,
@@ -85,60 +445,47 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         watch: bool,
     ) -> Self {
         let shader_content = fs::read_to_string(&shader_path)
+            .map(|content| preprocess_shader(&content))
             .expect("Failed to read shader file");
 
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Hot Reloadable Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_content.into()),
-        };
-
         let update_state = Arc::new(Mutex::new(None));
         let watcher = if watch {
-            Some(Self::start_shader_watcher(
-                shader_path.clone(),
-                update_state.clone(),
-            ))
+            Some(watch_shader(shader_path.clone(), update_state.clone()))
         } else {
             None
         };
 
         let window = app.main_window();
         let device = window.device();
-        let sample_count = window.msaa_samples();
+        let sample_count = msaa_samples();
         let format = Frame::TEXTURE_FORMAT;
-        let shader_module = device.create_shader_module(shader);
+        let cache = ctx.gpu_cache();
+        let shader_module = cache.shader_module(device, &shader_content);
 
         let params_bind_group_layout =
             Self::create_params_bind_group_layout::<P>(device);
         let params_buffer = Self::create_params_buffer(device, params);
-        let params_bind_group = Self::create_params_bind_group(
-            device,
-            &params_bind_group_layout,
-            &params_buffer,
-        );
+        let eye_buffers =
+            Eye::ALL.map(|eye| Self::create_eye_buffer(device, eye));
+        let camera_buffer = Self::create_camera_buffer(device);
+        let params_bind_groups = eye_buffers.each_ref().map(|eye_buffer| {
+            Self::create_params_bind_group(
+                device,
+                &params_bind_group_layout,
+                &params_buffer,
+                eye_buffer,
+                &camera_buffer,
+            )
+        });
 
         let textures = if texture_count > 0 {
             let texture_bind_group_layout =
                 Self::create_texture_bind_group_layout(device, texture_count);
 
-            let dummy_texture = wgpu::TextureBuilder::new()
-                .size([1, 1])
-                .format(Frame::TEXTURE_FORMAT)
-                .dimension(wgpu::TextureDimension::D2)
-                .usage(
-                    wgpu::TextureUsages::TEXTURE_BINDING
-                        | wgpu::TextureUsages::RENDER_ATTACHMENT,
-                )
-                .sample_count(1)
-                .build(device);
-
-            let dummy_texture_sampler =
-                device.create_sampler(&wgpu::SamplerDescriptor::default());
+            let sampler = cache.default_sampler(device);
+            let view = cache.placeholder_texture(device);
 
-            let mut builder =
-                wgpu::BindGroupBuilder::new().sampler(&dummy_texture_sampler);
-
-            let view = dummy_texture.view().build();
+            let mut builder = wgpu::BindGroupBuilder::new().sampler(&sampler);
 
             for _ in 0..texture_count {
                 builder = builder.texture_view(&view);
@@ -206,18 +553,13 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
             (window_size_logical[1] as f64 * scale_factor).round() as u32,
         ];
 
-        let depth_texture = if enable_depth_testing {
-            let texture = wgpu::TextureBuilder::new()
-                .size(window_size_physical)
-                .format(wgpu::TextureFormat::Depth32Float)
-                .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
-                .sample_count(sample_count)
-                .build(device);
-
-            Some(texture.view().build())
-        } else {
-            None
-        };
+        let depth_texture = enable_depth_testing.then(|| {
+            Self::create_depth_texture(
+                device,
+                window_size_physical,
+                sample_count,
+            )
+        });
 
         let creation_state = PipelineCreationState {
             device,
@@ -233,11 +575,13 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
 
         let render_pipeline = Self::create_render_pipeline(creation_state);
 
-        Self {
+        let state = Self {
             render_pipeline,
             vertex_buffer,
             params_buffer,
-            params_bind_group,
+            params_bind_groups,
+            eye_buffers,
+            camera_buffer,
             n_vertices,
             depth_stencil,
             depth_texture,
@@ -249,28 +593,84 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
             sample_count,
             window_size_physical,
             textures,
+            offscreen: RefCell::new(None),
+            hdr: None,
+            exposure: 0.0,
+            shader_content,
+            cache,
+            usage_report: RefCell::new(UsageReport::default()),
             update_state,
             _watcher: watcher,
+        };
+        state.report_usage();
+        state
+    }
+
+    /// Enables the HDR rendering path: the shader renders unclamped linear
+    /// color into an RGBA16F offscreen texture which is then exposed and
+    /// tonemapped onto the frame with `tonemap`. Without this, anything
+    /// brighter than 1.0 clips when the frame is presented. Textures returned
+    /// by [`Self::render_to_texture`] are never tonemapped so feedback loops
+    /// keep their full range.
+    pub fn hdr(mut self, tonemap: Tonemap) -> Self {
+        self.hdr = Some(tonemap);
+        *self.offscreen.get_mut() = None;
+        self
+    }
+
+    /// Changes the operator of a GpuState created with [`Self::hdr`]
+    pub fn set_tonemap(&mut self, tonemap: Tonemap) {
+        if self.hdr.is_none() {
+            warn_once!("set_tonemap has no effect unless HDR is enabled");
+            return;
         }
+        self.hdr = Some(tonemap);
+    }
+
+    /// Exposure in stops applied before tonemapping. 0.0 is neutral, 1.0
+    /// doubles brightness, -1.0 halves it. Only used in HDR mode
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    fn create_depth_texture(
+        device: &wgpu::Device,
+        size: [u32; 2],
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        wgpu::TextureBuilder::new()
+            .size(size)
+            .format(wgpu::TextureFormat::Depth32Float)
+            .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            .sample_count(sample_count)
+            .build(device)
+            .view()
+            .build()
     }
 
     fn create_params_bind_group_layout<P: Pod>(
         device: &wgpu::Device,
     ) -> wgpu::BindGroupLayout {
+        let uniform_entry = |binding, size| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX
+                | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(size as _),
+            },
+            count: None,
+        };
+
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX
-                    | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: wgpu::BufferSize::new(
-                        std::mem::size_of::<P>() as _,
-                    ),
-                },
-                count: None,
-            }],
+            entries: &[
+                uniform_entry(0, std::mem::size_of::<P>()),
+                // See `xtal::eye` in eye.wgsl
+                uniform_entry(1, std::mem::size_of::<EyeUniform>()),
+                // See `xtal::camera` in camera.wgsl
+                uniform_entry(2, std::mem::size_of::<CameraUniform>()),
+            ],
             label: Some("Params Bind Group Layout"),
         })
     }
@@ -286,17 +686,47 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         })
     }
 
+    fn create_eye_buffer(device: &wgpu::Device, eye: Eye) -> wgpu::Buffer {
+        let uniform =
+            EyeUniform::new(eye, stereo::stereo_settings().separation);
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Eye Buffer"),
+            contents: bytemuck::bytes_of(&uniform),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn create_camera_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::bytes_of(&CameraUniform::default()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
     fn create_params_bind_group(
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
         buffer: &wgpu::Buffer,
+        eye_buffer: &wgpu::Buffer,
+        camera_buffer: &wgpu::Buffer,
     ) -> wgpu::BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: buffer.as_entire_binding(),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: eye_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+            ],
             label: Some("Params Bind Group"),
         })
     }
@@ -392,40 +822,6 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
             })
     }
 
-    fn start_shader_watcher(
-        path: PathBuf,
-        state: Arc<Mutex<Option<PathBuf>>>,
-    ) -> notify::RecommendedWatcher {
-        let path_to_watch = path.clone();
-
-        let mut watcher = notify::recommended_watcher(move |res| {
-            let event: Event = match res {
-                Ok(event) => event,
-                Err(_) => return,
-            };
-
-            if event.kind
-                != notify::EventKind::Modify(notify::event::ModifyKind::Data(
-                    notify::event::DataChange::Content,
-                ))
-            {
-                return;
-            }
-
-            trace!("Shader {:?} changed", path);
-            if let Ok(mut guard) = state.lock() {
-                *guard = Some(path.clone());
-            }
-        })
-        .expect("Failed to create watcher");
-
-        watcher
-            .watch(&path_to_watch, RecursiveMode::NonRecursive)
-            .expect("Failed to start watching shader file");
-
-        watcher
-    }
-
     /// Set one or more input textures. The number of `texture_views` provided
     /// must match the `texture_count` argument supplied to the constructor. In
     /// a feedback-patch scenario where you need 2 textures but only have 1 on
@@ -461,8 +857,7 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         let device = window.device();
         let textures = self.textures.as_mut().unwrap();
 
-        let sampler =
-            device.create_sampler(&wgpu::SamplerDescriptor::default());
+        let sampler = self.cache.default_sampler(device);
 
         let mut entries = vec![wgpu::BindGroupEntry {
             binding: 0,
@@ -512,6 +907,7 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
     ) {
         self.check_and_handle_resize(app, window_size);
         self.update_shader(app);
+        self.update_sample_count(app);
         app.main_window().queue().write_buffer(
             &self.params_buffer,
             0,
@@ -519,6 +915,18 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         );
     }
 
+    /// Writes `camera` into the standard camera block (see
+    /// [`super::camera`]) using the aspect ratio of the rendered area
+    pub fn set_camera(&mut self, app: &App, camera: &Camera) {
+        let [w, h] = self.window_size_physical.map(|n| n as f32);
+        let aspect = LETTERBOX_ASPECT.lock().unwrap().unwrap_or(w / h);
+        app.main_window().queue().write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::bytes_of(&camera.uniform(aspect)),
+        );
+    }
+
     pub fn update_vertex_buffer(
         &mut self,
         app: &App,
@@ -561,48 +969,46 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         info!("Reloading shader from {:?}", path);
 
         let shader_content = match fs::read_to_string(&path) {
-            Ok(content) => content,
+            Ok(content) => preprocess_shader(&content),
             Err(_) => return,
         };
 
-        if self.validate_shader(&shader_content).is_ok() {
+        if validate_shader(&shader_content).is_ok() {
             self.recreate_pipeline(app, &shader_content);
             info!("Shader pipeline successfully recreated");
         }
     }
 
-    fn validate_shader(
-        &self,
-        shader_content: &str,
-    ) -> Result<naga::Module, ()> {
-        let module = match wgsl::parse_str(shader_content) {
-            Err(e) => {
-                error!("Failed to parse shader: {:?}", e);
-                return Err(());
-            }
-            Ok(m) => m,
-        };
+    /// Rebuilds the pipeline (and depth texture) when the runtime MSAA sample
+    /// count no longer matches the one this state was built with
+    fn update_sample_count(&mut self, app: &App) {
+        let sample_count = msaa_samples();
+        if sample_count == self.sample_count {
+            return;
+        }
 
-        let mut validator =
-            Validator::new(ValidationFlags::all(), Capabilities::empty());
+        info!("Rebuilding shader pipeline with {}x MSAA", sample_count);
 
-        if let Err(validation_error) = validator.validate(&module) {
-            error!("Shader validation failed:\n{:?}", validation_error);
-            return Err(());
+        self.sample_count = sample_count;
+        let shader_content = self.shader_content.clone();
+        self.recreate_pipeline(app, &shader_content);
+
+        if self.depth_stencil.is_some() {
+            self.depth_texture = Some(Self::create_depth_texture(
+                app.main_window().device(),
+                self.window_size_physical,
+                sample_count,
+            ));
         }
 
-        Ok(module)
+        *self.offscreen.get_mut() = None;
     }
 
     fn recreate_pipeline(&mut self, app: &App, shader_content: &str) {
         let window = app.main_window();
         let device = window.device();
 
-        let shader = wgpu::ShaderModuleDescriptor {
-            label: Some("Hot Reloadable Shader"),
-            source: wgpu::ShaderSource::Wgsl(shader_content.into()),
-        };
-        let shader_module = device.create_shader_module(shader);
+        let shader_module = self.cache.shader_module(device, shader_content);
 
         let pipeline_layout = if let Some(textures) = &self.textures {
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -634,6 +1040,7 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         };
 
         self.render_pipeline = Self::create_render_pipeline(creation_state);
+        self.shader_content = shader_content.to_string();
     }
 
     fn check_and_handle_resize(&mut self, app: &App, window_size: [u32; 2]) {
@@ -645,33 +1052,61 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
             .expect("Unable to get primary monitor")
             .scale_factor();
 
-        let window_size_physical = [
-            (window_size[0] as f64 * scale_factor).round() as u32,
-            (window_size[1] as f64 * scale_factor).round() as u32,
-        ];
+        // When letterboxed `window_size` only describes the content area but
+        // the depth texture must match the full frame
+        let window_size_physical = if LETTERBOX_ASPECT.lock().unwrap().is_some()
+        {
+            let (w, h) = window.inner_size_pixels();
+            [w, h]
+        } else {
+            [
+                (window_size[0] as f64 * scale_factor).round() as u32,
+                (window_size[1] as f64 * scale_factor).round() as u32,
+            ]
+        };
 
         if self.depth_stencil.is_some()
             && window_size_physical != self.window_size_physical
         {
-            let texture = wgpu::TextureBuilder::new()
-                .size(window_size_physical)
-                .format(wgpu::TextureFormat::Depth32Float)
-                .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
-                .sample_count(self.sample_count)
-                .build(device);
-
-            self.depth_texture = Some(texture.view().build());
+            self.depth_texture = Some(Self::create_depth_texture(
+                device,
+                window_size_physical,
+                self.sample_count,
+            ));
             self.window_size_physical = window_size_physical;
         }
     }
 
     pub fn render(&self, frame: &Frame) {
-        let mut encoder = frame.command_encoder();
+        self.render_to_frame(frame, DrawCall::Vertices);
+    }
 
-        let mut render_pass_builder = wgpu::RenderPassBuilder::new()
-            .color_attachment(frame.texture_view(), |color| {
-                color.load_op(wgpu::LoadOp::Load)
-            });
+    /// Estimates what this state holds for [`super::gpu_stats`]. Targets
+    /// returned by [`Self::render_to_texture`] belong to the caller and
+    /// aren't counted
+    fn report_usage(&self) {
+        let mut usage = GpuUsage::default()
+            .buffer(self.params_buffer.size())
+            .buffer(self.camera_buffer.size())
+            .pipelines(1);
+        for buffer in self.eye_buffers.iter().chain(&self.vertex_buffer) {
+            usage = usage.buffer(buffer.size());
+        }
+        if self.depth_texture.is_some() {
+            usage = usage.texture(
+                self.window_size_physical,
+                wgpu::TextureFormat::Depth32Float,
+                self.sample_count,
+            );
+        }
+        if let Some(offscreen) = self.offscreen.borrow().as_ref() {
+            usage = offscreen.usage(usage, self.sample_count);
+        }
+        self.usage_report.borrow_mut().set(usage);
+    }
+
+    fn render_to_frame(&self, frame: &Frame, draw_call: DrawCall) {
+        self.report_usage();
 
         if let Some(ref depth_texture) = self.depth_texture {
             // Can happen when switching sketches at runtime. We are correctly
@@ -682,25 +1117,115 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
                 warn!("Depth texture size mismatch. Skipping this frame.");
                 return;
             }
-
-            render_pass_builder = render_pass_builder
-                .depth_stencil_attachment(depth_texture, |depth| depth);
         }
 
-        let mut render_pass = render_pass_builder.begin(&mut encoder);
+        let viewport = letterbox_viewport(frame.texture_size());
+        let load_op = wgpu::LoadOp::Load;
+        let depth = self.depth_texture.as_deref();
+        let mut encoder = frame.command_encoder();
+        let stereo = stereo::stereo_settings();
 
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_bind_group(0, &self.params_bind_group, &[]);
-        if let Some(textures) = &self.textures {
-            render_pass.set_bind_group(1, &textures.bind_group, &[]);
+        if self.hdr.is_none()
+            && self.sample_count == frame.texture_msaa_samples()
+            && !stereo.is_enabled()
+        {
+            let targets = PassTargets {
+                color: frame.texture_view(),
+                resolve: None,
+                depth,
+            };
+            self.encode_pass(
+                &mut encoder,
+                targets,
+                load_op,
+                viewport,
+                draw_call,
+                Eye::Center,
+            );
+            return;
         }
 
-        if let Some(ref vertex_buffer) = self.vertex_buffer {
-            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            render_pass.draw(0..self.n_vertices, 0..1);
+        let device = frame.device_queue_pair().device();
+        let mut offscreen = self.offscreen.borrow_mut();
+        let size = frame.texture_size();
+        if offscreen.as_ref().is_none_or(|o| o.size != size) {
+            *offscreen = Some(Offscreen::new(
+                device,
+                size,
+                self.sample_count,
+                frame.texture_msaa_samples(),
+                self.hdr.is_some(),
+            ));
+        }
+        let offscreen = offscreen.as_mut().unwrap();
+
+        if stereo.is_enabled() {
+            offscreen.prepare_stereo(device, self.sample_count, stereo.mode);
+            let stereo_targets = offscreen.stereo.as_ref().unwrap();
+            let eyes = [
+                (Eye::Left, &stereo_targets.left),
+                (Eye::Right, &stereo_targets.right),
+            ];
+
+            for (eye, target) in eyes {
+                frame.device_queue_pair().queue().write_buffer(
+                    &self.eye_buffers[eye as usize],
+                    0,
+                    bytemuck::bytes_of(&EyeUniform::new(
+                        eye,
+                        stereo.separation,
+                    )),
+                );
+
+                let (color, resolve) = target.targets();
+                self.encode_pass(
+                    &mut encoder,
+                    PassTargets {
+                        color,
+                        resolve,
+                        depth,
+                    },
+                    wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    viewport,
+                    draw_call,
+                    eye,
+                );
+            }
+
+            stereo_targets
+                .pass
+                .encode(&mut encoder, &offscreen.target.resolved);
         } else {
-            error!("Use render_procedural if not using a vertex buffer");
-            panic!();
+            let (color, resolve) = offscreen.target.targets();
+            let targets = PassTargets {
+                color,
+                resolve,
+                depth,
+            };
+
+            self.encode_pass(
+                &mut encoder,
+                targets,
+                load_op,
+                viewport,
+                draw_call,
+                Eye::Center,
+            );
+        }
+
+        match &offscreen.output {
+            OffscreenOutput::Reshape(reshaper) => {
+                reshaper.encode_render_pass(frame.texture_view(), &mut encoder);
+            }
+            OffscreenOutput::Tonemap(pass) => {
+                pass.encode(
+                    frame.device_queue_pair().queue(),
+                    &mut encoder,
+                    frame.texture_view(),
+                    self.hdr.unwrap_or_default(),
+                    self.exposure,
+                );
+            }
         }
     }
 
@@ -709,16 +1234,20 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
         let device = window.device();
 
         // Create multisampled texture for rendering
-        let msaa_texture = wgpu::TextureBuilder::new()
-            .size(self.window_size_physical)
-            .format(Frame::TEXTURE_FORMAT)
-            .dimension(wgpu::TextureDimension::D2)
-            .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
-            .sample_count(self.sample_count)
-            .build(device);
+        let msaa_view = (self.sample_count > 1).then(|| {
+            wgpu::TextureBuilder::new()
+                .size(self.window_size_physical)
+                .format(Frame::TEXTURE_FORMAT)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
+                .sample_count(self.sample_count)
+                .build(device)
+                .view()
+                .build()
+        });
 
         // Create non-multisampled texture for resolving and sampling
-        let resolve_texture = wgpu::TextureBuilder::new()
+        let resolve_view = wgpu::TextureBuilder::new()
             .size(self.window_size_physical)
             .format(Frame::TEXTURE_FORMAT)
             .dimension(wgpu::TextureDimension::D2)
@@ -727,73 +1256,104 @@ impl<V: Pod + Zeroable + Typed> GpuState<V> {
                     | wgpu::TextureUsages::RENDER_ATTACHMENT,
             )
             .sample_count(1)
-            .build(device);
-
-        let depth_texture = if self.depth_texture.is_some() {
-            Some(
-                wgpu::TextureBuilder::new()
-                    .size(self.window_size_physical)
-                    .format(wgpu::TextureFormat::Depth32Float)
-                    .usage(wgpu::TextureUsages::RENDER_ATTACHMENT)
-                    .sample_count(self.sample_count)
-                    .build(device),
+            .build(device)
+            .view()
+            .build();
+
+        let depth_view = self.depth_texture.as_ref().map(|_| {
+            Self::create_depth_texture(
+                device,
+                self.window_size_physical,
+                self.sample_count,
             )
-        } else {
-            None
-        };
+        });
 
-        let msaa_view = msaa_texture.view().build();
-        let resolve_view = resolve_texture.view().build();
-        let depth_view = depth_texture.map(|tex| tex.view().build());
+        let (color, resolve) = match &msaa_view {
+            Some(msaa_view) => (&**msaa_view, Some(&*resolve_view)),
+            None => (&*resolve_view, None),
+        };
 
         let mut encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Render to Texture Encoder"),
             });
 
-        {
-            let mut render_pass = if let Some(ref depth_view) = depth_view {
-                wgpu::RenderPassBuilder::new()
-                    .color_attachment(&msaa_view, |color| {
-                        color
-                            .load_op(wgpu::LoadOp::Clear(
-                                wgpu::Color::TRANSPARENT,
-                            ))
-                            .store_op(true)
-                            .resolve_target(Some(&resolve_view))
-                    })
-                    .depth_stencil_attachment(depth_view, |depth| depth)
-                    .begin(&mut encoder)
-            } else {
-                wgpu::RenderPassBuilder::new()
-                    .color_attachment(&msaa_view, |color| {
-                        color
-                            .load_op(wgpu::LoadOp::Clear(
-                                wgpu::Color::TRANSPARENT,
-                            ))
-                            .store_op(true)
-                            .resolve_target(Some(&resolve_view))
-                    })
-                    .begin(&mut encoder)
-            };
+        self.encode_pass(
+            &mut encoder,
+            PassTargets {
+                color,
+                resolve,
+                depth: depth_view.as_deref(),
+            },
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            None,
+            ternary!(
+                self.vertex_buffer.is_some(),
+                DrawCall::Vertices,
+                DrawCall::Procedural(3)
+            ),
+            Eye::Center,
+        );
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_bind_group(0, &self.params_bind_group, &[]);
-            if let Some(textures) = &self.textures {
-                render_pass.set_bind_group(1, &textures.bind_group, &[]);
-            }
+        window.queue().submit(std::iter::once(encoder.finish()));
 
-            if let Some(ref vertex_buffer) = self.vertex_buffer {
-                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                render_pass.draw(0..self.n_vertices, 0..1);
-            } else {
-                render_pass.draw(0..3, 0..1);
-            }
+        resolve_view
+    }
+
+    fn encode_pass(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        targets: PassTargets,
+        load_op: wgpu::LoadOp<wgpu::Color>,
+        viewport: Option<[f32; 4]>,
+        draw_call: DrawCall,
+        eye: Eye,
+    ) {
+        let mut render_pass_builder = wgpu::RenderPassBuilder::new()
+            .color_attachment(targets.color, |color| {
+                color
+                    .load_op(load_op)
+                    .store_op(true)
+                    .resolve_target(targets.resolve)
+            });
+
+        if let Some(depth) = targets.depth {
+            render_pass_builder = render_pass_builder
+                .depth_stencil_attachment(depth, |depth| depth);
         }
 
-        window.queue().submit(std::iter::once(encoder.finish()));
+        let mut render_pass = render_pass_builder.begin(encoder);
 
-        resolve_view
+        if let Some([x, y, w, h]) = viewport {
+            render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(
+            0,
+            &self.params_bind_groups[eye as usize],
+            &[],
+        );
+        if let Some(textures) = &self.textures {
+            render_pass.set_bind_group(1, &textures.bind_group, &[]);
+        }
+
+        match draw_call {
+            DrawCall::Vertices => {
+                if let Some(ref vertex_buffer) = self.vertex_buffer {
+                    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                    render_pass.draw(0..self.n_vertices, 0..1);
+                } else {
+                    error!(
+                        "Use render_procedural if not using a vertex buffer"
+                    );
+                    panic!();
+                }
+            }
+            DrawCall::Procedural(vertex_count) => {
+                render_pass.draw(0..vertex_count, 0..1);
+            }
+        }
     }
 
     fn infer_vertex_attributes() -> Vec<wgpu::VertexAttribute> {
@@ -875,6 +1435,7 @@ impl GpuState<BasicPositionVertex> {
     /// See interference and wave_fract for examples.
     pub fn new_fullscreen<P: Pod + Zeroable>(
         app: &App,
+        ctx: &Context,
         window_size: [u32; 2],
         shader_path: PathBuf,
         params: &P,
@@ -882,6 +1443,7 @@ impl GpuState<BasicPositionVertex> {
     ) -> Self {
         Self::new(
             app,
+            ctx,
             window_size,
             shader_path,
             params,
@@ -900,12 +1462,14 @@ impl GpuState<()> {
     /// See spiral.rs for an example.
     pub fn new_procedural<P: Pod + Zeroable>(
         app: &App,
+        ctx: &Context,
         window_size: [u32; 2],
         shader_path: PathBuf,
         params: &P,
     ) -> Self {
         Self::new(
             app,
+            ctx,
             window_size,
             shader_path,
             params,
@@ -919,17 +1483,19 @@ impl GpuState<()> {
     }
 
     pub fn render_procedural(&self, frame: &Frame, vertex_count: u32) {
-        let mut encoder = frame.command_encoder();
-        let mut render_pass = wgpu::RenderPassBuilder::new()
-            .color_attachment(frame.texture_view(), |color| {
-                color.load_op(wgpu::LoadOp::Load)
-            })
-            .begin(&mut encoder);
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_bind_group(0, &self.params_bind_group, &[]);
-        if let Some(textures) = &self.textures {
-            render_pass.set_bind_group(1, &textures.bind_group, &[]);
-        }
-        render_pass.draw(0..vertex_count, 0..1);
+        self.render_to_frame(frame, DrawCall::Procedural(vertex_count));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_msaa_samples() {
+        assert_eq!(next_msaa_samples(1), 2);
+        assert_eq!(next_msaa_samples(4), 8);
+        assert_eq!(next_msaa_samples(8), 1);
+        assert_eq!(next_msaa_samples(3), 1);
     }
 }
@@ -0,0 +1,138 @@
+//! GPU resources that outlive any one sketch. Switching sketches otherwise
+//! recompiles every shader module and recreates every sampler and placeholder
+//! texture, which for heavy shader sketches means a multi-second hitch. The
+//! runtime owns a single [`GpuCache`] for the lifetime of the main window's
+//! device and hands it to sketches through [`Context::gpu_cache`], where
+//! [`GpuState`] picks it up.
+//!
+//! [`Context::gpu_cache`]: crate::prelude::Context::gpu_cache
+//! [`GpuState`]: crate::prelude::gpu::GpuState
+
+use nannou::prelude::*;
+use nannou::wgpu;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use super::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    filter: wgpu::FilterMode,
+    address_mode: wgpu::AddressMode,
+}
+
+#[derive(Default)]
+struct Resources {
+    /// Keyed by a hash of the (preprocessed) WGSL source
+    shader_modules: HashMap<u64, Rc<wgpu::ShaderModule>>,
+    samplers: HashMap<SamplerKey, Rc<wgpu::Sampler>>,
+    placeholder_texture: Option<Rc<wgpu::TextureView>>,
+}
+
+/// A cheaply cloneable handle to resources shared by every sketch. All
+/// resources must come from the main window's device
+#[derive(Clone, Default)]
+pub struct GpuCache {
+    resources: Rc<RefCell<Resources>>,
+}
+
+impl GpuCache {
+    /// Compiles `source` unless a module with identical source has already
+    /// been compiled
+    pub fn shader_module(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Rc<wgpu::ShaderModule> {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let key = hasher.finish();
+
+        self.resources
+            .borrow_mut()
+            .shader_modules
+            .entry(key)
+            .or_insert_with(|| {
+                trace!("Compiling shader module {:x}", key);
+                Rc::new(device.create_shader_module(
+                    wgpu::ShaderModuleDescriptor {
+                        label: Some("Cached Shader"),
+                        source: wgpu::ShaderSource::Wgsl(source.into()),
+                    },
+                ))
+            })
+            .clone()
+    }
+
+    pub fn sampler(
+        &self,
+        device: &wgpu::Device,
+        filter: wgpu::FilterMode,
+        address_mode: wgpu::AddressMode,
+    ) -> Rc<wgpu::Sampler> {
+        self.resources
+            .borrow_mut()
+            .samplers
+            .entry(SamplerKey {
+                filter,
+                address_mode,
+            })
+            .or_insert_with(|| {
+                Rc::new(device.create_sampler(&wgpu::SamplerDescriptor {
+                    label: Some("Cached Sampler"),
+                    address_mode_u: address_mode,
+                    address_mode_v: address_mode,
+                    address_mode_w: address_mode,
+                    mag_filter: filter,
+                    min_filter: filter,
+                    ..Default::default()
+                }))
+            })
+            .clone()
+    }
+
+    /// Equivalent to `wgpu::SamplerDescriptor::default()`
+    pub fn default_sampler(&self, device: &wgpu::Device) -> Rc<wgpu::Sampler> {
+        self.sampler(
+            device,
+            wgpu::FilterMode::Nearest,
+            wgpu::AddressMode::ClampToEdge,
+        )
+    }
+
+    /// A 1x1 texture for filling texture slots before real ones are set
+    pub fn placeholder_texture(
+        &self,
+        device: &wgpu::Device,
+    ) -> Rc<wgpu::TextureView> {
+        self.resources
+            .borrow_mut()
+            .placeholder_texture
+            .get_or_insert_with(|| {
+                let texture = wgpu::TextureBuilder::new()
+                    .size([1, 1])
+                    .format(Frame::TEXTURE_FORMAT)
+                    .dimension(wgpu::TextureDimension::D2)
+                    .usage(
+                        wgpu::TextureUsages::TEXTURE_BINDING
+                            | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    )
+                    .sample_count(1)
+                    .build(device);
+                Rc::new(texture.view().build())
+            })
+            .clone()
+    }
+}
+
+impl std::fmt::Debug for GpuCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let resources = self.resources.borrow();
+        f.debug_struct("GpuCache")
+            .field("shader_modules", &resources.shader_modules.len())
+            .field("samplers", &resources.samplers.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,233 @@
+//! Approximate per-sketch GPU resource usage for finding which sketches hog
+//! memory over a long session. [`GpuState`](super::gpu::GpuState) and the
+//! compute helpers report the buffers, textures, and pipelines they own to
+//! the sketch that was being initialized (or was live) when they were
+//! created, and withdraw them when dropped. Sizes are computed from
+//! dimensions and formats rather than queried from the driver, so treat
+//! them as estimates. Requested from the UI with
+//! `wv::Event::RequestGpuStats`.
+
+use nannou::wgpu;
+use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, Mutex};
+
+use crate::framework::prelude::*;
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuUsage {
+    pub buffers: u32,
+    pub buffer_bytes: u64,
+    pub textures: u32,
+    pub texture_bytes: u64,
+    pub pipelines: u32,
+}
+
+impl GpuUsage {
+    pub fn buffer(mut self, bytes: u64) -> Self {
+        self.buffers += 1;
+        self.buffer_bytes += bytes;
+        self
+    }
+
+    /// A 2D texture; multisampled textures count each sample
+    pub fn texture(
+        mut self,
+        size: [u32; 2],
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        self.textures += 1;
+        self.texture_bytes += size[0] as u64
+            * size[1] as u64
+            * bytes_per_pixel(format)
+            * sample_count.max(1) as u64;
+        self
+    }
+
+    pub fn pipelines(mut self, count: u32) -> Self {
+        self.pipelines += count;
+        self
+    }
+
+    /// Approximate VRAM held by buffers and textures
+    pub fn vram_bytes(&self) -> u64 {
+        self.buffer_bytes + self.texture_bytes
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.buffers += other.buffers;
+        self.buffer_bytes += other.buffer_bytes;
+        self.textures += other.textures;
+        self.texture_bytes += other.texture_bytes;
+        self.pipelines += other.pipelines;
+    }
+
+    fn subtract(&mut self, other: &Self) {
+        self.buffers = self.buffers.saturating_sub(other.buffers);
+        self.buffer_bytes =
+            self.buffer_bytes.saturating_sub(other.buffer_bytes);
+        self.textures = self.textures.saturating_sub(other.textures);
+        self.texture_bytes =
+            self.texture_bytes.saturating_sub(other.texture_bytes);
+        self.pipelines = self.pipelines.saturating_sub(other.pipelines);
+    }
+}
+
+/// Covers the formats the framework creates; anything else is assumed to be
+/// 4 bytes
+fn bytes_per_pixel(format: wgpu::TextureFormat) -> u64 {
+    use wgpu::TextureFormat as F;
+    match format {
+        F::R8Unorm => 1,
+        F::Rg8Unorm | F::R16Float => 2,
+        F::Rgba16Float | F::Rg32Float => 8,
+        F::Rgba32Float => 16,
+        _ => 4,
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SketchGpuUsage {
+    pub name: String,
+    /// Resources currently alive, including those of a pre-warmed or input
+    /// instance of the sketch
+    pub live: GpuUsage,
+    /// The most [`GpuUsage::vram_bytes`] held at once this session
+    pub peak_vram_bytes: u64,
+}
+
+static OWNER: Mutex<Option<String>> = Mutex::new(None);
+
+static USAGE: LazyLock<Mutex<HashMap<String, SketchGpuUsage>>> =
+    LazyLock::new(|| Mutex::new(HashMap::default()));
+
+/// Attributes resources created from now on to `name`. Set by the runtime to
+/// the live sketch so resources created lazily in `update` or `view` count too
+pub(crate) fn set_owner(name: &str) {
+    *OWNER.lock().unwrap() = Some(name.to_string());
+}
+
+/// Attributes resources created within `f` to `name`, e.g. while
+/// initializing a sketch that isn't live yet
+pub(crate) fn with_owner<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let previous = OWNER.lock().unwrap().replace(name.to_string());
+    let result = f();
+    *OWNER.lock().unwrap() = previous;
+    result
+}
+
+/// Every sketch that has owned GPU resources this session, unsorted
+pub fn all_usage() -> Vec<SketchGpuUsage> {
+    USAGE.lock().unwrap().values().cloned().collect()
+}
+
+fn apply(owner: &str, previous: &GpuUsage, next: &GpuUsage) {
+    let mut usage = USAGE.lock().unwrap();
+    let entry =
+        usage
+            .entry(owner.to_string())
+            .or_insert_with(|| SketchGpuUsage {
+                name: owner.to_string(),
+                ..Default::default()
+            });
+    entry.live.subtract(previous);
+    entry.live.add(next);
+    entry.peak_vram_bytes = entry.peak_vram_bytes.max(entry.live.vram_bytes());
+}
+
+/// One object's contribution to its owner's [`GpuUsage`], withdrawn on drop
+#[derive(Debug)]
+pub(crate) struct UsageReport {
+    owner: Option<String>,
+    usage: GpuUsage,
+}
+
+impl Default for UsageReport {
+    fn default() -> Self {
+        Self::for_owner(OWNER.lock().unwrap().clone())
+    }
+}
+
+impl UsageReport {
+    fn for_owner(owner: Option<String>) -> Self {
+        Self {
+            owner,
+            usage: GpuUsage::default(),
+        }
+    }
+
+    /// Replaces this object's contribution. Cheap when nothing changed so it
+    /// can be called every frame
+    pub fn set(&mut self, usage: GpuUsage) {
+        if usage == self.usage {
+            return;
+        }
+        if let Some(owner) = &self.owner {
+            apply(owner, &self.usage, &usage);
+        }
+        self.usage = usage;
+    }
+}
+
+impl Drop for UsageReport {
+    fn drop(&mut self) {
+        self.set(GpuUsage::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage_of(name: &str) -> SketchGpuUsage {
+        all_usage().into_iter().find(|u| u.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_usage_sizes() {
+        let usage = GpuUsage::default()
+            .buffer(64)
+            .texture([10, 10], wgpu::TextureFormat::Rgba16Float, 4)
+            .texture([10, 10], wgpu::TextureFormat::Depth32Float, 1)
+            .pipelines(2);
+        assert_eq!(usage.buffers, 1);
+        assert_eq!(usage.textures, 2);
+        assert_eq!(usage.texture_bytes, 3200 + 400);
+        assert_eq!(usage.vram_bytes(), 64 + 3600);
+        assert_eq!(usage.pipelines, 2);
+    }
+
+    #[test]
+    fn test_reports_and_peak() {
+        let owner = Some("gpu_stats_test".to_string());
+        let mut a = UsageReport::for_owner(owner.clone());
+        let mut b = UsageReport::for_owner(owner);
+
+        a.set(GpuUsage::default().buffer(100));
+        b.set(GpuUsage::default().buffer(50).pipelines(1));
+        let usage = usage_of("gpu_stats_test");
+        assert_eq!(usage.live.buffers, 2);
+        assert_eq!(usage.live.vram_bytes(), 150);
+        assert_eq!(usage.live.pipelines, 1);
+
+        a.set(GpuUsage::default().buffer(10));
+        assert_eq!(usage_of("gpu_stats_test").live.vram_bytes(), 60);
+
+        drop(a);
+        drop(b);
+        let usage = usage_of("gpu_stats_test");
+        assert_eq!(usage.live, GpuUsage::default());
+        assert_eq!(usage.peak_vram_bytes, 150);
+    }
+
+    #[test]
+    fn test_unowned_reports_are_ignored() {
+        let mut report = UsageReport::for_owner(None);
+        report.set(GpuUsage::default().buffer(12345));
+        assert!(all_usage().iter().all(|u| u.live.buffer_bytes != 12345));
+    }
+}
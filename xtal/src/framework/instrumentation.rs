@@ -1,13 +1,32 @@
-//! Bare-bones instrumentation for quick insights
+//! Bare-bones instrumentation for quick insights. The hot paths are only
+//! timed when built with the `instrumentation` feature, which costs nothing
+//! otherwise. Their timings are then logged once a second while enabled, which
+//! is the default and can be toggled at runtime with [`set_enabled`].
 
+use std::sync::atomic::{AtomicBool, Ordering};
 #[cfg(feature = "instrumentation")]
 use std::time::{Duration, Instant};
 
-#[cfg(feature = "instrumentation")]
 use super::prelude::*;
 
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(feature = "instrumentation"));
+
+#[cfg(feature = "runtime")]
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    info!(
+        "Instrumentation {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
 #[cfg(feature = "instrumentation")]
-#[derive(Debug)]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "instrumentation")]
+#[derive(Clone, Debug)]
 pub struct Instrumentation {
     label: String,
     total_duration: Duration,
@@ -19,7 +38,6 @@ pub struct Instrumentation {
 #[cfg(feature = "instrumentation")]
 impl Instrumentation {
     pub fn new(label: &str) -> Self {
-        info!("Instrumentation enabled");
         Self {
             label: label.to_string(),
             total_duration: Duration::ZERO,
@@ -29,11 +47,16 @@ impl Instrumentation {
         }
     }
 
-    pub fn start(&self) -> Instant {
-        Instant::now()
+    /// `None` while instrumentation is disabled
+    pub fn start(&self) -> Option<Instant> {
+        is_enabled().then(Instant::now)
     }
 
-    pub fn record(&mut self, start_time: Instant) {
+    pub fn record(&mut self, start_time: Option<Instant>) {
+        let Some(start_time) = start_time else {
+            return;
+        };
+
         let elapsed = start_time.elapsed();
         self.total_duration += elapsed;
         self.call_count += 1;
@@ -50,7 +73,10 @@ impl Instrumentation {
     pub fn report(&self) {
         if self.call_count > 0 {
             let avg_duration = self.total_duration / self.call_count as u32;
-            info!("[{}] Average: {:.2?}", self.label, avg_duration);
+            info!(
+                "[{}] Average: {:.2?} ({} calls)",
+                self.label, avg_duration, self.call_count
+            );
         }
     }
 }
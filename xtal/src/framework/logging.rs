@@ -1,15 +1,16 @@
 //! Logging module used for internal purposes yet can be helpful for debugging
 //! your own sketches. Requires the `logging` feature.
 
-use env_logger::{Builder, Env};
-use log::LevelFilter;
-use std::io::Write;
-use termcolor::{Color, ColorSpec, WriteColor};
-
 pub use log::{debug, error, info, trace, warn};
 
 /// Requires the `logging` feature.
+#[cfg(any(feature = "runtime", feature = "logging"))]
 pub fn init_logger() {
+    use env_logger::{Builder, Env};
+    use log::LevelFilter;
+    use std::io::Write;
+    use termcolor::{Color, ColorSpec, WriteColor};
+
     Builder::from_env(Env::default().default_filter_or("xtal=info"))
         .filter_module("nannou", LevelFilter::Warn)
         .format(|_buf, record| {
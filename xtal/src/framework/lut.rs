@@ -0,0 +1,373 @@
+//! 3D color lookup tables in the `.cube` format (as exported by Resolve,
+//! Photoshop, and most grading tools). A LUT is usually applied as a final
+//! color grade via the `lut` post effect (see [`crate::prelude::PostEffect`])
+//! but can also be sampled on the CPU with [`Lut::sample`].
+//!
+//! # Example
+//! ```yaml
+//! post:
+//!   - lut:
+//!       # Relative to this control script
+//!       path: luts/teal_orange.cube
+//!       strength: $grade
+//! ```
+
+use nannou::prelude::*;
+use notify::{Event, RecursiveMode, Watcher};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct Lut {
+    /// Number of entries along each axis
+    pub size: u32,
+    pub domain_min: Vec3,
+    pub domain_max: Vec3,
+    /// `size³` output colors with red changing fastest, then green, then blue
+    pub data: Vec<Vec3>,
+}
+
+impl Lut {
+    pub fn identity(size: u32) -> Self {
+        let size = size.max(2);
+        let max = (size - 1) as f32;
+        let data = (0..size.pow(3))
+            .map(|i| {
+                vec3(
+                    (i % size) as f32 / max,
+                    (i / size % size) as f32 / max,
+                    (i / (size * size)) as f32 / max,
+                )
+            })
+            .collect();
+
+        Self {
+            size,
+            domain_min: Vec3::ZERO,
+            domain_max: Vec3::ONE,
+            data,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read LUT {:?}: {}", path, e))?;
+        Self::parse(&content).map_err(|e| {
+            format!("Unable to parse LUT {:?}: {}", path, e).into()
+        })
+    }
+
+    pub fn parse(content: &str) -> Result<Self, Box<dyn Error>> {
+        let mut size = None;
+        let mut domain_min = Vec3::ZERO;
+        let mut domain_max = Vec3::ONE;
+        let mut data = vec![];
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            let keyword = tokens.next().unwrap_or_default();
+
+            if keyword.parse::<f32>().is_ok() {
+                data.push(parse_vec3(line)?);
+                continue;
+            }
+
+            let rest = line[keyword.len()..].trim();
+            match keyword {
+                "LUT_3D_SIZE" => size = Some(rest.parse::<u32>()?),
+                "DOMAIN_MIN" => domain_min = parse_vec3(rest)?,
+                "DOMAIN_MAX" => domain_max = parse_vec3(rest)?,
+                "LUT_3D_INPUT_RANGE" => {
+                    let range = rest
+                        .split_whitespace()
+                        .map(str::parse::<f32>)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if let [min, max] = range[..] {
+                        domain_min = Vec3::splat(min);
+                        domain_max = Vec3::splat(max);
+                    }
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".into()),
+                // TITLE and vendor specific keywords
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or("Missing LUT_3D_SIZE")?;
+        if size < 2 {
+            return Err(format!("Invalid LUT_3D_SIZE: {}", size).into());
+        }
+        if data.len() != size.pow(3) as usize {
+            return Err(format!(
+                "Expected {} entries for LUT_3D_SIZE {}, found {}",
+                size.pow(3),
+                size,
+                data.len()
+            )
+            .into());
+        }
+        if domain_min.cmpge(domain_max).any() {
+            return Err("DOMAIN_MIN must be less than DOMAIN_MAX".into());
+        }
+
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            data,
+        })
+    }
+
+    /// Trilinearly interpolated lookup (the same as the GPU pass)
+    pub fn sample(&self, color: Vec3) -> Vec3 {
+        let max = (self.size - 1) as f32;
+        let p = ((color - self.domain_min)
+            / (self.domain_max - self.domain_min))
+            .clamp(Vec3::ZERO, Vec3::ONE)
+            * max;
+        let lo = p.floor().min(Vec3::splat(max - 1.0));
+        let t = p - lo;
+        let (x, y, z) = (lo.x as u32, lo.y as u32, lo.z as u32);
+
+        let at = |dx, dy, dz| self.at(x + dx, y + dy, z + dz);
+
+        let c00 = at(0, 0, 0).lerp(at(1, 0, 0), t.x);
+        let c10 = at(0, 1, 0).lerp(at(1, 1, 0), t.x);
+        let c01 = at(0, 0, 1).lerp(at(1, 0, 1), t.x);
+        let c11 = at(0, 1, 1).lerp(at(1, 1, 1), t.x);
+
+        c00.lerp(c10, t.y).lerp(c01.lerp(c11, t.y), t.z)
+    }
+
+    fn at(&self, r: u32, g: u32, b: u32) -> Vec3 {
+        self.data[(r + g * self.size + b * self.size * self.size) as usize]
+    }
+
+    /// Texel data for an `Rgba16Float` 3D texture
+    pub(crate) fn rgba_f16_bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|c| [c.x, c.y, c.z, 1.0])
+            .flat_map(|v| f16_bits(v).to_le_bytes())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Lut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lut")
+            .field("size", &self.size)
+            .field("domain_min", &self.domain_min)
+            .field("domain_max", &self.domain_max)
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse_vec3(s: &str) -> Result<Vec3, Box<dyn Error>> {
+    let values = s
+        .split_whitespace()
+        .map(str::parse::<f32>)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match values[..] {
+        [r, g, b] => Ok(vec3(r, g, b)),
+        _ => Err(format!("Expected 3 values, got: {}", s).into()),
+    }
+}
+
+/// IEEE 754 half precision bits of `value` (round to nearest)
+pub(crate) fn f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = (bits >> 16) & 0x8000;
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x007f_ffff;
+
+    if value.is_nan() {
+        return (sign | 0x7e00) as u16;
+    }
+    if exponent >= 0x1f {
+        return (sign | 0x7c00) as u16;
+    }
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign as u16;
+        }
+        let mantissa = (mantissa | 0x0080_0000) >> (1 - exponent);
+        return (sign + ((mantissa + 0x1000) >> 13)) as u16;
+    }
+
+    // Rounding may carry into the exponent, which is the correct result
+    (sign + ((exponent as u32) << 10) + ((mantissa + 0x1000) >> 13)) as u16
+}
+
+#[derive(Debug)]
+struct UpdateState {
+    #[allow(dead_code)]
+    watcher: notify::RecommendedWatcher,
+    lut: Arc<Mutex<Option<Lut>>>,
+    has_changes: Arc<AtomicBool>,
+}
+
+/// A [`Lut`] loaded from disk that reloads whenever the file changes
+#[derive(Debug)]
+pub struct HotLut {
+    lut: Arc<Lut>,
+    update_state: UpdateState,
+}
+
+impl HotLut {
+    pub fn from_path(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let lut = Lut::from_path(&path)?;
+        let state = Arc::new(Mutex::new(None));
+        let has_changes = Arc::new(AtomicBool::new(false));
+
+        Ok(Self {
+            lut: Arc::new(lut),
+            update_state: UpdateState {
+                watcher: Self::setup_watcher(
+                    path,
+                    state.clone(),
+                    has_changes.clone(),
+                )?,
+                lut: state,
+                has_changes,
+            },
+        })
+    }
+
+    /// The current table. A new `Arc` is handed out after every reload so
+    /// GPU copies can be refreshed by comparing pointers
+    pub fn lut(&self) -> Arc<Lut> {
+        self.lut.clone()
+    }
+
+    /// Swaps in a reloaded LUT (if any). Returns true when the LUT changed
+    pub fn update(&mut self) -> bool {
+        if !self.update_state.has_changes.swap(false, Ordering::AcqRel) {
+            return false;
+        }
+
+        let new_lut = self
+            .update_state
+            .lut
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take());
+
+        match new_lut {
+            Some(lut) => {
+                self.lut = Arc::new(lut);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn setup_watcher(
+        path: PathBuf,
+        state: Arc<Mutex<Option<Lut>>>,
+        has_changes: Arc<AtomicBool>,
+    ) -> Result<notify::RecommendedWatcher, Box<dyn Error>> {
+        let path_to_watch = path.clone();
+
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let event: Event = match res {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+
+            if event.kind
+                != notify::EventKind::Modify(notify::event::ModifyKind::Data(
+                    notify::event::DataChange::Content,
+                ))
+            {
+                return;
+            }
+
+            info!("{:?} changed. Attempting to reload LUT.", path);
+
+            match Lut::from_path(&path) {
+                Ok(lut) => {
+                    if let Ok(mut guard) = state.lock() {
+                        has_changes.store(true, Ordering::Release);
+                        *guard = Some(lut);
+                    }
+                }
+                Err(e) => error!("{}", e),
+            }
+        })?;
+
+        watcher.watch(&path_to_watch, RecursiveMode::NonRecursive)?;
+
+        Ok(watcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    const INVERT_CUBE: &str = r#"
+# Inverts every channel
+TITLE "Invert"
+LUT_3D_SIZE 2
+
+1.0 1.0 1.0
+0.0 1.0 1.0
+1.0 0.0 1.0
+0.0 0.0 1.0
+1.0 1.0 0.0
+0.0 1.0 0.0
+1.0 0.0 0.0
+0.0 0.0 0.0
+"#;
+
+    #[test]
+    fn test_parse_and_sample() {
+        let lut = Lut::parse(INVERT_CUBE).unwrap();
+        assert_eq!(lut.size, 2);
+        let c = lut.sample(vec3(0.25, 0.5, 1.0));
+        assert_approx_eq!(c.x, 0.75);
+        assert_approx_eq!(c.y, 0.5);
+        assert_approx_eq!(c.z, 0.0);
+    }
+
+    #[test]
+    fn test_identity() {
+        let lut = Lut::identity(17);
+        let color = vec3(0.1, 0.62, 0.9);
+        let sampled = lut.sample(color);
+        assert_approx_eq!(sampled.x, color.x);
+        assert_approx_eq!(sampled.y, color.y);
+        assert_approx_eq!(sampled.z, color.z);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Lut::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut::parse("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(Lut::parse("0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_f16_bits() {
+        assert_eq!(f16_bits(0.0), 0x0000);
+        assert_eq!(f16_bits(1.0), 0x3c00);
+        assert_eq!(f16_bits(0.5), 0x3800);
+        assert_eq!(f16_bits(-2.0), 0xc000);
+        assert_eq!(f16_bits(65504.0), 0x7bff);
+        assert_eq!(f16_bits(1e6), 0x7c00);
+        assert_eq!(f16_bits(0.000_061_035_156), 0x0400);
+    }
+}
@@ -9,8 +9,10 @@ use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
 use std::thread;
+use tracing::trace_span;
 
 use super::prelude::*;
+use super::spans;
 
 static THREADS: LazyLock<
     Mutex<HashMap<ConnectionType, thread::JoinHandle<()>>>,
@@ -18,19 +20,33 @@ static THREADS: LazyLock<
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum ConnectionType {
+    Action,
     Clock,
     Control,
+    CueList,
+    Envelope,
     GlobalStartStop,
     Mapping,
+    Note,
+    SetList,
+    SysEx,
+    Trigger,
 }
 
 impl fmt::Display for ConnectionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ConnectionType::Action => write!(f, "Action"),
             ConnectionType::Clock => write!(f, "Clock"),
             ConnectionType::Control => write!(f, "Control"),
+            ConnectionType::CueList => write!(f, "CueList"),
+            ConnectionType::Envelope => write!(f, "Envelope"),
             ConnectionType::GlobalStartStop => write!(f, "GlobalStartStop"),
             ConnectionType::Mapping => write!(f, "Mapping"),
+            ConnectionType::Note => write!(f, "Note"),
+            ConnectionType::SetList => write!(f, "SetList"),
+            ConnectionType::SysEx => write!(f, "SysEx"),
+            ConnectionType::Trigger => write!(f, "Trigger"),
         }
     }
 }
@@ -43,9 +59,14 @@ pub fn on_message<F>(
 where
     F: Fn(u64, &[u8]) + Send + Sync + 'static,
 {
-    let midi_in = MidiInput::new(&connection_type.to_string())?;
+    let mut midi_in = MidiInput::new(&connection_type.to_string())?;
     let port = port.to_string();
 
+    // SysEx is ignored by default
+    if connection_type == ConnectionType::SysEx {
+        midi_in.ignore(Ignore::TimeAndActiveSense);
+    }
+
     let in_ports = midi_in.ports();
     let in_port = in_ports
         .iter()
@@ -73,7 +94,15 @@ where
                 &in_port,
                 &connection_name,
                 move |stamp, message, _| {
-                    trace!("MIDI message: {}, {:?}", stamp, message);
+                    let _span =
+                        trace_span!(target: spans::MIDI, "midi_message")
+                            .entered();
+                    tracing::debug!(
+                        target: spans::MIDI,
+                        stamp,
+                        bytes = ?message,
+                        "MIDI message"
+                    );
                     callback(stamp, message);
                 },
                 (),
@@ -116,6 +145,19 @@ pub fn disconnect(connection_type: ConnectionType) {
     }
 }
 
+/// Closes every input connection, waiting for each to be dropped so ports are
+/// released before the process exits
+pub fn disconnect_all() {
+    let handles: Vec<_> = THREADS.lock().unwrap().drain().collect();
+    for (connection_type, handle) in handles {
+        debug!("[disconnect_all] Closing {} connection", connection_type);
+        handle.thread().unpark();
+        if handle.join().is_err() {
+            error!("{} connection thread panicked", connection_type);
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub struct MidiOut {
     port: String,
@@ -152,6 +194,18 @@ impl MidiOut {
         }
         Ok(())
     }
+
+    /// Sends a complete SysEx message (including the leading `0xF0` and
+    /// trailing `0xF7`)
+    pub fn send_sysex(&mut self, message: &[u8]) -> Result<(), Box<dyn Error>> {
+        let Some(connection) = &mut self.connection else {
+            return Err(
+                "Midi ControlOut connection has not been established".into()
+            );
+        };
+        connection.send(message)?;
+        Ok(())
+    }
 }
 
 pub type PortIndexAndName = (usize, String);
@@ -194,3 +248,7 @@ pub fn print_ports() -> Result<(), Box<dyn Error>> {
 pub fn is_control_change(status: u8) -> bool {
     status & 0xF0 == 0xB0
 }
+
+pub fn is_note_on(status: u8) -> bool {
+    status & 0xF0 == 0x90
+}
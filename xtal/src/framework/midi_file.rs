@@ -0,0 +1,317 @@
+//! Standard MIDI File (`.mid`) reader used by the `midi_file` control type to
+//! play back automation authored in a DAW without the DAW connected. Only
+//! what is needed for control is kept: CC lanes and note on/off spans, both
+//! positioned in beats. Tempo changes are ignored on purpose – playback
+//! follows the sketch's timing source, so a file exported at any tempo lines
+//! up beat-for-beat.
+//!
+//! # Example
+//! ```rust,ignore
+//! let file = MidiFile::from_path(&to_absolute_path(file!(), "score.mid"))?;
+//! let beat = self.hub.animation.beats() % file.length;
+//!
+//! let cutoff = file.cc(0, 74, beat).unwrap_or(0.0);
+//! let kick = file.note(9, 36, beat);
+//! ```
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Debug, Default)]
+pub struct MidiFile {
+    /// Length in beats, rounded up to a whole bar of the file's first time
+    /// signature (4/4 if there is none) so that looping stays on the grid
+    pub length: f32,
+
+    /// `(channel, cc) => [(beat, value 0..1)]` sorted by beat
+    cc_lanes: HashMap<(u8, u8), Vec<(f32, f32)>>,
+
+    /// `(channel, note) => [(start beat, end beat)]` sorted by start
+    notes: HashMap<(u8, u8), Vec<(f32, f32)>>,
+}
+
+impl MidiFile {
+    pub fn from_path(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(path).map_err(|e| {
+            format!("Unable to read MIDI file {:?}: {}", path, e)
+        })?;
+        Self::parse(&bytes).map_err(|e| {
+            format!("Unable to parse MIDI file {:?}: {}", path, e).into()
+        })
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        let (kind, header) = reader.chunk()?;
+        if kind != *b"MThd" || header.len() < 6 {
+            return Err("Missing MThd header".into());
+        }
+        let track_count = u16::from_be_bytes([header[2], header[3]]);
+        let division = u16::from_be_bytes([header[4], header[5]]);
+        if division & 0x8000 != 0 {
+            return Err("SMPTE time division is not supported".into());
+        }
+        let ticks_per_beat = division.max(1) as f32;
+
+        let mut file = Self::default();
+        let mut end_tick = 0;
+        let mut bar_beats = None;
+
+        for _ in 0..track_count {
+            let (kind, track) = reader.chunk()?;
+            if kind != *b"MTrk" {
+                // Unknown chunks must be ignored per the spec
+                continue;
+            }
+
+            let mut track = Reader {
+                bytes: track,
+                position: 0,
+            };
+            let mut tick = 0;
+            let mut running_status = 0;
+            let mut open_notes: HashMap<(u8, u8), Vec<u32>> =
+                HashMap::default();
+
+            while !track.is_done() {
+                tick += track.variable_length()?;
+
+                let mut status = track.byte()?;
+                if status < 0x80 {
+                    // Running status: the byte just read is the first data
+                    // byte of a repeat of the previous message type
+                    status = running_status;
+                    track.position -= 1;
+                }
+
+                match status {
+                    0xff => {
+                        let kind = track.byte()?;
+                        let len = track.variable_length()? as usize;
+                        let data = track.take(len)?;
+                        // Time signatures with an impossible denominator are
+                        // skipped rather than trusted
+                        if kind == 0x58 && bar_beats.is_none() && len >= 2 {
+                            if let Some(denominator) =
+                                2_u32.checked_pow(data[1] as u32)
+                            {
+                                bar_beats = Some(
+                                    data[0] as f32 * 4.0 / denominator as f32,
+                                );
+                            }
+                        }
+                    }
+                    0xf0 | 0xf7 => {
+                        let len = track.variable_length()? as usize;
+                        track.take(len)?;
+                    }
+                    0x80..=0xef => {
+                        running_status = status;
+                        let channel = status & 0x0f;
+                        let data_len = ternary!(
+                            matches!(status & 0xf0, 0xc0 | 0xd0),
+                            1,
+                            2
+                        );
+                        let data = track.take(data_len)?;
+
+                        match (status & 0xf0, data) {
+                            (0xb0, [cc, value]) => file
+                                .cc_lanes
+                                .entry((channel, *cc))
+                                .or_default()
+                                .push((
+                                    tick as f32 / ticks_per_beat,
+                                    *value as f32 / 127.0,
+                                )),
+                            (0x90, [note, velocity]) if *velocity > 0 => {
+                                open_notes
+                                    .entry((channel, *note))
+                                    .or_default()
+                                    .push(tick);
+                            }
+                            (0x80 | 0x90, [note, _]) => {
+                                let key = (channel, *note);
+                                let start = open_notes
+                                    .get_mut(&key)
+                                    .filter(|starts| !starts.is_empty())
+                                    .map(|starts| starts.remove(0));
+                                if let Some(start) = start {
+                                    file.notes.entry(key).or_default().push((
+                                        start as f32 / ticks_per_beat,
+                                        tick as f32 / ticks_per_beat,
+                                    ));
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Unexpected status byte {:#x}",
+                            status
+                        )
+                        .into());
+                    }
+                }
+            }
+
+            // Notes left hanging last until the end of their track
+            for (key, starts) in open_notes {
+                for start in starts {
+                    file.notes.entry(key).or_default().push((
+                        start as f32 / ticks_per_beat,
+                        tick as f32 / ticks_per_beat,
+                    ));
+                }
+            }
+
+            end_tick = end_tick.max(tick);
+        }
+
+        for lane in file.cc_lanes.values_mut() {
+            lane.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        for spans in file.notes.values_mut() {
+            spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        let bar_beats = bar_beats.unwrap_or(4.0);
+        file.length =
+            (end_tick as f32 / ticks_per_beat / bar_beats).ceil() * bar_beats;
+
+        Ok(file)
+    }
+
+    /// The most recent value (0..1) of a CC lane at `beat`, or `None` if the
+    /// lane doesn't exist or hasn't started yet. Channels are zero-indexed
+    pub fn cc(&self, channel: u8, cc: u8, beat: f32) -> Option<f32> {
+        let lane = self.cc_lanes.get(&(channel, cc))?;
+        let index = lane.partition_point(|(b, _)| *b <= beat);
+        ternary!(index == 0, None, Some(lane[index - 1].1))
+    }
+
+    /// Whether a note is held at `beat`. Channels are zero-indexed
+    pub fn note(&self, channel: u8, note: u8, beat: f32) -> bool {
+        self.notes.get(&(channel, note)).is_some_and(|spans| {
+            spans
+                .iter()
+                .take_while(|(start, _)| *start <= beat)
+                .any(|(_, end)| beat < *end)
+        })
+    }
+}
+
+/// A chunk's 4 byte type and its contents
+type Chunk<'a> = ([u8; 4], &'a [u8]);
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_done(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self.position + len;
+        let bytes =
+            self.bytes.get(self.position..end).ok_or("Unexpected EOF")?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Box<dyn Error>> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn chunk(&mut self) -> Result<Chunk<'a>, Box<dyn Error>> {
+        let kind = self.take(4)?;
+        let len = u32::from_be_bytes(self.take(4)?.try_into()?) as usize;
+        Ok(([kind[0], kind[1], kind[2], kind[3]], self.take(len)?))
+    }
+
+    /// MIDI's big-endian base-128 integers (at most 4 bytes)
+    fn variable_length(&mut self) -> Result<u32, Box<dyn Error>> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let byte = self.byte()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid variable length quantity".into())
+    }
+}
+
+#[cfg(test)]
+pub mod midi_file_tests {
+    use super::*;
+
+    /// A one track, 96 PPQ file: CC 74 on channel 0 ramps 0 → 127 at beat 1,
+    /// and note 36 on channel 9 plays from beat 0 to 0.5 and again from beat
+    /// 2 to 3 (using running status and a velocity-0 note off). Ends at beat
+    /// 5 so it rounds up to two 4/4 bars
+    pub fn fixture() -> Vec<u8> {
+        let events: &[u8] = &[
+            0x00, 0xff, 0x58, 0x04, 0x04, 0x02, 0x18, 0x08, // 4/4
+            0x00, 0xb0, 74, 0, // CC 74 = 0
+            0x00, 0x99, 36, 100, // note on
+            0x30, 0x89, 36, 0, // note off at beat 0.5
+            0x30, 0xb0, 74, 127, // CC 74 = 127 at beat 1
+            0x60, 0x99, 36, 90, // note on at beat 2
+            0x60, 36, 0, // running status note off at beat 3
+            0x81, 0x40, 0xff, 0x2f, 0x00, // end of track at beat 5
+        ];
+
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend([0, 0, 0, 6, 0, 0, 0, 1, 0, 96]);
+        bytes.extend(b"MTrk");
+        bytes.extend((events.len() as u32).to_be_bytes());
+        bytes.extend(events);
+        bytes
+    }
+
+    #[test]
+    fn test_parse() {
+        let file = MidiFile::parse(&fixture()).unwrap();
+        assert_eq!(file.length, 8.0);
+
+        assert_eq!(file.cc(0, 74, 0.5), Some(0.0));
+        assert_eq!(file.cc(0, 74, 1.0), Some(1.0));
+        assert_eq!(file.cc(0, 74, 7.0), Some(1.0));
+        assert_eq!(file.cc(1, 74, 1.0), None);
+
+        assert!(file.note(9, 36, 0.0));
+        assert!(!file.note(9, 36, 0.5));
+        assert!(file.note(9, 36, 2.5));
+        assert!(!file.note(9, 36, 3.0));
+        assert!(!file.note(9, 37, 0.0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(MidiFile::parse(b"nope").is_err());
+
+        let mut smpte = fixture();
+        smpte[12] = 0xe7;
+        assert!(MidiFile::parse(&smpte).is_err());
+
+        let truncated = fixture();
+        assert!(MidiFile::parse(&truncated[..truncated.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_time_signature() {
+        let mut bytes = fixture();
+        bytes[27] = 0x40;
+        assert_eq!(MidiFile::parse(&bytes).unwrap().length, 8.0);
+    }
+}
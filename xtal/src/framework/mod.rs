@@ -1,14 +1,66 @@
 pub mod audio;
+#[cfg(any(test, feature = "bench"))]
+pub mod benchmarks;
+#[cfg(feature = "runtime")]
+pub mod calibration;
+#[cfg(feature = "runtime")]
+pub mod camera;
+#[cfg(feature = "runtime")]
+pub mod color;
 pub mod control;
+#[cfg(feature = "runtime")]
+pub mod depth;
+#[cfg(feature = "runtime")]
+pub mod displacer;
+#[cfg(feature = "runtime")]
+pub mod flocking;
+#[cfg(feature = "runtime")]
+pub mod frame_capture;
 pub mod frame_controller;
+#[cfg(feature = "runtime")]
+pub mod geometry;
+#[cfg(feature = "runtime")]
 pub mod gpu;
+#[cfg(feature = "runtime")]
+pub mod gpu_cache;
+#[cfg(feature = "runtime")]
+pub mod gpu_stats;
+#[cfg(any(feature = "runtime", feature = "instrumentation"))]
 pub mod instrumentation;
 pub mod logging;
+#[cfg(feature = "runtime")]
+pub mod lut;
 pub mod midi;
+pub mod midi_file;
 pub mod motion;
 pub mod noise;
 pub mod osc_receiver;
+#[cfg(feature = "runtime")]
+pub mod optical_flow;
+#[cfg(feature = "runtime")]
+pub mod palette;
+#[cfg(feature = "runtime")]
+pub mod physarum;
+#[cfg(feature = "runtime")]
+pub mod physics;
+#[cfg(feature = "runtime")]
+pub mod post;
 pub mod prelude;
+#[cfg(feature = "runtime")]
+pub mod reaction_diffusion;
+pub mod rng;
+#[cfg(feature = "runtime")]
 pub mod sketch;
+pub mod spans;
+#[cfg(feature = "runtime")]
+pub mod stereo;
+pub mod sysex;
+#[cfg(feature = "runtime")]
+pub mod tonemap;
+#[cfg(feature = "runtime")]
+pub mod typography;
 pub mod util;
+#[cfg(feature = "runtime")]
+pub mod warp;
+#[cfg(feature = "runtime")]
 pub mod window_rect;
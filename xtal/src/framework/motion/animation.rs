@@ -1,12 +1,14 @@
 //! Animation module providing musically-timed animation and transition methods
 
-use nannou::math::map_range;
-use nannou::rand::rngs::StdRng;
-use nannou::rand::{Rng, SeedableRng};
-use std::cell::RefCell;
+use nannou_core::math::map_range;
+use nannou_core::rand::rngs::StdRng;
+use nannou_core::rand::{Rng, SeedableRng};
+use std::cell::{Cell, RefCell};
 use std::str::FromStr;
 
 use crate::framework::frame_controller;
+#[cfg(feature = "instrumentation")]
+use crate::framework::instrumentation::Instrumentation;
 use crate::framework::prelude::*;
 
 /// Data structure used in conjunction with
@@ -295,6 +297,10 @@ impl FromStr for Mode {
 pub struct Animation<T: TimingSource> {
     pub timing: T,
     random_smooth_previous_values: RefCell<HashMap<u64, f32>>,
+    #[cfg(feature = "instrumentation")]
+    instrumentation: RefCell<Instrumentation>,
+    /// See [`Self::with_beats`]
+    pinned_beats: Cell<Option<f32>>,
 }
 
 impl<T: TimingSource> Animation<T> {
@@ -302,13 +308,45 @@ impl<T: TimingSource> Animation<T> {
         Self {
             timing,
             random_smooth_previous_values: RefCell::new(HashMap::default()),
+            #[cfg(feature = "instrumentation")]
+            instrumentation: RefCell::new(Instrumentation::new(
+                "Animation::automate",
+            )),
+            pinned_beats: Cell::new(None),
         }
     }
 
     /// Return the number of beats that have elapsed
     /// since (re)start of this Animation's Timing source
     pub fn beats(&self) -> f32 {
-        self.timing.beats()
+        self.pinned_beats
+            .get()
+            .unwrap_or_else(|| self.timing.beats())
+    }
+
+    /// Runs `f` with every animation method evaluated at `beats` instead of
+    /// the timing source's current beat, e.g. so everything read in one frame
+    /// sees the same beat, or to keep a single lane at a recalled phase
+    pub fn with_beats<R>(&self, beats: f32, f: impl FnOnce() -> R) -> R {
+        let previous = self.pinned_beats.replace(Some(beats));
+        let result = f();
+        self.pinned_beats.set(previous);
+        result
+    }
+
+    /// Runs `f` and then restores the memory [`Self::random_slewed`] slews
+    /// from, so sampling ahead of time doesn't disturb live output
+    pub fn preserving_state<R>(&self, f: impl FnOnce() -> R) -> R {
+        let previous = self.random_smooth_previous_values.borrow().clone();
+        let result = f();
+        *self.random_smooth_previous_values.borrow_mut() = previous;
+        result
+    }
+
+    /// [`Self::beats`] at full precision, for lining up audio-rate code with
+    /// animations. See [`SampleBeats`]
+    pub fn precise_beats(&self) -> f64 {
+        self.timing.precise_beats()
     }
 
     /// Convert `beats` to frame count
@@ -369,9 +407,10 @@ impl<T: TimingSource> Animation<T> {
     ) -> f32 {
         let beats = self.beats() - delay;
         let loop_count = ternary!(beats < 0.0, 0.0, (beats / duration).floor());
-        let seed = stem + ((duration + (max - min) + loop_count) as u64);
+        let seed =
+            stem.wrapping_add((duration + (max - min) + loop_count) as u64);
         let mut rng = StdRng::seed_from_u64(seed);
-        rng.gen_range(min..=max)
+        random_between(&mut rng, min, max)
     }
 
     /// Generate a randomized value once during every cycle of `duration`. The
@@ -392,13 +431,14 @@ impl<T: TimingSource> Animation<T> {
     ) -> f32 {
         let beats = self.beats() - delay;
         let loop_count = ternary!(beats < 0.0, 0.0, (beats / duration).floor());
-        let seed = stem + ((duration + (max - min) + loop_count) as u64);
+        let seed =
+            stem.wrapping_add((duration + (max - min) + loop_count) as u64);
         let mut rng = StdRng::seed_from_u64(seed);
-        let value = rng.gen_range(min..=max);
+        let value = random_between(&mut rng, min, max);
 
         // Ensures two different calls that share the same seed but differ in
         // delay have the same overall pattern
-        let key = stem + (delay.to_bits() as u64 * 10_000_000);
+        let key = stem.wrapping_add(delay.to_bits() as u64 * 10_000_000);
 
         let mut prev_values = self.random_smooth_previous_values.borrow_mut();
         let value = prev_values.get(&key).map_or(value, |prev| {
@@ -456,6 +496,19 @@ impl<T: TimingSource> Animation<T> {
     ///
     /// [breakpoints]: https://github.com/Lokua/xtal/blob/main/src/sketches/breakpoints.rs
     pub fn automate(&self, breakpoints: &[Breakpoint], mode: Mode) -> f32 {
+        #[cfg(feature = "instrumentation")]
+        let start = self.instrumentation.borrow().start();
+        let value = self.evaluate_breakpoints(breakpoints, mode);
+        #[cfg(feature = "instrumentation")]
+        self.instrumentation.borrow_mut().record(start);
+        value
+    }
+
+    fn evaluate_breakpoints(
+        &self,
+        breakpoints: &[Breakpoint],
+        mode: Mode,
+    ) -> f32 {
         assert!(!breakpoints.is_empty(), "At least 1 breakpoint is required");
         assert!(
             breakpoints[0].position == 0.0,
@@ -578,7 +631,7 @@ impl<T: TimingSource> Animation<T> {
                         + loop_count) as u64;
                     let mut rng = StdRng::seed_from_u64(seed);
                     let y = p1.value;
-                    rng.gen_range(y - amplitude..=y + amplitude)
+                    random_between(&mut rng, y - amplitude, y + amplitude)
                 }
                 Kind::RandomSmooth {
                     frequency,
@@ -615,9 +668,8 @@ impl<T: TimingSource> Animation<T> {
 
                     constrain.apply(value + random_mapped)
                 }
-                Kind::End => {
-                    panic!("Somehow we've moved beyond the end")
-                }
+                // Only reachable when an `End` is placed mid-sequence
+                Kind::End => p1.value,
             },
             _ => {
                 warn_once!("Could not match breakpoint {:?}", breakpoint);
@@ -638,6 +690,16 @@ impl<T: TimingSource> Animation<T> {
     }
 }
 
+/// Like `rng.gen_range(min..=max)` but tolerates reversed and non-finite
+/// bounds (which hot params can easily produce) instead of panicking
+fn random_between(rng: &mut StdRng, a: f32, b: f32) -> f32 {
+    let (min, max) = (a.min(b), a.max(b));
+    if !(max - min).is_finite() {
+        return ternary!(min.is_finite(), min, 0.0);
+    }
+    rng.gen_range(min..=max)
+}
+
 #[cfg(test)]
 pub mod animation_tests {
     use super::*;
@@ -845,6 +907,19 @@ pub mod animation_tests {
         assert_ne!(n, n5, "should return new number on next cycle");
     }
 
+    #[test]
+    #[serial]
+    fn test_random_tolerates_bad_ranges() {
+        init(0);
+        let a = create_instance();
+
+        let n = a.random(1.0, (1.0, 0.0), 0.0, 999);
+        assert!((0.0..=1.0).contains(&n), "reversed range");
+
+        let n = a.random(1.0, (f32::NAN, f32::INFINITY), 0.0, 999);
+        assert_eq!(n, 0.0, "non-finite range");
+    }
+
     #[test]
     #[serial]
     fn test_random_with_delay() {
@@ -210,7 +210,9 @@ impl FromStr for Easing {
             "ease_in_out_bounce" => Ok(Self::EaseInOutBounce),
             "logarithmic" => Ok(Self::Logarithmic),
 
-            "custom" => unimplemented!(),
+            "custom" => {
+                Err("`custom` easing can only be set from code".to_string())
+            }
 
             "exponential" => Ok(Self::Exponential(2.0)),
             "curve" => Ok(Self::Curve(2.0, 5.0)),
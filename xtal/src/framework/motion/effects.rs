@@ -7,11 +7,11 @@ use std::cell::RefCell;
 use std::f32::consts::{FRAC_PI_2, PI};
 use std::str::FromStr;
 
-use nannou::math::map_range;
+use nannou_core::math::map_range;
 
 use crate::framework::prelude::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Effect {
     Constrain(Constrain),
     Hysteresis(Hysteresis),
@@ -228,6 +228,83 @@ impl Default for Map {
     }
 }
 
+/// The domain and range an effect assumes its input and output occupy
+#[derive(Debug, Clone)]
+pub enum EffectRange {
+    Fixed(f32, f32),
+    Adaptive(AdaptiveRange),
+}
+
+impl EffectRange {
+    /// The `(min, max)` to process `input` with. Adaptive ranges are updated
+    /// with `input` first so it always falls within them
+    pub fn bounds(&self, input: f32) -> (f32, f32) {
+        match self {
+            Self::Fixed(min, max) => (*min, *max),
+            Self::Adaptive(adaptive) => adaptive.update(input),
+        }
+    }
+}
+
+impl From<(f32, f32)> for EffectRange {
+    fn from((min, max): (f32, f32)) -> Self {
+        Self::Fixed(min, max)
+    }
+}
+
+/// Follows the running min and max of a signal for effects whose upstream
+/// range changes over time. Each update the bounds relax toward the latest
+/// input by `decay` so the range can shrink back after an outlier. Since the
+/// state lives with the effect, an adaptive effect shared by several sources
+/// tracks all of them together
+#[derive(Debug, Clone)]
+pub struct AdaptiveRange {
+    /// Fraction of the distance to the input the bounds close per update,
+    /// though never past where the opposite bound was. 0.0 never forgets, 1.0
+    /// shrinks the range to the span of the latest two inputs
+    pub decay: f32,
+    bounds: RefCell<Option<(f32, f32)>>,
+}
+
+impl AdaptiveRange {
+    pub const DEFAULT_DECAY: f32 = 0.01;
+
+    pub fn new(decay: f32) -> Self {
+        Self {
+            decay: decay.clamp(0.0, 1.0),
+            bounds: RefCell::new(None),
+        }
+    }
+
+    pub fn update(&self, input: f32) -> (f32, f32) {
+        let mut bounds = self.bounds.borrow_mut();
+
+        let (min, max) = bounds.map_or((input, input), |(min, max)| {
+            (
+                ternary!(
+                    input < min,
+                    input,
+                    lerp(min, input, self.decay).min(max)
+                ),
+                ternary!(
+                    input > max,
+                    input,
+                    lerp(max, input, self.decay).max(min)
+                ),
+            )
+        });
+
+        *bounds = Some((min, max));
+        (min, max)
+    }
+}
+
+impl Default for AdaptiveRange {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DECAY)
+    }
+}
+
 /// Discretizes continuous input values into fixed steps, creating stair-case
 /// transitions.
 ///
@@ -241,23 +318,26 @@ pub struct Quantizer {
     pub step: f32,
 
     /// The (assumed) domain and range of the input and output signal
-    range: (f32, f32),
+    range: EffectRange,
 }
 
 impl Quantizer {
-    pub fn new(step: f32, range: (f32, f32)) -> Self {
-        Self { step, range }
+    pub fn new(step: f32, range: impl Into<EffectRange>) -> Self {
+        Self {
+            step,
+            range: range.into(),
+        }
     }
 
     pub fn apply(&self, input: f32) -> f32 {
-        let (min, max) = self.range;
+        let (min, max) = self.range.bounds(input);
         let steps_from_zero = (input / self.step).round();
         let quantized = steps_from_zero * self.step;
         quantized.clamp(min, max)
     }
 
-    pub fn set_range(&mut self, range: (f32, f32)) {
-        self.range = range;
+    pub fn set_range(&mut self, range: impl Into<EffectRange>) {
+        self.range = range.into();
     }
 }
 
@@ -265,7 +345,7 @@ impl Default for Quantizer {
     fn default() -> Self {
         Self {
             step: 0.25,
-            range: (0.0, 1.0),
+            range: EffectRange::Fixed(0.0, 1.0),
         }
     }
 }
@@ -506,7 +586,7 @@ pub struct WaveFolder {
     pub shape: f32,
 
     /// The (assumed) domain and range of the input and output signal
-    range: (f32, f32),
+    range: EffectRange,
 }
 
 impl WaveFolder {
@@ -516,7 +596,7 @@ impl WaveFolder {
         symmetry: f32,
         bias: f32,
         shape: f32,
-        range: (f32, f32),
+        range: impl Into<EffectRange>,
     ) -> Self {
         WaveFolder {
             gain,
@@ -524,24 +604,26 @@ impl WaveFolder {
             symmetry,
             bias,
             shape,
-            range,
+            range: range.into(),
         }
     }
 
     pub fn apply(&self, input: f32) -> f32 {
+        let bounds = self.range.bounds(input);
         let mut output = input;
         for _ in 0..self.iterations {
-            output = self.fold_once(output);
+            output = self.fold_once(output, bounds);
         }
         output
     }
 
-    pub fn set_range(&mut self, range: (f32, f32)) {
-        self.range = range;
+    pub fn set_range(&mut self, range: impl Into<EffectRange>) {
+        self.range = range.into();
     }
 
-    fn fold_once(&self, input: f32) -> f32 {
-        if self.gain < 1.0 {
+    fn fold_once(&self, input: f32, (min, max): (f32, f32)) -> f32 {
+        // An adaptive range is empty until the input starts moving
+        if self.gain < 1.0 || max - min <= f32::EPSILON {
             return input;
         }
         // Comments assume the following settings unless noted otherwise:
@@ -552,8 +634,6 @@ impl WaveFolder {
         // - symmetry: 1.0 (symmetric)
         // - shape: 0.0 (linear)
         // ---------------------
-        let (min, max) = self.range;
-
         let range = max - min; // 1.0
 
         // Center around 0.0 by subtracting the midpoint
@@ -637,7 +717,7 @@ impl Default for WaveFolder {
             bias: 0.0,
             // Linear folding
             shape: 0.0,
-            range: (0.0, 1.0),
+            range: EffectRange::Fixed(0.0, 1.0),
         }
     }
 }
@@ -654,6 +734,8 @@ pub fn equal_power_crossfade(a: f32, b: f32, mix: f32) -> f32 {
 
 #[cfg(test)]
 mod tests {
+    use super::AdaptiveRange;
+    use super::EffectRange;
     use super::Quantizer;
     use super::Saturator;
     use super::WaveFolder;
@@ -694,6 +776,34 @@ mod tests {
         assert_approx_eq!(quantizer.apply(0.95), 1.0);
     }
 
+    #[test]
+    fn test_adaptive_range() {
+        let range = AdaptiveRange::new(0.5);
+        assert_eq!(range.update(0.0), (0.0, 0.0));
+        assert_eq!(range.update(4.0), (0.0, 4.0));
+        // Bounds relax halfway toward the input each update
+        assert_eq!(range.update(2.0), (1.0, 3.0));
+        assert_eq!(range.update(-1.0), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn test_wave_folder_adaptive_range() {
+        let wf = WaveFolder::new(
+            2.0,
+            1,
+            1.0,
+            0.0,
+            0.0,
+            EffectRange::Adaptive(AdaptiveRange::new(0.0)),
+        );
+        // Passes through until the range opens up
+        assert_approx_eq!(wf.apply(10.0), 10.0);
+        // Then folds; at 2x gain the bottom of [0, 10] folds to the middle
+        assert_approx_eq!(wf.apply(0.0), 5.0);
+        // Same as the comments case scaled to [0, 10]
+        assert_approx_eq!(wf.apply(7.0), 9.0);
+    }
+
     #[test]
     fn test_saturator_center_unchanged() {
         let saturator = Saturator::default();
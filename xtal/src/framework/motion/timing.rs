@@ -19,10 +19,10 @@
 //! timing via `beats` (TODO: beats documentation)
 //!
 //! When running a xtal app you can pass a `timing` positional argument after
-//! the required `sketch` positional argument to specify what kind of timing
-//! system will be used to run animations on sketches that support it. Available
-//! options that are directly correlated to the TimingSource implementations in
-//! this module include:
+//! the `sketch` positional argument to specify what kind of timing system will
+//! be used to run animations on sketches that support it. Available options
+//! that are directly correlated to the TimingSource implementations in this
+//! module include:
 //!
 //! ## `frame`
 //!
@@ -54,12 +54,12 @@
 
 use nannou_osc as osc;
 use std::{
-    env,
     error::Error,
     sync::{
-        Arc,
+        Arc, Mutex,
         atomic::{AtomicBool, AtomicU32, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use crate::framework::frame_controller;
@@ -89,6 +89,75 @@ impl Bpm {
 pub trait TimingSource: Clone {
     fn beats(&self) -> f32;
     fn bpm(&self) -> f32;
+
+    /// [`Self::beats`] without the loss of precision of an `f32` over long
+    /// sessions. Sources that only advance once per frame also interpolate
+    /// between frames so audio-rate consumers don't see the beat move in steps.
+    /// See [`SampleBeats`]
+    fn precise_beats(&self) -> f64 {
+        self.beats() as f64
+    }
+
+    /// Starts listening to the external clock of sources that have one. Only
+    /// needed for sources built without their inputs, see
+    /// `global::without_inputs`
+    fn connect(&self) {}
+}
+
+/// Maps sample indices within an audio buffer to beats so audio-rate code
+/// (envelope followers, synthesis) lines up exactly with animations driven by
+/// the same [`TimingSource`]. Create one at the start of each buffer:
+///
+/// ```rust
+/// let beats = SampleBeats::from_timing(&hub.animation.timing, sample_rate);
+/// for (i, sample) in buffer.iter_mut().enumerate() {
+///     let phase = beats.beat_at(i) % 1.0;
+///     // ...
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleBeats {
+    start: f64,
+    beats_per_sample: f64,
+}
+
+impl SampleBeats {
+    /// `start` is the beat of the buffer's first sample
+    pub fn new(start: f64, bpm: f32, sample_rate: u32) -> Self {
+        Self {
+            start,
+            beats_per_sample: bpm as f64 / 60.0 / sample_rate.max(1) as f64,
+        }
+    }
+
+    /// Starts the buffer at the current beat of `timing`
+    pub fn from_timing(timing: &impl TimingSource, sample_rate: u32) -> Self {
+        Self::new(timing.precise_beats(), timing.bpm(), sample_rate)
+    }
+
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    pub fn beats_per_sample(&self) -> f64 {
+        self.beats_per_sample
+    }
+
+    pub fn beat_at(&self, sample: usize) -> f64 {
+        self.start + sample as f64 * self.beats_per_sample
+    }
+
+    /// The fractional sample index at which `beat` falls. Negative or past the
+    /// end of the buffer when `beat` is outside of it
+    pub fn sample_at(&self, beat: f64) -> f64 {
+        (beat - self.start) / self.beats_per_sample
+    }
+
+    /// Moves the start to the sample after a buffer of `samples` length, for
+    /// consumers that would rather free-run between reads of the timing source
+    pub fn advance(&mut self, samples: usize) {
+        self.start = self.beat_at(samples);
+    }
 }
 
 /// Wrapper for all [`TimingSource`] implementations which allows run-time
@@ -108,9 +177,8 @@ pub enum Timing {
 
 impl Timing {
     pub fn new(bpm: Bpm) -> Self {
-        let args: Vec<String> = env::args().collect();
-        let timing_arg = args.get(2).map(|s| s.as_str()).unwrap_or("frame");
-        let timing = match timing_arg {
+        let timing_arg = crate::global::timing_source();
+        let timing = match timing_arg.as_str() {
             "osc" => Timing::Osc(OscTransportTiming::new(bpm)),
             "midi" => Timing::Midi(MidiSongTiming::new(bpm)),
             "hybrid" => Timing::Hybrid(HybridTiming::new(bpm)),
@@ -141,6 +209,26 @@ impl TimingSource for Timing {
             Timing::Manual(t) => t.beats(),
         }
     }
+
+    fn precise_beats(&self) -> f64 {
+        match self {
+            Timing::Frame(t) => t.precise_beats(),
+            Timing::Osc(t) => t.precise_beats(),
+            Timing::Midi(t) => t.precise_beats(),
+            Timing::Hybrid(t) => t.precise_beats(),
+            Timing::Manual(t) => t.precise_beats(),
+        }
+    }
+
+    fn connect(&self) {
+        match self {
+            Timing::Frame(t) => t.connect(),
+            Timing::Osc(t) => t.connect(),
+            Timing::Midi(t) => t.connect(),
+            Timing::Hybrid(t) => t.connect(),
+            Timing::Manual(t) => t.connect(),
+        }
+    }
 }
 
 /// Uses an internal frame counter coupled with the app's current BPM to provide
@@ -167,6 +255,14 @@ impl TimingSource for FrameTiming {
         let frames_per_beat = seconds_per_beat * frame_controller::fps();
         frame_controller::frame_count() as f32 / frames_per_beat
     }
+
+    fn precise_beats(&self) -> f64 {
+        let seconds_per_beat = 60.0 / self.bpm.get() as f64;
+        let frames_per_beat = seconds_per_beat * frame_controller::fps() as f64;
+        let frames = frame_controller::frame_count() as f64
+            + frame_controller::frame_progress() as f64;
+        frames / frames_per_beat
+    }
 }
 
 pub const CLOCK: u8 = 0xF8; // 248
@@ -178,12 +274,45 @@ pub const SONG_POSITION: u8 = 0xF2; // 242
 const PULSES_PER_QUARTER_NOTE: u32 = 24;
 const TICKS_PER_QUARTER_NOTE: u32 = 960;
 
+/// Gaps between CLOCK messages longer than this (a pulse at 25 BPM) are the
+/// clock stalling rather than its rate
+const MAX_PULSE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How far toward the next pulse interpolation may go before it arrives
+const MAX_PULSE_PROGRESS: f64 = 0.999;
+
+/// The most recent CLOCK message and the measured time between pulses
+#[derive(Clone, Copy, Debug)]
+struct ClockPulse {
+    at: Instant,
+    interval: Option<Duration>,
+}
+
+impl ClockPulse {
+    fn received(previous: Option<Self>, at: Instant) -> Self {
+        let interval = previous.and_then(|previous| {
+            let interval = at.duration_since(previous.at);
+            if interval.is_zero() || interval > MAX_PULSE_INTERVAL {
+                return previous.interval;
+            }
+            // Smooth out jitter in when messages arrive
+            Some(previous.interval.map_or(interval, |average| {
+                average.mul_f64(0.75) + interval.mul_f64(0.25)
+            }))
+        });
+        Self { at, interval }
+    }
+}
+
 /// Provides timing by following an internal MIDI clock with optional syncing to
 /// Song Position Pointer messages.
 #[derive(Clone, Debug)]
 pub struct MidiSongTiming {
     clock_count: Arc<AtomicU32>,
 
+    /// When the most recent CLOCK arrived. See [`Self::pulse_progress`]
+    last_clock: Arc<Mutex<Option<ClockPulse>>>,
+
     /// When true, clock works as a subdivision of song_position; when false,
     /// clock is "absolute" and only reset upon receiving START. See
     /// [`HybridTiming`] for a combination of MTC and this struct for high
@@ -199,12 +328,15 @@ impl MidiSongTiming {
     pub fn new(bpm: Bpm) -> Self {
         let timing = Self {
             clock_count: Arc::new(AtomicU32::default()),
+            last_clock: Arc::new(Mutex::new(None)),
             follow_song_position_messages: true,
             song_position: Arc::new(AtomicU32::default()),
             bpm,
         };
 
-        timing.setup_midi_listener();
+        if !crate::global::inputs_deferred() {
+            timing.setup_midi_listener();
+        }
         timing
     }
 
@@ -212,12 +344,15 @@ impl MidiSongTiming {
     pub fn new_no_song_position(bpm: Bpm) -> Self {
         let timing = Self {
             clock_count: Arc::new(AtomicU32::default()),
+            last_clock: Arc::new(Mutex::new(None)),
             follow_song_position_messages: false,
             song_position: Arc::new(AtomicU32::default()),
             bpm,
         };
 
-        timing.setup_midi_listener();
+        if !crate::global::inputs_deferred() {
+            timing.setup_midi_listener();
+        }
         timing
     }
 
@@ -231,6 +366,7 @@ impl MidiSongTiming {
         };
 
         let clock_count = self.clock_count.clone();
+        let last_clock = self.last_clock.clone();
         let song_position = self.song_position.clone();
         let follow_song_position_messages = self.follow_song_position_messages;
 
@@ -244,6 +380,11 @@ impl MidiSongTiming {
                 match message[0] {
                     CLOCK => {
                         clock_count.fetch_add(1, Ordering::SeqCst);
+                        let mut last_clock = last_clock.lock().unwrap();
+                        *last_clock = Some(ClockPulse::received(
+                            *last_clock,
+                            Instant::now(),
+                        ));
                     }
                     SONG_POSITION => {
                         if !follow_song_position_messages {
@@ -279,12 +420,14 @@ impl MidiSongTiming {
                     START => {
                         debug!("Received START message");
                         clock_count.store(0, Ordering::SeqCst);
+                        *last_clock.lock().unwrap() = None;
                     }
                     CONTINUE => {
                         debug!("Received CONTINUE message");
                     }
                     STOP => {
                         debug!("Received STOP message");
+                        *last_clock.lock().unwrap() = None;
                     }
                     _ => {}
                 }
@@ -303,18 +446,43 @@ impl MidiSongTiming {
     }
 
     fn beats(&self) -> f32 {
-        let clock_offset = self.clock_count.load(Ordering::Relaxed) as f32
-            / PULSES_PER_QUARTER_NOTE as f32;
+        self.precise_beats() as f32
+    }
+
+    fn precise_beats(&self) -> f64 {
+        let pulses = self.clock_count.load(Ordering::Relaxed) as f64
+            + self.pulse_progress();
+        let clock_offset = pulses / PULSES_PER_QUARTER_NOTE as f64;
 
         if self.follow_song_position_messages {
             let ticks = self.song_position.load(Ordering::Relaxed);
-            let beats = ticks as f32 / TICKS_PER_QUARTER_NOTE as f32;
+            let beats = ticks as f64 / TICKS_PER_QUARTER_NOTE as f64;
 
             beats + clock_offset
         } else {
             clock_offset
         }
     }
+
+    /// How far [0, 1) the clock is toward its next pulse, estimated from the
+    /// time since the last one and the measured rate of the clock (the
+    /// configured BPM until two pulses have arrived). Without this beats would
+    /// only move 24 times per quarter note and anything captured at a higher
+    /// rate than that would show steps
+    fn pulse_progress(&self) -> f64 {
+        let Some(pulse) = *self.last_clock.lock().unwrap() else {
+            return 0.0;
+        };
+        let pulse_seconds = pulse.interval.map_or_else(
+            || {
+                60.0 / (self.bpm.get().max(1.0) as f64
+                    * PULSES_PER_QUARTER_NOTE as f64)
+            },
+            |interval| interval.as_secs_f64(),
+        );
+        (pulse.at.elapsed().as_secs_f64() / pulse_seconds)
+            .clamp(0.0, MAX_PULSE_PROGRESS)
+    }
 }
 
 impl TimingSource for MidiSongTiming {
@@ -322,9 +490,17 @@ impl TimingSource for MidiSongTiming {
         self.bpm.get()
     }
 
+    fn connect(&self) {
+        self.setup_midi_listener();
+    }
+
     fn beats(&self) -> f32 {
         self.beats()
     }
+
+    fn precise_beats(&self) -> f64 {
+        self.precise_beats()
+    }
 }
 
 const MTC_QUARTER_FRAME: u8 = 0xF1;
@@ -358,7 +534,9 @@ impl HybridTiming {
             frames: Arc::new(AtomicU32::default()),
         };
 
-        timing.setup_mtc_listener();
+        if !crate::global::inputs_deferred() {
+            timing.setup_mtc_listener();
+        }
         timing
     }
 
@@ -526,9 +704,18 @@ impl TimingSource for HybridTiming {
         self.bpm.get()
     }
 
+    fn connect(&self) {
+        self.midi_timing.connect();
+        self.setup_mtc_listener();
+    }
+
     fn beats(&self) -> f32 {
         self.beats()
     }
+
+    fn precise_beats(&self) -> f64 {
+        self.midi_timing.precise_beats()
+    }
 }
 
 /// Uses the Open Sound Protocol to sync with Ableton Live via the
@@ -560,9 +747,11 @@ impl OscTransportTiming {
             ticks: Arc::new(AtomicU32::default()),
         };
 
-        timing
-            .setup_osc_listener()
-            .expect("Unable to setup OSC listener");
+        if !crate::global::inputs_deferred() {
+            timing
+                .setup_osc_listener()
+                .expect("Unable to setup OSC listener");
+        }
 
         timing
     }
@@ -592,13 +781,17 @@ impl OscTransportTiming {
     }
 
     fn beats(&self) -> f32 {
+        self.precise_beats() as f32
+    }
+
+    fn precise_beats(&self) -> f64 {
         if !self.is_playing.load(Ordering::Acquire) {
             return 0.0;
         }
 
-        let bars = self.bars.load(Ordering::Acquire) as f32;
-        let beats = self.beats.load(Ordering::Acquire) as f32;
-        let ticks = f32::from_bits(self.ticks.load(Ordering::Acquire));
+        let bars = self.bars.load(Ordering::Acquire) as f64;
+        let beats = self.beats.load(Ordering::Acquire) as f64;
+        let ticks = f32::from_bits(self.ticks.load(Ordering::Acquire)) as f64;
 
         (bars * 4.0) + beats + ticks
     }
@@ -609,9 +802,18 @@ impl TimingSource for OscTransportTiming {
         self.bpm.get()
     }
 
+    fn connect(&self) {
+        self.setup_osc_listener()
+            .expect("Unable to setup OSC listener");
+    }
+
     fn beats(&self) -> f32 {
         self.beats()
     }
+
+    fn precise_beats(&self) -> f64 {
+        self.precise_beats()
+    }
 }
 
 /// Allows sketches to visualize animations statically by manually providing
@@ -665,7 +867,9 @@ impl TestTiming for OscTransportTiming {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert_approx_eq;
     use serial_test::serial;
+    use std::time::Duration;
 
     #[test]
     #[serial]
@@ -679,5 +883,75 @@ mod tests {
 
         // Each bar is 4 beats, so bar 44 starts at beat 176
         assert_eq!(timing.beats(), 176.0);
+        assert_eq!(timing.precise_beats(), 176.0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_midi_timing_interpolates_between_pulses() {
+        let timing = MidiSongTiming::new_no_song_position(Bpm::new(120.0));
+        timing.clock_count.store(24, Ordering::Relaxed);
+        assert_eq!(timing.precise_beats(), 1.0);
+
+        let pulse = Duration::from_secs_f64(60.0 / (120.0 * 24.0));
+        let set_last_clock = |elapsed: Duration, interval: Option<Duration>| {
+            *timing.last_clock.lock().unwrap() = Some(ClockPulse {
+                at: Instant::now() - elapsed,
+                interval,
+            });
+        };
+
+        set_last_clock(pulse / 2, None);
+        let beats = timing.precise_beats();
+        assert!(beats >= 1.0 + 0.5 / 24.0, "{}", beats);
+        assert!(beats < 1.0 + 1.0 / 24.0, "{}", beats);
+
+        // Follows the measured rate rather than the configured BPM
+        set_last_clock(pulse / 2, Some(pulse * 2));
+        let beats = timing.precise_beats();
+        assert!(beats >= 1.0 + 0.25 / 24.0, "{}", beats);
+        assert!(beats < 1.0 + 0.5 / 24.0, "{}", beats);
+
+        // Holds just short of the next pulse when the clock stalls
+        set_last_clock(pulse * 10, None);
+        assert!(timing.precise_beats() < 1.0 + 1.0 / 24.0);
+    }
+
+    #[test]
+    fn test_clock_pulse_interval() {
+        let start = Instant::now();
+        let first = ClockPulse::received(None, start);
+        assert_eq!(first.interval, None);
+
+        let pulse = Duration::from_millis(20);
+        let second = ClockPulse::received(Some(first), start + pulse);
+        assert_eq!(second.interval, Some(pulse));
+
+        // Stalls don't count toward the rate
+        let third = ClockPulse::received(Some(second), start + pulse * 20);
+        assert_eq!(third.interval, Some(pulse));
+    }
+
+    #[test]
+    fn test_sample_beats() {
+        // 120 BPM at 48kHz is 2 beats per second, 24000 samples per beat
+        let beats = SampleBeats::new(4.0, 120.0, 48_000);
+        assert_eq!(beats.beat_at(0), 4.0);
+        assert_approx_eq!(beats.beat_at(12_000), 4.5);
+        assert_approx_eq!(beats.sample_at(4.5), 12_000.0);
+        assert_approx_eq!(beats.sample_at(3.5), -12_000.0);
+
+        let mut next = beats;
+        next.advance(512);
+        assert_eq!(next.start(), beats.beat_at(512));
+    }
+
+    #[test]
+    fn test_sample_beats_from_timing() {
+        let mut timing = ManualTiming::new(Bpm::new(90.0));
+        timing.set_beats(8.25);
+        let beats = SampleBeats::from_timing(&timing, 44_100);
+        assert_eq!(beats.start(), 8.25);
+        assert_approx_eq!(beats.beat_at(44_100), 9.75);
     }
 }
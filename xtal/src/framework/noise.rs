@@ -1,7 +1,7 @@
-//! Wrappers around nannou::noise modules that simplify imports and work solely
+//! Wrappers around [`noise`] modules that simplify imports and work solely
 //! with f32
 
-use nannou::noise::{NoiseFn, OpenSimplex, Perlin, Seedable};
+use noise::{NoiseFn, OpenSimplex, Perlin, Seedable};
 
 pub struct PerlinNoise {
     noise: Perlin,
@@ -0,0 +1,661 @@
+//! Optical flow (per-pixel motion between consecutive frames) computed on the
+//! GPU with Lucas-Kanade. Feed it any texture each frame, e.g. another
+//! sketch's output via [`crate::prelude::Context::input`] or a
+//! [`crate::prelude::gpu::GpuState::render_to_texture`], and read the motion
+//! back two ways:
+//!
+//! - [`OpticalFlow::texture_view`]: an RGBA16F texture with velocity in
+//!   analysis pixels per frame in `rg` (texture space, y down) and speed in
+//!   `b`, for shaders
+//! - [`OpticalFlow::field`]: a coarse [`VectorField`] on the CPU, for particle
+//!   sketches. It's read back without stalling the GPU so it trails the
+//!   texture by a frame or two
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let flow = gpu::OpticalFlow::new(app, [320, 180], [32, 18]);
+//!
+//! // update
+//! if let Some(camera) = ctx.input("camera") {
+//!     self.flow.update_params(&self.hub, "flow");
+//!     self.flow.update(app, &camera);
+//! }
+//! for particle in &mut self.particles {
+//!     particle.velocity += self.flow.field().sample(particle.position, wr.rect());
+//! }
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::framework::prelude::*;
+
+/// The WGSL source of every pass
+pub const OPTICAL_FLOW_WGSL: &str = include_str!("optical_flow.wgsl");
+
+/// Must match `@workgroup_size` in optical_flow.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+const LUMA_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Float;
+const FLOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// A grid of 2D vectors, row-major with the first row at the top
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VectorField {
+    size: [usize; 2],
+    vectors: Vec<Vec2>,
+}
+
+impl VectorField {
+    pub fn new(size: [usize; 2]) -> Self {
+        Self {
+            size,
+            vectors: vec![Vec2::ZERO; size[0] * size[1]],
+        }
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    pub fn vectors(&self) -> &[Vec2] {
+        &self.vectors
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Vec2 {
+        self.vectors[y * self.size[0] + x]
+    }
+
+    /// Bilinear sample between cell centers. `uv` spans `[0, 1]` with `[0, 0]`
+    /// at the top left
+    pub fn sample_uv(&self, uv: Vec2) -> Vec2 {
+        let [w, h] = self.size;
+        if w == 0 || h == 0 {
+            return Vec2::ZERO;
+        }
+
+        let max = vec2((w - 1) as f32, (h - 1) as f32);
+        let p = (uv * vec2(w as f32, h as f32) - 0.5).clamp(Vec2::ZERO, max);
+        let (x0, y0) = (p.x.floor() as usize, p.y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+        let t = p - p.floor();
+
+        let top = self.get(x0, y0).lerp(self.get(x1, y0), t.x);
+        let bottom = self.get(x0, y1).lerp(self.get(x1, y1), t.x);
+        top.lerp(bottom, t.y)
+    }
+
+    /// The vector at `point` scaled to `rect`, e.g. `wr.rect()`. For
+    /// [`OpticalFlow::field`] that's velocity in `rect` units per frame
+    pub fn sample(&self, point: Vec2, rect: Rect) -> Vec2 {
+        let uv = vec2(
+            (point.x - rect.left()) / rect.w(),
+            (rect.top() - point.y) / rect.h(),
+        );
+        self.sample_uv(uv) * rect.wh()
+    }
+
+    pub fn mean(&self) -> Vec2 {
+        if self.vectors.is_empty() {
+            return Vec2::ZERO;
+        }
+        self.vectors.iter().sum::<Vec2>() / self.vectors.len() as f32
+    }
+
+    /// Average length of the vectors; a simple "how much motion" signal
+    pub fn mean_magnitude(&self) -> f32 {
+        if self.vectors.is_empty() {
+            return 0.0;
+        }
+        self.vectors.iter().map(|v| v.length()).sum::<f32>()
+            / self.vectors.len() as f32
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ShaderParams {
+    // w, h, window_radius, threshold
+    a: [f32; 4],
+    // grid_w, grid_h, max_speed, ..unused
+    b: [f32; 4],
+}
+
+pub struct OpticalFlow {
+    /// Half the size of the square each pixel's motion is solved over.
+    /// Larger windows catch faster motion but blur its edges
+    pub window_radius: u32,
+    /// Minimum texture (structure tensor determinant) a window needs before
+    /// its motion is trusted. Raise it to quiet noise in flat areas
+    pub threshold: f32,
+    /// Upper limit of the per-pixel speed in analysis pixels per frame
+    pub max_speed: f32,
+    /// How much of the previous [`VectorField`] each readback keeps, 0 to 1
+    pub smoothing: f32,
+
+    size: [u32; 2],
+    grid: [u32; 2],
+    field: VectorField,
+
+    sampler: wgpu::Sampler,
+    luma_layout: wgpu::BindGroupLayout,
+    luma_pipeline: wgpu::ComputePipeline,
+    flow_pipeline: wgpu::ComputePipeline,
+    field_pipeline: wgpu::ComputePipeline,
+    params_buffer: wgpu::Buffer,
+    cells_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+
+    luma_views: [wgpu::TextureView; 2],
+    /// `flow_bind_groups[i]` compares `luma_views[i]` against the other
+    flow_bind_groups: [wgpu::BindGroup; 2],
+    flow_view: wgpu::TextureView,
+    field_bind_group: wgpu::BindGroup,
+    /// Index of the newest luma frame
+    current: usize,
+    /// False until there are two frames to compare
+    primed: bool,
+
+    /// Whether `readback_buffer` is mapped or waiting to be
+    reading: bool,
+    /// Set from the map callback once `readback_buffer` can be read
+    mapped: Arc<AtomicBool>,
+}
+
+impl std::fmt::Debug for OpticalFlow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpticalFlow")
+            .field("window_radius", &self.window_radius)
+            .field("threshold", &self.threshold)
+            .field("max_speed", &self.max_speed)
+            .field("smoothing", &self.smoothing)
+            .field("size", &self.size)
+            .field("grid", &self.grid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OpticalFlow {
+    /// `size` is the analysis resolution (sources of any size are scaled to
+    /// it; smaller is faster and less noisy) and `grid` the resolution of
+    /// [`Self::field`]
+    pub fn new(app: &App, size: [u32; 2], grid: [u32; 2]) -> Self {
+        let window = app.main_window();
+        let device = window.device();
+
+        let module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Optical Flow Shader"),
+                source: wgpu::ShaderSource::Wgsl(OPTICAL_FLOW_WGSL.into()),
+            });
+
+        let params_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<
+                    ShaderParams,
+                >()
+                    as _),
+            },
+            count: None,
+        };
+
+        let texture_entry = |binding, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let storage_texture_entry =
+            |binding, format| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            };
+
+        let luma_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Optical Flow Luma Bind Group Layout"),
+                entries: &[
+                    params_entry,
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                    texture_entry(2, true),
+                    storage_texture_entry(3, LUMA_FORMAT),
+                ],
+            });
+
+        let flow_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Optical Flow Bind Group Layout"),
+                entries: &[
+                    params_entry,
+                    texture_entry(4, false),
+                    texture_entry(5, false),
+                    storage_texture_entry(6, FLOW_FORMAT),
+                ],
+            });
+
+        let field_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Optical Flow Field Bind Group Layout"),
+                entries: &[
+                    params_entry,
+                    texture_entry(7, false),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 8,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage {
+                                read_only: false,
+                            },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let create_pipeline = |layout, entry_point| {
+            let pipeline_layout = device.create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("Optical Flow Pipeline Layout"),
+                    bind_group_layouts: &[layout],
+                    push_constant_ranges: &[],
+                },
+            );
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Optical Flow Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+            })
+        };
+
+        let luma_pipeline = create_pipeline(&luma_layout, "luma_main");
+        let flow_pipeline = create_pipeline(&flow_layout, "flow_main");
+        let field_pipeline = create_pipeline(&field_layout, "field_main");
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Optical Flow Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Optical Flow Params Buffer"),
+            size: std::mem::size_of::<ShaderParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let cells_size = (grid[0] * grid[1]).max(1) as u64 * 8;
+        let cells_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Optical Flow Cells Buffer"),
+            size: cells_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Optical Flow Readback Buffer"),
+            size: cells_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let create_texture = |format| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(format)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::STORAGE_BINDING,
+                )
+                .sample_count(1)
+                .build(device)
+                .view()
+                .build()
+        };
+
+        let luma_views = [(); 2].map(|_| create_texture(LUMA_FORMAT));
+        let flow_view = create_texture(FLOW_FORMAT);
+
+        let flow_bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Optical Flow Bind Group"),
+                layout: &flow_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(
+                            &luma_views[i],
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 5,
+                        resource: wgpu::BindingResource::TextureView(
+                            &luma_views[1 - i],
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 6,
+                        resource: wgpu::BindingResource::TextureView(
+                            &flow_view,
+                        ),
+                    },
+                ],
+            })
+        });
+
+        let field_bind_group =
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Optical Flow Field Bind Group"),
+                layout: &field_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 7,
+                        resource: wgpu::BindingResource::TextureView(
+                            &flow_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 8,
+                        resource: cells_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        Self {
+            window_radius: 2,
+            threshold: 1e-5,
+            max_speed: 8.0,
+            smoothing: 0.5,
+            size,
+            grid,
+            field: VectorField::new([grid[0] as usize, grid[1] as usize]),
+            sampler,
+            luma_layout,
+            luma_pipeline,
+            flow_pipeline,
+            field_pipeline,
+            params_buffer,
+            cells_buffer,
+            readback_buffer,
+            luma_views,
+            flow_bind_groups,
+            flow_view,
+            field_bind_group,
+            current: 0,
+            primed: false,
+            reading: false,
+            mapped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Reads `<prefix>.window_radius`, `<prefix>.threshold`,
+    /// `<prefix>.max_speed`, and `<prefix>.smoothing` from `hub`, leaving any
+    /// that aren't declared unchanged
+    pub fn update_params<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        prefix: &str,
+    ) {
+        let name = |param| format!("{}.{}", prefix, param);
+        if hub.has(&name("window_radius")) {
+            self.window_radius =
+                hub.get(&name("window_radius")).round().max(0.0) as u32;
+        }
+        for (param, field) in [
+            ("threshold", &mut self.threshold),
+            ("max_speed", &mut self.max_speed),
+            ("smoothing", &mut self.smoothing),
+        ] {
+            if hub.has(&name(param)) {
+                *field = hub.get(&name(param));
+            }
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// Motion between the last two frames passed to [`Self::update`]
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.flow_view
+    }
+
+    /// The most recently read back motion, averaged per grid cell, in
+    /// fractions of the frame per frame with y up. See [`VectorField::sample`]
+    pub fn field(&self) -> &VectorField {
+        &self.field
+    }
+
+    /// Forgets the previous frame so the next update doesn't report a jump
+    /// (e.g. after switching sources)
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+
+    /// Analyzes `source` against the previous frame. Call once per new frame
+    /// of the source; calling it twice with the same frame reports no motion
+    pub fn update(&mut self, app: &App, source: &wgpu::TextureView) {
+        let window = app.main_window();
+        let device = window.device();
+        let queue = window.queue();
+
+        self.poll_readback(device);
+
+        let [w, h] = self.size;
+        let [grid_w, grid_h] = self.grid;
+        let params = ShaderParams {
+            a: [
+                w as f32,
+                h as f32,
+                self.window_radius as f32,
+                self.threshold,
+            ],
+            b: [grid_w as f32, grid_h as f32, self.max_speed, 0.0],
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let next = 1 - self.current;
+        let luma_bind_group =
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Optical Flow Luma Bind Group"),
+                layout: &self.luma_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: self.params_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(
+                            &self.luma_views[next],
+                        ),
+                    },
+                ],
+            });
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Optical Flow Encoder"),
+            });
+
+        {
+            let mut pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Optical Flow Pass"),
+                });
+            let (x, y) =
+                (w.div_ceil(WORKGROUP_SIZE), h.div_ceil(WORKGROUP_SIZE));
+
+            pass.set_pipeline(&self.luma_pipeline);
+            pass.set_bind_group(0, &luma_bind_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+
+            if self.primed {
+                pass.set_pipeline(&self.flow_pipeline);
+                pass.set_bind_group(0, &self.flow_bind_groups[next], &[]);
+                pass.dispatch_workgroups(x, y, 1);
+
+                pass.set_pipeline(&self.field_pipeline);
+                pass.set_bind_group(0, &self.field_bind_group, &[]);
+                pass.dispatch_workgroups(
+                    grid_w.div_ceil(WORKGROUP_SIZE),
+                    grid_h.div_ceil(WORKGROUP_SIZE),
+                    1,
+                );
+            }
+        }
+
+        let read = self.primed && !self.reading;
+        if read {
+            encoder.copy_buffer_to_buffer(
+                &self.cells_buffer,
+                0,
+                &self.readback_buffer,
+                0,
+                self.readback_buffer.size(),
+            );
+        }
+
+        queue.submit(Some(encoder.finish()));
+
+        if read {
+            self.reading = true;
+            let mapped = self.mapped.clone();
+            self.readback_buffer.slice(..).map_async(
+                wgpu::MapMode::Read,
+                move |result| {
+                    if result.is_ok() {
+                        mapped.store(true, Ordering::Release);
+                    }
+                },
+            );
+        }
+
+        self.current = next;
+        self.primed = true;
+    }
+
+    /// Copies the cells into [`Self::field`] if the last readback finished,
+    /// without waiting for it
+    fn poll_readback(&mut self, device: &wgpu::Device) {
+        if !self.reading {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+        if !self.mapped.swap(false, Ordering::Acquire) {
+            return;
+        }
+
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let cells: &[[f32; 2]] = bytemuck::cast_slice(&data);
+            blend_cells(&mut self.field, cells, self.smoothing);
+        }
+
+        self.readback_buffer.unmap();
+        self.reading = false;
+    }
+}
+
+/// Mixes `cells` (y down, as written by the shader) into `field` (y up)
+fn blend_cells(field: &mut VectorField, cells: &[[f32; 2]], smoothing: f32) {
+    let smoothing = smoothing.clamp(0.0, 1.0);
+    for (vector, [x, y]) in field.vectors.iter_mut().zip(cells) {
+        *vector = vec2(*x, -*y).lerp(*vector, smoothing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use crate::framework::gpu::validate_shader;
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        validate_shader(OPTICAL_FLOW_WGSL).unwrap();
+        assert!(OPTICAL_FLOW_WGSL.contains(&format!(
+            "@workgroup_size({}, {})",
+            WORKGROUP_SIZE, WORKGROUP_SIZE
+        )));
+    }
+
+    #[test]
+    fn test_blend_cells() {
+        let mut field = VectorField::new([2, 1]);
+        blend_cells(&mut field, &[[1.0, 1.0], [0.0, -2.0]], 0.0);
+        assert_eq!(field.vectors(), [vec2(1.0, -1.0), vec2(0.0, 2.0)]);
+
+        blend_cells(&mut field, &[[0.0, 0.0], [0.0, 0.0]], 0.5);
+        assert_eq!(field.vectors(), [vec2(0.5, -0.5), vec2(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_vector_field_sample() {
+        let mut field = VectorField::new([2, 2]);
+        blend_cells(
+            &mut field,
+            &[[1.0, 0.0], [3.0, 0.0], [1.0, 0.0], [3.0, 0.0]],
+            0.0,
+        );
+
+        // Cell centers and clamped edges
+        assert_eq!(field.sample_uv(vec2(0.25, 0.25)), vec2(1.0, 0.0));
+        assert_eq!(field.sample_uv(vec2(0.0, 1.0)), vec2(1.0, 0.0));
+        assert_eq!(field.sample_uv(vec2(0.5, 0.5)), vec2(2.0, 0.0));
+
+        let rect = Rect::from_w_h(100.0, 50.0);
+        assert_eq!(field.sample(vec2(0.0, 0.0), rect), vec2(200.0, 0.0));
+
+        assert_eq!(field.mean(), vec2(2.0, 0.0));
+        assert_approx_eq!(field.mean_magnitude(), 2.0);
+    }
+}
@@ -0,0 +1,145 @@
+// -----------------------------------------------------------------------------
+//  XTAL OPTICAL FLOW
+//  Lucas-Kanade optical flow between consecutive frames. `luma_main` converts
+//  the source to grayscale at the analysis resolution, `flow_main` solves for
+//  per-pixel motion, and `field_main` averages it into a coarse grid for the
+//  CPU. See optical_flow.rs
+// -----------------------------------------------------------------------------
+
+struct Params {
+    // w, h, window_radius, threshold
+    a: vec4f,
+    // grid_w, grid_h, max_speed, ..unused
+    b: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+// luma_main
+
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@group(0) @binding(2)
+var source: texture_2d<f32>;
+
+@group(0) @binding(3)
+var luma_out: texture_storage_2d<r32float, write>;
+
+// flow_main
+
+@group(0) @binding(4)
+var current: texture_2d<f32>;
+
+@group(0) @binding(5)
+var previous: texture_2d<f32>;
+
+@group(0) @binding(6)
+var flow_out: texture_storage_2d<rgba16float, write>;
+
+// field_main
+
+@group(0) @binding(7)
+var flow: texture_2d<f32>;
+
+@group(0) @binding(8)
+var<storage, read_write> cells: array<vec2f>;
+
+fn size() -> vec2i {
+    return vec2i(params.a.xy);
+}
+
+@compute @workgroup_size(8, 8)
+fn luma_main(@builtin(global_invocation_id) id: vec3u) {
+    let position = vec2i(id.xy);
+    let s = size();
+    if (position.x >= s.x || position.y >= s.y) {
+        return;
+    }
+
+    let uv = (vec2f(position) + 0.5) / vec2f(s);
+    let color = textureSampleLevel(source, source_sampler, uv, 0.0).rgb;
+    let luma = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+    textureStore(luma_out, position, vec4f(luma, 0.0, 0.0, 1.0));
+}
+
+fn load(texture: texture_2d<f32>, position: vec2i) -> f32 {
+    return textureLoad(texture, clamp(position, vec2i(0), size() - 1), 0).r;
+}
+
+@compute @workgroup_size(8, 8)
+fn flow_main(@builtin(global_invocation_id) id: vec3u) {
+    let position = vec2i(id.xy);
+    let s = size();
+    if (position.x >= s.x || position.y >= s.y) {
+        return;
+    }
+
+    let radius = i32(params.a.z);
+    let threshold = params.a.w;
+    let max_speed = params.b.z;
+
+    // Structure tensor and temporal terms summed over the window
+    var ixx = 0.0;
+    var iyy = 0.0;
+    var ixy = 0.0;
+    var ixt = 0.0;
+    var iyt = 0.0;
+
+    for (var y = -radius; y <= radius; y += 1) {
+        for (var x = -radius; x <= radius; x += 1) {
+            let p = position + vec2i(x, y);
+            let ix = (load(current, p + vec2i(1, 0))
+                - load(current, p - vec2i(1, 0))) * 0.5;
+            let iy = (load(current, p + vec2i(0, 1))
+                - load(current, p - vec2i(0, 1))) * 0.5;
+            let it = load(current, p) - load(previous, p);
+            ixx += ix * ix;
+            iyy += iy * iy;
+            ixy += ix * iy;
+            ixt += ix * it;
+            iyt += iy * it;
+        }
+    }
+
+    // Flat or edge-only regions can't be solved; report no motion
+    let det = ixx * iyy - ixy * ixy;
+    var velocity = vec2f(0.0);
+    if (det > threshold) {
+        velocity = vec2f(iyy * ixt - ixy * iyt, ixx * iyt - ixy * ixt) / -det;
+        let speed = length(velocity);
+        if (speed > max_speed) {
+            velocity *= max_speed / speed;
+        }
+    }
+
+    textureStore(
+        flow_out,
+        position,
+        vec4f(velocity, length(velocity), 1.0)
+    );
+}
+
+@compute @workgroup_size(8, 8)
+fn field_main(@builtin(global_invocation_id) id: vec3u) {
+    let grid = vec2u(params.b.xy);
+    if (id.x >= grid.x || id.y >= grid.y) {
+        return;
+    }
+
+    let s = size();
+    let start = vec2i(id.xy) * s / vec2i(grid);
+    let end = max((vec2i(id.xy) + 1) * s / vec2i(grid), start + 1);
+
+    var sum = vec2f(0.0);
+    for (var y = start.y; y < end.y; y += 1) {
+        for (var x = start.x; x < end.x; x += 1) {
+            sum += textureLoad(flow, vec2i(x, y), 0).xy;
+        }
+    }
+
+    // Average in fractions of the frame per frame
+    let count = f32((end.x - start.x) * (end.y - start.y));
+    cells[id.y * grid.x + id.x] = sum / count / vec2f(s);
+}
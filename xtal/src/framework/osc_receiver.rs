@@ -94,6 +94,7 @@ impl Receiver {
         Ok(())
     }
 
+    #[cfg(feature = "runtime")]
     pub fn stop(&self) -> Result<(), Box<dyn Error>> {
         self.thread_running.store(false, Ordering::SeqCst);
         let mut thread_handle = self.thread_handle.lock().unwrap();
@@ -103,6 +104,7 @@ impl Receiver {
         Ok(())
     }
 
+    #[cfg(feature = "runtime")]
     pub fn restart(&self) -> Result<(), Box<dyn Error>> {
         self.stop()?;
         info!("Restarting...");
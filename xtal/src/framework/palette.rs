@@ -0,0 +1,305 @@
+//! Extracts a K-color palette from a reference image (k-means clustering in
+//! CIELAB space) so sketches can be art-directed from mood-board images.
+//! Palettes can be loaded directly or declared in a control script with the
+//! `palette` control type and read with
+//! [`crate::prelude::ControlHub::palette`].
+//!
+//! # Example
+//! ```rust,ignore
+//! let palette = Palette::from_image_path(
+//!     to_absolute_path(file!(), "mood.jpg"),
+//!     5,
+//! )?
+//! .by_lightness();
+//!
+//! for (i, color) in palette.colors().iter().enumerate() {
+//!     // ...
+//! }
+//!
+//! let color = palette.gradient(self.hub.get("t"));
+//! ```
+
+use nannou::image;
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::path::Path;
+
+/// Images are subsampled to about this many pixels before clustering
+const MAX_SAMPLES: usize = 16_384;
+const MAX_ITERATIONS: usize = 32;
+
+#[derive(Clone, Debug, Default)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+    weights: Vec<f32>,
+    lab: Vec<Vec3>,
+}
+
+impl Palette {
+    /// Extracts up to `k` colors from the image at `path`, most dominant
+    /// first. Deterministic for the same image
+    pub fn from_image_path(
+        path: impl AsRef<Path>,
+        k: usize,
+    ) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| format!("Unable to open image {:?}: {}", path, e))?
+            .to_rgb8();
+
+        let pixel_count = (image.width() * image.height()) as usize;
+        let stride = (pixel_count / MAX_SAMPLES).max(1);
+        let pixels: Vec<Vec3> = image
+            .pixels()
+            .step_by(stride)
+            .map(|p| vec3(p[0] as f32, p[1] as f32, p[2] as f32) / 255.0)
+            .collect();
+
+        Ok(Self::from_pixels(&pixels, k))
+    }
+
+    /// Extracts up to `k` colors from sRGB `pixels` in the 0..1 range, most
+    /// dominant first
+    pub fn from_pixels(pixels: &[Vec3], k: usize) -> Self {
+        let samples: Vec<Vec3> =
+            pixels.iter().map(|p| srgb_to_lab(*p)).collect();
+
+        let clusters = kmeans(&samples, k, &mut StdRng::seed_from_u64(0));
+        let total = samples.len().max(1) as f32;
+
+        Self {
+            colors: clusters
+                .iter()
+                .map(|(lab, _)| {
+                    let c = lab_to_srgb(*lab);
+                    rgb(c.x, c.y, c.z)
+                })
+                .collect(),
+            weights: clusters
+                .iter()
+                .map(|(_, count)| *count as f32 / total)
+                .collect(),
+            lab: clusters.into_iter().map(|(lab, _)| lab).collect(),
+        }
+    }
+
+    /// Reorders colors from darkest to lightest; handy for gradients
+    pub fn by_lightness(mut self) -> Self {
+        let mut order: Vec<usize> = (0..self.lab.len()).collect();
+        order.sort_by(|a, b| self.lab[*a].x.total_cmp(&self.lab[*b].x));
+        self.colors = order.iter().map(|i| self.colors[*i]).collect();
+        self.weights = order.iter().map(|i| self.weights[*i]).collect();
+        self.lab = order.iter().map(|i| self.lab[*i]).collect();
+        self
+    }
+
+    pub fn colors(&self) -> &[Rgb] {
+        &self.colors
+    }
+
+    /// The fraction of the image each color represents, in the same order as
+    /// [`Self::colors`]
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Samples the palette as an evenly spaced gradient where `t` in 0..1
+    /// spans the first to last color. Interpolates in Lab to avoid the muddy
+    /// midpoints of RGB blending
+    pub fn gradient(&self, t: f32) -> Rgb {
+        match self.lab.len() {
+            0 => rgb(0.0, 0.0, 0.0),
+            1 => self.colors[0],
+            len => {
+                let position = t.clamp(0.0, 1.0) * (len - 1) as f32;
+                let index = (position.floor() as usize).min(len - 2);
+                let lab = self.lab[index]
+                    .lerp(self.lab[index + 1], position - index as f32);
+                let c = lab_to_srgb(lab);
+                rgb(c.x, c.y, c.z)
+            }
+        }
+    }
+}
+
+/// Returns `(center, member count)` for up to `k` clusters sorted by count
+fn kmeans(samples: &[Vec3], k: usize, rng: &mut StdRng) -> Vec<(Vec3, usize)> {
+    let k = k.min(samples.len());
+    if k == 0 {
+        return vec![];
+    }
+
+    let nearest = |centers: &[Vec3], p: Vec3| {
+        centers
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, c.distance_squared(p)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+    };
+
+    // k-means++ seeding: spread initial centers out proportionally to their
+    // squared distance from the centers chosen so far
+    let mut centers = vec![samples[rng.gen_range(0..samples.len())]];
+    while centers.len() < k {
+        let distances: Vec<f32> =
+            samples.iter().map(|p| nearest(&centers, *p).1).collect();
+        let total: f32 = distances.iter().sum();
+        if total <= f32::EPSILON {
+            // Fewer distinct colors than k
+            break;
+        }
+        let mut target = rng.gen_range(0.0..total);
+        let index = distances
+            .iter()
+            .position(|d| {
+                target -= d;
+                target <= 0.0
+            })
+            .unwrap_or(samples.len() - 1);
+        centers.push(samples[index]);
+    }
+
+    let mut assignments = vec![usize::MAX; samples.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (assignment, p) in assignments.iter_mut().zip(samples) {
+            let (index, _) = nearest(&centers, *p);
+            if *assignment != index {
+                *assignment = index;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = vec![(Vec3::ZERO, 0); centers.len()];
+        for (assignment, p) in assignments.iter().zip(samples) {
+            sums[*assignment].0 += *p;
+            sums[*assignment].1 += 1;
+        }
+        for (center, (sum, count)) in centers.iter_mut().zip(sums) {
+            // Empty clusters keep their previous center
+            if count > 0 {
+                *center = sum / count as f32;
+            }
+        }
+    }
+
+    let mut counts = vec![0; centers.len()];
+    for assignment in &assignments {
+        counts[*assignment] += 1;
+    }
+
+    let mut clusters: Vec<(Vec3, usize)> = centers
+        .into_iter()
+        .zip(counts)
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    clusters.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    clusters
+}
+
+// D65 reference white
+const WHITE: [f32; 3] = [0.950_47, 1.0, 1.088_83];
+
+/// sRGB (0..1) to CIELAB where L is 0..100
+fn srgb_to_lab(c: Vec3) -> Vec3 {
+    let linear = |v: f32| {
+        ternary!(v <= 0.040_45, v / 12.92, ((v + 0.055) / 1.055).powf(2.4))
+    };
+    let (r, g, b) = (linear(c.x), linear(c.y), linear(c.z));
+
+    let xyz = vec3(
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    ) / Vec3::from(WHITE);
+
+    let f =
+        |t: f32| ternary!(t > 0.008_856, t.cbrt(), 7.787 * t + 16.0 / 116.0);
+    let (fx, fy, fz) = (f(xyz.x), f(xyz.y), f(xyz.z));
+
+    vec3(116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Inverse of [`srgb_to_lab`], clamped to the sRGB gamut
+fn lab_to_srgb(lab: Vec3) -> Vec3 {
+    let fy = (lab.x + 16.0) / 116.0;
+    let fx = fy + lab.y / 500.0;
+    let fz = fy - lab.z / 200.0;
+
+    let f_inv = |t: f32| {
+        let t3 = t * t * t;
+        ternary!(t3 > 0.008_856, t3, (t - 16.0 / 116.0) / 7.787)
+    };
+    let xyz = vec3(f_inv(fx), f_inv(fy), f_inv(fz)) * Vec3::from(WHITE);
+
+    let r = 3.240_454_2 * xyz.x - 1.537_138_5 * xyz.y - 0.498_531_4 * xyz.z;
+    let g = -0.969_266 * xyz.x + 1.876_010_8 * xyz.y + 0.041_556 * xyz.z;
+    let b = 0.055_643_4 * xyz.x - 0.204_025_9 * xyz.y + 1.057_225_2 * xyz.z;
+
+    let gamma = |v: f32| {
+        let v = v.clamp(0.0, 1.0);
+        ternary!(
+            v <= 0.003_130_8,
+            v * 12.92,
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        )
+    };
+
+    vec3(gamma(r), gamma(g), gamma(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_lab_round_trip() {
+        for c in [vec3(0.2, 0.5, 0.8), vec3(1.0, 1.0, 1.0), Vec3::ZERO] {
+            let back = lab_to_srgb(srgb_to_lab(c));
+            assert_approx_eq!(back.x, c.x);
+            assert_approx_eq!(back.y, c.y);
+            assert_approx_eq!(back.z, c.z);
+        }
+        assert_approx_eq!(srgb_to_lab(Vec3::ONE).x, 100.0, 0.01);
+    }
+
+    #[test]
+    fn test_from_pixels() {
+        let red = vec3(1.0, 0.0, 0.0);
+        let blue = vec3(0.0, 0.0, 1.0);
+        let mut pixels = vec![red; 30];
+        pixels.extend(vec![blue; 10]);
+
+        let palette = Palette::from_pixels(&pixels, 2);
+        assert_eq!(palette.len(), 2);
+        assert_approx_eq!(palette.weights()[0], 0.75);
+        assert_approx_eq!(palette.colors()[0].red, 1.0);
+        assert_approx_eq!(palette.colors()[1].blue, 1.0);
+
+        let palette = palette.by_lightness();
+        assert_approx_eq!(palette.colors()[0].blue, 1.0);
+        assert_approx_eq!(palette.gradient(1.0).red, 1.0);
+    }
+
+    #[test]
+    fn test_fewer_colors_than_k() {
+        let palette = Palette::from_pixels(&[vec3(0.5, 0.5, 0.5); 8], 4);
+        assert_eq!(palette.len(), 1);
+        assert_approx_eq!(palette.weights()[0], 1.0);
+    }
+}
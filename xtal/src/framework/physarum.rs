@@ -0,0 +1,684 @@
+//! A reusable physarum (slime mold) simulation run as compute shaders. Agents
+//! sense a trail map ahead of them, turn toward the strongest trail, move, and
+//! deposit; the trail map is then blurred and decayed. The trail lives in all
+//! channels of an RGBA16F texture that can be bound to any
+//! [`crate::prelude::gpu::GpuState`] with `set_texture` or handed to other
+//! sketches through [`crate::prelude::Sketch::render_output`]. Values aren't
+//! normalized, so map them in the display shader (e.g. `1 - exp(-v)`).
+//!
+//! The built-in WGSL is [`PHYSARUM_WGSL`]. Copy it next to a sketch and pass
+//! its path to [`Physarum::shader`] to customize it with hot reloading.
+//!
+//! # Example
+//! ```yaml
+//! slime.agents:
+//!   type: slider
+//!   range: [1000, 1000000]
+//!   default: 200000
+//!   step: 1000
+//!
+//! slime.sensor_distance:
+//!   type: slider
+//!   range: [1, 40]
+//!   default: 9
+//! ```
+//!
+//! ```rust,ignore
+//! // init
+//! let slime = gpu::Physarum::new(app, [700, 700], 200_000)
+//!     .shader(to_absolute_path(file!(), "physarum.wgsl"));
+//!
+//! // update
+//! self.slime.update_params(&self.hub, "slime");
+//! self.slime.step(app, 2);
+//! self.gpu.set_texture(app, self.slime.texture_view());
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+use nannou::wgpu;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::framework::gpu::{preprocess_shader, validate_shader, watch_shader};
+use crate::framework::gpu_stats::{GpuUsage, UsageReport};
+use crate::framework::prelude::*;
+
+/// The built-in WGSL source of both passes
+pub const PHYSARUM_WGSL: &str = include_str!("physarum.wgsl");
+
+const AGENTS_ENTRY_POINT: &str = "agents_main";
+const DIFFUSE_ENTRY_POINT: &str = "diffuse_main";
+
+/// Must match the `@workgroup_size`s in physarum.wgsl
+const AGENTS_WORKGROUP_SIZE: u32 = 64;
+const DIFFUSE_WORKGROUP_SIZE: u32 = 8;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Distances are in trail map texels and angles in radians. Rates are per
+/// step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysarumParams {
+    /// Angle between the forward sensor and each side sensor
+    pub sensor_angle: f32,
+    pub sensor_distance: f32,
+    /// Largest turn an agent makes in one step
+    pub turn_speed: f32,
+    pub move_speed: f32,
+    /// Trail added where each agent lands
+    pub deposit: f32,
+    /// Fraction of the trail removed
+    pub decay: f32,
+    /// Blend between the unblurred (0) and fully blurred (1) trail
+    pub diffuse: f32,
+}
+
+impl Default for PhysarumParams {
+    fn default() -> Self {
+        Self {
+            sensor_angle: PI / 8.0,
+            sensor_distance: 9.0,
+            turn_speed: PI / 8.0,
+            move_speed: 1.0,
+            deposit: 1.0,
+            decay: 0.05,
+            diffuse: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ShaderParams {
+    // w, h, agent_count, frame
+    a: [f32; 4],
+    // sensor_angle, sensor_distance, turn_speed, move_speed
+    b: [f32; 4],
+    // deposit, decay, diffuse, ..unused
+    c: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+struct Agent {
+    position: [f32; 2],
+    angle: f32,
+    _pad: f32,
+}
+
+pub struct Physarum {
+    pub params: PhysarumParams,
+    size: [u32; 2],
+    /// Requested count, applied on the next [`Self::step`]
+    agent_count: usize,
+    /// Count the agent buffer currently holds
+    allocated_agents: usize,
+    rng: StdRng,
+    frame: u32,
+
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    agents_pipeline: wgpu::ComputePipeline,
+    diffuse_pipeline: wgpu::ComputePipeline,
+    params_buffer: wgpu::Buffer,
+    agents_buffer: wgpu::Buffer,
+    /// Fixed point deposits accumulated by the agent pass and consumed (then
+    /// zeroed) by the diffuse pass
+    deposits_buffer: wgpu::Buffer,
+    /// Ping-pong pair; each step reads one and writes the other
+    trails: [wgpu::Texture; 2],
+    views: [wgpu::TextureView; 2],
+    /// `bind_groups[i]` reads `trails[i]` and writes the other
+    bind_groups: [wgpu::BindGroup; 2],
+    /// Index of the trail holding the latest state
+    current: usize,
+    usage_report: UsageReport,
+
+    // State access for hot reloading
+    shader_state: Arc<Mutex<Option<PathBuf>>>,
+    _watcher: Option<notify::RecommendedWatcher>,
+}
+
+impl std::fmt::Debug for Physarum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Physarum")
+            .field("params", &self.params)
+            .field("size", &self.size)
+            .field("agent_count", &self.agent_count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Physarum {
+    /// Creates a `size` trail map (independent of the window) with
+    /// `agent_count` agents at random positions and headings
+    pub fn new(app: &App, size: [u32; 2], agent_count: usize) -> Self {
+        let window = app.main_window();
+        let device = window.device();
+
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Physarum Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<ShaderParams>() as _,
+                            ),
+                        },
+                        count: None,
+                    },
+                    storage(1),
+                    storage(2),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: false,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::StorageTexture {
+                            access: wgpu::StorageTextureAccess::WriteOnly,
+                            format: FORMAT,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Physarum Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let (agents_pipeline, diffuse_pipeline) =
+            create_pipelines(device, &pipeline_layout, PHYSARUM_WGSL);
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Physarum Params Buffer"),
+            size: std::mem::size_of::<ShaderParams>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let [w, h] = size;
+        let deposits_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Physarum Deposits Buffer"),
+            size: (w * h * 4) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let agents_buffer = create_agents_buffer(device, agent_count);
+
+        let trails = [(); 2].map(|_| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(FORMAT)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::STORAGE_BINDING
+                        | wgpu::TextureUsages::COPY_DST,
+                )
+                .sample_count(1)
+                .build(device)
+        });
+
+        let views = [0, 1].map(|i| trails[i].view().build());
+
+        let bind_groups = create_bind_groups(
+            device,
+            &bind_group_layout,
+            &params_buffer,
+            &agents_buffer,
+            &deposits_buffer,
+            &views,
+        );
+
+        let mut physarum = Self {
+            params: PhysarumParams::default(),
+            size,
+            agent_count,
+            allocated_agents: agent_count,
+            rng: StdRng::seed_from_u64(0),
+            frame: 0,
+            bind_group_layout,
+            pipeline_layout,
+            agents_pipeline,
+            diffuse_pipeline,
+            params_buffer,
+            agents_buffer,
+            deposits_buffer,
+            trails,
+            views,
+            bind_groups,
+            current: 0,
+            usage_report: UsageReport::default(),
+            shader_state: Arc::new(Mutex::new(None)),
+            _watcher: None,
+        };
+
+        physarum.report_usage();
+        physarum.reset(app);
+        physarum
+    }
+
+    /// Replaces the built-in WGSL with the file at `path` (which must declare
+    /// the same bindings and entry points) and reloads it whenever it
+    /// changes. Invalid versions are logged and skipped
+    pub fn shader(mut self, path: PathBuf) -> Self {
+        self._watcher =
+            Some(watch_shader(path.clone(), self.shader_state.clone()));
+        // Loaded on the next step like any other change
+        if let Ok(mut guard) = self.shader_state.lock() {
+            *guard = Some(path);
+        }
+        self
+    }
+
+    /// Seeds where agents spawn on [`Self::reset`] and when the agent count
+    /// changes
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn agent_count(&self) -> usize {
+        self.agent_count
+    }
+
+    /// Takes effect on the next [`Self::step`], respawning every agent
+    pub fn set_agent_count(&mut self, count: usize) {
+        self.agent_count = count;
+    }
+
+    /// Reads `<prefix>.sensor_angle`, `<prefix>.sensor_distance`,
+    /// `<prefix>.turn_speed`, `<prefix>.move_speed`, `<prefix>.deposit`,
+    /// `<prefix>.decay`, `<prefix>.diffuse`, and `<prefix>.agents` from
+    /// `hub`, leaving any that aren't declared unchanged. Angles are in
+    /// degrees here since they're friendlier on sliders
+    pub fn update_params<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        prefix: &str,
+    ) {
+        let params = &mut self.params;
+        for (name, field, scale) in [
+            ("sensor_angle", &mut params.sensor_angle, PI / 180.0),
+            ("sensor_distance", &mut params.sensor_distance, 1.0),
+            ("turn_speed", &mut params.turn_speed, PI / 180.0),
+            ("move_speed", &mut params.move_speed, 1.0),
+            ("deposit", &mut params.deposit, 1.0),
+            ("decay", &mut params.decay, 1.0),
+            ("diffuse", &mut params.diffuse, 1.0),
+        ] {
+            let name = format!("{}.{}", prefix, name);
+            if hub.has(&name) {
+                *field = hub.get(&name) * scale;
+            }
+        }
+
+        let name = format!("{}.agents", prefix);
+        if hub.has(&name) {
+            self.set_agent_count(hub.get(&name).max(0.0) as usize);
+        }
+    }
+
+    /// The latest trail map
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.views[self.current]
+    }
+
+    /// Respawns every agent and clears the trail map
+    pub fn reset(&mut self, app: &App) {
+        let window = app.main_window();
+        let queue = window.queue();
+        let [w, h] = self.size;
+
+        let agents =
+            spawn_agents(&mut self.rng, self.allocated_agents, self.size);
+        queue.write_buffer(
+            &self.agents_buffer,
+            0,
+            bytemuck::cast_slice(&agents),
+        );
+        queue.write_buffer(
+            &self.deposits_buffer,
+            0,
+            &vec![0; (w * h * 4) as usize],
+        );
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.trails[self.current],
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &vec![0; (w * h * 8) as usize],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(w * 8),
+                rows_per_image: Some(h),
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Runs `iterations` simulation steps, first applying shader and agent
+    /// count changes
+    pub fn step(&mut self, app: &App, iterations: usize) {
+        self.update_shader(app);
+        self.update_agent_count(app);
+
+        if iterations == 0 {
+            return;
+        }
+
+        let window = app.main_window();
+        let device = window.device();
+        let queue = window.queue();
+        let [w, h] = self.size;
+
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::bytes_of(&self.shader_params()),
+        );
+        self.frame = self.frame.wrapping_add(1);
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Physarum Encoder"),
+            });
+
+        {
+            let mut pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Physarum Pass"),
+                });
+
+            for _ in 0..iterations {
+                pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+
+                if self.allocated_agents > 0 {
+                    pass.set_pipeline(&self.agents_pipeline);
+                    pass.dispatch_workgroups(
+                        (self.allocated_agents as u32)
+                            .div_ceil(AGENTS_WORKGROUP_SIZE),
+                        1,
+                        1,
+                    );
+                }
+
+                pass.set_pipeline(&self.diffuse_pipeline);
+                pass.dispatch_workgroups(
+                    w.div_ceil(DIFFUSE_WORKGROUP_SIZE),
+                    h.div_ceil(DIFFUSE_WORKGROUP_SIZE),
+                    1,
+                );
+
+                self.current = 1 - self.current;
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+
+    fn shader_params(&self) -> ShaderParams {
+        let p = &self.params;
+        let [w, h] = self.size;
+        ShaderParams {
+            a: [
+                w as f32,
+                h as f32,
+                self.allocated_agents as f32,
+                // Kept small enough to stay exact as an f32
+                (self.frame % 65536) as f32,
+            ],
+            b: [
+                p.sensor_angle,
+                p.sensor_distance,
+                p.turn_speed,
+                p.move_speed,
+            ],
+            c: [p.deposit, p.decay, p.diffuse, 0.0],
+        }
+    }
+
+    fn update_shader(&mut self, app: &App) {
+        let Some(path) = self
+            .shader_state
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+        else {
+            return;
+        };
+
+        info!("Reloading physarum shader from {:?}", path);
+
+        let shader_content = match fs::read_to_string(&path) {
+            Ok(content) => preprocess_shader(&content),
+            Err(e) => {
+                error!("Failed to read {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        if validate_shader(&shader_content).is_err() {
+            return;
+        }
+
+        let window = app.main_window();
+        (self.agents_pipeline, self.diffuse_pipeline) = create_pipelines(
+            window.device(),
+            &self.pipeline_layout,
+            &shader_content,
+        );
+    }
+
+    fn update_agent_count(&mut self, app: &App) {
+        if self.agent_count == self.allocated_agents {
+            return;
+        }
+
+        let window = app.main_window();
+        let device = window.device();
+
+        self.allocated_agents = self.agent_count;
+        self.agents_buffer = create_agents_buffer(device, self.agent_count);
+        self.bind_groups = create_bind_groups(
+            device,
+            &self.bind_group_layout,
+            &self.params_buffer,
+            &self.agents_buffer,
+            &self.deposits_buffer,
+            &self.views,
+        );
+
+        let agents = spawn_agents(&mut self.rng, self.agent_count, self.size);
+        window.queue().write_buffer(
+            &self.agents_buffer,
+            0,
+            bytemuck::cast_slice(&agents),
+        );
+        self.report_usage();
+    }
+
+    fn report_usage(&mut self) {
+        let usage = GpuUsage::default()
+            .buffer(self.params_buffer.size())
+            .buffer(self.agents_buffer.size())
+            .buffer(self.deposits_buffer.size())
+            .texture(self.size, FORMAT, 1)
+            .texture(self.size, FORMAT, 1)
+            .pipelines(2);
+        self.usage_report.set(usage);
+    }
+}
+
+fn create_pipelines(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    source: &str,
+) -> (wgpu::ComputePipeline, wgpu::ComputePipeline) {
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Physarum Shader"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+
+    let create = |entry_point| {
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Physarum Pipeline"),
+            layout: Some(layout),
+            module: &module,
+            entry_point,
+        })
+    };
+
+    (create(AGENTS_ENTRY_POINT), create(DIFFUSE_ENTRY_POINT))
+}
+
+fn create_agents_buffer(device: &wgpu::Device, count: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Physarum Agents Buffer"),
+        // Empty bindings aren't allowed
+        size: (count.max(1) * std::mem::size_of::<Agent>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    params_buffer: &wgpu::Buffer,
+    agents_buffer: &wgpu::Buffer,
+    deposits_buffer: &wgpu::Buffer,
+    views: &[wgpu::TextureView; 2],
+) -> [wgpu::BindGroup; 2] {
+    [0, 1].map(|i| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Physarum Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: agents_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: deposits_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&views[i]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&views[1 - i]),
+                },
+            ],
+        })
+    })
+}
+
+/// Random positions within `size` texels and random headings
+fn spawn_agents(
+    rng: &mut impl Rng,
+    count: usize,
+    size: [u32; 2],
+) -> Vec<Agent> {
+    let [w, h] = size.map(|x| x as f32);
+    (0..count)
+        .map(|_| Agent {
+            position: [rng.gen_range(0.0..w), rng.gen_range(0.0..h)],
+            angle: rng.gen_range(0.0..TAU),
+            _pad: 0.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        validate_shader(PHYSARUM_WGSL).unwrap();
+    }
+
+    #[test]
+    fn test_wgsl_matches_rust() {
+        for entry_point in [AGENTS_ENTRY_POINT, DIFFUSE_ENTRY_POINT] {
+            assert!(PHYSARUM_WGSL.contains(&format!("fn {}(", entry_point)));
+        }
+        assert!(
+            PHYSARUM_WGSL.contains(&format!(
+                "@workgroup_size({})",
+                AGENTS_WORKGROUP_SIZE
+            ))
+        );
+        assert!(PHYSARUM_WGSL.contains(&format!(
+            "@workgroup_size({}, {})",
+            DIFFUSE_WORKGROUP_SIZE, DIFFUSE_WORKGROUP_SIZE
+        )));
+        // The WGSL struct is vec2f + f32 + padding
+        assert_eq!(std::mem::size_of::<Agent>(), 16);
+    }
+
+    #[test]
+    fn test_spawn_agents() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let agents = spawn_agents(&mut rng, 100, [20, 10]);
+        assert_eq!(agents.len(), 100);
+        for agent in agents {
+            assert!((0.0..20.0).contains(&agent.position[0]));
+            assert!((0.0..10.0).contains(&agent.position[1]));
+            assert!((0.0..TAU).contains(&agent.angle));
+        }
+    }
+}
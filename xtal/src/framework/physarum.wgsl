@@ -0,0 +1,146 @@
+// -----------------------------------------------------------------------------
+//  XTAL PHYSARUM
+//  Agent-based slime mold. `agents` senses the trail map, turns toward the
+//  strongest trail, moves, and deposits; `diffuse` blurs and decays the trail
+//  map into the other ping-pong texture. See physarum.rs
+// -----------------------------------------------------------------------------
+
+const TAU: f32 = 6.283185307179586;
+
+// Deposits are accumulated as fixed point so agents landing on the same texel
+// can add atomically
+const DEPOSIT_SCALE: f32 = 1024.0;
+
+struct Params {
+    // w, h, agent_count, frame (seeds random turns)
+    a: vec4f,
+    // sensor_angle, sensor_distance, turn_speed, move_speed
+    b: vec4f,
+    // deposit, decay, diffuse, ..unused
+    c: vec4f,
+}
+
+struct Agent {
+    position: vec2f,
+    angle: f32,
+    _pad: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var<storage, read_write> agents: array<Agent>;
+
+@group(0) @binding(2)
+var<storage, read_write> deposits: array<atomic<u32>>;
+
+@group(0) @binding(3)
+var trail_source: texture_2d<f32>;
+
+@group(0) @binding(4)
+var trail_destination: texture_storage_2d<rgba16float, write>;
+
+fn size() -> vec2i {
+    return vec2i(params.a.xy);
+}
+
+fn wrap(position: vec2i) -> vec2i {
+    let s = size();
+    return (position % s + s) % s;
+}
+
+fn hash(value: u32) -> u32 {
+    var x = value;
+    x ^= x >> 16u;
+    x *= 0x7feb352du;
+    x ^= x >> 15u;
+    x *= 0x846ca68bu;
+    x ^= x >> 16u;
+    return x;
+}
+
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed)) / 4294967295.0;
+}
+
+fn sense(agent: Agent, offset: f32) -> f32 {
+    let angle = agent.angle + offset;
+    let p = agent.position + vec2f(cos(angle), sin(angle)) * params.b.y;
+    return textureLoad(trail_source, wrap(vec2i(floor(p))), 0).r;
+}
+
+@compute @workgroup_size(64)
+fn agents_main(@builtin(global_invocation_id) id: vec3u) {
+    let index = id.x;
+    if (index >= u32(params.a.z)) {
+        return;
+    }
+
+    let sensor_angle = params.b.x;
+    let turn_speed = params.b.z;
+    let move_speed = params.b.w;
+    let deposit = params.c.x;
+
+    var agent = agents[index];
+
+    let forward = sense(agent, 0.0);
+    let left = sense(agent, sensor_angle);
+    let right = sense(agent, -sensor_angle);
+    // Positions change every iteration so mixing them in varies the seed
+    // between iterations of the same frame
+    let r = random(
+        hash(index) ^ u32(params.a.w) ^ bitcast<u32>(agent.position.x)
+            ^ hash(bitcast<u32>(agent.position.y))
+    );
+
+    if (forward > left && forward > right) {
+        // Keep going
+    } else if (forward < left && forward < right) {
+        agent.angle += (r - 0.5) * 2.0 * turn_speed;
+    } else if (left > right) {
+        agent.angle += r * turn_speed;
+    } else if (right > left) {
+        agent.angle -= r * turn_speed;
+    }
+
+    let s = vec2f(size());
+    let moved = agent.position
+        + vec2f(cos(agent.angle), sin(agent.angle)) * move_speed;
+    agent.position = (moved % s + s) % s;
+    agent.angle = agent.angle % TAU;
+    agents[index] = agent;
+
+    let texel = wrap(vec2i(floor(agent.position)));
+    let i = u32(texel.y * size().x + texel.x);
+    atomicAdd(&deposits[i], u32(deposit * DEPOSIT_SCALE));
+}
+
+@compute @workgroup_size(8, 8)
+fn diffuse_main(@builtin(global_invocation_id) id: vec3u) {
+    let position = vec2i(id.xy);
+    let s = size();
+    if (position.x >= s.x || position.y >= s.y) {
+        return;
+    }
+
+    let decay = params.c.y;
+    let diffuse = params.c.z;
+
+    var sum = 0.0;
+    for (var y = -1; y <= 1; y += 1) {
+        for (var x = -1; x <= 1; x += 1) {
+            let p = wrap(position + vec2i(x, y));
+            sum += textureLoad(trail_source, p, 0).r;
+        }
+    }
+
+    let i = u32(position.y * s.x + position.x);
+    let deposited = f32(atomicExchange(&deposits[i], 0u)) / DEPOSIT_SCALE;
+
+    let center = textureLoad(trail_source, position, 0).r + deposited;
+    let blurred = sum / 9.0 + deposited;
+    let value = mix(center, blurred, diffuse) * (1.0 - decay);
+
+    textureStore(trail_destination, position, vec4f(value, value, value, 1.0));
+}
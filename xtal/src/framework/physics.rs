@@ -0,0 +1,344 @@
+//! Lightweight verlet physics for cloth, rope, and blob style sketches. Time is
+//! measured in beats so simulations stay in sync with the sketch's tempo.
+//!
+//! # Example
+//! ```rust,ignore
+//! let mut world = World::new().gravity(vec2(0.0, -200.0));
+//! let a = world.add_pinned(vec2(0.0, 100.0));
+//! let b = world.add_particle(vec2(50.0, 100.0));
+//! world.add_constraint(a, b);
+//!
+//! // in update
+//! world.update(self.hub.animation.beats());
+//! ```
+
+use nannou::prelude::*;
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub position: Vec2,
+    pub previous: Vec2,
+    pub acceleration: Vec2,
+    pub inverse_mass: f32,
+    pub pinned: bool,
+}
+
+impl Particle {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            previous: position,
+            acceleration: Vec2::ZERO,
+            inverse_mass: 1.0,
+            pinned: false,
+        }
+    }
+
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.inverse_mass = ternary!(mass > 0.0, 1.0 / mass, 0.0);
+        self
+    }
+
+    /// Implicit velocity (per step) as stored by the verlet integrator
+    pub fn velocity(&self) -> Vec2 {
+        self.position - self.previous
+    }
+
+    pub fn apply_force(&mut self, force: Vec2) {
+        self.acceleration += force * self.inverse_mass;
+    }
+
+    /// Move the particle without imparting velocity
+    pub fn teleport(&mut self, position: Vec2) {
+        self.position = position;
+        self.previous = position;
+    }
+}
+
+/// A soft link between two particles that pulls them toward `rest_length`
+/// proportionally to `stiffness` in the range [0.0, 1.0]
+#[derive(Clone, Copy, Debug)]
+pub struct Spring {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+}
+
+/// A rigid link between two particles. Equivalent to a [`Spring`] with a
+/// stiffness of 1.0 but kept separate so they can be drawn differently
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceConstraint {
+    pub a: usize,
+    pub b: usize,
+    pub length: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct World {
+    pub particles: Vec<Particle>,
+    pub springs: Vec<Spring>,
+    pub constraints: Vec<DistanceConstraint>,
+    /// Acceleration applied to every particle in units per beat²
+    pub gravity: Vec2,
+    /// Fraction of velocity retained each step in [0.0, 1.0]
+    pub damping: f32,
+    /// Number of constraint relaxation passes per step. Higher is stiffer
+    pub iterations: usize,
+    /// Particles are kept inside these bounds when set
+    pub bounds: Option<Rect>,
+    /// Fraction of velocity retained when bouncing off of bounds
+    pub restitution: f32,
+    /// Upper limit of a single integration step in beats. Larger deltas are
+    /// divided into multiple sub-steps to keep the simulation stable
+    pub max_step: f32,
+    last_beat: Option<f32>,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            particles: vec![],
+            springs: vec![],
+            constraints: vec![],
+            gravity: Vec2::ZERO,
+            damping: 0.99,
+            iterations: 4,
+            bounds: None,
+            restitution: 0.5,
+            max_step: 1.0 / 16.0,
+            last_beat: None,
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    /// Returns the index of the new particle
+    pub fn add_particle(&mut self, position: Vec2) -> usize {
+        self.particles.push(Particle::new(position));
+        self.particles.len() - 1
+    }
+
+    pub fn add_pinned(&mut self, position: Vec2) -> usize {
+        let index = self.add_particle(position);
+        self.particles[index].pinned = true;
+        index
+    }
+
+    /// Adds a spring with a rest length equal to the particles' current
+    /// distance
+    pub fn add_spring(&mut self, a: usize, b: usize, stiffness: f32) {
+        let rest_length = self.distance(a, b);
+        self.springs.push(Spring {
+            a,
+            b,
+            rest_length,
+            stiffness: stiffness.clamp(0.0, 1.0),
+        });
+    }
+
+    /// Adds a rigid link with a length equal to the particles' current
+    /// distance
+    pub fn add_constraint(&mut self, a: usize, b: usize) {
+        let length = self.distance(a, b);
+        self.constraints.push(DistanceConstraint { a, b, length });
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> f32 {
+        self.particles[a]
+            .position
+            .distance(self.particles[b].position)
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.springs.clear();
+        self.constraints.clear();
+        self.last_beat = None;
+    }
+
+    /// Advance the simulation to `beats`, usually `hub.animation.beats()`. The
+    /// first call only records the starting point. Backwards jumps (e.g. a
+    /// MIDI Start/SPP) reset the reference without stepping.
+    pub fn update(&mut self, beats: f32) {
+        let Some(last_beat) = self.last_beat.replace(beats) else {
+            return;
+        };
+
+        let delta = beats - last_beat;
+        if delta <= 0.0 {
+            return;
+        }
+
+        let steps = (delta / self.max_step).ceil().max(1.0) as usize;
+        let dt = delta / steps as f32;
+        for _ in 0..steps {
+            self.step(dt);
+        }
+    }
+
+    /// Advance the simulation by `dt` beats
+    pub fn step(&mut self, dt: f32) {
+        self.integrate(dt);
+        for _ in 0..self.iterations {
+            self.solve_springs();
+            self.solve_constraints();
+            self.collide_bounds();
+        }
+    }
+
+    fn integrate(&mut self, dt: f32) {
+        let dt2 = dt * dt;
+        for p in self.particles.iter_mut() {
+            if p.pinned {
+                p.previous = p.position;
+                p.acceleration = Vec2::ZERO;
+                continue;
+            }
+            let velocity = p.velocity() * self.damping;
+            let acceleration = p.acceleration + self.gravity;
+            p.previous = p.position;
+            p.position += velocity + acceleration * dt2;
+            p.acceleration = Vec2::ZERO;
+        }
+    }
+
+    fn solve_springs(&mut self) {
+        for i in 0..self.springs.len() {
+            let Spring {
+                a,
+                b,
+                rest_length,
+                stiffness,
+            } = self.springs[i];
+            self.relax(a, b, rest_length, stiffness);
+        }
+    }
+
+    fn solve_constraints(&mut self) {
+        for i in 0..self.constraints.len() {
+            let DistanceConstraint { a, b, length } = self.constraints[i];
+            self.relax(a, b, length, 1.0);
+        }
+    }
+
+    fn relax(&mut self, a: usize, b: usize, target: f32, stiffness: f32) {
+        let pa = &self.particles[a];
+        let pb = &self.particles[b];
+        let wa = ternary!(pa.pinned, 0.0, pa.inverse_mass);
+        let wb = ternary!(pb.pinned, 0.0, pb.inverse_mass);
+        let total = wa + wb;
+        if total == 0.0 {
+            return;
+        }
+
+        let delta = pb.position - pa.position;
+        let distance = delta.length();
+        if distance < f32::EPSILON {
+            return;
+        }
+
+        let correction = delta * ((distance - target) / distance) * stiffness;
+        self.particles[a].position += correction * (wa / total);
+        self.particles[b].position -= correction * (wb / total);
+    }
+
+    fn collide_bounds(&mut self) {
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+
+        for p in self.particles.iter_mut().filter(|p| !p.pinned) {
+            let velocity = p.velocity();
+
+            if p.position.x < bounds.left() || p.position.x > bounds.right() {
+                p.position.x =
+                    p.position.x.clamp(bounds.left(), bounds.right());
+                p.previous.x = p.position.x + velocity.x * self.restitution;
+            }
+
+            if p.position.y < bounds.bottom() || p.position.y > bounds.top() {
+                p.position.y =
+                    p.position.y.clamp(bounds.bottom(), bounds.top());
+                p.previous.y = p.position.y + velocity.y * self.restitution;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_gravity_pulls_free_particles_only() {
+        let mut world = World::new().gravity(vec2(0.0, -10.0));
+        let pinned = world.add_pinned(vec2(0.0, 0.0));
+        let free = world.add_particle(vec2(10.0, 0.0));
+
+        for _ in 0..10 {
+            world.step(0.1);
+        }
+
+        assert_eq!(world.particles[pinned].position, vec2(0.0, 0.0));
+        assert!(world.particles[free].position.y < 0.0);
+    }
+
+    #[test]
+    fn test_constraint_preserves_length() {
+        let mut world = World::new().gravity(vec2(0.0, -100.0)).iterations(16);
+        let a = world.add_pinned(vec2(0.0, 0.0));
+        let b = world.add_particle(vec2(10.0, 0.0));
+        world.add_constraint(a, b);
+
+        for _ in 0..20 {
+            world.step(0.05);
+        }
+
+        assert_approx_eq!(world.distance(a, b), 10.0, 0.01);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bounds = Rect::from_w_h(100.0, 100.0);
+        let mut world = World::new().gravity(vec2(0.0, -1000.0)).bounds(bounds);
+        let p = world.add_particle(vec2(0.0, 0.0));
+
+        world.update(0.0);
+        world.update(4.0);
+
+        assert!(world.particles[p].position.y >= bounds.bottom());
+    }
+}
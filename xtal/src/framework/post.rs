@@ -0,0 +1,655 @@
+//! Built-in post-processing stack applied to the final frame (Draw and
+//! GpuState output alike). Effects, their order, and their params are declared
+//! under the top-level `post` key of a sketch's control script and hot-reload
+//! like any other control. Params accept `$name` references so they can be
+//! driven by sliders, MIDI, audio, or animations. The runtime applies the stack
+//! right after the sketch's `view`, so sketches don't need to do anything.
+//!
+//! # Example
+//! ```yaml
+//! glow:
+//!   type: slider
+//!   default: 0.5
+//!
+//! post:
+//!   - bloom:
+//!       threshold: 0.7
+//!       intensity: $glow
+//!       radius: 4
+//!   - chromatic_aberration:
+//!       amount: 2
+//!   - vignette:
+//!       amount: 0.5
+//!       softness: 0.5
+//!   - grain:
+//!       amount: 0.05
+//!   # A .cube file relative to this script; hot-reloads on save
+//!   - lut:
+//!       path: grade.cube
+//!       strength: 0.8
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+use std::sync::Arc;
+
+use crate::framework::frame_controller;
+use crate::framework::lut::Lut;
+use crate::framework::prelude::*;
+
+/// The WGSL source of every post pass
+pub const POST_WGSL: &str = include_str!("post.wgsl");
+
+/// A resolved (all params are plain numbers) post effect. See
+/// [`crate::prelude::ControlHub::post_effects`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PostEffect {
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    Grain {
+        amount: f32,
+        size: f32,
+    },
+    Vignette {
+        amount: f32,
+        softness: f32,
+    },
+    ChromaticAberration {
+        amount: f32,
+    },
+    /// Color grade through a 3D LUT. `strength` blends between the input (0)
+    /// and the fully graded color (1)
+    Lut {
+        lut: Arc<Lut>,
+        strength: f32,
+    },
+    /// Sorts runs of pixels brighter than `threshold` by luminance, brightest
+    /// last along `direction` (an axis-aligned unit vector in texture space,
+    /// y down). Each iteration is one pass of an odd-even transposition sort
+    PixelSort {
+        threshold: f32,
+        direction: [f32; 2],
+        iterations: usize,
+    },
+    /// Displaces random blocks like a corrupted video stream
+    Datamosh {
+        amount: f32,
+        block_size: f32,
+        probability: f32,
+        hold: f32,
+    },
+    /// Randomly tears lines sideways over darkened scanlines
+    ScanlineGlitch {
+        amount: f32,
+        frequency: f32,
+        line_height: f32,
+        scanlines: f32,
+    },
+}
+
+/// Keeps a runaway `iterations` param from queueing thousands of passes
+const MAX_PIXEL_SORT_ITERATIONS: usize = 512;
+
+impl PostEffect {
+    /// Fragment entry points in post.wgsl run (in order) for this effect
+    fn passes(&self) -> Vec<&'static str> {
+        match self {
+            Self::Bloom { .. } => vec!["fs_bloom_h", "fs_bloom_v"],
+            Self::Grain { .. } => vec!["fs_grain"],
+            Self::Vignette { .. } => vec!["fs_vignette"],
+            Self::ChromaticAberration { .. } => {
+                vec!["fs_chromatic_aberration"]
+            }
+            Self::Lut { .. } => vec!["fs_lut"],
+            Self::PixelSort { iterations, .. } => {
+                let iterations = (*iterations).min(MAX_PIXEL_SORT_ITERATIONS);
+                (0..iterations)
+                    .map(|i| {
+                        ternary!(
+                            i % 2 == 0,
+                            "fs_pixel_sort_even",
+                            "fs_pixel_sort_odd"
+                        )
+                    })
+                    .collect()
+            }
+            Self::Datamosh { .. } => vec!["fs_datamosh"],
+            Self::ScanlineGlitch { .. } => vec!["fs_scanline_glitch"],
+        }
+    }
+
+    /// Packed into `Params::values` in post.wgsl
+    fn values(&self) -> [f32; 4] {
+        match *self {
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => [threshold, intensity, radius, 0.0],
+            Self::Grain { amount, size } => [amount, size, 0.0, 0.0],
+            Self::Vignette { amount, softness } => [amount, softness, 0.0, 0.0],
+            Self::ChromaticAberration { amount } => [amount, 0.0, 0.0, 0.0],
+            Self::Lut { ref lut, strength } => {
+                [strength, lut.size as f32, 0.0, 0.0]
+            }
+            Self::PixelSort {
+                threshold,
+                direction,
+                ..
+            } => [threshold, direction[0], direction[1], 0.0],
+            Self::Datamosh {
+                amount,
+                block_size,
+                probability,
+                hold,
+            } => [amount, block_size, probability, hold],
+            Self::ScanlineGlitch {
+                amount,
+                frequency,
+                line_height,
+                scanlines,
+            } => [amount, frequency, line_height, scanlines],
+        }
+    }
+}
+
+const ENTRY_POINTS: &[&str] = &[
+    "fs_bloom_h",
+    "fs_bloom_v",
+    "fs_grain",
+    "fs_vignette",
+    "fs_chromatic_aberration",
+    "fs_lut",
+    "fs_pixel_sort_even",
+    "fs_pixel_sort_odd",
+    "fs_datamosh",
+    "fs_scanline_glitch",
+];
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    resolution: [f32; 2],
+    time: f32,
+    _pad: f32,
+    values: [f32; 4],
+    lut_domain_min: [f32; 4],
+    lut_domain_max: [f32; 4],
+}
+
+/// GPU resources sized for a specific frame. Rebuilt on resize
+struct Resources {
+    size: [u32; 2],
+    dst_sample_count: u32,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+
+    /// Bound in place of a LUT for every other effect
+    identity_lut: wgpu::TextureViewHandle,
+
+    /// Single-sampled ping-pong targets. The frame is copied into one of
+    /// these before the first pass
+    targets: [wgpu::TextureView; 3],
+
+    /// Copies the frame into `targets[0]` when the frame isn't multisampled
+    /// (multisampled frames are resolved instead)
+    reshaper: Option<wgpu::TextureReshaper>,
+
+    /// Pipelines for intermediate passes (rendering into `targets`)
+    pipelines: HashMap<&'static str, wgpu::RenderPipeline>,
+
+    /// Pipelines for the last pass (rendering into the frame)
+    final_pipelines: HashMap<&'static str, wgpu::RenderPipeline>,
+
+    /// One per pass since all writes happen before the frame is submitted
+    params_buffers: Vec<wgpu::Buffer>,
+}
+
+impl Resources {
+    fn new(frame: &Frame) -> Self {
+        let device = frame.device_queue_pair().device();
+        let size = frame.texture_size();
+        let dst_sample_count = frame.texture_msaa_samples();
+
+        let shader_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Post Shader"),
+                source: wgpu::ShaderSource::Wgsl(POST_WGSL.into()),
+            });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float {
+                    filterable: true,
+                },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<Params>() as _,
+                            ),
+                        },
+                        count: None,
+                    },
+                    texture_entry(1),
+                    texture_entry(2),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D3,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("Post Bind Group Layout"),
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let create_pipeline = |entry_point, sample_count| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Post Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Frame::TEXTURE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        };
+
+        let pipelines = ENTRY_POINTS
+            .iter()
+            .map(|entry| (*entry, create_pipeline(*entry, 1)))
+            .collect();
+
+        let final_pipelines = ENTRY_POINTS
+            .iter()
+            .map(|entry| (*entry, create_pipeline(*entry, dst_sample_count)))
+            .collect();
+
+        let targets = [(); 3].map(|_| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(Frame::TEXTURE_FORMAT)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                )
+                .sample_count(1)
+                .build(device)
+                .view()
+                .build()
+        });
+
+        let reshaper = (dst_sample_count == 1).then(|| {
+            wgpu::TextureReshaper::new(
+                device,
+                frame.texture_view(),
+                1,
+                wgpu::TextureSampleType::Float { filterable: true },
+                1,
+                Frame::TEXTURE_FORMAT,
+            )
+        });
+
+        // Linear filtering is what makes LUT lookups trilinear
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            size,
+            dst_sample_count,
+            bind_group_layout,
+            sampler,
+            identity_lut: upload_lut(
+                device,
+                frame.device_queue_pair().queue(),
+                &Lut::identity(2),
+            ),
+            targets,
+            reshaper,
+            pipelines,
+            final_pipelines,
+            params_buffers: vec![],
+        }
+    }
+
+    fn ensure_params_buffers(&mut self, device: &wgpu::Device, count: usize) {
+        while self.params_buffers.len() < count {
+            self.params_buffers.push(device.create_buffer(
+                &wgpu::BufferDescriptor {
+                    label: Some("Post Params Buffer"),
+                    size: std::mem::size_of::<Params>() as u64,
+                    usage: wgpu::BufferUsages::UNIFORM
+                        | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                },
+            ));
+        }
+    }
+
+    /// Copies the frame's current contents into `targets[0]`
+    fn capture(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        match &self.reshaper {
+            Some(reshaper) => {
+                reshaper.encode_render_pass(&self.targets[0], encoder);
+            }
+            None => {
+                // An empty pass is enough to resolve the multisampled frame
+                wgpu::RenderPassBuilder::new()
+                    .color_attachment(frame.texture_view(), |color| {
+                        color
+                            .load_op(wgpu::LoadOp::Load)
+                            .resolve_target(Some(&*self.targets[0]))
+                    })
+                    .begin(encoder);
+            }
+        }
+    }
+}
+
+/// Creates a 3D texture view of `lut` with red along x, green along y, and
+/// blue along z
+fn upload_lut(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    lut: &Lut,
+) -> wgpu::TextureViewHandle {
+    device
+        .create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Post LUT Texture"),
+                size: wgpu::Extent3d {
+                    width: lut.size,
+                    height: lut.size,
+                    depth_or_array_layers: lut.size,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D3,
+                format: wgpu::TextureFormat::Rgba16Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            &lut.rgba_f16_bytes(),
+        )
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Runs the current sketch's [`PostEffect`]s over the frame. Applied by the
+/// runtime after `view`
+#[derive(Default)]
+pub(crate) struct PostStack {
+    effects: Vec<PostEffect>,
+    resources: Option<Resources>,
+
+    /// GPU copies of the LUTs referenced by `effects`. A reloaded LUT arrives
+    /// as a new `Arc` so stale entries are dropped by pointer comparison
+    luts: Vec<(Arc<Lut>, wgpu::TextureViewHandle)>,
+}
+
+impl std::fmt::Debug for PostStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostStack")
+            .field("effects", &self.effects)
+            .finish()
+    }
+}
+
+impl PostStack {
+    pub fn set_effects(&mut self, effects: Vec<PostEffect>) {
+        self.effects = effects;
+    }
+
+    pub fn apply(&mut self, frame: &Frame) {
+        let effects = &self.effects;
+        let total_passes: usize =
+            effects.iter().map(|e| e.passes().len()).sum();
+        if total_passes == 0 {
+            return;
+        }
+        let time = frame_controller::frame_count() as f32;
+
+        if self.resources.as_ref().is_none_or(|r| {
+            r.size != frame.texture_size()
+                || r.dst_sample_count != frame.texture_msaa_samples()
+        }) {
+            self.resources = Some(Resources::new(frame));
+        }
+
+        let device = frame.device_queue_pair().device();
+        let queue = frame.device_queue_pair().queue();
+        let resources = self.resources.as_mut().unwrap();
+        resources.ensure_params_buffers(device, total_passes);
+        let resources = &*resources;
+
+        let effect_luts: Vec<&Arc<Lut>> = effects
+            .iter()
+            .filter_map(|effect| match effect {
+                PostEffect::Lut { lut, .. } => Some(lut),
+                _ => None,
+            })
+            .collect();
+        self.luts
+            .retain(|(lut, _)| effect_luts.iter().any(|l| Arc::ptr_eq(l, lut)));
+        for lut in effect_luts {
+            if !self.luts.iter().any(|(l, _)| Arc::ptr_eq(l, lut)) {
+                let view = upload_lut(device, queue, lut);
+                self.luts.push((lut.clone(), view));
+            }
+        }
+
+        let mut encoder = frame.command_encoder();
+        resources.capture(frame, &mut encoder);
+
+        let [w, h] = resources.size;
+        let mut source = 0;
+        let mut pass_index = 0;
+
+        for effect in effects {
+            let original = source;
+
+            let (lut_view, lut_domain_min, lut_domain_max) = match effect {
+                PostEffect::Lut { lut, .. } => (
+                    &self
+                        .luts
+                        .iter()
+                        .find(|(l, _)| Arc::ptr_eq(l, lut))
+                        .unwrap()
+                        .1,
+                    lut.domain_min.extend(0.0).to_array(),
+                    lut.domain_max.extend(1.0).to_array(),
+                ),
+                _ => (&resources.identity_lut, [0.0; 4], [1.0; 4]),
+            };
+
+            for entry_point in effect.passes() {
+                let is_last = pass_index == total_passes - 1;
+
+                let params_buffer = &resources.params_buffers[pass_index];
+                queue.write_buffer(
+                    params_buffer,
+                    0,
+                    bytemuck::bytes_of(&Params {
+                        resolution: [w as f32, h as f32],
+                        time,
+                        _pad: 0.0,
+                        values: effect.values(),
+                        lut_domain_min,
+                        lut_domain_max,
+                    }),
+                );
+
+                let bind_group =
+                    device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        layout: &resources.bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: params_buffer.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &resources.targets[source],
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: wgpu::BindingResource::TextureView(
+                                    &resources.targets[original],
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::Sampler(
+                                    &resources.sampler,
+                                ),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 4,
+                                resource: wgpu::BindingResource::TextureView(
+                                    lut_view,
+                                ),
+                            },
+                        ],
+                        label: Some("Post Bind Group"),
+                    });
+
+                // Any target that isn't being read from this pass
+                let output = (0..resources.targets.len())
+                    .find(|i| *i != source && *i != original)
+                    .unwrap();
+
+                let (target, pipeline) = if is_last {
+                    (
+                        frame.texture_view(),
+                        &resources.final_pipelines[&entry_point],
+                    )
+                } else {
+                    (
+                        &*resources.targets[output],
+                        &resources.pipelines[&entry_point],
+                    )
+                };
+
+                let mut render_pass = wgpu::RenderPassBuilder::new()
+                    .color_attachment(target, |color| color)
+                    .begin(&mut encoder);
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+
+                source = output;
+                pass_index += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        let module = naga::front::wgsl::parse_str(POST_WGSL).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_entry_points_exist() {
+        for entry_point in ENTRY_POINTS {
+            assert!(
+                POST_WGSL.contains(&format!("fn {}(", entry_point)),
+                "post.wgsl is missing {}",
+                entry_point
+            );
+        }
+    }
+
+    #[test]
+    fn test_pixel_sort_passes() {
+        let sort = |iterations| PostEffect::PixelSort {
+            threshold: 0.5,
+            direction: [0.0, 1.0],
+            iterations,
+        };
+
+        assert!(sort(0).passes().is_empty());
+        assert_eq!(
+            sort(3).passes(),
+            [
+                "fs_pixel_sort_even",
+                "fs_pixel_sort_odd",
+                "fs_pixel_sort_even"
+            ]
+        );
+        assert_eq!(sort(100_000).passes().len(), MAX_PIXEL_SORT_ITERATIONS);
+    }
+}
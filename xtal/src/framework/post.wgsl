@@ -0,0 +1,253 @@
+// -----------------------------------------------------------------------------
+//  XTAL POST
+//  Built-in screen-space effects applied to the final frame. Each fragment
+//  entry point is a single pass; see `PostEffect::passes` in post.rs
+// -----------------------------------------------------------------------------
+
+struct Params {
+    resolution: vec2f,
+    // Frame count; used to animate grain
+    time: f32,
+    _pad: f32,
+    // Effect specific, see `PostEffect::values`
+    values: vec4f,
+    // LUT only: input range mapped onto the 3D texture
+    lut_domain_min: vec4f,
+    lut_domain_max: vec4f,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+// Output of the previous pass
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+// Input of the current effect (before any of its passes)
+@group(0) @binding(2)
+var original: texture_2d<f32>;
+
+@group(0) @binding(3)
+var tex_sampler: sampler;
+
+// The current effect's LUT (a 2x2x2 identity table for other effects)
+@group(0) @binding(4)
+var lut: texture_3d<f32>;
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2f(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+// -----------------------------------------------------------------------------
+//  Bloom: values = [threshold, intensity, radius, _]
+// -----------------------------------------------------------------------------
+
+fn bright(color: vec3f, threshold: f32) -> vec3f {
+    return max(color - vec3f(threshold), vec3f(0.0));
+}
+
+// 9 tap gaussian along `direction`. When `threshold` is >= 0 each tap is
+// bright-passed first
+fn blur(uv: vec2f, direction: vec2f, threshold: f32) -> vec3f {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let step = direction * params.values.z / params.resolution;
+
+    var sum = vec3f(0.0);
+    for (var i = -4; i <= 4; i++) {
+        let color = textureSample(source, tex_sampler, uv + step * f32(i)).rgb;
+        let tap = select(color, bright(color, threshold), threshold >= 0.0);
+        sum += tap * weights[abs(i)];
+    }
+
+    return sum;
+}
+
+@fragment
+fn fs_bloom_h(in: VertexOutput) -> @location(0) vec4f {
+    return vec4f(blur(in.uv, vec2f(1.0, 0.0), params.values.x), 1.0);
+}
+
+@fragment
+fn fs_bloom_v(in: VertexOutput) -> @location(0) vec4f {
+    let glow = blur(in.uv, vec2f(0.0, 1.0), -1.0);
+    let base = textureSample(original, tex_sampler, in.uv);
+    return vec4f(base.rgb + glow * params.values.y, base.a);
+}
+
+// -----------------------------------------------------------------------------
+//  Grain: values = [amount, size, _, _]
+// -----------------------------------------------------------------------------
+
+fn hash(p: vec3f) -> f32 {
+    var q = fract(p * 0.1031);
+    q += dot(q, q.zyx + 31.32);
+    return fract((q.x + q.y) * q.z);
+}
+
+@fragment
+fn fs_grain(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source, tex_sampler, in.uv);
+    let cell = floor(in.uv * params.resolution / max(params.values.y, 1.0));
+    let noise = hash(vec3f(cell, params.time)) - 0.5;
+    return vec4f(color.rgb + noise * params.values.x, color.a);
+}
+
+// -----------------------------------------------------------------------------
+//  Vignette: values = [amount, softness, _, _]
+// -----------------------------------------------------------------------------
+
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source, tex_sampler, in.uv);
+    // 0 at the center, 1 at the corners
+    let d = length((in.uv - 0.5) * 2.0) / sqrt(2.0);
+    let edge = 1.0 - clamp(params.values.y, 0.001, 1.0);
+    let falloff = smoothstep(edge, 1.0, d);
+    return vec4f(color.rgb * (1.0 - params.values.x * falloff), color.a);
+}
+
+// -----------------------------------------------------------------------------
+//  Chromatic Aberration: values = [amount, _, _, _]
+// -----------------------------------------------------------------------------
+
+@fragment
+fn fs_chromatic_aberration(in: VertexOutput) -> @location(0) vec4f {
+    let offset = (in.uv - 0.5) * 2.0 * params.values.x / params.resolution;
+    let r = textureSample(source, tex_sampler, in.uv + offset).r;
+    let center = textureSample(source, tex_sampler, in.uv);
+    let b = textureSample(source, tex_sampler, in.uv - offset).b;
+    return vec4f(r, center.g, b, center.a);
+}
+
+// -----------------------------------------------------------------------------
+//  LUT: values = [strength, size, _, _]
+// -----------------------------------------------------------------------------
+
+@fragment
+fn fs_lut(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source, tex_sampler, in.uv);
+    let size = params.values.y;
+    let range = params.lut_domain_max.rgb - params.lut_domain_min.rgb;
+    let coords = clamp(
+        (color.rgb - params.lut_domain_min.rgb) / range,
+        vec3f(0.0),
+        vec3f(1.0)
+    );
+    // Map onto texel centers so the hardware interpolates between entries
+    let uvw = coords * ((size - 1.0) / size) + 0.5 / size;
+    let graded = textureSample(lut, tex_sampler, uvw).rgb;
+    return vec4f(mix(color.rgb, graded, params.values.x), color.a);
+}
+
+// -----------------------------------------------------------------------------
+//  Pixel Sort: values = [threshold, direction.x, direction.y, _]
+//  One step of an odd-even transposition sort. Neighboring pixels that are
+//  both above the threshold swap when out of order, so over many passes runs
+//  of bright pixels sort by luminance with the brightest last along direction
+// -----------------------------------------------------------------------------
+
+fn luminance(color: vec3f) -> f32 {
+    return dot(color, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+fn pixel_sort(position: vec4f, parity: i32) -> vec4f {
+    let size = vec2i(params.resolution);
+    let p = vec2i(position.xy);
+    let direction = vec2i(params.values.yz);
+    let color = textureLoad(source, p, 0);
+
+    // Pairs alternate between (0, 1), (2, 3)... and (1, 2), (3, 4)... along
+    // the direction. The first pixel of each pair looks ahead, the second back
+    let index = p.x * direction.x + p.y * direction.y + parity;
+    let first = (index % 2 + 2) % 2 == 0;
+    let q = p + select(-direction, direction, first);
+    if (any(q < vec2i(0)) || any(q >= size)) {
+        return color;
+    }
+
+    let other = textureLoad(source, q, 0);
+    let l = luminance(color.rgb);
+    let lo = luminance(other.rgb);
+    if (l < params.values.x || lo < params.values.x) {
+        return color;
+    }
+
+    let swap = select(l < lo, l > lo, first);
+    return select(color, other, swap);
+}
+
+@fragment
+fn fs_pixel_sort_even(in: VertexOutput) -> @location(0) vec4f {
+    return pixel_sort(in.position, 0);
+}
+
+@fragment
+fn fs_pixel_sort_odd(in: VertexOutput) -> @location(0) vec4f {
+    return pixel_sort(in.position, 1);
+}
+
+// -----------------------------------------------------------------------------
+//  Datamosh: values = [amount, block_size, probability, hold]
+// -----------------------------------------------------------------------------
+
+@fragment
+fn fs_datamosh(in: VertexOutput) -> @location(0) vec4f {
+    let block_size = max(params.values.y, 1.0);
+    // New blocks are picked every `hold` frames
+    let seed = floor(params.time / max(params.values.w, 1.0));
+    let block = floor(in.uv * params.resolution / block_size);
+
+    let moshed = hash(vec3f(block, seed)) < params.values.z;
+    let direction = vec2f(
+        hash(vec3f(block, seed + 17.0)),
+        hash(vec3f(block.yx, seed + 31.0))
+    ) - 0.5;
+    let shift = select(
+        vec2f(0.0),
+        direction * 2.0 * params.values.x / params.resolution,
+        moshed
+    );
+
+    let color = textureSample(source, tex_sampler, in.uv + shift);
+    // Displaced blocks also smear toward the color at their source's center
+    // like a stale macroblock
+    let center = (block + 0.5) * block_size / params.resolution + shift;
+    let stale = textureSample(source, tex_sampler, center);
+    return select(color, mix(color, stale, 0.5), moshed);
+}
+
+// -----------------------------------------------------------------------------
+//  Scanline Glitch: values = [amount, frequency, line_height, scanlines]
+// -----------------------------------------------------------------------------
+
+@fragment
+fn fs_scanline_glitch(in: VertexOutput) -> @location(0) vec4f {
+    let line_height = max(params.values.z, 1.0);
+    let line = floor(in.uv.y * params.resolution.y / line_height);
+
+    let torn = hash(vec3f(line, 0.0, params.time)) < params.values.y;
+    let tear = (hash(vec3f(line, 1.0, params.time)) - 0.5) * 2.0;
+    let offset = select(0.0, tear * params.values.x / params.resolution.x, torn);
+
+    // Torn lines wrap around and split their red and blue channels
+    let uv = vec2f(fract(in.uv.x + offset), in.uv.y);
+    let split = vec2f(offset * 0.5, 0.0);
+    let r = textureSample(source, tex_sampler, uv + split).r;
+    let center = textureSample(source, tex_sampler, uv);
+    let b = textureSample(source, tex_sampler, uv - split).b;
+
+    let dark_row = floor(in.position.y) % 2.0;
+    let dim = 1.0 - params.values.w * dark_row;
+    return vec4f(vec3f(r, center.g, b) * dim, center.a);
+}
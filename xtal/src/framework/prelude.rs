@@ -5,13 +5,18 @@ pub use crate::debug_throttled;
 pub use crate::framework::audio::*;
 pub use crate::framework::control::*;
 #[allow(unused_imports)]
+#[cfg(feature = "runtime")]
 pub use crate::framework::gpu;
 pub use crate::framework::logging::*;
 pub use crate::framework::midi;
 pub use crate::framework::motion::*;
 pub use crate::framework::noise::*;
+#[cfg(feature = "runtime")]
+pub use crate::framework::rng::RngStream;
+#[cfg(feature = "runtime")]
 pub use crate::framework::sketch::*;
 pub use crate::framework::util::*;
+#[cfg(feature = "runtime")]
 pub use crate::framework::window_rect::*;
 pub use crate::ternary;
 pub use crate::warn_once;
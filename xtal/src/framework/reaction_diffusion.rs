@@ -0,0 +1,557 @@
+//! A ready-made Gray-Scott reaction-diffusion simulation run as a compute
+//! shader. Chemical A lives in the red channel and B in the green channel of
+//! an RGBA16F texture that can be bound to any
+//! [`crate::prelude::gpu::GpuState`] with `set_texture` or handed to other
+//! sketches through [`crate::prelude::Sketch::render_output`].
+//!
+//! # Example
+//! ```yaml
+//! rd.preset:
+//!   type: select
+//!   default: coral
+//!   options: [custom, coral, mitosis, spots, worms, maze, waves, holes]
+//!
+//! # Only read while the preset is `custom`
+//! rd.feed:
+//!   type: slider
+//!   default: 0.055
+//!   range: [0.0, 0.1]
+//!   step: 0.0001
+//! ```
+//!
+//! ```rust,ignore
+//! // init
+//! let mut rd = gpu::ReactionDiffusion::new(app, [512, 512]);
+//! rd.seed_random(app, &mut ctx.rng("seed"), 20, 0.02);
+//!
+//! // update
+//! self.rd.update_params(&self.hub, "rd");
+//! self.rd.step(app, 16);
+//! self.gpu.set_texture(app, self.rd.texture_view());
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::rand::Rng;
+use nannou::wgpu;
+
+use crate::framework::gpu_stats::{GpuUsage, UsageReport};
+use crate::framework::lut::f16_bits;
+use crate::framework::prelude::*;
+
+/// The WGSL source of the simulation step
+pub const REACTION_DIFFUSION_WGSL: &str =
+    include_str!("reaction_diffusion.wgsl");
+
+/// Must match `@workgroup_size` in reaction_diffusion.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Feed and kill rates known to produce distinct patterns with the default
+/// diffusion rates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReactionDiffusionPreset {
+    Coral,
+    Mitosis,
+    Spots,
+    Worms,
+    Maze,
+    Waves,
+    Holes,
+}
+
+impl ReactionDiffusionPreset {
+    pub const ALL: [Self; 7] = [
+        Self::Coral,
+        Self::Mitosis,
+        Self::Spots,
+        Self::Worms,
+        Self::Maze,
+        Self::Waves,
+        Self::Holes,
+    ];
+
+    /// Lowercase name, as used in `select` control options
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Coral => "coral",
+            Self::Mitosis => "mitosis",
+            Self::Spots => "spots",
+            Self::Worms => "worms",
+            Self::Maze => "maze",
+            Self::Waves => "waves",
+            Self::Holes => "holes",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// `(feed, kill)`
+    pub fn feed_kill(&self) -> (f32, f32) {
+        match self {
+            Self::Coral => (0.0545, 0.062),
+            Self::Mitosis => (0.0367, 0.0649),
+            Self::Spots => (0.035, 0.065),
+            Self::Worms => (0.078, 0.061),
+            Self::Maze => (0.029, 0.057),
+            Self::Waves => (0.014, 0.045),
+            Self::Holes => (0.039, 0.058),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReactionDiffusionParams {
+    /// Rate A is replenished
+    pub feed: f32,
+    /// Rate B is removed
+    pub kill: f32,
+    pub diffusion_a: f32,
+    pub diffusion_b: f32,
+    /// Time per step. Values much above 1.0 become unstable
+    pub dt: f32,
+}
+
+impl Default for ReactionDiffusionParams {
+    fn default() -> Self {
+        let (feed, kill) = ReactionDiffusionPreset::Coral.feed_kill();
+        Self {
+            feed,
+            kill,
+            diffusion_a: 1.0,
+            diffusion_b: 0.5,
+            dt: 1.0,
+        }
+    }
+}
+
+impl ReactionDiffusionParams {
+    pub fn preset(preset: ReactionDiffusionPreset) -> Self {
+        let (feed, kill) = preset.feed_kill();
+        Self {
+            feed,
+            kill,
+            ..Self::default()
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ShaderParams {
+    // feed, kill, diffusion_a, diffusion_b
+    a: [f32; 4],
+    // dt, ..unused
+    b: [f32; 4],
+}
+
+impl From<&ReactionDiffusionParams> for ShaderParams {
+    fn from(params: &ReactionDiffusionParams) -> Self {
+        Self {
+            a: [
+                params.feed,
+                params.kill,
+                params.diffusion_a,
+                params.diffusion_b,
+            ],
+            b: [params.dt, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+pub struct ReactionDiffusion {
+    pub params: ReactionDiffusionParams,
+    size: [u32; 2],
+    pipeline: wgpu::ComputePipeline,
+    params_buffer: wgpu::Buffer,
+    /// Ping-pong pair; each step reads one and writes the other
+    textures: [wgpu::Texture; 2],
+    views: [wgpu::TextureView; 2],
+    /// `bind_groups[i]` reads `textures[i]` and writes the other
+    bind_groups: [wgpu::BindGroup; 2],
+    /// Index of the texture holding the latest state
+    current: usize,
+    usage_report: UsageReport,
+}
+
+impl std::fmt::Debug for ReactionDiffusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReactionDiffusion")
+            .field("params", &self.params)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReactionDiffusion {
+    /// Creates a `size` grid (independent of the window) filled with A and
+    /// seeded with a square of B in the center
+    pub fn new(app: &App, size: [u32; 2]) -> Self {
+        let window = app.main_window();
+        let device = window.device();
+
+        let module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Reaction Diffusion Shader"),
+                source: wgpu::ShaderSource::Wgsl(
+                    REACTION_DIFFUSION_WGSL.into(),
+                ),
+            });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Reaction Diffusion Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<ShaderParams>() as _,
+                            ),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: false,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::StorageTexture {
+                            access: wgpu::StorageTextureAccess::WriteOnly,
+                            format: FORMAT,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Reaction Diffusion Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Reaction Diffusion Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "main",
+            });
+
+        let params = ReactionDiffusionParams::default();
+
+        let params_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Reaction Diffusion Params Buffer"),
+                contents: bytemuck::bytes_of(&ShaderParams::from(&params)),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let textures = [(); 2].map(|_| {
+            wgpu::TextureBuilder::new()
+                .size(size)
+                .format(FORMAT)
+                .dimension(wgpu::TextureDimension::D2)
+                .usage(
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::STORAGE_BINDING
+                        | wgpu::TextureUsages::COPY_DST,
+                )
+                .sample_count(1)
+                .build(device)
+        });
+
+        let views = [0, 1].map(|i| textures[i].view().build());
+
+        let bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Reaction Diffusion Bind Group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&views[i]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(
+                            &views[1 - i],
+                        ),
+                    },
+                ],
+            })
+        });
+
+        let mut rd = Self {
+            params,
+            size,
+            pipeline,
+            params_buffer,
+            textures,
+            views,
+            bind_groups,
+            current: 0,
+            usage_report: UsageReport::default(),
+        };
+
+        rd.usage_report.set(
+            GpuUsage::default()
+                .buffer(rd.params_buffer.size())
+                .texture(size, FORMAT, 1)
+                .texture(size, FORMAT, 1)
+                .pipelines(1),
+        );
+        rd.seed_center(app, 0.05);
+        rd
+    }
+
+    pub fn preset(mut self, preset: ReactionDiffusionPreset) -> Self {
+        self.set_preset(preset);
+        self
+    }
+
+    /// Replaces feed and kill, keeping the diffusion rates and dt
+    pub fn set_preset(&mut self, preset: ReactionDiffusionPreset) {
+        (self.params.feed, self.params.kill) = preset.feed_kill();
+    }
+
+    /// Reads `<prefix>.feed`, `<prefix>.kill`, `<prefix>.diffusion_a`,
+    /// `<prefix>.diffusion_b`, and `<prefix>.dt` from `hub`, leaving any that
+    /// aren't declared unchanged. If `<prefix>.preset` is declared and names a
+    /// [`ReactionDiffusionPreset`] it overrides feed and kill; any other
+    /// option (e.g. `custom`) defers to the sliders
+    pub fn update_params<T: TimingSource>(
+        &mut self,
+        hub: &ControlHub<T>,
+        prefix: &str,
+    ) {
+        let params = &mut self.params;
+        for (name, field) in [
+            ("feed", &mut params.feed),
+            ("kill", &mut params.kill),
+            ("diffusion_a", &mut params.diffusion_a),
+            ("diffusion_b", &mut params.diffusion_b),
+            ("dt", &mut params.dt),
+        ] {
+            let name = format!("{}.{}", prefix, name);
+            if hub.has(&name) {
+                *field = hub.get(&name);
+            }
+        }
+
+        let name = format!("{}.preset", prefix);
+        if hub.has(&name) {
+            if let Some(preset) =
+                ReactionDiffusionPreset::from_name(&hub.string(&name))
+            {
+                self.set_preset(preset);
+            }
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// The latest state: A in red, B in green
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.views[self.current]
+    }
+
+    /// Runs `iterations` simulation steps. More iterations per frame makes
+    /// patterns grow faster without the instability of a larger dt
+    pub fn step(&mut self, app: &App, iterations: usize) {
+        if iterations == 0 {
+            return;
+        }
+
+        let window = app.main_window();
+        let device = window.device();
+        let queue = window.queue();
+
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::bytes_of(&ShaderParams::from(&self.params)),
+        );
+
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Reaction Diffusion Encoder"),
+            });
+
+        {
+            let mut pass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some("Reaction Diffusion Pass"),
+                });
+            pass.set_pipeline(&self.pipeline);
+
+            let [w, h] = self.size;
+            for _ in 0..iterations {
+                pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+                pass.dispatch_workgroups(
+                    w.div_ceil(WORKGROUP_SIZE),
+                    h.div_ceil(WORKGROUP_SIZE),
+                    1,
+                );
+                self.current = 1 - self.current;
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+
+    /// Resets the grid to all A, adding B wherever `b` (given a point in
+    /// `[0, 1]` uv space) returns more than zero
+    pub fn seed(&mut self, app: &App, b: impl Fn(Vec2) -> f32) {
+        let window = app.main_window();
+        let [w, h] = self.size;
+
+        window.queue().write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.textures[self.current],
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &seed_data(self.size, b),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(w * 8),
+                rows_per_image: Some(h),
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Seeds a square of B with sides of `size` (in uv units) in the center
+    pub fn seed_center(&mut self, app: &App, size: f32) {
+        let half = size / 2.0;
+        self.seed(app, |uv| {
+            let d = (uv - 0.5).abs();
+            ternary!(d.x <= half && d.y <= half, 1.0, 0.0)
+        });
+    }
+
+    /// Seeds `count` discs of B at random positions. `radius` is in uv units
+    pub fn seed_random(
+        &mut self,
+        app: &App,
+        rng: &mut impl Rng,
+        count: usize,
+        radius: f32,
+    ) {
+        let centers: Vec<Vec2> = (0..count)
+            .map(|_| vec2(rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)))
+            .collect();
+        self.seed(app, |uv| {
+            let hit = centers.iter().any(|c| c.distance(uv) <= radius);
+            ternary!(hit, 1.0, 0.0)
+        });
+    }
+}
+
+/// `Rgba16Float` texels with A = 1 - B/2 and B from `b`, sampled at each
+/// texel's center
+fn seed_data(size: [u32; 2], b: impl Fn(Vec2) -> f32) -> Vec<u8> {
+    let [w, h] = size;
+    (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let uv =
+                vec2((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+            let b = b(uv).clamp(0.0, 1.0);
+            [1.0 - b * 0.5, b, 0.0, 1.0]
+        })
+        .flat_map(|v| f16_bits(v).to_le_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        let module =
+            naga::front::wgsl::parse_str(REACTION_DIFFUSION_WGSL).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+
+    #[test]
+    fn test_wgsl_matches_workgroup_size() {
+        assert!(REACTION_DIFFUSION_WGSL.contains(&format!(
+            "@workgroup_size({}, {})",
+            WORKGROUP_SIZE, WORKGROUP_SIZE
+        )));
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in ReactionDiffusionPreset::ALL {
+            assert_eq!(
+                ReactionDiffusionPreset::from_name(preset.name()),
+                Some(preset)
+            );
+            assert_eq!(
+                ReactionDiffusionParams::preset(preset).diffusion_a,
+                1.0
+            );
+        }
+        assert_eq!(ReactionDiffusionPreset::from_name("custom"), None);
+    }
+
+    #[test]
+    fn test_seed_data() {
+        let data = seed_data([4, 2], |uv| ternary!(uv.x > 0.5, 1.0, 0.0));
+        assert_eq!(data.len(), 4 * 2 * 8);
+
+        let texel = |x: usize, y: usize| {
+            let i = (y * 4 + x) * 8;
+            data[i..i + 8].to_vec()
+        };
+        let bytes = |values: [f32; 4]| -> Vec<u8> {
+            values
+                .iter()
+                .flat_map(|v| f16_bits(*v).to_le_bytes())
+                .collect()
+        };
+
+        assert_eq!(texel(0, 0), bytes([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(texel(3, 1), bytes([0.5, 1.0, 0.0, 1.0]));
+    }
+}
@@ -0,0 +1,69 @@
+// -----------------------------------------------------------------------------
+//  XTAL REACTION DIFFUSION
+//  One Gray-Scott step. Chemical A is stored in the red channel and B in the
+//  green channel; see reaction_diffusion.rs
+// -----------------------------------------------------------------------------
+
+struct Params {
+    // feed, kill, diffusion_a, diffusion_b
+    a: vec4f,
+    // dt, ..unused
+    b: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+@group(0) @binding(2)
+var destination: texture_storage_2d<rgba16float, write>;
+
+// Wraps so patterns tile across the edges
+fn load(position: vec2i, offset: vec2i, size: vec2i) -> vec2f {
+    let p = (position + offset + size) % size;
+    return textureLoad(source, p, 0).rg;
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3u) {
+    let size = vec2i(textureDimensions(source));
+    let position = vec2i(id.xy);
+    if (position.x >= size.x || position.y >= size.y) {
+        return;
+    }
+
+    let feed = params.a.x;
+    let kill = params.a.y;
+    let diffusion_a = params.a.z;
+    let diffusion_b = params.a.w;
+    let dt = params.b.x;
+
+    let center = load(position, vec2i(0, 0), size);
+
+    // 3x3 Laplacian: 0.2 for edges, 0.05 for corners, -1 for the center
+    var laplacian = -center;
+    laplacian += 0.2 * (
+        load(position, vec2i(-1, 0), size) +
+        load(position, vec2i(1, 0), size) +
+        load(position, vec2i(0, -1), size) +
+        load(position, vec2i(0, 1), size)
+    );
+    laplacian += 0.05 * (
+        load(position, vec2i(-1, -1), size) +
+        load(position, vec2i(1, -1), size) +
+        load(position, vec2i(-1, 1), size) +
+        load(position, vec2i(1, 1), size)
+    );
+
+    let a = center.x;
+    let b = center.y;
+    let reaction = a * b * b;
+
+    let da = diffusion_a * laplacian.x - reaction + feed * (1.0 - a);
+    let db = diffusion_b * laplacian.y + reaction - (kill + feed) * b;
+    let next = clamp(vec2f(a, b) + vec2f(da, db) * dt, vec2f(0.0), vec2f(1.0));
+
+    textureStore(destination, position, vec4f(next, 0.0, 1.0));
+}
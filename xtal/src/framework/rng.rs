@@ -0,0 +1,137 @@
+//! Named random number streams shared through
+//! [`crate::prelude::Context::rng`]. Each stream is seeded from a base seed and
+//! its own name only, so drawing more or fewer numbers from one stream (e.g.
+//! by toggling a randomized feature) never shifts the sequence of another.
+//! Streams use [`ChaCha8Rng`] rather than `StdRng`, whose algorithm may change
+//! between `rand` releases, so a seed gives the same sequence across updates.
+
+#[cfg(any(test, feature = "runtime"))]
+use nannou_core::rand::SeedableRng;
+use nannou_core::rand::{self, RngCore};
+use rand_chacha::ChaCha8Rng;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(any(test, feature = "runtime"))]
+use crate::framework::prelude::*;
+
+/// A handle to one named stream. Clones share the same position, so a handle
+/// can be kept on the sketch instead of looked up every frame
+#[derive(Clone, Debug)]
+pub struct RngStream(Rc<RefCell<ChaCha8Rng>>);
+
+impl RngCore for RngStream {
+    fn next_u32(&mut self) -> u32 {
+        self.0.borrow_mut().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.borrow_mut().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.borrow_mut().fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.borrow_mut().try_fill_bytes(dest)
+    }
+}
+
+#[cfg(any(test, feature = "runtime"))]
+#[derive(Debug, Default)]
+pub struct RngStreams {
+    seed: u64,
+    streams: HashMap<String, RngStream>,
+}
+
+#[cfg(any(test, feature = "runtime"))]
+impl RngStreams {
+    pub fn get(&mut self, name: &str) -> RngStream {
+        let seed = self.seed;
+        self.streams
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                RngStream(Rc::new(RefCell::new(ChaCha8Rng::seed_from_u64(
+                    stream_seed(seed, name),
+                ))))
+            })
+            .clone()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Changes the base seed of every stream and restarts them
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.reset();
+    }
+
+    /// Restarts every stream from its first number, including through handles
+    /// that were already handed out
+    pub fn reset(&mut self) {
+        for (name, stream) in &self.streams {
+            *stream.0.borrow_mut() =
+                ChaCha8Rng::seed_from_u64(stream_seed(self.seed, name));
+        }
+    }
+}
+
+#[cfg(any(test, feature = "runtime"))]
+fn stream_seed(seed: u64, name: &str) -> u64 {
+    // FNV-1a of the name then a SplitMix64 finalizer so similar names and
+    // neighboring seeds end up far apart
+    let hash = name.bytes().fold(0xCBF2_9CE4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    });
+    let mut z = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ hash;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou_core::rand::Rng;
+
+    fn take(stream: &mut RngStream, n: usize) -> Vec<u32> {
+        (0..n).map(|_| stream.gen_range(0..u32::MAX)).collect()
+    }
+
+    #[test]
+    fn test_streams_are_independent() {
+        let mut streams = RngStreams::default();
+        let mut a = streams.get("a");
+        let expected = take(&mut a, 4);
+
+        let mut fresh = RngStreams::default();
+        let mut b = fresh.get("b");
+        take(&mut b, 100);
+        let mut a = fresh.get("a");
+        assert_eq!(take(&mut a, 4), expected);
+
+        let mut b = streams.get("b");
+        assert_ne!(take(&mut b, 4), expected);
+    }
+
+    #[test]
+    fn test_reset_and_seed() {
+        let mut streams = RngStreams::default();
+        let mut a = streams.get("a");
+        let first = take(&mut a, 4);
+
+        // Clones share a position
+        let mut clone = a.clone();
+        assert_ne!(take(&mut clone, 4), first);
+
+        streams.reset();
+        assert_eq!(take(&mut a, 4), first);
+
+        streams.set_seed(1);
+        assert_eq!(streams.seed(), 1);
+        assert_ne!(take(&mut a, 4), first);
+    }
+}
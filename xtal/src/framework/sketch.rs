@@ -1,8 +1,18 @@
 use nannou::color::IntoLinSrgba;
 use nannou::draw::properties::ColorScalar;
 use nannou::prelude::*;
+use nannou::wgpu;
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
 
+use super::calibration::{CalibrationMode, CalibrationOverlay};
+use super::frame_capture::{FrameCapture, STAGES, Stage};
+use super::frame_controller;
+use super::gpu_cache::GpuCache;
+use super::post::PostStack;
 use super::prelude::*;
+use super::rng::{RngStream, RngStreams};
+use super::warp::OutputWarp;
 use crate::runtime::app::ClearFlag;
 
 /// A configuration that all sketches must export in order to integrate
@@ -14,6 +24,15 @@ pub struct SketchConfig {
     /// The name that will be displayed in the window titles and sketch selector
     pub display_name: &'static str,
 
+    /// Free-form categories for filtering in the sketch selector, e.g.
+    /// `&["shader", "genuary"]`
+    pub tags: &'static [&'static str],
+
+    pub author: &'static str,
+
+    /// A sentence or two shown alongside the sketch in the sketch selector
+    pub description: &'static str,
+
     /// See [`PlayMode`]
     pub play_mode: PlayMode,
 
@@ -29,6 +48,11 @@ pub struct SketchConfig {
 
     /// The default height the main window should open at
     pub h: i32,
+
+    /// Multisample anti-aliasing sample count (1, 2, 4, or 8) used by
+    /// [`crate::prelude::gpu::GpuState`] pipelines. Nannou `Draw` output is
+    /// always rendered with the window's 4x. Can be changed at runtime.
+    pub msaa_samples: u32,
 }
 
 #[derive(PartialEq)]
@@ -42,6 +66,12 @@ pub enum PlayMode {
     /// Same as advance, but only advances if the `Advance` button or `A` key is
     /// pressed
     ManualAdvance,
+
+    /// Only renders when a control value changes, an animation, audio control,
+    /// or snapshot transition is active, the window changes, or
+    /// [`Context::invalidate`] is called. Keeps the CPU/GPU idle for
+    /// near-static sketches while still reacting instantly to input
+    OnDemand,
 }
 
 /// Context passed down from the Xtal runtime
@@ -50,6 +80,15 @@ pub struct Context {
     bpm: Bpm,
     clear_flag: ClearFlag,
     window_rect: WindowRect,
+    aspect_preset: Rc<Cell<AspectPreset>>,
+    safe_area_guides: Rc<Cell<bool>>,
+    post_stack: Rc<RefCell<PostStack>>,
+    calibration: Rc<RefCell<CalibrationOverlay>>,
+    output_warp: Rc<RefCell<OutputWarp>>,
+    frame_capture: Rc<RefCell<FrameCapture>>,
+    inputs: Rc<RefCell<HashMap<String, Rc<wgpu::TextureView>>>>,
+    rng_streams: Rc<RefCell<RngStreams>>,
+    gpu_cache: GpuCache,
 }
 
 impl Context {
@@ -62,6 +101,15 @@ impl Context {
             bpm,
             clear_flag,
             window_rect,
+            aspect_preset: Rc::new(Cell::new(AspectPreset::default())),
+            safe_area_guides: Rc::new(Cell::new(false)),
+            post_stack: Rc::new(RefCell::new(PostStack::default())),
+            calibration: Rc::new(RefCell::new(CalibrationOverlay::default())),
+            output_warp: Rc::new(RefCell::new(OutputWarp::default())),
+            frame_capture: Rc::new(RefCell::new(FrameCapture::default())),
+            inputs: Rc::new(RefCell::new(HashMap::default())),
+            rng_streams: Rc::new(RefCell::new(RngStreams::default())),
+            gpu_cache: GpuCache::default(),
         }
     }
 
@@ -77,6 +125,183 @@ impl Context {
         self.window_rect.clone()
     }
 
+    /// The aspect preset the main window is currently sized to. Changed at
+    /// runtime with `Shift A`
+    pub fn aspect_preset(&self) -> AspectPreset {
+        self.aspect_preset.get()
+    }
+
+    pub(crate) fn set_aspect_preset(&self, preset: AspectPreset) {
+        self.aspect_preset.set(preset);
+    }
+
+    /// Whether safe-area guides are enabled (toggled at runtime with `T`). The
+    /// runtime draws them over the sketch's output after `view`, for display
+    /// only, so they never end up in captured images or recordings
+    pub fn safe_area_guides(&self) -> bool {
+        self.safe_area_guides.get()
+    }
+
+    pub(crate) fn set_safe_area_guides(&self, enabled: bool) {
+        self.safe_area_guides.set(enabled);
+    }
+
+    /// The calibration pattern's current mode (cycled at runtime with
+    /// `Shift T`). See [`crate::framework::calibration`]
+    pub fn calibration_mode(&self) -> CalibrationMode {
+        self.calibration.borrow().mode()
+    }
+
+    pub(crate) fn calibration(&self) -> RefMut<'_, CalibrationOverlay> {
+        self.calibration.borrow_mut()
+    }
+
+    /// Runs the runtime's [`STAGES`] over everything the sketch rendered in
+    /// `view`. Pending captures are copied out at [`Stage::Capture`], so the
+    /// guides, calibration pattern, and output warp only ever show up in the
+    /// window
+    pub(crate) fn present(&self, app: &App, frame: &Frame) {
+        for stage in STAGES {
+            match stage {
+                Stage::Post => self.post_stack.borrow_mut().apply(frame),
+                Stage::Letterbox => {
+                    if self.window_rect().is_letterboxed() {
+                        let draw = app.draw();
+                        self.draw_letterbox(&draw);
+                        draw.to_frame(app, frame).unwrap();
+                    }
+                }
+                Stage::Guides => {
+                    if self.safe_area_guides() {
+                        let draw = app.draw();
+                        self.draw_safe_area_guides(&draw);
+                        draw.to_frame(app, frame).unwrap();
+                    }
+                }
+                Stage::Calibration => {
+                    self.calibration.borrow_mut().apply(frame)
+                }
+                Stage::Capture => self.frame_capture.borrow_mut().apply(frame),
+                Stage::Warp => self.output_warp.borrow_mut().apply(frame),
+            }
+        }
+    }
+
+    /// Images, flipbook frames, and recording frames are captured from the
+    /// next presented frame. See [`crate::framework::frame_capture`]
+    pub(crate) fn frame_capture(&self) -> RefMut<'_, FrameCapture> {
+        self.frame_capture.borrow_mut()
+    }
+
+    /// Action-safe and title-safe outlines plus center lines
+    fn draw_safe_area_guides(&self, draw: &Draw) {
+        let wr = self.window_rect();
+        let color = rgba(0.0, 1.0, 1.0, 0.6);
+
+        for rect in [wr.action_safe(), wr.title_safe()] {
+            draw.rect()
+                .xy(rect.xy())
+                .wh(rect.wh())
+                .no_fill()
+                .stroke_weight(1.0)
+                .stroke(color);
+        }
+
+        draw.line()
+            .points(vec2(wr.left(), 0.0), vec2(wr.right(), 0.0))
+            .weight(1.0)
+            .color(color);
+        draw.line()
+            .points(vec2(0.0, wr.bottom()), vec2(0.0, wr.top()))
+            .weight(1.0)
+            .color(color);
+    }
+
+    /// Black bars over the areas outside of the letterboxed content area (see
+    /// [`WindowRect::window`]) so anything drawn past the content edges is
+    /// hidden. [`gpu::GpuState`] already renders into the content area only
+    fn draw_letterbox(&self, draw: &Draw) {
+        let wr = self.window_rect();
+        let window = wr.window();
+        let content = wr.rect();
+
+        for bar in [
+            Rect::from_corners(window.bottom_left(), content.top_left()),
+            Rect::from_corners(content.bottom_right(), window.top_right()),
+            Rect::from_corners(window.bottom_left(), content.bottom_right()),
+            Rect::from_corners(content.top_left(), window.top_right()),
+        ] {
+            if bar.w() > 0.0 && bar.h() > 0.0 {
+                draw.rect().xy(bar.xy()).wh(bar.wh()).color(BLACK);
+            }
+        }
+    }
+
+    pub(crate) fn post_stack(&self) -> RefMut<'_, PostStack> {
+        self.post_stack.borrow_mut()
+    }
+
+    /// The latest output of the sketch declared as `name` under the control
+    /// script's top-level `inputs` key, rendered by the runtime right before
+    /// this sketch's `update`. Bind it with [`gpu::GpuState::set_texture`] to
+    /// build "source + effect" stacks out of separate sketches. `None` until
+    /// the source has rendered or if it doesn't implement
+    /// [`Sketch::render_output`]
+    pub fn input(&self, name: &str) -> Option<Rc<wgpu::TextureView>> {
+        self.inputs.borrow().get(name).cloned()
+    }
+
+    pub(crate) fn set_input(&self, name: &str, view: wgpu::TextureView) {
+        self.inputs
+            .borrow_mut()
+            .insert(name.to_string(), Rc::new(view));
+    }
+
+    pub(crate) fn retain_inputs(&self, f: impl Fn(&str) -> bool) {
+        self.inputs.borrow_mut().retain(|name, _| f(name));
+    }
+
+    /// Shader modules, samplers, and textures shared by every sketch for as
+    /// long as the app runs. See [`GpuCache`]
+    pub fn gpu_cache(&self) -> GpuCache {
+        self.gpu_cache.clone()
+    }
+
+    /// An independent random number stream for `name`, seeded from `name` and
+    /// [`Self::rng_seed`] only, so e.g. toggling a randomized feature that draws
+    /// from `"particles"` doesn't change what `"palette"` produces. Streams
+    /// restart when switching sketches
+    ///
+    /// ```rust,ignore
+    /// let mut rng = ctx.rng("particles");
+    /// let x = rng.gen_range(-1.0..1.0);
+    /// ```
+    pub fn rng(&self, name: &str) -> RngStream {
+        self.rng_streams.borrow_mut().get(name)
+    }
+
+    pub fn rng_seed(&self) -> u64 {
+        self.rng_streams.borrow().seed()
+    }
+
+    /// Changes the base seed of every [`Self::rng`] stream and restarts them,
+    /// e.g. to explore variations of a sketch
+    pub fn set_rng_seed(&self, seed: u64) {
+        self.rng_streams.borrow_mut().set_seed(seed);
+    }
+
+    pub(crate) fn reset_rng(&self) {
+        self.rng_streams.borrow_mut().reset();
+    }
+
+    /// Request a render on the next frame when using [`PlayMode::OnDemand`],
+    /// e.g. after an async task or background computation completes. Calling
+    /// this from `update` or `view` will keep the sketch rendering every frame
+    /// for as long as it keeps being called. No-op in other play modes.
+    pub fn invalidate(&self) {
+        frame_controller::invalidate();
+    }
+
     /// True for a single frame after pressing **Clear**
     pub fn should_clear(&self) -> bool {
         self.clear_flag.get()
@@ -104,7 +329,49 @@ impl Context {
 pub trait Sketch {
     fn update(&mut self, _app: &App, _update: Update, _ctx: &Context) {}
     fn event(&mut self, _app: &App, _event: &Event) {}
-    fn view(&self, app: &App, frame: Frame, ctx: &Context);
+
+    /// Renders the sketch into `frame`. The runtime still owns the frame
+    /// afterwards and applies the `post` stack, its overlays (letterbox bars,
+    /// [`Context::safe_area_guides`], and the calibration pattern), and the
+    /// output warp before it's presented. Captures only include the `post`
+    /// stack and letterbox bars
+    fn view(&self, app: &App, frame: &Frame, ctx: &Context);
+
+    /// Called once the sketch's window and persisted state are ready, both on
+    /// startup and after switching to it
+    fn on_load(&mut self, _app: &App, _ctx: &Context) {}
+
+    /// Called right before switching to another sketch
+    fn on_unload(&mut self, _app: &App, _ctx: &Context) {}
+
+    /// Called when the size of [`Context::window_rect`] changes
+    fn on_resize(&mut self, _app: &App, _ctx: &Context) {}
+
+    /// Called when a recording starts, before its first frame is captured.
+    /// Useful for resetting simulations so every take starts the same way
+    fn on_record_start(&mut self, _app: &App, _ctx: &Context) {}
+
+    /// Renders the sketch offscreen so other sketches can use it as an input
+    /// (see [`Context::input`]). Only called, after `update`, while another
+    /// sketch declares this one under its `inputs`. Typically
+    /// [`gpu::GpuState::render_to_texture`]
+    fn render_output(
+        &self,
+        _app: &App,
+        _ctx: &Context,
+    ) -> Option<wgpu::TextureView> {
+        None
+    }
+}
+
+/// The same hooks as [`Sketch`] for reusable parts of a sketch. Mark a field
+/// with `#[sketch(lifecycle)]` and `SketchComponents` will forward every hook
+/// to it after calling the sketch's own
+pub trait SketchLifecycle {
+    fn on_load(&mut self, _app: &App, _ctx: &Context) {}
+    fn on_unload(&mut self, _app: &App, _ctx: &Context) {}
+    fn on_resize(&mut self, _app: &App, _ctx: &Context) {}
+    fn on_record_start(&mut self, _app: &App, _ctx: &Context) {}
 }
 
 /// Secondary trait that all sketches must implement in order to integrate with
@@ -115,6 +382,43 @@ pub trait Sketch {
 /// ```
 pub trait SketchDerived {
     fn hub(&mut self) -> Option<&mut dyn ControlHubProvider>;
+
+    /// Fields marked `#[sketch(lifecycle)]`
+    fn lifecycle_components(&mut self) -> Vec<&mut dyn SketchLifecycle> {
+        vec![]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum LifecycleHook {
+    Load,
+    Unload,
+    Resize,
+    RecordStart,
+}
+
+/// Calls `hook` on the sketch then on its lifecycle components
+pub(crate) fn run_lifecycle_hook(
+    sketch: &mut dyn SketchAll,
+    hook: LifecycleHook,
+    app: &App,
+    ctx: &Context,
+) {
+    match hook {
+        LifecycleHook::Load => sketch.on_load(app, ctx),
+        LifecycleHook::Unload => sketch.on_unload(app, ctx),
+        LifecycleHook::Resize => sketch.on_resize(app, ctx),
+        LifecycleHook::RecordStart => sketch.on_record_start(app, ctx),
+    }
+
+    for component in sketch.lifecycle_components() {
+        match hook {
+            LifecycleHook::Load => component.on_load(app, ctx),
+            LifecycleHook::Unload => component.on_unload(app, ctx),
+            LifecycleHook::Resize => component.on_resize(app, ctx),
+            LifecycleHook::RecordStart => component.on_record_start(app, ctx),
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -0,0 +1,333 @@
+//! Runtime-switchable [`tracing`] spans for the main subsystems (update, view,
+//! hub, audio, MIDI). Each subsystem can be toggled while a sketch runs; while
+//! enabled its spans are timed and its `tracing` events are forwarded to the
+//! logger at the same level, and while disabled they cost an atomic load.
+//! Timings are aggregated per call stack so they can be exported in the
+//! "folded" format understood by flamegraph tools such as
+//! `inferno-flamegraph` or `flamegraph.pl`.
+//!
+//! # Example
+//! ```rust,ignore
+//! spans::set_enabled(Subsystem::Hub, true);
+//!
+//! let _span = tracing::trace_span!(target: spans::HUB, "expensive").entered();
+//! tracing::debug!(target: spans::HUB, count = 3, "Doing expensive things");
+//!
+//! fs::write("hub.folded", spans::folded())?;
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::{Interest, Subscriber};
+use tracing::{Event, Level, Metadata};
+
+use crate::framework::prelude::*;
+
+pub const UPDATE: &str = "xtal::update";
+pub const VIEW: &str = "xtal::view";
+pub const HUB: &str = "xtal::hub";
+pub const AUDIO: &str = "xtal::audio";
+pub const MIDI: &str = "xtal::midi";
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Update,
+    View,
+    Hub,
+    Audio,
+    Midi,
+}
+
+impl Subsystem {
+    pub const ALL: [Self; 5] =
+        [Self::Update, Self::View, Self::Hub, Self::Audio, Self::Midi];
+
+    pub fn target(&self) -> &'static str {
+        match self {
+            Self::Update => UPDATE,
+            Self::View => VIEW,
+            Self::Hub => HUB,
+            Self::Audio => AUDIO,
+            Self::Midi => MIDI,
+        }
+    }
+
+    fn from_target(target: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.target() == target)
+    }
+
+    fn bit(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// Bitmask of enabled [`Subsystem`]s
+static ENABLED: AtomicU8 = AtomicU8::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// `id => (label, reference count)`
+static SPANS: LazyLock<Mutex<HashMap<u64, (String, usize)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::default()));
+
+/// `folded stack => timing`
+static TIMINGS: LazyLock<Mutex<HashMap<String, SpanTiming>>> =
+    LazyLock::new(|| Mutex::new(HashMap::default()));
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpanTiming {
+    /// Time spent in the span itself, excluding child spans
+    pub self_time: Duration,
+    pub count: u64,
+}
+
+struct Frame {
+    id: u64,
+    label: String,
+    start: Instant,
+    children: Duration,
+}
+
+/// Installs the span subscriber as the global `tracing` default. Safe to call
+/// more than once; warns if another subscriber is already installed
+pub fn init() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        if tracing::subscriber::set_global_default(SpanSubscriber).is_err() {
+            warn!("A tracing subscriber is already installed");
+        }
+    });
+}
+
+pub fn set_enabled(subsystem: Subsystem, enabled: bool) {
+    if enabled {
+        ENABLED.fetch_or(subsystem.bit(), Ordering::Relaxed);
+    } else {
+        ENABLED.fetch_and(!subsystem.bit(), Ordering::Relaxed);
+    }
+}
+
+pub fn is_enabled(subsystem: Subsystem) -> bool {
+    ENABLED.load(Ordering::Relaxed) & subsystem.bit() != 0
+}
+
+/// Timings per call stack (`outer;inner`), slowest first
+pub fn timings() -> Vec<(String, SpanTiming)> {
+    let mut timings: Vec<_> = TIMINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(stack, timing)| (stack.clone(), *timing))
+        .collect();
+    timings.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.self_time));
+    timings
+}
+
+/// Timings as one `stack microseconds` line per call stack
+pub fn folded() -> String {
+    timings()
+        .iter()
+        .fold(String::new(), |mut folded, (stack, timing)| {
+            let _ =
+                writeln!(folded, "{} {}", stack, timing.self_time.as_micros());
+            folded
+        })
+}
+
+pub fn reset() {
+    TIMINGS.lock().unwrap().clear();
+}
+
+struct SpanSubscriber;
+
+impl Subscriber for SpanSubscriber {
+    fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+        // Subsystems are toggled at runtime so every callsite must be checked
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        Subsystem::from_target(metadata.target()).is_some_and(is_enabled)
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut visitor = FieldVisitor::default();
+        span.record(&mut visitor);
+
+        let name = span.metadata().name();
+        let label = match visitor.control {
+            Some(control) => format!("{}({})", name, control),
+            None => name.to_string(),
+        };
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        SPANS.lock().unwrap().insert(id, (label, 1));
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let target = metadata.target();
+        let message = format!("{}{}", visitor.message, visitor.fields);
+        match *metadata.level() {
+            Level::ERROR => error!(target: target, "{}", message),
+            Level::WARN => warn!(target: target, "{}", message),
+            Level::INFO => info!(target: target, "{}", message),
+            Level::DEBUG => debug!(target: target, "{}", message),
+            _ => trace!(target: target, "{}", message),
+        }
+    }
+
+    fn enter(&self, span: &Id) {
+        let id = span.into_u64();
+        let Some(label) = SPANS.lock().unwrap().get(&id).map(|s| s.0.clone())
+        else {
+            return;
+        };
+        STACK.with_borrow_mut(|stack| {
+            stack.push(Frame {
+                id,
+                label,
+                start: Instant::now(),
+                children: Duration::ZERO,
+            })
+        });
+    }
+
+    fn exit(&self, span: &Id) {
+        let id = span.into_u64();
+        STACK.with_borrow_mut(|stack| {
+            let Some(index) = stack.iter().rposition(|f| f.id == id) else {
+                return;
+            };
+            let frame = stack.remove(index);
+            let elapsed = frame.start.elapsed();
+
+            let mut path = stack
+                .iter()
+                .take(index)
+                .map(|f| f.label.as_str())
+                .collect::<Vec<_>>()
+                .join(";");
+            if index > 0 {
+                path.push(';');
+                stack[index - 1].children += elapsed;
+            }
+            path.push_str(&frame.label);
+
+            let mut timings = TIMINGS.lock().unwrap();
+            let timing = timings.entry(path).or_default();
+            timing.self_time += elapsed.saturating_sub(frame.children);
+            timing.count += 1;
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(entry) = SPANS.lock().unwrap().get_mut(&span.into_u64()) {
+            entry.1 += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = SPANS.lock().unwrap();
+        let id = span.into_u64();
+        let closed = spans.get_mut(&id).is_some_and(|entry| {
+            entry.1 -= 1;
+            entry.1 == 0
+        });
+        if closed {
+            spans.remove(&id);
+        }
+        closed
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    /// Span label suffix for `control = name` fields
+    control: Option<String>,
+    /// Everything else as ` key=value`
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "control" => self.control = Some(value.to_string()),
+            "message" => self.message = value.to_string(),
+            name => {
+                let _ = write!(self.fields, " {}={}", name, value);
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tracing::trace_span;
+
+    #[test]
+    #[serial]
+    fn test_spans() {
+        init();
+        reset();
+
+        set_enabled(Subsystem::Hub, true);
+        set_enabled(Subsystem::View, false);
+        {
+            let _outer = trace_span!(target: HUB, "update").entered();
+            for _ in 0..2 {
+                let _inner =
+                    trace_span!(target: HUB, "get", control = "a").entered();
+            }
+            let _ignored = trace_span!(target: VIEW, "view").entered();
+        }
+
+        let by_stack: HashMap<String, SpanTiming> =
+            timings().into_iter().collect();
+        assert_eq!(by_stack.len(), 2);
+        assert_eq!(by_stack["update"].count, 1);
+        assert_eq!(by_stack["update;get(a)"].count, 2);
+
+        assert!(
+            folded()
+                .lines()
+                .any(|line| line.starts_with("update;get(a) "))
+        );
+
+        set_enabled(Subsystem::Hub, false);
+        reset();
+        {
+            let _span = trace_span!(target: HUB, "update").entered();
+        }
+        assert!(timings().is_empty());
+    }
+}
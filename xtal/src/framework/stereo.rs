@@ -0,0 +1,315 @@
+//! Stereo rendering for anaglyph glasses and side-by-side 3D displays. When
+//! the runtime's stereo mode is on (UI > Settings > Rendering) every
+//! [`crate::prelude::gpu::GpuState`] renders its pipeline twice, once per eye,
+//! and composites the two renders onto the frame. Sketches only need to read
+//! the eye uniform and offset their camera by it:
+//!
+//! ```wgsl
+//! // #include xtal::eye
+//!
+//! @fragment
+//! fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+//!     let ro = vec3f(eye_offset(), 0.0, -3.0);
+//!     // ...
+//! }
+//! ```
+//!
+//! The uniform lives at `@group(0) @binding(1)`, next to the params. Shaders
+//! that don't declare it render both eyes identically. Draw-based rendering is
+//! not affected.
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// The WGSL source of the compositing pass
+pub const STEREO_WGSL: &str = include_str!("stereo.wgsl");
+
+/// The `xtal::eye` shader include that declares the eye uniform
+pub const EYE_WGSL: &str = include_str!("eye.wgsl");
+
+static STEREO: Mutex<StereoSettings> = Mutex::new(StereoSettings {
+    mode: StereoMode::Off,
+    separation: DEFAULT_SEPARATION,
+});
+
+const DEFAULT_SEPARATION: f32 = 0.05;
+
+pub(crate) fn set_stereo(settings: StereoSettings) {
+    *STEREO.lock().unwrap() = settings;
+}
+
+/// The runtime's current stereo settings
+pub fn stereo_settings() -> StereoSettings {
+    *STEREO.lock().unwrap()
+}
+
+/// How the left and right eye renders are combined onto the frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum StereoMode {
+    /// Render once with the eye uniform's side set to 0
+    #[default]
+    Off,
+    /// Red/cyan: red from the left eye, green and blue from the right
+    Anaglyph,
+    /// Half side-by-side as expected by most 3D TVs and projectors: each eye
+    /// is squeezed into its half of the frame
+    SideBySide,
+}
+
+impl StereoMode {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            Self::Off | Self::Anaglyph => "fs_anaglyph",
+            Self::SideBySide => "fs_side_by_side",
+        }
+    }
+}
+
+/// Persisted in [`crate::runtime::serialization::GlobalSettings`]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StereoSettings {
+    pub mode: StereoMode,
+    /// Distance between the eyes, passed as is to shaders. Its scale depends
+    /// on the units of the sketch's camera
+    pub separation: f32,
+}
+
+impl StereoSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.mode != StereoMode::Off
+    }
+}
+
+impl Default for StereoSettings {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            separation: DEFAULT_SEPARATION,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Eye {
+    Center,
+    Left,
+    Right,
+}
+
+impl Eye {
+    pub const ALL: [Eye; 3] = [Eye::Center, Eye::Left, Eye::Right];
+
+    pub fn side(&self) -> f32 {
+        match self {
+            Self::Center => 0.0,
+            Self::Left => -1.0,
+            Self::Right => 1.0,
+        }
+    }
+}
+
+/// Mirrors the `Eye` struct in eye.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub(crate) struct EyeUniform {
+    side: f32,
+    separation: f32,
+    _pad: [f32; 2],
+}
+
+impl EyeUniform {
+    pub fn new(eye: Eye, separation: f32) -> Self {
+        Self {
+            side: eye.side(),
+            separation,
+            _pad: [0.0; 2],
+        }
+    }
+}
+
+/// Fullscreen pass that combines the left and right eye textures
+pub(crate) struct StereoPass {
+    mode: StereoMode,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl StereoPass {
+    pub fn new(
+        device: &wgpu::Device,
+        left: &wgpu::TextureViewHandle,
+        right: &wgpu::TextureViewHandle,
+        mode: StereoMode,
+    ) -> Self {
+        let shader_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Stereo Shader"),
+                source: wgpu::ShaderSource::Wgsl(STEREO_WGSL.into()),
+            });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float {
+                    filterable: true,
+                },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    texture_entry(0),
+                    texture_entry(1),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+                label: Some("Stereo Bind Group Layout"),
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(left),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(right),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Stereo Bind Group"),
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Stereo Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Stereo Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: mode.entry_point(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Frame::TEXTURE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            });
+
+        Self {
+            mode,
+            pipeline,
+            bind_group,
+        }
+    }
+
+    pub fn mode(&self) -> StereoMode {
+        self.mode
+    }
+
+    pub fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::TextureViewHandle,
+    ) {
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(dst, |color| color)
+            .begin(encoder);
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::gpu::{preprocess_shader, validate_shader};
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        validate_shader(STEREO_WGSL).unwrap();
+    }
+
+    #[test]
+    fn test_eye_include() {
+        let shader = preprocess_shader(
+            r#"
+// #include xtal::eye
+
+@fragment
+fn fs_main() -> @location(0) vec4f {
+    return vec4f(eye_offset(), 0.0, 0.0, 1.0);
+}
+"#,
+        );
+        validate_shader(&shader).unwrap();
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = StereoSettings {
+            mode: StereoMode::SideBySide,
+            separation: 0.1,
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert_eq!(json, r#"{"mode":"SideBySide","separation":0.1}"#);
+        assert_eq!(
+            serde_json::from_str::<StereoSettings>(&json).unwrap(),
+            settings
+        );
+        assert_eq!(
+            serde_json::from_str::<StereoSettings>("{}").unwrap(),
+            StereoSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_eye_uniform_matches_wgsl() {
+        assert_eq!(std::mem::size_of::<EyeUniform>(), 16);
+        assert_eq!(EyeUniform::new(Eye::Left, 0.2).side, -1.0);
+        assert!(EYE_WGSL.contains("@group(0) @binding(1)"));
+    }
+}
@@ -0,0 +1,47 @@
+// -----------------------------------------------------------------------------
+//  XTAL STEREO
+//  Built-in pass that composites the left and right eye renders of a
+//  GpuState pipeline. See xtal::gpu::StereoMode
+// -----------------------------------------------------------------------------
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+}
+
+@group(0) @binding(0)
+var left: texture_2d<f32>;
+
+@group(0) @binding(1)
+var right: texture_2d<f32>;
+
+@group(0) @binding(2)
+var source_sampler: sampler;
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2f(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+// Red/cyan: red from the left eye, green and blue from the right
+@fragment
+fn fs_anaglyph(in: VertexOutput) -> @location(0) vec4f {
+    let l = textureSample(left, source_sampler, in.uv);
+    let r = textureSample(right, source_sampler, in.uv);
+    return vec4f(l.r, r.g, r.b, max(l.a, r.a));
+}
+
+// Half side-by-side: each eye squeezed into its half of the frame
+@fragment
+fn fs_side_by_side(in: VertexOutput) -> @location(0) vec4f {
+    let x = fract(in.uv.x * 2.0);
+    let uv = vec2f(x, in.uv.y);
+    let l = textureSample(left, source_sampler, uv);
+    let r = textureSample(right, source_sampler, uv);
+    return select(r, l, in.uv.x < 0.5);
+}
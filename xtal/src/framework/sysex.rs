@@ -0,0 +1,197 @@
+//! Backup and restore of hardware controller state with SysEx dumps. Many
+//! controllers (Faderfox, Launch Control XL custom modes, etc.) can send their
+//! setup as one or more SysEx messages and accept the same messages back to
+//! restore it. A [`SysExRecorder`] collects whatever a controller sends and a
+//! [`SysExDump`] can be saved as a standard `.syx` file and sent back out.
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::midi::{self, ConnectionType, MidiOut};
+use super::prelude::*;
+
+pub const SYSEX_START: u8 = 0xF0;
+pub const SYSEX_END: u8 = 0xF7;
+
+/// Controllers need a moment to process each message; most manuals recommend
+/// 10-20ms between them
+const SEND_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An ordered list of complete SysEx messages, each framed by
+/// [`SYSEX_START`] and [`SYSEX_END`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SysExDump {
+    messages: Vec<Vec<u8>>,
+}
+
+impl SysExDump {
+    /// Parses the contents of a `.syx` file: SysEx messages back to back
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut assembler = Assembler::default();
+        let messages = assembler.push(bytes);
+
+        if assembler.pending.is_some() {
+            return Err("SysEx dump ends with an incomplete message".into());
+        }
+
+        Ok(Self { messages })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.messages.concat()
+    }
+
+    pub fn messages(&self) -> &[Vec<u8>] {
+        &self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends every message to `port`, pausing briefly between each. Blocks
+    /// until done so call from a thread other than the main one
+    pub fn send(&self, port: &str) -> Result<(), Box<dyn Error>> {
+        let mut midi_out = MidiOut::new(port);
+        midi_out.connect()?;
+
+        for (i, message) in self.messages.iter().enumerate() {
+            if i > 0 {
+                thread::sleep(SEND_INTERVAL);
+            }
+            midi_out.send_sysex(message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Joins SysEx messages that arrive split across several callbacks (as they
+/// can on macOS) and drops anything that isn't SysEx
+#[derive(Debug, Default)]
+struct Assembler {
+    pending: Option<Vec<u8>>,
+}
+
+impl Assembler {
+    /// Returns the messages completed by `bytes`
+    fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut complete = vec![];
+
+        for &byte in bytes {
+            match byte {
+                SYSEX_START => {
+                    if self.pending.is_some() {
+                        warn!("Dropping unterminated SysEx message");
+                    }
+                    self.pending = Some(vec![byte]);
+                }
+                SYSEX_END => {
+                    if let Some(mut message) = self.pending.take() {
+                        message.push(byte);
+                        complete.push(message);
+                    }
+                }
+                // Real-time messages may be interleaved anywhere
+                0xF8..=0xFF => {}
+                // Any other status byte cancels an unterminated message
+                0x80..=0xEF | 0xF1..=0xF6 => {
+                    if self.pending.take().is_some() {
+                        warn!("Dropping unterminated SysEx message");
+                    }
+                }
+                _ => {
+                    if let Some(message) = &mut self.pending {
+                        message.push(byte);
+                    }
+                }
+            }
+        }
+
+        complete
+    }
+}
+
+/// Collects the SysEx messages a controller sends to `port` until stopped
+pub struct SysExRecorder {
+    dump: Arc<Mutex<SysExDump>>,
+}
+
+impl SysExRecorder {
+    pub fn start(port: &str) -> Result<Self, Box<dyn Error>> {
+        let dump = Arc::new(Mutex::new(SysExDump::default()));
+        let dump_clone = dump.clone();
+        let assembler = Mutex::new(Assembler::default());
+
+        midi::on_message(ConnectionType::SysEx, port, move |_, message| {
+            let complete = assembler.lock().unwrap().push(message);
+            if !complete.is_empty() {
+                debug!("Received {} SysEx message(s)", complete.len());
+                dump_clone.lock().unwrap().messages.extend(complete);
+            }
+        })?;
+
+        info!("Recording SysEx from {}", port);
+
+        Ok(Self { dump })
+    }
+
+    pub fn stop(self) -> SysExDump {
+        midi::disconnect(ConnectionType::SysEx);
+        self.dump.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = [0xF0, 0x00, 0x20, 0x01, 0xF7, 0xF0, 0x7E, 0x7F, 0xF7];
+        let dump = SysExDump::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            dump.messages(),
+            &[
+                vec![0xF0, 0x00, 0x20, 0x01, 0xF7],
+                vec![0xF0, 0x7E, 0x7F, 0xF7]
+            ]
+        );
+        assert_eq!(dump.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_incomplete() {
+        assert!(SysExDump::from_bytes(&[0xF0, 0x01]).is_err());
+        assert!(SysExDump::from_bytes(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_assembler_chunks() {
+        let mut assembler = Assembler::default();
+        assert!(assembler.push(&[0xF0, 0x01, 0x02]).is_empty());
+        // Clock interleaved mid-message is skipped
+        assert!(assembler.push(&[0xF8, 0x03]).is_empty());
+        assert_eq!(
+            assembler.push(&[0x04, 0xF7]),
+            vec![vec![0xF0, 0x01, 0x02, 0x03, 0x04, 0xF7]]
+        );
+    }
+
+    #[test]
+    fn test_assembler_ignores_other_messages() {
+        let mut assembler = Assembler::default();
+        assert!(assembler.push(&[0xB0, 0x07, 0x7F]).is_empty());
+
+        // A CC interrupting a message cancels it
+        assert!(assembler.push(&[0xF0, 0x01]).is_empty());
+        assert!(assembler.push(&[0xB0, 0x07, 0x7F, 0xF7]).is_empty());
+        assert!(assembler.pending.is_none());
+    }
+}
@@ -0,0 +1,270 @@
+//! Tonemapping for HDR [`crate::prelude::gpu::GpuState`] pipelines. Shaders
+//! render unclamped linear color into an RGBA16F offscreen texture which is
+//! then exposed and tonemapped onto the frame by a built-in post pass, so
+//! bright (e.g. bloom-heavy) output rolls off smoothly instead of clipping.
+//!
+//! # Example
+//! ```rust,ignore
+//! // init
+//! let gpu = gpu::GpuState::new_fullscreen(
+//!     app,
+//!     ctx,
+//!     wr.resolution_u32(),
+//!     to_absolute_path(file!(), "bloom.wgsl"),
+//!     &params,
+//!     0,
+//! )
+//! .hdr(gpu::Tonemap::Aces);
+//!
+//! // update
+//! self.gpu.set_exposure(self.hub.get("exposure"));
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+
+/// The WGSL source of the tonemap pass
+pub const TONEMAP_WGSL: &str = include_str!("tonemap.wgsl");
+
+/// Operator used to map HDR color into displayable range
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Tonemap {
+    /// Exposure only; anything brighter than 1.0 clips
+    Clamp,
+    /// `c / (1 + c)`. Preserves hue but flattens highlights
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve. Punchier contrast with a
+    /// gentle shoulder
+    #[default]
+    Aces,
+}
+
+impl Tonemap {
+    /// The `mode` the WGSL shader switches on
+    fn index(&self) -> u32 {
+        match self {
+            Self::Clamp => 0,
+            Self::Reinhard => 1,
+            Self::Aces => 2,
+        }
+    }
+
+    /// CPU version of the tonemap pass. `exposure` is in stops
+    pub fn apply(&self, color: Vec3, exposure: f32) -> Vec3 {
+        let x = color * 2.0_f32.powf(exposure);
+        match self {
+            Self::Clamp => x.clamp(Vec3::ZERO, Vec3::ONE),
+            Self::Reinhard => {
+                let x = x.max(Vec3::ZERO);
+                x / (Vec3::ONE + x)
+            }
+            Self::Aces => {
+                let x = x.max(Vec3::ZERO);
+                let (a, b, d, e, f) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                ((x * (a * x + b)) / (x * (d * x + e) + f))
+                    .clamp(Vec3::ZERO, Vec3::ONE)
+            }
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    exposure: f32,
+    mode: u32,
+    _pad: [f32; 2],
+}
+
+/// Fullscreen pass that samples an HDR texture and writes tonemapped color
+/// into the frame
+pub(crate) struct TonemapPass {
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl TonemapPass {
+    pub fn new(
+        device: &wgpu::Device,
+        source: &wgpu::TextureViewHandle,
+        dst_sample_count: u32,
+    ) -> Self {
+        let shader_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Tonemap Shader"),
+                source: wgpu::ShaderSource::Wgsl(TONEMAP_WGSL.into()),
+            });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                std::mem::size_of::<Params>() as _,
+                            ),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+                label: Some("Tonemap Bind Group Layout"),
+            });
+
+        let params_buffer =
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Tonemap Params Buffer"),
+                contents: bytemuck::bytes_of(&Params {
+                    exposure: 0.0,
+                    mode: Tonemap::default().index(),
+                    _pad: [0.0; 2],
+                }),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let sampler =
+            device.create_sampler(&wgpu::SamplerDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Tonemap Bind Group"),
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Tonemap Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Tonemap Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Frame::TEXTURE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: dst_sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            });
+
+        Self {
+            pipeline,
+            params_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::TextureViewHandle,
+        tonemap: Tonemap,
+        exposure: f32,
+    ) {
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::bytes_of(&Params {
+                exposure,
+                mode: tonemap.index(),
+                _pad: [0.0; 2],
+            }),
+        );
+
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(dst, |color| color)
+            .begin(encoder);
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_operators() {
+        let bright = vec3(4.0, 1.0, 0.25);
+        for tonemap in [Tonemap::Clamp, Tonemap::Reinhard, Tonemap::Aces] {
+            let c = tonemap.apply(bright, 0.0);
+            assert!(c.max_element() <= 1.0, "{:?}", tonemap);
+            assert!(c.x >= c.y && c.y >= c.z, "{:?}", tonemap);
+        }
+        assert_approx_eq!(Tonemap::Reinhard.apply(Vec3::ONE, 0.0).x, 0.5);
+        assert_approx_eq!(Tonemap::Clamp.apply(Vec3::splat(0.25), 1.0).x, 0.5);
+    }
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        let module = naga::front::wgsl::parse_str(TONEMAP_WGSL).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+    }
+}
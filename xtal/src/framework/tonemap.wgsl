@@ -0,0 +1,74 @@
+// -----------------------------------------------------------------------------
+//  XTAL TONEMAP
+//  Built-in post pass for HDR GpuState pipelines. Mirrors
+//  xtal::gpu::Tonemap::apply - keep in sync!
+// -----------------------------------------------------------------------------
+
+struct Params {
+    // Exposure in stops
+    exposure: f32,
+    // See `Tonemap::index`
+    mode: u32,
+    _pad: vec2f,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var source: texture_2d<f32>;
+
+@group(0) @binding(2)
+var source_sampler: sampler;
+
+// Single triangle covering the whole viewport
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2f(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source, source_sampler, in.uv);
+    let exposed = color.rgb * exp2(params.exposure);
+
+    var mapped: vec3f;
+    switch params.mode {
+        case 1u: {
+            mapped = reinhard(exposed);
+        }
+        case 2u: {
+            mapped = aces(exposed);
+        }
+        default: {
+            mapped = clamp(exposed, vec3f(0.0), vec3f(1.0));
+        }
+    }
+
+    return vec4f(mapped, clamp(color.a, 0.0, 1.0));
+}
+
+fn reinhard(c: vec3f) -> vec3f {
+    let x = max(c, vec3f(0.0));
+    return x / (1.0 + x);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces(c: vec3f) -> vec3f {
+    let x = max(c, vec3f(0.0));
+    let a = 2.51;
+    let b = 0.03;
+    let d = 2.43;
+    let e = 0.59;
+    let f = 0.14;
+    return clamp((x * (a * x + b)) / (x * (d * x + e) + f), vec3f(0.0), vec3f(1.0));
+}
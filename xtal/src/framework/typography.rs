@@ -0,0 +1,416 @@
+//! Text as geometry. Loads fonts and exposes glyph outlines as polylines and
+//! evenly spaced point samples (as opposed to rasterized text) so letterforms
+//! can be displaced, animated, or fed to other geometry helpers. Outlines are
+//! cached per text/size/rect (least recently used outlines are evicted) and
+//! font files are hot-reloaded.
+//!
+//! # Example
+//! ```rust,ignore
+//! let typography = Typography::from_path(
+//!     to_absolute_path(file!(), "fonts/Inter.ttf")
+//! )?;
+//!
+//! // in view
+//! let outline = self.typography.outline("XTAL", 256, wr.rect());
+//! for point in outline.sample(4.0) {
+//!     let p = point + noise_offset(point);
+//!     draw.ellipse().xy(p).radius(1.0);
+//! }
+//! ```
+
+use nannou::lyon::path::PathEvent;
+use nannou::prelude::*;
+use nannou::text::{self, Font};
+use notify::{Event, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::framework::prelude::*;
+
+/// Number of line segments used to approximate each bezier curve segment
+const CURVE_RESOLUTION: usize = 8;
+
+/// Animated text or layouts produce a new cache key every frame, so only this
+/// many outlines are kept around
+const MAX_CACHED_OUTLINES: usize = 256;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct CacheKey {
+    text: String,
+    size: u32,
+    rect: [u32; 4],
+}
+
+impl CacheKey {
+    fn new(text: &str, size: u32, rect: Rect) -> Self {
+        Self {
+            text: text.to_string(),
+            size,
+            rect: [
+                rect.x().to_bits(),
+                rect.y().to_bits(),
+                rect.w().to_bits(),
+                rect.h().to_bits(),
+            ],
+        }
+    }
+}
+
+struct CacheEntry {
+    outline: Rc<TextOutline>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct OutlineCache {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+}
+
+impl OutlineCache {
+    fn get(&mut self, key: &CacheKey) -> Option<Rc<TextOutline>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.outline.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, outline: Rc<TextOutline>) {
+        if self.entries.len() >= MAX_CACHED_OUTLINES {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                outline,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+struct UpdateState {
+    #[allow(dead_code)]
+    watcher: notify::RecommendedWatcher,
+    font: Arc<Mutex<Option<Font>>>,
+    has_changes: Arc<AtomicBool>,
+}
+
+pub struct Typography {
+    font: Font,
+    cache: RefCell<OutlineCache>,
+    update_state: Option<UpdateState>,
+}
+
+impl Default for Typography {
+    /// Uses nannou's built-in Noto Sans
+    fn default() -> Self {
+        Self::new(text::font::default_notosans())
+    }
+}
+
+impl Typography {
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            cache: RefCell::new(OutlineCache::default()),
+            update_state: None,
+        }
+    }
+
+    /// Load a TTF/OTF font file and watch it for changes. Call
+    /// [`Self::update`] once per frame to pick up changes.
+    pub fn from_path(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let font = Self::load(&path)?;
+        let mut typography = Self::new(font);
+
+        let state = Arc::new(Mutex::new(None));
+        let has_changes = Arc::new(AtomicBool::new(false));
+
+        typography.update_state = Some(UpdateState {
+            watcher: Self::setup_watcher(
+                path,
+                state.clone(),
+                has_changes.clone(),
+            )?,
+            font: state,
+            has_changes,
+        });
+
+        Ok(typography)
+    }
+
+    /// Swaps in a reloaded font (if any) and clears the outline cache.
+    /// Returns true when the font changed.
+    pub fn update(&mut self) -> bool {
+        let new_font = self.update_state.as_ref().and_then(|update_state| {
+            if !update_state.has_changes.swap(false, Ordering::AcqRel) {
+                return None;
+            }
+            update_state
+                .font
+                .lock()
+                .ok()
+                .and_then(|mut guard| guard.take())
+        });
+
+        match new_font {
+            Some(font) => {
+                self.font = font;
+                self.clear_cache();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Outline of `text` laid out (centered) within `rect` at `size`. Results
+    /// are cached so calling this every frame with the same arguments is cheap.
+    /// Only the [`MAX_CACHED_OUTLINES`] most recently used outlines are kept
+    pub fn outline(
+        &self,
+        text: &str,
+        size: u32,
+        rect: Rect,
+    ) -> Rc<TextOutline> {
+        let key = CacheKey::new(text, size, rect);
+
+        if let Some(outline) = self.cache.borrow_mut().get(&key) {
+            return outline;
+        }
+
+        let layout = text::text(text)
+            .font(self.font.clone())
+            .font_size(size)
+            .center_justify()
+            .build(rect);
+
+        let outline =
+            Rc::new(TextOutline::from_path_events(layout.path_events()));
+
+        self.cache.borrow_mut().insert(key, outline.clone());
+
+        outline
+    }
+
+    fn load(path: &Path) -> Result<Font, Box<dyn Error>> {
+        text::font::from_file(path).map_err(|e| {
+            format!("Unable to load font {:?}: {:?}", path, e).into()
+        })
+    }
+
+    fn setup_watcher(
+        path: PathBuf,
+        state: Arc<Mutex<Option<Font>>>,
+        has_changes: Arc<AtomicBool>,
+    ) -> Result<notify::RecommendedWatcher, Box<dyn Error>> {
+        let path_to_watch = path.clone();
+
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let event: Event = match res {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+
+            if event.kind
+                != notify::EventKind::Modify(notify::event::ModifyKind::Data(
+                    notify::event::DataChange::Content,
+                ))
+            {
+                return;
+            }
+
+            info!("{:?} changed. Attempting to reload font.", path);
+
+            match Self::load(&path) {
+                Ok(font) => {
+                    if let Ok(mut guard) = state.lock() {
+                        has_changes.store(true, Ordering::Release);
+                        *guard = Some(font);
+                    }
+                }
+                Err(e) => error!("{}", e),
+            }
+        })?;
+
+        watcher.watch(&path_to_watch, RecursiveMode::NonRecursive)?;
+
+        Ok(watcher)
+    }
+}
+
+/// Flattened glyph outlines. Each contour is a closed polyline that does not
+/// repeat its first point. Outer contours and holes (e.g. the inside of an
+/// "O") are both included; use [`crate::prelude::geometry::polygon_area`]
+/// to tell them apart by winding if needed.
+#[derive(Clone, Debug, Default)]
+pub struct TextOutline {
+    pub contours: Vec<Vec<Vec2>>,
+}
+
+impl TextOutline {
+    pub fn from_path_events<I>(events: I) -> Self
+    where
+        I: IntoIterator<Item = PathEvent>,
+    {
+        let mut contours = vec![];
+        let mut current: Vec<Vec2> = vec![];
+
+        for event in events {
+            match event {
+                PathEvent::Begin { at } => {
+                    current = vec![vec2(at.x, at.y)];
+                }
+                PathEvent::Line { to, .. } => {
+                    current.push(vec2(to.x, to.y));
+                }
+                PathEvent::Quadratic { from, ctrl, to } => {
+                    let [p0, p1, p2] = [from, ctrl, to].map(|p| vec2(p.x, p.y));
+                    for i in 1..=CURVE_RESOLUTION {
+                        let t = i as f32 / CURVE_RESOLUTION as f32;
+                        current.push(p0.lerp(p1, t).lerp(p1.lerp(p2, t), t));
+                    }
+                }
+                PathEvent::Cubic {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                } => {
+                    let [p0, p1, p2, p3] =
+                        [from, ctrl1, ctrl2, to].map(|p| vec2(p.x, p.y));
+                    for i in 1..=CURVE_RESOLUTION {
+                        let t = i as f32 / CURVE_RESOLUTION as f32;
+                        let a = p0.lerp(p1, t).lerp(p1.lerp(p2, t), t);
+                        let b = p1.lerp(p2, t).lerp(p2.lerp(p3, t), t);
+                        current.push(a.lerp(b, t));
+                    }
+                }
+                PathEvent::End { .. } => {
+                    if current.len() > 1 && current.first() == current.last() {
+                        current.pop();
+                    }
+                    if current.len() > 2 {
+                        contours.push(std::mem::take(&mut current));
+                    }
+                }
+            }
+        }
+
+        Self { contours }
+    }
+
+    /// Contours with the first point repeated at the end for use with
+    /// `draw.polyline()`
+    pub fn polylines(&self) -> Vec<Vec<Vec2>> {
+        self.contours
+            .iter()
+            .map(|contour| {
+                let mut points = contour.clone();
+                points.extend(contour.first());
+                points
+            })
+            .collect()
+    }
+
+    /// Points spaced evenly `spacing` units apart along every contour
+    pub fn sample(&self, spacing: f32) -> Vec<Vec2> {
+        self.contours
+            .iter()
+            .flat_map(|contour| resample_closed(contour, spacing))
+            .collect()
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut points = self.contours.iter().flatten();
+        let first = *points.next()?;
+        let (min, max) = points
+            .fold((first, first), |(min, max), p| (min.min(*p), max.max(*p)));
+        Some(Rect::from_corners(min, max))
+    }
+}
+
+fn resample_closed(contour: &[Vec2], spacing: f32) -> Vec<Vec2> {
+    let spacing = spacing.max(f32::EPSILON);
+    let mut points = vec![];
+    let mut carry = 0.0;
+
+    for (i, a) in contour.iter().enumerate() {
+        let b = contour[(i + 1) % contour.len()];
+        let length = a.distance(b);
+        let mut d = carry;
+        while d < length {
+            points.push(a.lerp(b, d / length));
+            d += spacing;
+        }
+        carry = d - length;
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    #[test]
+    fn test_resample_closed_square() {
+        let square = [
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(10.0, 10.0),
+            vec2(0.0, 10.0),
+        ];
+        let points = resample_closed(&square, 2.5);
+        assert_eq!(points.len(), 16);
+        assert_approx_eq!(points[5].x, 10.0);
+        assert_approx_eq!(points[5].y, 2.5);
+    }
+
+    #[test]
+    fn test_outline_cache_is_bounded() {
+        let typography = Typography::default();
+        let rect = Rect::from_w_h(512.0, 512.0);
+
+        let first = typography.outline("0", 32, rect);
+        for i in 1..MAX_CACHED_OUTLINES * 2 {
+            typography.outline(&i.to_string(), 32, rect);
+            assert!(typography.cache.borrow().len() <= MAX_CACHED_OUTLINES);
+        }
+
+        let last = (MAX_CACHED_OUTLINES * 2 - 1).to_string();
+        let cached = typography.outline(&last, 32, rect);
+        assert!(Rc::ptr_eq(&cached, &typography.outline(&last, 32, rect)));
+        assert!(!Rc::ptr_eq(&first, &typography.outline("0", 32, rect)));
+    }
+}
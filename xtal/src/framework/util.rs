@@ -1,12 +1,13 @@
 use ahash::RandomState;
+#[cfg(feature = "runtime")]
 use nannou::prelude::*;
-use nannou::rand::Rng;
-use nannou::rand::rand;
-use nannou::rand::thread_rng;
+use nannou_core::rand::{Rng, random, thread_rng};
 use std::collections::{HashMap as StdHashMap, HashSet as StdHashSet};
+use std::f32::consts::PI;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
 
+#[cfg(feature = "runtime")]
 use super::prelude::*;
 
 pub const TWO_PI: f32 = PI * 2.0;
@@ -51,7 +52,7 @@ pub fn bool_to_f32(cond: bool) -> f32 {
 pub mod constrain {
     /// Clamp a value between min and max
     pub fn clamp(value: f32, min: f32, max: f32) -> f32 {
-        nannou::prelude::clamp(value, min, max)
+        nannou_core::math::clamp(value, min, max)
     }
 
     /// Clamp a value between min and max such that values that overshoot are
@@ -113,7 +114,7 @@ pub fn random_bool() -> bool {
 }
 
 pub fn random_within_range_stepped(min: f32, max: f32, step: f32) -> f32 {
-    let mut rng = rand::thread_rng();
+    let mut rng = thread_rng();
     let random_value = min + rng.gen_range(0.0..1.0) * (max - min);
     let quantized_value = (random_value / step).round() * step;
     f32::max(min, f32::min(max, quantized_value))
@@ -131,6 +132,7 @@ pub fn safe_range(min: f32, max: f32) -> (f32, f32) {
     (a, b)
 }
 
+#[cfg(feature = "runtime")]
 pub(crate) fn set_window_position(
     app: &App,
     window_id: window::Id,
@@ -143,6 +145,7 @@ pub(crate) fn set_window_position(
         .set_outer_position(nannou::winit::dpi::PhysicalPosition::new(x, y));
 }
 
+#[cfg(feature = "runtime")]
 pub(crate) fn set_window_size(
     window: &nannou::winit::window::Window,
     w: i32,
@@ -0,0 +1,529 @@
+//! Projection mapping output stage. The finished frame (after any `post`
+//! effects) is drawn onto a corner-pinned quad, optionally bent by a Bezier
+//! mesh, so sketches can be projected onto surfaces that aren't flat or square
+//! to the projector without external mapping software. Warps are edited under
+//! **UI > Settings > Output Warp** and saved per venue. The runtime runs the
+//! stage last, after the sketch's `view` and its own overlays, and only for
+//! display: captured images and recordings are taken before it.
+
+use bytemuck::{Pod, Zeroable};
+use nannou::prelude::*;
+use nannou::wgpu;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use wgpu_types::SamplerBindingType;
+
+/// The WGSL source of the warp stage
+pub const WARP_WGSL: &str = include_str!("warp.wgsl");
+
+/// Quads per side of the tessellated output mesh
+const SUBDIVISIONS: usize = 32;
+
+static OUTPUT_WARP: Mutex<Option<Warp>> = Mutex::new(None);
+
+/// Bumped on every [`set_output_warp`] so the stage knows to rebuild its mesh
+static VERSION: AtomicU32 = AtomicU32::new(0);
+
+/// Sets the warp applied to the main window's output, `None` to disable
+pub(crate) fn set_output_warp(warp: Option<Warp>) {
+    *OUTPUT_WARP.lock().unwrap() = warp;
+    VERSION.fetch_add(1, Ordering::AcqRel);
+}
+
+pub fn output_warp() -> Option<Warp> {
+    OUTPUT_WARP.lock().unwrap().clone()
+}
+
+/// Corner pin plus an optional mesh, both in normalized coordinates with the
+/// origin at the top-left and y pointing down
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Warp {
+    /// Where the source's top-left, top-right, bottom-right, and bottom-left
+    /// corners land in the output
+    pub corners: [[f32; 2]; 4],
+    /// Applied in source space before the corner pin
+    pub mesh: Option<WarpMesh>,
+}
+
+impl Default for Warp {
+    fn default() -> Self {
+        Self {
+            corners: [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            mesh: None,
+        }
+    }
+}
+
+impl Warp {
+    /// The projective transform taking the unit square onto [`Self::corners`]
+    pub fn homography(&self) -> Mat3 {
+        let [[x0, y0], [x1, y1], [x2, y2], [x3, y3]] = self.corners;
+        let (dx1, dy1) = (x1 - x2, y1 - y2);
+        let (dx2, dy2) = (x3 - x2, y3 - y2);
+        let (dx3, dy3) = (x0 - x1 + x2 - x3, y0 - y1 + y2 - y3);
+
+        let det = dx1 * dy2 - dx2 * dy1;
+        let (g, h) = if det.abs() < f32::EPSILON {
+            (0.0, 0.0)
+        } else {
+            ((dx3 * dy2 - dx2 * dy3) / det, (dx1 * dy3 - dx3 * dy1) / det)
+        };
+
+        Mat3::from_cols(
+            vec3(x1 - x0 + g * x1, y1 - y0 + g * y1, g),
+            vec3(x3 - x0 + h * x3, y3 - y0 + h * y3, h),
+            vec3(x0, y0, 1.0),
+        )
+    }
+
+    /// Where the source point `uv` lands in the output
+    pub fn map(&self, uv: Vec2) -> Vec2 {
+        let p = self.mesh_point(uv);
+        let h = self.homography() * p.extend(1.0);
+        h.truncate() / h.z
+    }
+
+    fn mesh_point(&self, uv: Vec2) -> Vec2 {
+        match &self.mesh {
+            Some(mesh) if mesh.is_valid() => mesh.evaluate(uv),
+            _ => uv,
+        }
+    }
+
+    /// A triangle list covering the source with positions in clip space
+    fn vertices(&self) -> Vec<WarpVertex> {
+        let homography = self.homography();
+        let n = SUBDIVISIONS;
+
+        let grid: Vec<WarpVertex> = (0..=n)
+            .flat_map(|row| (0..=n).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let uv = vec2(col as f32 / n as f32, row as f32 / n as f32);
+                let h = homography * self.mesh_point(uv).extend(1.0);
+                WarpVertex {
+                    // Keeping w lets the GPU interpolate uv with perspective
+                    position: [2.0 * h.x - h.z, h.z - 2.0 * h.y, 0.0, h.z],
+                    uv: uv.to_array(),
+                }
+            })
+            .collect();
+
+        let index = |col: usize, row: usize| row * (n + 1) + col;
+        let mut vertices = Vec::with_capacity(n * n * 6);
+        for row in 0..n {
+            for col in 0..n {
+                for (c, r) in [(0, 0), (1, 0), (1, 1), (0, 0), (1, 1), (0, 1)] {
+                    vertices.push(grid[index(col + c, row + r)]);
+                }
+            }
+        }
+        vertices
+    }
+}
+
+/// A tensor-product Bezier surface over the source. Control points start on a
+/// regular grid, which maps every point to itself; moving them bends the
+/// image smoothly
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarpMesh {
+    pub cols: usize,
+    pub rows: usize,
+    /// `cols * rows` control points, row by row from the top-left
+    pub points: Vec<[f32; 2]>,
+}
+
+impl WarpMesh {
+    /// An identity mesh with at least 2 control points per side
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let cols = cols.max(2);
+        let rows = rows.max(2);
+        let points = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                [
+                    col as f32 / (cols - 1) as f32,
+                    row as f32 / (rows - 1) as f32,
+                ]
+            })
+            .collect();
+        Self { cols, rows, points }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.cols >= 2
+            && self.rows >= 2
+            && self.points.len() == self.cols * self.rows
+    }
+
+    pub fn evaluate(&self, uv: Vec2) -> Vec2 {
+        let bu = bernstein(self.cols - 1, uv.x);
+        let bv = bernstein(self.rows - 1, uv.y);
+        let mut p = Vec2::ZERO;
+        for (row, wv) in bv.iter().enumerate() {
+            for (col, wu) in bu.iter().enumerate() {
+                p += Vec2::from(self.points[row * self.cols + col]) * (wu * wv);
+            }
+        }
+        p
+    }
+}
+
+/// The degree `n` Bernstein basis at `t`
+fn bernstein(n: usize, t: f32) -> Vec<f32> {
+    let mut binomial = 1.0;
+    (0..=n)
+        .map(|i| {
+            if i > 0 {
+                binomial *= (n + 1 - i) as f32 / i as f32;
+            }
+            binomial * t.powi(i as i32) * (1.0 - t).powi((n - i) as i32)
+        })
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct WarpVertex {
+    position: [f32; 4],
+    uv: [f32; 2],
+}
+
+struct Resources {
+    size: [u32; 2],
+    dst_sample_count: u32,
+    /// Single-sampled copy of the frame the mesh samples from
+    source: wgpu::TextureView,
+    /// Copies the frame into `source` when the frame isn't multisampled
+    /// (multisampled frames are resolved instead)
+    reshaper: Option<wgpu::TextureReshaper>,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: Option<(wgpu::Buffer, u32)>,
+}
+
+impl Resources {
+    fn new(frame: &Frame) -> Self {
+        let device = frame.device_queue_pair().device();
+        let size = frame.texture_size();
+        let dst_sample_count = frame.texture_msaa_samples();
+
+        let shader_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Warp Shader"),
+                source: wgpu::ShaderSource::Wgsl(WARP_WGSL.into()),
+            });
+
+        let source = wgpu::TextureBuilder::new()
+            .size(size)
+            .format(Frame::TEXTURE_FORMAT)
+            .dimension(wgpu::TextureDimension::D2)
+            .usage(
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+            .sample_count(1)
+            .build(device)
+            .view()
+            .build();
+
+        let reshaper = (dst_sample_count == 1).then(|| {
+            wgpu::TextureReshaper::new(
+                device,
+                frame.texture_view(),
+                1,
+                wgpu::TextureSampleType::Float { filterable: true },
+                1,
+                Frame::TEXTURE_FORMAT,
+            )
+        });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+                label: Some("Warp Bind Group Layout"),
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Warp Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Warp Bind Group"),
+        });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Warp Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Warp Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: "vs_main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<WarpVertex>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![
+                            0 => Float32x4,
+                            1 => Float32x2
+                        ],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Frame::TEXTURE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: dst_sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            });
+
+        Self {
+            size,
+            dst_sample_count,
+            source,
+            reshaper,
+            bind_group,
+            pipeline,
+            vertex_buffer: None,
+        }
+    }
+
+    /// Copies the frame's current contents into `source`
+    fn capture(&self, frame: &Frame, encoder: &mut wgpu::CommandEncoder) {
+        match &self.reshaper {
+            Some(reshaper) => {
+                reshaper.encode_render_pass(&self.source, encoder);
+            }
+            None => {
+                // An empty pass is enough to resolve the multisampled frame
+                wgpu::RenderPassBuilder::new()
+                    .color_attachment(frame.texture_view(), |color| {
+                        color
+                            .load_op(wgpu::LoadOp::Load)
+                            .resolve_target(Some(&self.source))
+                    })
+                    .begin(encoder);
+            }
+        }
+    }
+}
+
+/// Draws the frame through the current [`output_warp`]. Applied by the runtime
+/// after `view`
+#[derive(Default)]
+pub(crate) struct OutputWarp {
+    resources: Option<Resources>,
+
+    /// The [`VERSION`] the vertex buffer was built from
+    version: u32,
+}
+
+impl std::fmt::Debug for OutputWarp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutputWarp")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OutputWarp {
+    pub fn apply(&mut self, frame: &Frame) {
+        let warp = OUTPUT_WARP.lock().unwrap();
+        let Some(warp) = warp.as_ref() else {
+            return;
+        };
+
+        if self.resources.as_ref().is_none_or(|r| {
+            r.size != frame.texture_size()
+                || r.dst_sample_count != frame.texture_msaa_samples()
+        }) {
+            self.resources = Some(Resources::new(frame));
+        }
+        let resources = self.resources.as_mut().unwrap();
+
+        let version = VERSION.load(Ordering::Acquire);
+        if resources.vertex_buffer.is_none() || version != self.version {
+            let vertices = warp.vertices();
+            let buffer = frame.device_queue_pair().device().create_buffer_init(
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some("Warp Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                },
+            );
+            resources.vertex_buffer = Some((buffer, vertices.len() as u32));
+            self.version = version;
+        }
+
+        let mut encoder = frame.command_encoder();
+        resources.capture(frame, &mut encoder);
+
+        let (vertex_buffer, vertex_count) =
+            resources.vertex_buffer.as_ref().unwrap();
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(frame.texture_view(), |color| {
+                color.load_op(wgpu::LoadOp::Clear(wgpu::Color::BLACK))
+            })
+            .begin(&mut encoder);
+        render_pass.set_pipeline(&resources.pipeline);
+        render_pass.set_bind_group(0, &resources.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..*vertex_count, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::gpu::validate_shader;
+
+    fn assert_vec2_eq(a: Vec2, b: Vec2) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_wgsl_is_valid() {
+        validate_shader(WARP_WGSL).unwrap();
+    }
+
+    #[test]
+    fn test_identity() {
+        let warp = Warp::default();
+        for uv in [vec2(0.0, 0.0), vec2(0.25, 0.75), vec2(1.0, 1.0)] {
+            assert_vec2_eq(warp.map(uv), uv);
+        }
+
+        let warp = Warp {
+            mesh: Some(WarpMesh::new(4, 3)),
+            ..Default::default()
+        };
+        for uv in [vec2(0.1, 0.9), vec2(0.5, 0.5), vec2(0.8, 0.3)] {
+            assert_vec2_eq(warp.map(uv), uv);
+        }
+    }
+
+    #[test]
+    fn test_corner_pin() {
+        let corners = [[0.1, 0.2], [0.9, 0.0], [1.0, 1.0], [0.0, 0.8]];
+        let warp = Warp {
+            corners,
+            mesh: None,
+        };
+
+        let unit = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        for (uv, corner) in unit.iter().zip(corners) {
+            assert_vec2_eq(warp.map(Vec2::from(*uv)), Vec2::from(corner));
+        }
+
+        // Straight lines stay straight: the midpoint of the top edge lands on
+        // the line between the top corners
+        let top = warp.map(vec2(0.5, 0.0));
+        let a = Vec2::from(corners[0]);
+        let b = Vec2::from(corners[1]);
+        assert!((b - a).perp_dot(top - a).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mesh() {
+        let mut mesh = WarpMesh::new(3, 3);
+        // Pull the center control point right
+        mesh.points[4] = [0.8, 0.5];
+
+        let warp = Warp {
+            mesh: Some(mesh),
+            ..Default::default()
+        };
+        assert!(warp.map(vec2(0.5, 0.5)).x > 0.5);
+        // Corners stay pinned
+        assert_vec2_eq(warp.map(vec2(0.0, 0.0)), vec2(0.0, 0.0));
+        assert_vec2_eq(warp.map(vec2(1.0, 1.0)), vec2(1.0, 1.0));
+
+        // Invalid meshes are ignored
+        let warp = Warp {
+            mesh: Some(WarpMesh {
+                cols: 3,
+                rows: 3,
+                points: vec![],
+            }),
+            ..Default::default()
+        };
+        assert_vec2_eq(warp.map(vec2(0.3, 0.6)), vec2(0.3, 0.6));
+    }
+
+    #[test]
+    fn test_vertices() {
+        let vertices = Warp::default().vertices();
+        assert_eq!(vertices.len(), SUBDIVISIONS * SUBDIVISIONS * 6);
+        // Top-left of the source lands top-left in clip space
+        assert_eq!(vertices[0].position, [-1.0, 1.0, 0.0, 1.0]);
+        assert_eq!(vertices[0].uv, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_serde() {
+        let warp = Warp {
+            mesh: Some(WarpMesh::new(2, 2)),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&warp).unwrap();
+        assert_eq!(serde_json::from_str::<Warp>(&json).unwrap(), warp);
+        assert_eq!(
+            serde_json::from_str::<Warp>("{}").unwrap(),
+            Warp::default()
+        );
+    }
+}
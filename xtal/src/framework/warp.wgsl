@@ -0,0 +1,36 @@
+// -----------------------------------------------------------------------------
+//  XTAL WARP
+//  Output stage that draws the captured frame onto a warped mesh for
+//  projection mapping. Vertices arrive in clip space with the corner-pin
+//  homography's w so the source uv is interpolated perspective-correctly.
+//  See xtal::warp
+// -----------------------------------------------------------------------------
+
+struct VertexInput {
+    @location(0) position: vec4f,
+    @location(1) uv: vec2f,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+}
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = in.position;
+    out.uv = in.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return textureSample(source, source_sampler, in.uv);
+}
@@ -1,11 +1,96 @@
 use nannou::prelude::*;
 use std::cell::RefCell;
+use std::fmt;
 use std::rc::Rc;
 
+/// Fraction of each dimension trimmed from every edge to get the action-safe
+/// area (93% of the frame)
+pub const ACTION_SAFE_INSET: f32 = 0.035;
+
+/// Fraction of each dimension trimmed from every edge to get the title-safe
+/// area (90% of the frame)
+pub const TITLE_SAFE_INSET: f32 = 0.05;
+
+/// Named aspect ratios the main window can be switched to at runtime, mostly
+/// for previewing compositions destined for social media formats
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AspectPreset {
+    /// The width and height defined in the sketch's
+    /// [`crate::prelude::SketchConfig`]
+    #[default]
+    Sketch,
+    /// 1:1
+    Square,
+    /// 9:16 (stories, reels, shorts)
+    Vertical,
+    /// 16:9
+    Widescreen,
+    /// 4:5 (feed posts)
+    Portrait,
+}
+
+impl AspectPreset {
+    pub const ALL: [Self; 5] = [
+        Self::Sketch,
+        Self::Square,
+        Self::Vertical,
+        Self::Widescreen,
+        Self::Portrait,
+    ];
+
+    /// Width divided by height, or `None` for [`AspectPreset::Sketch`]
+    pub fn ratio(&self) -> Option<f32> {
+        match self {
+            Self::Sketch => None,
+            Self::Square => Some(1.0),
+            Self::Vertical => Some(9.0 / 16.0),
+            Self::Widescreen => Some(16.0 / 9.0),
+            Self::Portrait => Some(4.0 / 5.0),
+        }
+    }
+
+    /// The preset after this one, wrapping back around to
+    /// [`AspectPreset::Sketch`]
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Window dimensions for this preset given the sketch's own dimensions.
+    /// The longest edge of the sketch is kept so switching presets never
+    /// grows the window beyond what the sketch was designed for.
+    pub fn size(&self, sketch_w: i32, sketch_h: i32) -> (i32, i32) {
+        let Some(ratio) = self.ratio() else {
+            return (sketch_w, sketch_h);
+        };
+        let long_edge = sketch_w.max(sketch_h) as f32;
+        if ratio >= 1.0 {
+            (long_edge as i32, (long_edge / ratio).round() as i32)
+        } else {
+            ((long_edge * ratio).round() as i32, long_edge as i32)
+        }
+    }
+}
+
+impl fmt::Display for AspectPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Sketch => "Sketch",
+            Self::Square => "1:1",
+            Self::Vertical => "9:16",
+            Self::Widescreen => "16:9",
+            Self::Portrait => "4:5",
+        };
+        write!(f, "{}", label)
+    }
+}
+
 #[derive(Debug)]
 struct WindowRectState {
     current: Rect,
     last: Rect,
+    /// The full window. Only differs from `current` when letterboxing
+    window: Rect,
 }
 
 /// A wrapper around nannou's `Rect` that is used to provide the main window to
@@ -25,6 +110,7 @@ impl WindowRect {
             state: Rc::new(RefCell::new(WindowRectState {
                 current: initial,
                 last: initial,
+                window: initial,
             })),
         }
     }
@@ -33,6 +119,24 @@ impl WindowRect {
         self.state.borrow_mut().current = rect;
     }
 
+    pub(crate) fn set_window(&mut self, rect: Rect) {
+        self.state.borrow_mut().window = rect;
+    }
+
+    /// The full main window. Identical to [`Self::rect`] unless the runtime's
+    /// letterbox mode is enabled, in which case [`Self::rect`] (and all other
+    /// dimension methods) describe the fixed-aspect content area centered
+    /// within this rect.
+    pub fn window(&self) -> Rect {
+        self.state.borrow().window
+    }
+
+    pub fn is_letterboxed(&self) -> bool {
+        let state = self.state.borrow();
+        state.window.w() != state.current.w()
+            || state.window.h() != state.current.h()
+    }
+
     /// Returns true if the window size has changed since the last time
     /// [`Self::mark_unchanged`] was called. Use this in the `update` function
     /// when you want to perform an expensive operation only when needed.
@@ -144,6 +248,30 @@ impl WindowRect {
         self.state.borrow().current.y()
     }
 
+    /// The largest rect with the given width/height `ratio` that fits within
+    /// the window, centered
+    pub fn fit_aspect(&self, ratio: f32) -> Rect {
+        fit_aspect(self.rect(), ratio)
+    }
+
+    /// The window rect with `inset` (a fraction of each dimension) trimmed
+    /// from every edge. See [`ACTION_SAFE_INSET`] and [`TITLE_SAFE_INSET`]
+    pub fn safe_area(&self, inset: f32) -> Rect {
+        let (w, h) = self.wh();
+        Rect::from_xy_wh(
+            self.rect().xy(),
+            vec2(w * (1.0 - inset * 2.0), h * (1.0 - inset * 2.0)),
+        )
+    }
+
+    pub fn action_safe(&self) -> Rect {
+        self.safe_area(ACTION_SAFE_INSET)
+    }
+
+    pub fn title_safe(&self) -> Rect {
+        self.safe_area(TITLE_SAFE_INSET)
+    }
+
     pub fn pad(&self, value: f32) -> Rect {
         self.state.borrow().current.pad(value)
     }
@@ -180,3 +308,60 @@ impl WindowRect {
         self.state.borrow().current.bottom_right()
     }
 }
+
+/// The largest rect with the given width/height `ratio` that fits within
+/// `rect`, centered
+pub(crate) fn fit_aspect(rect: Rect, ratio: f32) -> Rect {
+    let (w, h) = rect.w_h();
+    let fitted = if w / h > ratio {
+        vec2(h * ratio, h)
+    } else {
+        vec2(w, w / ratio)
+    };
+    Rect::from_xy_wh(rect.xy(), fitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_size_keeps_long_edge() {
+        assert_eq!(AspectPreset::Sketch.size(700, 500), (700, 500));
+        assert_eq!(AspectPreset::Square.size(700, 500), (700, 700));
+        assert_eq!(AspectPreset::Vertical.size(1600, 900), (900, 1600));
+        assert_eq!(AspectPreset::Widescreen.size(900, 1600), (1600, 900));
+        assert_eq!(AspectPreset::Portrait.size(500, 1000), (800, 1000));
+    }
+
+    #[test]
+    fn test_preset_next_wraps() {
+        let mut preset = AspectPreset::default();
+        for _ in 0..AspectPreset::ALL.len() {
+            preset = preset.next();
+        }
+        assert_eq!(preset, AspectPreset::Sketch);
+    }
+
+    #[test]
+    fn test_fit_aspect_and_safe_area() {
+        let wr = WindowRect::new(Rect::from_w_h(400.0, 200.0));
+        let fitted = wr.fit_aspect(1.0);
+        assert_eq!((fitted.w(), fitted.h()), (200.0, 200.0));
+        let fitted = wr.fit_aspect(4.0);
+        assert_eq!((fitted.w(), fitted.h()), (400.0, 100.0));
+        let safe = wr.title_safe();
+        assert_eq!((safe.w(), safe.h()), (360.0, 180.0));
+    }
+
+    #[test]
+    fn test_letterboxed() {
+        let window = Rect::from_w_h(400.0, 200.0);
+        let mut wr = WindowRect::new(window);
+        assert!(!wr.is_letterboxed());
+        wr.set_current(fit_aspect(window, 1.0));
+        assert!(wr.is_letterboxed());
+        assert_eq!(wr.wh(), (200.0, 200.0));
+        assert_eq!(wr.window().w(), 400.0);
+    }
+}
@@ -13,16 +13,22 @@ pub(crate) mod framework;
 pub(crate) mod runtime;
 pub(crate) use runtime::global;
 
-#[cfg(not(docsrs))]
+#[cfg(all(feature = "runtime", not(docsrs)))]
 /// Run the app after registering your sketches with [`register`]
 pub use crate::runtime::app::run;
 
 #[doc(hidden)]
+#[cfg(feature = "runtime")]
 pub use crate::runtime::registry::REGISTRY;
 
 #[doc(hidden)]
 pub mod internal {
+    #[cfg(feature = "bench")]
+    pub use crate::framework::benchmarks;
+    pub use crate::framework::frame_controller;
     pub use crate::framework::midi::{self};
+    pub use crate::framework::sysex;
+    #[cfg(feature = "runtime")]
     pub use crate::runtime::web_view_process::run as run_web_view;
 }
 
@@ -30,20 +36,58 @@ pub mod internal {
 pub mod prelude {
     pub use crate::framework::audio::Audio;
     pub use crate::framework::control::audio_controls::*;
+    pub use crate::framework::control::constants::*;
+    pub use crate::framework::control::control_error::*;
+    pub use crate::framework::control::control_history::*;
     pub use crate::framework::control::control_hub::*;
     pub use crate::framework::control::control_hub_builder::*;
     pub use crate::framework::control::control_hub_provider::*;
     pub use crate::framework::control::control_traits::*;
+    pub use crate::framework::control::debug_graph::*;
+    pub use crate::framework::control::latency::{Latency, LatencyOffsets};
     pub use crate::framework::control::midi_controls::*;
     pub use crate::framework::control::osc_controls::*;
+    pub use crate::framework::control::script_error::*;
+    pub use crate::framework::control::triggers::*;
     pub use crate::framework::control::ui_controls::*;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::camera::{Camera, CameraPose, CameraUniform};
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::color;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::depth::{
+        DepthBackend, DepthCamera, DepthDevice, DepthFrame, DepthIntrinsics,
+        list_depth_devices, register_depth_backend,
+    };
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::displacer::*;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::geometry;
+    #[cfg(feature = "runtime")]
     pub use crate::framework::gpu;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::lut::{HotLut, Lut};
+    pub use crate::framework::midi_file::MidiFile;
     pub use crate::framework::motion::*;
     pub use crate::framework::noise::*;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::palette::Palette;
+    #[cfg(feature = "runtime")]
+    pub use crate::physics;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::post::PostEffect;
+    pub use crate::framework::rng::RngStream;
+    #[cfg(feature = "runtime")]
     pub use crate::framework::sketch::*;
+    pub use crate::framework::spans;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::typography::*;
     pub use crate::framework::util::*;
-    pub use crate::framework::window_rect::WindowRect;
+    #[cfg(feature = "runtime")]
+    pub use crate::framework::window_rect::{AspectPreset, WindowRect};
+    #[cfg(feature = "runtime")]
     pub use crate::register;
+    #[cfg(feature = "runtime")]
     pub use crate::runtime::app::run;
     pub use crate::ternary;
     pub use xtal_macros::{SketchComponents, uniforms};
@@ -61,19 +105,52 @@ pub mod prelude {
 /// Control sketch parameters with UI controls, MIDI, OSC, and audio
 pub mod control {
     pub use crate::framework::control::audio_controls::*;
+    pub use crate::framework::control::constants::*;
+    pub use crate::framework::control::control_error::*;
+    pub use crate::framework::control::control_history::*;
     pub use crate::framework::control::control_hub::*;
     pub use crate::framework::control::control_hub_builder::*;
     pub use crate::framework::control::control_traits::*;
+    pub use crate::framework::control::debug_graph::*;
+    pub use crate::framework::control::latency::{Latency, LatencyOffsets};
     pub use crate::framework::control::midi_controls::*;
     pub use crate::framework::control::osc_controls::*;
+    pub use crate::framework::control::script_error::*;
+    pub use crate::framework::control::triggers::*;
     pub use crate::framework::control::ui_controls::*;
 }
 
+/// Computational geometry helpers
+#[cfg(feature = "runtime")]
+pub mod geometry {
+    pub use crate::framework::geometry::*;
+}
+
+/// Drive the frame clock from a host other than the bundled runtime
+pub mod frame {
+    pub use crate::framework::frame_controller::{
+        fps, frame_count, is_frozen, is_paused, set_fps, set_frozen,
+        set_paused, tick,
+    };
+}
+
 /// Timing, animation, and easing methods
 pub mod motion {
     pub use crate::framework::motion::*;
 }
 
+/// Verlet particles, springs, and constraints plus flocking
+#[cfg(feature = "runtime")]
+pub mod physics {
+    pub use crate::framework::flocking::*;
+    pub use crate::framework::physics::*;
+}
+
+/// Audio device, MIDI port, OSC port, and directory settings
+pub mod settings {
+    pub use crate::runtime::global::*;
+}
+
 /// A dumping ground for miscellaneous helpers
 pub mod util {
     pub use crate::framework::util::*;
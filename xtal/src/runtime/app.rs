@@ -1,28 +1,58 @@
 use chrono::Utc;
+use clap::Parser;
+use indexmap::IndexMap;
 use nannou::prelude::*;
+use nannou_osc as osc;
 use std::cell::{Cell, Ref};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::path::PathBuf;
-use std::process::Child;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::mpsc;
 use std::time::Duration;
-use std::{env, str, thread};
-
+use std::{str, thread};
+use tracing::trace_span;
+
+use super::auto_bpm::{self, AutoBpm};
+use super::cli::Args;
+use super::control_panel::{ControlPanel, ControlPanelState};
+use super::cue_list::{Cue, CueList};
+use super::flipbook::Flipbook;
 use super::map_mode::{MapMode, Mappings};
-use super::recording::{self, RecordingState};
+use super::midi_actions::{self, MidiAction, MidiActionMappings};
+use super::network_sync::{NetworkSync, SyncMessage, SyncRole};
+use super::osc_query::OscQueryServer;
+use super::output_warps::OutputWarps;
+use super::recording::{
+    self, CountInTick, PendingEncodes, RecordingSettings, RecordingState,
+};
 use super::registry::REGISTRY;
 use super::serialization::{
-    GLOBAL_SETTINGS_VERSION, GlobalSettings, TransitorySketchState,
+    GLOBAL_SETTINGS_VERSION, GlobalSettings, TimingOverrides,
+    TransitorySketchState,
 };
+use super::set_list::{SetList, SetListEntry};
+use super::sketch_inputs::SketchInputs;
 use super::storage;
 use super::tap_tempo::TapTempo;
 use super::web_view::{self as wv};
+use crate::framework::calibration::CalibrationMode;
+use crate::framework::depth::list_depth_devices;
+use crate::framework::gpu_stats;
+use crate::framework::instrumentation;
 use crate::framework::osc_receiver::SHARED_OSC_RECEIVER;
-use crate::framework::{frame_controller, prelude::*};
+use crate::framework::sketch::{self, LifecycleHook};
+use crate::framework::spans::{self, Subsystem};
+use crate::framework::stereo;
+use crate::framework::sysex::SysExRecorder;
+use crate::framework::warp;
+use crate::framework::{frame_controller, prelude::*, window_rect};
 use crate::runtime::global;
 
+/// Frames between control history updates sent to the UI
+const HISTORY_SEND_INTERVAL: u32 = 6;
+
 pub fn run() {
     nannou::app(model)
         .update(update)
@@ -40,50 +70,155 @@ pub enum AppEvent {
     AdvanceSingleFrame,
     Alert(String),
     AlertAndLog(String, log::Level),
+    AspectPreset(AspectPreset),
+    AudioDeviceChanged,
+    /// Follow the BPM and beat phase detected on the audio device
+    AutoBpmEnabled(bool),
+    /// Capture `frames` frames `beats` apart into a single contact sheet
+    CaptureFlipbook(usize, f32),
     CaptureFrame,
     ChangeAudioDevice(String),
+    ChangeBpm(f32),
+    ChangeDepthDevice(String),
+    ChangeFps(f32),
+    ChangeLatencyOffsets(LatencyOffsets),
     ChangeMidiClockPort(String),
     ChangeMidiControlInputPort(String),
     ChangeMidiControlOutputPort(String),
+    ChangeMsaaSamples(u32),
     ChangeOscPort(u16),
+    ChangeRecordingSettings(RecordingSettings),
+    /// Override a slider's range, or restore its declared one when `None`
+    ChangeSliderRange(String, Option<SliderRange>),
+    ChangeStereo(gpu::StereoSettings),
+    ChangeSyncRole(SyncRole),
     ClearNextFrame,
     CommitMappings,
+    /// Fire the cue at this position in the cue list
+    CueGoTo(usize),
+    /// Fire the first cue with this name
+    CueGoToName(String),
+    CueNext,
+    CuePrevious,
     CurrentlyMapping(String),
+    CycleAspectPreset,
+    /// Off -> over the sketch -> test pattern only. See
+    /// [`crate::framework::calibration`]
+    CycleCalibration,
+    FlipbookCaptured,
+    /// Stop rendering while the clock keeps running. See
+    /// [`frame_controller::set_frozen`]
+    Frozen(bool),
+    /// Latch (`true`) or release a control's current value. See
+    /// [`ControlHub::set_hold`]
+    Hold(String, bool),
     HubPopulated,
     Hrcc(bool),
     EncodingComplete,
+    EncodingProgress(Option<recording::EncodingProgress>),
+    Instrumentation(bool),
+    /// Bind the next CC received to an action, or stop learning when `None`
+    LearnMidiAction(Option<MidiAction>),
     MappingsEnabled(bool),
+    MidiActionLearned(MidiAction, ChannelAndController),
     MidiContinue,
     MidiStart,
     MidiStop,
+    /// Raise (or lower, when negative) the BPM by this amount
+    NudgeBpm(f32),
     OpenOsDir(wv::OsDir),
+    /// Replace the output warps and re-apply the current venue's warp
+    OutputWarps(OutputWarps),
     Paused(bool),
     PerfMode(bool),
+    Prewarm(bool),
+    /// Build a sketch ahead of switching to it. See
+    /// [`AppModel::prewarm_sketch`]
+    PrewarmSketch(String),
+    /// Switch the live sketch to another control script profile. See
+    /// [`ControlHub::set_profile`]
+    Profile(String),
     QueueRecord,
+    /// Quit, first asking the web view what to do with takes still encoding
     Quit,
     Randomize(Exclusions),
     ReceiveDir(wv::UserDir, String),
     ReceiveMappings(Mappings),
+    /// Play back a control value recording, or stop (`None`). See
+    /// [`ControlHub::start_replay`]
+    Replay(Option<PathBuf>),
+    RequestDebugGraph,
+    RequestGpuStats,
+    /// Sample a control over the coming beats. Answered with
+    /// [`wv::Event::Preview`]
+    RequestPreview(String, usize, f32),
+    /// Drops a marker into the current take, named automatically when `None`
+    RecordingMarker(Option<String>),
+    /// Start recording control values to a CSV file, or stop and save
+    /// (`None`). See [`ControlHub::start_value_recording`]
+    RecordValues(Option<Vec<String>>),
     RemoveMapping(String),
+    RemoveMidiAction(MidiAction),
+    /// Replace the favorite sketches with the given order
+    ReorderFavorites(Vec<String>),
+    RuntimeBypass(String, bool),
     Reset,
+    ResetTimingOverrides,
     Resize,
     Save(Exclusions),
+    SaveTrace,
+    ScriptErrorChanged,
     SendMidi,
     SendMappings,
+    /// Replace the set list's entries and MIDI binding
+    SetList(SetList),
+    SetListGoTo(usize),
+    SetListNext,
+    SetListPrevious,
+    /// Flush state, close MIDI and OSC, and exit. See [`AppModel::shutdown`]
+    Shutdown(PendingEncodes),
+    /// Copy the named values from one snapshot into another. See
+    /// [`ControlHub::copy_snapshot_values`]
+    SnapshotCopyValues(String, String, Vec<String>),
     SnapshotDelete(String),
+    /// Compare two snapshots. Answered with [`wv::Event::SnapshotDiff`]
+    SnapshotDiff(String, String),
     SnapshotRecall(String),
+    /// Recall a snapshot for only the given controls and groups. See
+    /// [`ControlHub::recall_snapshot_only`]
+    SnapshotRecallOnly(String, Vec<String>),
     SnapshotStore(String),
     SnapshotEnded,
+    /// See [`ControlHub::set_snapshot_phase`]
+    SnapshotPhase(bool),
+    Solo(Option<String>),
     SwitchSketch(String),
+    SyncReceived(SyncMessage),
+    /// Start (`true`) or stop and save (`false`) recording a SysEx dump from
+    /// the MIDI control input port
+    SysExBackup(bool),
+    /// Send the current sketch's saved SysEx dump to the MIDI control output
+    /// port
+    SysExRestore,
     Tap,
     TapTempoEnabled(bool),
     TransitionTime(f32),
     StartRecording,
     StopRecording,
+    ToggleFavorite(String),
     ToggleFullScreen,
     ToggleGuiFocus,
+    ToggleLetterbox,
     ToggleMainFocus,
+    ToggleSafeAreaGuides,
+    TraceSubsystem(Subsystem, bool),
+    TrackControlHistory(HistoryTracking),
+    /// Fire an `adsr` envelope at full velocity. See
+    /// [`ControlHub::trigger_envelope`]
+    TriggerEnvelope(String),
     UpdateUiControl((String, ControlValue)),
+    /// The web view process exited or crashed. Another one may connect later
+    WebViewDisconnected,
     WebViewReady,
 }
 
@@ -116,26 +251,72 @@ pub type ClearFlag = Rc<Cell<bool>>;
 struct AppModel {
     app_rx: AppEventReceiver,
     app_tx: AppEventSender,
+    auto_bpm: AutoBpm,
+    /// Whether the bar position was aligned to the first confident estimate
+    /// since auto BPM was enabled
+    auto_bpm_locked: bool,
     clear_next_frame: ClearFlag,
+    /// Stands in for the web view when launched with `--no-ui`
+    control_panel: Option<ControlPanel>,
     ctx: Context,
+    cue_list: CueList,
+    /// The current sketch's randomization exclusions as last loaded or saved
+    exclusions: Exclusions,
+    flipbook: Option<Flipbook>,
+    history_tracking: HistoryTracking,
+    /// The frame the control history was last sent to the UI on
+    history_sent_frame: u32,
     hrcc: bool,
     image_index: Option<storage::ImageIndex>,
     keys_held: HashSet<Key>,
+    letterbox: bool,
     mappings_enabled: bool,
     main_maximized: Cell<bool>,
     main_window_id: window::Id,
     map_mode: MapMode,
+    /// See [`midi_actions`]
+    midi_actions: MidiActionMappings,
     midi_out: Option<midi::MidiOut>,
+    network_sync: NetworkSync,
+    osc_query: Option<OscQueryServer>,
+    output_warps: OutputWarps,
     perf_mode: bool,
+    prewarm: bool,
+    /// A sketch built ahead of time by [`AppEvent::PrewarmSketch`]
+    prewarmed: Option<(String, Box<dyn SketchAll>)>,
+    /// Set once shutdown has asked to save the take and SysEx dump in
+    /// progress, so it only waits on them once
+    quit_saving: bool,
+    /// Set once shutdown has started; the app exits when encoding finishes
+    quitting: bool,
     recording_state: RecordingState,
     session_id: String,
+    set_list: SetList,
     sketch: Box<dyn SketchAll>,
     sketch_config: &'static SketchConfig,
+    /// Sketches declared under the live sketch's `inputs`
+    sketch_inputs: SketchInputs,
+    snapshot_phase: bool,
+    sysex_recorder: Option<SysExRecorder>,
     tap_tempo: TapTempo,
     tap_tempo_enabled: bool,
+    timing_overrides: TimingOverrides,
     transition_time: f32,
+    /// Set with `--position`
+    window_position_override: Option<(i32, i32)>,
+    /// Set with `--size`; replaces every sketch's own size
+    window_size_override: Option<(i32, i32)>,
+    /// Bypassed controls as last sent to the web view
+    wv_bypassed: wv::Bypassed,
+    /// Controls as last sent to the web view, keyed by name
+    wv_controls: HashMap<String, wv::Control>,
+    /// Set when any control may have changed; checked once per frame
+    wv_controls_dirty: bool,
+    /// Controls known to have changed when not all of them may have
+    wv_changed_controls: HashSet<String>,
     wv_pending_messages: VecDeque<wv::Event>,
-    wv_process: Child,
+    /// `None` when launched with `--no-ui`
+    wv_process: Option<wv::WebViewProcess>,
     wv_ready: bool,
     wv_tx: wv::EventSender,
 }
@@ -161,6 +342,157 @@ impl AppModel {
         })
     }
 
+    /// Sends recent values of tracked controls to the UI every few rendered
+    /// frames
+    fn send_control_history(&mut self) {
+        let frame = frame_controller::frame_count();
+        if self.history_tracking == HistoryTracking::Off
+            || frame == self.history_sent_frame
+            || frame % HISTORY_SEND_INTERVAL != 0
+        {
+            return;
+        }
+        self.history_sent_frame = frame;
+
+        if let Some(history) = self.hub().map(|hub| hub.history()) {
+            self.wv_tx.emit(wv::Event::ControlHistory(history));
+        }
+    }
+
+    /// Answers runtime bypass and solo changes with the updated graph so the
+    /// UI can reflect them, or alerts why the change was rejected
+    fn emit_debug_graph_result(&self, result: Result<DebugGraph, String>) {
+        match result {
+            Ok(graph) => self.wv_tx.emit(wv::Event::DebugGraph(Some(graph))),
+            Err(e) => self.app_tx.alert_and_log(e, log::Level::Warn),
+        }
+    }
+
+    /// Publishes the current sketch's OSC controls to OSCQuery clients
+    fn update_osc_query(&mut self) {
+        let controls = self
+            .hub()
+            .map_or_else(Default::default, |hub| hub.osc_controls.configs());
+
+        if let Some(server) = &self.osc_query {
+            server.set_namespace(self.sketch_config.display_name, &controls);
+        }
+    }
+
+    /// The main window size for the current [`AspectPreset`]
+    fn window_size(&self) -> (i32, i32) {
+        let (w, h) = self
+            .window_size_override
+            .unwrap_or((self.sketch_config.w, self.sketch_config.h));
+        self.ctx.aspect_preset().size(w, h)
+    }
+
+    /// The width/height ratio the sketch is designed for: the current
+    /// [`AspectPreset`] or the sketch's own dimensions
+    fn designed_aspect(&self) -> f32 {
+        self.ctx.aspect_preset().ratio().unwrap_or(
+            self.sketch_config.w as f32 / self.sketch_config.h as f32,
+        )
+    }
+
+    /// Syncs the context's [`WindowRect`] with the main window. When
+    /// letterboxing, sketches are handed the largest rect of the designed
+    /// aspect ratio that fits in the window instead of the window itself.
+    /// Returns true if the sketch's rect changed size
+    fn update_window_rect(&mut self, app: &App) -> bool {
+        let window_rect = self.main_window(app).unwrap().rect();
+        let aspect = self.letterbox.then(|| self.designed_aspect());
+        let content = aspect.map_or(window_rect, |aspect| {
+            window_rect::fit_aspect(window_rect, aspect)
+        });
+
+        let wr = &mut self.ctx.window_rect();
+        wr.set_window(window_rect);
+        let resized = content.w() != wr.w() || content.h() != wr.h();
+        if resized {
+            wr.set_current(content);
+        }
+
+        gpu::set_letterbox_aspect(aspect);
+
+        // Window changes always warrant a redraw in on-demand mode
+        frame_controller::invalidate();
+
+        resized
+    }
+
+    /// Like [`Self::update_window_rect`] but also lets the sketch know when
+    /// its rect changed size
+    fn resize_window_rect(&mut self, app: &App) {
+        if self.update_window_rect(app) {
+            self.run_lifecycle_hook(app, LifecycleHook::Resize);
+            self.sketch_inputs.on_resize(app, &self.ctx);
+        }
+    }
+
+    fn run_lifecycle_hook(&mut self, app: &App, hook: LifecycleHook) {
+        sketch::run_lifecycle_hook(self.sketch.as_mut(), hook, app, &self.ctx);
+    }
+
+    /// Records full control and bypass lists as what the web view last
+    /// received so later updates only need to send what changed
+    fn set_web_view_baseline(
+        &mut self,
+        controls: &[wv::Control],
+        bypassed: &wv::Bypassed,
+    ) {
+        self.wv_controls = controls
+            .iter()
+            .map(|control| (control.name.clone(), control.clone()))
+            .collect();
+        self.wv_bypassed = bypassed.clone();
+        self.wv_controls_dirty = false;
+        self.wv_changed_controls.clear();
+    }
+
+    /// Sends the controls and bypassed values that changed since they were
+    /// last sent. Called once per frame so any number of changes within a
+    /// frame cost at most two messages
+    fn flush_web_view_changes(&mut self) {
+        if !self.wv_ready
+            || (!self.wv_controls_dirty && self.wv_changed_controls.is_empty())
+        {
+            return;
+        }
+
+        let candidates = if std::mem::take(&mut self.wv_controls_dirty) {
+            self.wv_changed_controls.clear();
+            self.web_view_controls()
+        } else {
+            let names = std::mem::take(&mut self.wv_changed_controls);
+            self.web_view_controls_named(&names)
+        };
+
+        let changed: Vec<wv::Control> = candidates
+            .into_iter()
+            .filter(|control| {
+                self.wv_controls.get(&control.name) != Some(control)
+            })
+            .collect();
+
+        if !changed.is_empty() {
+            for control in &changed {
+                self.wv_controls
+                    .insert(control.name.clone(), control.clone());
+            }
+            self.wv_tx.emit(wv::Event::ControlsChanged(changed));
+        }
+
+        let bypassed = self
+            .hub()
+            .map_or_else(HashMap::default, |hub| hub.bypassed());
+
+        if bypassed != self.wv_bypassed {
+            self.wv_bypassed = bypassed.clone();
+            self.wv_tx.emit(wv::Event::BypassedChanged(bypassed));
+        }
+    }
+
     fn web_view_controls(&mut self) -> Vec<wv::Control> {
         self.hub().map_or_else(Vec::new, |hub| {
             hub.ui_controls
@@ -171,15 +503,36 @@ impl AppModel {
         })
     }
 
+    /// Like [`Self::web_view_controls`] but only builds `names`
+    fn web_view_controls_named(
+        &mut self,
+        names: &HashSet<String>,
+    ) -> Vec<wv::Control> {
+        self.hub().map_or_else(Vec::new, |hub| {
+            let configs = hub.ui_controls.config_refs();
+            names
+                .iter()
+                .filter_map(|name| configs.get(name))
+                .map(|config| wv::Control::from_config_and_hub((config, hub)))
+                .collect()
+        })
+    }
+
     fn on_app_event(&mut self, app: &App, event: AppEvent) {
         match event {
             AppEvent::AdvanceSingleFrame => {
                 frame_controller::advance_single_frame();
             }
             AppEvent::Alert(text) => {
+                if let Some(control_panel) = &mut self.control_panel {
+                    control_panel.set_alert(text.clone());
+                }
                 self.wv_tx.emit(wv::Event::Alert(text));
             }
             AppEvent::AlertAndLog(text, level) => {
+                if let Some(control_panel) = &mut self.control_panel {
+                    control_panel.set_alert(text.clone());
+                }
                 self.wv_tx.emit(wv::Event::Alert(text.clone()));
 
                 match level {
@@ -190,6 +543,39 @@ impl AppModel {
                     log::Level::Trace => trace!("{}", text),
                 }
             }
+            AppEvent::AspectPreset(preset) => {
+                self.ctx.set_aspect_preset(preset);
+                let (w, h) = self.window_size();
+                let window = self.main_window(app).unwrap();
+                set_window_size(window.winit_window(), w, h);
+                self.main_maximized.set(false);
+                self.resize_window_rect(app);
+                self.app_tx.alert_and_log(
+                    format!("Aspect: {} ({}x{})", preset, w, h),
+                    log::Level::Info,
+                );
+            }
+            AppEvent::CaptureFlipbook(frames, beats) => {
+                if self.flipbook.is_some() {
+                    self.app_tx.alert("Flipbook capture already in progress");
+                    return;
+                }
+                match Flipbook::new(frames, beats, self.ctx.bpm().get()) {
+                    Ok(flipbook) => {
+                        self.flipbook = Some(flipbook);
+                        self.app_tx.alert(format!(
+                            "Capturing {} frames {} beats apart",
+                            frames, beats
+                        ));
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to start flipbook: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
             AppEvent::CaptureFrame => {
                 let filename =
                     format!("{}-{}.png", self.sketch_name(), uuid_5());
@@ -197,25 +583,56 @@ impl AppModel {
                 let file_path =
                     &PathBuf::from(global::images_dir()).join(&filename);
 
-                self.main_window(app)
-                    .unwrap()
-                    .capture_frame(file_path.clone());
+                self.ctx.frame_capture().request(file_path.clone());
 
-                if let Some(image_index) = &mut self.image_index {
-                    image_index.items.push(storage::ImageIndexItem {
-                        filename,
-                        created_at: Utc::now().to_rfc3339().to_string(),
-                    });
-                    if let Err(e) = storage::save_image_index(image_index) {
-                        error!("{}", e);
-                    }
-                }
+                self.index_image(filename);
 
                 self.app_tx.alert_and_log(
                     format!("Image saved to {:?}", file_path),
                     log::Level::Info,
                 );
             }
+            AppEvent::AudioDeviceChanged => {
+                let status =
+                    self.hub().map_or(AudioDeviceStatus::Inactive, |hub| {
+                        hub.audio_controls.device_status()
+                    });
+                let event = wv::Event::AudioDevice {
+                    status,
+                    devices: list_audio_devices().unwrap_or_default(),
+                };
+                if self.wv_ready {
+                    self.wv_tx.emit(event);
+                } else {
+                    self.wv_pending_messages.push_back(event);
+                }
+            }
+            AppEvent::AutoBpmEnabled(enabled) => {
+                self.auto_bpm_locked = false;
+                if !enabled {
+                    self.auto_bpm.stop();
+                    self.ctx.bpm().set(self.sketch_bpm());
+                    self.wv_tx.emit(wv::Event::Bpm(self.ctx.bpm().get()));
+                    self.app_tx.alert_and_log(
+                        "Sketch BPM has been restored",
+                        log::Level::Info,
+                    );
+                    return;
+                }
+                match self.auto_bpm.start() {
+                    Ok(_) => self.app_tx.alert_and_log(
+                        "Listening for beats on the audio device",
+                        log::Level::Info,
+                    ),
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Unable to start auto BPM: {}", e),
+                            log::Level::Error,
+                        );
+                        self.wv_tx.emit(wv::Event::AutoBpmEnabled(false));
+                    }
+                }
+            }
             AppEvent::ChangeAudioDevice(name) => {
                 global::set_audio_device_name(&name);
                 if let Some(hub) = self.hub_mut() {
@@ -226,8 +643,52 @@ impl AppModel {
                         })
                         .ok();
                 }
+                if self.auto_bpm.is_enabled() {
+                    self.auto_bpm_locked = false;
+                    if let Err(e) = self.auto_bpm.start() {
+                        error!("Error restarting auto BPM: {}", e);
+                        self.wv_tx.emit(wv::Event::AutoBpmEnabled(false));
+                    }
+                }
+                self.app_tx.emit(AppEvent::AudioDeviceChanged);
+                self.save_global_state();
+            }
+            AppEvent::ChangeBpm(bpm) => {
+                if bpm <= 0.0 {
+                    self.wv_tx.emit(wv::Event::Bpm(self.ctx.bpm().get()));
+                    return;
+                }
+                self.timing_overrides.bpm = Some(bpm);
+                self.ctx.bpm().set(bpm);
+                self.wv_tx.emit(wv::Event::Bpm(bpm));
+                self.app_tx.alert(format!(
+                    "BPM set to {}. Save to persist for this sketch",
+                    bpm
+                ));
+            }
+            AppEvent::ChangeDepthDevice(name) => {
+                // Sketches' DepthCameras reconnect on their next update
+                global::set_depth_device_name(&name);
                 self.save_global_state();
             }
+            AppEvent::ChangeFps(fps) => {
+                if self.recording_state.is_capturing() {
+                    self.wv_tx.emit(wv::Event::Fps(frame_controller::fps()));
+                    self.app_tx.alert("Unable to change FPS while recording");
+                    return;
+                }
+                if fps <= 0.0 {
+                    self.wv_tx.emit(wv::Event::Fps(frame_controller::fps()));
+                    return;
+                }
+                self.timing_overrides.fps = Some(fps);
+                frame_controller::set_fps(fps);
+                self.wv_tx.emit(wv::Event::Fps(frame_controller::fps()));
+                self.app_tx.alert(format!(
+                    "FPS set to {}. Save to persist for this sketch",
+                    fps
+                ));
+            }
             AppEvent::ChangeMidiClockPort(port) => {
                 global::set_midi_clock_port(&port);
                 AppModel::start_midi_clock_listener(self.app_tx.tx.clone());
@@ -235,6 +696,9 @@ impl AppModel {
             }
             AppEvent::ChangeMidiControlInputPort(port) => {
                 global::set_midi_control_in_port(&port);
+                self.start_set_list_listener();
+                self.start_cue_list_listener();
+                self.start_midi_action_listener();
                 if let Some(hub) = self.hub_mut() {
                     hub.midi_controls
                         .restart()
@@ -257,6 +721,18 @@ impl AppModel {
                 };
                 self.save_global_state();
             }
+            AppEvent::ChangeMsaaSamples(samples) => {
+                gpu::set_msaa_samples(samples);
+                frame_controller::invalidate();
+                self.app_tx.alert(format!(
+                    "MSAA set to {}x (applies to shader pipelines)",
+                    gpu::msaa_samples()
+                ));
+            }
+            AppEvent::ChangeLatencyOffsets(latency_offsets) => {
+                global::set_latency_offsets(latency_offsets);
+                self.save_global_state();
+            }
             AppEvent::ChangeOscPort(port) => {
                 global::set_osc_port(port);
                 if let Err(e) = SHARED_OSC_RECEIVER.restart() {
@@ -264,8 +740,46 @@ impl AppModel {
                 }
                 self.save_global_state()
             }
+            AppEvent::ChangeRecordingSettings(settings) => {
+                self.recording_state.settings = settings;
+                self.save_global_state();
+            }
+            AppEvent::ChangeSliderRange(name, range) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                let result = match range {
+                    Some(range) => hub.set_slider_range(&name, range),
+                    None => hub.reset_slider_range(&name),
+                };
+                match result {
+                    Ok(_) => {
+                        self.wv_changed_controls.insert(name);
+                        self.update_osc_query();
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(e, log::Level::Error);
+                    }
+                }
+            }
+            AppEvent::ChangeStereo(settings) => {
+                stereo::set_stereo(settings);
+                self.save_global_state();
+            }
+            AppEvent::ChangeSyncRole(role) => {
+                if let Err(e) = self.network_sync.set_role(role, &self.app_tx) {
+                    self.app_tx.alert_and_log(
+                        format!("Unable to start network sync: {}", e),
+                        log::Level::Error,
+                    );
+                }
+                self.wv_tx
+                    .emit(wv::Event::SyncRole(self.network_sync.role()));
+                self.save_global_state();
+            }
             AppEvent::ClearNextFrame => {
                 self.clear_next_frame.set(true);
+                frame_controller::invalidate();
             }
             AppEvent::CommitMappings => {
                 if self.hub().is_none() {
@@ -355,6 +869,45 @@ impl AppModel {
                     .inspect_err(|e| error!("Error in CurrentlyMapping: {}", e))
                     .ok();
             }
+            AppEvent::CueGoTo(position) => {
+                self.reload_cue_list();
+                let cue = self.cue_list.go_to(position).cloned();
+                self.fire_cue(app, cue);
+            }
+            AppEvent::CueGoToName(name) => {
+                self.reload_cue_list();
+                let Some(position) = self.cue_list.find(&name) else {
+                    self.app_tx.alert(format!("No cue named {}", name));
+                    return;
+                };
+                let cue = self.cue_list.go_to(position).cloned();
+                self.fire_cue(app, cue);
+            }
+            AppEvent::CueNext => {
+                self.reload_cue_list();
+                let cue = self.cue_list.next_cue().cloned();
+                self.fire_cue(app, cue);
+            }
+            AppEvent::CuePrevious => {
+                self.reload_cue_list();
+                let cue = self.cue_list.previous_cue().cloned();
+                self.fire_cue(app, cue);
+            }
+            AppEvent::CycleAspectPreset => {
+                let preset = self.ctx.aspect_preset().next();
+                self.app_tx.emit(AppEvent::AspectPreset(preset));
+            }
+            AppEvent::CycleCalibration => {
+                let mode = self.ctx.calibration_mode().next();
+                self.ctx.calibration().set_mode(mode);
+                frame_controller::advance_single_frame();
+                frame_controller::invalidate();
+                self.app_tx.alert(match mode {
+                    CalibrationMode::Off => "Calibration pattern disabled",
+                    CalibrationMode::Overlay => "Calibration overlay enabled",
+                    CalibrationMode::TestPattern => "Test pattern enabled",
+                });
+            }
             AppEvent::Hrcc(hrcc) => {
                 self.hrcc = hrcc;
                 if let Some(hub) = self.hub_mut() {
@@ -375,16 +928,101 @@ impl AppModel {
                     log::Level::Info,
                 );
             }
+            AppEvent::FlipbookCaptured => {
+                let Some(flipbook) = self.flipbook.take() else {
+                    return;
+                };
+
+                let window = self.main_window(app).unwrap();
+                if let Err(e) =
+                    self.ctx.frame_capture().await_saves(window.device())
+                {
+                    error!("Error waiting for flipbook frames: {:?}", e);
+                }
+
+                let filename =
+                    format!("{}-flipbook-{}.png", self.sketch_name(), uuid_5());
+                let file_path =
+                    PathBuf::from(global::images_dir()).join(&filename);
+                self.index_image(filename);
+
+                // Decoding and compositing full size frames takes a moment
+                let app_tx = self.app_tx.clone();
+                thread::spawn(move || match flipbook.save(&file_path) {
+                    Ok(_) => app_tx.alert_and_log(
+                        format!("Flipbook saved to {:?}", file_path),
+                        log::Level::Info,
+                    ),
+                    Err(e) => app_tx.alert_and_log(
+                        format!("Failed to save flipbook: {}", e),
+                        log::Level::Error,
+                    ),
+                });
+            }
+            AppEvent::Frozen(frozen) => {
+                frame_controller::set_frozen(frozen);
+            }
+            AppEvent::Hold(name, held) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                let name = hub.canonical_name(&name).to_string();
+                match hub.set_hold(&name, held).map(|_| hub.held()) {
+                    Ok(held) => {
+                        self.wv_tx.emit(wv::Event::Held(held));
+                        self.wv_changed_controls.insert(name);
+                    }
+                    Err(e) => self.app_tx.alert_and_log(e, log::Level::Warn),
+                }
+            }
             AppEvent::HubPopulated => {
+                self.update_osc_query();
                 let controls = self.web_view_controls();
                 let bypassed =
                     self.hub().map_or_else(HashMap::default, |h| h.bypassed());
+                self.set_web_view_baseline(&controls, &bypassed);
                 let event = wv::Event::HubPopulated((controls, bypassed));
                 self.wv_tx.emit(event);
                 self.app_tx.alert("Hub repopulated");
             }
             AppEvent::EncodingComplete => {
-                self.wv_tx.emit(wv::Event::Encoding(false));
+                if !self.recording_state.is_encoding() {
+                    self.wv_tx.emit(wv::Event::Encoding(false));
+                    self.wv_tx.emit(wv::Event::EncodingProgress(None));
+                    if self.quitting {
+                        self.exit();
+                    }
+                }
+            }
+            AppEvent::EncodingProgress(progress) => {
+                self.wv_tx.emit(wv::Event::EncodingProgress(progress));
+            }
+            AppEvent::Instrumentation(enabled) => {
+                if !cfg!(feature = "instrumentation") {
+                    self.app_tx.alert(
+                        "Instrumentation needs the `instrumentation` feature",
+                    );
+                    return;
+                }
+                instrumentation::set_enabled(enabled);
+                self.app_tx.alert(format!(
+                    "Instrumentation {}",
+                    if enabled { "enabled" } else { "disabled" }
+                ));
+            }
+            AppEvent::LearnMidiAction(action) => {
+                self.map_mode.stop();
+
+                let Some(action) = action else {
+                    return;
+                };
+
+                let app_tx = self.app_tx.clone();
+                midi_actions::learn(move |ch_cc| {
+                    app_tx.emit(AppEvent::MidiActionLearned(action, ch_cc));
+                })
+                .inspect_err(|e| error!("Error in LearnMidiAction: {}", e))
+                .ok();
             }
             AppEvent::MappingsEnabled(enabled) => {
                 self.mappings_enabled = enabled;
@@ -393,26 +1031,33 @@ impl AppModel {
                 }
                 self.save_global_state();
             }
+            AppEvent::MidiActionLearned(action, ch_cc) => {
+                midi::disconnect(midi::ConnectionType::Mapping);
+                // One controller per action, last one wins
+                self.midi_actions.retain(|_, mapped| *mapped != ch_cc);
+                self.midi_actions.insert(action, ch_cc);
+                self.midi_actions_changed();
+                self.app_tx.alert_and_log(
+                    format!(
+                        "Mapped {:?} to channel {}, CC {}",
+                        action, ch_cc.0, ch_cc.1
+                    ),
+                    log::Level::Info,
+                );
+            }
             AppEvent::MidiStart | AppEvent::MidiContinue => {
                 info!("Received MIDI Start/Continue. Resetting frame count.");
 
                 frame_controller::reset_frame_count();
 
                 if self.recording_state.is_queued {
-                    match self.recording_state.start_recording() {
-                        Ok(message) => {
-                            self.app_tx.alert(message);
-                            self.wv_tx.emit(wv::Event::StartRecording);
-                        }
-                        Err(e) => {
-                            self.app_tx.alert_and_log(
-                                format!("Failed to start recording: {}", e),
-                                log::Level::Error,
-                            );
-                        }
-                    }
+                    self.begin_recording(app);
                 }
             }
+            AppEvent::NudgeBpm(delta) => {
+                let bpm = (self.ctx.bpm().get() + delta).max(1.0);
+                self.app_tx.emit(AppEvent::ChangeBpm(bpm));
+            }
             AppEvent::OpenOsDir(os_dir) => {
                 let result = match os_dir {
                     wv::OsDir::Cache => {
@@ -433,32 +1078,82 @@ impl AppModel {
             AppEvent::MidiStop => {
                 self.app_tx.emit(AppEvent::StopRecording);
             }
+            AppEvent::OutputWarps(output_warps) => {
+                self.output_warps = output_warps;
+                warp::set_output_warp(self.output_warps.active());
+                if let Err(e) = storage::save_output_warps(&self.output_warps) {
+                    error!("Error saving output warps: {}", e);
+                }
+            }
             AppEvent::Paused(paused) => {
                 frame_controller::set_paused(paused);
             }
             AppEvent::PerfMode(perf_mode) => {
                 self.perf_mode = perf_mode;
             }
+            AppEvent::Prewarm(prewarm) => {
+                self.prewarm = prewarm;
+                if !prewarm {
+                    self.prewarmed = None;
+                }
+                self.save_global_state();
+            }
+            AppEvent::PrewarmSketch(name) => {
+                self.prewarm_sketch(app, &name);
+            }
+            AppEvent::Profile(name) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                let result = hub.set_profile(&name);
+                let profile = hub.profile().to_string();
+
+                match result {
+                    Ok(()) => {
+                        self.app_tx.alert_and_log(
+                            format!("Switched to the `{}` profile", profile),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to switch profile: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+
+                self.wv_tx.emit(wv::Event::Profile(profile));
+            }
             AppEvent::QueueRecord => {
                 self.recording_state.is_queued =
                     !self.recording_state.is_queued;
 
                 if self.recording_state.is_queued {
+                    let bpm = self.ctx.bpm().get();
+                    self.recording_state.start_pre_roll(bpm);
                     self.app_tx.alert_and_log(
-                        "Recording queued. Awaiting MIDI start message.",
+                        ternary!(
+                            self.recording_state.is_capturing(),
+                            "Recording queued with pre-roll. \
+                            Awaiting MIDI start message.",
+                            "Recording queued. Awaiting MIDI start message."
+                        ),
                         log::Level::Info,
                     );
+                } else {
+                    self.recording_state.cancel_pre_roll();
                 }
             }
             AppEvent::Quit => {
                 debug!("AppEvent::Quit requested");
-                match self.wv_process.kill() {
-                    Ok(_) => debug!("Killed ui_process"),
-                    Err(e) => error!("Error killing ui_process {}", e),
+                let takes = self.recording_state.unfinished_takes();
+                if takes > 0 && !self.quitting && self.wv_tx.is_connected() {
+                    self.wv_tx.emit(wv::Event::ConfirmQuit(takes));
+                } else {
+                    self.app_tx
+                        .emit(AppEvent::Shutdown(PendingEncodes::Finish));
                 }
-                thread::sleep(Duration::from_millis(50));
-                debug!("Exiting main process");
-                std::process::exit(0);
             }
             AppEvent::Randomize(exclusions) => {
                 let app_tx = self.app_tx.clone();
@@ -479,6 +1174,9 @@ impl AppModel {
                     wv::UserDir::Images => global::set_images_dir(&dir),
                     wv::UserDir::UserData => {
                         global::set_user_data_dir(&dir);
+                        self.load_set_list();
+                        self.load_output_warps();
+                        self.reload_cue_list();
                         if let Some(image_index) = &self.image_index {
                             if !storage::image_metadata_exists()
                                 && !image_index.items.is_empty()
@@ -501,58 +1199,331 @@ impl AppModel {
             AppEvent::ReceiveMappings(mappings) => {
                 self.map_mode.set_mappings(mappings);
             }
-            AppEvent::RemoveMapping(name) => {
-                self.map_mode.remove(&name);
-                self.map_mode.currently_mapping = None;
-                self.hub_mut()
-                    .unwrap()
-                    .midi_controls
-                    .remove(&MapMode::proxy_name(&name));
-                self.app_tx.emit(AppEvent::SendMappings);
-            }
-            AppEvent::Reset => {
-                frame_controller::reset_frame_count();
-                self.app_tx.alert("Reset");
-            }
-            AppEvent::Resize => {
-                let window = self.main_window(app).unwrap();
-                let rect = window.rect();
-                let wr = &mut self.ctx.window_rect();
-
-                if rect.w() != wr.w() || rect.h() != wr.h() {
-                    wr.set_current(rect);
-                }
-            }
-            AppEvent::Save(exclusions) => {
-                let mappings = self.map_mode.mappings();
-
-                match storage::save_sketch_state(
-                    self.sketch_name().as_str(),
-                    self.hub().unwrap(),
-                    mappings,
-                    exclusions,
-                ) {
-                    Ok(path_buf) => {
+            AppEvent::RecordValues(Some(selection)) => {
+                let path = storage::value_recording_path(&self.sketch_name());
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                match hub.start_value_recording(&path, &selection) {
+                    Ok(_) => {
                         self.app_tx.alert_and_log(
-                            format!("Controls saved to {:?}", path_buf),
+                            format!("Recording control values to {:?}", path),
                             log::Level::Info,
                         );
+                        self.wv_tx.emit(wv::Event::RecordingValues(true));
                     }
                     Err(e) => {
                         self.app_tx.alert_and_log(
-                            format!("Failed to save controls: {}", e),
+                            format!("Unable to record control values: {}", e),
                             log::Level::Error,
                         );
                     }
                 }
             }
-            AppEvent::SendMappings => {
-                let mappings = self.map_mode.mappings();
-                self.wv_tx.emit(wv::Event::Mappings(mappings));
+            AppEvent::RecordValues(None) => {
+                self.stop_value_recording();
             }
-            AppEvent::SendMidi => {
-                let hrcc = self.hrcc;
-
+            AppEvent::RecordingMarker(name) => {
+                match self.recording_state.add_marker(name) {
+                    Ok(marker) => {
+                        let message = format!(
+                            "Added marker \"{}\" at frame {}",
+                            marker.name, marker.frame
+                        );
+                        self.app_tx.alert_and_log(message, log::Level::Info);
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Unable to add marker: {}", e),
+                            log::Level::Warn,
+                        );
+                    }
+                }
+            }
+            AppEvent::RemoveMapping(name) => {
+                self.map_mode.remove(&name);
+                self.map_mode.currently_mapping = None;
+                self.hub_mut()
+                    .unwrap()
+                    .midi_controls
+                    .remove(&MapMode::proxy_name(&name));
+                self.app_tx.emit(AppEvent::SendMappings);
+            }
+            AppEvent::RemoveMidiAction(action) => {
+                self.midi_actions.remove(&action);
+                self.midi_actions_changed();
+            }
+            AppEvent::ReorderFavorites(names) => {
+                REGISTRY.write().unwrap().set_favorites(names);
+                self.favorites_changed();
+            }
+            AppEvent::Replay(Some(path)) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                match hub.start_replay(&path) {
+                    Ok(_) => {
+                        self.app_tx.alert_and_log(
+                            format!("Replaying control values from {:?}", path),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Unable to replay control values: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
+            AppEvent::Replay(None) => {
+                if let Some(hub) = self.hub_mut() {
+                    if hub.is_replaying() {
+                        hub.stop_replay();
+                        self.app_tx.alert("Stopped replaying control values");
+                    }
+                }
+            }
+            AppEvent::RequestDebugGraph => {
+                let graph = self.hub().map(|hub| hub.debug_graph());
+                self.wv_tx.emit(wv::Event::DebugGraph(graph));
+            }
+            AppEvent::RequestGpuStats => {
+                let usage = REGISTRY.read().unwrap().gpu_usage();
+                self.wv_tx.emit(wv::Event::GpuStats(usage));
+            }
+            AppEvent::RequestPreview(name, resolution, beats) => {
+                let values = self.hub().map_or_else(Vec::new, |hub| {
+                    hub.preview(&name, resolution, beats)
+                });
+                self.wv_tx.emit(wv::Event::Preview { name, values });
+            }
+            AppEvent::RuntimeBypass(name, bypassed) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                let result = hub
+                    .set_runtime_bypass(&name, bypassed)
+                    .map(|_| hub.debug_graph());
+                self.emit_debug_graph_result(result);
+                self.wv_controls_dirty = true;
+            }
+            AppEvent::Reset => {
+                frame_controller::reset_frame_count();
+                self.app_tx.alert("Reset");
+            }
+            AppEvent::ResetTimingOverrides => {
+                if self.recording_state.is_capturing()
+                    && self.timing_overrides.fps.is_some()
+                {
+                    self.app_tx.alert("Unable to change FPS while recording");
+                    return;
+                }
+                self.timing_overrides = TimingOverrides::default();
+                self.apply_timing_overrides();
+                self.wv_tx.emit(wv::Event::Fps(frame_controller::fps()));
+                self.wv_tx.emit(wv::Event::Bpm(self.ctx.bpm().get()));
+                self.app_tx.alert(
+                    "Sketch FPS and BPM have been restored. \
+                    Save to persist for this sketch",
+                );
+            }
+            AppEvent::Resize => {
+                self.resize_window_rect(app);
+            }
+            AppEvent::Save(exclusions) => {
+                self.exclusions = exclusions.clone();
+                let mappings = self.map_mode.mappings();
+
+                match storage::save_sketch_state(
+                    self.sketch_name().as_str(),
+                    self.hub().unwrap(),
+                    mappings,
+                    exclusions,
+                    self.timing_overrides,
+                ) {
+                    Ok(path_buf) => {
+                        self.app_tx.alert_and_log(
+                            format!("Controls saved to {:?}", path_buf),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to save controls: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
+            AppEvent::SendMappings => {
+                let mappings = self.map_mode.mappings();
+                self.wv_tx.emit(wv::Event::Mappings(mappings));
+            }
+            AppEvent::SetList(set_list) => {
+                self.set_list.update(set_list);
+                if let Err(e) = storage::save_set_list(&self.set_list) {
+                    self.app_tx.alert_and_log(
+                        format!("Failed to save set list: {}", e),
+                        log::Level::Error,
+                    );
+                }
+                self.start_set_list_listener();
+                self.wv_tx.emit(wv::Event::SetList(self.set_list.clone()));
+            }
+            AppEvent::SetListGoTo(position) => {
+                let entry = self.set_list.go_to(position).cloned();
+                self.go_to_set_list_entry(app, entry);
+            }
+            AppEvent::SetListNext => {
+                let entry = self.set_list.next_entry().cloned();
+                self.go_to_set_list_entry(app, entry);
+            }
+            AppEvent::SetListPrevious => {
+                let entry = self.set_list.previous_entry().cloned();
+                self.go_to_set_list_entry(app, entry);
+            }
+            AppEvent::Shutdown(pending) => {
+                let unsaved = self.recording_state.is_recording
+                    || self.sysex_recorder.is_some();
+
+                // Queue the take and save the dump first so they are handled
+                // like everything else in flight
+                if unsaved && !self.quit_saving {
+                    self.quit_saving = true;
+                    if self.recording_state.is_recording {
+                        self.app_tx.emit(AppEvent::StopRecording);
+                    }
+                    if self.sysex_recorder.is_some() {
+                        self.app_tx.emit(AppEvent::SysExBackup(false));
+                    }
+                    self.app_tx.emit(AppEvent::Shutdown(pending));
+                    return;
+                }
+
+                // Saving failed; don't wait on a take that will never finish
+                if unsaved {
+                    error!("Unable to save the recording in progress");
+                    self.recording_state.is_recording = false;
+                    self.sysex_recorder = None;
+                }
+                self.shutdown(pending);
+            }
+            AppEvent::SaveTrace => {
+                let saved =
+                    storage::save_trace(&self.sketch_name(), &spans::folded());
+
+                match saved {
+                    Ok(path) => {
+                        spans::reset();
+                        self.app_tx.alert_and_log(
+                            format!("Trace saved to {:?}", path),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to save trace: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
+            AppEvent::SysExBackup(true) => {
+                let Some(port) = global::midi_control_in_port() else {
+                    self.app_tx.alert("No MIDI input port to record from");
+                    return;
+                };
+
+                match SysExRecorder::start(&port) {
+                    Ok(recorder) => {
+                        self.sysex_recorder = Some(recorder);
+                        self.app_tx.alert(format!(
+                            "Recording SysEx from {}. \
+                            Send a dump from the controller then stop.",
+                            port
+                        ));
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to record SysEx: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
+            AppEvent::SysExBackup(false) => {
+                let Some(recorder) = self.sysex_recorder.take() else {
+                    return;
+                };
+
+                let dump = recorder.stop();
+                if dump.is_empty() {
+                    self.app_tx.alert("No SysEx received; nothing saved");
+                    return;
+                }
+
+                match storage::save_sysex_dump(&self.sketch_name(), &dump) {
+                    Ok(path) => {
+                        self.app_tx.alert_and_log(
+                            format!(
+                                "Saved {} SysEx message(s) to {:?}",
+                                dump.len(),
+                                path
+                            ),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to save SysEx: {}", e),
+                            log::Level::Error,
+                        );
+                    }
+                }
+            }
+            AppEvent::SysExRestore => {
+                let Some(port) = global::midi_control_out_port() else {
+                    self.app_tx.alert("No MIDI output port to restore to");
+                    return;
+                };
+
+                let dump = match storage::load_sysex_dump(&self.sketch_name()) {
+                    Ok(dump) => dump,
+                    Err(e) => {
+                        self.app_tx.alert_and_log(
+                            format!("Failed to load SysEx: {}", e),
+                            log::Level::Error,
+                        );
+                        return;
+                    }
+                };
+
+                // Sending is paced so it happens off the main thread
+                let app_tx = self.app_tx.clone();
+                thread::spawn(move || match dump.send(&port) {
+                    Ok(_) => app_tx.alert_and_log(
+                        format!(
+                            "Sent {} SysEx message(s) to {}",
+                            dump.len(),
+                            port
+                        ),
+                        log::Level::Info,
+                    ),
+                    Err(e) => app_tx.alert_and_log(
+                        format!("Failed to send SysEx: {}", e),
+                        log::Level::Error,
+                    ),
+                });
+            }
+            AppEvent::ScriptErrorChanged => {
+                let script_error =
+                    self.hub().and_then(|hub| hub.script_error().cloned());
+                self.wv_tx.emit(wv::Event::ScriptError(script_error));
+            }
+            AppEvent::SendMidi => {
+                let hrcc = self.hrcc;
+
                 let messages = self
                     .hub()
                     .map(|hub| {
@@ -597,14 +1568,33 @@ impl AppModel {
                 }
             }
             AppEvent::SnapshotEnded => {
-                let controls = self.web_view_controls();
-                self.wv_tx.emit(wv::Event::SnapshotEnded(controls));
+                self.wv_controls_dirty = true;
+                self.wv_tx.emit(wv::Event::SnapshotEnded);
                 self.app_tx.alert_and_log(
                     "Snapshot/Transition ended",
                     log::Level::Info,
                 );
                 self.app_tx.emit(AppEvent::SendMidi);
             }
+            AppEvent::SnapshotCopyValues(from, to, names) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                match hub.copy_snapshot_values(&from, &to, &names) {
+                    Ok(count) => {
+                        self.app_tx.alert_and_log(
+                            format!(
+                                "Copied {} values from snapshot {:?} to {:?}",
+                                count, from, to
+                            ),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(e, log::Level::Error);
+                    }
+                }
+            }
             AppEvent::SnapshotDelete(id) => {
                 if let Some(hub) = self.hub_mut() {
                     hub.delete_snapshot(&id);
@@ -614,7 +1604,30 @@ impl AppModel {
                     );
                 }
             }
+            AppEvent::SnapshotDiff(a, b) => {
+                let Some(hub) = self.hub() else {
+                    return;
+                };
+                match hub.diff_snapshots(&a, &b) {
+                    Ok(deltas) => {
+                        let deltas = deltas
+                            .iter()
+                            .map(wv::SnapshotDiffEntry::from)
+                            .collect();
+                        self.wv_tx.emit(wv::Event::SnapshotDiff {
+                            a,
+                            b,
+                            deltas,
+                        });
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(e, log::Level::Error);
+                    }
+                }
+            }
             AppEvent::SnapshotRecall(id) => {
+                self.network_sync
+                    .broadcast(&SyncMessage::SnapshotRecall(id.clone()));
                 if let Some(hub) = self.hub_mut() {
                     match hub.recall_snapshot(&id) {
                         Ok(_) => {
@@ -629,6 +1642,46 @@ impl AppModel {
                     }
                 }
             }
+            AppEvent::SnapshotRecallOnly(id, selection) => {
+                self.network_sync
+                    .broadcast(&SyncMessage::SnapshotRecallOnly(
+                        id.clone(),
+                        selection.clone(),
+                    ));
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                match hub.recall_snapshot_only(&id, &selection) {
+                    Ok(_) => {
+                        self.app_tx.alert_and_log(
+                            format!(
+                                "Snapshot {:?} recalled for {}",
+                                id,
+                                selection.join(", ")
+                            ),
+                            log::Level::Info,
+                        );
+                    }
+                    Err(e) => {
+                        self.app_tx.alert_and_log(e, log::Level::Error);
+                    }
+                }
+            }
+            AppEvent::SnapshotPhase(enabled) => {
+                self.snapshot_phase = enabled;
+                if let Some(hub) = self.hub_mut() {
+                    hub.set_snapshot_phase(enabled);
+                }
+                self.save_global_state();
+            }
+            AppEvent::Solo(name) => {
+                let Some(hub) = self.hub_mut() else {
+                    return;
+                };
+                let result =
+                    hub.set_solo(name.as_deref()).map(|_| hub.debug_graph());
+                self.emit_debug_graph_result(result);
+            }
             AppEvent::SnapshotStore(digit) => {
                 if let Some(hub) = self.hub_mut() {
                     hub.take_snapshot(&digit);
@@ -644,31 +1697,39 @@ impl AppModel {
                 }
             }
             AppEvent::StartRecording => {
-                match self.recording_state.start_recording() {
-                    Ok(message) => {
-                        self.app_tx.alert(message);
-                    }
-                    Err(e) => {
-                        self.app_tx.alert_and_log(
-                            format!("Failed to start recording: {}", e),
-                            log::Level::Error,
-                        );
-                    }
+                let bpm = self.ctx.bpm().get();
+                if self.recording_state.start_count_in(bpm) {
+                    info!("Counting in");
+                    return;
                 }
+                self.begin_recording(app);
             }
             AppEvent::StopRecording => {
-                let rs = &self.recording_state;
+                if self.recording_state.cancel_count_in() {
+                    self.wv_tx.emit(wv::Event::CountIn(None));
+                    self.app_tx.alert("Count-in cancelled");
+                    return;
+                }
 
-                if rs.is_recording && !rs.is_encoding {
+                if self.recording_state.is_recording {
                     match self
                         .recording_state
                         .stop_recording(self.sketch_config, &self.session_id)
                     {
-                        Ok(_) => {
+                        Ok(report) => {
                             self.wv_tx.emit(wv::Event::Encoding(true));
+                            let level = ternary!(
+                                self.recording_state.health.is_clean(),
+                                log::Level::Info,
+                                log::Level::Warn
+                            );
+                            self.app_tx.alert_and_log(report, level);
                         }
                         Err(e) => {
-                            error!("Failed to stop recording: {}", e);
+                            self.app_tx.alert_and_log(
+                                format!("Failed to stop recording: {}", e),
+                                log::Level::Error,
+                            );
                         }
                     }
                 }
@@ -676,6 +1737,41 @@ impl AppModel {
             AppEvent::SwitchSketch(name) => {
                 self.switch_sketch(app, &name);
             }
+            AppEvent::SyncReceived(message) => {
+                if self.network_sync.role() != SyncRole::Follower {
+                    return;
+                }
+                match message {
+                    SyncMessage::Frame {
+                        frame_count,
+                        bpm,
+                        sketch,
+                    } => {
+                        if sketch != self.sketch_name() {
+                            if REGISTRY.read().unwrap().get(&sketch).is_some() {
+                                self.switch_sketch(app, &sketch);
+                            } else {
+                                warn_once!(
+                                    "Leader is running unknown sketch `{}`",
+                                    sketch
+                                );
+                            }
+                        }
+                        if bpm != self.ctx.bpm().get() {
+                            self.ctx.bpm().set(bpm);
+                            self.wv_tx.emit(wv::Event::Bpm(bpm));
+                        }
+                        frame_controller::set_frame_count(frame_count);
+                    }
+                    SyncMessage::SnapshotRecall(id) => {
+                        self.app_tx.emit(AppEvent::SnapshotRecall(id));
+                    }
+                    SyncMessage::SnapshotRecallOnly(id, selection) => {
+                        self.app_tx
+                            .emit(AppEvent::SnapshotRecallOnly(id, selection));
+                    }
+                }
+            }
             AppEvent::Tap => {
                 if self.tap_tempo_enabled {
                     self.ctx.bpm().set(self.tap_tempo.tap());
@@ -684,7 +1780,7 @@ impl AppModel {
             }
             AppEvent::TapTempoEnabled(enabled) => {
                 self.tap_tempo_enabled = enabled;
-                self.ctx.bpm().set(self.sketch_config.bpm);
+                self.ctx.bpm().set(self.sketch_bpm());
                 self.wv_tx.emit(wv::Event::Bpm(self.ctx.bpm().get()));
                 self.app_tx.alert_and_log(
                     ternary!(
@@ -700,171 +1796,807 @@ impl AppModel {
                 if let Some(hub) = self.hub_mut() {
                     hub.set_transition_time(transition_time);
                 }
-                self.save_global_state();
+                self.wv_tx.emit(wv::Event::TransitionTime(transition_time));
+                self.send_midi_action_feedback();
+                self.save_global_state();
+            }
+            AppEvent::ToggleFavorite(name) => {
+                REGISTRY.write().unwrap().toggle_favorite(&name);
+                self.favorites_changed();
+            }
+            AppEvent::ToggleFullScreen => {
+                let window = self.main_window(app).unwrap();
+                if let Some(monitor) = window.current_monitor() {
+                    let monitor_size = monitor.size();
+                    let is_maximized = self.main_maximized.get();
+
+                    if is_maximized {
+                        let (w, h) = self.window_size();
+                        window.set_inner_size_points(w as f32, h as f32);
+                        self.main_maximized.set(false);
+                    } else {
+                        window.set_inner_size_pixels(
+                            monitor_size.width,
+                            monitor_size.height,
+                        );
+                        self.main_maximized.set(true);
+                    }
+                }
+            }
+            AppEvent::ToggleGuiFocus => {
+                if let Some(control_panel) = &mut self.control_panel {
+                    control_panel.toggle_visibility(app);
+                }
+                self.wv_tx.emit(wv::Event::ToggleGuiFocus);
+            }
+            AppEvent::ToggleLetterbox => {
+                self.letterbox = !self.letterbox;
+                self.resize_window_rect(app);
+                self.clear_next_frame.set(true);
+                frame_controller::advance_single_frame();
+                self.app_tx.alert(ternary!(
+                    self.letterbox,
+                    format!("Letterboxing to {:.3}:1", self.designed_aspect()),
+                    "Letterboxing disabled".to_string()
+                ));
+            }
+            AppEvent::ToggleMainFocus => {
+                let window = self.main_window(app).unwrap();
+                window.set_visible(true);
+                window.winit_window().focus_window();
+            }
+            AppEvent::ToggleSafeAreaGuides => {
+                let enabled = !self.ctx.safe_area_guides();
+                self.ctx.set_safe_area_guides(enabled);
+                frame_controller::advance_single_frame();
+                frame_controller::invalidate();
+                self.app_tx.alert(ternary!(
+                    enabled,
+                    "Safe-area guides enabled",
+                    "Safe-area guides disabled"
+                ));
+            }
+            AppEvent::TraceSubsystem(subsystem, enabled) => {
+                spans::set_enabled(subsystem, enabled);
+                self.app_tx.alert(format!(
+                    "Tracing {:?}: {}",
+                    subsystem,
+                    if enabled { "on" } else { "off" }
+                ));
+            }
+            AppEvent::TrackControlHistory(tracking) => {
+                self.history_tracking = tracking.clone();
+                if let Some(hub) = self.hub_mut() {
+                    hub.set_history_tracking(tracking);
+                }
+            }
+            AppEvent::TriggerEnvelope(name) => {
+                let Some(hub) = self.hub() else {
+                    return;
+                };
+                if !hub.trigger_envelope(&name, 1.0) {
+                    self.app_tx.alert_and_log(
+                        format!("No envelope named {:?}", name),
+                        log::Level::Warn,
+                    );
+                }
+            }
+            AppEvent::UpdateUiControl((name, value)) => {
+                let hub = self.hub_mut().unwrap();
+                hub.ui_controls.set(&name, value.clone());
+
+                match value {
+                    // The UI already shows the new value
+                    ControlValue::Float(value) => {
+                        if let Some(control) = self.wv_controls.get_mut(&name) {
+                            control.value = value.to_string();
+                        }
+                    }
+                    // Revaluate disabled state
+                    ControlValue::Bool(_) | ControlValue::String(_) => {
+                        self.wv_controls_dirty = true;
+                    }
+                }
+            }
+            AppEvent::WebViewDisconnected => {
+                warn!("Web view disconnected");
+                self.wv_ready = false;
+
+                // Queue a fresh copy of what the web view needs on startup so
+                // a respawned one picks up where the last left off
+                self.wv_pending_messages.clear();
+                let event =
+                    self.web_view_load_sketch(frame_controller::is_paused());
+                self.wv_pending_messages.push_back(event);
+                self.app_tx.emit(AppEvent::AudioDeviceChanged);
+            }
+            AppEvent::WebViewReady => {
+                self.wv_ready = true;
+                // Catch up on changes made since the queued LoadSketch
+                self.wv_controls_dirty = true;
+
+                // Not clearing the queue as this is great for live reload!
+                // TODO: find a better way since this can undo some state
+                for message in &self.wv_pending_messages {
+                    self.wv_tx.emit(message.clone());
+                }
+
+                let registry = REGISTRY.read().unwrap();
+
+                self.wv_tx.emit(wv::Event::Init {
+                    audio_device: global::audio_device_name()
+                        .unwrap_or_default(),
+                    audio_devices: list_audio_devices().unwrap_or_default(),
+                    depth_device: global::depth_device_name()
+                        .unwrap_or_default(),
+                    depth_devices: list_depth_devices().unwrap_or_default(),
+                    favorite_sketches: registry.favorites().to_vec(),
+                    hrcc: self.hrcc,
+                    images_dir: global::images_dir(),
+                    is_light_theme: matches!(
+                        dark_light::detect(),
+                        dark_light::Mode::Light
+                    ),
+                    latency_offsets: global::latency_offsets(),
+                    mappings_enabled: self.mappings_enabled,
+                    midi_clock_port: global::midi_clock_port()
+                        .unwrap_or_default(),
+                    midi_input_port: global::midi_control_in_port()
+                        .unwrap_or_default(),
+                    midi_output_port: global::midi_control_out_port()
+                        .unwrap_or_default(),
+                    midi_input_ports: midi::list_input_ports().unwrap(),
+                    midi_output_ports: midi::list_output_ports().unwrap(),
+                    midi_actions: self.midi_actions.clone(),
+                    osc_port: global::osc_port(),
+                    output_warps: self.output_warps.clone(),
+                    prewarm: self.prewarm,
+                    recent_sketches: registry.recently_used().to_vec(),
+                    recording_settings: self.recording_state.settings.clone(),
+                    set_list: self.set_list.clone(),
+                    sketch_names: registry.names().clone(),
+                    sketches: registry
+                        .configs()
+                        .map(wv::SketchMetadata::from)
+                        .collect(),
+                    sketch_name: self.sketch_name(),
+                    snapshot_phase: self.snapshot_phase,
+                    stereo: gpu::stereo_settings(),
+                    sync_role: self.network_sync.role(),
+                    transition_time: self.transition_time,
+                    user_data_dir: global::user_data_dir(),
+                    videos_dir: global::videos_dir(),
+                });
+            }
+        }
+    }
+
+    fn capture_flipbook_frame(&self) {
+        let Some(flipbook) = &self.flipbook else {
+            return;
+        };
+
+        if let Some(path) = flipbook.next_capture_path() {
+            self.ctx.frame_capture().request(path);
+            if flipbook.is_complete() {
+                self.app_tx.emit(AppEvent::FlipbookCaptured);
+            }
+        }
+    }
+
+    fn capture_recording_frame(&self) {
+        let frame_count = self.recording_state.recorded_frames.get();
+
+        let recording_dir = match &self.recording_state.recording_dir {
+            Some(path) => path,
+            None => {
+                error!(
+                    "Unable to access recording dir {:?}",
+                    &self.recording_state.recording_dir
+                );
+                return;
+            }
+        };
+
+        let filename = recording::frame_filename(frame_count);
+        self.ctx
+            .frame_capture()
+            .request(recording_dir.join(filename));
+
+        self.recording_state.recorded_frames.set(frame_count + 1);
+        self.recording_state.on_frame_captured(frame_count);
+        self.recording_state
+            .stamp_frame(frame_count, self.ctx.bpm().get());
+        self.recording_state
+            .health
+            .on_capture(frame_controller::frame_count());
+    }
+
+    fn begin_recording(&mut self, app: &App) {
+        match self.recording_state.start_recording() {
+            Ok(message) => {
+                self.app_tx.alert(message);
+                self.wv_tx.emit(wv::Event::StartRecording);
+                self.run_lifecycle_hook(app, LifecycleHook::RecordStart);
+            }
+            Err(e) => {
+                self.app_tx.alert_and_log(
+                    format!("Failed to start recording: {}", e),
+                    log::Level::Error,
+                );
+            }
+        }
+    }
+
+    fn on_count_in_tick(&mut self, app: &App, tick: CountInTick) {
+        match tick {
+            CountInTick::Beat(remaining) => {
+                self.wv_tx.emit(wv::Event::CountIn(Some(remaining)));
+                self.app_tx.alert(format!("Count-in: {}", remaining));
+                let accent =
+                    remaining == self.recording_state.settings.count_in;
+                self.send_count_in_click(accent);
+            }
+            CountInTick::Done => {
+                self.wv_tx.emit(wv::Event::CountIn(None));
+                self.begin_recording(app);
+            }
+        }
+    }
+
+    /// A side stick on the GM drum channel, louder on the first beat
+    fn send_count_in_click(&mut self, accent: bool) {
+        let Some(midi_out) = &mut self.midi_out else {
+            return;
+        };
+        let velocity = ternary!(accent, 127, 90);
+        let result = midi_out
+            .send(&[0x99, 37, velocity])
+            .and_then(|_| midi_out.send(&[0x89, 37, 0]));
+        if let Err(e) = result {
+            warn!("Unable to send count-in click: {}", e);
+        }
+    }
+
+    /// Saves the live sketch's control value recording, if any
+    fn stop_value_recording(&mut self) {
+        let Some(hub) = self.hub_mut() else {
+            return;
+        };
+        if !hub.is_recording_values() {
+            return;
+        }
+        match hub.stop_value_recording() {
+            Ok((path, rows)) => {
+                self.app_tx.alert_and_log(
+                    format!(
+                        "Saved {} frames of control values to {:?}",
+                        rows, path
+                    ),
+                    log::Level::Info,
+                );
+            }
+            Err(e) => {
+                self.app_tx.alert_and_log(
+                    format!("Failed to save control values: {}", e),
+                    log::Level::Error,
+                );
+            }
+        }
+        self.wv_tx.emit(wv::Event::RecordingValues(false));
+    }
+
+    fn switch_sketch(&mut self, app: &App, name: &str) {
+        self.stop_value_recording();
+        let registry = REGISTRY.read().unwrap();
+
+        let sketch_info = registry.get(name).unwrap_or_else(|| {
+            error!("No sketch named `{}`. Defaulting to `template`", name);
+            registry.get("template").unwrap()
+        });
+
+        frame_controller::set_fps(sketch_info.config.fps);
+        gpu::set_msaa_samples(sketch_info.config.msaa_samples);
+        self.sketch_config = sketch_info.config;
+        self.session_id = recording::generate_session_id();
+        self.clear_next_frame.set(true);
+
+        self.run_lifecycle_hook(app, LifecycleHook::Unload);
+        self.sketch_inputs.clear(&self.ctx);
+        self.ctx.reset_rng();
+        let prewarmed = self
+            .prewarmed
+            .take()
+            .filter(|(prewarmed, _)| prewarmed == sketch_info.config.name);
+        let was_prewarmed = prewarmed.is_some();
+        self.sketch = match prewarmed {
+            Some((_, sketch)) => sketch,
+            None => sketch_info.init(app, &self.ctx),
+        };
+        gpu_stats::set_owner(sketch_info.config.name);
+
+        let mappings_enabled = self.mappings_enabled;
+        if let Some(hub) = self.hub_mut() {
+            hub.midi_proxies_enabled = mappings_enabled;
+            hub.clear_snapshots();
+            if was_prewarmed {
+                hub.connect_inputs();
+            }
+        }
+
+        let display_name = sketch_info.config.display_name;
+        drop(registry);
+
+        self.init_sketch_environment(app);
+        self.save_global_state();
+
+        self.app_tx.alert(format!("Switched to {}", display_name));
+    }
+
+    /// Switches to the entry's sketch (unless already running) and recalls its
+    /// snapshot. `None` means the set list couldn't move any further
+    fn go_to_set_list_entry(&mut self, app: &App, entry: Option<SetListEntry>) {
+        let Some(entry) = entry else {
+            self.app_tx
+                .alert("No more set list entries in that direction");
+            return;
+        };
+
+        if entry.sketch != self.sketch_config.name {
+            self.switch_sketch(app, &entry.sketch);
+        }
+
+        if let Some(snapshot) = &entry.snapshot {
+            let beats = entry.transition_time.unwrap_or(self.transition_time);
+            self.recall_snapshot_over(snapshot, beats);
+        }
+
+        let position = self.set_list.position.map_or(0, |p| p + 1);
+        self.app_tx.alert(format!(
+            "Set list {}/{}: {}",
+            position,
+            self.set_list.entries.len(),
+            entry.sketch
+        ));
+        self.wv_tx.emit(wv::Event::SetList(self.set_list.clone()));
+    }
+
+    /// Re-reads the cue list so edits made since the last cue take effect,
+    /// keeping the current position
+    fn reload_cue_list(&mut self) {
+        let cue_list = match storage::load_cue_list() {
+            Ok(cue_list) => cue_list,
+            Err(e) => {
+                self.app_tx.alert_and_log(
+                    format!("Error loading cue list: {}", e),
+                    log::Level::Error,
+                );
+                return;
+            }
+        };
+
+        let bindings_changed = cue_list.next_note != self.cue_list.next_note
+            || cue_list.previous_note != self.cue_list.previous_note;
+
+        self.cue_list.update(cue_list);
+
+        if bindings_changed {
+            self.start_cue_list_listener();
+        }
+    }
+
+    /// Runs a cue's actions in order: switch sketch (unless already running),
+    /// recall its snapshot, then set its controls. `None` means the cue list
+    /// couldn't move any further
+    fn fire_cue(&mut self, app: &App, cue: Option<Cue>) {
+        let Some(cue) = cue else {
+            self.app_tx.alert("No more cues in that direction");
+            return;
+        };
+
+        if let Some(sketch) = &cue.sketch {
+            if *sketch != self.sketch_config.name {
+                self.switch_sketch(app, sketch);
+            }
+        }
+
+        if let Some(snapshot) = &cue.snapshot {
+            let beats = cue.transition_time.unwrap_or(self.transition_time);
+            self.recall_snapshot_over(snapshot, beats);
+        }
+
+        if let Some(hub) = self.hub_mut() {
+            let errors: Vec<String> = cue
+                .controls
+                .iter()
+                .filter_map(|(name, value)| hub.set(name, *value).err())
+                .collect();
+            for e in errors {
+                self.app_tx.alert_and_log(
+                    format!("Cue {}: {}", cue.label(), e),
+                    log::Level::Warn,
+                );
+            }
+        }
+
+        let position = self.cue_list.position.map_or(0, |p| p + 1);
+        self.app_tx.alert(format!(
+            "Cue {}/{}: {}",
+            position,
+            self.cue_list.cues.len(),
+            cue.label()
+        ));
+    }
+
+    /// Recalls a snapshot over `beats` rather than the global transition time
+    fn recall_snapshot_over(&mut self, id: &str, beats: f32) {
+        let transition_time = self.transition_time;
+        let Some(hub) = self.hub_mut() else {
+            return;
+        };
+        hub.set_transition_time(beats);
+        let result = hub.recall_snapshot(id);
+        hub.set_transition_time(transition_time);
+        if let Err(e) = result {
+            self.app_tx.alert_and_log(e, log::Level::Error);
+        }
+    }
+
+    /// Builds the control panel for this frame and forwards its edits
+    fn update_control_panel(&mut self, since_start: Duration) {
+        if self.control_panel.is_none() {
+            return;
+        }
+
+        let controls = self.web_view_controls();
+        let bpm = self.ctx.bpm().get();
+        let sketch_name = self.sketch_config.display_name;
+
+        if let Some(control_panel) = &mut self.control_panel {
+            let events = control_panel.update(
+                since_start,
+                ControlPanelState {
+                    sketch_name,
+                    fps: frame_controller::average_fps(),
+                    bpm,
+                    controls: &controls,
+                },
+            );
+            for event in events {
+                self.app_tx.emit(event);
+            }
+        }
+    }
+
+    fn kill_web_view(&mut self) {
+        if let Some(wv_process) = &self.wv_process {
+            wv_process.kill();
+        }
+    }
+
+    /// Saves global settings and closes MIDI and OSC, then exits as soon as
+    /// nothing is left encoding. Sketch controls are left alone since those
+    /// are only ever persisted by an explicit Save. Safe to call again, e.g.
+    /// to cancel encodes that a previous call was waiting on
+    fn shutdown(&mut self, pending: PendingEncodes) {
+        if !self.quitting {
+            self.quitting = true;
+            info!("Shutting down");
+            self.stop_value_recording();
+            self.save_global_state();
+            self.auto_bpm.stop();
+            midi::disconnect_all();
+            if let Err(e) = SHARED_OSC_RECEIVER.stop() {
+                error!("Error stopping OSC receiver: {}", e);
+            }
+        }
+
+        if pending == PendingEncodes::Cancel {
+            self.recording_state.encode_queue.cancel();
+        }
+
+        let takes = self.recording_state.unfinished_takes();
+        if takes == 0 {
+            self.exit();
+        }
+
+        self.app_tx.alert_and_log(
+            format!(
+                "Quitting once {} take(s) finish encoding{}",
+                takes,
+                ternary!(
+                    pending == PendingEncodes::Cancel,
+                    " or are cancelled",
+                    ""
+                )
+            ),
+            log::Level::Info,
+        );
+    }
+
+    fn exit(&mut self) -> ! {
+        self.kill_web_view();
+        thread::sleep(Duration::from_millis(50));
+        debug!("Exiting main process");
+        std::process::exit(0);
+    }
+
+    /// SIGINT/SIGTERM, for runs without a window to quit from. The first
+    /// signal shuts down like Quit without asking, waiting on encodes; the
+    /// second cancels them; the third exits immediately
+    fn start_signal_listener(&self) {
+        let app_tx = self.app_tx.clone();
+        let signals = AtomicU32::new(0);
+
+        let result = ctrlc::set_handler(move || {
+            match signals.fetch_add(1, Ordering::SeqCst) {
+                0 => {
+                    info!("Received interrupt; shutting down");
+                    app_tx.emit(AppEvent::Shutdown(PendingEncodes::Finish));
+                }
+                1 => {
+                    warn!("Received second interrupt; cancelling encodes");
+                    app_tx.emit(AppEvent::Shutdown(PendingEncodes::Cancel));
+                }
+                _ => std::process::exit(130),
             }
-            AppEvent::ToggleFullScreen => {
-                let window = self.main_window(app).unwrap();
-                if let Some(monitor) = window.current_monitor() {
-                    let monitor_size = monitor.size();
-                    let is_maximized = self.main_maximized.get();
+        });
 
-                    if is_maximized {
-                        window.set_inner_size_points(
-                            self.sketch_config.w as f32,
-                            self.sketch_config.h as f32,
-                        );
-                        self.main_maximized.set(false);
-                    } else {
-                        window.set_inner_size_pixels(
-                            monitor_size.width,
-                            monitor_size.height,
-                        );
-                        self.main_maximized.set(true);
-                    }
+        if let Err(e) = result {
+            warn!("Unable to install signal handler: {}", e);
+        }
+    }
+
+    fn load_output_warps(&mut self) {
+        self.output_warps = storage::load_output_warps()
+            .inspect_err(|e| error!("Error loading output warps: {}", e))
+            .unwrap_or_default();
+        warp::set_output_warp(self.output_warps.active());
+        self.wv_tx
+            .emit(wv::Event::OutputWarps(self.output_warps.clone()));
+    }
+
+    fn load_set_list(&mut self) {
+        self.set_list = storage::load_set_list()
+            .inspect_err(|e| error!("Error loading set list: {}", e))
+            .unwrap_or_default();
+        self.start_set_list_listener();
+        self.wv_tx.emit(wv::Event::SetList(self.set_list.clone()));
+    }
+
+    /// Listens on the MIDI control input port for [`CueList::next_note`] and
+    /// [`CueList::previous_note`]
+    fn start_cue_list_listener(&self) {
+        let next = self.cue_list.next_note;
+        let previous = self.cue_list.previous_note;
+        let port = global::midi_control_in_port();
+
+        let Some(port) = port.filter(|_| next.is_some() || previous.is_some())
+        else {
+            midi::disconnect(midi::ConnectionType::CueList);
+            return;
+        };
+
+        let app_tx = self.app_tx.clone();
+
+        let result = midi::on_message(
+            midi::ConnectionType::CueList,
+            &port,
+            move |_stamp, message| {
+                // Note On with a velocity of 0 is a Note Off
+                if message.len() < 3
+                    || message[0] & 0xF0 != 0x90
+                    || message[2] == 0
+                {
+                    return;
+                }
+                let note = Some((message[0] & 0x0F, message[1]));
+                if note == next {
+                    app_tx.emit(AppEvent::CueNext);
+                } else if note == previous {
+                    app_tx.emit(AppEvent::CuePrevious);
                 }
+            },
+        );
+
+        if let Err(e) = result {
+            warn!("Failed to start cue list MIDI listener: {}", e);
+        }
+    }
+
+    /// Fires cues from `/cue/next`, `/cue/previous`, and `/cue/go` with a cue
+    /// number (starting at 1) or name. Registered once as the OSC receiver
+    /// keeps callbacks across restarts
+    fn start_cue_osc_listener(&self) {
+        let app_tx = self.app_tx.clone();
+        SHARED_OSC_RECEIVER.register_callback("/cue/next", move |_| {
+            app_tx.emit(AppEvent::CueNext);
+        });
+
+        let app_tx = self.app_tx.clone();
+        SHARED_OSC_RECEIVER.register_callback("/cue/previous", move |_| {
+            app_tx.emit(AppEvent::CuePrevious);
+        });
+
+        let app_tx = self.app_tx.clone();
+        SHARED_OSC_RECEIVER.register_callback("/cue/go", move |msg| match msg
+            .args
+            .first()
+        {
+            Some(osc::Type::Int(number)) if *number > 0 => {
+                app_tx.emit(AppEvent::CueGoTo(*number as usize - 1));
             }
-            AppEvent::ToggleGuiFocus => {
-                self.wv_tx.emit(wv::Event::ToggleGuiFocus);
+            Some(osc::Type::Float(number)) if *number >= 1.0 => {
+                app_tx.emit(AppEvent::CueGoTo(*number as usize - 1));
             }
-            AppEvent::ToggleMainFocus => {
-                let window = self.main_window(app).unwrap();
-                window.set_visible(true);
-                window.winit_window().focus_window();
+            Some(osc::Type::String(name)) => {
+                app_tx.emit(AppEvent::CueGoToName(name.clone()));
             }
-            AppEvent::UpdateUiControl((name, value)) => {
-                let hub = self.hub_mut().unwrap();
-                hub.ui_controls.set(&name, value.clone());
+            _ => warn!("/cue/go expects a cue number or name"),
+        });
+    }
 
-                // Revaluate disabled state
-                if matches!(
-                    value,
-                    ControlValue::Bool(_) | ControlValue::String(_)
-                ) {
-                    let controls = self.web_view_controls();
-                    self.wv_tx.emit(wv::Event::UpdatedControls(controls));
-                }
-            }
-            AppEvent::WebViewReady => {
-                self.wv_ready = true;
+    /// Listens on the MIDI control input port for [`SetList::next_cc`]
+    fn start_set_list_listener(&self) {
+        let (Some((channel, controller)), Some(port)) =
+            (self.set_list.next_cc, global::midi_control_in_port())
+        else {
+            midi::disconnect(midi::ConnectionType::SetList);
+            return;
+        };
 
-                // Not clearing the queue as this is great for live reload!
-                // TODO: find a better way since this can undo some state
-                for message in &self.wv_pending_messages {
-                    self.wv_tx.emit(message.clone());
+        let app_tx = self.app_tx.clone();
+        let was_high = AtomicBool::new(false);
+
+        let result = midi::on_message(
+            midi::ConnectionType::SetList,
+            &port,
+            move |_stamp, message| {
+                if message.len() < 3
+                    || message[0] & 0xF0 != 0xB0
+                    || message[0] & 0x0F != channel
+                    || message[1] != controller
+                {
+                    return;
+                }
+                let high = message[2] >= 64;
+                if high && !was_high.swap(high, Ordering::Relaxed) {
+                    app_tx.emit(AppEvent::SetListNext);
+                } else {
+                    was_high.store(high, Ordering::Relaxed);
                 }
+            },
+        );
 
-                let registry = REGISTRY.read().unwrap();
+        if let Err(e) = result {
+            warn!("Failed to start set list MIDI listener: {}", e);
+        }
+    }
 
-                self.wv_tx.emit(wv::Event::Init {
-                    audio_device: global::audio_device_name()
-                        .unwrap_or_default(),
-                    audio_devices: list_audio_devices().unwrap_or_default(),
-                    hrcc: self.hrcc,
-                    images_dir: global::images_dir(),
-                    is_light_theme: matches!(
-                        dark_light::detect(),
-                        dark_light::Mode::Light
+    /// Listens on the MIDI control input port for [`Self::midi_actions`]
+    fn start_midi_action_listener(&self) {
+        let app_tx = self.app_tx.clone();
+
+        let result =
+            midi_actions::listen(&self.midi_actions, move |action, value| {
+                app_tx.emit(match action {
+                    MidiAction::BpmDown => {
+                        AppEvent::NudgeBpm(-midi_actions::BPM_NUDGE)
+                    }
+                    MidiAction::BpmUp => {
+                        AppEvent::NudgeBpm(midi_actions::BPM_NUDGE)
+                    }
+                    MidiAction::Tap => AppEvent::Tap,
+                    MidiAction::TransitionTime => AppEvent::TransitionTime(
+                        midi_actions::transition_time_from_cc(value),
                     ),
-                    mappings_enabled: self.mappings_enabled,
-                    midi_clock_port: global::midi_clock_port()
-                        .unwrap_or_default(),
-                    midi_input_port: global::midi_control_in_port()
-                        .unwrap_or_default(),
-                    midi_output_port: global::midi_control_out_port()
-                        .unwrap_or_default(),
-                    midi_input_ports: midi::list_input_ports().unwrap(),
-                    midi_output_ports: midi::list_output_ports().unwrap(),
-                    osc_port: global::osc_port(),
-                    sketch_names: registry.names().clone(),
-                    sketch_name: self.sketch_name(),
-                    transition_time: self.transition_time,
-                    user_data_dir: global::user_data_dir(),
-                    videos_dir: global::videos_dir(),
                 });
-            }
+            });
+
+        if let Err(e) = result {
+            warn!("Failed to start MIDI action listener: {}", e);
         }
     }
 
-    fn capture_recording_frame(&self, app: &App) {
-        let frame_count = self.recording_state.recorded_frames.get();
-        let window = self.main_window(app).unwrap();
-
-        let recording_dir = match &self.recording_state.recording_dir {
-            Some(path) => path,
-            None => {
-                error!(
-                    "Unable to access recording dir {:?}",
-                    &self.recording_state.recording_dir
-                );
-                return;
-            }
+    /// Sends the current transition time to the controller it is bound to so
+    /// encoders with LED rings or motorized faders stay in sync
+    fn send_midi_action_feedback(&mut self) {
+        let (Some((channel, controller)), Some(midi_out)) = (
+            self.midi_actions.get(&MidiAction::TransitionTime),
+            &mut self.midi_out,
+        ) else {
+            return;
         };
 
-        let filename = format!("frame-{:06}.png", frame_count);
-        window.capture_frame(recording_dir.join(filename));
-
-        self.recording_state.recorded_frames.set(frame_count + 1);
+        let value = midi_actions::transition_time_to_cc(self.transition_time);
+        if let Err(e) = midi_out.send(&[0xB0 | channel, *controller, value]) {
+            warn!("Unable to send MIDI action feedback: {}", e);
+        }
     }
 
-    fn switch_sketch(&mut self, app: &App, name: &str) {
-        let registry = REGISTRY.read().unwrap();
-
-        let sketch_info = registry.get(name).unwrap_or_else(|| {
-            error!("No sketch named `{}`. Defaulting to `template`", name);
-            registry.get("template").unwrap()
-        });
-
-        frame_controller::set_fps(sketch_info.config.fps);
-        self.sketch_config = sketch_info.config;
-        self.session_id = recording::generate_session_id();
-        self.clear_next_frame.set(true);
+    fn midi_actions_changed(&mut self) {
+        self.start_midi_action_listener();
+        self.send_midi_action_feedback();
+        self.wv_tx
+            .emit(wv::Event::MidiActions(self.midi_actions.clone()));
+        self.save_global_state();
+    }
 
-        let sketch = (sketch_info.factory)(app, &self.ctx);
-        self.sketch = sketch;
+    fn favorites_changed(&mut self) {
+        let favorites = REGISTRY.read().unwrap().favorites().to_vec();
+        self.wv_tx.emit(wv::Event::Favorites(favorites));
+        self.save_global_state();
+    }
 
-        let mappings_enabled = self.mappings_enabled;
-        if let Some(hub) = self.hub_mut() {
-            hub.midi_proxies_enabled = mappings_enabled;
-            hub.clear_snapshots();
+    /// Builds `name` ahead of time so that switching to it doesn't stall on
+    /// compiling its shaders and parsing its control script. Sketches hold
+    /// `Rc` and GPU state tied to the main thread so this runs between frames
+    /// rather than on a background thread; the point is to pay the cost when
+    /// a sketch is picked instead of at the moment it goes live. The live
+    /// sketch keeps the MIDI and OSC inputs until then
+    fn prewarm_sketch(&mut self, app: &App, name: &str) {
+        if !self.prewarm
+            || name == self.sketch_config.name
+            || self.prewarmed.as_ref().is_some_and(|(n, _)| n == name)
+        {
+            return;
         }
 
-        self.init_sketch_environment(app);
+        let registry = REGISTRY.read().unwrap();
+        let Some(sketch_info) = registry.get(name) else {
+            warn!("No sketch named `{}` to pre-warm", name);
+            return;
+        };
 
-        let display_name = sketch_info.config.display_name;
-        self.app_tx.alert(format!("Switched to {}", display_name));
+        let sketch =
+            global::without_inputs(|| sketch_info.init(app, &self.ctx));
+        drop(registry);
+        self.prewarmed = Some((name.to_string(), sketch));
+
+        info!("Pre-warmed {}", name);
     }
 
     /// A helper to DRY-up the common needs of initializing a sketch on startup
     /// and switching sketches at runtime like window sizing, placement,
     /// persisted state recall, and sending data to the UI
     fn init_sketch_environment(&mut self, app: &App) {
-        self.recording_state = recording::RecordingState::new(
-            recording::frames_dir(&self.session_id, self.sketch_config.name),
-        );
+        REGISTRY.write().unwrap().mark_used(self.sketch_config.name);
+
+        self.recording_state
+            .start_session(&self.session_id, self.sketch_config.name);
 
         let window = self.main_window(app).unwrap();
         window.set_title(self.sketch_config.display_name);
 
         if !self.perf_mode {
-            set_window_position(app, self.main_window_id, 0, 0);
-            set_window_size(
-                window.winit_window(),
-                self.sketch_config.w,
-                self.sketch_config.h,
-            );
+            let (w, h) = self.window_size();
+            let (x, y) = self.window_position_override.unwrap_or((0, 0));
+            set_window_position(app, self.main_window_id, x, y);
+            set_window_size(window.winit_window(), w, h);
         }
 
-        self.ctx.window_rect().set_current(window.rect());
+        self.update_window_rect(app);
 
-        let paused = self.sketch_config.play_mode != PlayMode::Loop;
+        let paused = matches!(
+            self.sketch_config.play_mode,
+            PlayMode::Advance | PlayMode::ManualAdvance
+        );
         frame_controller::set_paused(paused);
+        frame_controller::set_on_demand(
+            self.sketch_config.play_mode == PlayMode::OnDemand,
+        );
 
-        let exclusions = self.load_sketch_state().unwrap_or_default();
+        self.exclusions = self.load_sketch_state().unwrap_or_default();
+        self.apply_timing_overrides();
 
         let mappings_enabled = self.mappings_enabled;
         let transition_time = self.transition_time;
+        let snapshot_phase = self.snapshot_phase;
+        let history_tracking = self.history_tracking.clone();
         let tx1 = self.app_tx.clone();
         let tx2 = self.app_tx.clone();
+        let tx3 = self.app_tx.clone();
+        let tx4 = self.app_tx.clone();
         if let Some(hub) = self.hub_mut() {
             hub.register_populated_callback(move || {
                 tx1.emit(AppEvent::HubPopulated);
@@ -872,57 +2604,107 @@ impl AppModel {
             hub.register_snapshot_ended_callback(move || {
                 tx2.emit(AppEvent::SnapshotEnded);
             });
+            hub.register_script_error_callback(move || {
+                tx3.emit(AppEvent::ScriptErrorChanged);
+            });
+            hub.register_audio_device_callback(move || {
+                tx4.emit(AppEvent::AudioDeviceChanged);
+            });
             hub.set_transition_time(transition_time);
+            hub.set_snapshot_phase(snapshot_phase);
+            hub.set_history_tracking(history_tracking);
             hub.midi_proxies_enabled = mappings_enabled;
         }
 
+        self.update_osc_query();
+
+        let event = self.web_view_load_sketch(paused);
+
+        if self.wv_ready {
+            self.wv_tx.emit(event);
+        } else {
+            self.wv_pending_messages.push_back(event);
+        }
+
+        self.app_tx.emit(AppEvent::SendMidi);
+        self.app_tx.emit(AppEvent::AudioDeviceChanged);
+
+        self.run_lifecycle_hook(app, LifecycleHook::Load);
+    }
+
+    fn web_view_load_sketch(&mut self, paused: bool) -> wv::Event {
         let bypassed = self
             .hub_mut()
             .map_or_else(HashMap::default, |hub| hub.bypassed());
+        let controls = self.web_view_controls();
+        self.set_web_view_baseline(&controls, &bypassed);
 
         let snapshot_slots = self
             .hub()
             .map_or_else(Vec::new, |hub| hub.snapshot_keys_sorted());
+        let (profile, profiles) = self.hub().map_or_else(
+            || (DEFAULT_PROFILE.to_string(), Vec::new()),
+            |hub| (hub.profile().to_string(), hub.profiles()),
+        );
 
-        let event = wv::Event::LoadSketch {
+        wv::Event::LoadSketch {
             bpm: self.ctx.bpm().get(),
             bypassed,
-            controls: self.web_view_controls(),
+            controls,
             display_name: self.sketch_config.display_name.to_string(),
             fps: frame_controller::fps(),
             mappings: self.map_mode.mappings(),
+            msaa_samples: gpu::msaa_samples(),
             paused,
             perf_mode: self.perf_mode,
+            profile,
+            profiles,
+            recent_sketches: REGISTRY.read().unwrap().recently_used().to_vec(),
             sketch_name: self.sketch_name(),
             sketch_width: self.sketch_config.w,
             sketch_height: self.sketch_config.h,
             snapshot_slots,
             tap_tempo_enabled: self.tap_tempo_enabled,
-            exclusions,
-        };
-
-        if self.wv_ready {
-            self.wv_tx.emit(event);
-        } else {
-            self.wv_pending_messages.push_back(event);
+            auto_bpm_enabled: self.auto_bpm.is_enabled(),
+            exclusions: self.exclusions.clone(),
         }
+    }
 
-        self.app_tx.emit(AppEvent::SendMidi);
+    fn index_image(&mut self, filename: String) {
+        if let Some(image_index) = &mut self.image_index {
+            image_index.items.push(storage::ImageIndexItem {
+                filename,
+                created_at: Utc::now().to_rfc3339().to_string(),
+            });
+            if let Err(e) = storage::save_image_index(image_index) {
+                error!("{}", e);
+            }
+        }
     }
 
     fn save_global_state(&mut self) {
         if let Err(e) = storage::save_global_state(GlobalSettings {
             version: GLOBAL_SETTINGS_VERSION.to_string(),
             images_dir: global::images_dir(),
+            latency_offsets: global::latency_offsets(),
             audio_device_name: global::audio_device_name().unwrap_or_default(),
+            depth_device_name: global::depth_device_name().unwrap_or_default(),
+            favorite_sketches: REGISTRY.read().unwrap().favorites().to_vec(),
             hrcc: self.hrcc,
             mappings_enabled: self.mappings_enabled,
+            midi_actions: self.midi_actions.clone(),
             midi_clock_port: global::midi_clock_port().unwrap_or_default(),
             midi_control_in_port: global::midi_control_in_port()
                 .unwrap_or_default(),
             midi_control_out_port: global::midi_control_out_port()
                 .unwrap_or_default(),
             osc_port: global::osc_port(),
+            prewarm: self.prewarm,
+            recent_sketches: REGISTRY.read().unwrap().recently_used().to_vec(),
+            recording: self.recording_state.settings.clone(),
+            snapshot_phase: self.snapshot_phase,
+            stereo: gpu::stereo_settings(),
+            sync_role: self.network_sync.role(),
             transition_time: self.transition_time,
             user_data_dir: global::user_data_dir(),
             videos_dir: global::videos_dir(),
@@ -936,12 +2718,79 @@ impl AppModel {
         }
     }
 
-    /// Load MIDI, OSC, and UI controls along with any snapshots or MIDI
-    /// mappings the user has saved to disk
+    /// The sketch's BPM, taking any runtime override into account
+    fn sketch_bpm(&self) -> f32 {
+        self.timing_overrides.bpm.unwrap_or(self.sketch_config.bpm)
+    }
+
+    /// The sketch's FPS, taking any runtime override into account
+    fn sketch_fps(&self) -> f32 {
+        self.timing_overrides.fps.unwrap_or(self.sketch_config.fps)
+    }
+
+    /// Applies [`Self::timing_overrides`] (or the sketch's config values
+    /// when not overridden). The BPM is left alone while tap tempo or auto BPM
+    /// is enabled so the tapped or detected tempo carries across sketches
+    fn apply_timing_overrides(&mut self) {
+        frame_controller::set_fps(self.sketch_fps());
+        if !self.tap_tempo_enabled && !self.auto_bpm.is_enabled() {
+            self.ctx.bpm().set(self.sketch_bpm());
+        }
+    }
+
+    /// Follows the latest confident [`auto_bpm::TempoEstimate`] by setting the
+    /// BPM and nudging the frame count so beats land on detected beats. The
+    /// first estimate after enabling also starts a new bar on the last
+    /// detected beat
+    fn apply_auto_bpm(&mut self) {
+        let Some((estimate, detected_at)) = self.auto_bpm.take_estimate()
+        else {
+            return;
+        };
+        if estimate.confidence < auto_bpm::MIN_CONFIDENCE {
+            return;
+        }
+
+        let bpm = self.ctx.bpm().get();
+        let detected_bpm = (estimate.bpm * 10.0).round() / 10.0;
+        let bpm_changed = (detected_bpm - bpm).abs() >= 0.5;
+        let next_bpm = ternary!(bpm_changed, detected_bpm, bpm);
+
+        let frames_per_beat = |bpm: f32| 60.0 / bpm * frame_controller::fps();
+        let beats =
+            frame_controller::frame_count() as f32 / frames_per_beat(bpm);
+        let phase = estimate.phase_after(detected_at.elapsed().as_secs_f32());
+        let drift = auto_bpm::beat_drift(phase, beats);
+
+        if bpm_changed || !self.auto_bpm_locked || drift.abs() > 0.05 {
+            let aligned = ternary!(
+                self.auto_bpm_locked,
+                beats + drift,
+                (beats / 4.0).round() * 4.0 + phase
+            );
+            frame_controller::set_frame_count(
+                (aligned * frames_per_beat(next_bpm)).round() as u32,
+            );
+            self.auto_bpm_locked = true;
+        }
+
+        if bpm_changed {
+            self.ctx.bpm().set(next_bpm);
+            self.wv_tx.emit(wv::Event::Bpm(next_bpm));
+        }
+    }
+
+    /// Load MIDI, OSC, and UI controls along with any snapshots, MIDI
+    /// mappings, or FPS/BPM overrides the user has saved to disk
     fn load_sketch_state(&mut self) -> Result<Exclusions, Box<dyn Error>> {
+        self.timing_overrides = TimingOverrides::default();
+
         let app_tx = self.app_tx.clone();
         let sketch_name = self.sketch_name();
         let mappings = self.map_mode.mappings();
+        let renames = self
+            .hub()
+            .map_or_else(IndexMap::default, |hub| hub.renames().clone());
 
         let mut current_state =
             self.hub()
@@ -951,15 +2800,23 @@ impl AppModel {
                         midi_controls: hub.midi_controls.clone(),
                         osc_controls: hub.osc_controls.clone(),
                         snapshots: hub.snapshots.clone(),
+                        snapshot_phases: hub.snapshot_phases.clone(),
                         mappings,
                         exclusions: Vec::new(),
+                        timing: TimingOverrides::default(),
+                        slider_ranges: HashMap::default(),
                     }
                 });
 
-        match storage::load_sketch_state(&sketch_name, &mut current_state) {
+        match storage::load_sketch_state(
+            &sketch_name,
+            &mut current_state,
+            &renames,
+        ) {
             Ok(state) => {
                 self.map_mode.clear();
                 self.map_mode.set_mappings(state.mappings.clone());
+                self.timing_overrides = state.timing;
 
                 let Some(hub) = self.hub_mut() else {
                     return Ok(Vec::new());
@@ -1023,24 +2880,30 @@ impl AppModel {
 impl Drop for AppModel {
     fn drop(&mut self) {
         debug!("Dropping...");
-        match self.wv_process.kill() {
-            Ok(_) => debug!("Killed ui_process"),
-            Err(e) => error!("Error killing ui_process {}", e),
-        }
+        self.kill_web_view();
     }
 }
 
 fn model(app: &App) -> AppModel {
+    spans::init();
+
+    let args = Args::parse();
+
     let global_settings = match storage::load_global_state() {
         Ok(gs) => {
             info!("Restoring global settings: {:?}", gs);
             global::set_audio_device_name(&gs.audio_device_name);
+            if !gs.depth_device_name.is_empty() {
+                global::set_depth_device_name(&gs.depth_device_name);
+            }
             global::set_images_dir(&gs.images_dir);
+            global::set_latency_offsets(gs.latency_offsets);
             global::set_midi_clock_port(&gs.midi_clock_port);
             global::set_midi_control_in_port(&gs.midi_control_in_port);
             global::set_midi_control_out_port(&gs.midi_control_out_port);
             global::set_osc_port(gs.osc_port);
             global::set_user_data_dir(&gs.user_data_dir);
+            stereo::set_stereo(gs.stereo);
             global::set_videos_dir(&gs.videos_dir);
             gs
         }
@@ -1050,14 +2913,27 @@ fn model(app: &App) -> AppModel {
         }
     };
 
-    let args: Vec<String> = env::args().collect();
-    let initial_sketch = args
-        .get(1)
-        .map_or_else(|| "template".to_string(), |s| s.to_string());
+    if let Some(port) = &args.midi_in {
+        global::set_midi_control_in_port(port);
+    }
+    if let Some(port) = &args.midi_clock {
+        global::set_midi_clock_port(port);
+    }
+    if let Some(port) = args.osc_port {
+        global::set_osc_port(port);
+    }
+    global::set_timing_source(&args.timing);
+
+    let initial_sketch = &args.sketch;
+
+    let mut registry = REGISTRY.write().unwrap();
+    registry.set_favorites(global_settings.favorite_sketches);
+    registry.set_recently_used(global_settings.recent_sketches);
+    drop(registry);
 
     let registry = REGISTRY.read().unwrap();
 
-    let sketch_info = registry.get(&initial_sketch).unwrap_or_else(|| {
+    let sketch_info = registry.get(initial_sketch).unwrap_or_else(|| {
         error!(
             "No sketch named `{}`. Defaulting to `template`",
             initial_sketch
@@ -1068,17 +2944,34 @@ fn model(app: &App) -> AppModel {
     app.set_fullscreen_on_shortcut(false);
     app.set_exit_on_escape(false);
 
-    let main_window_id = app
-        .new_window()
-        .size(sketch_info.config.w as u32, sketch_info.config.h as u32)
-        .build()
-        .unwrap();
+    let (w, h) = args
+        .size
+        .unwrap_or((sketch_info.config.w, sketch_info.config.h));
+    let main_window_id =
+        app.new_window().size(w as u32, h as u32).build().unwrap();
+
+    // Applied here too since Performance Mode skips repositioning
+    if let Some((x, y)) = args.position {
+        set_window_position(app, main_window_id, x, y);
+    }
 
     let rect = app
         .window(main_window_id)
         .expect("Unable to get window")
         .rect();
 
+    let control_panel = args.no_ui.then(|| {
+        let id = app
+            .new_window()
+            .title("Xtal Controls")
+            .size(320, 640)
+            .raw_event(control_panel_raw_event)
+            .view(control_panel_view)
+            .build()
+            .unwrap();
+        ControlPanel::new(&app.window(id).unwrap())
+    });
+
     let bpm = Bpm::new(sketch_info.config.bpm);
     let bpm_clone = bpm.clone();
     let raw_bpm = bpm.get();
@@ -1091,7 +2984,9 @@ fn model(app: &App) -> AppModel {
     );
 
     frame_controller::set_fps(sketch_info.config.fps);
-    let sketch = (sketch_info.factory)(app, &ctx);
+    gpu::set_msaa_samples(sketch_info.config.msaa_samples);
+    let sketch = sketch_info.init(app, &ctx);
+    gpu_stats::set_owner(sketch_info.config.name);
 
     let (raw_event_tx, event_rx) = mpsc::channel();
     let midi_tx = raw_event_tx.clone();
@@ -1112,8 +3007,19 @@ fn model(app: &App) -> AppModel {
         .inspect_err(|e| error!("Error in model: {}", e))
         .ok();
 
+    let osc_query = OscQueryServer::start()
+        .inspect_err(|e| warn!("Unable to start OSCQuery server: {}", e))
+        .ok();
+
     let event_tx = AppEventSender::new(raw_event_tx);
-    let (web_view_tx, ui_process) = wv::launch(&event_tx).unwrap();
+    let (web_view_tx, ui_process) = if args.no_ui {
+        info!("Running without the web view UI");
+        (wv::EventSender::disconnected(), None)
+    } else {
+        let ipc_path = args.ipc_path.clone().unwrap_or_else(wv::ipc_path);
+        let (tx, process) = wv::launch(&event_tx, ipc_path, !args.external_ui);
+        (tx, Some(process))
+    };
     let ui_tx = web_view_tx.clone();
 
     thread::spawn(move || {
@@ -1126,59 +3032,166 @@ fn model(app: &App) -> AppModel {
     let mut model = AppModel {
         app_rx: event_rx,
         app_tx: event_tx,
+        auto_bpm: AutoBpm::default(),
+        auto_bpm_locked: false,
         clear_next_frame,
+        control_panel,
         ctx,
+        cue_list: storage::load_cue_list()
+            .inspect_err(|e| error!("Error loading cue list: {}", e))
+            .unwrap_or_default(),
+        exclusions: Vec::new(),
+        flipbook: None,
+        history_tracking: HistoryTracking::Off,
+        history_sent_frame: 0,
         hrcc: global_settings.hrcc,
         image_index,
         keys_held: HashSet::default(),
+        letterbox: false,
         mappings_enabled: global_settings.mappings_enabled,
         main_maximized: Cell::new(false),
         main_window_id,
         map_mode: MapMode::default(),
+        midi_actions: global_settings.midi_actions.clone(),
         midi_out,
-        perf_mode: false,
-        recording_state: RecordingState::default(),
+        network_sync: NetworkSync::default(),
+        osc_query,
+        output_warps: storage::load_output_warps()
+            .inspect_err(|e| error!("Error loading output warps: {}", e))
+            .unwrap_or_default(),
+        perf_mode: args.perf,
+        prewarm: global_settings.prewarm,
+        prewarmed: None,
+        quit_saving: false,
+        quitting: false,
+        recording_state: RecordingState::new(global_settings.recording.clone()),
         session_id: uuid_5(),
+        set_list: storage::load_set_list()
+            .inspect_err(|e| error!("Error loading set list: {}", e))
+            .unwrap_or_default(),
         sketch,
         sketch_config: sketch_info.config,
+        sketch_inputs: SketchInputs::default(),
+        snapshot_phase: global_settings.snapshot_phase,
+        sysex_recorder: None,
         tap_tempo: TapTempo::new(raw_bpm),
         tap_tempo_enabled: false,
+        timing_overrides: TimingOverrides::default(),
         transition_time: global_settings.transition_time,
+        window_position_override: args.position,
+        window_size_override: args.size,
+        wv_bypassed: HashMap::default(),
+        wv_controls: HashMap::default(),
+        wv_controls_dirty: false,
+        wv_changed_controls: HashSet::default(),
         wv_pending_messages: VecDeque::new(),
         wv_process: ui_process,
-        wv_ready: false,
+        // Nothing will ever send Ready so don't queue messages for it
+        wv_ready: args.no_ui,
         wv_tx: web_view_tx,
     };
 
+    drop(registry);
     model.init_sketch_environment(app);
+    warp::set_output_warp(model.output_warps.active());
+    model.start_set_list_listener();
+    model.start_cue_list_listener();
+    model.start_cue_osc_listener();
+    model.start_midi_action_listener();
+    model.start_signal_listener();
+
+    if let Some(snapshot) = &args.snapshot {
+        model.recall_snapshot_over(snapshot, 0.0);
+    }
+
+    if let Some(path) = &args.replay {
+        model.app_tx.emit(AppEvent::Replay(Some(path.clone())));
+    }
+
+    if global_settings.sync_role != SyncRole::Off {
+        model
+            .app_tx
+            .emit(AppEvent::ChangeSyncRole(global_settings.sync_role));
+    }
 
     model
 }
 
 fn update(app: &App, model: &mut AppModel, update: Update) {
+    let _span = trace_span!(target: spans::UPDATE, "update").entered();
+
     while let Ok(event) = model.app_rx.try_recv() {
         model.on_app_event(app, event);
     }
 
+    model.update_control_panel(update.since_start);
+    model.flush_web_view_changes();
+    model.apply_auto_bpm();
+
     // Should this come _after_ `wrapped_update` and possibly behind a
     // `did_update` returned from frame_controller?
     if let Some(hub) = model.hub_mut() {
         hub.update();
     }
 
+    // MIDI and OSC move sliders without the web view knowing
+    if let Some(hub) = model.hub_mut() {
+        let changed = hub.take_input_changes();
+        model.wv_changed_controls.extend(changed);
+    }
+
+    let inputs = model
+        .hub()
+        .map(|hub| hub.inputs().clone())
+        .unwrap_or_default();
+    let sketch_name = model.sketch_config.name;
+    if model
+        .sketch_inputs
+        .sync(app, &model.ctx, sketch_name, &inputs)
+    {
+        if let Some(hub) = model.hub_mut() {
+            hub.reconnect_inputs();
+        }
+    }
+
     frame_controller::wrapped_update(
         app,
         &mut model.sketch,
         update,
-        |app, sketch, update| sketch.update(app, update, &model.ctx),
+        |app, sketch, update| {
+            let _span =
+                trace_span!(target: spans::UPDATE, "sketch_update").entered();
+            model.sketch_inputs.update(app, update, &model.ctx);
+            sketch.update(app, update, &model.ctx)
+        },
     );
 
-    if model.recording_state.is_encoding {
-        model.recording_state.on_encoding_message(
-            model.sketch_config,
-            &mut model.session_id,
-            &model.app_tx,
-        );
+    if model.network_sync.role() == SyncRole::Leader {
+        model.network_sync.broadcast(&SyncMessage::Frame {
+            frame_count: frame_controller::frame_count(),
+            bpm: model.ctx.bpm().get(),
+            sketch: model.sketch_name(),
+        });
+    }
+
+    model.send_control_history();
+
+    let post_effects =
+        model.hub().map_or_else(Vec::new, |hub| hub.post_effects());
+    model.ctx.post_stack().set_effects(post_effects);
+
+    if let Some(tick) = model.recording_state.tick_count_in() {
+        model.on_count_in_tick(app, tick);
+    }
+
+    if model.recording_state.is_capturing() {
+        model.recording_state.check_health(&model.app_tx);
+        model.recording_state.beats =
+            model.hub().map_or(0.0, |hub| hub.animation.beats());
+    }
+
+    if model.recording_state.is_encoding() {
+        model.recording_state.on_encoding_message(&model.app_tx);
     }
 }
 
@@ -1198,6 +3211,13 @@ fn event(app: &App, model: &mut AppModel, event: Event) {
                 return;
             }
 
+            // Let the control panel have keys while a widget is focused
+            if let Some(control_panel) = &model.control_panel {
+                if control_panel.wants_keyboard_input() {
+                    return;
+                }
+            }
+
             model.keys_held.insert(key);
 
             let logo_pressed = app.keys.mods.logo();
@@ -1241,6 +3261,10 @@ fn event(app: &App, model: &mut AppModel, event: Event) {
                 Key::A if has_no_modifiers => {
                     model.app_tx.emit(AppEvent::AdvanceSingleFrame);
                 }
+                // Shift A
+                Key::A if shift_pressed && !platform_mod_pressed => {
+                    model.app_tx.emit(AppEvent::CycleAspectPreset);
+                }
                 // F (any)
                 Key::F => {
                     model.app_tx.emit(AppEvent::ToggleFullScreen);
@@ -1254,6 +3278,10 @@ fn event(app: &App, model: &mut AppModel, event: Event) {
                 Key::M if !platform_mod_pressed => {
                     model.app_tx.emit(AppEvent::ToggleMainFocus);
                 }
+                // L
+                Key::L if has_no_modifiers => {
+                    model.app_tx.emit(AppEvent::ToggleLetterbox);
+                }
                 // R
                 Key::R if has_no_modifiers => {
                     model.app_tx.emit(AppEvent::Reset);
@@ -1262,6 +3290,34 @@ fn event(app: &App, model: &mut AppModel, event: Event) {
                 Key::S if has_no_modifiers => {
                     model.app_tx.emit(AppEvent::CaptureFrame);
                 }
+                // Shift S
+                Key::S if shift_pressed && !platform_mod_pressed => {
+                    model.app_tx.emit(AppEvent::CaptureFlipbook(9, 1.0));
+                }
+                // N
+                Key::N if has_no_modifiers => {
+                    model.app_tx.emit(AppEvent::SetListNext);
+                }
+                // Shift N
+                Key::N if shift_pressed && !platform_mod_pressed => {
+                    model.app_tx.emit(AppEvent::SetListPrevious);
+                }
+                // C
+                Key::C if has_no_modifiers => {
+                    model.app_tx.emit(AppEvent::CueNext);
+                }
+                // Shift C
+                Key::C if shift_pressed && !platform_mod_pressed => {
+                    model.app_tx.emit(AppEvent::CuePrevious);
+                }
+                // T
+                Key::T if has_no_modifiers => {
+                    model.app_tx.emit(AppEvent::ToggleSafeAreaGuides);
+                }
+                // Shift T
+                Key::T if shift_pressed && !platform_mod_pressed => {
+                    model.app_tx.emit(AppEvent::CycleCalibration);
+                }
                 _ => {}
             }
         }
@@ -1279,13 +3335,25 @@ fn event(app: &App, model: &mut AppModel, event: Event) {
 }
 
 fn view(app: &App, model: &AppModel, frame: Frame) {
+    let _span = trace_span!(target: spans::VIEW, "view").entered();
+
     let did_render = frame_controller::wrapped_view(
         app,
         &model.sketch,
         frame,
-        |app, sketch, frame| sketch.view(app, frame, &model.ctx),
+        |app, sketch, frame| {
+            {
+                let _span =
+                    trace_span!(target: spans::VIEW, "sketch_view").entered();
+                sketch.view(app, &frame, &model.ctx);
+            }
+            model.ctx.present(app, &frame);
+        },
     );
 
+    // The frame has been submitted so its capture can be read back
+    model.ctx.frame_capture().save();
+
     if did_render {
         frame_controller::clear_force_render();
 
@@ -1293,8 +3361,26 @@ fn view(app: &App, model: &AppModel, frame: Frame) {
             model.clear_next_frame.set(false);
         }
 
-        if model.recording_state.is_recording {
-            model.capture_recording_frame(app);
+        if model.recording_state.is_capturing() {
+            model.capture_recording_frame();
         }
+
+        model.capture_flipbook_frame();
+    }
+}
+
+fn control_panel_raw_event(
+    _app: &App,
+    model: &mut AppModel,
+    event: &nannou::winit::event::WindowEvent,
+) {
+    if let Some(control_panel) = &mut model.control_panel {
+        control_panel.handle_raw_event(event);
+    }
+}
+
+fn control_panel_view(_app: &App, model: &AppModel, frame: Frame) {
+    if let Some(control_panel) = &model.control_panel {
+        control_panel.draw(&frame);
     }
 }
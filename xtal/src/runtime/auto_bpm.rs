@@ -0,0 +1,372 @@
+//! Beat tracking on the audio input for gigs without a MIDI clock. Onsets are
+//! detected with spectral flux, the tempo is the strongest autocorrelation
+//! lag of the last few seconds of onsets (weighted towards 120 BPM to avoid
+//! octave errors), and the phase is the offset that best lines a comb of beats
+//! up with the onsets. When enabled (the header's Auto BPM toggle) the
+//! estimate drives the global [`Bpm`] and nudges the frame count so beats and
+//! bar boundaries land on the detected beats.
+
+use cpal::{Stream, traits::*};
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::f32::consts::TAU;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::framework::prelude::*;
+use crate::runtime::global;
+
+const FRAME_SIZE: usize = 1024;
+const HOP_SIZE: usize = 512;
+/// Seconds of onsets kept for analysis
+const HISTORY: f32 = 8.0;
+/// Seconds of onsets needed before the first estimate
+const MIN_HISTORY: f32 = 4.0;
+const MIN_BPM: f32 = 70.0;
+const MAX_BPM: f32 = 180.0;
+/// Tempo prior: center and width in octaves
+const PRIOR_BPM: f32 = 120.0;
+const PRIOR_OCTAVES: f32 = 1.0;
+
+/// Estimates below this are ignored by the runtime
+pub const MIN_CONFIDENCE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoEstimate {
+    pub bpm: f32,
+    /// Normalized autocorrelation of the chosen tempo in [0, 1]
+    pub confidence: f32,
+    /// Fraction of a beat elapsed since the last detected beat as of the end
+    /// of the analyzed audio
+    pub phase: f32,
+}
+
+impl TempoEstimate {
+    /// [`Self::phase`] after `seconds` more have passed, wrapped to [0, 1)
+    pub fn phase_after(&self, seconds: f32) -> f32 {
+        (self.phase + seconds * self.bpm / 60.0).rem_euclid(1.0)
+    }
+}
+
+pub struct BeatTracker {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    pending: Vec<f32>,
+    previous_magnitudes: Vec<f32>,
+    onsets: VecDeque<f32>,
+    /// Onset envelope samples per second
+    hop_rate: f32,
+    hops_since_estimate: usize,
+}
+
+impl BeatTracker {
+    pub fn new(sample_rate: f32) -> Self {
+        let window = (0..FRAME_SIZE)
+            .map(|i| {
+                let x = i as f32 / (FRAME_SIZE - 1) as f32;
+                0.5 - 0.5 * (TAU * x).cos()
+            })
+            .collect();
+
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FRAME_SIZE),
+            window,
+            pending: Vec::with_capacity(FRAME_SIZE * 2),
+            previous_magnitudes: vec![0.0; FRAME_SIZE / 2],
+            onsets: VecDeque::new(),
+            hop_rate: sample_rate / HOP_SIZE as f32,
+            hops_since_estimate: 0,
+        }
+    }
+
+    /// Feeds mono samples. Returns a new estimate about once per second once
+    /// enough audio has been heard
+    pub fn process(&mut self, samples: &[f32]) -> Option<TempoEstimate> {
+        self.pending.extend_from_slice(samples);
+
+        let mut estimate = None;
+        while self.pending.len() >= FRAME_SIZE {
+            let flux = self.spectral_flux();
+            self.pending.drain(..HOP_SIZE);
+
+            self.onsets.push_back(flux);
+            if self.onsets.len() > (HISTORY * self.hop_rate) as usize {
+                self.onsets.pop_front();
+            }
+
+            self.hops_since_estimate += 1;
+            if self.hops_since_estimate >= self.hop_rate as usize
+                && self.onsets.len() >= (MIN_HISTORY * self.hop_rate) as usize
+            {
+                self.hops_since_estimate = 0;
+                estimate = self.estimate();
+            }
+        }
+        estimate
+    }
+
+    fn spectral_flux(&mut self) -> f32 {
+        let mut spectrum: Vec<Complex<f32>> = self.pending[..FRAME_SIZE]
+            .iter()
+            .zip(&self.window)
+            .map(|(sample, w)| Complex::new(sample * w, 0.0))
+            .collect();
+        self.fft.process(&mut spectrum);
+
+        let mut flux = 0.0;
+        for (bin, previous) in
+            spectrum.iter().zip(self.previous_magnitudes.iter_mut())
+        {
+            // Log compression keeps quiet onsets from being drowned out
+            let magnitude = (1.0 + 100.0 * bin.norm()).ln();
+            flux += (magnitude - *previous).max(0.0);
+            *previous = magnitude;
+        }
+        flux
+    }
+
+    fn estimate(&self) -> Option<TempoEstimate> {
+        // Remove the mean so sustained loudness doesn't correlate
+        let mean = self.onsets.iter().sum::<f32>() / self.onsets.len() as f32;
+        let onsets: Vec<f32> =
+            self.onsets.iter().map(|o| (o - mean).max(0.0)).collect();
+
+        let autocorrelation = |lag: usize| -> f32 {
+            onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum()
+        };
+
+        let energy = autocorrelation(0);
+        if energy <= f32::EPSILON {
+            return None;
+        }
+
+        let min_lag = (60.0 * self.hop_rate / MAX_BPM).floor() as usize;
+        let max_lag = (60.0 * self.hop_rate / MIN_BPM).ceil() as usize;
+
+        // Beats rarely fall on a whole number of hops so each peak is smeared
+        // over neighboring lags
+        let smoothed = |lag: usize| -> f32 {
+            0.5 * autocorrelation(lag - 1)
+                + autocorrelation(lag)
+                + 0.5 * autocorrelation(lag + 1)
+        };
+
+        // One extra lag either side for interpolating the peak
+        let first_lag = min_lag - 1;
+        let scores: Vec<f32> = (first_lag..=max_lag + 1)
+            .map(|lag| {
+                let bpm = 60.0 * self.hop_rate / lag as f32;
+                let octaves = (bpm / PRIOR_BPM).log2() / PRIOR_OCTAVES;
+                smoothed(lag) * (-0.5 * octaves * octaves).exp()
+            })
+            .collect();
+
+        let (best, _) = scores[1..scores.len() - 1]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))?;
+        let mut best = best + 1;
+
+        // Off-beat hats correlate at 1.5 beats as strongly as kicks do at one,
+        // so prefer the faster tempo of a 3:2 pair when it's about as strong
+        let faster = ((first_lag + best) as f32 * 2.0 / 3.0).round() as usize;
+        if faster > first_lag
+            && smoothed(faster) >= 0.8 * smoothed(first_lag + best)
+        {
+            best = faster - first_lag;
+        }
+
+        // Parabolic interpolation for a fractional lag
+        let (l, c, r) = (scores[best - 1], scores[best], scores[best + 1]);
+        let denominator = l - 2.0 * c + r;
+        let offset = ternary!(
+            denominator.abs() > f32::EPSILON,
+            0.5 * (l - r) / denominator,
+            0.0
+        );
+        let lag = (first_lag + best) as f32 + offset.clamp(-0.5, 0.5);
+        let bpm = 60.0 * self.hop_rate / lag;
+        let confidence =
+            (autocorrelation(first_lag + best) / energy).clamp(0.0, 1.0);
+
+        Some(TempoEstimate {
+            bpm,
+            confidence,
+            phase: Self::phase(&onsets, lag),
+        })
+    }
+
+    /// Beats elapsed since the comb of period `lag` that best fits `onsets`
+    /// last hit, as a fraction of a beat
+    fn phase(onsets: &[f32], lag: f32) -> f32 {
+        let last = onsets.len() - 1;
+        let (hops_since_beat, _) = (0..lag.ceil() as usize)
+            .map(|offset| {
+                let mut score = 0.0;
+                let mut position = (last - offset) as f32;
+                while position >= 0.0 {
+                    score += onsets[position.round() as usize];
+                    position -= lag;
+                }
+                (offset, score)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+
+        // The onset envelope lags the audio by about half a frame
+        let latency = (FRAME_SIZE / 2) as f32 / HOP_SIZE as f32;
+        ((hops_since_beat as f32 + latency) / lag).rem_euclid(1.0)
+    }
+}
+
+/// Runs a [`BeatTracker`] on the global audio device's first channel
+#[derive(Default)]
+pub struct AutoBpm {
+    stream: Option<Stream>,
+    latest: Arc<Mutex<Option<(TempoEstimate, Instant)>>>,
+}
+
+impl AutoBpm {
+    pub fn is_enabled(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        self.stop();
+
+        let device_name = global::audio_device_name().unwrap_or_default();
+        let device = cpal::default_host()
+            .input_devices()?
+            .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
+            .ok_or_else(|| {
+                format!("Audio device not found: {:?}", device_name)
+            })?;
+        let config: cpal::StreamConfig = device.default_input_config()?.into();
+        let channels = (config.channels as usize).max(1);
+
+        let mut tracker = BeatTracker::new(config.sample_rate.0 as f32);
+        let latest = self.latest.clone();
+        let stream = device.build_input_stream(
+            &config,
+            move |data: &[f32], _| {
+                let mono: Vec<f32> =
+                    data.iter().step_by(channels).copied().collect();
+                if let Some(estimate) = tracker.process(&mono) {
+                    *latest.lock().unwrap() = Some((estimate, Instant::now()));
+                }
+            },
+            move |err| error!("Error in auto BPM stream: {}", err),
+            None,
+        )?;
+        stream.play()?;
+        self.stream = Some(stream);
+
+        info!("Auto BPM listening to {:?}", device_name);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        self.stream = None;
+        *self.latest.lock().unwrap() = None;
+    }
+
+    /// The estimate made since the last call, if any, along with when it
+    /// was made
+    pub fn take_estimate(&self) -> Option<(TempoEstimate, Instant)> {
+        self.latest.lock().unwrap().take()
+    }
+}
+
+/// How far `beats` is from `phase`, in beats within [-0.5, 0.5)
+pub fn beat_drift(phase: f32, beats: f32) -> f32 {
+    (phase - beats.rem_euclid(1.0) + 0.5).rem_euclid(1.0) - 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+
+    const SAMPLE_RATE: f32 = 44_100.0;
+
+    /// A decaying noise burst on every beat
+    fn click_track(bpm: f32, seconds: f32, offset: f32) -> Vec<f32> {
+        let samples_per_beat = SAMPLE_RATE * 60.0 / bpm;
+        let mut rng = 12345_u32;
+        (0..(seconds * SAMPLE_RATE) as usize)
+            .map(|i| {
+                let t = (i as f32 - offset * SAMPLE_RATE)
+                    .rem_euclid(samples_per_beat);
+                rng = rng.wrapping_mul(1664525).wrapping_add(1013904223);
+                let noise = (rng >> 8) as f32 / (1 << 24) as f32 - 0.5;
+                noise * (-t / 400.0).exp()
+            })
+            .collect()
+    }
+
+    fn track(samples: &[f32]) -> Option<TempoEstimate> {
+        let mut tracker = BeatTracker::new(SAMPLE_RATE);
+        let mut estimate = None;
+        for chunk in samples.chunks(512) {
+            estimate = tracker.process(chunk).or(estimate);
+        }
+        estimate
+    }
+
+    #[test]
+    fn test_tempo() {
+        for bpm in [85.0, 95.0, 110.0, 128.0, 140.0, 150.0, 174.0] {
+            // Quieter off-beat hats shouldn't double the tempo
+            let kicks = click_track(bpm, 8.0, 0.0);
+            let hats = click_track(bpm, 8.0, 30.0 / bpm);
+            let mix: Vec<f32> =
+                kicks.iter().zip(&hats).map(|(k, h)| k + 0.3 * h).collect();
+            let estimate = track(&mix).unwrap();
+            assert!(
+                (estimate.bpm - bpm).abs() < 1.5,
+                "expected {}, got {:?}",
+                bpm,
+                estimate
+            );
+            assert!(estimate.confidence >= MIN_CONFIDENCE);
+        }
+    }
+
+    #[test]
+    fn test_phase() {
+        let bpm = 120.0;
+        // Beats fall at 0.1s, 0.6s, ..., 7.6s; the audio ends at 8.0s, 0.8
+        // beats after the last one
+        let samples = click_track(bpm, 8.0, 0.1);
+        let mut tracker = BeatTracker::new(SAMPLE_RATE);
+        tracker.process(&samples);
+        let estimate = tracker.estimate().unwrap();
+        let drift = beat_drift(estimate.phase, 0.8);
+        assert!(drift.abs() < 0.1, "{:?}", estimate);
+    }
+
+    #[test]
+    fn test_silence() {
+        assert_eq!(track(&vec![0.0; (SAMPLE_RATE * 6.0) as usize]), None);
+    }
+
+    #[test]
+    fn test_beat_drift() {
+        assert_approx_eq!(beat_drift(0.25, 3.0), 0.25);
+        assert_approx_eq!(beat_drift(0.0, 4.75), 0.25);
+        assert_approx_eq!(beat_drift(0.9, 2.1), -0.2);
+    }
+
+    #[test]
+    fn test_phase_after() {
+        let estimate = TempoEstimate {
+            bpm: 120.0,
+            confidence: 1.0,
+            phase: 0.5,
+        };
+        assert_approx_eq!(estimate.phase_after(1.0), 0.5);
+        assert_approx_eq!(estimate.phase_after(0.75), 0.0);
+    }
+}
@@ -0,0 +1,154 @@
+//! Command line arguments for [`crate::runtime::app::run`]. Everything is
+//! optional so a bare `cargo run` still opens the template sketch; the flags
+//! exist so launches can be scripted per venue.
+
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Parser)]
+#[command(about = "Run an Xtal sketch")]
+pub struct Args {
+    /// The sketch to open
+    #[arg(default_value = "template")]
+    pub sketch: String,
+
+    /// Animation timing source: frame, osc, midi, or hybrid
+    #[arg(default_value = "frame")]
+    pub timing: String,
+
+    /// Snapshot to recall (without a transition) once the sketch has loaded
+    #[arg(long)]
+    pub snapshot: Option<String>,
+
+    /// Control value recording to play back once the sketch has loaded, e.g.
+    /// to re-render a performance. See `ControlHub::start_replay`
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Main window size as WIDTHxHEIGHT, used instead of the sketch's size
+    #[arg(long, value_parser = parse_size)]
+    pub size: Option<(i32, i32)>,
+
+    /// Main window position as X,Y
+    #[arg(long, value_parser = parse_position, allow_hyphen_values = true)]
+    pub position: Option<(i32, i32)>,
+
+    /// Start in Performance Mode
+    #[arg(long)]
+    pub perf: bool,
+
+    /// Don't launch the web view UI process
+    #[arg(long)]
+    pub no_ui: bool,
+
+    /// Don't spawn the web view; wait for one started separately with
+    /// `cargo run --release -p xtal`
+    #[arg(long, conflicts_with = "no_ui")]
+    pub external_ui: bool,
+
+    /// File the web view IPC server name is published to. Defaults to
+    /// $XTAL_IPC_PATH or a file in the temp dir
+    #[arg(long)]
+    pub ipc_path: Option<PathBuf>,
+
+    /// MIDI control input port, overriding the saved setting
+    #[arg(long)]
+    pub midi_in: Option<String>,
+
+    /// MIDI clock input port, overriding the saved setting
+    #[arg(long)]
+    pub midi_clock: Option<String>,
+
+    /// OSC port, overriding the saved setting
+    #[arg(long)]
+    pub osc_port: Option<u16>,
+}
+
+fn parse_size(s: &str) -> Result<(i32, i32), String> {
+    parse_pair(s, 'x')
+        .filter(|(w, h)| *w > 0 && *h > 0)
+        .ok_or_else(|| {
+            format!("Expected WIDTHxHEIGHT (e.g. 1920x1080), got `{}`", s)
+        })
+}
+
+fn parse_position(s: &str) -> Result<(i32, i32), String> {
+    parse_pair(s, ',')
+        .ok_or_else(|| format!("Expected X,Y (e.g. 0,0), got `{}`", s))
+}
+
+fn parse_pair(s: &str, separator: char) -> Option<(i32, i32)> {
+    let (a, b) = s.split_once(separator)?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let args = Args::try_parse_from(["xtal"]).unwrap();
+        assert_eq!(args.sketch, "template");
+        assert_eq!(args.timing, "frame");
+        assert!(!args.perf && !args.no_ui && !args.external_ui);
+        assert!(args.ipc_path.is_none());
+    }
+
+    #[test]
+    fn test_flags() {
+        let args = Args::try_parse_from([
+            "xtal",
+            "blob",
+            "midi",
+            "--snapshot",
+            "3",
+            "--replay",
+            "take.csv",
+            "--size",
+            "1920x1080",
+            "--position",
+            "-1920,0",
+            "--perf",
+            "--no-ui",
+            "--midi-in",
+            "Faderfox EC4",
+            "--osc-port",
+            "9000",
+        ])
+        .unwrap();
+
+        assert_eq!(args.sketch, "blob");
+        assert_eq!(args.timing, "midi");
+        assert_eq!(args.snapshot.as_deref(), Some("3"));
+        assert_eq!(args.replay, Some(PathBuf::from("take.csv")));
+        assert_eq!(args.size, Some((1920, 1080)));
+        assert_eq!(args.position, Some((-1920, 0)));
+        assert!(args.perf && args.no_ui);
+        assert_eq!(args.midi_in.as_deref(), Some("Faderfox EC4"));
+        assert_eq!(args.osc_port, Some(9000));
+    }
+
+    #[test]
+    fn test_external_ui() {
+        let args = Args::try_parse_from([
+            "xtal",
+            "--external-ui",
+            "--ipc-path",
+            "/tmp/xtal.ipc",
+        ])
+        .unwrap();
+        assert!(args.external_ui);
+        assert_eq!(args.ipc_path, Some(PathBuf::from("/tmp/xtal.ipc")));
+
+        assert!(
+            Args::try_parse_from(["xtal", "--external-ui", "--no-ui"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        assert!(Args::try_parse_from(["xtal", "--size", "0x100"]).is_err());
+        assert!(Args::try_parse_from(["xtal", "--size", "big"]).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! A minimal egui window listing the current sketch's controls, used in place
+//! of the web view when launched with `--no-ui`. It runs in the main process
+//! so it's usable on machines that struggle with the web view, or when the
+//! frontend is broken. Sliders can be dragged or focused with Tab and nudged
+//! with the arrow keys.
+
+use nannou::prelude::*;
+use nannou::winit::event::WindowEvent;
+use nannou_egui::{Egui, egui};
+use std::time::Duration;
+
+use super::app::AppEvent;
+use super::web_view::{Control, ControlKind};
+use crate::framework::prelude::*;
+
+/// What the panel displays on a given frame
+pub struct ControlPanelState<'a> {
+    pub sketch_name: &'a str,
+    pub fps: f32,
+    pub bpm: f32,
+    pub controls: &'a [Control],
+}
+
+pub struct ControlPanel {
+    egui: Egui,
+    window_id: window::Id,
+    visible: bool,
+    /// The last alert, since there's no web view footer to show it in
+    alert: String,
+}
+
+impl ControlPanel {
+    pub fn new(window: &Window) -> Self {
+        Self {
+            egui: Egui::from_window(window),
+            window_id: window.id(),
+            visible: true,
+            alert: String::new(),
+        }
+    }
+
+    pub fn window_id(&self) -> window::Id {
+        self.window_id
+    }
+
+    pub fn set_alert(&mut self, text: String) {
+        self.alert = text;
+    }
+
+    /// True while a widget is capturing keystrokes, in which case app
+    /// shortcuts should be ignored
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.egui.ctx().wants_keyboard_input()
+    }
+
+    pub fn handle_raw_event(&mut self, event: &WindowEvent) {
+        self.egui.handle_raw_event(event);
+    }
+
+    pub fn draw(&self, frame: &Frame) {
+        frame.clear(BLACK);
+        if let Err(e) = self.egui.draw_to_frame(frame) {
+            error!("Unable to draw control panel: {:?}", e);
+        }
+    }
+
+    pub fn toggle_visibility(&mut self, app: &App) {
+        if let Some(window) = app.window(self.window_id) {
+            self.visible = !self.visible;
+            window.set_visible(self.visible);
+        }
+    }
+
+    /// Builds the panel for this frame and returns any control edits as
+    /// [`AppEvent::UpdateUiControl`] events
+    pub fn update(
+        &mut self,
+        since_start: Duration,
+        state: ControlPanelState,
+    ) -> Vec<AppEvent> {
+        let mut events = vec![];
+
+        self.egui.set_elapsed_time(since_start);
+        let ctx = self.egui.begin_frame();
+
+        egui::CentralPanel::default().show(&ctx, |ui| {
+            ui.heading(state.sketch_name);
+            ui.label(format!("{:.1} FPS, {:.1} BPM", state.fps, state.bpm));
+            if !self.alert.is_empty() {
+                ui.label(&self.alert);
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for control in state.controls {
+                    ui.add_enabled_ui(!control.disabled, |ui| {
+                        if let Some(value) = control_ui(ui, control) {
+                            events.push(AppEvent::UpdateUiControl((
+                                control.name.clone(),
+                                value,
+                            )));
+                        }
+                    });
+                }
+            });
+        });
+
+        events
+    }
+}
+
+/// Draws a single control, returning its new value when edited
+fn control_ui(ui: &mut egui::Ui, control: &Control) -> Option<ControlValue> {
+    match control.kind {
+        ControlKind::Checkbox => {
+            let mut checked = control.value == "true";
+            ui.checkbox(&mut checked, &control.name)
+                .changed()
+                .then_some(ControlValue::Bool(checked))
+        }
+        ControlKind::Select => {
+            let mut selected = None;
+            egui::ComboBox::from_label(&control.name)
+                .selected_text(&control.value)
+                .show_ui(ui, |ui| {
+                    for option in &control.options {
+                        let is_selected = *option == control.value;
+                        if ui.selectable_label(is_selected, option).clicked()
+                            && !is_selected
+                        {
+                            selected = Some(option.clone());
+                        }
+                    }
+                });
+            selected.map(ControlValue::String)
+        }
+        ControlKind::Separator => {
+            ui.separator();
+            None
+        }
+        ControlKind::Slider => {
+            let mut value = control.value.parse::<f32>().unwrap_or(control.min);
+            let mut slider =
+                egui::Slider::new(&mut value, control.min..=control.max)
+                    .step_by(control.step as f64)
+                    .text(&control.name);
+            if let Some(display) = &control.display {
+                slider = slider
+                    .custom_formatter(|v, _| display.format(v as f32))
+                    .custom_parser(|text| display.parse(text).map(f64::from));
+            }
+            ui.add(slider)
+                .changed()
+                .then_some(ControlValue::Float(value))
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! A hand-written list of cues for theater-style shows. Each cue bundles
+//! actions – switch sketch, recall a snapshot, set controls – that run
+//! together when it's fired. Cues are stepped with the `C` key (`Shift C`
+//! steps back), optional MIDI notes on the control input port, or the OSC
+//! addresses `/cue/next`, `/cue/previous`, and `/cue/go` (with a cue's
+//! number or name). The list lives in `cues.yaml` in the user data dir and is
+//! re-read before each step so it can be edited during rehearsal.
+//!
+//! ```yaml
+//! next_note: [0, 60]
+//! cues:
+//!   - name: opening
+//!     sketch: physarum_template
+//!     snapshot: intro
+//!     transition_time: 8
+//!   - name: storm
+//!     controls:
+//!       hue: 0.7
+//!       exposure: 2
+//! ```
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// A MIDI channel and note number
+pub type ChannelAndNote = (u8, u8);
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Cue {
+    /// Shown when the cue fires and usable with `/cue/go`
+    pub name: Option<String>,
+    /// Switched to first unless already running
+    pub sketch: Option<String>,
+    pub snapshot: Option<String>,
+    /// Overrides the global transition time when recalling [`Self::snapshot`]
+    pub transition_time: Option<f32>,
+    /// Values in each control's own range, set after the snapshot is recalled
+    pub controls: IndexMap<String, f32>,
+}
+
+impl Cue {
+    /// The cue's name, falling back to its sketch
+    pub fn label(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.sketch.as_deref())
+            .unwrap_or("")
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CueList {
+    pub cues: Vec<Cue>,
+
+    /// Fires the next cue on each Note On of this note
+    pub next_note: Option<ChannelAndNote>,
+
+    /// Fires the previous cue on each Note On of this note
+    pub previous_note: Option<ChannelAndNote>,
+
+    /// Index of the cue that fired last; `None` before the first
+    #[serde(skip)]
+    pub position: Option<usize>,
+}
+
+impl CueList {
+    pub fn current(&self) -> Option<&Cue> {
+        self.position.and_then(|position| self.cues.get(position))
+    }
+
+    /// Advances to and returns the next cue, or `None` (without moving) when
+    /// already at the end
+    pub fn next_cue(&mut self) -> Option<&Cue> {
+        let next = self.position.map_or(0, |position| position + 1);
+        self.go_to(next)
+    }
+
+    /// Steps back to and returns the previous cue, or `None` (without moving)
+    /// when already at the start
+    pub fn previous_cue(&mut self) -> Option<&Cue> {
+        let previous = self.position?.checked_sub(1)?;
+        self.go_to(previous)
+    }
+
+    pub fn go_to(&mut self, position: usize) -> Option<&Cue> {
+        if position >= self.cues.len() {
+            return None;
+        }
+        self.position = Some(position);
+        self.cues.get(position)
+    }
+
+    /// Position of the first cue named `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.cues
+            .iter()
+            .position(|cue| cue.name.as_deref() == Some(name))
+    }
+
+    /// Replaces the cues and bindings while keeping the position when it is
+    /// still in range
+    pub fn update(&mut self, other: CueList) {
+        self.cues = other.cues;
+        self.next_note = other.next_note;
+        self.previous_note = other.previous_note;
+        self.position = self.position.filter(|p| *p < self.cues.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> CueList {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cue_list = parse(
+            r#"
+next_note: [1, 60]
+cues:
+  - name: opening
+    sketch: foo
+    snapshot: intro
+    transition_time: 8
+  - controls:
+      hue: 0.7
+"#,
+        );
+
+        assert_eq!(cue_list.next_note, Some((1, 60)));
+        assert_eq!(cue_list.previous_note, None);
+        assert_eq!(cue_list.cues[0].label(), "opening");
+        assert_eq!(cue_list.cues[0].transition_time, Some(8.0));
+        assert_eq!(cue_list.cues[1].controls.get("hue"), Some(&0.7));
+        assert_eq!(cue_list.cues[1].label(), "");
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut cue_list = parse(
+            r#"
+cues:
+  - name: a
+  - name: b
+"#,
+        );
+
+        assert!(cue_list.current().is_none());
+        assert!(cue_list.previous_cue().is_none());
+        assert_eq!(cue_list.next_cue().unwrap().label(), "a");
+        assert_eq!(cue_list.next_cue().unwrap().label(), "b");
+        assert!(cue_list.next_cue().is_none());
+        assert_eq!(cue_list.previous_cue().unwrap().label(), "a");
+        assert_eq!(cue_list.find("b"), Some(1));
+        assert_eq!(cue_list.find("c"), None);
+    }
+
+    #[test]
+    fn test_update_keeps_position() {
+        let mut cue_list = parse("cues: [{ name: a }, { name: b }]");
+        cue_list.go_to(1);
+
+        cue_list.update(parse("cues: [{ name: a }, { name: b }, { name: c }]"));
+        assert_eq!(cue_list.position, Some(1));
+
+        cue_list.update(parse("cues: [{ name: a }]"));
+        assert!(cue_list.position.is_none());
+    }
+}
@@ -0,0 +1,171 @@
+//! Captures a burst of frames spaced a number of beats apart and lays them out
+//! left-to-right, top-to-bottom on a single contact sheet image – a way to
+//! show motion in a still format for posts and print.
+
+use nannou::image::{self, GenericImage, Rgba, RgbaImage};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::storage::cache_dir;
+use crate::framework::frame_controller;
+use crate::framework::prelude::*;
+
+/// Space between and around frames in pixels
+const GUTTER: u32 = 16;
+
+#[derive(Debug)]
+pub struct Flipbook {
+    frame_count: usize,
+    start_frame: u32,
+    frames_per_capture: f32,
+    dir: PathBuf,
+    captured: RefCell<Vec<PathBuf>>,
+}
+
+impl Flipbook {
+    /// Prepares to capture `frame_count` frames `beats` apart starting with
+    /// the next rendered frame
+    pub fn new(
+        frame_count: usize,
+        beats: f32,
+        bpm: f32,
+    ) -> Result<Self, Box<dyn Error>> {
+        if frame_count == 0 {
+            return Err("Flipbook requires at least one frame".into());
+        }
+
+        let dir = cache_dir()
+            .ok_or("Unable to access cache dir")?
+            .join(format!("flipbook-{}", uuid_5()));
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            frame_count,
+            start_frame: frame_controller::frame_count(),
+            frames_per_capture: (beats * 60.0 / bpm * frame_controller::fps())
+                .max(1.0),
+            dir,
+            captured: RefCell::new(vec![]),
+        })
+    }
+
+    /// Returns a path to capture the current frame to if one is due
+    pub fn next_capture_path(&self) -> Option<PathBuf> {
+        let mut captured = self.captured.borrow_mut();
+        let index = captured.len();
+        let elapsed =
+            frame_controller::frame_count().saturating_sub(self.start_frame);
+
+        if self.is_complete_at(index)
+            || (elapsed as f32) < index as f32 * self.frames_per_capture
+        {
+            return None;
+        }
+
+        let path = self.dir.join(format!("frame-{:03}.png", index));
+        captured.push(path.clone());
+        Some(path)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.is_complete_at(self.captured.borrow().len())
+    }
+
+    fn is_complete_at(&self, captured: usize) -> bool {
+        captured >= self.frame_count
+    }
+
+    /// Composites the captured frames to `path` and removes them. Frame
+    /// captures are written asynchronously so make sure they have finished
+    /// (see `FrameCapture::await_saves`) before calling
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let frames = self
+            .captured
+            .borrow()
+            .iter()
+            .map(|path| Ok(image::open(path)?.to_rgba8()))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        contact_sheet(&frames)?.save(path)?;
+
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            warn!("Unable to remove flipbook frames {:?}: {}", self.dir, e);
+        }
+
+        Ok(())
+    }
+}
+
+/// Lays `frames` out in the squarest grid that fits them on a black
+/// background. All frames are expected to share the first frame's size
+pub fn contact_sheet(
+    frames: &[RgbaImage],
+) -> Result<RgbaImage, Box<dyn Error>> {
+    let first = frames.first().ok_or("No frames to composite")?;
+    let (w, h) = first.dimensions();
+
+    let columns = (frames.len() as f32).sqrt().ceil() as u32;
+    let rows = (frames.len() as u32).div_ceil(columns);
+
+    let mut sheet = RgbaImage::from_pixel(
+        columns * (w + GUTTER) + GUTTER,
+        rows * (h + GUTTER) + GUTTER,
+        Rgba([0, 0, 0, 255]),
+    );
+
+    for (i, frame) in frames.iter().enumerate() {
+        if frame.dimensions() != (w, h) {
+            return Err("Flipbook frames changed size mid-capture".into());
+        }
+        let i = i as u32;
+        let x = GUTTER + (i % columns) * (w + GUTTER);
+        let y = GUTTER + (i / columns) * (h + GUTTER);
+        sheet.copy_from(frame, x, y)?;
+    }
+
+    Ok(sheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(4, 2, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let frames: Vec<_> = (1..=5).map(|i| frame(i * 10)).collect();
+        let sheet = contact_sheet(&frames).unwrap();
+
+        // 5 frames -> 3 columns, 2 rows
+        assert_eq!(
+            sheet.dimensions(),
+            (3 * (4 + GUTTER) + GUTTER, 2 * (2 + GUTTER) + GUTTER)
+        );
+        assert_eq!(sheet.get_pixel(0, 0), &Rgba([0, 0, 0, 255]));
+        assert_eq!(sheet.get_pixel(GUTTER, GUTTER), &Rgba([10, 10, 10, 255]));
+        assert_eq!(
+            sheet.get_pixel(GUTTER * 2 + 4, GUTTER),
+            &Rgba([20, 20, 20, 255])
+        );
+        assert_eq!(
+            sheet.get_pixel(GUTTER * 2 + 4, GUTTER * 2 + 2),
+            &Rgba([50, 50, 50, 255])
+        );
+        // Unused last cell stays background
+        assert_eq!(
+            sheet.get_pixel(GUTTER * 3 + 8, GUTTER * 2 + 2),
+            &Rgba([0, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn test_contact_sheet_errors() {
+        assert!(contact_sheet(&[]).is_err());
+        assert!(contact_sheet(&[frame(0), RgbaImage::new(1, 1)]).is_err());
+    }
+}
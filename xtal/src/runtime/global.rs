@@ -2,6 +2,8 @@ use directories_next::{BaseDirs, UserDirs};
 use std::error::Error;
 use std::sync::{LazyLock, Mutex};
 
+#[cfg(feature = "runtime")]
+use crate::framework::depth::list_depth_devices;
 use crate::framework::prelude::*;
 
 const DEFAULT_OSC_PORT: u16 = 2346;
@@ -25,6 +27,23 @@ pub fn set_audio_device_name(name: &str) {
     );
 }
 
+#[cfg(feature = "runtime")]
+pub fn depth_device_name() -> Option<String> {
+    let global = GLOBAL.lock().unwrap();
+    global.depth_device_name.clone()
+}
+
+#[cfg(feature = "runtime")]
+pub fn set_depth_device_name(name: &str) {
+    let mut global = GLOBAL.lock().unwrap();
+    global.depth_device_name = set_device_or_fallback(
+        "Depth device",
+        name,
+        list_depth_devices,
+        |name| name,
+    );
+}
+
 pub fn images_dir() -> String {
     let global = GLOBAL.lock().unwrap();
     global.images_dir.clone()
@@ -35,6 +54,34 @@ pub fn set_images_dir(dir: &str) {
     global.images_dir = dir.to_string();
 }
 
+/// Whether anything being built should leave the shared MIDI and OSC listeners
+/// alone. See [`without_inputs`]
+pub fn inputs_deferred() -> bool {
+    let global = GLOBAL.lock().unwrap();
+    global.inputs_deferred
+}
+
+/// Runs `f` such that the control hubs and timing sources it builds don't take
+/// the shared MIDI and OSC listeners from whatever currently has them, e.g. to
+/// build a sketch ahead of time without disturbing the live one. Hand them
+/// over later with [`ControlHub::connect_inputs`]
+pub fn without_inputs<R>(f: impl FnOnce() -> R) -> R {
+    GLOBAL.lock().unwrap().inputs_deferred = true;
+    let result = f();
+    GLOBAL.lock().unwrap().inputs_deferred = false;
+    result
+}
+
+pub fn latency_offsets() -> LatencyOffsets {
+    let global = GLOBAL.lock().unwrap();
+    global.latency_offsets
+}
+
+pub fn set_latency_offsets(latency_offsets: LatencyOffsets) {
+    let mut global = GLOBAL.lock().unwrap();
+    global.latency_offsets = latency_offsets;
+}
+
 pub fn midi_clock_port() -> Option<String> {
     let global = GLOBAL.lock().unwrap();
     global.midi_clock_port.clone()
@@ -57,7 +104,7 @@ pub fn midi_control_in_port() -> Option<String> {
 
 pub fn set_midi_control_in_port(port: &str) {
     let mut global = GLOBAL.lock().unwrap();
-    global.midi_clock_port = set_device_or_fallback(
+    global.midi_control_in_port = set_device_or_fallback(
         "MIDI control in port",
         port,
         midi::list_input_ports,
@@ -72,10 +119,10 @@ pub fn midi_control_out_port() -> Option<String> {
 
 pub fn set_midi_control_out_port(port: &str) {
     let mut global = GLOBAL.lock().unwrap();
-    global.midi_clock_port = set_device_or_fallback(
+    global.midi_control_out_port = set_device_or_fallback(
         "MIDI control out port",
         port,
-        midi::list_input_ports,
+        midi::list_output_ports,
         |(_, name)| name,
     );
 }
@@ -90,6 +137,17 @@ pub fn set_osc_port(port: u16) {
     global.osc_port = port;
 }
 
+/// The `timing` command line argument. See [`crate::motion::Timing`]
+pub fn timing_source() -> String {
+    let global = GLOBAL.lock().unwrap();
+    global.timing_source.clone()
+}
+
+pub fn set_timing_source(timing_source: &str) {
+    let mut global = GLOBAL.lock().unwrap();
+    global.timing_source = timing_source.to_string();
+}
+
 pub fn user_data_dir() -> String {
     let global = GLOBAL.lock().unwrap();
     global.user_data_dir.clone()
@@ -112,11 +170,16 @@ pub fn set_videos_dir(dir: &str) {
 
 pub struct Global {
     audio_device_name: Option<String>,
+    #[cfg(feature = "runtime")]
+    depth_device_name: Option<String>,
     images_dir: String,
+    inputs_deferred: bool,
+    latency_offsets: LatencyOffsets,
     midi_clock_port: Option<String>,
     midi_control_in_port: Option<String>,
     midi_control_out_port: Option<String>,
     osc_port: u16,
+    timing_source: String,
     user_data_dir: String,
     videos_dir: String,
 }
@@ -127,6 +190,11 @@ impl Default for Global {
             .ok()
             .and_then(|devices| devices.first().cloned());
 
+        #[cfg(feature = "runtime")]
+        let depth_device_name = list_depth_devices()
+            .ok()
+            .and_then(|devices| devices.first().cloned());
+
         let midi_input_port = midi::list_input_ports()
             .ok()
             .and_then(|ports| ports.first().map(|(_, name)| name.clone()));
@@ -137,11 +205,16 @@ impl Default for Global {
 
         Self {
             audio_device_name,
+            #[cfg(feature = "runtime")]
+            depth_device_name,
             images_dir: user_dir(|ud| ud.picture_dir(), "Images"),
+            inputs_deferred: false,
+            latency_offsets: LatencyOffsets::default(),
             midi_clock_port: midi_input_port.clone(),
             midi_control_in_port: midi_input_port,
             midi_control_out_port: midi_output_port,
             osc_port: DEFAULT_OSC_PORT,
+            timing_source: "frame".to_string(),
             user_data_dir: user_dir(|ud| ud.document_dir(), "SketchData"),
             videos_dir: user_dir(|ud| ud.video_dir(), "Videos"),
         }
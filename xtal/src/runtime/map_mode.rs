@@ -1,12 +1,16 @@
 //! Provides runtime mapping of MIDI CCs to UI sliders, AKA "MIDI learn"
+#[cfg(feature = "runtime")]
 use std::error::Error;
+#[cfg(feature = "runtime")]
 use std::fmt;
+#[cfg(feature = "runtime")]
 use std::sync::{Arc, Mutex};
 
 use crate::framework::prelude::*;
 
 pub type Mappings = HashMap<String, ChannelAndController>;
 
+#[cfg(feature = "runtime")]
 pub struct MapModeState {
     mappings: Mappings,
     /// Used to store the MSB of an MSB/LSB pair used in 14bit MIDI (CCs 0-31)
@@ -16,10 +20,13 @@ pub struct MapModeState {
 /// Provides live MIDI mapping functionality
 pub struct MapMode {
     /// The name of the current slider that has been selected for live mapping
+    #[cfg(feature = "runtime")]
     pub currently_mapping: Option<String>,
+    #[cfg(feature = "runtime")]
     pub state: Arc<Mutex<MapModeState>>,
 }
 
+#[cfg(feature = "runtime")]
 impl Default for MapMode {
     fn default() -> Self {
         Self {
@@ -54,7 +61,10 @@ impl MapMode {
     pub fn is_proxy_name(name: &str) -> bool {
         name.ends_with(Self::PROXY_NAME_SUFFIX)
     }
+}
 
+#[cfg(feature = "runtime")]
+impl MapMode {
     pub fn mappings(&self) -> Mappings {
         let state = self.state.lock().unwrap();
         state.mappings.clone()
@@ -212,12 +222,14 @@ impl MapMode {
     }
 }
 
+#[cfg(feature = "runtime")]
 #[derive(Debug)]
 pub enum MappingError {
     DuplicateMappings(Vec<String>),
     ConsecutiveHrccMsb,
 }
 
+#[cfg(feature = "runtime")]
 impl fmt::Display for MappingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -236,4 +248,5 @@ impl fmt::Display for MappingError {
     }
 }
 
+#[cfg(feature = "runtime")]
 impl std::error::Error for MappingError {}
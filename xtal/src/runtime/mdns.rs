@@ -0,0 +1,349 @@
+//! A minimal multicast DNS responder ([RFC 6762]/[RFC 6763]) that advertises
+//! a single DNS-SD service. Just enough for zeroconf clients to find
+//! [`super::osc_query`] without depending on a system daemon or library.
+//!
+//! [RFC 6762]: https://www.rfc-editor.org/rfc/rfc6762
+//! [RFC 6763]: https://www.rfc-editor.org/rfc/rfc6763
+
+use socket2::{Domain, Protocol, Socket, Type};
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+use crate::framework::prelude::*;
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+const TTL: u32 = 120;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+
+const CLASS_IN: u16 = 1;
+/// Set on records only we can answer for so caches replace rather than merge
+const CACHE_FLUSH: u16 = 0x8000;
+
+#[derive(Clone, Debug)]
+pub struct ServiceAdvertisement {
+    /// Human readable instance name, e.g. "Xtal"
+    pub instance: String,
+    /// e.g. "_oscjson._tcp"
+    pub service_type: String,
+    pub port: u16,
+    pub txt: Vec<String>,
+    pub ip: Ipv4Addr,
+}
+
+impl ServiceAdvertisement {
+    fn service_name(&self) -> String {
+        format!("{}.local", self.service_type)
+    }
+
+    fn instance_name(&self) -> String {
+        format!("{}.{}.local", self.instance, self.service_type)
+    }
+
+    fn host_name(&self) -> String {
+        let ip = self.ip.to_string().replace('.', "-");
+        format!("xtal-{}.local", ip)
+    }
+
+    /// A response packet carrying every record for the service
+    fn response(&self) -> Vec<u8> {
+        let mut packet = vec![];
+        // ID, flags (authoritative response), 0 questions, 4 answers
+        for value in [0, 0x8400, 0, 4, 0, 0] {
+            write_u16(&mut packet, value);
+        }
+
+        let mut ptr = vec![];
+        write_name(&mut ptr, &self.instance_name());
+        write_record(
+            &mut packet,
+            &self.service_name(),
+            TYPE_PTR,
+            CLASS_IN,
+            &ptr,
+        );
+
+        let mut srv = vec![];
+        for value in [0, 0, self.port] {
+            write_u16(&mut srv, value);
+        }
+        write_name(&mut srv, &self.host_name());
+        write_record(
+            &mut packet,
+            &self.instance_name(),
+            TYPE_SRV,
+            CLASS_IN | CACHE_FLUSH,
+            &srv,
+        );
+
+        let mut txt = vec![];
+        for entry in &self.txt {
+            txt.push(entry.len().min(255) as u8);
+            txt.extend(entry.bytes().take(255));
+        }
+        if txt.is_empty() {
+            // A TXT record must contain at least one (empty) string
+            txt.push(0);
+        }
+        write_record(
+            &mut packet,
+            &self.instance_name(),
+            TYPE_TXT,
+            CLASS_IN | CACHE_FLUSH,
+            &txt,
+        );
+
+        write_record(
+            &mut packet,
+            &self.host_name(),
+            TYPE_A,
+            CLASS_IN | CACHE_FLUSH,
+            &self.ip.octets(),
+        );
+
+        packet
+    }
+
+    /// True if `packet` is a query containing a question about any of our
+    /// records
+    fn should_answer(&self, packet: &[u8]) -> bool {
+        let names = [
+            (self.service_name(), TYPE_PTR),
+            (self.instance_name(), TYPE_SRV),
+            (self.instance_name(), TYPE_TXT),
+            (self.host_name(), TYPE_A),
+        ];
+
+        questions(packet).iter().any(|(name, kind)| {
+            names.iter().any(|(ours, our_kind)| {
+                name.eq_ignore_ascii_case(ours)
+                    && (kind == our_kind || *kind == TYPE_ANY)
+            })
+        })
+    }
+}
+
+/// Advertises `service` on a background thread for the lifetime of the
+/// process: announces it twice at startup (per the RFC) then answers matching
+/// queries
+pub fn advertise(service: ServiceAdvertisement) -> Result<(), Box<dyn Error>> {
+    let socket = bind()?;
+    let response = service.response();
+    let destination = SocketAddr::from((MDNS_ADDR, MDNS_PORT));
+
+    thread::spawn(move || {
+        let mut announcements = 0;
+        let mut buffer = [0; 9_000];
+
+        loop {
+            if announcements < 2 {
+                if let Err(e) = socket.send_to(&response, destination) {
+                    warn!("Unable to send mDNS announcement: {}", e);
+                }
+                announcements += 1;
+            }
+
+            match socket.recv_from(&mut buffer) {
+                Ok((len, _)) if service.should_answer(&buffer[..len]) => {
+                    if let Err(e) = socket.send_to(&response, destination) {
+                        warn!("Unable to send mDNS response: {}", e);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+
+    info!("Advertising {} via mDNS", service.instance_name());
+
+    Ok(())
+}
+
+/// The address other hosts on the LAN most likely reach us on
+pub fn local_ipv4() -> Option<Ipv4Addr> {
+    // Connecting a UDP socket sends nothing but selects a route
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((MDNS_ADDR, MDNS_PORT)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_unspecified() => Some(ip),
+        _ => None,
+    }
+}
+
+fn bind() -> Result<UdpSocket, Box<dyn Error>> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+
+    // Share the port with the OS responder (mDNSResponder, Avahi) if any
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+
+    socket
+        .bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+
+    let socket: UdpSocket = socket.into();
+    socket.join_multicast_v4(&MDNS_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    Ok(socket)
+}
+
+fn write_u16(packet: &mut Vec<u8>, value: u16) {
+    packet.extend(value.to_be_bytes());
+}
+
+fn write_name(packet: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        packet.push(label.len().min(63) as u8);
+        packet.extend(label.bytes().take(63));
+    }
+    packet.push(0);
+}
+
+fn write_record(
+    packet: &mut Vec<u8>,
+    name: &str,
+    kind: u16,
+    class: u16,
+    data: &[u8],
+) {
+    write_name(packet, name);
+    write_u16(packet, kind);
+    write_u16(packet, class);
+    packet.extend(TTL.to_be_bytes());
+    write_u16(packet, data.len() as u16);
+    packet.extend(data);
+}
+
+/// `(name, type)` of each question in a query. Empty for responses and
+/// malformed packets
+fn questions(packet: &[u8]) -> Vec<(String, u16)> {
+    let read_u16 = |offset: usize| {
+        packet
+            .get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+
+    let is_query = read_u16(2).is_some_and(|flags| flags & 0x8000 == 0);
+    let count = read_u16(4).unwrap_or(0);
+    if !is_query {
+        return vec![];
+    }
+
+    let mut questions = vec![];
+    let mut offset = 12;
+    for _ in 0..count {
+        let Some((name, end)) = read_name(packet, offset) else {
+            break;
+        };
+        let Some(kind) = read_u16(end) else {
+            break;
+        };
+        questions.push((name, kind));
+        // Skip type and class
+        offset = end + 4;
+    }
+    questions
+}
+
+/// Reads a possibly compressed name starting at `offset`, returning it and
+/// the offset just past it
+fn read_name(packet: &[u8], offset: usize) -> Option<(String, usize)> {
+    let mut labels = vec![];
+    let mut position = offset;
+    let mut end = None;
+
+    // Bounded to guard against pointer loops
+    for _ in 0..128 {
+        let len = *packet.get(position)? as usize;
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(position + 1)));
+        }
+        if len & 0xc0 == 0xc0 {
+            let pointer =
+                (len & 0x3f) << 8 | *packet.get(position + 1)? as usize;
+            end.get_or_insert(position + 2);
+            position = pointer;
+            continue;
+        }
+        let label = packet.get(position + 1..position + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        position += 1 + len;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> ServiceAdvertisement {
+        ServiceAdvertisement {
+            instance: "Xtal".to_string(),
+            service_type: "_oscjson._tcp".to_string(),
+            port: 2347,
+            txt: vec!["txtvers=1".to_string()],
+            ip: Ipv4Addr::new(192, 168, 1, 20),
+        }
+    }
+
+    fn query(name: &str, kind: u16) -> Vec<u8> {
+        let mut packet = vec![];
+        for value in [0, 0, 1, 0, 0, 0] {
+            write_u16(&mut packet, value);
+        }
+        write_name(&mut packet, name);
+        write_u16(&mut packet, kind);
+        write_u16(&mut packet, CLASS_IN);
+        packet
+    }
+
+    #[test]
+    fn test_should_answer() {
+        let service = service();
+        assert!(service.should_answer(&query("_oscjson._tcp.local", TYPE_PTR)));
+        assert!(
+            service.should_answer(&query("xtal._OSCJSON._tcp.local", TYPE_ANY))
+        );
+        assert!(
+            service.should_answer(&query("xtal-192-168-1-20.local", TYPE_A))
+        );
+        assert!(!service.should_answer(&query("_http._tcp.local", TYPE_PTR)));
+        assert!(!service.should_answer(&service.response()));
+    }
+
+    #[test]
+    fn test_compressed_question() {
+        // "_oscjson._tcp.local" then a question pointing back into it
+        let mut packet = query("_oscjson._tcp.local", TYPE_SRV);
+        packet[5] = 2;
+        packet.extend([4, b'X', b't', b'a', b'l', 0xc0, 12]);
+        write_u16(&mut packet, TYPE_SRV);
+        write_u16(&mut packet, CLASS_IN);
+
+        let questions = questions(&packet);
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[1].0, "Xtal._oscjson._tcp.local");
+        assert!(service().should_answer(&packet));
+    }
+
+    #[test]
+    fn test_response() {
+        let packet = service().response();
+        assert_eq!(&packet[..12], &[0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0]);
+
+        let (name, end) = read_name(&packet, 12).unwrap();
+        assert_eq!(name, "_oscjson._tcp.local");
+        assert_eq!(&packet[end..end + 4], &[0, 12, 0, 1]);
+        assert_eq!(&packet[packet.len() - 4..], &[192, 168, 1, 20]);
+    }
+}
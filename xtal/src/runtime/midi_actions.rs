@@ -0,0 +1,198 @@
+//! Runtime actions that can be bound to MIDI CCs from **Settings > MIDI
+//! Actions**, independent of any sketch. Bindings are learned like slider
+//! mappings (see [`crate::runtime::map_mode`]) but are global since they
+//! control the runtime rather than a sketch's parameters.
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::framework::prelude::*;
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Deserialize,
+    Serialize,
+)]
+pub enum MidiAction {
+    /// Lowers the BPM by [`BPM_NUDGE`] each time the CC crosses 64
+    BpmDown,
+    /// Raises the BPM by [`BPM_NUDGE`] each time the CC crosses 64
+    BpmUp,
+    /// Taps the tempo each time the CC crosses 64
+    Tap,
+    /// Picks a transition time from [`TRANSITION_TIMES`] across the CC's range
+    TransitionTime,
+}
+
+pub type MidiActionMappings = HashMap<MidiAction, ChannelAndController>;
+
+pub const BPM_NUDGE: f32 = 1.0;
+
+/// The transition times (in beats) offered by the UI, shortest first
+pub const TRANSITION_TIMES: [f32; 14] = [
+    0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0,
+];
+
+/// Spreads [`TRANSITION_TIMES`] evenly over the 7bit CC range
+pub fn transition_time_from_cc(value: u8) -> f32 {
+    let last = TRANSITION_TIMES.len() - 1;
+    let index = (value.min(127) as usize * last + 63) / 127;
+    TRANSITION_TIMES[index]
+}
+
+/// The inverse of [`transition_time_from_cc`] for the closest listed time, used
+/// to send the current value back to the controller
+pub fn transition_time_to_cc(time: f32) -> u8 {
+    let last = TRANSITION_TIMES.len() - 1;
+    let index = TRANSITION_TIMES
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            (*a - time).abs().total_cmp(&(*b - time).abs())
+        })
+        .map_or(0, |(index, _)| index);
+    ((index * 127 + last / 2) / last) as u8
+}
+
+/// Turns raw Control Change messages into actions. Button-like actions fire
+/// once when the CC crosses from below 64 to 64 or above, so both momentary
+/// and toggle buttons work
+#[derive(Debug, Default)]
+pub struct MidiActionDispatcher {
+    lookup: HashMap<ChannelAndController, MidiAction>,
+    high: HashMap<MidiAction, bool>,
+}
+
+impl MidiActionDispatcher {
+    pub fn new(mappings: &MidiActionMappings) -> Self {
+        Self {
+            lookup: mappings.iter().map(|(a, ch_cc)| (*ch_cc, *a)).collect(),
+            high: HashMap::default(),
+        }
+    }
+
+    /// Returns the action bound to `message` along with the CC value when it
+    /// should fire
+    pub fn dispatch(&mut self, message: &[u8]) -> Option<(MidiAction, u8)> {
+        if message.len() < 3 || !midi::is_control_change(message[0]) {
+            return None;
+        }
+
+        let action = *self.lookup.get(&(message[0] & 0x0F, message[1]))?;
+        let value = message[2];
+
+        if action == MidiAction::TransitionTime {
+            return Some((action, value));
+        }
+
+        let high = value >= 64;
+        let was_high = self.high.insert(action, high).unwrap_or(false);
+        (high && !was_high).then_some((action, value))
+    }
+}
+
+/// Listens on the MIDI control input port for the bound actions, calling
+/// `callback` for each one that fires
+pub fn listen<F>(
+    mappings: &MidiActionMappings,
+    callback: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(MidiAction, u8) + Send + Sync + 'static,
+{
+    let Some(port) = crate::global::midi_control_in_port() else {
+        midi::disconnect(midi::ConnectionType::Action);
+        return Ok(());
+    };
+
+    if mappings.is_empty() {
+        midi::disconnect(midi::ConnectionType::Action);
+        return Ok(());
+    }
+
+    let dispatcher = Mutex::new(MidiActionDispatcher::new(mappings));
+
+    midi::on_message(midi::ConnectionType::Action, &port, move |_, message| {
+        if let Some((action, value)) =
+            dispatcher.lock().unwrap().dispatch(message)
+        {
+            callback(action, value);
+        }
+    })
+}
+
+/// Calls `callback` with the channel and controller of the next Control
+/// Change received on the MIDI control input port. Shares the connection used
+/// by slider mapping so only one of them can be learning at a time
+pub fn learn<F>(callback: F) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(ChannelAndController) + Send + Sync + 'static,
+{
+    let Some(port) = crate::global::midi_control_in_port() else {
+        warn!(
+            "Skipping {} listener setup; no MIDI port.",
+            midi::ConnectionType::Mapping
+        );
+        return Ok(());
+    };
+
+    let learned = AtomicBool::new(false);
+
+    midi::on_message(midi::ConnectionType::Mapping, &port, move |_, message| {
+        if message.len() < 3 || !midi::is_control_change(message[0]) {
+            return;
+        }
+        if !learned.swap(true, Ordering::Relaxed) {
+            callback((message[0] & 0x0F, message[1]));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_time_cc() {
+        assert_eq!(transition_time_from_cc(0), 0.25);
+        assert_eq!(transition_time_from_cc(127), 32.0);
+        for (index, time) in TRANSITION_TIMES.iter().enumerate() {
+            let cc = transition_time_to_cc(*time);
+            assert_eq!(transition_time_from_cc(cc), *time, "index {}", index);
+        }
+        assert_eq!(transition_time_to_cc(5.0), transition_time_to_cc(4.0));
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let mut mappings = MidiActionMappings::default();
+        mappings.insert(MidiAction::Tap, (0, 10));
+        mappings.insert(MidiAction::TransitionTime, (1, 20));
+        let mut dispatcher = MidiActionDispatcher::new(&mappings);
+
+        assert_eq!(
+            dispatcher.dispatch(&[0xB0, 10, 127]),
+            Some((MidiAction::Tap, 127))
+        );
+        // Held high doesn't refire until released
+        assert_eq!(dispatcher.dispatch(&[0xB0, 10, 100]), None);
+        assert_eq!(dispatcher.dispatch(&[0xB0, 10, 0]), None);
+        assert!(dispatcher.dispatch(&[0xB0, 10, 127]).is_some());
+
+        assert_eq!(
+            dispatcher.dispatch(&[0xB1, 20, 0]),
+            Some((MidiAction::TransitionTime, 0))
+        );
+        assert_eq!(dispatcher.dispatch(&[0xB0, 20, 64]), None);
+        assert_eq!(dispatcher.dispatch(&[0x90, 10, 127]), None);
+    }
+}
@@ -1,10 +1,43 @@
+#[cfg(feature = "runtime")]
 pub mod app;
+#[cfg(feature = "runtime")]
+pub mod auto_bpm;
+#[cfg(feature = "runtime")]
+pub mod cli;
+#[cfg(feature = "runtime")]
+pub mod control_panel;
+#[cfg(feature = "runtime")]
+pub mod cue_list;
+#[cfg(feature = "runtime")]
+pub mod flipbook;
 pub mod global;
 pub mod map_mode;
+#[cfg(feature = "runtime")]
+pub mod midi_actions;
+#[cfg(feature = "runtime")]
+mod mdns;
+#[cfg(feature = "runtime")]
+pub mod network_sync;
+#[cfg(feature = "runtime")]
+pub mod osc_query;
+#[cfg(feature = "runtime")]
+pub mod output_warps;
+#[cfg(feature = "runtime")]
 pub mod recording;
+#[cfg(feature = "runtime")]
+pub mod recording_health;
+#[cfg(feature = "runtime")]
 pub mod registry;
 pub mod serialization;
+#[cfg(feature = "runtime")]
+pub mod set_list;
+#[cfg(feature = "runtime")]
+pub mod sketch_inputs;
+#[cfg(feature = "runtime")]
 pub mod storage;
+#[cfg(feature = "runtime")]
 pub mod tap_tempo;
+#[cfg(feature = "runtime")]
 pub mod web_view;
+#[cfg(feature = "runtime")]
 pub mod web_view_process;
@@ -0,0 +1,213 @@
+//! Keeps several instances in lockstep for multi-machine, multi-projector
+//! installations. A leader multicasts its frame count, BPM and current sketch
+//! every frame along with snapshot recalls as they happen; followers on the
+//! same network apply them as if they happened locally. Messages are small
+//! JSON datagrams so they're easy to inspect with any UDP tool.
+//!
+//! Delivery is best effort: a lost frame message is corrected by the next one
+//! and followers that join late or miss a sketch switch catch up on the next
+//! frame, but a dropped snapshot recall is not retried.
+
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::error::Error;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use super::app::{AppEvent, AppEventSender};
+use crate::framework::prelude::*;
+
+/// Administratively scoped (site-local) multicast group
+const SYNC_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 23, 48);
+const SYNC_PORT: u16 = 2348;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub enum SyncRole {
+    #[default]
+    Off,
+    Leader,
+    Follower,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum SyncMessage {
+    Frame {
+        frame_count: u32,
+        bpm: f32,
+        sketch: String,
+    },
+    SnapshotRecall(String),
+    /// See [`crate::prelude::ControlHub::recall_snapshot_only`]
+    SnapshotRecallOnly(String, Vec<String>),
+}
+
+impl SyncMessage {
+    fn encode(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NetworkSync {
+    role: SyncRole,
+    /// Only present when leading
+    socket: Option<UdpSocket>,
+    /// Cleared to stop the follower thread
+    listening: Option<Arc<AtomicBool>>,
+}
+
+impl NetworkSync {
+    pub fn role(&self) -> SyncRole {
+        self.role
+    }
+
+    /// Stops whatever the previous role was doing and starts the new one.
+    /// Received messages are forwarded as [`AppEvent::SyncReceived`]
+    pub fn set_role(
+        &mut self,
+        role: SyncRole,
+        app_tx: &AppEventSender,
+    ) -> Result<(), Box<dyn Error>> {
+        self.stop();
+
+        match role {
+            SyncRole::Off => {}
+            SyncRole::Leader => {
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+                // Stay on the local network
+                socket.set_multicast_ttl_v4(1)?;
+                // Lets a follower on the same machine hear us
+                socket.set_multicast_loop_v4(true)?;
+                self.socket = Some(socket);
+            }
+            SyncRole::Follower => {
+                self.listening = Some(Self::listen(app_tx.clone())?);
+            }
+        }
+
+        self.role = role;
+        info!("Network sync role: {:?}", role);
+
+        Ok(())
+    }
+
+    /// Sends `message` to followers. Does nothing unless leading
+    pub fn broadcast(&self, message: &SyncMessage) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+
+        let sent = message.encode().and_then(|bytes| {
+            Ok(socket.send_to(&bytes, (SYNC_ADDR, SYNC_PORT))?)
+        });
+
+        if let Err(e) = sent {
+            warn_once!("Unable to send network sync message: {}", e);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.socket = None;
+        if let Some(listening) = self.listening.take() {
+            listening.store(false, Ordering::Release);
+        }
+        self.role = SyncRole::Off;
+    }
+
+    fn listen(
+        app_tx: AppEventSender,
+    ) -> Result<Arc<AtomicBool>, Box<dyn Error>> {
+        let socket = bind()?;
+        let listening = Arc::new(AtomicBool::new(true));
+        let running = listening.clone();
+
+        thread::spawn(move || {
+            let mut buffer = [0; 1_024];
+            while running.load(Ordering::Acquire) {
+                // Times out periodically so a role change is noticed
+                let Ok((len, _)) = socket.recv_from(&mut buffer) else {
+                    continue;
+                };
+                match SyncMessage::decode(&buffer[..len]) {
+                    Ok(message) => {
+                        app_tx.emit(AppEvent::SyncReceived(message));
+                    }
+                    Err(e) => {
+                        debug!("Ignoring invalid network sync message: {}", e);
+                    }
+                }
+            }
+            debug!("Network sync follower stopped");
+        });
+
+        Ok(listening)
+    }
+}
+
+impl Drop for NetworkSync {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn bind() -> Result<UdpSocket, Box<dyn Error>> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+
+    // Allow several followers on one machine (e.g. one per projector)
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+
+    socket
+        .bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, SYNC_PORT)).into())?;
+
+    let socket: UdpSocket = socket.into();
+    socket.join_multicast_v4(&SYNC_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_read_timeout(Some(Duration::from_millis(250)))?;
+
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let messages = [
+            SyncMessage::Frame {
+                frame_count: 1234,
+                bpm: 127.5,
+                sketch: "template".to_string(),
+            },
+            SyncMessage::SnapshotRecall("3".to_string()),
+            SyncMessage::SnapshotRecallOnly(
+                "3".to_string(),
+                vec!["color".to_string()],
+            ),
+        ];
+
+        for message in messages {
+            let bytes = message.encode().unwrap();
+            assert_eq!(SyncMessage::decode(&bytes).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_message_encoding() {
+        let bytes = SyncMessage::SnapshotRecall("3".to_string())
+            .encode()
+            .unwrap();
+        assert_eq!(bytes, br#"{"SnapshotRecall":"3"}"#);
+
+        assert!(SyncMessage::decode(b"{\"Nope\":1}").is_err());
+        assert!(SyncMessage::decode(b"").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! An [OSCQuery][spec] server describing the current sketch's `osc` controls
+//! so clients like Vezér or Chataigne can discover and bind to them instead
+//! of having addresses typed in by hand. The namespace is served as JSON over
+//! HTTP and advertised over zeroconf as `_oscjson._tcp`. It is regenerated
+//! whenever the hub is populated or the sketch changes.
+//!
+//! Only the core query protocol is implemented: values are write-only (OSC
+//! values are received, never sent) and the optional WebSocket `LISTEN`
+//! extension is not supported.
+//!
+//! [spec]: https://github.com/Vidvox/OSCQueryProposal
+
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::global;
+use super::mdns::{self, ServiceAdvertisement};
+use crate::framework::prelude::*;
+
+/// The HTTP port to try first. Any free port is used when it's taken since
+/// clients find the actual port via zeroconf
+pub const DEFAULT_PORT: u16 = 2347;
+
+/// Access value meaning "clients may send but not read"
+const ACCESS_WRITE_ONLY: u8 = 2;
+
+#[derive(Debug, Default)]
+struct Node {
+    full_path: String,
+    contents: BTreeMap<String, Node>,
+    control: Option<OscControlConfig>,
+}
+
+impl Node {
+    /// Builds a tree from `address => config` (addresses without leading
+    /// slash, e.g. `"synth/cutoff"`)
+    fn from_controls(controls: &HashMap<String, OscControlConfig>) -> Self {
+        let mut root = Self {
+            full_path: "/".to_string(),
+            ..Default::default()
+        };
+
+        for (address, config) in controls {
+            let mut node = &mut root;
+            let mut path = String::new();
+            for segment in address.split('/').filter(|s| !s.is_empty()) {
+                path.push('/');
+                path.push_str(segment);
+                node = node.contents.entry(segment.to_string()).or_insert_with(
+                    || Self {
+                        full_path: path.clone(),
+                        ..Default::default()
+                    },
+                );
+            }
+            node.control = Some(config.clone());
+        }
+
+        root
+    }
+
+    fn find(&self, path: &str) -> Option<&Self> {
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .try_fold(self, |node, segment| node.contents.get(segment))
+    }
+
+    fn to_json(&self) -> Value {
+        let mut value = json!({ "FULL_PATH": self.full_path });
+
+        if let Some(config) = &self.control {
+            // Values are normalized on the wire and mapped on receipt
+            value["TYPE"] = json!("f");
+            value["ACCESS"] = json!(ACCESS_WRITE_ONLY);
+            value["RANGE"] = json!([{ "MIN": 0.0, "MAX": 1.0 }]);
+            value["DESCRIPTION"] =
+                json!(format!("Mapped to {}..{}", config.min, config.max));
+
+            if let Some(display) = &config.display {
+                value["DESCRIPTION"] = json!(format!(
+                    "Mapped to {}..{}",
+                    display.format(config.min),
+                    display.format(config.max)
+                ));
+                if let Some(unit) = &display.unit {
+                    value["UNIT"] = json!([unit]);
+                }
+            }
+        }
+
+        if !self.contents.is_empty() {
+            value["CONTENTS"] = self
+                .contents
+                .iter()
+                .map(|(name, node)| (name.clone(), node.to_json()))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+
+        value
+    }
+}
+
+#[derive(Debug, Default)]
+struct Namespace {
+    name: String,
+    root: Node,
+}
+
+#[derive(Debug)]
+pub struct OscQueryServer {
+    namespace: Arc<Mutex<Namespace>>,
+    pub port: u16,
+}
+
+impl OscQueryServer {
+    /// Starts serving an empty namespace and advertises it on the network
+    pub fn start() -> Result<Self, Box<dyn Error>> {
+        let listener = TcpListener::bind(("0.0.0.0", DEFAULT_PORT))
+            .or_else(|_| TcpListener::bind(("0.0.0.0", 0)))?;
+        let port = listener.local_addr()?.port();
+        let namespace = Arc::new(Mutex::new(Namespace::default()));
+
+        let state = namespace.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = Self::handle(stream, &state) {
+                    debug!("OSCQuery request failed: {}", e);
+                }
+            }
+        });
+
+        info!("OSCQuery server listening on port {}", port);
+
+        // Without zeroconf clients can still connect to the port directly
+        let advertised = mdns::local_ipv4()
+            .ok_or_else(|| "No network address found".into())
+            .and_then(|ip| {
+                mdns::advertise(ServiceAdvertisement {
+                    instance: "Xtal".to_string(),
+                    service_type: "_oscjson._tcp".to_string(),
+                    port,
+                    txt: vec!["txtvers=1".to_string()],
+                    ip,
+                })
+            });
+        if let Err(e) = advertised {
+            warn!("Unable to advertise OSCQuery server: {}", e);
+        }
+
+        Ok(Self { namespace, port })
+    }
+
+    /// Replaces the served namespace. `controls` is keyed by OSC address
+    /// without leading slash as in [`OscControls`]
+    pub fn set_namespace(
+        &self,
+        name: &str,
+        controls: &HashMap<String, OscControlConfig>,
+    ) {
+        let mut namespace = self.namespace.lock().unwrap();
+        namespace.name = name.to_string();
+        namespace.root = Node::from_controls(controls);
+    }
+
+    fn handle(
+        mut stream: TcpStream,
+        namespace: &Mutex<Namespace>,
+    ) -> Result<(), Box<dyn Error>> {
+        stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // Drain headers; nothing in them matters here
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let response = match Self::respond(target, &namespace.lock().unwrap()) {
+            Some(body) => format!(
+                "HTTP/1.1 200 OK\r\n\
+                Content-Type: application/json\r\n\
+                Content-Length: {}\r\n\
+                Connection: close\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+            None => "HTTP/1.1 404 Not Found\r\n\
+                Content-Length: 0\r\n\
+                Connection: close\r\n\r\n"
+                .to_string(),
+        };
+
+        stream.write_all(response.as_bytes())?;
+        Ok(())
+    }
+
+    /// The JSON body for a request target such as `/synth?HOST_INFO`, or
+    /// `None` if there is nothing at that path
+    fn respond(target: &str, namespace: &Namespace) -> Option<String> {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        if query == "HOST_INFO" {
+            return Some(
+                json!({
+                    "NAME": format!("Xtal: {}", namespace.name),
+                    "OSC_PORT": global::osc_port(),
+                    "OSC_TRANSPORT": "UDP",
+                    "EXTENSIONS": {
+                        "ACCESS": true,
+                        "CLIPMODE": false,
+                        "DESCRIPTION": true,
+                        "LISTEN": false,
+                        "RANGE": true,
+                        "TYPE": true,
+                        "VALUE": false,
+                    },
+                })
+                .to_string(),
+            );
+        }
+
+        let node = namespace.root.find(path)?.to_json();
+
+        if query.is_empty() {
+            return Some(node.to_string());
+        }
+
+        // A single attribute, e.g. `?RANGE`
+        node.get(query)
+            .map(|value| json!({ query: value }).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namespace() -> Namespace {
+        let mut controls = HashMap::default();
+        for address in ["synth/cutoff", "synth/resonance", "speed"] {
+            controls.insert(
+                address.to_string(),
+                OscControlConfig::new(address, (0.0, 10.0), 0.0),
+            );
+        }
+        controls.get_mut("synth/cutoff").unwrap().display =
+            Some(ValueDisplay {
+                unit: Some("hz".to_string()),
+                decimals: None,
+                multiplier: 100.0,
+            });
+
+        Namespace {
+            name: "test".to_string(),
+            root: Node::from_controls(&controls),
+        }
+    }
+
+    fn respond(target: &str) -> Option<Value> {
+        OscQueryServer::respond(target, &namespace())
+            .map(|body| serde_json::from_str(&body).unwrap())
+    }
+
+    #[test]
+    fn test_namespace() {
+        let root = respond("/").unwrap();
+        assert_eq!(root["FULL_PATH"], "/");
+        assert_eq!(
+            root["CONTENTS"]["synth"]["CONTENTS"]["cutoff"]["FULL_PATH"],
+            "/synth/cutoff"
+        );
+        assert_eq!(root["CONTENTS"]["speed"]["TYPE"], "f");
+
+        let cutoff = respond("/synth/cutoff").unwrap();
+        assert_eq!(cutoff["ACCESS"], 2);
+        assert_eq!(cutoff["RANGE"][0]["MAX"], 1.0);
+        assert_eq!(cutoff["UNIT"][0], "hz");
+        assert_eq!(cutoff["DESCRIPTION"], "Mapped to 0hz..1000hz");
+        assert!(respond("/speed").unwrap().get("UNIT").is_none());
+        assert!(cutoff.get("CONTENTS").is_none());
+
+        assert!(respond("/synth").unwrap().get("TYPE").is_none());
+        assert!(respond("/missing").is_none());
+    }
+
+    #[test]
+    fn test_queries() {
+        assert_eq!(
+            respond("/synth/cutoff?TYPE").unwrap(),
+            json!({ "TYPE": "f" })
+        );
+        assert!(respond("/synth/cutoff?VALUE").is_none());
+
+        let host_info = respond("/?HOST_INFO").unwrap();
+        assert_eq!(host_info["NAME"], "Xtal: test");
+        assert_eq!(host_info["OSC_TRANSPORT"], "UDP");
+    }
+}
@@ -0,0 +1,70 @@
+//! Projection mapping warps for the main window, saved per venue so the same
+//! rig can be re-aligned quickly when moving between spaces. Stored in the
+//! user data dir and edited under UI > Settings > Output Warp. See
+//! [`crate::framework::warp`].
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::framework::warp::Warp;
+
+pub const DEFAULT_VENUE: &str = "default";
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OutputWarps {
+    pub enabled: bool,
+    /// Key of the warp in [`Self::venues`] currently being projected
+    pub venue: String,
+    pub venues: IndexMap<String, Warp>,
+}
+
+impl Default for OutputWarps {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            venue: DEFAULT_VENUE.to_string(),
+            venues: IndexMap::new(),
+        }
+    }
+}
+
+impl OutputWarps {
+    /// The current venue's warp, or the identity warp for a new venue
+    pub fn current(&self) -> Warp {
+        self.venues.get(&self.venue).cloned().unwrap_or_default()
+    }
+
+    /// The warp the output stage should apply, if any
+    pub fn active(&self) -> Option<Warp> {
+        self.enabled.then(|| self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_and_active() {
+        let mut warps = OutputWarps::default();
+        assert_eq!(warps.current(), Warp::default());
+        assert_eq!(warps.active(), None);
+
+        let mut warp = Warp::default();
+        warp.corners[0] = [0.1, 0.1];
+        warps.venues.insert("club".to_string(), warp.clone());
+        warps.venue = "club".to_string();
+        warps.enabled = true;
+        assert_eq!(warps.active(), Some(warp));
+
+        warps.venue = "gallery".to_string();
+        assert_eq!(warps.active(), Some(Warp::default()));
+    }
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let warps: OutputWarps = serde_json::from_str("{}").unwrap();
+        assert_eq!(warps, OutputWarps::default());
+    }
+}
@@ -1,209 +1,753 @@
-use std::cell::Cell;
+//! Video recording. Each recording within a sketch session is a numbered take
+//! with its frames, video, and a JSON metadata sidecar (including any markers
+//! dropped while recording) kept under take-numbered paths so long capture
+//! sessions can be reviewed take by take.
+
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use std::error::Error;
+use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::str;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use super::app;
+use super::recording_health::RecordingHealth;
 use super::storage::cache_dir;
+use crate::framework::frame_controller;
 use crate::framework::prelude::*;
 use crate::runtime::app::AppEvent;
 use crate::runtime::global;
 
+/// A named point in a take, e.g. "drop" or "glitch", to jump to when reviewing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Marker {
+    pub name: String,
+    /// Index of the frame that was about to be captured when the marker was
+    /// dropped
+    pub frame: u32,
+}
+
+/// Written next to each take's video as `take-NN.json`
+#[derive(Debug, Serialize)]
+struct TakeMetadata<'a> {
+    sketch: &'a str,
+    session_id: &'a str,
+    take: u32,
+    fps: f32,
+    frames: u32,
+    dropped_frames: u32,
+    duplicated_frames: u32,
+    markers: Vec<MarkerMetadata<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MarkerMetadata<'a> {
+    name: &'a str,
+    frame: u32,
+    seconds: f32,
+}
+
+/// Persisted in [`crate::runtime::serialization::GlobalSettings`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordingSettings {
+    /// Beats captured before MIDI Start when a recording is queued so the
+    /// first downbeat is never missed. The downbeat is marked as "Bar 1" in
+    /// the take's sidecar for trimming later
+    pub pre_roll: f32,
+    /// Beats counted in (on screen and as MIDI clicks sent to the MIDI output
+    /// port) before a recording started with the Record button begins
+    pub count_in: u32,
+    /// Burn the frame number, bar and beat, BPM, and sketch name into the
+    /// encoded video. The live output is never affected
+    pub timestamp_overlay: bool,
+}
+
+/// Timing of a captured frame for [`RecordingSettings::timestamp_overlay`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FrameStamp {
+    frame: u32,
+    beats: f32,
+    bpm: f32,
+}
+
+#[derive(Debug)]
+struct CountIn {
+    remaining: u32,
+    beat: Duration,
+    next_beat: Instant,
+}
+
+pub enum CountInTick {
+    /// Beats left including this one, e.g. 4, 3, 2, 1
+    Beat(u32),
+    Done,
+}
+
 #[derive(Debug)]
 pub struct RecordingState {
     pub is_recording: bool,
-    pub is_encoding: bool,
     pub is_queued: bool,
+    pub settings: RecordingSettings,
+    /// How many of the most recent frames are kept while a queued recording
+    /// waits for MIDI Start; `None` when not pre-rolling
+    pre_roll_frames: Option<u32>,
+    /// The first frame of the take, past any pre-roll that was discarded
+    first_frame: u32,
+    count_in: Option<CountIn>,
     pub recorded_frames: Cell<u32>,
+    /// 1-based number of the current (or next) take within the session
+    pub take: u32,
+    pub markers: Vec<Marker>,
+    /// Beats elapsed on the sketch's timing source, refreshed every update so
+    /// frames captured during `view` can be stamped
+    pub beats: f32,
+    stamps: RefCell<Vec<FrameStamp>>,
+    /// The frame rate in effect when recording started. Frames are encoded at
+    /// this rate regardless of the sketch's configured FPS so runtime
+    /// overrides produce correctly paced videos
+    pub fps: f32,
     pub recording_dir: Option<PathBuf>,
-    pub encoding_thread: Option<thread::JoinHandle<()>>,
-    pub encoding_progress_rx: Option<mpsc::Receiver<EncodingMessage>>,
-    pub encoding_start: Option<Instant>,
+    /// Dropped frame and disk space checks for the current take
+    pub health: RecordingHealth,
+    /// Outlives sessions so switching sketches doesn't drop pending takes
+    pub encode_queue: EncodeQueue,
 }
 
 impl Default for RecordingState {
     fn default() -> Self {
         Self {
             is_recording: false,
-            is_encoding: false,
             is_queued: false,
+            settings: RecordingSettings::default(),
+            pre_roll_frames: None,
+            first_frame: 0,
+            count_in: None,
             recorded_frames: Cell::new(0),
+            take: 1,
+            markers: vec![],
+            beats: 0.0,
+            stamps: RefCell::new(vec![]),
+            fps: frame_controller::fps(),
             recording_dir: Some(PathBuf::from(global::videos_dir())),
-            encoding_thread: None,
-            encoding_progress_rx: None,
-            encoding_start: None,
+            health: RecordingHealth::default(),
+            encode_queue: EncodeQueue::default(),
         }
     }
 }
 
 impl RecordingState {
-    pub fn new(recording_dir: Option<PathBuf>) -> Self {
+    pub fn new(settings: RecordingSettings) -> Self {
         Self {
-            recording_dir,
+            settings,
             ..Default::default()
         }
     }
 
+    /// Starts a new session at take 1, discarding any recording in progress.
+    /// Takes already queued for encoding are unaffected
+    pub fn start_session(&mut self, session_id: &str, sketch_name: &str) {
+        self.is_recording = false;
+        self.is_queued = false;
+        self.pre_roll_frames = None;
+        self.first_frame = 0;
+        self.count_in = None;
+        self.recorded_frames.set(0);
+        self.take = 1;
+        self.markers.clear();
+        self.stamps.borrow_mut().clear();
+        self.recording_dir = frames_dir(session_id, sketch_name, self.take);
+    }
+
+    pub fn is_encoding(&self) -> bool {
+        self.encode_queue.is_busy()
+    }
+
+    /// Whether frames should be captured this frame
+    pub fn is_capturing(&self) -> bool {
+        self.is_recording || self.pre_roll_frames.is_some()
+    }
+
+    /// Takes that exiting now would lose: the one being recorded plus any
+    /// encoding or waiting to
+    pub fn unfinished_takes(&self) -> usize {
+        self.encode_queue.pending + usize::from(self.is_recording)
+    }
+
     pub fn start_recording(&mut self) -> Result<String, Box<dyn Error>> {
-        if let Some(path) = &self.recording_dir {
-            self.is_recording = true;
-            let message =
-                format!("Recording. Frames will be written to {:?}", path);
-            info!("{}", message.clone());
-            Ok(message)
+        let Some(path) = self.recording_dir.clone() else {
+            return Err("Unable to access recording path".into());
+        };
+
+        self.is_recording = true;
+        self.count_in = None;
+
+        if let Some(pre_roll_frames) = self.pre_roll_frames.take() {
+            // Keep capturing into the same take; the pre-roll becomes its head
+            let downbeat = self.recorded_frames.get();
+            self.first_frame = downbeat.saturating_sub(pre_roll_frames);
+            self.markers.push(Marker {
+                name: "Bar 1".to_string(),
+                frame: downbeat,
+            });
         } else {
-            Err("Unable to access recording path".into())
+            self.fps = frame_controller::fps();
+            self.first_frame = 0;
+            self.markers.clear();
+            self.stamps.borrow_mut().clear();
+            self.health.reset();
+        }
+
+        let message = format!(
+            "Recording take {}. Frames will be written to {:?}",
+            self.take, path
+        );
+        info!("{}", message.clone());
+        Ok(message)
+    }
+
+    /// Starts capturing right away when a recording is queued with
+    /// [`RecordingSettings::pre_roll`], keeping only the last `pre_roll` beats
+    /// of frames until MIDI Start arrives
+    pub fn start_pre_roll(&mut self, bpm: f32) {
+        if self.settings.pre_roll <= 0.0 || self.recording_dir.is_none() {
+            return;
+        }
+        self.fps = frame_controller::fps();
+        let seconds = self.settings.pre_roll * 60.0 / bpm;
+        self.pre_roll_frames = Some((seconds * self.fps).ceil() as u32);
+        self.recorded_frames.set(0);
+        self.first_frame = 0;
+        self.markers.clear();
+        self.stamps.borrow_mut().clear();
+        self.health.reset();
+    }
+
+    /// Discards pre-roll frames when a queued recording is cancelled
+    pub fn cancel_pre_roll(&mut self) {
+        if self.pre_roll_frames.take().is_none() {
+            return;
+        }
+        if let Some(dir) = &self.recording_dir {
+            if let Err(e) = fs::remove_dir_all(dir) {
+                warn!("Unable to remove pre-roll frames: {}", e);
+            }
+        }
+        self.recorded_frames.set(0);
+    }
+
+    /// Called after each captured frame. While pre-rolling, drops the frame
+    /// that just fell out of the pre-roll window
+    pub fn on_frame_captured(&self, frame: u32) {
+        let Some(pre_roll_frames) = self.pre_roll_frames else {
+            return;
+        };
+        let (Some(expired), Some(dir)) =
+            (frame.checked_sub(pre_roll_frames), &self.recording_dir)
+        else {
+            return;
+        };
+        // Deleting a frame that isn't written yet is harmless
+        let _ = fs::remove_file(dir.join(frame_filename(expired)));
+    }
+
+    /// Records the timing of a captured frame when
+    /// [`RecordingSettings::timestamp_overlay`] is on
+    pub fn stamp_frame(&self, frame: u32, bpm: f32) {
+        if self.settings.timestamp_overlay {
+            self.stamps.borrow_mut().push(FrameStamp {
+                frame,
+                beats: self.beats,
+                bpm,
+            });
+        }
+    }
+
+    pub fn start_count_in(&mut self, bpm: f32) -> bool {
+        if self.settings.count_in == 0 || self.count_in.is_some() {
+            return false;
         }
+        self.count_in = Some(CountIn {
+            remaining: self.settings.count_in,
+            beat: Duration::from_secs_f32(60.0 / bpm),
+            next_beat: Instant::now(),
+        });
+        true
     }
 
+    pub fn is_counting_in(&self) -> bool {
+        self.count_in.is_some()
+    }
+
+    pub fn cancel_count_in(&mut self) -> bool {
+        self.count_in.take().is_some()
+    }
+
+    /// Polled every update during a count-in. Returns a tick on each beat
+    pub fn tick_count_in(&mut self) -> Option<CountInTick> {
+        let count_in = self.count_in.as_mut()?;
+        let now = Instant::now();
+        if now < count_in.next_beat {
+            return None;
+        }
+        if count_in.remaining == 0 {
+            self.count_in = None;
+            return Some(CountInTick::Done);
+        }
+        let beat = count_in.remaining;
+        count_in.remaining -= 1;
+        count_in.next_beat += count_in.beat;
+        Some(CountInTick::Beat(beat))
+    }
+
+    /// Marks the frame about to be captured. Defaults the name to its position
+    /// in the take, e.g. "Marker 3"
+    pub fn add_marker(
+        &mut self,
+        name: Option<String>,
+    ) -> Result<&Marker, Box<dyn Error>> {
+        if !self.is_recording {
+            return Err("Markers can only be added while recording".into());
+        }
+        let name = name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| format!("Marker {}", self.markers.len() + 1));
+        self.markers.push(Marker {
+            name,
+            frame: self.recorded_frames.get(),
+        });
+        Ok(self.markers.last().unwrap())
+    }
+
+    /// Warns about dropped frames and low disk space while recording
+    pub fn check_health(&mut self, app_tx: &app::AppEventSender) {
+        if let Some(dir) = &self.recording_dir {
+            self.health.check(dir, app_tx);
+        }
+    }
+
+    /// Queues the take for encoding and moves on to the next one so recording
+    /// can resume right away. Returns a [`RecordingHealth::report`] on the
+    /// stopped take
     pub fn stop_recording(
         &mut self,
         sketch_config: &SketchConfig,
         session_id: &str,
-    ) -> Result<(), Box<dyn Error>> {
-        if !self.is_encoding {
-            self.is_recording = false;
-            self.is_queued = false;
-            self.is_encoding = true;
-
-            let (encoding_progress_tx, rx) = mpsc::channel();
-            self.encoding_progress_rx = Some(rx);
-
-            let path = self
-                .recording_dir
-                .as_ref()
-                .ok_or("No recording directory")?
-                .to_string_lossy()
-                .into_owned();
-
-            let output_path = video_output_path(session_id, sketch_config.name)
-                .ok_or("Could not determine output path")?
-                .to_string_lossy()
-                .into_owned();
-
-            let fps = sketch_config.fps;
-            let total_frames = self.recorded_frames.get();
-
-            info!("Preparing to encode. Output path: {}", output_path);
-            debug!("Spawning encoding_thread");
-
-            self.encoding_start = Some(Instant::now());
-            self.encoding_thread = Some(thread::spawn(move || {
-                if let Err(e) = frames_to_video(
-                    &path,
-                    fps,
-                    &output_path,
-                    total_frames,
-                    encoding_progress_tx,
-                ) {
-                    error!("Error in frames_to_video: {:?}", e);
-                }
-            }));
+    ) -> Result<String, Box<dyn Error>> {
+        self.is_recording = false;
+        self.is_queued = false;
 
-            Ok(())
-        } else {
-            Err("Already encoding".into())
+        let recording_dir =
+            self.recording_dir.clone().ok_or("No recording directory")?;
+
+        let output_path =
+            video_output_path(session_id, sketch_config.name, self.take)
+                .ok_or("Could not determine output path")?;
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
         }
+
+        if let Err(e) = self.write_metadata(
+            &output_path.with_extension("json"),
+            sketch_config.name,
+            session_id,
+        ) {
+            warn!("Unable to write take metadata: {}", e);
+        }
+
+        info!(
+            "Queueing take {} for encoding. Output path: {:?}",
+            self.take, output_path
+        );
+
+        let total_frames = self.recorded_frames.get() - self.first_frame;
+        let report = self.health.report(self.take, total_frames, self.fps);
+
+        let subtitles = if self.settings.timestamp_overlay {
+            let path = recording_dir.join(TIMESTAMPS_FILENAME);
+            let srt = timestamps_srt(
+                &self.stamps.borrow(),
+                sketch_config.name,
+                self.first_frame,
+                self.fps,
+            );
+            match fs::write(&path, srt) {
+                Ok(()) => Some(path),
+                Err(e) => {
+                    warn!("Unable to write timestamps: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        self.encode_queue.push(EncodeJob {
+            take: self.take,
+            frames_dir: recording_dir,
+            output_path,
+            fps: self.fps,
+            start_frame: self.first_frame,
+            total_frames,
+            subtitles,
+        });
+
+        self.take += 1;
+        self.recorded_frames.set(0);
+        self.first_frame = 0;
+        self.markers.clear();
+        self.stamps.borrow_mut().clear();
+        self.recording_dir =
+            frames_dir(session_id, sketch_config.name, self.take);
+
+        Ok(report)
     }
 
-    pub fn on_encoding_message(
-        &mut self,
-        sketch_config: &SketchConfig,
-        session_id: &mut String,
-        event_tx: &app::AppEventSender,
-    ) {
-        if let Some(rx) = self.encoding_progress_rx.take() {
-            while let Ok(message) = rx.try_recv() {
-                match message {
-                    EncodingMessage::Progress(progress) => {
-                        let percentage = (progress * 100.0).round();
-                        debug!("rx progress: {}%", percentage);
-                        event_tx.alert(format!(
-                            "Encoding progress: {}%",
-                            percentage
-                        ));
+    fn write_metadata(
+        &self,
+        path: &Path,
+        sketch_name: &str,
+        session_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let metadata = TakeMetadata {
+            sketch: sketch_name,
+            session_id,
+            take: self.take,
+            fps: self.fps,
+            frames: self.recorded_frames.get() - self.first_frame,
+            dropped_frames: self.health.dropped(),
+            duplicated_frames: self.health.duplicated(),
+            markers: self
+                .markers
+                .iter()
+                .map(|marker| {
+                    // Relative to the encoded video, which starts at the first
+                    // frame kept
+                    let frame = marker.frame.saturating_sub(self.first_frame);
+                    MarkerMetadata {
+                        name: &marker.name,
+                        frame,
+                        seconds: frame as f32 / self.fps,
                     }
-                    EncodingMessage::Complete => {
-                        info!("Encoding complete");
-                        if let Some(start_time) = self.encoding_start.take() {
-                            let duration = start_time.elapsed();
-                            let secs = duration.as_secs();
-                            info!(
-                                "Encoding duration: {}m {}s",
-                                secs / 60,
-                                secs % 60
-                            );
-                        }
-                        self.is_encoding = false;
-                        self.encoding_progress_rx = None;
-                        let output_path =
-                            video_output_path(session_id, sketch_config.name)
-                                .unwrap()
-                                .to_string_lossy()
-                                .into_owned();
+                })
+                .collect(),
+        };
+        fs::write(path, serde_json::to_string_pretty(&metadata)?)?;
+        Ok(())
+    }
+
+    pub fn on_encoding_message(&mut self, event_tx: &app::AppEventSender) {
+        self.encode_queue.poll(event_tx);
+    }
+}
+
+/// A stopped take waiting to be stitched into a video
+#[derive(Debug)]
+pub struct EncodeJob {
+    pub take: u32,
+    pub frames_dir: PathBuf,
+    pub output_path: PathBuf,
+    pub fps: f32,
+    /// The first frame file of the take; non-zero when pre-roll was trimmed
+    pub start_frame: u32,
+    pub total_frames: u32,
+    /// Burned into the video, see [`RecordingSettings::timestamp_overlay`]
+    pub subtitles: Option<PathBuf>,
+}
+
+/// What to do with takes still encoding when quitting
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PendingEncodes {
+    /// Wait for every take to finish encoding, then exit
+    Finish,
+    /// Stop ffmpeg and drop queued takes. Their frames are left on disk
+    Cancel,
+}
+
+/// Sent to the web view whenever the encode queue changes
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingProgress {
+    /// The take currently being encoded
+    pub take: u32,
+    /// Progress of the current take in [0.0, 1.0]
+    pub progress: f32,
+    /// Takes waiting behind the current one
+    pub queued: usize,
+}
+
+/// Encodes takes one at a time, in the order they were stopped, on a single
+/// background thread that is spawned on first use
+#[derive(Debug, Default)]
+pub struct EncodeQueue {
+    job_tx: Option<mpsc::Sender<EncodeJob>>,
+    message_rx: Option<mpsc::Receiver<EncodingMessage>>,
+    pending: usize,
+    current: Option<(u32, Instant)>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl EncodeQueue {
+    pub fn push(&mut self, job: EncodeJob) {
+        if self.job_tx.is_none() {
+            let (job_tx, job_rx) = mpsc::channel();
+            let (message_tx, message_rx) = mpsc::channel();
+            let cancelled = self.cancelled.clone();
+            debug!("Spawning encoding thread");
+            thread::spawn(move || encode_jobs(job_rx, message_tx, cancelled));
+            self.job_tx = Some(job_tx);
+            self.message_rx = Some(message_rx);
+        }
+
+        if let Some(job_tx) = &self.job_tx {
+            if job_tx.send(job).is_ok() {
+                self.pending += 1;
+            } else {
+                error!("Encoding thread has exited; dropping job");
+            }
+        }
+    }
+
+    /// Whether any take is encoding or waiting to
+    pub fn is_busy(&self) -> bool {
+        self.pending > 0
+    }
+
+    /// Stops the take being encoded and skips the rest. Only used when
+    /// quitting, so there is no way to resume
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    fn progress(&self, progress: f32) -> Option<EncodingProgress> {
+        self.current.map(|(take, _)| EncodingProgress {
+            take,
+            progress,
+            queued: self.pending.saturating_sub(1),
+        })
+    }
+
+    /// Forwards messages from the encoding thread as alerts and
+    /// [`AppEvent::EncodingProgress`] events
+    pub fn poll(&mut self, event_tx: &app::AppEventSender) {
+        let Some(rx) = self.message_rx.take() else {
+            return;
+        };
+
+        while let Ok(message) = rx.try_recv() {
+            match message {
+                EncodingMessage::Started(take) => {
+                    self.current = Some((take, Instant::now()));
+                    event_tx
+                        .emit(AppEvent::EncodingProgress(self.progress(0.0)));
+                }
+                EncodingMessage::Progress(progress) => {
+                    let percentage = (progress * 100.0).round();
+                    debug!("rx progress: {}%", percentage);
+                    event_tx.emit(AppEvent::EncodingProgress(
+                        self.progress(progress),
+                    ));
+                }
+                EncodingMessage::Complete(output_path) => {
+                    if let Some((take, start_time)) = self.current.take() {
+                        let secs = start_time.elapsed().as_secs();
+                        info!(
+                            "Encoding of take {} complete. Duration: {}m {}s",
+                            take,
+                            secs / 60,
+                            secs % 60
+                        );
                         event_tx.alert(format!(
-                            "Encoding complete. Video path: {}",
-                            output_path
+                            "Encoding of take {} complete. Video path: {}",
+                            take,
+                            output_path.display()
                         ));
-                        event_tx.emit(AppEvent::EncodingComplete);
-                        *session_id = generate_session_id();
-                        self.recorded_frames.set(0);
-                        if let Some(new_path) =
-                            frames_dir(session_id, sketch_config.name)
-                        {
-                            self.recording_dir = Some(new_path);
-                        }
-                    }
-                    EncodingMessage::Error(error) => {
-                        let message =
-                            format!("Received encoding error: {}", error);
-                        event_tx.alert(message.clone());
-                        error!("{}", message);
                     }
+                    self.pending = self.pending.saturating_sub(1);
+                    event_tx.emit(AppEvent::EncodingComplete);
                 }
+                EncodingMessage::Cancelled(take) => {
+                    self.current = None;
+                    warn!(
+                        "Encoding of take {} cancelled; its frames were kept",
+                        take
+                    );
+                    self.pending = self.pending.saturating_sub(1);
+                    event_tx.emit(AppEvent::EncodingComplete);
+                }
+                EncodingMessage::Error(error) => {
+                    let take = self.current.take().map_or(0, |(take, _)| take);
+                    let message =
+                        format!("Encoding error in take {}: {}", take, error);
+                    event_tx.alert(message.clone());
+                    error!("{}", message);
+                    self.pending = self.pending.saturating_sub(1);
+                    event_tx.emit(AppEvent::EncodingComplete);
+                }
+            }
+        }
+
+        self.message_rx = Some(rx);
+    }
+}
+
+fn encode_jobs(
+    job_rx: mpsc::Receiver<EncodeJob>,
+    message_tx: mpsc::Sender<EncodingMessage>,
+    cancelled: Arc<AtomicBool>,
+) {
+    for job in job_rx {
+        if cancelled.load(Ordering::Acquire) {
+            let _ = message_tx.send(EncodingMessage::Cancelled(job.take));
+            continue;
+        }
+
+        let _ = message_tx.send(EncodingMessage::Started(job.take));
+
+        let progress_tx = message_tx.clone();
+        let result = frames_to_video(
+            &job.frames_dir.to_string_lossy(),
+            job.fps,
+            &job.output_path.to_string_lossy(),
+            job.start_frame,
+            job.total_frames,
+            job.subtitles.as_deref(),
+            &cancelled,
+            |progress| {
+                let _ = progress_tx.send(EncodingMessage::Progress(progress));
+            },
+        );
+
+        let message = match result {
+            Ok(()) => EncodingMessage::Complete(job.output_path),
+            Err(_) if cancelled.load(Ordering::Acquire) => {
+                EncodingMessage::Cancelled(job.take)
             }
-            self.encoding_progress_rx = Some(rx);
+            Err(e) => EncodingMessage::Error(e.to_string()),
+        };
+        if message_tx.send(message).is_err() {
+            warn!("Encoding message receiver dropped");
         }
     }
 }
 
-/// Used to differentiate multiple recordings for the same base sketch name
+/// Used to differentiate recording sessions for the same base sketch name
 pub fn generate_session_id() -> String {
     uuid_5()
 }
 
+/// Name of a captured frame within a take's frames dir. Keep in sync with the
+/// `-i` pattern in [`frames_to_video`]
+pub fn frame_filename(frame: u32) -> String {
+    format!("frame-{:06}.png", frame)
+}
+
+/// Written into a take's frames dir when
+/// [`RecordingSettings::timestamp_overlay`] is on
+const TIMESTAMPS_FILENAME: &str = "timestamps.srt";
+
+/// One subtitle per frame, timed relative to the encoded video
+fn timestamps_srt(
+    stamps: &[FrameStamp],
+    sketch_name: &str,
+    first_frame: u32,
+    fps: f32,
+) -> String {
+    let ms = |frame: u32| (frame as f64 * 1000.0 / fps as f64).round() as u64;
+    let time = |ms: u64| {
+        format!(
+            "{:02}:{:02}:{:02},{:03}",
+            ms / 3_600_000,
+            ms / 60_000 % 60,
+            ms / 1000 % 60,
+            ms % 1000
+        )
+    };
+
+    stamps
+        .iter()
+        .filter(|stamp| stamp.frame >= first_frame)
+        .enumerate()
+        .map(|(index, stamp)| {
+            let frame = stamp.frame - first_frame;
+            let bar = (stamp.beats / 4.0).floor() as i32 + 1;
+            let beat = stamp.beats.rem_euclid(4.0).floor() as i32 + 1;
+            format!(
+                "{}\n{} --> {}\n{} | frame {} | bar {}.{} | {:.1} BPM\n\n",
+                index + 1,
+                time(ms(frame)),
+                time(ms(frame + 1)),
+                sketch_name,
+                frame,
+                bar,
+                beat,
+                stamp.bpm
+            )
+        })
+        .collect()
+}
+
+fn take_name(take: u32) -> String {
+    format!("take-{:02}", take)
+}
+
 /// Location of individual, temporary frame captures that will later be stitched
 /// into a single video
-pub fn frames_dir(session_id: &str, sketch_name: &str) -> Option<PathBuf> {
+pub fn frames_dir(
+    session_id: &str,
+    sketch_name: &str,
+    take: u32,
+) -> Option<PathBuf> {
     cache_dir().map(|config_dir| {
         config_dir
             .join("Captures")
             .join(sketch_name)
             .join(session_id)
+            .join(take_name(take))
     })
 }
 
-/// Path to the final encoded mp4 video
+/// Path to the final encoded mp4 video. Every take of a session shares a
+/// directory, e.g. `<videos>/<sketch>-<session>/take-01.mp4`
 pub fn video_output_path(
     session_id: &str,
     sketch_name: &str,
+    take: u32,
 ) -> Option<PathBuf> {
     Some(
         PathBuf::from(global::videos_dir())
             .join(format!("{}-{}", sketch_name, session_id))
+            .join(take_name(take))
             .with_extension("mp4"),
     )
 }
 
-pub enum EncodingMessage {
-    /// Progress updates as a percentage [0.0, 1.0]
+/// Escapes `path` for use as an option value in an ffmpeg filter, e.g.
+/// `subtitles=<path>`
+fn escape_filter_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for c in path.to_string_lossy().chars() {
+        if matches!(c, '\\' | ':' | '\'') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+enum EncodingMessage {
+    /// The take number of the job that was just picked up
+    Started(u32),
+    /// Progress of the current job as a percentage [0.0, 1.0]
     Progress(f32),
-    Complete,
+    Complete(PathBuf),
+    Cancelled(u32),
     Error(String),
 }
 
@@ -211,23 +755,42 @@ pub fn frames_to_video(
     frame_dir: &str,
     fps: f32,
     output_path: &str,
+    start_frame: u32,
     total_frames: u32,
-    progress_sender: mpsc::Sender<EncodingMessage>,
+    subtitles: Option<&Path>,
+    cancelled: &AtomicBool,
+    on_progress: impl Fn(f32),
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let process = Command::new("ffmpeg")
+    let mut command = Command::new("ffmpeg");
+    command.args([
+        // Don't overwrite
+        "-n",
+        // ---
+        "-loglevel",
+        "level+info",
+        // ---
+        "-framerate",
+        &fps.to_string(),
+        // ---
+        "-start_number",
+        &start_frame.to_string(),
+        // ---
+        "-i",
+        &format!("{}/frame-%06d.png", frame_dir),
+    ]);
+
+    if let Some(subtitles) = subtitles {
+        command.args([
+            "-vf",
+            &format!(
+                "subtitles={}:force_style='Alignment=7,FontSize=12'",
+                escape_filter_path(&std::path::absolute(subtitles)?)
+            ),
+        ]);
+    }
+
+    let mut process = command
         .args([
-            // Don't overwrite
-            "-n",
-            // ---
-            "-loglevel",
-            "level+info",
-            // ---
-            "-framerate",
-            &fps.to_string(),
-            // ---
-            "-i",
-            &format!("{}/frame-%06d.png", frame_dir),
-            // ---
             "-c:v",
             "libx264",
             // ---
@@ -267,12 +830,11 @@ pub fn frames_to_video(
 
     debug!("ffmpeg process spawned");
 
-    let stdout = process.stdout.unwrap();
+    let stdout = process.stdout.take().unwrap();
     let stdout_reader = BufReader::new(stdout);
 
-    let stderr = process.stderr.unwrap();
+    let stderr = process.stderr.take().unwrap();
     let stderr_reader = BufReader::new(stderr);
-    let error_sender = progress_sender.clone();
 
     let error_thread = thread::spawn(move || -> Result<(), String> {
         for line in stderr_reader.lines().map_while(Result::ok) {
@@ -281,8 +843,6 @@ pub fn frames_to_video(
                 warn!("Detected ffmpeg warning: {}", line);
             } else if line.contains("warning") || line.contains("fatal") {
                 error!("Detected ffmpeg error: {}", line);
-                let message = EncodingMessage::Error(line.clone());
-                let _ = error_sender.send(message);
                 return Err(line);
             }
         }
@@ -290,6 +850,13 @@ pub fn frames_to_video(
     });
 
     for line in stdout_reader.lines().map_while(Result::ok) {
+        if cancelled.load(Ordering::Acquire) {
+            let _ = process.kill();
+            let _ = process.wait();
+            // ffmpeg leaves an unplayable file behind when killed mid-encode
+            let _ = fs::remove_file(output_path);
+            return Err("Encoding cancelled".into());
+        }
         if line.starts_with("frame=") {
             let frame_str = line
                 .strip_prefix("frame=")
@@ -299,23 +866,131 @@ pub fn frames_to_video(
             if let Ok(frame) = frame_str.unwrap().parse::<u32>() {
                 let progress = frame as f32 / total_frames as f32;
                 debug!("frames_to_video progress: {}", progress);
-                let message = EncodingMessage::Progress(progress);
-                progress_sender.send(message)?;
+                on_progress(progress);
             }
         }
     }
 
     match error_thread.join() {
-        Ok(Ok(())) => {
-            if progress_sender.send(EncodingMessage::Complete).is_err() {
-                warn!("Completion receiver dropped");
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(line)) => Err(line.into()),
+        Err(err) => Err(format!("Error thread panicked: {:?}", err).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_marker() {
+        let mut state = RecordingState::default();
+        assert!(state.add_marker(None).is_err());
+
+        state.is_recording = true;
+        state.recorded_frames.set(120);
+        assert_eq!(state.add_marker(Some("drop".into())).unwrap().frame, 120);
+        state.recorded_frames.set(240);
+        assert_eq!(
+            state.add_marker(Some(" ".into())).unwrap().name,
+            "Marker 2"
+        );
+        assert_eq!(state.markers.len(), 2);
+    }
+
+    #[test]
+    fn test_pre_roll() {
+        let mut state = RecordingState::new(RecordingSettings {
+            pre_roll: 1.0,
+            count_in: 0,
+            ..Default::default()
+        });
+        state.recording_dir = None;
+        state.start_pre_roll(120.0);
+        assert!(!state.is_capturing(), "needs a recording dir");
+
+        state.recording_dir = Some(std::env::temp_dir().join("xtal-pre-roll"));
+        state.start_pre_roll(120.0);
+        assert!(state.is_capturing() && !state.is_recording);
+
+        state.recorded_frames.set(100);
+        state.start_recording().unwrap();
+        assert_eq!(state.first_frame, 100 - (0.5 * state.fps).ceil() as u32);
+        assert_eq!(state.markers[0].name, "Bar 1");
+        assert_eq!(state.markers[0].frame, 100);
+    }
+
+    #[test]
+    fn test_count_in() {
+        let mut state = RecordingState::new(RecordingSettings {
+            pre_roll: 0.0,
+            count_in: 2,
+            ..Default::default()
+        });
+        assert!(state.start_count_in(6000.0));
+        assert!(!state.start_count_in(6000.0), "already counting in");
+
+        let mut ticks = vec![];
+        while state.is_counting_in() {
+            match state.tick_count_in() {
+                Some(CountInTick::Beat(beat)) => ticks.push(beat),
+                Some(CountInTick::Done) => ticks.push(0),
+                None => thread::sleep(Duration::from_millis(1)),
             }
         }
-        Ok(Err(_)) => {}
-        Err(err) => {
-            error!("Error thread panicked: {:?}", err);
+        assert_eq!(ticks, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_unfinished_takes() {
+        let mut state = RecordingState::default();
+        assert_eq!(state.unfinished_takes(), 0);
+
+        state.encode_queue.pending = 2;
+        assert_eq!(state.unfinished_takes(), 2);
+
+        state.is_recording = true;
+        assert_eq!(state.unfinished_takes(), 3);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let mut state = RecordingState::default();
+        state.stamp_frame(0, 120.0);
+        assert!(state.stamps.borrow().is_empty(), "overlay is off");
+
+        state.settings.timestamp_overlay = true;
+        for (frame, beats) in [(9, 3.5), (10, 4.0), (11, 4.5)] {
+            state.beats = beats;
+            state.stamp_frame(frame, 120.0);
         }
+
+        // Frames before the first (trimmed pre-roll) are skipped
+        let srt = timestamps_srt(&state.stamps.borrow(), "blob", 10, 30.0);
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:00,033\n\
+            blob | frame 0 | bar 2.1 | 120.0 BPM\n\n\
+            2\n00:00:00,033 --> 00:00:00,067\n\
+            blob | frame 1 | bar 2.1 | 120.0 BPM\n\n"
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_take_paths() {
+        let path = video_output_path("abc", "blob", 3).unwrap();
+        assert!(path.ends_with("blob-abc/take-03.mp4"));
+    }
+
+    #[test]
+    fn test_escape_filter_path() {
+        assert_eq!(
+            escape_filter_path(Path::new("/tmp/it's/timestamps.srt")),
+            "/tmp/it\\'s/timestamps.srt"
+        );
+        assert_eq!(
+            escape_filter_path(Path::new("C:\\Videos\\a.srt")),
+            "C\\:\\\\Videos\\\\a.srt"
+        );
+    }
 }
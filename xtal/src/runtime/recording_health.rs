@@ -0,0 +1,204 @@
+//! Checks run while recording so an unusable capture is noticed before the gig
+//! ends: free disk space on the capture volume and frames the clock advanced
+//! past without capturing (dropped) or captured twice (duplicated), either of
+//! which makes the video drift against audio recorded alongside it.
+
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::app::AppEventSender;
+use crate::framework::prelude::*;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Warn once when free space drops below this
+const LOW_DISK_SPACE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Warn on every check below this
+const CRITICAL_DISK_SPACE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct RecordingHealth {
+    /// [`crate::framework::frame_controller::frame_count`] at the previous
+    /// capture
+    last_frame: Cell<Option<u32>>,
+    dropped: Cell<u32>,
+    duplicated: Cell<u32>,
+    reported_dropped: u32,
+    last_check: Option<Instant>,
+    /// Disk space is read with `df` on a separate thread so a slow volume
+    /// can't stall a frame
+    disk_rx: Option<mpsc::Receiver<Option<u64>>>,
+    low_disk_warned: bool,
+}
+
+impl RecordingHealth {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Called after each captured frame with the frame controller's count.
+    /// Each frame should advance it by exactly one
+    pub fn on_capture(&self, frame_count: u32) {
+        let last_frame = self.last_frame.replace(Some(frame_count));
+
+        // None after a frame count reset, which isn't a capture problem
+        let Some(delta) =
+            last_frame.and_then(|last| frame_count.checked_sub(last))
+        else {
+            return;
+        };
+
+        match delta {
+            0 => self.duplicated.set(self.duplicated.get() + 1),
+            1 => {}
+            _ => self.dropped.set(self.dropped.get() + delta - 1),
+        }
+    }
+
+    pub fn dropped(&self) -> u32 {
+        self.dropped.get()
+    }
+
+    pub fn duplicated(&self) -> u32 {
+        self.duplicated.get()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.dropped() == 0 && self.duplicated() == 0
+    }
+
+    /// Alerts about new dropped frames and low disk space, at most every
+    /// [`CHECK_INTERVAL`]
+    pub fn check(&mut self, recording_dir: &Path, app_tx: &AppEventSender) {
+        let now = Instant::now();
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_check = Some(now);
+
+        let dropped = self.dropped();
+        if dropped > self.reported_dropped {
+            self.reported_dropped = dropped;
+            app_tx.alert_and_log(
+                format!(
+                    "Recording has dropped {} frames; the video will run \
+                    short of real time",
+                    dropped
+                ),
+                log::Level::Warn,
+            );
+        }
+
+        if let Some(rx) = self.disk_rx.take() {
+            match rx.try_recv() {
+                Ok(Some(available)) => self.on_disk_space(available, app_tx),
+                Ok(None) | Err(TryRecvError::Disconnected) => {}
+                Err(TryRecvError::Empty) => {
+                    self.disk_rx = Some(rx);
+                    return;
+                }
+            }
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let dir = recording_dir.to_path_buf();
+        thread::spawn(move || {
+            let _ = tx.send(available_disk_space(&dir));
+        });
+        self.disk_rx = Some(rx);
+    }
+
+    fn on_disk_space(&mut self, available: u64, app_tx: &AppEventSender) {
+        let gb = available as f64 / (1024.0 * 1024.0 * 1024.0);
+        if available < CRITICAL_DISK_SPACE {
+            app_tx.alert_and_log(
+                format!("Only {:.2} GB of disk space left. Stop recording", gb),
+                log::Level::Error,
+            );
+        } else if available < LOW_DISK_SPACE && !self.low_disk_warned {
+            self.low_disk_warned = true;
+            app_tx.alert_and_log(
+                format!("Low disk space: {:.1} GB left for recording", gb),
+                log::Level::Warn,
+            );
+        }
+    }
+
+    /// Summary shown once a take is stopped
+    pub fn report(&self, take: u32, frames: u32, fps: f32) -> String {
+        let summary = format!(
+            "Take {}: {} frames ({:.1}s)",
+            take,
+            frames,
+            frames as f32 / fps
+        );
+        if self.is_clean() {
+            format!("{} with no dropped or duplicated frames", summary)
+        } else {
+            format!(
+                "{} with {} dropped and {} duplicated frames; expect drift \
+                against audio",
+                summary,
+                self.dropped(),
+                self.duplicated()
+            )
+        }
+    }
+}
+
+/// Free space in bytes on the volume holding `path` (or its nearest existing
+/// ancestor) using `df`, which ships with macOS and Linux. `None` on other
+/// platforms or if `df` fails
+pub fn available_disk_space(path: &Path) -> Option<u64> {
+    let existing: PathBuf = path.ancestors().find(|p| p.exists())?.into();
+    let output = Command::new("df").arg("-Pk").arg(existing).output().ok()?;
+    parse_df_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads the "Available" column (in 1K blocks) from POSIX `df -Pk` output
+fn parse_df_output(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kilobytes: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_capture() {
+        let health = RecordingHealth::default();
+        health.on_capture(10);
+        health.on_capture(11);
+        assert!(health.is_clean());
+
+        health.on_capture(14);
+        health.on_capture(14);
+        assert_eq!(health.dropped(), 2);
+        assert_eq!(health.duplicated(), 1);
+
+        // A reset of the frame counter starts counting over
+        health.on_capture(0);
+        health.on_capture(1);
+        assert_eq!(health.dropped(), 2);
+        assert_eq!(health.duplicated(), 1);
+    }
+
+    #[test]
+    fn test_parse_df_output() {
+        let output = "Filesystem 1024-blocks Used Available Capacity \
+            Mounted on\n/dev/disk3s5 971350180 600000000 360000000 63% \
+            /System/Volumes/Data\n";
+        assert_eq!(parse_df_output(output), Some(360000000 * 1024));
+        assert_eq!(parse_df_output(""), None);
+    }
+}
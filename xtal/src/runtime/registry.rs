@@ -3,6 +3,7 @@ use nannou::prelude::*;
 use std::str;
 use std::sync::{LazyLock, RwLock};
 
+use crate::framework::gpu_stats::{self, SketchGpuUsage};
 use crate::framework::prelude::*;
 
 /// Register all of your project's sketches
@@ -56,12 +57,25 @@ pub struct SketchInfo {
     pub factory: DynamicSketchFn,
 }
 
+impl SketchInfo {
+    /// Calls [`Self::factory`], attributing the GPU resources the sketch
+    /// creates to it. See [`gpu_stats`]
+    pub fn init(&self, app: &App, ctx: &Context) -> Box<dyn SketchAll> {
+        gpu_stats::with_owner(self.config.name, || (self.factory)(app, ctx))
+    }
+}
+
 pub static REGISTRY: LazyLock<RwLock<SketchRegistry>> =
     LazyLock::new(|| RwLock::new(SketchRegistry::new()));
 
+/// How many sketch names [`SketchRegistry::recently_used`] holds on to
+pub const MAX_RECENT: usize = 10;
+
 pub struct SketchRegistry {
     sketches: IndexMap<String, SketchInfo>,
     names: Option<Vec<String>>,
+    recent: Vec<String>,
+    favorites: Vec<String>,
 }
 
 impl SketchRegistry {
@@ -69,6 +83,8 @@ impl SketchRegistry {
         Self {
             sketches: IndexMap::new(),
             names: None,
+            recent: vec![],
+            favorites: vec![],
         }
     }
 
@@ -102,4 +118,240 @@ impl SketchRegistry {
                 Call prepare() first.",
         )
     }
+
+    pub fn configs(&self) -> impl Iterator<Item = &'static SketchConfig> {
+        self.sketches.values().map(|info| info.config)
+    }
+
+    /// Every tag used by at least one sketch, sorted and deduplicated
+    pub fn tags(&self) -> Vec<&'static str> {
+        let mut tags: Vec<&'static str> = self
+            .configs()
+            .flat_map(|config| config.tags.iter().copied())
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+
+    pub fn with_tag(&self, tag: &str) -> Vec<&'static SketchConfig> {
+        self.configs()
+            .filter(|config| config.tags.contains(&tag))
+            .collect()
+    }
+
+    /// Case-insensitive match of `query` against each sketch's name, display
+    /// name, author, description, and tags, in registration order
+    pub fn search(&self, query: &str) -> Vec<&'static SketchConfig> {
+        let query = query.trim().to_lowercase();
+        self.configs()
+            .filter(|config| {
+                query.is_empty()
+                    || [
+                        config.name,
+                        config.display_name,
+                        config.author,
+                        config.description,
+                    ]
+                    .iter()
+                    .chain(config.tags)
+                    .any(|field| field.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+
+    /// GPU usage of every registered sketch that has created GPU resources
+    /// this session, largest peak first
+    pub fn gpu_usage(&self) -> Vec<SketchGpuUsage> {
+        let mut usage: Vec<SketchGpuUsage> = gpu_stats::all_usage()
+            .into_iter()
+            .filter(|usage| self.sketches.contains_key(&usage.name))
+            .collect();
+        usage.sort_by(|a, b| {
+            b.peak_vram_bytes
+                .cmp(&a.peak_vram_bytes)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        usage
+    }
+
+    /// Most recently used first
+    pub fn recently_used(&self) -> &[String] {
+        &self.recent
+    }
+
+    /// Moves `name` to the front of [`Self::recently_used`]
+    pub fn mark_used(&mut self, name: &str) {
+        if !self.sketches.contains_key(name) {
+            return;
+        }
+        self.recent.retain(|n| n != name);
+        self.recent.insert(0, name.to_string());
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// Restores a persisted list, dropping sketches that no longer exist
+    pub fn set_recently_used(&mut self, names: Vec<String>) {
+        self.recent = names
+            .into_iter()
+            .filter(|name| self.sketches.contains_key(name))
+            .take(MAX_RECENT)
+            .collect();
+    }
+
+    /// In the user's chosen order
+    pub fn favorites(&self) -> &[String] {
+        &self.favorites
+    }
+
+    pub fn is_favorite(&self, name: &str) -> bool {
+        self.favorites.iter().any(|n| n == name)
+    }
+
+    /// Appends `name` to the favorites or removes it if already there.
+    /// Returns whether it is now a favorite
+    pub fn toggle_favorite(&mut self, name: &str) -> bool {
+        if self.is_favorite(name) {
+            self.favorites.retain(|n| n != name);
+            false
+        } else if self.sketches.contains_key(name) {
+            self.favorites.push(name.to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replaces the favorites, e.g. to reorder them or restore a persisted
+    /// list. Unknown sketches and duplicates are dropped
+    pub fn set_favorites(&mut self, names: Vec<String>) {
+        self.favorites.clear();
+        for name in names {
+            if self.sketches.contains_key(&name) && !self.is_favorite(&name) {
+                self.favorites.push(name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: SketchConfig = SketchConfig {
+        name: "a",
+        display_name: "Alpha",
+        tags: &["shader", "genuary"],
+        author: "",
+        description: "Warped noise field",
+        play_mode: PlayMode::Loop,
+        fps: 60.0,
+        bpm: 134.0,
+        w: 100,
+        h: 100,
+        msaa_samples: 1,
+    };
+
+    const B: SketchConfig = SketchConfig {
+        name: "b",
+        display_name: "Beta",
+        tags: &["shader"],
+        author: "Someone",
+        description: "",
+        play_mode: PlayMode::Loop,
+        fps: 60.0,
+        bpm: 134.0,
+        w: 100,
+        h: 100,
+        msaa_samples: 1,
+    };
+
+    fn registry() -> SketchRegistry {
+        let mut registry = SketchRegistry::new();
+        registry.register(&A, |_, _| unreachable!());
+        registry.register(&B, |_, _| unreachable!());
+        registry.prepare();
+        registry
+    }
+
+    fn names(configs: Vec<&SketchConfig>) -> Vec<&str> {
+        configs.iter().map(|config| config.name).collect()
+    }
+
+    #[test]
+    fn test_tags() {
+        let registry = registry();
+        assert_eq!(registry.tags(), vec!["genuary", "shader"]);
+        assert_eq!(names(registry.with_tag("shader")), vec!["a", "b"]);
+        assert_eq!(names(registry.with_tag("genuary")), vec!["a"]);
+        assert!(registry.with_tag("nope").is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let registry = registry();
+        assert_eq!(names(registry.search("NOISE")), vec!["a"]);
+        assert_eq!(names(registry.search("someone")), vec!["b"]);
+        assert_eq!(names(registry.search("genu")), vec!["a"]);
+        assert_eq!(names(registry.search(" ")), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_gpu_usage() {
+        use crate::framework::gpu_stats::{GpuUsage, UsageReport};
+
+        let registry = registry();
+        let mut reports =
+            [("a", 10), ("b", 20), ("missing", 30)].map(|(name, bytes)| {
+                let mut report =
+                    gpu_stats::with_owner(name, UsageReport::default);
+                report.set(GpuUsage::default().buffer(bytes));
+                report
+            });
+
+        let usage = registry.gpu_usage();
+        assert_eq!(
+            usage.iter().map(|u| u.name.as_str()).collect::<Vec<_>>(),
+            vec!["b", "a"]
+        );
+        assert_eq!(usage[0].live.buffer_bytes, 20);
+
+        reports[1].set(GpuUsage::default());
+        assert_eq!(registry.gpu_usage()[1].live, GpuUsage::default());
+        assert_eq!(registry.gpu_usage()[1].peak_vram_bytes, 20);
+    }
+
+    #[test]
+    fn test_recently_used() {
+        let mut registry = registry();
+        registry.mark_used("a");
+        registry.mark_used("b");
+        registry.mark_used("a");
+        registry.mark_used("missing");
+        assert_eq!(registry.recently_used(), &["a", "b"]);
+
+        registry.set_recently_used(vec!["b".into(), "gone".into()]);
+        assert_eq!(registry.recently_used(), &["b"]);
+    }
+
+    #[test]
+    fn test_favorites() {
+        let mut registry = registry();
+        assert!(registry.toggle_favorite("b"));
+        assert!(registry.toggle_favorite("a"));
+        assert!(!registry.toggle_favorite("missing"));
+        assert_eq!(registry.favorites(), &["b", "a"]);
+
+        registry.set_favorites(vec![
+            "a".into(),
+            "gone".into(),
+            "b".into(),
+            "a".into(),
+        ]);
+        assert_eq!(registry.favorites(), &["a", "b"]);
+
+        assert!(!registry.toggle_favorite("a"));
+        assert_eq!(registry.favorites(), &["b"]);
+        assert!(!registry.is_favorite("a"));
+    }
 }
@@ -1,43 +1,77 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use super::map_mode::{MapMode, Mappings};
-use crate::framework::control::control_hub::Snapshots;
+#[cfg(feature = "runtime")]
+use super::midi_actions::MidiActionMappings;
+#[cfg(feature = "runtime")]
+use super::network_sync::SyncRole;
+#[cfg(feature = "runtime")]
+use super::recording::RecordingSettings;
+use crate::framework::control::control_hub::{SnapshotPhases, Snapshots};
 use crate::framework::prelude::*;
+#[cfg(feature = "runtime")]
 use crate::runtime::global;
 
+#[cfg(feature = "runtime")]
 pub const GLOBAL_SETTINGS_VERSION: &str = "1";
 
+#[cfg(feature = "runtime")]
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct GlobalSettings {
     pub version: String,
     pub audio_device_name: String,
+    pub depth_device_name: String,
+    /// See [`crate::runtime::registry::SketchRegistry::favorites`]
+    pub favorite_sketches: Vec<String>,
     pub hrcc: bool,
     pub images_dir: String,
+    pub latency_offsets: LatencyOffsets,
     pub mappings_enabled: bool,
+    /// See [`crate::runtime::midi_actions`]
+    pub midi_actions: MidiActionMappings,
     pub midi_clock_port: String,
     pub midi_control_in_port: String,
     pub midi_control_out_port: String,
     pub osc_port: u16,
+    pub prewarm: bool,
+    /// See [`crate::runtime::registry::SketchRegistry::recently_used`]
+    pub recent_sketches: Vec<String>,
+    pub recording: RecordingSettings,
+    pub snapshot_phase: bool,
+    pub stereo: gpu::StereoSettings,
+    pub sync_role: SyncRole,
     pub transition_time: f32,
     pub user_data_dir: String,
     pub videos_dir: String,
 }
 
+#[cfg(feature = "runtime")]
 impl Default for GlobalSettings {
     fn default() -> Self {
         Self {
             version: GLOBAL_SETTINGS_VERSION.to_string(),
             audio_device_name: global::audio_device_name().unwrap_or_default(),
+            depth_device_name: global::depth_device_name().unwrap_or_default(),
+            favorite_sketches: vec![],
             hrcc: false,
             images_dir: global::images_dir(),
+            latency_offsets: global::latency_offsets(),
             mappings_enabled: true,
+            midi_actions: MidiActionMappings::default(),
             midi_clock_port: global::midi_clock_port().unwrap_or_default(),
             midi_control_in_port: global::midi_control_in_port()
                 .unwrap_or_default(),
             midi_control_out_port: global::midi_control_out_port()
                 .unwrap_or_default(),
             osc_port: global::osc_port(),
+            prewarm: false,
+            recent_sketches: vec![],
+            recording: RecordingSettings::default(),
+            snapshot_phase: false,
+            stereo: gpu::StereoSettings::default(),
+            sync_role: SyncRole::Off,
             transition_time: 4.0,
             user_data_dir: global::user_data_dir(),
             videos_dir: global::videos_dir(),
@@ -47,6 +81,14 @@ impl Default for GlobalSettings {
 
 pub const PROGRAM_STATE_VERSION: &str = "2";
 
+/// Per-sketch runtime overrides of [`SketchConfig::fps`] and
+/// [`SketchConfig::bpm`]. `None` means use the sketch's config value.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct TimingOverrides {
+    pub fps: Option<f32>,
+    pub bpm: Option<f32>,
+}
+
 /// Everything needed to recall a patch
 #[derive(Deserialize, Serialize)]
 pub struct SerializableSketchState {
@@ -68,6 +110,13 @@ pub struct SerializableSketchState {
 
     #[serde(default)]
     pub exclusions: Exclusions,
+
+    #[serde(default)]
+    pub timing: TimingOverrides,
+
+    /// See [`ControlHub::set_slider_range`]
+    #[serde(default)]
+    pub slider_ranges: HashMap<String, SliderRange>,
 }
 
 impl From<&TransitorySketchState> for SerializableSketchState {
@@ -115,12 +164,21 @@ impl From<&TransitorySketchState> for SerializableSketchState {
             .snapshots
             .iter()
             .map(|(name, snapshot)| {
-                (name.clone(), SerializableSnapshot::new(state, snapshot))
+                (
+                    name.clone(),
+                    SerializableSnapshot::new(
+                        state,
+                        snapshot,
+                        state.snapshot_phases.get(name),
+                    ),
+                )
             })
             .collect();
 
         let mappings = state.mappings.clone();
         let exclusions = state.exclusions.clone();
+        let timing = state.timing;
+        let slider_ranges = state.slider_ranges.clone();
 
         Self {
             version: PROGRAM_STATE_VERSION.to_string(),
@@ -130,8 +188,105 @@ impl From<&TransitorySketchState> for SerializableSketchState {
             snapshots,
             mappings,
             exclusions,
+            timing,
+            slider_ranges,
+        }
+    }
+}
+
+impl SerializableSketchState {
+    /// Moves everything saved under an old name in `renames` (`old => new`)
+    /// to the new name: values, MIDI mapping proxies, snapshots, mappings,
+    /// exclusions, and slider ranges. Entries already saved under the new
+    /// name win and the old ones are dropped. Returns how many entries
+    /// changed so callers know whether to write the state back
+    pub fn rename_controls(
+        &mut self,
+        renames: &IndexMap<String, String>,
+    ) -> usize {
+        let mut changed = 0;
+
+        for (old, new) in renames {
+            let old_proxy = MapMode::proxy_name(old);
+            let new_proxy = MapMode::proxy_name(new);
+
+            changed += rename_entries(&mut self.ui_controls, old, new);
+            changed += rename_entries(&mut self.midi_controls, old, new);
+            changed +=
+                rename_entries(&mut self.midi_controls, &old_proxy, &new_proxy);
+            changed += rename_entries(&mut self.osc_controls, old, new);
+
+            for snapshot in self.snapshots.values_mut() {
+                changed += rename_entries(&mut snapshot.ui_controls, old, new);
+                changed +=
+                    rename_entries(&mut snapshot.midi_controls, old, new);
+                changed += rename_entries(
+                    &mut snapshot.midi_controls,
+                    &old_proxy,
+                    &new_proxy,
+                );
+                changed += rename_entries(&mut snapshot.osc_controls, old, new);
+                changed +=
+                    rename_entries(&mut snapshot.animation_phases, old, new);
+            }
+
+            changed += rename_key(&mut self.mappings, old, new);
+            changed += rename_key(&mut self.slider_ranges, old, new);
+            changed += rename_entries(&mut self.exclusions, old, new);
+        }
+
+        changed
+    }
+}
+
+trait Named {
+    fn name_mut(&mut self) -> &mut String;
+}
+
+impl Named for String {
+    fn name_mut(&mut self) -> &mut String {
+        self
+    }
+}
+
+impl Named for ControlConfig {
+    fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+}
+
+impl Named for BasicNameValueConfig {
+    fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+}
+
+fn rename_entries<T: Named>(items: &mut Vec<T>, old: &str, new: &str) -> usize {
+    if items.iter_mut().any(|item| *item.name_mut() == new) {
+        let len = items.len();
+        items.retain_mut(|item| *item.name_mut() != old);
+        return len - items.len();
+    }
+
+    let mut renamed = 0;
+    for item in items.iter_mut() {
+        let name = item.name_mut();
+        if *name == old {
+            *name = new.to_string();
+            renamed += 1;
         }
     }
+    renamed
+}
+
+fn rename_key<V>(map: &mut HashMap<String, V>, old: &str, new: &str) -> usize {
+    let Some(value) = map.remove(old) else {
+        return 0;
+    };
+    if !map.contains_key(new) {
+        map.insert(new.to_string(), value);
+    }
+    1
 }
 
 #[derive(Serialize, Deserialize)]
@@ -202,12 +357,17 @@ pub struct SerializableSnapshot {
     pub ui_controls: Vec<ControlConfig>,
     pub midi_controls: Vec<BasicNameValueConfig>,
     pub osc_controls: Vec<BasicNameValueConfig>,
+
+    /// Beats per animation. Only present for snapshots taken with phase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub animation_phases: Vec<BasicNameValueConfig>,
 }
 
 impl SerializableSnapshot {
     pub fn new(
         state: &TransitorySketchState,
         snapshot: &HashMap<String, ControlValue>,
+        phases: Option<&HashMap<String, f32>>,
     ) -> Self {
         let mut ui_controls = Vec::new();
         let mut midi_controls = Vec::new();
@@ -233,10 +393,20 @@ impl SerializableSnapshot {
             }
         }
 
+        let animation_phases = phases
+            .into_iter()
+            .flatten()
+            .map(|(name, beats)| BasicNameValueConfig {
+                name: name.clone(),
+                value: *beats,
+            })
+            .collect();
+
         SerializableSnapshot {
             ui_controls,
             midi_controls,
             osc_controls,
+            animation_phases,
         }
     }
 }
@@ -249,8 +419,11 @@ pub struct TransitorySketchState {
     pub midi_controls: MidiControls,
     pub osc_controls: OscControls,
     pub snapshots: Snapshots,
+    pub snapshot_phases: SnapshotPhases,
     pub mappings: Mappings,
     pub exclusions: Exclusions,
+    pub timing: TimingOverrides,
+    pub slider_ranges: HashMap<String, SliderRange>,
 }
 
 impl Default for TransitorySketchState {
@@ -260,8 +433,11 @@ impl Default for TransitorySketchState {
             midi_controls: MidiControlBuilder::new().build(),
             osc_controls: OscControlBuilder::new().build(),
             snapshots: HashMap::default(),
+            snapshot_phases: HashMap::default(),
             mappings: HashMap::default(),
             exclusions: Vec::new(),
+            timing: TimingOverrides::default(),
+            slider_ranges: HashMap::default(),
         }
     }
 }
@@ -269,9 +445,17 @@ impl Default for TransitorySketchState {
 impl TransitorySketchState {
     /// Merge incoming serialized data into self
     pub fn merge(&mut self, serialized_state: SerializableSketchState) {
+        // Before MIDI mappings are set up so proxies get the overridden ranges
+        for (name, range) in &serialized_state.slider_ranges {
+            if self.ui_controls.set_slider_range(name, *range) {
+                self.slider_ranges.insert(name.clone(), *range);
+            }
+        }
+
         self.merge_ui_controls(&serialized_state);
         self.mappings = serialized_state.mappings.clone();
         self.exclusions = serialized_state.exclusions.clone();
+        self.timing = serialized_state.timing;
 
         // Must happen before merging MIDI controls otherwise there will be no
         // MIDI proxy configs to merge the saved MIDI proxy values into
@@ -374,6 +558,7 @@ impl TransitorySketchState {
 
     fn merge_snapshots(&mut self, serialized_state: SerializableSketchState) {
         self.snapshots.clear();
+        self.snapshot_phases.clear();
 
         for (name, snapshot) in serialized_state.snapshots {
             let mut values = HashMap::default();
@@ -396,7 +581,89 @@ impl TransitorySketchState {
                 );
             }
 
+            if !snapshot.animation_phases.is_empty() {
+                self.snapshot_phases.insert(
+                    name.clone(),
+                    snapshot
+                        .animation_phases
+                        .iter()
+                        .map(|phase| (phase.name.clone(), phase.value))
+                        .collect(),
+                );
+            }
+
             self.snapshots.insert(name, values);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(name: &str, value: f32) -> BasicNameValueConfig {
+        BasicNameValueConfig {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn names(configs: &[BasicNameValueConfig]) -> Vec<&str> {
+        configs.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rename_controls() {
+        let mut state: SerializableSketchState = serde_json::from_str(
+            r#"{
+                "version": "2",
+                "ui_controls": [
+                    { "type": "slider", "name": "radius", "value": 0.5 },
+                    { "type": "slider", "name": "size", "value": 0.1 },
+                    { "type": "checkbox", "name": "invert", "value": true }
+                ],
+                "midi_controls": [],
+                "osc_controls": [],
+                "exclusions": ["invert"],
+                "mappings": { "radius": [0, 1] }
+            }"#,
+        )
+        .unwrap();
+        state.midi_controls = vec![value(&MapMode::proxy_name("radius"), 0.5)];
+        state.snapshots.insert(
+            "1".to_string(),
+            SerializableSnapshot {
+                ui_controls: vec![],
+                midi_controls: vec![],
+                osc_controls: vec![value("speed", 2.0)],
+                animation_phases: vec![],
+            },
+        );
+
+        let renames: IndexMap<String, String> = [
+            ("radius", "size"),
+            ("invert", "flip"),
+            ("speed", "rate"),
+            ("missing", "whatever"),
+        ]
+        .into_iter()
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .collect();
+
+        assert_eq!(state.rename_controls(&renames), 6);
+
+        let ui: Vec<_> =
+            state.ui_controls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(ui, vec!["size", "flip"]);
+        assert_eq!(state.ui_controls[0].value, ControlValue::Float(0.1));
+        assert_eq!(
+            names(&state.midi_controls),
+            vec![MapMode::proxy_name("size").as_str()]
+        );
+        assert_eq!(names(&state.snapshots["1"].osc_controls), vec!["rate"]);
+        assert!(state.mappings.contains_key("size"));
+        assert_eq!(state.exclusions, vec!["flip"]);
+
+        assert_eq!(state.rename_controls(&renames), 0);
+    }
+}
@@ -0,0 +1,114 @@
+//! An ordered queue of sketches for structured live shows. Each entry names a
+//! sketch and optionally a snapshot to recall once it's loaded, along with the
+//! transition time to recall it over. Stepping is bound to the `N` key (`Shift
+//! N` steps back) and optionally to a MIDI CC on the control input port. Set
+//! lists are stored in the user data dir so each project can keep its own.
+
+use serde::{Deserialize, Serialize};
+
+use crate::framework::prelude::*;
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SetListEntry {
+    pub sketch: String,
+    pub snapshot: Option<String>,
+    /// Overrides the global transition time when recalling [`Self::snapshot`]
+    pub transition_time: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SetList {
+    pub entries: Vec<SetListEntry>,
+
+    /// Steps to the next entry whenever this CC crosses from below 64 to 64
+    /// or above
+    pub next_cc: Option<ChannelAndController>,
+
+    /// Index of the entry that was stepped to last; `None` before the first
+    /// step
+    pub position: Option<usize>,
+}
+
+impl SetList {
+    pub fn current(&self) -> Option<&SetListEntry> {
+        self.position
+            .and_then(|position| self.entries.get(position))
+    }
+
+    /// Advances to and returns the next entry, or `None` (without moving)
+    /// when already at the end
+    pub fn next_entry(&mut self) -> Option<&SetListEntry> {
+        let next = self.position.map_or(0, |position| position + 1);
+        self.go_to(next)
+    }
+
+    /// Steps back to and returns the previous entry, or `None` (without
+    /// moving) when already at the start
+    pub fn previous_entry(&mut self) -> Option<&SetListEntry> {
+        let previous = self.position?.checked_sub(1)?;
+        self.go_to(previous)
+    }
+
+    pub fn go_to(&mut self, position: usize) -> Option<&SetListEntry> {
+        if position >= self.entries.len() {
+            return None;
+        }
+        self.position = Some(position);
+        self.entries.get(position)
+    }
+
+    /// Replaces the entries and binding while keeping the position when it is
+    /// still in range
+    pub fn update(&mut self, other: SetList) {
+        self.entries = other.entries;
+        self.next_cc = other.next_cc;
+        self.position = self.position.filter(|p| *p < self.entries.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sketch: &str) -> SetListEntry {
+        SetListEntry {
+            sketch: sketch.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut set_list = SetList {
+            entries: vec![entry("a"), entry("b")],
+            ..Default::default()
+        };
+
+        assert!(set_list.current().is_none());
+        assert!(set_list.previous_entry().is_none());
+        assert_eq!(set_list.next_entry().unwrap().sketch, "a");
+        assert_eq!(set_list.next_entry().unwrap().sketch, "b");
+        assert!(set_list.next_entry().is_none());
+        assert_eq!(set_list.current().unwrap().sketch, "b");
+        assert_eq!(set_list.previous_entry().unwrap().sketch, "a");
+        assert!(set_list.previous_entry().is_none());
+        assert_eq!(set_list.position, Some(0));
+    }
+
+    #[test]
+    fn test_update_clamps_position() {
+        let mut set_list = SetList {
+            entries: vec![entry("a"), entry("b")],
+            position: Some(1),
+            ..Default::default()
+        };
+
+        set_list.update(SetList {
+            entries: vec![entry("c")],
+            ..Default::default()
+        });
+        assert!(set_list.position.is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! Runs the sketches declared under the live sketch's top-level `inputs` key
+//! alongside it so their output can be read with
+//! [`crate::prelude::Context::input`]. This lets a sketch act as an effect over
+//! one or more "source" sketches instead of having to render everything
+//! itself.
+//!
+//! Sources share the live sketch's [`Context`] (window size, BPM, frame count)
+//! but only the live sketch's hub listens to MIDI and OSC, so a source's
+//! controls hold their script values and animations.
+
+use indexmap::IndexMap;
+use nannou::prelude::*;
+
+use crate::framework::prelude::*;
+use crate::framework::sketch::{self, LifecycleHook, SketchAll};
+use crate::runtime::registry::REGISTRY;
+
+struct Source {
+    sketch_name: String,
+    sketch: Box<dyn SketchAll>,
+}
+
+#[derive(Default)]
+pub struct SketchInputs {
+    /// Keyed by input (texture) name
+    sources: IndexMap<String, Source>,
+}
+
+impl SketchInputs {
+    pub fn clear(&mut self, ctx: &Context) {
+        self.sources.clear();
+        ctx.retain_inputs(|_| false);
+    }
+
+    /// Builds newly declared sources and drops ones no longer declared.
+    /// Returns true if any were built, in which case the live hub needs to
+    /// reclaim the MIDI and OSC inputs (see [`ControlHub::reconnect_inputs`])
+    pub fn sync(
+        &mut self,
+        app: &App,
+        ctx: &Context,
+        live_sketch: &str,
+        inputs: &IndexMap<String, String>,
+    ) -> bool {
+        self.sources.retain(|name, source| {
+            inputs.get(name) == Some(&source.sketch_name)
+        });
+        ctx.retain_inputs(|name| self.sources.contains_key(name));
+
+        let mut built = false;
+
+        for (name, sketch_name) in inputs {
+            if self.sources.contains_key(name) {
+                continue;
+            }
+
+            // A source can't itself have sources, so the only possible cycle
+            // is a sketch reading its own output
+            if sketch_name == live_sketch {
+                warn_once!("Sketch `{}` can't be its own input", sketch_name);
+                continue;
+            }
+
+            let registry = REGISTRY.read().unwrap();
+            let Some(sketch_info) = registry.get(sketch_name) else {
+                warn_once!(
+                    "No sketch named `{}` for input `{}`",
+                    sketch_name,
+                    name
+                );
+                continue;
+            };
+
+            let sketch = sketch_info.init(app, ctx);
+            drop(registry);
+
+            self.sources.insert(
+                name.clone(),
+                Source {
+                    sketch_name: sketch_name.clone(),
+                    sketch,
+                },
+            );
+            built = true;
+
+            info!("Loaded {} as input `{}`", sketch_name, name);
+        }
+
+        built
+    }
+
+    /// Updates each source then publishes its output to `ctx`
+    pub fn update(&mut self, app: &App, update: Update, ctx: &Context) {
+        for (name, source) in &mut self.sources {
+            if let Some(provider) = source.sketch.hub() {
+                if let Some(hub) =
+                    provider.as_any_mut().downcast_mut::<ControlHub<Timing>>()
+                {
+                    hub.update();
+                }
+            }
+
+            source.sketch.update(app, update, ctx);
+
+            match source.sketch.render_output(app, ctx) {
+                Some(view) => ctx.set_input(name, view),
+                None => warn_once!(
+                    "Input `{}` ({}) doesn't implement `render_output`",
+                    name,
+                    source.sketch_name
+                ),
+            }
+        }
+    }
+
+    pub fn on_resize(&mut self, app: &App, ctx: &Context) {
+        for source in self.sources.values_mut() {
+            sketch::run_lifecycle_hook(
+                source.sketch.as_mut(),
+                LifecycleHook::Resize,
+                app,
+                ctx,
+            );
+        }
+    }
+}
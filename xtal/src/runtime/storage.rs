@@ -2,13 +2,19 @@ use std::error::Error;
 use std::path::PathBuf;
 use std::{fs, str};
 
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use super::cue_list::CueList;
 use super::map_mode::Mappings;
+use super::output_warps::OutputWarps;
 use super::serialization::{
-    GlobalSettings, SerializableSketchState, TransitorySketchState,
+    GlobalSettings, SerializableSketchState, TimingOverrides,
+    TransitorySketchState,
 };
+use super::set_list::SetList;
 use crate::framework::prelude::*;
+use crate::framework::sysex::SysExDump;
 use crate::runtime::global;
 
 /// The appropriate OS config dir, currently used to store serialized
@@ -47,6 +53,30 @@ pub fn load_global_state() -> Result<GlobalSettings, Box<dyn Error>> {
     Ok(settings)
 }
 
+/// Writes [`spans::folded`] timings to the cache dir for flamegraph tools
+///
+/// [`spans::folded`]: crate::framework::spans::folded
+pub fn save_trace(
+    sketch_name: &str,
+    folded: &str,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = cache_dir().ok_or("Unable to access cache dir")?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}-trace-{}.folded", sketch_name, uuid_5()));
+    fs::write(&path, folded)?;
+    Ok(path)
+}
+
+/// A new file for a [`ControlHub::start_value_recording`] session
+///
+/// [`ControlHub::start_value_recording`]:
+/// crate::framework::control::ControlHub::start_value_recording
+pub fn value_recording_path(sketch_name: &str) -> PathBuf {
+    PathBuf::from(global::user_data_dir())
+        .join("ValueRecordings")
+        .join(format!("{}-{}.csv", sketch_name, uuid_5()))
+}
+
 fn sketch_state_storage_path(sketch_name: &str) -> PathBuf {
     PathBuf::from(global::user_data_dir())
         .join("Controls")
@@ -58,14 +88,18 @@ pub fn save_sketch_state<T: TimingSource + std::fmt::Debug + 'static>(
     hub: &ControlHub<T>,
     mappings: Mappings,
     exclusions: Vec<String>,
+    timing: TimingOverrides,
 ) -> Result<PathBuf, Box<dyn Error>> {
     let state = TransitorySketchState {
         ui_controls: hub.ui_controls.clone(),
         midi_controls: hub.midi_controls.clone(),
         osc_controls: hub.osc_controls.clone(),
         snapshots: hub.snapshots.clone(),
+        snapshot_phases: hub.snapshot_phases.clone(),
         mappings,
         exclusions,
+        timing,
+        slider_ranges: hub.slider_ranges().clone(),
     };
 
     let serializable_controls = SerializableSketchState::from(&state);
@@ -81,21 +115,141 @@ pub fn save_sketch_state<T: TimingSource + std::fmt::Debug + 'static>(
 /// Takes in external sketch state and merges with deserialized state.
 /// This ensures that the external state can be the source of truth for ui,
 /// midi, and osc keys rather than possibly loading invalid or outdated data
-/// from file.
+/// from file. `renames` (see [`ControlHub::renames`]) are applied first and
+/// the file is rewritten if any saved entries moved.
 pub fn load_sketch_state<'a>(
     sketch_name: &str,
     state: &'a mut TransitorySketchState,
+    renames: &IndexMap<String, String>,
 ) -> Result<&'a mut TransitorySketchState, Box<dyn Error>> {
     let path = sketch_state_storage_path(sketch_name);
-    let bytes = fs::read(path)?;
+    let bytes = fs::read(&path)?;
     let json = str::from_utf8(&bytes).ok().map(|s| s.to_owned()).unwrap();
 
-    let serialized = serde_json::from_str::<SerializableSketchState>(&json)?;
+    let mut serialized =
+        serde_json::from_str::<SerializableSketchState>(&json)?;
+
+    let renamed = serialized.rename_controls(renames);
+    if renamed > 0 {
+        fs::write(&path, serde_json::to_string_pretty(&serialized)?)?;
+        info!(
+            "Migrated {} saved entries of {} to renamed controls",
+            renamed, sketch_name
+        );
+    }
+
     state.merge(serialized);
 
     Ok(state)
 }
 
+// -----------------------------------------------------------------------------
+// SysEx
+// -----------------------------------------------------------------------------
+
+/// Dumps are kept per sketch alongside its mappings so each sketch can restore
+/// the controller setup its mappings were made with
+fn sysex_dump_path(sketch_name: &str) -> PathBuf {
+    PathBuf::from(global::user_data_dir())
+        .join("SysEx")
+        .join(format!("{}.syx", sketch_name))
+}
+
+pub fn save_sysex_dump(
+    sketch_name: &str,
+    dump: &SysExDump,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let path = sysex_dump_path(sketch_name);
+    if let Some(parent_dir) = path.parent() {
+        fs::create_dir_all(parent_dir)?;
+    }
+    fs::write(&path, dump.to_bytes())?;
+    Ok(path)
+}
+
+pub fn load_sysex_dump(sketch_name: &str) -> Result<SysExDump, Box<dyn Error>> {
+    let path = sysex_dump_path(sketch_name);
+    let bytes = fs::read(&path)
+        .map_err(|e| format!("Unable to read {:?}: {}", path, e))?;
+    SysExDump::from_bytes(&bytes)
+}
+
+// -----------------------------------------------------------------------------
+// Set List
+// -----------------------------------------------------------------------------
+
+fn set_list_path() -> PathBuf {
+    PathBuf::from(global::user_data_dir()).join("set_list.json")
+}
+
+/// Returns an empty set list when none has been saved yet. The position is
+/// not restored so a set always starts from the top
+pub fn load_set_list() -> Result<SetList, Box<dyn Error>> {
+    let path = set_list_path();
+    if !path.try_exists().unwrap_or(false) {
+        return Ok(SetList::default());
+    }
+    let bytes = fs::read(&path)?;
+    let mut set_list: SetList = serde_json::from_slice(&bytes)?;
+    set_list.position = None;
+    Ok(set_list)
+}
+
+pub fn save_set_list(set_list: &SetList) -> Result<(), Box<dyn Error>> {
+    let path = set_list_path();
+    if let Some(parent_dir) = path.parent() {
+        fs::create_dir_all(parent_dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(set_list)?)?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Output Warps
+// -----------------------------------------------------------------------------
+
+fn output_warps_path() -> PathBuf {
+    PathBuf::from(global::user_data_dir()).join("output_warps.json")
+}
+
+/// Returns disabled, identity warps when none have been saved yet
+pub fn load_output_warps() -> Result<OutputWarps, Box<dyn Error>> {
+    let path = output_warps_path();
+    if !path.try_exists().unwrap_or(false) {
+        return Ok(OutputWarps::default());
+    }
+    let bytes = fs::read(&path)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub fn save_output_warps(warps: &OutputWarps) -> Result<(), Box<dyn Error>> {
+    let path = output_warps_path();
+    if let Some(parent_dir) = path.parent() {
+        fs::create_dir_all(parent_dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(warps)?)?;
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Cue List
+// -----------------------------------------------------------------------------
+
+/// Hand-written, so unlike the set list it's YAML and never saved by Xtal
+fn cue_list_path() -> PathBuf {
+    PathBuf::from(global::user_data_dir()).join("cues.yaml")
+}
+
+/// Returns an empty cue list when none has been written yet
+pub fn load_cue_list() -> Result<CueList, Box<dyn Error>> {
+    let path = cue_list_path();
+    if !path.try_exists().unwrap_or(false) {
+        return Ok(CueList::default());
+    }
+    let text = fs::read_to_string(&path)?;
+    Ok(serde_yml::from_str(&text)?)
+}
+
 // -----------------------------------------------------------------------------
 // Image Index
 // -----------------------------------------------------------------------------
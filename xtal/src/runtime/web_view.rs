@@ -9,19 +9,58 @@
 //! app::AppEvent
 //! ```
 //! (and vis a versa)
-use ipc_channel::ipc::{IpcOneShotServer, IpcReceiver, IpcSender};
+//!
+//! # Reconnecting
+//! The parent publishes the name of the IPC server it's listening on to
+//! [`ipc_path`] so a web view can also be started on its own with
+//! `cargo run --release -p xtal`. Whenever the web view disconnects the parent
+//! starts listening again so a restarted one can connect in its place.
+//!
+//! # Watchdog
+//! A spawned child that crashes is respawned, with a growing delay and up to
+//! a limit, and once the new web view is ready the parent resends the full app
+//! state. A child that exits before it ever connects (a failed build or a
+//! panic on startup) is caught by a watchdog and respawned the same way
+//! instead of leaving the parent waiting on a connection that will never come.
+use ipc_channel::ipc::{self, IpcOneShotServer, IpcReceiver, IpcSender};
 use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
 use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use super::app::AppEventSender;
 use super::map_mode::Mappings;
+use super::midi_actions::{MidiAction, MidiActionMappings};
+use super::network_sync::SyncRole;
+use super::output_warps::OutputWarps;
+use super::recording::{EncodingProgress, PendingEncodes, RecordingSettings};
+use super::set_list::SetList;
 use crate::framework::control::ui_controls;
+use crate::framework::gpu_stats::SketchGpuUsage;
 use crate::framework::prelude::*;
+use crate::framework::spans::Subsystem;
 use crate::runtime::app::AppEvent;
 
-type Bypassed = HashMap<String, f32>;
+pub type Bypassed = HashMap<String, f32>;
+
+/// Overrides where the IPC server name is published. Needed when running more
+/// than one instance or when the temp dir isn't shared between processes
+pub const IPC_PATH_ENV: &str = "XTAL_IPC_PATH";
+
+/// Consecutive crashes after which the child is no longer respawned
+const MAX_RESPAWNS: u32 = 5;
+
+/// How long a connection must last for the crash count to be reset
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+
+/// How often a spawned child is checked while waiting for it to connect
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum UserDir {
@@ -39,6 +78,30 @@ pub enum OsDir {
 /// Used to send/receive data from our app into a web view using ipc-channel.
 /// Most events should be assumed to be one-way from child to parent unless
 /// otherwise documented.
+/// The parts of a [`SketchConfig`] the sketch selector needs for searching and
+/// filtering
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SketchMetadata {
+    pub name: String,
+    pub display_name: String,
+    pub tags: Vec<String>,
+    pub author: String,
+    pub description: String,
+}
+
+impl From<&SketchConfig> for SketchMetadata {
+    fn from(config: &SketchConfig) -> Self {
+        Self {
+            name: config.name.to_string(),
+            display_name: config.display_name.to_string(),
+            tags: config.tags.iter().map(|tag| tag.to_string()).collect(),
+            author: config.author.to_string(),
+            description: config.description.to_string(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Event {
     Advance,
@@ -46,55 +109,143 @@ pub enum Event {
     /// Sent from parent to alert frontend of various success/failures
     Alert(String),
 
+    /// Sent from parent when the audio device disconnects or reconnects and
+    /// after the device is changed or a sketch is loaded
+    AudioDevice {
+        status: AudioDeviceStatus,
+        devices: Vec<String>,
+    },
+
+    /// Sent from child to toggle auto BPM, and from parent when it fails to
+    /// start
+    AutoBpmEnabled(bool),
+
     /// Sent from parent every ~1sec
     AverageFps(f32),
 
-    /// Sent from parent after receiving Tap event
+    /// Sent from parent after receiving Tap, ChangeBpm, or
+    /// ResetTimingOverrides events
     Bpm(f32),
+
+    /// Sent from parent, at most once per frame, when the set of bypassed
+    /// controls or their values change
+    BypassedChanged(Bypassed),
+
+    /// Frame count and beats between frames. See [`AppEvent::CaptureFlipbook`]
+    CaptureFlipbook(usize, f32),
     CaptureFrame,
     ChangeAudioDevice(String),
+    ChangeBpm(f32),
+    ChangeDepthDevice(String),
 
     /// Event intercepted from frontend -> web_view_process to open a File
     /// Dialog. See [`Event::ReceiveDir`] for making use of the new dir
     ChangeDir(UserDir),
+    ChangeFps(f32),
+    ChangeLatencyOffsets(LatencyOffsets),
     ChangeMidiClockPort(String),
     ChangeMidiControlInputPort(String),
     ChangeMidiControlOutputPort(String),
+    ChangeMsaaSamples(u32),
     ChangeOscPort(u16),
+    ChangeRecordingSettings(RecordingSettings),
+    /// A slider name and its new range, or `None` to restore the declared one.
+    /// See [`ControlHub::set_slider_range`]
+    ChangeSliderRange(String, Option<SliderRange>),
+    ChangeStereo(gpu::StereoSettings),
+    ChangeSyncRole(SyncRole),
 
     ClearBuffer,
     CommitMappings,
+
+    /// Sent from parent in response to [`Event::Quit`] when this many takes
+    /// are still recording or encoding. Answered with [`Event::Shutdown`]
+    ConfirmQuit(usize),
+
+    /// Sent from parent every few frames while history tracking is enabled
+    /// (see [`Event::TrackControlHistory`]): recent values per control, oldest
+    /// first
+    ControlHistory(HashMap<String, Vec<f32>>),
+
+    /// Sent from parent, at most once per frame, with only the controls whose
+    /// value or disabled state changed since they were last sent
+    ControlsChanged(Vec<Control>),
+    /// Sent from parent on each beat of a recording count-in, with the beats
+    /// remaining, and `None` once it ends
+    CountIn(Option<u32>),
     CurrentlyMapping(String),
+    CycleAspectPreset,
+    CycleCalibration,
+
+    /// Sent from parent in response to [`Event::RequestDebugGraph`]. `None`
+    /// when the sketch has no hub
+    DebugGraph(Option<DebugGraph>),
 
-    /// Sent from parent after a recording has been stopped and video encoding
-    /// has began
+    /// Sent from parent after a recording has been stopped and its take queued
+    /// for encoding (`true`) and once the encode queue is empty (`false`)
     Encoding(bool),
 
+    /// Sent from parent as takes are encoded; `None` once the queue is empty
+    EncodingProgress(Option<EncodingProgress>),
+
     /// TODO: are we even using this?
     Error(String),
+
+    /// Sent from parent whenever favorite sketches are toggled or reordered
+    Favorites(Vec<String>),
+
+    /// Sent from parent after receiving ChangeFps or ResetTimingOverrides
+    /// events
+    Fps(f32),
+    Frozen(bool),
+
+    /// Sent from parent in response to [`Event::RequestGpuStats`]
+    GpuStats(Vec<SketchGpuUsage>),
+
+    /// Sent from parent in response to [`Event::Hold`] with every held
+    /// control
+    Held(Vec<String>),
+
+    /// Latch (`true`) or release the current value of a control by name
+    Hold(String, bool),
     Hrcc(bool),
 
     /// Sent from parent whenever a control script has changed and controls have
     /// been reloaded
     HubPopulated((Vec<Control>, Bypassed)),
+    Instrumentation(bool),
 
     /// Sent from parent after child sends [`Event::Ready`]
     #[serde(rename_all = "camelCase")]
     Init {
         audio_device: String,
         audio_devices: Vec<String>,
+        depth_device: String,
+        depth_devices: Vec<String>,
+        favorite_sketches: Vec<String>,
         hrcc: bool,
         images_dir: String,
         is_light_theme: bool,
+        latency_offsets: LatencyOffsets,
         mappings_enabled: bool,
         midi_clock_port: String,
         midi_input_port: String,
         midi_output_port: String,
         midi_input_ports: Vec<(usize, String)>,
         midi_output_ports: Vec<(usize, String)>,
+        midi_actions: MidiActionMappings,
         osc_port: u16,
+        output_warps: OutputWarps,
+        prewarm: bool,
+        recent_sketches: Vec<String>,
+        recording_settings: RecordingSettings,
+        set_list: SetList,
         sketch_names: Vec<String>,
+        sketches: Vec<SketchMetadata>,
         sketch_name: String,
+        snapshot_phase: bool,
+        stereo: gpu::StereoSettings,
+        sync_role: SyncRole,
         transition_time: f32,
         user_data_dir: String,
         videos_dir: String,
@@ -109,22 +260,55 @@ pub enum Event {
         display_name: String,
         fps: f32,
         mappings: Mappings,
+        msaa_samples: u32,
         paused: bool,
         perf_mode: bool,
+        profile: String,
+        /// Empty unless the sketch declares profiles
+        profiles: Vec<String>,
+        recent_sketches: Vec<String>,
         sketch_name: String,
         sketch_width: i32,
         sketch_height: i32,
         snapshot_slots: Vec<String>,
         tap_tempo_enabled: bool,
+        auto_bpm_enabled: bool,
         exclusions: Exclusions,
     },
 
+    /// Sent from child to start (or cancel, when `None`) learning the CC for a
+    /// runtime MIDI action
+    LearnMidiAction(Option<MidiAction>),
+
     /// Sent whenever the user physically moves a MIDI control when in map mode
     Mappings(Mappings),
     MappingsEnabled(bool),
+
+    /// Sent from parent whenever a runtime MIDI action is learned or removed
+    MidiActions(MidiActionMappings),
     OpenOsDir(OsDir),
+
+    /// A two-way message. Sent from the UI after editing a warp, switching
+    /// venues, or toggling the warp, and from parent when they are reloaded
+    OutputWarps(OutputWarps),
     Paused(bool),
     PerfMode(bool),
+    Prewarm(bool),
+    /// Sent when a sketch is picked in the sketch list while pre-warming is
+    /// enabled; [`Event::SwitchSketch`] follows once the user confirms
+    PrewarmSketch(String),
+
+    /// Sent from parent in response to [`Event::RequestPreview`]. Empty when
+    /// the sketch has no such control
+    Preview {
+        name: String,
+        values: Vec<f32>,
+    },
+
+    /// Sent from child to switch the sketch's control script profile, and
+    /// from parent with the profile actually in use afterwards. See
+    /// [`ControlHub::set_profile`]
+    Profile(String),
     QueueRecord,
     Quit,
     Ready,
@@ -135,17 +319,84 @@ pub enum Event {
     /// 3. Sent from here back to frontend to show the updated dir
     ReceiveDir(UserDir, String),
     Randomize(Exclusions),
+    /// Drops a marker into the current recording take
+    RecordingMarker(Option<String>),
+
+    /// Sent from parent when a control value recording starts (`true`) or is
+    /// saved (`false`)
+    RecordingValues(bool),
+
+    /// Start recording the values of the given controls and groups (every
+    /// control when empty) or stop and save (`None`). See
+    /// [`ControlHub::start_value_recording`]
+    RecordValues(Option<Vec<String>>),
     RemoveMapping(String),
+    RemoveMidiAction(MidiAction),
+    /// Replace the favorite sketches with the given order. Answered with
+    /// [`Event::Favorites`]
+    ReorderFavorites(Vec<String>),
+    /// Play back a control value recording at this path, or stop (`None`).
+    /// See [`ControlHub::start_replay`]
+    Replay(Option<String>),
+    RequestDebugGraph,
+    /// Answered with [`Event::GpuStats`]
+    RequestGpuStats,
+    /// Sample a control over the coming beats. Answered with
+    /// [`Event::Preview`]. See [`ControlHub::preview`]
+    RequestPreview {
+        name: String,
+        resolution: usize,
+        beats: f32,
+    },
+    /// Compare two snapshots. Answered with [`Event::SnapshotDiff`]
+    RequestSnapshotDiff(String, String),
     Reset,
+    ResetTimingOverrides,
+    /// Temporarily bypass (`true`) or restore a control, effect, or `mod`
+    /// entry by name. Answered with [`Event::DebugGraph`]
+    RuntimeBypass(String, bool),
     Save(Vec<String>),
+    SaveTrace,
+
+    /// Sent from parent when a hot-reloaded control script fails to load, and
+    /// with `None` once it loads again. Controls keep the last good
+    /// configuration in the meantime
+    ScriptError(Option<ScriptError>),
     SendMidi,
 
-    /// Sent from parent after a snapshot has completed so we can keep controls
-    /// in sync
-    SnapshotEnded(Vec<Control>),
+    /// A two-way message. Sent from the UI after editing the set list and
+    /// from parent whenever the set list or its position changes
+    SetList(SetList),
+    SetListGoTo(usize),
+    SetListNext,
+    SetListPrevious,
+    Shutdown(PendingEncodes),
+
+    /// Sent from parent after a snapshot has completed. The new values follow
+    /// in [`Event::ControlsChanged`]
+    SnapshotEnded,
+    /// See [`ControlHub::copy_snapshot_values`]
+    SnapshotCopyValues {
+        from: String,
+        to: String,
+        names: Vec<String>,
+    },
     SnapshotDelete(String),
+
+    /// Sent from parent in response to [`Event::RequestSnapshotDiff`]
+    SnapshotDiff {
+        a: String,
+        b: String,
+        deltas: Vec<SnapshotDiffEntry>,
+    },
     SnapshotRecall(String),
+    /// A snapshot id and the controls or groups to recall
+    SnapshotRecallOnly(String, Vec<String>),
     SnapshotStore(String),
+    SnapshotPhase(bool),
+    /// Apply only the named modulator, or all of them again when `None`.
+    /// Answered with [`Event::DebugGraph`]
+    Solo(Option<String>),
 
     /// A two-way message. Can be sent manually from UI, or set from backend
     /// when receiving a MIDI Start when QueueRecording is enabled
@@ -156,16 +407,35 @@ pub enum Event {
     StopRecording,
 
     SwitchSketch(String),
+
+    /// Sent from parent after a ChangeSyncRole event, reporting the role that
+    /// is actually in effect (`Off` if it failed to start)
+    SyncRole(SyncRole),
+
+    /// Start (`true`) or stop and save (`false`) recording a SysEx dump. See
+    /// [`AppEvent::SysExBackup`]
+    SysExBackup(bool),
+    SysExRestore,
     Tap,
     TapTempoEnabled(bool),
+    /// Answered with [`Event::Favorites`]
+    ToggleFavorite(String),
     ToggleFullScreen,
 
     /// Two message depending on which window receives the key event
     ToggleGuiFocus,
+    ToggleLetterbox,
 
     /// Two message depending on which window receives the key event
     ToggleMainFocus,
+    ToggleSafeAreaGuides,
+    TraceSubsystem(Subsystem, bool),
+
+    /// Chooses which controls' values are sent back in
+    /// [`Event::ControlHistory`]. Persists across sketch switches
+    TrackControlHistory(HistoryTracking),
     TransitionTime(f32),
+    TriggerEnvelope(String),
     UpdateControlBool {
         name: String,
         value: bool,
@@ -178,215 +448,656 @@ pub enum Event {
         name: String,
         value: String,
     },
-
-    /// Sent from parent
-    UpdatedControls(Vec<Control>),
 }
 
 pub type Sender = IpcSender<Event>;
 pub type Receiver = IpcReceiver<Event>;
 
-#[derive(Clone)]
+/// Sends events to the web view when one is connected and drops them
+/// otherwise
+#[derive(Clone, Default)]
 pub struct EventSender {
-    tx: Sender,
+    tx: Arc<Mutex<Option<Sender>>>,
 }
 
 impl EventSender {
-    pub fn new(tx: Sender) -> Self {
-        Self { tx }
+    /// A sender that drops every event, for running without the web view
+    pub fn disconnected() -> Self {
+        Self::default()
     }
 
     pub fn emit(&self, event: Event) {
-        self.tx.send(event).expect("Failed to send event");
+        let mut tx = self.tx.lock().unwrap();
+        if let Some(sender) = tx.as_ref() {
+            if let Err(e) = sender.send(event) {
+                warn!("Web view unreachable: {}", e);
+                *tx = None;
+            }
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.tx.lock().unwrap().is_some()
+    }
+
+    fn connect(&self, sender: Sender) {
+        *self.tx.lock().unwrap() = Some(sender);
+    }
+
+    fn disconnect(&self) {
+        *self.tx.lock().unwrap() = None;
     }
 }
 
 type Bootstrap = (Sender, Receiver);
 
+/// Where the parent publishes its IPC server name; see [`IPC_PATH_ENV`]
+pub fn ipc_path() -> PathBuf {
+    std::env::var_os(IPC_PATH_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("xtal-web-view.ipc"))
+}
+
+/// The IPC server name published by a running parent, for web views started
+/// on their own
+pub fn published_server_name(path: &Path) -> Result<String, Box<dyn Error>> {
+    let name = fs::read_to_string(path).map_err(|e| {
+        format!("No Xtal process listening at {}: {}", path.display(), e)
+    })?;
+    Ok(name.trim().to_string())
+}
+
+/// A handle to the web view child process, shared with the thread that
+/// respawns it
+#[derive(Clone)]
+pub struct WebViewProcess {
+    child: Arc<Mutex<Option<Child>>>,
+    ipc_path: PathBuf,
+    stopped: Arc<AtomicBool>,
+}
+
+impl WebViewProcess {
+    fn new(ipc_path: PathBuf) -> Self {
+        Self {
+            child: Arc::new(Mutex::new(None)),
+            ipc_path,
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Kills the child and stops listening for new connections
+    pub fn kill(&self) {
+        self.stopped.store(true, Ordering::Release);
+
+        if let Some(child) = self.child.lock().unwrap().as_mut() {
+            match child.kill() {
+                Ok(_) => debug!("Killed ui_process"),
+                Err(e) => error!("Error killing ui_process {}", e),
+            }
+        }
+
+        fs::remove_file(&self.ipc_path).ok();
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    fn has_exited(&self) -> bool {
+        self.child
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(Some(_))))
+    }
+
+    fn spawn(&self, server_name: &str) -> Result<(), Box<dyn Error>> {
+        let mut child = Command::new("cargo")
+            .args(["run", "--release", "-p", "xtal", "--", server_name])
+            .env("RUST_LOG", "xtal=debug")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        trace!("Child process spawned");
+
+        if let Some(stdout) = child.stdout.take() {
+            let reader = BufReader::new(stdout);
+            thread::spawn(move || {
+                for line in reader.lines().map_while(Result::ok) {
+                    println!("{}", line);
+                }
+            });
+        }
+
+        *self.child.lock().unwrap() = Some(child);
+
+        Ok(())
+    }
+
+    /// Waits for the child to exit after a disconnect, returning true if it
+    /// exited abnormally (or hung and had to be killed)
+    fn reap(&self) -> bool {
+        let Some(mut child) = self.child.lock().unwrap().take() else {
+            return false;
+        };
+
+        for _ in 0..10 {
+            if let Ok(Some(status)) = child.try_wait() {
+                return !status.success();
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        child.kill().ok();
+        child.wait().ok();
+        true
+    }
+}
+
 /// Launches the tao/wry web_view code as a child process and sets up IPC
 /// channels. This is necessary because both tao and nannou need to run on a
 /// main thread and control the event loop, which we can't have in a single
-/// process (at least not on all OSs)
+/// process (at least not on all OSs). When `spawn_child` is false no child is
+/// started and a web view is expected to be launched separately. Either way
+/// the connection is supervised on a background thread; see the module docs
 pub fn launch(
     app_tx: &AppEventSender,
-) -> Result<(EventSender, Child), Box<dyn std::error::Error>> {
-    let (server, server_name) = IpcOneShotServer::<Bootstrap>::new()?;
+    ipc_path: PathBuf,
+    spawn_child: bool,
+) -> (EventSender, WebViewProcess) {
+    let wv_tx = EventSender::default();
+    let process = WebViewProcess::new(ipc_path);
+
+    let app_tx = app_tx.clone();
+    let supervisor_tx = wv_tx.clone();
+    let supervisor_process = process.clone();
+    thread::spawn(move || {
+        supervise(app_tx, supervisor_tx, supervisor_process, spawn_child);
+    });
+
+    (wv_tx, process)
+}
 
-    let mut child = Command::new("cargo")
-        .args(["run", "--release", "-p", "xtal", "--", &server_name])
-        .env("RUST_LOG", "xtal=debug")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()?;
+fn supervise(
+    app_tx: AppEventSender,
+    wv_tx: EventSender,
+    process: WebViewProcess,
+    spawn_child: bool,
+) {
+    let mut spawn = spawn_child;
+    let mut crashes = 0;
 
-    trace!("Child process spawned");
+    while !process.is_stopped() {
+        let started = Instant::now();
 
-    if let Some(stdout) = child.stdout.take() {
-        let reader = BufReader::new(stdout);
-        thread::spawn(move || {
-            for line in reader.lines().map_while(Result::ok) {
-                println!("{}", line);
+        if let Err(e) = connect(&app_tx, &wv_tx, &process, spawn) {
+            error!("Web view connection error: {}", e);
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        wv_tx.disconnect();
+
+        if process.is_stopped() {
+            break;
+        }
+
+        app_tx.emit(AppEvent::WebViewDisconnected);
+
+        // Only crashed children are respawned; web views that were closed
+        // are expected to be started again on their own
+        let crashed = spawn_child && process.reap();
+        spawn = false;
+
+        match restart(crashed, crashes, started.elapsed()) {
+            Restart::Wait => {}
+            Restart::Respawn(count) => {
+                crashes = count;
+                spawn = true;
+                warn!(
+                    "Web view crashed; respawning ({}/{})",
+                    crashes, MAX_RESPAWNS
+                );
+                thread::sleep(Duration::from_secs(crashes as u64));
             }
-        });
+            Restart::GiveUp => {
+                error!(
+                    "Web view crashed {} times; no longer respawning",
+                    MAX_RESPAWNS
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Restart {
+    /// Wait for a web view to connect on its own
+    Wait,
+    /// Respawn the child after its nth consecutive crash
+    Respawn(u32),
+    GiveUp,
+}
+
+/// What to do once the child disconnects, given whether it `crashed`, how many
+/// consecutive `crashes` preceded it, and how long it was up. A child that
+/// stayed up longer than [`STABLE_CONNECTION`] starts the crash count over
+fn restart(crashed: bool, crashes: u32, uptime: Duration) -> Restart {
+    if !crashed {
+        return Restart::Wait;
+    }
+
+    let crashes = ternary!(uptime > STABLE_CONNECTION, 0, crashes);
+
+    if crashes < MAX_RESPAWNS {
+        Restart::Respawn(crashes + 1)
+    } else {
+        Restart::GiveUp
+    }
+}
+
+/// Listens for (and optionally spawns) a single web view, then forwards its
+/// events until it disconnects
+fn connect(
+    app_tx: &AppEventSender,
+    wv_tx: &EventSender,
+    process: &WebViewProcess,
+    spawn: bool,
+) -> Result<(), Box<dyn Error>> {
+    let (server, server_name) = IpcOneShotServer::<Bootstrap>::new()?;
+    fs::write(&process.ipc_path, &server_name)?;
+
+    let connected = Arc::new(AtomicBool::new(false));
+    let abandoned = Arc::new(AtomicBool::new(false));
+
+    if spawn {
+        process.spawn(&server_name)?;
+        watch_startup(
+            process.clone(),
+            server_name,
+            connected.clone(),
+            abandoned.clone(),
+        );
+    } else {
+        info!(
+            "Waiting for a web view to connect via {}",
+            process.ipc_path.display()
+        );
     }
 
     let (_receiver, (sender, receiver)): (IpcReceiver<Bootstrap>, Bootstrap) =
         server.accept()?;
+    connected.store(true, Ordering::Release);
 
-    let app_tx = app_tx.clone();
-    let wv_tx = sender.clone();
+    if abandoned.load(Ordering::Acquire) {
+        return Err("Web view exited before connecting".into());
+    }
 
-    thread::spawn(move || {
-        while let Ok(message) = receiver.recv() {
-            trace!("Received message from child: {:?}", message);
+    wv_tx.connect(sender);
+    info!("Web view connected");
 
-            // Events with empty implementations are direct Parent->Child
-            match message {
-                Event::Advance => {
-                    app_tx.emit(AppEvent::AdvanceSingleFrame);
-                }
-                Event::Alert(_) => {}
-                Event::AverageFps(_) => {}
-                Event::Bpm(_) => {}
-                Event::CaptureFrame => {
-                    app_tx.emit(AppEvent::CaptureFrame);
-                }
-                Event::ChangeAudioDevice(name) => {
-                    app_tx.emit(AppEvent::ChangeAudioDevice(name));
-                }
-                Event::ChangeDir(_) => {}
-                Event::ChangeMidiClockPort(port) => {
-                    app_tx.emit(AppEvent::ChangeMidiClockPort(port));
-                }
-                Event::ChangeMidiControlInputPort(port) => {
-                    app_tx.emit(AppEvent::ChangeMidiControlInputPort(port));
-                }
-                Event::ChangeMidiControlOutputPort(port) => {
-                    app_tx.emit(AppEvent::ChangeMidiControlOutputPort(port));
-                }
-                Event::ChangeOscPort(port) => {
-                    app_tx.emit(AppEvent::ChangeOscPort(port));
-                }
-                Event::ClearBuffer => {
-                    app_tx.emit(AppEvent::ClearNextFrame);
-                }
-                Event::CommitMappings => {
-                    app_tx.emit(AppEvent::CommitMappings);
-                }
-                Event::CurrentlyMapping(name) => {
-                    app_tx.emit(AppEvent::CurrentlyMapping(name.clone()));
-                }
-                Event::Encoding(_) => {}
-                Event::Error(e) => error!("Received error from child: {}", e),
-                Event::Hrcc(hrcc) => {
-                    app_tx.emit(AppEvent::Hrcc(hrcc));
-                }
-                Event::HubPopulated(_) => {}
-                Event::Init { .. } => {}
-                Event::LoadSketch { .. } => {}
-                Event::Mappings(mappings) => {
-                    app_tx.emit(AppEvent::ReceiveMappings(mappings));
-                }
-                Event::MappingsEnabled(enabled) => {
-                    app_tx.emit(AppEvent::MappingsEnabled(enabled));
-                }
-                Event::OpenOsDir(os_dir) => {
-                    app_tx.emit(AppEvent::OpenOsDir(os_dir));
-                }
-                Event::Paused(paused) => {
-                    app_tx.emit(AppEvent::Paused(paused));
-                }
-                Event::PerfMode(perf_mode) => {
-                    app_tx.emit(AppEvent::PerfMode(perf_mode));
-                }
-                Event::QueueRecord => {
-                    app_tx.emit(AppEvent::QueueRecord);
-                }
-                Event::Quit => {
-                    app_tx.emit(AppEvent::Quit);
-                }
-                Event::Randomize(exclusions) => {
-                    app_tx.emit(AppEvent::Randomize(exclusions));
-                }
-                Event::Ready => {
-                    app_tx.emit(AppEvent::WebViewReady);
-                }
-                Event::ReceiveDir(kind, dir) => {
-                    app_tx
-                        .emit(AppEvent::ReceiveDir(kind.clone(), dir.clone()));
-                    wv_tx.send(Event::ReceiveDir(kind, dir)).unwrap();
-                }
-                Event::RemoveMapping(name) => {
-                    app_tx.emit(AppEvent::RemoveMapping(name));
-                }
-                Event::Reset => {
-                    app_tx.emit(AppEvent::Reset);
-                }
-                Event::StartRecording => {
-                    app_tx.emit(AppEvent::StartRecording);
-                }
-                Event::Save(exclusions) => {
-                    app_tx.emit(AppEvent::Save(exclusions));
-                }
-                Event::SendMidi => {
-                    app_tx.emit(AppEvent::SendMidi);
-                }
-                Event::SnapshotEnded(_) => {}
-                Event::SnapshotRecall(id) => {
-                    app_tx.emit(AppEvent::SnapshotRecall(id.clone()));
-                }
-                Event::SnapshotDelete(id) => {
-                    app_tx.emit(AppEvent::SnapshotDelete(id.clone()));
-                }
-                Event::SnapshotStore(id) => {
-                    app_tx.emit(AppEvent::SnapshotStore(id.clone()));
-                }
-                Event::StopRecording => {
-                    app_tx.emit(AppEvent::StopRecording);
-                }
-                Event::SwitchSketch(sketch_name) => {
-                    app_tx.emit(AppEvent::SwitchSketch(sketch_name.clone()));
-                }
-                Event::Tap => {
-                    app_tx.emit(AppEvent::Tap);
-                }
-                Event::TapTempoEnabled(enabled) => {
-                    app_tx.emit(AppEvent::TapTempoEnabled(enabled));
-                }
-                Event::ToggleFullScreen => {
-                    app_tx.emit(AppEvent::ToggleFullScreen);
-                }
-                Event::ToggleGuiFocus => {}
-                Event::ToggleMainFocus => {
-                    app_tx.emit(AppEvent::ToggleMainFocus);
-                }
-                Event::TransitionTime(time) => {
-                    app_tx.emit(AppEvent::TransitionTime(time));
-                }
-                Event::UpdateControlBool { name, value } => {
-                    app_tx.emit(AppEvent::UpdateUiControl((
-                        name.clone(),
-                        ControlValue::from(value),
-                    )))
-                }
-                Event::UpdateControlFloat { name, value } => {
-                    app_tx.emit(AppEvent::UpdateUiControl((
-                        name.clone(),
-                        ControlValue::from(value),
-                    )))
-                }
-                Event::UpdateControlString { name, value } => {
-                    app_tx.emit(AppEvent::UpdateUiControl((
-                        name.clone(),
-                        ControlValue::from(value.clone()),
-                    )))
+    forward(app_tx, wv_tx, receiver);
+
+    Ok(())
+}
+
+/// Watches a freshly spawned child until it connects. If it exits first,
+/// marks the attempt `abandoned` and connects a dead channel pair in its place
+/// so [`connect`] stops waiting and the supervisor can respawn it
+fn watch_startup(
+    process: WebViewProcess,
+    server_name: String,
+    connected: Arc<AtomicBool>,
+    abandoned: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        while !connected.load(Ordering::Acquire) && !process.is_stopped() {
+            if process.has_exited() {
+                abandoned.store(true, Ordering::Release);
+                if let Err(e) = unblock_accept(server_name) {
+                    error!("Unable to unblock web view connection: {}", e);
                 }
-                Event::UpdatedControls(_) => {}
+                return;
             }
+            thread::sleep(WATCHDOG_INTERVAL);
         }
     });
+}
 
-    Ok((EventSender::new(sender), child))
+fn unblock_accept(server_name: String) -> Result<(), Box<dyn Error>> {
+    // Both counterparts are dropped right away so the forwarding loop ends as
+    // soon as it starts
+    let (sender, _) = ipc::channel::<Event>()?;
+    let (_, receiver) = ipc::channel::<Event>()?;
+    IpcSender::<Bootstrap>::connect(server_name)?.send((sender, receiver))?;
+    Ok(())
+}
+
+fn forward(app_tx: &AppEventSender, wv_tx: &EventSender, receiver: Receiver) {
+    while let Ok(message) = receiver.recv() {
+        trace!("Received message from child: {:?}", message);
+
+        // Events with empty implementations are direct Parent->Child
+        match message {
+            Event::Advance => {
+                app_tx.emit(AppEvent::AdvanceSingleFrame);
+            }
+            Event::Alert(_) => {}
+            Event::AudioDevice { .. } => {}
+            Event::AutoBpmEnabled(enabled) => {
+                app_tx.emit(AppEvent::AutoBpmEnabled(enabled));
+            }
+            Event::AverageFps(_) => {}
+            Event::Bpm(_) => {}
+            Event::BypassedChanged(_) => {}
+            Event::CaptureFlipbook(frames, beats) => {
+                app_tx.emit(AppEvent::CaptureFlipbook(frames, beats));
+            }
+            Event::CaptureFrame => {
+                app_tx.emit(AppEvent::CaptureFrame);
+            }
+            Event::ChangeAudioDevice(name) => {
+                app_tx.emit(AppEvent::ChangeAudioDevice(name));
+            }
+            Event::ChangeBpm(bpm) => {
+                app_tx.emit(AppEvent::ChangeBpm(bpm));
+            }
+            Event::ChangeDepthDevice(name) => {
+                app_tx.emit(AppEvent::ChangeDepthDevice(name));
+            }
+            Event::ChangeDir(_) => {}
+            Event::ChangeFps(fps) => {
+                app_tx.emit(AppEvent::ChangeFps(fps));
+            }
+            Event::ChangeLatencyOffsets(latency_offsets) => {
+                app_tx.emit(AppEvent::ChangeLatencyOffsets(latency_offsets));
+            }
+            Event::ChangeMidiClockPort(port) => {
+                app_tx.emit(AppEvent::ChangeMidiClockPort(port));
+            }
+            Event::ChangeMidiControlInputPort(port) => {
+                app_tx.emit(AppEvent::ChangeMidiControlInputPort(port));
+            }
+            Event::ChangeMidiControlOutputPort(port) => {
+                app_tx.emit(AppEvent::ChangeMidiControlOutputPort(port));
+            }
+            Event::ChangeMsaaSamples(samples) => {
+                app_tx.emit(AppEvent::ChangeMsaaSamples(samples));
+            }
+            Event::ChangeOscPort(port) => {
+                app_tx.emit(AppEvent::ChangeOscPort(port));
+            }
+            Event::ChangeRecordingSettings(settings) => {
+                app_tx.emit(AppEvent::ChangeRecordingSettings(settings));
+            }
+            Event::ChangeSliderRange(name, range) => {
+                app_tx.emit(AppEvent::ChangeSliderRange(name, range));
+            }
+            Event::ChangeStereo(settings) => {
+                app_tx.emit(AppEvent::ChangeStereo(settings));
+            }
+            Event::ChangeSyncRole(role) => {
+                app_tx.emit(AppEvent::ChangeSyncRole(role));
+            }
+            Event::ClearBuffer => {
+                app_tx.emit(AppEvent::ClearNextFrame);
+            }
+            Event::CommitMappings => {
+                app_tx.emit(AppEvent::CommitMappings);
+            }
+            Event::ConfirmQuit(_) => {}
+            Event::ControlHistory(_) => {}
+            Event::ControlsChanged(_) => {}
+            Event::CountIn(_) => {}
+            Event::CurrentlyMapping(name) => {
+                app_tx.emit(AppEvent::CurrentlyMapping(name.clone()));
+            }
+            Event::CycleAspectPreset => {
+                app_tx.emit(AppEvent::CycleAspectPreset);
+            }
+            Event::CycleCalibration => {
+                app_tx.emit(AppEvent::CycleCalibration);
+            }
+            Event::DebugGraph(_) => {}
+            Event::Encoding(_) => {}
+            Event::EncodingProgress(_) => {}
+            Event::Error(e) => error!("Received error from child: {}", e),
+            Event::Favorites(_) => {}
+            Event::Fps(_) => {}
+            Event::Frozen(frozen) => {
+                app_tx.emit(AppEvent::Frozen(frozen));
+            }
+            Event::GpuStats(_) => {}
+            Event::Held(_) => {}
+            Event::Hold(name, held) => {
+                app_tx.emit(AppEvent::Hold(name, held));
+            }
+            Event::Hrcc(hrcc) => {
+                app_tx.emit(AppEvent::Hrcc(hrcc));
+            }
+            Event::HubPopulated(_) => {}
+            Event::Init { .. } => {}
+            Event::Instrumentation(enabled) => {
+                app_tx.emit(AppEvent::Instrumentation(enabled));
+            }
+            Event::LearnMidiAction(action) => {
+                app_tx.emit(AppEvent::LearnMidiAction(action));
+            }
+            Event::LoadSketch { .. } => {}
+            Event::Mappings(mappings) => {
+                app_tx.emit(AppEvent::ReceiveMappings(mappings));
+            }
+            Event::MappingsEnabled(enabled) => {
+                app_tx.emit(AppEvent::MappingsEnabled(enabled));
+            }
+            Event::MidiActions(_) => {}
+            Event::OpenOsDir(os_dir) => {
+                app_tx.emit(AppEvent::OpenOsDir(os_dir));
+            }
+            Event::OutputWarps(output_warps) => {
+                app_tx.emit(AppEvent::OutputWarps(output_warps));
+            }
+            Event::Paused(paused) => {
+                app_tx.emit(AppEvent::Paused(paused));
+            }
+            Event::PerfMode(perf_mode) => {
+                app_tx.emit(AppEvent::PerfMode(perf_mode));
+            }
+            Event::Prewarm(prewarm) => {
+                app_tx.emit(AppEvent::Prewarm(prewarm));
+            }
+            Event::PrewarmSketch(name) => {
+                app_tx.emit(AppEvent::PrewarmSketch(name));
+            }
+            Event::Preview { .. } => {}
+            Event::Profile(name) => {
+                app_tx.emit(AppEvent::Profile(name));
+            }
+            Event::QueueRecord => {
+                app_tx.emit(AppEvent::QueueRecord);
+            }
+            Event::Quit => {
+                app_tx.emit(AppEvent::Quit);
+            }
+            Event::Randomize(exclusions) => {
+                app_tx.emit(AppEvent::Randomize(exclusions));
+            }
+            Event::Ready => {
+                app_tx.emit(AppEvent::WebViewReady);
+            }
+            Event::ReceiveDir(kind, dir) => {
+                app_tx.emit(AppEvent::ReceiveDir(kind.clone(), dir.clone()));
+                wv_tx.emit(Event::ReceiveDir(kind, dir));
+            }
+            Event::RecordingMarker(name) => {
+                app_tx.emit(AppEvent::RecordingMarker(name));
+            }
+            Event::RecordingValues(_) => {}
+            Event::RecordValues(selection) => {
+                app_tx.emit(AppEvent::RecordValues(selection));
+            }
+            Event::RemoveMapping(name) => {
+                app_tx.emit(AppEvent::RemoveMapping(name));
+            }
+            Event::RemoveMidiAction(action) => {
+                app_tx.emit(AppEvent::RemoveMidiAction(action));
+            }
+            Event::ReorderFavorites(names) => {
+                app_tx.emit(AppEvent::ReorderFavorites(names));
+            }
+            Event::Replay(path) => {
+                app_tx.emit(AppEvent::Replay(path.map(PathBuf::from)));
+            }
+            Event::RequestDebugGraph => {
+                app_tx.emit(AppEvent::RequestDebugGraph);
+            }
+            Event::RequestGpuStats => {
+                app_tx.emit(AppEvent::RequestGpuStats);
+            }
+            Event::RequestPreview {
+                name,
+                resolution,
+                beats,
+            } => {
+                app_tx.emit(AppEvent::RequestPreview(name, resolution, beats));
+            }
+            Event::RequestSnapshotDiff(a, b) => {
+                app_tx.emit(AppEvent::SnapshotDiff(a.clone(), b.clone()));
+            }
+            Event::Reset => {
+                app_tx.emit(AppEvent::Reset);
+            }
+            Event::ResetTimingOverrides => {
+                app_tx.emit(AppEvent::ResetTimingOverrides);
+            }
+            Event::RuntimeBypass(name, bypassed) => {
+                app_tx.emit(AppEvent::RuntimeBypass(name, bypassed));
+            }
+            Event::StartRecording => {
+                app_tx.emit(AppEvent::StartRecording);
+            }
+            Event::Save(exclusions) => {
+                app_tx.emit(AppEvent::Save(exclusions));
+            }
+            Event::SaveTrace => {
+                app_tx.emit(AppEvent::SaveTrace);
+            }
+            Event::ScriptError(_) => {}
+            Event::SendMidi => {
+                app_tx.emit(AppEvent::SendMidi);
+            }
+            Event::SetList(set_list) => {
+                app_tx.emit(AppEvent::SetList(set_list));
+            }
+            Event::SetListGoTo(position) => {
+                app_tx.emit(AppEvent::SetListGoTo(position));
+            }
+            Event::SetListNext => {
+                app_tx.emit(AppEvent::SetListNext);
+            }
+            Event::SetListPrevious => {
+                app_tx.emit(AppEvent::SetListPrevious);
+            }
+            Event::Shutdown(pending) => {
+                app_tx.emit(AppEvent::Shutdown(pending));
+            }
+            Event::SnapshotEnded => {}
+            Event::SnapshotRecall(id) => {
+                app_tx.emit(AppEvent::SnapshotRecall(id.clone()));
+            }
+            Event::SnapshotRecallOnly(id, selection) => {
+                app_tx.emit(AppEvent::SnapshotRecallOnly(
+                    id.clone(),
+                    selection.clone(),
+                ));
+            }
+            Event::SnapshotCopyValues { from, to, names } => {
+                app_tx.emit(AppEvent::SnapshotCopyValues(
+                    from.clone(),
+                    to.clone(),
+                    names.clone(),
+                ));
+            }
+            Event::SnapshotDelete(id) => {
+                app_tx.emit(AppEvent::SnapshotDelete(id.clone()));
+            }
+            Event::SnapshotDiff { .. } => {}
+            Event::SnapshotStore(id) => {
+                app_tx.emit(AppEvent::SnapshotStore(id.clone()));
+            }
+            Event::SnapshotPhase(enabled) => {
+                app_tx.emit(AppEvent::SnapshotPhase(enabled));
+            }
+            Event::Solo(name) => {
+                app_tx.emit(AppEvent::Solo(name));
+            }
+            Event::StopRecording => {
+                app_tx.emit(AppEvent::StopRecording);
+            }
+            Event::SwitchSketch(sketch_name) => {
+                app_tx.emit(AppEvent::SwitchSketch(sketch_name.clone()));
+            }
+            Event::SyncRole(_) => {}
+            Event::SysExBackup(recording) => {
+                app_tx.emit(AppEvent::SysExBackup(recording));
+            }
+            Event::SysExRestore => {
+                app_tx.emit(AppEvent::SysExRestore);
+            }
+            Event::Tap => {
+                app_tx.emit(AppEvent::Tap);
+            }
+            Event::TapTempoEnabled(enabled) => {
+                app_tx.emit(AppEvent::TapTempoEnabled(enabled));
+            }
+            Event::ToggleFavorite(name) => {
+                app_tx.emit(AppEvent::ToggleFavorite(name));
+            }
+            Event::ToggleFullScreen => {
+                app_tx.emit(AppEvent::ToggleFullScreen);
+            }
+            Event::ToggleGuiFocus => {}
+            Event::ToggleLetterbox => {
+                app_tx.emit(AppEvent::ToggleLetterbox);
+            }
+            Event::ToggleMainFocus => {
+                app_tx.emit(AppEvent::ToggleMainFocus);
+            }
+            Event::ToggleSafeAreaGuides => {
+                app_tx.emit(AppEvent::ToggleSafeAreaGuides);
+            }
+            Event::TraceSubsystem(subsystem, enabled) => {
+                app_tx.emit(AppEvent::TraceSubsystem(subsystem, enabled));
+            }
+            Event::TrackControlHistory(tracking) => {
+                app_tx.emit(AppEvent::TrackControlHistory(tracking));
+            }
+            Event::TransitionTime(time) => {
+                app_tx.emit(AppEvent::TransitionTime(time));
+            }
+            Event::TriggerEnvelope(name) => {
+                app_tx.emit(AppEvent::TriggerEnvelope(name));
+            }
+            Event::UpdateControlBool { name, value } => {
+                app_tx.emit(AppEvent::UpdateUiControl((
+                    name.clone(),
+                    ControlValue::from(value),
+                )))
+            }
+            Event::UpdateControlFloat { name, value } => {
+                app_tx.emit(AppEvent::UpdateUiControl((
+                    name.clone(),
+                    ControlValue::from(value),
+                )))
+            }
+            Event::UpdateControlString { name, value } => {
+                app_tx.emit(AppEvent::UpdateUiControl((
+                    name.clone(),
+                    ControlValue::from(value.clone()),
+                )))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -436,7 +1147,7 @@ pub enum ControlKind {
 /// Which just looks stupid and gets worse when you have to do anything real in
 /// a generic way, so here we are picking the lesser of two not-so-great
 /// solutions by just over-packing the data type
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Control {
     pub kind: ControlKind,
     pub name: String,
@@ -446,6 +1157,14 @@ pub struct Control {
     pub min: f32,
     pub max: f32,
     pub step: f32,
+    /// See [`ControlHub::describe`]
+    pub help: Option<String>,
+    /// See [`ControlHub::display`]
+    pub display: Option<ValueDisplay>,
+    /// See [`ControlHub::groups_of`]
+    pub groups: Vec<String>,
+    /// Only set for sliders that more than one input can set
+    pub input: Option<ControlInput>,
 }
 
 impl Default for Control {
@@ -459,6 +1178,10 @@ impl Default for Control {
             min: 0.0,
             max: 1.0,
             step: 0.001,
+            help: None,
+            display: None,
+            groups: vec![],
+            input: None,
         }
     }
 }
@@ -471,6 +1194,9 @@ impl Control {
         let mut result = Control::default();
         result.disabled = ui_control.is_disabled(&hub.ui_controls);
         result.name = ui_control.name().to_string();
+        result.help = hub.describe(ui_control.name()).map(str::to_string);
+        result.display = hub.display(ui_control.name()).cloned();
+        result.groups = hub.groups_of(ui_control.name());
 
         match ui_control {
             ui_controls::UiControlConfig::Checkbox { name, .. } => {
@@ -497,9 +1223,111 @@ impl Control {
                 result.min = *min;
                 result.max = *max;
                 result.step = *step;
+                result.input = ControlInput::from_hub(name, hub);
             }
         }
 
         result
     }
 }
+
+/// See [`ControlHub::active_input`]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ControlInput {
+    pub active: InputSource,
+    pub last: Option<InputSource>,
+    pub arbitration: Arbitration,
+}
+
+impl ControlInput {
+    fn from_hub(name: &str, hub: &ControlHub<Timing>) -> Option<Self> {
+        if hub.input_sources(name).len() < 2 {
+            return None;
+        }
+        Some(Self {
+            active: hub.active_input(name)?,
+            last: hub.last_input(name),
+            arbitration: hub.arbitration(name)?,
+        })
+    }
+}
+
+/// A [`SnapshotDelta`] with values formatted like [`Control::value`]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SnapshotDiffEntry {
+    pub name: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+    pub delta: Option<f32>,
+}
+
+impl From<&SnapshotDelta> for SnapshotDiffEntry {
+    fn from(delta: &SnapshotDelta) -> Self {
+        Self {
+            name: delta.name.clone(),
+            a: delta.a.as_ref().map(ControlValue::to_string),
+            b: delta.b.as_ref().map(ControlValue::to_string),
+            delta: delta.delta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart() {
+        let brief = Duration::from_secs(1);
+        assert_eq!(restart(false, 0, brief), Restart::Wait);
+        assert_eq!(restart(false, MAX_RESPAWNS, brief), Restart::Wait);
+        assert_eq!(restart(true, 0, brief), Restart::Respawn(1));
+        assert_eq!(
+            restart(true, MAX_RESPAWNS - 1, brief),
+            Restart::Respawn(MAX_RESPAWNS)
+        );
+        assert_eq!(restart(true, MAX_RESPAWNS, brief), Restart::GiveUp);
+    }
+
+    #[test]
+    fn test_restart_resets_after_stable_connection() {
+        let stable = STABLE_CONNECTION + Duration::from_secs(1);
+        assert_eq!(restart(true, MAX_RESPAWNS, stable), Restart::Respawn(1));
+        assert_eq!(
+            restart(true, MAX_RESPAWNS, STABLE_CONNECTION),
+            Restart::GiveUp
+        );
+    }
+
+    #[test]
+    fn test_watchdog_unblocks_accept() {
+        let process = WebViewProcess::new(
+            std::env::temp_dir().join("xtal-test-watchdog.ipc"),
+        );
+
+        // Runs no tests and exits right away, like a child that never connects
+        let stub = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "no_such_test"])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        *process.child.lock().unwrap() = Some(stub);
+
+        let (server, server_name) =
+            IpcOneShotServer::<Bootstrap>::new().unwrap();
+        let connected = Arc::new(AtomicBool::new(false));
+        let abandoned = Arc::new(AtomicBool::new(false));
+        watch_startup(
+            process.clone(),
+            server_name,
+            connected.clone(),
+            abandoned.clone(),
+        );
+
+        let (_, (_sender, receiver)): (IpcReceiver<Bootstrap>, Bootstrap) =
+            server.accept().unwrap();
+        assert!(abandoned.load(Ordering::Acquire));
+        assert!(receiver.recv().is_err(), "channel should be dead");
+        assert!(!process.reap(), "stub exited cleanly");
+    }
+}
@@ -36,8 +36,13 @@ pub fn run() -> Result<(), Box<dyn Error>> {
     init_logger();
     info!("Starting web_view_process");
 
-    let server_name = std::env::args().nth(1).unwrap();
-    let (sender, receiver) = setup_ipc_connection(server_name).unwrap();
+    // Spawned by the main process, or started on its own to (re)connect to
+    // an already running one
+    let server_name = match std::env::args().nth(1) {
+        Some(name) => name,
+        None => wv::published_server_name(&wv::ipc_path())?,
+    };
+    let (sender, receiver) = setup_ipc_connection(server_name)?;
     let event_loop = EventLoop::new();
 
     let window = WindowBuilder::new()